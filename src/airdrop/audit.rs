@@ -0,0 +1,286 @@
+use std::{collections::BTreeMap, fs, io::BufRead, path::Path};
+
+use anyhow::anyhow;
+use indexmap::IndexMap;
+use serde_json::Value;
+use solana_client::rpc_config::RpcTransactionConfig;
+use solana_sdk::{signature::Signature, system_instruction::SystemInstruction, system_program};
+use solana_transaction_status::{UiTransactionEncoding, UiTransactionTokenBalance};
+
+use super::*;
+use crate::fees::Receipt;
+use crate::files::{has_extension, is_ndjson, open_file, read_json};
+use crate::limiter::with_rate_limit_backoff;
+use crate::spinner::create_progress_bar;
+
+pub struct AirdropAuditArgs {
+    pub client: RpcClient,
+    pub receipts_dir: PathBuf,
+    pub recipients: PathBuf,
+    pub output: PathBuf,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DuplicateRecipient {
+    pub address: String,
+    pub count: usize,
+}
+
+/// A recipient who already received an airdrop, with where each delivery was found.
+#[derive(Debug, Serialize)]
+pub struct ServedRecipient {
+    pub address: String,
+    pub sources: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct AirdropAudit {
+    pub recipients: usize,
+    pub duplicates: Vec<DuplicateRecipient>,
+    pub already_served: Vec<ServedRecipient>,
+    /// Ledger files and transactions that couldn't be read, so their recipients are unknown.
+    pub unreadable: Vec<String>,
+}
+
+/// Past deliveries found in a receipts directory.
+#[derive(Default)]
+struct Ledger {
+    signatures: Vec<(String, String)>,
+    served: BTreeMap<String, Vec<String>>,
+    unreadable: Vec<String>,
+}
+
+impl Ledger {
+    fn serve(&mut self, recipient: String, source: String) {
+        self.served.entry(recipient).or_default().push(source);
+    }
+
+    fn read_file(&mut self, path: &Path) -> Result<()> {
+        let name = path.display().to_string();
+
+        // Receipts written with the global --receipts option, one JSON object per line.
+        if is_ndjson(path) {
+            let receipts: Vec<Receipt> = read_json(path)?;
+            for receipt in receipts {
+                self.signatures.push((receipt.signature, name.clone()));
+            }
+            return Ok(());
+        }
+
+        let value: Value = read_json(path)?;
+        match value {
+            // The successful transactions written by `airdrop sol` and `airdrop spl`.
+            Value::Array(signatures) => {
+                for signature in signatures {
+                    let signature = signature
+                        .as_str()
+                        .ok_or_else(|| anyhow!("expected a list of signatures"))?;
+                    self.signatures.push((signature.to_string(), name.clone()));
+                }
+            }
+            // An `airdrop nft` checkpoint, which is keyed by recipient.
+            Value::Object(_) => {
+                let checkpoint: IndexMap<String, AirdroppedNft> = serde_json::from_value(value)?;
+                for (recipient, nft) in checkpoint {
+                    self.serve(recipient, format!("{name}: {}", nft.signature));
+                }
+            }
+            _ => return Err(anyhow!("not a receipts file")),
+        }
+        Ok(())
+    }
+}
+
+/// Transfer destinations of a confirmed transaction: the recipients of SOL transfers and
+/// the owners of token accounts whose balance went up. The fee payer is never a recipient.
+fn transaction_recipients(client: &RpcClient, signature: &str) -> Result<Vec<String>> {
+    let signature =
+        Signature::from_str(signature).map_err(|_| anyhow!("Invalid signature: {signature}"))?;
+
+    let tx = with_rate_limit_backoff(|| {
+        client.get_transaction_with_config(
+            &signature,
+            RpcTransactionConfig {
+                encoding: Some(UiTransactionEncoding::Base64),
+                commitment: Some(client.commitment()),
+                max_supported_transaction_version: Some(0),
+            },
+        )
+    })?;
+
+    let meta = tx
+        .transaction
+        .meta
+        .ok_or_else(|| anyhow!("Transaction has no status metadata"))?;
+    if meta.err.is_some() {
+        return Ok(vec![]);
+    }
+    let versioned = tx
+        .transaction
+        .transaction
+        .decode()
+        .ok_or_else(|| anyhow!("Failed to decode transaction"))?;
+
+    let keys = versioned.message.static_account_keys();
+    let payer = keys.first().map(|k| k.to_string());
+    let mut recipients = vec![];
+
+    for ix in versioned.message.instructions() {
+        if keys.get(ix.program_id_index as usize) != Some(&system_program::ID) {
+            continue;
+        }
+        if let Ok(SystemInstruction::Transfer { .. }) = bincode::deserialize(&ix.data) {
+            if let Some(to) = ix.accounts.get(1).and_then(|i| keys.get(*i as usize)) {
+                recipients.push(to.to_string());
+            }
+        }
+    }
+
+    let pre: Vec<UiTransactionTokenBalance> =
+        Option::from(meta.pre_token_balances).unwrap_or_default();
+    let post: Vec<UiTransactionTokenBalance> =
+        Option::from(meta.post_token_balances).unwrap_or_default();
+    for balance in post {
+        let before = pre
+            .iter()
+            .find(|b| b.account_index == balance.account_index && b.mint == balance.mint)
+            .and_then(|b| b.ui_token_amount.amount.parse::<u64>().ok())
+            .unwrap_or(0);
+        let after = balance.ui_token_amount.amount.parse::<u64>().unwrap_or(0);
+        if after > before {
+            if let Some(owner) = Option::<String>::from(balance.owner) {
+                recipients.push(owner);
+            }
+        }
+    }
+
+    recipients.retain(|r| Some(r) != payer.as_ref());
+    recipients.sort_unstable();
+    recipients.dedup();
+    Ok(recipients)
+}
+
+/// Read a recipient list: a CSV file with the address in the first column, or any of the
+/// JSON lists the airdrop commands take.
+fn read_recipients(path: &Path) -> Result<Vec<String>> {
+    let addresses: Vec<String> = if has_extension(path, "csv") {
+        let mut addresses = vec![];
+        for (i, line) in open_file(path)?.lines().enumerate() {
+            let line = line?;
+            let address = line.split(',').next().unwrap_or_default().trim();
+            // Skip a header row.
+            if address.is_empty() || (i == 0 && Pubkey::from_str(address).is_err()) {
+                continue;
+            }
+            addresses.push(address.to_string());
+        }
+        addresses
+    } else {
+        match read_json(path)? {
+            Value::Array(items) => items
+                .iter()
+                .map(|item| match item {
+                    Value::String(address) => Ok(address.clone()),
+                    item => item["address"]
+                        .as_str()
+                        .map(String::from)
+                        .ok_or_else(|| anyhow!("Recipient has no address: {item}")),
+                })
+                .collect::<Result<_>>()?,
+            Value::Object(recipients) => recipients.keys().cloned().collect(),
+            _ => return Err(anyhow!("Unrecognized recipient list format")),
+        }
+    };
+
+    for address in &addresses {
+        Pubkey::from_str(address).map_err(|_| anyhow!("Invalid recipient: {address}"))?;
+    }
+    Ok(addresses)
+}
+
+/// Check a recipient list against every ledger from earlier airdrops before sending a new
+/// one, reporting wallets that were already served and wallets listed more than once.
+///
+/// Deliveries are found from the confirmed transactions themselves rather than from how
+/// they were sent, so retried, resent and durable nonce transactions are all caught.
+pub fn airdrop_audit(args: AirdropAuditArgs) -> Result<()> {
+    let recipients = read_recipients(&args.recipients)?;
+
+    let mut ledger = Ledger::default();
+    let mut paths: Vec<PathBuf> = fs::read_dir(&args.receipts_dir)?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| has_extension(path, "json") || is_ndjson(path))
+        .collect();
+    paths.sort();
+
+    for path in &paths {
+        if let Err(e) = ledger.read_file(path) {
+            ledger.unreadable.push(format!("{}: {e}", path.display()));
+        }
+    }
+
+    let signatures = std::mem::take(&mut ledger.signatures);
+    let pb = create_progress_bar("Fetching receipt transactions...", signatures.len() as u64);
+    for (signature, file) in signatures {
+        match transaction_recipients(&args.client, &signature) {
+            Ok(served) => {
+                for recipient in served {
+                    ledger.serve(recipient, format!("{file}: {signature}"));
+                }
+            }
+            Err(e) => ledger.unreadable.push(format!("{signature}: {e}")),
+        }
+        pb.inc(1);
+    }
+    pb.finish_and_clear();
+
+    let mut counts: IndexMap<&str, usize> = IndexMap::new();
+    for recipient in &recipients {
+        *counts.entry(recipient.as_str()).or_default() += 1;
+    }
+
+    let duplicates: Vec<DuplicateRecipient> = counts
+        .iter()
+        .filter(|(_, count)| **count > 1)
+        .map(|(address, count)| DuplicateRecipient {
+            address: address.to_string(),
+            count: *count,
+        })
+        .collect();
+
+    let already_served: Vec<ServedRecipient> = counts
+        .keys()
+        .filter_map(|address| {
+            ledger.served.get(*address).map(|sources| ServedRecipient {
+                address: address.to_string(),
+                sources: sources.clone(),
+            })
+        })
+        .collect();
+
+    let audit = AirdropAudit {
+        recipients: recipients.len(),
+        duplicates,
+        already_served,
+        unreadable: ledger.unreadable,
+    };
+
+    println!(
+        "Checked {} recipients against {} ledger files",
+        audit.recipients,
+        paths.len()
+    );
+    println!("Listed more than once: {}", audit.duplicates.len());
+    println!("Already served: {}", audit.already_served.len());
+    if !audit.unreadable.is_empty() {
+        println!(
+            "Could not read {} files or transactions, so the audit may be incomplete",
+            audit.unreadable.len()
+        );
+    }
+
+    serde_json::to_writer_pretty(File::create(&args.output)?, &audit)?;
+    println!("Audit written to {}", args.output.display());
+
+    Ok(())
+}
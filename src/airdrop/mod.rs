@@ -1,35 +1,63 @@
+pub mod audit;
+pub mod nft;
 pub mod process;
 pub mod sol;
 pub mod spl;
+pub use audit::*;
+pub use nft::*;
 pub use process::*;
 pub use sol::*;
 pub use spl::*;
 
+use std::collections::HashSet;
 pub use std::{collections::HashMap, fs::File, path::PathBuf, str::FromStr};
 
 pub use anyhow::Result;
 use indicatif::ProgressBar;
 pub use jib::{Jib, JibFailedTransaction, Network};
-pub use log::debug;
+pub use log::{debug, warn};
 use metaboss_lib::data::Priority;
 pub use serde::{Deserialize, Serialize};
 pub use solana_client::rpc_client::RpcClient;
 pub use solana_sdk::{pubkey::Pubkey, signer::Signer};
 
+use crate::checkpoint;
+use crate::order;
 pub use crate::update::{parse_keypair, parse_solana_config};
 
-#[derive(Debug, Clone, Deserialize, Serialize)]
-struct FailedTransaction {
-    transaction_accounts: Vec<String>,
-    recipients: HashMap<String, u64>,
-    error: String,
-}
+// Test transactions take 3_150, but we pad it a bit.
+pub const AIRDROP_SOL_CU: u32 = 5_000;
 
-#[derive(Debug, Clone, Deserialize, Serialize)]
-struct Recipient {
-    address: String,
-    amount: u64,
+/// Drop the recipients a previous run already airdropped to according to the checkpoint,
+/// and put the rest in the configured batch order.
+fn remaining_recipients<V>(command: &str, list: HashMap<String, V>) -> Vec<(String, V)> {
+    let remaining: HashSet<String> = checkpoint::remaining(command, list.keys().cloned().collect())
+        .into_iter()
+        .collect();
+    let list = list
+        .into_iter()
+        .filter(|(address, _)| remaining.contains(address))
+        .collect();
+    order::ordered_by(list, |(address, _)| address.as_str())
 }
 
-// Test transactions take 3_150, but we pad it a bit.
-pub const AIRDROP_SOL_CU: u32 = 5_000;
+/// Record each recipient in the checkpoint, given the account its transfer is paid into.
+/// Jib only returns the signatures of successful transactions, so a recipient counts as
+/// failed if that account is in any failed transaction and done otherwise. For token
+/// airdrops this is the recipient's token account, since a transfer to an existing one
+/// doesn't reference the wallet at all.
+fn record_recipients(
+    command: &str,
+    recipients: &[(String, Pubkey)],
+    failures: &[JibFailedTransaction],
+) {
+    for (recipient, account) in recipients {
+        let failure = failures
+            .iter()
+            .find(|f| f.message.account_keys.contains(account));
+        match failure {
+            Some(failure) => checkpoint::record_failed(command, recipient, &failure.error),
+            None => checkpoint::record_done(command, recipient, None),
+        }
+    }
+}
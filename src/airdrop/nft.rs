@@ -0,0 +1,202 @@
+use std::{collections::HashSet, fs, path::Path};
+
+use anyhow::anyhow;
+use mpl_token_metadata::types::Data;
+use solana_sdk::signature::Keypair;
+
+use super::*;
+use crate::files::{has_extension, read_json};
+use crate::mint::{mint, mint_next_edition};
+use crate::spinner::create_progress_bar;
+use crate::workspace::output_path;
+
+/// Name the outcome of each recipient is recorded under.
+const AIRDROP_NFT_COMMAND: &str = "airdrop-nft";
+/// Name the metadata files that were minted are recorded under, by file name.
+const AIRDROP_NFT_FILE_COMMAND: &str = "airdrop-nft-file";
+
+pub struct AirdropNftArgs {
+    pub client: RpcClient,
+    pub keypair: Option<String>,
+    pub recipient_list: String,
+    pub nft_data_dir: Option<PathBuf>,
+    pub master_edition: Option<Pubkey>,
+    pub immutable: bool,
+    pub primary_sale_happened: bool,
+    pub priority: Priority,
+}
+
+/// A recipient in the checkpoint files earlier releases of `airdrop nft` wrote, which
+/// `airdrop audit` still reads.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct AirdroppedNft {
+    pub mint: String,
+    pub signature: String,
+    /// The metadata file the NFT was minted from, unset for printed editions.
+    pub file: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+struct FailedNftAirdrop {
+    recipient: String,
+    file: Option<String>,
+    error: String,
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default()
+}
+
+fn metadata_files(dir: &Path) -> Result<Vec<PathBuf>> {
+    let mut files: Vec<PathBuf> = fs::read_dir(dir)?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| has_extension(path, "json"))
+        .collect();
+    files.sort();
+    Ok(files)
+}
+
+/// Mint one NFT to each recipient, either from a directory of metadata files or by
+/// printing editions of a master edition.
+///
+/// Each recipient, and each metadata file that was minted, is recorded in the global
+/// `--resume` file as soon as its NFT lands, so running the command again with the same
+/// file only airdrops to the recipients that are still missing one.
+pub fn airdrop_nft(args: AirdropNftArgs) -> Result<()> {
+    if args.nft_data_dir.is_some() == args.master_edition.is_some() {
+        return Err(anyhow!(
+            "Specify exactly one of --nft-data-dir or --master-edition"
+        ));
+    }
+
+    let solana_opts = parse_solana_config();
+    let keypair = parse_keypair(args.keypair.clone(), solana_opts);
+
+    let recipients: Vec<String> = read_json(&args.recipient_list)?;
+    for recipient in &recipients {
+        Pubkey::from_str(recipient).map_err(|_| anyhow!("Invalid recipient: {recipient}"))?;
+    }
+
+    if !checkpoint::is_open() {
+        warn!("No --resume file is set, so running this again airdrops to every recipient again");
+    }
+
+    // A recipient listed more than once still gets a single NFT.
+    let mut seen = HashSet::new();
+    let recipients: Vec<String> = recipients
+        .into_iter()
+        .filter(|r| seen.insert(r.clone()))
+        .collect();
+    let pending = checkpoint::remaining(AIRDROP_NFT_COMMAND, recipients);
+
+    if pending.is_empty() {
+        println!("Every recipient has already received an NFT.");
+        return Ok(());
+    }
+
+    // Hand out the metadata files that haven't been minted yet, in file name order.
+    let files: Vec<Option<PathBuf>> = match &args.nft_data_dir {
+        Some(dir) => {
+            let files = metadata_files(dir)?;
+            let unused: HashSet<String> = checkpoint::remaining(
+                AIRDROP_NFT_FILE_COMMAND,
+                files.iter().map(|f| file_name(f)).collect(),
+            )
+            .into_iter()
+            .collect();
+            let available: Vec<Option<PathBuf>> = files
+                .into_iter()
+                .filter(|f| unused.contains(&file_name(f)))
+                .map(Some)
+                .collect();
+
+            if available.len() < pending.len() {
+                return Err(anyhow!(
+                    "{} recipients still need an NFT but only {} unused metadata files are in {dir:?}",
+                    pending.len(),
+                    available.len()
+                ));
+            }
+            available
+        }
+        None => vec![None; pending.len()],
+    };
+
+    let mut delivered = 0;
+    let mut failures = vec![];
+
+    let pb = create_progress_bar("Airdropping NFTs...", pending.len() as u64);
+    for (recipient, file) in pending.iter().zip(files) {
+        let result = match (&file, &args.master_edition) {
+            (Some(file), _) => File::open(file)
+                .map_err(|e| anyhow!("Failed to open {file:?}: {e}"))
+                .and_then(|f| {
+                    serde_json::from_reader::<_, Data>(f)
+                        .map_err(|e| anyhow!("Failed to parse {file:?}: {e}"))
+                })
+                .and_then(|data| {
+                    mint(
+                        &args.client,
+                        Keypair::from_bytes(&keypair.to_bytes())?,
+                        Pubkey::from_str(recipient)?,
+                        data,
+                        args.immutable,
+                        args.primary_sale_happened,
+                        0,
+                        None,
+                        false,
+                        args.priority.clone(),
+                    )
+                }),
+            (None, Some(master_edition)) => mint_next_edition(
+                &args.client,
+                &args.keypair,
+                &master_edition.to_string(),
+                &Some(recipient.clone()),
+                &args.priority,
+            ),
+            (None, None) => unreachable!(),
+        };
+
+        let file = file.map(|f| file_name(&f));
+        match result {
+            Ok((signature, mint)) => {
+                if let Some(file) = &file {
+                    checkpoint::record_done(
+                        AIRDROP_NFT_FILE_COMMAND,
+                        file,
+                        Some(signature.to_string()),
+                    );
+                }
+                checkpoint::record_minted(AIRDROP_NFT_COMMAND, recipient, &mint.to_string());
+                delivered += 1;
+            }
+            Err(e) => {
+                checkpoint::record_failed(AIRDROP_NFT_COMMAND, recipient, &e);
+                failures.push(FailedNftAirdrop {
+                    recipient: recipient.clone(),
+                    file,
+                    error: e.to_string(),
+                });
+            }
+        }
+        pb.inc(1);
+    }
+    pb.finish_and_clear();
+
+    println!("Airdropped {delivered} NFTs, {} failed.", failures.len());
+
+    if !failures.is_empty() {
+        let timestamp = chrono::Local::now().format("%Y-%m-%d-%H-%M-%S").to_string();
+        let failures_file = output_path(format!("mb-airdrop-nft-failures-{timestamp}.json"));
+        serde_json::to_writer_pretty(File::create(&failures_file)?, &failures)?;
+        println!(
+            "Failures written to {}. Run the command again with the same --resume file to retry them.",
+            failures_file.display()
+        );
+    }
+
+    Ok(())
+}
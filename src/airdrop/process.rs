@@ -57,6 +57,53 @@ pub enum AirdropSubcommands {
         #[structopt(short = "P", long, default_value = "none")]
         priority: Priority,
     },
+    /// Airdrop one NFT to each recipient, minted from metadata files or printed from a master edition
+    #[structopt(name = "nft")]
+    Nft {
+        /// Path to the owner keypair file
+        #[structopt(short, long)]
+        keypair: Option<String>,
+
+        /// Path to the JSON list of recipient addresses
+        #[structopt(short = "L", long)]
+        recipient_list: String,
+
+        /// Directory of NFT data JSON files, one per recipient
+        #[structopt(short = "d", long)]
+        nft_data_dir: Option<PathBuf>,
+
+        /// Master edition mint to print an edition from for each recipient
+        #[structopt(short, long)]
+        master_edition: Option<Pubkey>,
+
+        /// Mint the NFTs as immutable
+        #[structopt(short, long)]
+        immutable: bool,
+
+        /// Set primary sale happened on the NFTs
+        #[structopt(short, long)]
+        primary_sale_happened: bool,
+
+        /// Priority of the transaction: higher priority costs more.
+        #[structopt(short = "P", long, default_value = "none")]
+        priority: Priority,
+    },
+    /// Check a recipient list against past airdrop receipts for wallets already served
+    #[structopt(name = "audit")]
+    Audit {
+        /// Directory of receipts from earlier airdrops: successful transaction files,
+        /// NFT airdrop checkpoints and --receipts files
+        #[structopt(short = "d", long)]
+        receipts_dir: PathBuf,
+
+        /// Recipient list for the new airdrop, as CSV or JSON
+        #[structopt(short = "L", long)]
+        recipients: PathBuf,
+
+        /// File to write the audit report to
+        #[structopt(short, long, default_value = "mb-airdrop-audit.json")]
+        output: PathBuf,
+    },
     /// Convert the bin cache file to json for readability
     ReadCache {
         /// Path to the cache file
@@ -108,6 +155,34 @@ pub async fn process_airdrop(client: RpcClient, commands: AirdropSubcommands) ->
             })
             .await
         }
+        AirdropSubcommands::Nft {
+            keypair,
+            recipient_list,
+            nft_data_dir,
+            master_edition,
+            immutable,
+            primary_sale_happened,
+            priority,
+        } => airdrop_nft(AirdropNftArgs {
+            client,
+            keypair,
+            recipient_list,
+            nft_data_dir,
+            master_edition,
+            immutable,
+            primary_sale_happened,
+            priority,
+        }),
+        AirdropSubcommands::Audit {
+            receipts_dir,
+            recipients,
+            output,
+        } => airdrop_audit(AirdropAuditArgs {
+            client,
+            receipts_dir,
+            recipients,
+            output,
+        }),
         AirdropSubcommands::ReadCache { cache_file, errors } => {
             let path = std::path::Path::new(&cache_file);
             let file = File::open(path)?;
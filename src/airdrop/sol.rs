@@ -1,4 +1,7 @@
 use super::*;
+use crate::constants::GLOBAL_RATE_LIMIT;
+use crate::files::read_json;
+use crate::workspace::output_path;
 
 pub struct AirdropSolArgs {
     pub client: RpcClient,
@@ -25,8 +28,10 @@ pub async fn airdrop_sol(args: AirdropSolArgs) -> Result<()> {
     let now = chrono::Local::now();
     let timestamp = now.format("%Y-%m-%d-%H-%M-%S").to_string();
 
-    let mut cache_file_name = format!("mb-cache-airdrop-{timestamp}.bin");
-    let successful_tx_file_name = format!("mb-successful-airdrops-{timestamp}.json");
+    let mut cache_file_name = output_path(format!("mb-cache-airdrop-{timestamp}.bin"))
+        .display()
+        .to_string();
+    let successful_tx_file_name = output_path(format!("mb-successful-airdrops-{timestamp}.json"));
 
     let priority_fee = match args.priority {
         Priority::None => 200,         // 1 lamport
@@ -39,22 +44,30 @@ pub async fn airdrop_sol(args: AirdropSolArgs) -> Result<()> {
     jib.set_priority_fee(priority_fee);
     jib.set_compute_budget(AIRDROP_SOL_CU);
 
-    if let Some(rate) = args.rate_limit {
+    let global_rate_limit = GLOBAL_RATE_LIMIT.read().unwrap().map(|rate| rate as u64);
+    if let Some(rate) = global_rate_limit.or(args.rate_limit) {
         jib.set_rate_limit(rate);
     }
 
+    // Recipients from the list, recorded in the checkpoint once the airdrop is done.
+    let command = "airdrop-sol";
+    let mut recipients: Vec<(String, Pubkey)> = vec![];
+
     // Airdrop case
     let results = if let Some(list_file) = args.recipient_list {
-        let airdrop_list: HashMap<String, u64> = serde_json::from_reader(File::open(list_file)?)?;
+        let airdrop_list: HashMap<String, u64> = read_json(list_file)?;
+        let airdrop_list = remaining_recipients(command, airdrop_list);
 
         for (address, amount) in &airdrop_list {
             let pubkey = match Pubkey::from_str(address) {
                 Ok(pubkey) => pubkey,
                 Err(_) => {
-                    eprintln!("Invalid address: {}, skipping...", address);
+                    warn!("Invalid address: {address}, skipping");
+                    checkpoint::record_failed(command, address, "Invalid address");
                     continue;
                 }
             };
+            recipients.push((address.clone(), pubkey));
 
             instructions.push(solana_sdk::system_instruction::transfer(
                 &jib.payer().pubkey(),
@@ -95,6 +108,7 @@ pub async fn airdrop_sol(args: AirdropSolArgs) -> Result<()> {
     results.into_iter().for_each(|r| {
         if r.is_failure() {
             let failure = r.get_failure().unwrap();
+            debug!("Transaction failed: {}", failure.error);
             failures.push(failure);
         } else {
             debug!("Transaction successful: {}", r.signature().unwrap()); // Signatures exist on successes.
@@ -102,6 +116,8 @@ pub async fn airdrop_sol(args: AirdropSolArgs) -> Result<()> {
         }
     });
 
+    record_recipients(command, &recipients, &failures);
+
     // Write cache file and successful transactions.
     if !successes.is_empty() {
         let successful_tx_file = std::fs::File::create(successful_tx_file_name)?;
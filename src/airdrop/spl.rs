@@ -8,10 +8,17 @@ use solana_program::{
     program_pack::Pack,
     pubkey, system_program,
 };
+use solana_sdk::{compute_budget::ComputeBudgetInstruction, transaction::Transaction};
 use spl_associated_token_account::get_associated_token_address;
 use spl_token::instruction::transfer_checked;
+use std::collections::HashSet;
 
 use super::*;
+use crate::constants::{GLOBAL_RATE_LIMIT, MAX_MULTIPLE_ACCOUNTS};
+use crate::files::read_json;
+use crate::pack::{pack_groups, InstructionGroup};
+use crate::spinner::create_progress_bar;
+use crate::workspace::output_path;
 
 pub struct AirdropSplArgs {
     pub client: RpcClient,
@@ -24,26 +31,21 @@ pub struct AirdropSplArgs {
     pub rate_limit: Option<u64>,
 }
 
-#[derive(Debug, Clone, Deserialize, Serialize)]
-struct SplFailedTransaction {
-    transaction_accounts: Vec<String>,
-    recipients: HashMap<String, f64>,
-    error: String,
-}
-
-type Recipient = String;
-type Ata = String;
+// Compute units for a transfer_checked to an existing token account, padded.
+const AIRDROP_SPL_TRANSFER_CU: u32 = 8_000;
+// Extra compute units to create a missing associated token account, padded.
+const AIRDROP_SPL_CREATE_ATA_CU: u32 = 30_000;
+// Packed transactions sent per blockhash.
+const AIRDROP_SPL_BATCH_SIZE: usize = 10;
 
 pub async fn airdrop_spl(args: AirdropSplArgs) -> Result<()> {
     let solana_opts = parse_solana_config();
     let keypair = parse_keypair(args.keypair, solana_opts);
 
     let mut jib = Jib::new(vec![keypair], args.client.url())?;
-    let mut instructions = vec![];
-
-    let mut recipients_lookup: HashMap<Ata, Recipient> = HashMap::new();
+    let payer = jib.payer().pubkey();
 
-    let source_ata = get_associated_token_address(&jib.payer().pubkey(), &args.mint);
+    let source_ata = get_associated_token_address(&payer, &args.mint);
 
     let mint_account =
         spl_token::state::Mint::unpack(args.client.get_account(&args.mint)?.data.as_slice())?;
@@ -58,29 +60,39 @@ pub async fn airdrop_spl(args: AirdropSplArgs) -> Result<()> {
     let now = chrono::Local::now();
     let timestamp = now.format("%Y-%m-%d-%H-%M-%S").to_string();
 
-    let mut cache_file_name = format!("mb-cache-airdrop-{timestamp}.bin");
-    let successful_tx_file_name = format!("mb-successful-airdrops-{timestamp}.json");
+    let mut cache_file_name = output_path(format!("mb-cache-airdrop-{timestamp}.bin"))
+        .display()
+        .to_string();
+    let successful_tx_file_name = output_path(format!("mb-successful-airdrops-{timestamp}.json"));
 
+    // Micro-lamports per compute unit, so the fee scales with the recipients in each transaction.
     let priority_fee = match args.priority {
-        Priority::None => 200,         // 1 lamport
-        Priority::Low => 200_000,      // 1_000 lamports
-        Priority::Medium => 1_000_000, // 5_000 lamports
-        Priority::High => 5_000_000,   // 25_000 lamports
-        Priority::Max => 20_000_000,   // 100_000 lamports
+        Priority::None => 200,
+        Priority::Low => 200_000,
+        Priority::Medium => 1_000_000,
+        Priority::High => 5_000_000,
+        Priority::Max => 20_000_000,
     };
 
     jib.set_priority_fee(priority_fee);
-    jib.set_compute_budget(AIRDROP_SOL_CU);
 
-    if let Some(rate) = args.rate_limit {
+    let global_rate_limit = GLOBAL_RATE_LIMIT.read().unwrap().map(|rate| rate as u64);
+    if let Some(rate) = global_rate_limit.or(args.rate_limit) {
         jib.set_rate_limit(rate);
     }
 
+    // Recipients from the list and their token accounts, recorded in the checkpoint once the
+    // airdrop is done.
+    let command = format!("airdrop-spl-{}", args.mint);
+    let mut recipients: Vec<(String, Pubkey)> = vec![];
+
+    // Airdrop case
     let results = if let Some(list_file) = args.recipient_list {
-        let airdrop_list: HashMap<String, f64> = serde_json::from_reader(File::open(list_file)?)?;
+        let airdrop_list: HashMap<String, f64> = read_json(list_file)?;
+        let airdrop_list = remaining_recipients(&command, airdrop_list);
 
         if args.mint_tokens {
-            let total_tokens = airdrop_list.values().sum::<f64>();
+            let total_tokens = airdrop_list.iter().map(|(_, amount)| amount).sum::<f64>();
 
             let total_tokens_native_units = convert_to_base_units(total_tokens, decimals)
                 .ok_or(anyhow!("Invalid total amount of SPL tokens to mint"))?;
@@ -89,13 +101,14 @@ pub async fn airdrop_spl(args: AirdropSplArgs) -> Result<()> {
                 &spl_token::ID,
                 &args.mint,
                 &source_ata,
-                &jib.payer().pubkey(),
+                &payer,
                 &[],
                 total_tokens_native_units,
             )?;
             send_and_confirm_tx(&args.client, &[jib.payer()], &[mint_tokens_ix])?;
         }
 
+        let mut transfers = vec![];
         for (address, amount) in &airdrop_list {
             let amount_native_units = convert_to_base_units(*amount, decimals).ok_or(anyhow!(
                 format!("Invalid token amount for address {address}")
@@ -104,37 +117,90 @@ pub async fn airdrop_spl(args: AirdropSplArgs) -> Result<()> {
             let pubkey = match Pubkey::from_str(address) {
                 Ok(pubkey) => pubkey,
                 Err(_) => {
-                    eprintln!("Invalid address: {}, skipping...", address);
+                    warn!("Invalid address: {address}, skipping");
+                    checkpoint::record_failed(&command, address, "Invalid address");
                     continue;
                 }
             };
 
             let destination_ata = get_associated_token_address(&pubkey, &args.mint);
+            recipients.push((address.clone(), destination_ata));
+            transfers.push((pubkey, destination_ata, amount_native_units));
+        }
 
-            recipients_lookup.insert(destination_ata.to_string(), pubkey.to_string());
+        // Only recipients without a token account need the create instruction, so check
+        // which ATAs already exist before building any transactions.
+        let atas: Vec<Pubkey> = transfers.iter().map(|(_, ata, _)| *ata).collect();
+        let existing = existing_accounts(&args.client, &atas)?;
+        let missing = atas.len() - existing.len();
+        println!(
+            "{} recipients already have a token account, {missing} will be created",
+            existing.len()
+        );
 
-            instructions.push(create_token_if_missing_instruction(
-                &jib.payer().pubkey(),
-                &destination_ata,
-                &args.mint,
-                &pubkey,
-                &destination_ata,
-            ));
+        let groups = transfers
+            .into_iter()
+            .map(|(owner, destination_ata, amount)| {
+                let mut instructions = vec![];
+                let mut compute_units = AIRDROP_SPL_TRANSFER_CU;
+                if !existing.contains(&destination_ata) {
+                    instructions.push(create_token_if_missing_instruction(
+                        &payer,
+                        &destination_ata,
+                        &args.mint,
+                        &owner,
+                        &destination_ata,
+                    ));
+                    compute_units += AIRDROP_SPL_CREATE_ATA_CU;
+                }
+                instructions.push(transfer_checked(
+                    &spl_token::ID,
+                    &source_ata,
+                    &args.mint,
+                    &destination_ata,
+                    &payer,
+                    &[],
+                    amount,
+                    decimals,
+                )?);
+                Ok(InstructionGroup {
+                    key: owner,
+                    instructions,
+                    compute_units,
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let transfer_count = groups.len();
+        // Jib doesn't use lookup tables or nonces, so the transactions are sized without them.
+        let packed = pack_groups(&payer, groups, &[], &[])?;
+        println!(
+            "Sending {transfer_count} transfers in {} transactions",
+            packed.len()
+        );
 
-            instructions.push(transfer_checked(
-                &spl_token::ID,
-                &source_ata,
-                &args.mint,
-                &destination_ata,
-                &jib.payer().pubkey(),
-                &[],
-                amount_native_units,
-                decimals,
-            )?);
+        let mut results = vec![];
+        for batch in packed.chunks(AIRDROP_SPL_BATCH_SIZE) {
+            let blockhash = args.client.get_latest_blockhash()?;
+            let transactions = batch
+                .iter()
+                .map(|tx| {
+                    let mut instructions = vec![
+                        ComputeBudgetInstruction::set_compute_unit_limit(tx.compute_units),
+                        ComputeBudgetInstruction::set_compute_unit_price(priority_fee),
+                    ];
+                    instructions.extend(tx.instructions.iter().cloned());
+                    Transaction::new_signed_with_payer(
+                        &instructions,
+                        Some(&payer),
+                        &[jib.payer()],
+                        blockhash,
+                    )
+                })
+                .collect();
+            results.extend(jib.hoist_with_transactions(transactions).await?);
         }
-
-        jib.set_instructions(instructions);
-        jib.hoist().await?
+        results
     } else if let Some(cache_file) = args.cache_file {
         cache_file_name = PathBuf::from(cache_file.clone())
             .file_name()
@@ -163,6 +229,7 @@ pub async fn airdrop_spl(args: AirdropSplArgs) -> Result<()> {
     results.into_iter().for_each(|r| {
         if r.is_failure() {
             let failure = r.get_failure().unwrap();
+            debug!("Transaction failed: {}", failure.error);
             failures.push(failure);
         } else {
             debug!("Transaction successful: {}", r.signature().unwrap()); // Signatures exist on successes.
@@ -170,6 +237,8 @@ pub async fn airdrop_spl(args: AirdropSplArgs) -> Result<()> {
         }
     });
 
+    record_recipients(&command, &recipients, &failures);
+
     // Write cache file and successful transactions.
     if !successes.is_empty() {
         let successful_tx_file = std::fs::File::create(successful_tx_file_name)?;
@@ -187,6 +256,26 @@ pub async fn airdrop_spl(args: AirdropSplArgs) -> Result<()> {
     Ok(())
 }
 
+/// The accounts that exist out of the given ones, fetched in batches. This deliberately
+/// skips the RPC cache, since a token account created after caching would be missed.
+fn existing_accounts(client: &RpcClient, pubkeys: &[Pubkey]) -> Result<HashSet<Pubkey>> {
+    let pb = create_progress_bar("Checking recipient token accounts...", pubkeys.len() as u64);
+    let mut existing = HashSet::new();
+    for chunk in pubkeys.chunks(MAX_MULTIPLE_ACCOUNTS) {
+        let accounts = client.get_multiple_accounts(chunk)?;
+        existing.extend(
+            chunk
+                .iter()
+                .zip(accounts)
+                .filter(|(_, account)| account.is_some())
+                .map(|(pubkey, _)| *pubkey),
+        );
+        pb.inc(chunk.len() as u64);
+    }
+    pb.finish_and_clear();
+    Ok(existing)
+}
+
 const MPL_TOOLBOX_ID: Pubkey = pubkey!("TokExjvjJmhKaRBShsBAsbSvEWMA1AgUNK7ps4SAc2p");
 
 #[derive(Debug, Clone, BorshSerialize, BorshDeserialize)]
@@ -204,7 +293,7 @@ pub enum TokenExtrasInstruction {
     /// - Token exists and Token is not ATA: Instruction succeeds.
     /// - Token does not exist and Token is ATA: Instruction creates the ATA account and succeeds.
     /// - Token does not exist and Token is not ATA: Instruction fails as we cannot create a
-    ///    non-ATA account without it being a signer.
+    ///   non-ATA account without it being a signer.
     ///
     /// Note that additional checks are made to ensure that the token account provided
     /// matches the mint account and owner account provided.
@@ -0,0 +1,299 @@
+//! On-chain announcements: SPL Memo transactions signed by a collection's update authority.
+//!
+//! A message is split into chunks that each fit in a transaction, and every chunk starts
+//! with a header line of the form `mb-announce/1 <id> <part>/<parts> <collection>` so the
+//! reader can put the message back together. The collection is `-` for announcements not
+//! tied to one. Only memos in successful transactions signed by the authority are read
+//! back, so anyone can verify an announcement came from the authority.
+
+use std::{collections::BTreeMap, fs, path::PathBuf, str::FromStr};
+
+use anyhow::{anyhow, Result};
+use log::warn;
+use metaboss_lib::{data::Priority, decode::decode_metadata_from_mint};
+use serde::Serialize;
+use solana_client::{rpc_client::RpcClient, rpc_config::RpcTransactionConfig};
+use solana_sdk::{
+    instruction::{AccountMeta, Instruction},
+    pubkey,
+    pubkey::Pubkey,
+    signature::Signature,
+    signer::Signer,
+};
+use solana_transaction_status::UiTransactionEncoding;
+
+use crate::{
+    history::fetch_page,
+    parse::{parse_keypair, parse_solana_config},
+    pipeline::Pipeline,
+};
+
+pub const MEMO_PROGRAM_ID: Pubkey = pubkey!("MemoSq4gqABAXKb96qnH8TysNcWxMyWCqXgDLGmfcHr");
+
+const ANNOUNCE_PREFIX: &str = "mb-announce/1";
+
+pub struct AnnounceArgs {
+    pub client: RpcClient,
+    pub keypair: Option<String>,
+    pub message: PathBuf,
+    pub collection: Option<String>,
+    pub chunk_size: usize,
+    pub priority: Priority,
+}
+
+pub struct AnnouncementsArgs {
+    pub client: RpcClient,
+    pub authority: String,
+    pub collection: Option<String>,
+    pub limit: usize,
+    pub output: Option<PathBuf>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Announcement {
+    pub id: String,
+    pub collection: Option<String>,
+    pub block_time: Option<i64>,
+    /// Signatures of the transactions holding each part, in order.
+    pub signatures: Vec<String>,
+    pub message: String,
+}
+
+/// Split the message into chunks of at most `size` bytes, on character boundaries.
+fn chunk_message(message: &str, size: usize) -> Vec<&str> {
+    let mut chunks = vec![];
+    let mut rest = message;
+    while !rest.is_empty() {
+        let mut end = size.min(rest.len());
+        while !rest.is_char_boundary(end) {
+            end -= 1;
+        }
+        let (chunk, tail) = rest.split_at(end);
+        chunks.push(chunk);
+        rest = tail;
+    }
+    chunks
+}
+
+fn memo_ix(authority: &Pubkey, memo: String) -> Instruction {
+    Instruction {
+        program_id: MEMO_PROGRAM_ID,
+        accounts: vec![AccountMeta::new_readonly(*authority, true)],
+        data: memo.into_bytes(),
+    }
+}
+
+/// Publish a message as memos signed by the authority, one transaction per chunk.
+pub fn announce(args: AnnounceArgs) -> Result<()> {
+    let solana_opts = parse_solana_config();
+    let authority = parse_keypair(args.keypair, solana_opts);
+
+    let message = fs::read_to_string(&args.message)?;
+    if message.trim().is_empty() {
+        return Err(anyhow!("The message file is empty"));
+    }
+    if args.chunk_size == 0 {
+        return Err(anyhow!("Chunk size must be greater than 0"));
+    }
+
+    // Announcing for a collection only makes sense from its update authority, which is what
+    // holders will check the signer against.
+    if let Some(collection) = &args.collection {
+        let md = decode_metadata_from_mint(&args.client, collection.clone())?;
+        if md.update_authority != authority.pubkey() {
+            return Err(anyhow!(
+                "{} is not the update authority of collection {collection}",
+                authority.pubkey()
+            ));
+        }
+    }
+
+    let id = chrono::Utc::now().timestamp().to_string();
+    let collection = args.collection.as_deref().unwrap_or("-");
+    let chunks = chunk_message(&message, args.chunk_size);
+    let parts = chunks.len();
+
+    let pipeline = Pipeline::with_priority(args.priority)
+        .with_active_lookup_table()?
+        .with_active_nonce()?;
+    for (i, chunk) in chunks.into_iter().enumerate() {
+        let memo = format!(
+            "{ANNOUNCE_PREFIX} {id} {}/{parts} {collection}\n{chunk}",
+            i + 1
+        );
+        let sig = pipeline.run(
+            &args.client,
+            &[&authority],
+            vec![memo_ix(&authority.pubkey(), memo)],
+        )?;
+        println!("Part {}/{parts}: {sig}", i + 1);
+    }
+    println!("Announcement {id} published by {}", authority.pubkey());
+
+    Ok(())
+}
+
+struct Part {
+    id: String,
+    part: usize,
+    parts: usize,
+    collection: Option<String>,
+    text: String,
+}
+
+fn parse_part(memo: &str) -> Option<Part> {
+    let (header, text) = memo.split_once('\n')?;
+    let mut fields = header.split(' ');
+    if fields.next()? != ANNOUNCE_PREFIX {
+        return None;
+    }
+    let id = fields.next()?.to_string();
+    let (part, parts) = fields.next()?.split_once('/')?;
+    let collection = match fields.next()? {
+        "-" => None,
+        collection => Some(collection.to_string()),
+    };
+    Some(Part {
+        id,
+        part: part.parse().ok()?,
+        parts: parts.parse().ok()?,
+        collection,
+        text: text.to_string(),
+    })
+}
+
+/// Announcement memos of a transaction, if the authority signed it.
+fn signed_parts(client: &RpcClient, signature: &str, authority: &Pubkey) -> Result<Vec<Part>> {
+    let tx = client.get_transaction_with_config(
+        &Signature::from_str(signature)?,
+        RpcTransactionConfig {
+            encoding: Some(UiTransactionEncoding::Base64),
+            commitment: Some(client.commitment()),
+            max_supported_transaction_version: Some(0),
+        },
+    )?;
+    let versioned = tx
+        .transaction
+        .transaction
+        .decode()
+        .ok_or_else(|| anyhow!("Failed to decode transaction {signature}"))?;
+
+    let signers = versioned.message.header().num_required_signatures as usize;
+    let keys = versioned.message.static_account_keys();
+    if !keys[..signers.min(keys.len())].contains(authority) {
+        return Ok(vec![]);
+    }
+
+    Ok(versioned
+        .message
+        .instructions()
+        .iter()
+        .filter(|ix| keys.get(ix.program_id_index as usize) == Some(&MEMO_PROGRAM_ID))
+        .filter_map(|ix| std::str::from_utf8(&ix.data).ok())
+        .filter_map(parse_part)
+        .collect())
+}
+
+/// Read back the announcements an authority published, newest first.
+pub fn announcements(args: AnnouncementsArgs) -> Result<()> {
+    let authority = Pubkey::from_str(&args.authority)
+        .map_err(|_| anyhow!("Invalid authority address: {}", args.authority))?;
+
+    // Parts by announcement id, with the signature and block time of each part.
+    let mut found: BTreeMap<String, Vec<(Part, String, Option<i64>)>> = BTreeMap::new();
+    let mut complete = 0;
+    let mut before = None;
+    loop {
+        let page = fetch_page(&args.client, &authority, before, None)?;
+        let Some(last) = page.last() else {
+            break;
+        };
+        before = Some(Signature::from_str(&last.signature)?);
+
+        // The RPC includes memos in the signature list, so only transactions that have an
+        // announcement memo are fetched.
+        for record in page {
+            if record.err.is_some()
+                || !record
+                    .memo
+                    .as_deref()
+                    .is_some_and(|m| m.contains(ANNOUNCE_PREFIX))
+            {
+                continue;
+            }
+            let parts = match signed_parts(&args.client, &record.signature, &authority) {
+                Ok(parts) => parts,
+                Err(e) => {
+                    warn!("Skipping {}: {e}", record.signature);
+                    continue;
+                }
+            };
+            for part in parts {
+                if args.collection.is_some() && part.collection != args.collection {
+                    continue;
+                }
+                let entry = found.entry(part.id.clone()).or_default();
+                let parts = part.parts;
+                entry.push((part, record.signature.clone(), record.block_time));
+                if entry.len() == parts {
+                    complete += 1;
+                }
+            }
+        }
+
+        if complete >= args.limit {
+            break;
+        }
+    }
+
+    let mut announcements = vec![];
+    for (id, mut parts) in found.into_iter().rev() {
+        parts.sort_by_key(|(part, _, _)| part.part);
+        parts.dedup_by_key(|(part, _, _)| part.part);
+        let expected = parts[0].0.parts;
+        if parts.len() != expected || parts.iter().enumerate().any(|(i, p)| p.0.part != i + 1) {
+            warn!("Skipping incomplete announcement {id}");
+            continue;
+        }
+        announcements.push(Announcement {
+            id,
+            collection: parts[0].0.collection.clone(),
+            block_time: parts.iter().filter_map(|(_, _, t)| *t).max(),
+            signatures: parts.iter().map(|(_, sig, _)| sig.clone()).collect(),
+            message: parts.into_iter().map(|(part, _, _)| part.text).collect(),
+        });
+        if announcements.len() == args.limit {
+            break;
+        }
+    }
+
+    if let Some(output) = &args.output {
+        serde_json::to_writer_pretty(fs::File::create(output)?, &announcements)?;
+        println!(
+            "{} announcements written to {}",
+            announcements.len(),
+            output.display()
+        );
+        return Ok(());
+    }
+
+    if announcements.is_empty() {
+        println!("No announcements found for {authority}");
+    }
+    for announcement in &announcements {
+        let time = announcement
+            .block_time
+            .and_then(|t| chrono::DateTime::from_timestamp(t, 0))
+            .map(|t| t.to_rfc3339())
+            .unwrap_or_else(|| "unknown time".to_string());
+        match &announcement.collection {
+            Some(collection) => {
+                println!("== {} ({time}, collection {collection})", announcement.id)
+            }
+            None => println!("== {} ({time})", announcement.id),
+        }
+        println!("{}\n", announcement.message);
+    }
+
+    Ok(())
+}
@@ -0,0 +1,61 @@
+mod supply;
+
+pub use supply::*;
+
+use std::path::PathBuf;
+
+use anyhow::Result;
+use solana_client::rpc_client::RpcClient;
+use structopt::StructOpt;
+
+#[derive(Debug, StructOpt)]
+pub enum AuditSubcommands {
+    /// Compare the current mints under a creator against a previous hash list
+    #[structopt(name = "supply")]
+    Supply {
+        /// Path to the previous hash list (a JSON list of mint addresses)
+        #[structopt(short = "H", long)]
+        hashlist: PathBuf,
+
+        /// Verified creator address, or candy machine id with --v2 or --v3
+        #[structopt(short, long)]
+        creator: String,
+
+        /// Position of the creator in the creators array
+        #[structopt(short, long, default_value = "0")]
+        position: usize,
+
+        /// Candy machine v2 id
+        #[structopt(long = "v2")]
+        v2: bool,
+
+        /// Candy machine v3 id
+        #[structopt(long = "v3")]
+        v3: bool,
+
+        /// Directory to write the report to
+        #[structopt(short, long, default_value = ".")]
+        output: PathBuf,
+    },
+}
+
+pub fn process_audit(client: RpcClient, commands: AuditSubcommands) -> Result<()> {
+    match commands {
+        AuditSubcommands::Supply {
+            hashlist,
+            creator,
+            position,
+            v2,
+            v3,
+            output,
+        } => audit_supply(AuditSupplyArgs {
+            client,
+            hashlist,
+            creator,
+            position,
+            v2,
+            v3,
+            output,
+        }),
+    }
+}
@@ -0,0 +1,153 @@
+use std::{collections::HashSet, fs::File, path::PathBuf, str::FromStr};
+
+use anyhow::{anyhow, Result};
+use serde::Serialize;
+use solana_client::rpc_client::RpcClient;
+use solana_program::program_pack::Pack;
+use solana_sdk::pubkey::Pubkey;
+use spl_token::state::Mint;
+
+use crate::constants::MAX_MULTIPLE_ACCOUNTS;
+use crate::files::read_json;
+use crate::snapshot::get_mint_accounts;
+use crate::spinner::create_progress_bar;
+use crate::workspace::output_path;
+
+pub struct AuditSupplyArgs {
+    pub client: RpcClient,
+    pub hashlist: PathBuf,
+    pub creator: String,
+    pub position: usize,
+    pub v2: bool,
+    pub v3: bool,
+    pub output: PathBuf,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SupplyAudit {
+    pub creator: String,
+    pub previous_supply: usize,
+    pub current_supply: usize,
+    /// In the hash list, but the mint no longer exists or has no supply.
+    pub burned: Vec<String>,
+    /// In the hash list and still exists, but no longer has the verified creator.
+    pub missing: Vec<String>,
+    /// Verified under the creator but not in the hash list.
+    pub unexpected: Vec<String>,
+}
+
+/// Mints from the list whose mint account is closed or has a supply of zero.
+fn find_burned(client: &RpcClient, mints: &[String]) -> Result<HashSet<String>> {
+    let mut burned = HashSet::new();
+
+    let pb = create_progress_bar("Checking mint accounts...", mints.len() as u64);
+    for chunk in mints.chunks(MAX_MULTIPLE_ACCOUNTS) {
+        let pubkeys = chunk
+            .iter()
+            .map(|m| Pubkey::from_str(m).map_err(|_| anyhow!("Invalid mint address: {m}")))
+            .collect::<Result<Vec<_>>>()?;
+
+        let accounts = client.get_multiple_accounts(&pubkeys)?;
+        for (mint, account) in chunk.iter().zip(accounts) {
+            let is_burned = match account {
+                Some(account) => Mint::unpack(&account.data)
+                    .map(|m| m.supply == 0)
+                    .unwrap_or(false),
+                None => true,
+            };
+            if is_burned {
+                burned.insert(mint.clone());
+            }
+        }
+        pb.inc(chunk.len() as u64);
+    }
+    pb.finish_and_clear();
+
+    Ok(burned)
+}
+
+/// Compare the mints currently verified under a creator against a hash list.
+pub fn supply_audit(
+    client: &RpcClient,
+    hashlist: &[String],
+    creator: String,
+    position: usize,
+    v2: bool,
+    v3: bool,
+) -> Result<SupplyAudit> {
+    let previous: HashSet<&String> = hashlist.iter().collect();
+
+    let current_mints = get_mint_accounts(
+        client,
+        &Some(creator.clone()),
+        position,
+        None,
+        false,
+        v2,
+        v3,
+    )?;
+    let current: HashSet<&String> = current_mints.iter().collect();
+
+    let gone: Vec<String> = hashlist
+        .iter()
+        .filter(|m| !current.contains(m))
+        .cloned()
+        .collect();
+    let burned_set = find_burned(client, &gone)?;
+
+    let (mut burned, mut missing): (Vec<String>, Vec<String>) =
+        gone.into_iter().partition(|m| burned_set.contains(m));
+    let mut unexpected: Vec<String> = current_mints
+        .iter()
+        .filter(|m| !previous.contains(m))
+        .cloned()
+        .collect();
+
+    burned.sort_unstable();
+    missing.sort_unstable();
+    unexpected.sort_unstable();
+
+    Ok(SupplyAudit {
+        creator,
+        previous_supply: previous.len(),
+        current_supply: current.len(),
+        burned,
+        missing,
+        unexpected,
+    })
+}
+
+pub fn audit_supply(args: AuditSupplyArgs) -> Result<()> {
+    let hashlist: Vec<String> = read_json(&args.hashlist)?;
+
+    let audit = supply_audit(
+        &args.client,
+        &hashlist,
+        args.creator,
+        args.position,
+        args.v2,
+        args.v3,
+    )?;
+
+    let output = output_path(&args.output);
+    std::fs::create_dir_all(&output)?;
+    let report_path = output.join("mb_audit_supply.json");
+    serde_json::to_writer_pretty(File::create(&report_path)?, &audit)?;
+
+    println!("Previous supply: {}", audit.previous_supply);
+    println!("Current supply: {}", audit.current_supply);
+    println!("Burned: {}", audit.burned.len());
+    println!("No longer verified: {}", audit.missing.len());
+    println!("Unexpected: {}", audit.unexpected.len());
+
+    if !audit.unexpected.is_empty() {
+        println!(
+            "WARNING: {} mints under this creator are not in the hash list. They may be unauthorized mints or prints.",
+            audit.unexpected.len()
+        );
+    }
+
+    println!("Report written to {}", report_path.display());
+
+    Ok(())
+}
@@ -0,0 +1,227 @@
+//! Measure how reliably and quickly transactions land through different RPC endpoints
+//! and priority fees.
+
+use std::{
+    fs::File,
+    path::PathBuf,
+    thread::sleep,
+    time::{Duration, Instant},
+};
+
+use anyhow::{anyhow, Result};
+use metaboss_lib::data::Priority;
+use serde::Serialize;
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::{
+    commitment_config::CommitmentConfig, compute_budget::ComputeBudgetInstruction,
+    signature::Keypair, signer::Signer, system_instruction, transaction::Transaction,
+};
+use structopt::StructOpt;
+
+use crate::workspace::output_path;
+use crate::{
+    parse::{parse_keypair, parse_solana_config},
+    pipeline::priority_micro_lamports,
+    spinner::create_progress_bar,
+};
+
+// A system transfer uses 150 compute units; leave headroom for the budget instructions.
+const TRANSFER_COMPUTE_UNITS: u32 = 1_000;
+const POLL_INTERVAL_MS: u64 = 500;
+
+#[derive(Debug, StructOpt)]
+pub enum BenchSubcommands {
+    /// Send small self-transfers through each endpoint at each priority fee and report
+    /// landing rates and confirmation times
+    #[structopt(name = "rpc")]
+    Rpc {
+        /// Path to the keypair that signs and pays for the test transfers
+        #[structopt(short, long)]
+        keypair: Option<String>,
+
+        /// Comma separated list of RPC endpoints to compare
+        #[structopt(short, long, use_delimiter = true, required = true)]
+        endpoints: Vec<String>,
+
+        /// Comma separated list of priority levels to test
+        #[structopt(
+            short = "P",
+            long,
+            use_delimiter = true,
+            default_value = "none,low,medium,high"
+        )]
+        priorities: Vec<Priority>,
+
+        /// Transactions to send for each endpoint and priority
+        #[structopt(short, long, default_value = "5")]
+        samples: usize,
+
+        /// Seconds to wait for each transaction before counting it as dropped
+        #[structopt(long, default_value = "60")]
+        max_wait: u64,
+
+        /// Directory to write the report to
+        #[structopt(short, long, default_value = ".")]
+        output: PathBuf,
+    },
+}
+
+pub struct BenchRpcArgs {
+    pub keypair: Option<String>,
+    pub endpoints: Vec<String>,
+    pub priorities: Vec<Priority>,
+    pub samples: usize,
+    pub max_wait: u64,
+    pub output: PathBuf,
+}
+
+#[derive(Debug, Serialize)]
+pub struct BenchResult {
+    pub endpoint: String,
+    pub priority: String,
+    pub micro_lamports: u64,
+    pub sent: usize,
+    pub landed: usize,
+    pub landing_rate: f64,
+    pub median_confirmation_ms: Option<u64>,
+    pub errors: Vec<String>,
+}
+
+pub fn process_bench(commands: BenchSubcommands) -> Result<()> {
+    match commands {
+        BenchSubcommands::Rpc {
+            keypair,
+            endpoints,
+            priorities,
+            samples,
+            max_wait,
+            output,
+        } => bench_rpc(BenchRpcArgs {
+            keypair,
+            endpoints,
+            priorities,
+            samples,
+            max_wait,
+            output,
+        }),
+    }
+}
+
+/// Send one self-transfer and wait for it to confirm, returning how long it took.
+fn send_sample(
+    client: &RpcClient,
+    keypair: &Keypair,
+    micro_lamports: u64,
+    nonce: u64,
+    max_wait: Duration,
+) -> Result<Duration> {
+    // Vary the amount so every transaction has a unique signature.
+    let ixs = [
+        ComputeBudgetInstruction::set_compute_unit_limit(TRANSFER_COMPUTE_UNITS),
+        ComputeBudgetInstruction::set_compute_unit_price(micro_lamports),
+        system_instruction::transfer(&keypair.pubkey(), &keypair.pubkey(), nonce),
+    ];
+
+    let recent_blockhash = client.get_latest_blockhash()?;
+    let tx = Transaction::new_signed_with_payer(
+        &ixs,
+        Some(&keypair.pubkey()),
+        &[keypair],
+        recent_blockhash,
+    );
+
+    let start = Instant::now();
+    let sig = client.send_transaction(&tx)?;
+
+    while start.elapsed() < max_wait {
+        if let Some(status) = client.get_signature_status(&sig)? {
+            status?;
+            return Ok(start.elapsed());
+        }
+        sleep(Duration::from_millis(POLL_INTERVAL_MS));
+    }
+
+    Err(anyhow!("{sig} not confirmed after {}s", max_wait.as_secs()))
+}
+
+fn median(durations: &mut [Duration]) -> Option<u64> {
+    if durations.is_empty() {
+        return None;
+    }
+    durations.sort_unstable();
+    Some(durations[durations.len() / 2].as_millis() as u64)
+}
+
+pub fn bench_rpc(args: BenchRpcArgs) -> Result<()> {
+    if args.samples == 0 {
+        return Err(anyhow!("Samples must be at least 1"));
+    }
+
+    let solana_opts = parse_solana_config();
+    let keypair = parse_keypair(args.keypair, solana_opts);
+    let max_wait = Duration::from_secs(args.max_wait);
+
+    let total = args.endpoints.len() * args.priorities.len() * args.samples;
+    let pb = create_progress_bar("Benchmarking...", total as u64);
+
+    let mut results = Vec::new();
+    let mut nonce = 1;
+
+    for endpoint in &args.endpoints {
+        let client =
+            RpcClient::new_with_commitment(endpoint.clone(), CommitmentConfig::confirmed());
+
+        for priority in &args.priorities {
+            let micro_lamports = priority_micro_lamports(priority);
+            let mut durations = Vec::new();
+            let mut errors = Vec::new();
+
+            for _ in 0..args.samples {
+                match send_sample(&client, &keypair, micro_lamports, nonce, max_wait) {
+                    Ok(duration) => durations.push(duration),
+                    Err(e) => errors.push(e.to_string()),
+                }
+                nonce += 1;
+                pb.inc(1);
+            }
+
+            results.push(BenchResult {
+                endpoint: endpoint.clone(),
+                priority: priority.to_string(),
+                micro_lamports,
+                sent: args.samples,
+                landed: durations.len(),
+                landing_rate: durations.len() as f64 / args.samples as f64,
+                median_confirmation_ms: median(&mut durations),
+                errors,
+            });
+        }
+    }
+    pb.finish_and_clear();
+
+    println!(
+        "{:<40} {:<8} {:>8} {:>12}",
+        "Endpoint", "Priority", "Landed", "Median (ms)"
+    );
+    for r in &results {
+        let median = r
+            .median_confirmation_ms
+            .map(|ms| ms.to_string())
+            .unwrap_or_else(|| String::from("-"));
+        println!(
+            "{:<40} {:<8} {:>8} {:>12}",
+            r.endpoint,
+            r.priority,
+            format!("{}/{}", r.landed, r.sent),
+            median
+        );
+    }
+
+    let output = output_path(&args.output);
+    std::fs::create_dir_all(&output)?;
+    let report_path = output.join("mb_bench_rpc.json");
+    serde_json::to_writer_pretty(File::create(&report_path)?, &results)?;
+    println!("Report written to {}", report_path.display());
+
+    Ok(())
+}
@@ -1,7 +1,15 @@
-use crate::{cache::NewValue, update::parse_mint_list};
+use crate::{
+    cache::NewValue,
+    output::record_transaction,
+    update::{batch_mints, parse_mint_list},
+};
 
 use super::*;
 
+use borsh::BorshDeserialize;
+use metaboss_lib::{derive::derive_token_record_pda, transaction::send_and_confirm_tx};
+use mpl_token_metadata::{instructions::BurnV1Builder, types::TokenStandard};
+
 pub struct BurnAssetArgs {
     pub client: Arc<RpcClient>,
     pub keypair: Arc<Keypair>,
@@ -16,6 +24,7 @@ pub struct BurnAssetAllArgs {
     pub keypair: Option<String>,
     pub mint_list: Option<String>,
     pub cache_file: Option<String>,
+    pub force: bool,
     pub rate_limit: usize,
     pub retries: u8,
     pub priority: Priority,
@@ -25,23 +34,76 @@ pub async fn burn_asset(args: BurnAssetArgs) -> Result<Signature, ActionError> {
     let mint = Pubkey::from_str(&args.mint_account)
         .map_err(|e| ActionError::ActionFailed(args.mint_account.to_string(), e.to_string()))?;
 
+    let token_program = get_token_program(&args.client, &mint)
+        .map_err(|e| ActionError::ActionFailed(args.mint_account.to_string(), e.to_string()))?;
+
     // We support the user passing in a non-ATA token account, but otherwise we derive the ATA.
     let token = if let Some(token) = args.token_account {
         Pubkey::from_str(&token)
             .map_err(|e| ActionError::ActionFailed(args.mint_account.to_string(), e.to_string()))?
     } else {
-        get_associated_token_address(&args.keypair.pubkey(), &mint)
+        get_associated_token_address_with_program_id(&args.keypair.pubkey(), &mint, &token_program)
     };
 
-    let burn_args = metaboss_lib::burn::BurnAssetArgs::V1 {
-        authority: &args.keypair,
+    burn_asset_with_token_program(
+        &args.client,
+        &args.keypair,
         mint,
         token,
-        amount: args.amount,
-    };
+        args.amount,
+        token_program,
+    )
+    .map_err(|e| ActionError::ActionFailed(args.mint_account.to_string(), e.to_string()))
+}
+
+// Built here with the same accounts as metaboss_lib, which always passes the legacy token
+// program to BurnV1 and doesn't hand back the instructions to record for the JSON output.
+fn burn_asset_with_token_program(
+    client: &RpcClient,
+    authority: &Keypair,
+    mint: Pubkey,
+    token: Pubkey,
+    amount: u64,
+    token_program: Pubkey,
+) -> AnyResult<Signature> {
+    let metadata_pubkey = derive_metadata_pda(&mint);
+    let md_account = client.get_account_data(&metadata_pubkey)?;
+    let md = Metadata::deserialize(&mut md_account.as_slice())?;
+
+    let mut burn_builder = BurnV1Builder::new();
+    burn_builder
+        .authority(authority.pubkey())
+        .mint(mint)
+        .metadata(metadata_pubkey)
+        .token(token)
+        .spl_token_program(token_program)
+        .amount(amount);
+
+    if matches!(
+        md.token_standard,
+        Some(
+            TokenStandard::NonFungible
+                | TokenStandard::NonFungibleEdition
+                | TokenStandard::ProgrammableNonFungible
+        ) | None
+    ) {
+        burn_builder.edition(Some(derive_edition_pda(&mint)));
+
+        if let Some(TokenStandard::ProgrammableNonFungible) = md.token_standard {
+            burn_builder.token_record(Some(derive_token_record_pda(&mint, &token)));
+        }
+    }
+
+    let collection_metadata = md
+        .collection
+        .filter(|c| c.verified)
+        .map(|c| derive_metadata_pda(&c.key));
+    burn_builder.collection_metadata(collection_metadata);
 
-    metaboss_lib::burn::burn_asset(&args.client, burn_args)
-        .map_err(|e| ActionError::ActionFailed(args.mint_account.to_string(), e.to_string()))
+    let instructions = [burn_builder.instruction()];
+    let sig = send_and_confirm_tx(client, &[authority], &instructions)?;
+    record_transaction(&sig, &instructions);
+    Ok(sig)
 }
 
 pub struct BurnAssetAll {}
@@ -52,7 +114,7 @@ impl Action for BurnAssetAll {
         "burn-asset-all"
     }
 
-    async fn action(args: RunActionArgs) -> Result<(), ActionError> {
+    async fn action(args: RunActionArgs) -> Result<ActionOutcome, ActionError> {
         burn_asset(BurnAssetArgs {
             client: args.client.clone(),
             keypair: args.keypair.clone(),
@@ -62,7 +124,7 @@ impl Action for BurnAssetAll {
             priority: args.priority,
         })
         .await
-        .map(|_| ())
+        .map(ActionOutcome::Sent)
     }
 }
 
@@ -74,6 +136,8 @@ pub async fn burn_asset_all(args: BurnAssetAllArgs) -> AnyResult<()> {
     let payer = None;
 
     let mint_list = parse_mint_list(args.mint_list, &args.cache_file)?;
+    let mints = batch_mints(&mint_list, &args.cache_file)?;
+    check_no_live_prints(&args.client, &mints, args.force)?;
 
     let args = BatchActionArgs {
         client: args.client,
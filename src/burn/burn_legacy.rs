@@ -1,6 +1,10 @@
 use borsh::BorshDeserialize;
 
-use crate::{cache::NewValue, update::parse_mint_list};
+use crate::{
+    cache::NewValue,
+    output::record_transaction,
+    update::{batch_mints, parse_mint_list},
+};
 
 use super::*;
 
@@ -13,6 +17,7 @@ pub struct BurnAllArgs {
     pub keypair: Option<String>,
     pub mint_list: Option<String>,
     pub cache_file: Option<String>,
+    pub force: bool,
     pub rate_limit: usize,
     pub retries: u8,
     pub priority: Priority,
@@ -48,9 +53,11 @@ pub async fn burn_one(
     client: RpcClient,
     keypair: Option<String>,
     mint_address: String,
+    force: bool,
     priority: Priority,
 ) -> AnyResult<()> {
     let mint_pubkey = Pubkey::from_str(&mint_address)?;
+    check_no_live_prints(&client, &[mint_address], force)?;
     let solana_opts = parse_solana_config();
     let keypair = parse_keypair(keypair, solana_opts);
 
@@ -107,11 +114,11 @@ impl Action for BurnAll {
         "burn-all"
     }
 
-    async fn action(args: RunActionArgs) -> Result<(), ActionError> {
+    async fn action(args: RunActionArgs) -> Result<ActionOutcome, ActionError> {
         let mint_pubkey = Pubkey::from_str(&args.mint_account)
             .map_err(|e| ActionError::ActionFailed(args.mint_account.to_string(), e.to_string()))?;
 
-        let _sig = burn(BurnArgs {
+        let sig = burn(BurnArgs {
             client: args.client.clone(),
             keypair: args.keypair.clone(),
             mint_pubkey,
@@ -120,7 +127,7 @@ impl Action for BurnAll {
         .await
         .map_err(|e| ActionError::ActionFailed(args.mint_account.to_string(), e.to_string()))?;
 
-        Ok(())
+        Ok(ActionOutcome::Sent(sig))
     }
 }
 
@@ -132,6 +139,8 @@ pub async fn burn_all(args: BurnAllArgs) -> AnyResult<()> {
     let payer = None;
 
     let mint_list = parse_mint_list(args.mint_list, &args.cache_file)?;
+    let mints = batch_mints(&mint_list, &args.cache_file)?;
+    check_no_live_prints(&args.client, &mints, args.force)?;
 
     let args = BatchActionArgs {
         client: args.client,
@@ -150,8 +159,12 @@ pub async fn burn_all(args: BurnAllArgs) -> AnyResult<()> {
 }
 
 pub async fn burn(args: BurnArgs) -> AnyResult<Signature> {
-    let assoc = get_associated_token_address(&args.keypair.pubkey(), &args.mint_pubkey);
-    let spl_token_program_id = spl_token::id();
+    let spl_token_program_id = get_token_program(&args.client, &args.mint_pubkey)?;
+    let assoc = get_associated_token_address_with_program_id(
+        &args.keypair.pubkey(),
+        &args.mint_pubkey,
+        &spl_token_program_id,
+    );
     let metadata_pubkey = derive_metadata_pda(&args.mint_pubkey);
     let master_edition = derive_edition_pda(&args.mint_pubkey);
 
@@ -197,6 +210,7 @@ pub async fn burn(args: BurnArgs) -> AnyResult<Signature> {
         || args.client.send_and_confirm_transaction(&tx),
     );
     let sig = res?;
+    record_transaction(&sig, &instructions);
 
     Ok(sig)
 }
@@ -207,13 +221,13 @@ impl Action for BurnPrintAll {
         "burn-print-all"
     }
 
-    async fn action(args: RunActionArgs) -> Result<(), ActionError> {
+    async fn action(args: RunActionArgs) -> Result<ActionOutcome, ActionError> {
         let mint_pubkey = Pubkey::from_str(&args.mint_account)
             .map_err(|e| ActionError::ActionFailed(args.mint_account.to_string(), e.to_string()))?;
         let master_mint_pubkey = Pubkey::from_str(&args.new_value)
             .map_err(|e| ActionError::ActionFailed(args.mint_account.to_string(), e.to_string()))?;
 
-        let _sig = burn_print(BurnPrintArgs {
+        let sig = burn_print(BurnPrintArgs {
             client: args.client.clone(),
             keypair: args.keypair.clone(),
             mint_pubkey,
@@ -223,7 +237,7 @@ impl Action for BurnPrintAll {
         .await
         .map_err(|e| ActionError::ActionFailed(args.mint_account.to_string(), e.to_string()))?;
 
-        Ok(())
+        Ok(ActionOutcome::Sent(sig))
     }
 }
 
@@ -253,16 +267,21 @@ pub async fn burn_print_all(args: BurnPrintAllArgs) -> AnyResult<()> {
 }
 
 pub async fn burn_print(args: BurnPrintArgs) -> AnyResult<Signature> {
-    let print_edition_token =
-        get_associated_token_address(&args.keypair.pubkey(), &args.mint_pubkey);
+    let spl_token_program_id = get_token_program(&args.client, &args.mint_pubkey)?;
+    let print_edition_token = get_associated_token_address_with_program_id(
+        &args.keypair.pubkey(),
+        &args.mint_pubkey,
+        &spl_token_program_id,
+    );
 
     // Find the master edition holder.
     let master_edition_owner =
         get_largest_token_account_owner(&args.client, args.master_mint_pubkey)?;
-    let master_edition_token =
-        get_associated_token_address(&master_edition_owner, &args.master_mint_pubkey);
-
-    let spl_token_program_id = spl_token::id();
+    let master_edition_token = get_associated_token_address_for_mint(
+        &args.client,
+        &master_edition_owner,
+        &args.master_mint_pubkey,
+    )?;
     let metadata_pubkey = derive_metadata_pda(&args.mint_pubkey);
 
     let master_edition_pda = derive_edition_pda(&args.master_mint_pubkey);
@@ -304,6 +323,7 @@ pub async fn burn_print(args: BurnPrintArgs) -> AnyResult<Signature> {
         || args.client.send_and_confirm_transaction(&tx),
     );
     let sig = res?;
+    record_transaction(&sig, &instructions);
 
     Ok(sig)
 }
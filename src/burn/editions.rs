@@ -0,0 +1,145 @@
+use std::collections::HashSet;
+
+use anyhow::anyhow;
+use log::warn;
+use metaboss_lib::snapshot::get_edition_accounts_by_master;
+use solana_account_decoder::{UiAccountEncoding, UiDataSliceConfig};
+use solana_client::{
+    rpc_config::{RpcAccountInfoConfig, RpcProgramAccountsConfig},
+    rpc_filter::{Memcmp, MemcmpEncodedBytes, RpcFilterType},
+};
+use solana_sdk::commitment_config::CommitmentConfig;
+
+use crate::{cache::NewValue, spinner::create_spinner};
+
+use super::*;
+
+// Token accounts start with the mint, then the owner and the amount.
+const TOKEN_ACCOUNT_OWNER_OFFSET: usize = 32;
+const TOKEN_ACCOUNT_LEN: u64 = 165;
+
+pub struct BurnEditionsArgs {
+    pub client: RpcClient,
+    pub keypair: Option<String>,
+    pub master_mint: String,
+    pub cache_file: Option<String>,
+    pub rate_limit: usize,
+    pub retries: u8,
+    pub priority: Priority,
+}
+
+/// Mints of every token the wallet holds, under both token programs.
+fn held_mints(client: &RpcClient, owner: &Pubkey) -> AnyResult<HashSet<Pubkey>> {
+    let mut mints = HashSet::new();
+    for token_program in [spl_token::ID, spl_token_2022::ID] {
+        #[allow(deprecated)]
+        let mut filters = vec![RpcFilterType::Memcmp(Memcmp {
+            offset: TOKEN_ACCOUNT_OWNER_OFFSET,
+            bytes: MemcmpEncodedBytes::Bytes(owner.to_bytes().to_vec()),
+            encoding: None,
+        })];
+        // Token-2022 accounts grow with their extensions, so only legacy accounts have a fixed size.
+        if token_program == spl_token::ID {
+            filters.push(RpcFilterType::DataSize(TOKEN_ACCOUNT_LEN));
+        }
+        let config = RpcProgramAccountsConfig {
+            filters: Some(filters),
+            account_config: RpcAccountInfoConfig {
+                encoding: Some(UiAccountEncoding::Base64),
+                // Only the mint and amount are needed.
+                data_slice: Some(UiDataSliceConfig {
+                    offset: 0,
+                    length: 32 + 32 + 8,
+                }),
+                commitment: Some(CommitmentConfig::confirmed()),
+                min_context_slot: None,
+            },
+            with_context: None,
+        };
+
+        for (address, account) in client.get_program_accounts_with_config(&token_program, config)? {
+            if account.data.len() < 72 {
+                warn!("Token account {address} returned too little data, skipping");
+                continue;
+            }
+            let amount = u64::from_le_bytes(account.data[64..72].try_into()?);
+            if amount > 0 {
+                mints.insert(Pubkey::try_from(&account.data[..32])?);
+            }
+        }
+    }
+    Ok(mints)
+}
+
+/// The print editions of the master that the wallet holds, found by matching the edition
+/// accounts of the master to the editions of the wallet's mints.
+pub fn held_prints(
+    client: &RpcClient,
+    owner: &Pubkey,
+    master_mint: &Pubkey,
+) -> AnyResult<Vec<String>> {
+    let master_edition = derive_edition_pda(master_mint);
+    let editions: HashSet<Pubkey> =
+        get_edition_accounts_by_master(client, &master_edition.to_string())?
+            .into_iter()
+            .map(|(edition, _)| edition)
+            .collect();
+
+    let mut prints: Vec<String> = held_mints(client, owner)?
+        .into_iter()
+        .filter(|mint| editions.contains(&derive_edition_pda(mint)))
+        .map(|mint| mint.to_string())
+        .collect();
+    prints.sort();
+    Ok(prints)
+}
+
+/// Burn every print of the master edition held by the keypair, each with its edition marker.
+pub async fn burn_editions(args: BurnEditionsArgs) -> AnyResult<()> {
+    let solana_opts = parse_solana_config();
+    let keypair = parse_keypair(args.keypair, solana_opts);
+    let master_mint = Pubkey::from_str(&args.master_mint)
+        .map_err(|_| anyhow!("Invalid master mint: {}", args.master_mint))?;
+
+    // A cache file holds the prints that failed last time, so only those are retried.
+    let mint_list = match &args.cache_file {
+        Some(_) => None,
+        None => {
+            let spinner = create_spinner("Finding held print editions...");
+            let prints = held_prints(&args.client, &keypair.pubkey(), &master_mint)?;
+            spinner.finish_and_clear();
+
+            if prints.is_empty() {
+                println!(
+                    "{} holds no print editions of {master_mint}",
+                    keypair.pubkey()
+                );
+                return Ok(());
+            }
+            println!(
+                "Burning {} print editions of {master_mint} held by {}",
+                prints.len(),
+                keypair.pubkey()
+            );
+            Some(prints)
+        }
+    };
+
+    // We don't support an optional payer for this action currently.
+    let payer = None;
+
+    let args = BatchActionArgs {
+        client: args.client,
+        keypair,
+        payer,
+        mint_list,
+        cache_file: args.cache_file,
+        new_value: NewValue::Single(args.master_mint),
+        rate_limit: args.rate_limit,
+        retries: args.retries,
+        priority: args.priority,
+    };
+    BurnPrintAll::run(args).await?;
+
+    Ok(())
+}
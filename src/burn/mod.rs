@@ -14,19 +14,24 @@ use solana_sdk::{
     transaction::Transaction,
 };
 
-use spl_associated_token_account::get_associated_token_address;
-use spl_token;
+use spl_associated_token_account::get_associated_token_address_with_program_id;
 use std::{str::FromStr, sync::Arc};
 
 use crate::{
-    cache::{Action, BatchActionArgs, RunActionArgs},
+    cache::{Action, ActionOutcome, BatchActionArgs, RunActionArgs},
     derive::{derive_edition_marker_pda, derive_edition_pda, derive_metadata_pda},
     errors::ActionError,
     parse::{parse_keypair, parse_solana_config},
-    utils::get_largest_token_account_owner,
+    utils::{
+        get_associated_token_address_for_mint, get_largest_token_account_owner, get_token_program,
+    },
 };
 
 mod burn_legacy;
 pub use burn_legacy::*;
 mod burn_asset;
 pub use burn_asset::*;
+mod editions;
+pub use editions::*;
+mod prints;
+pub use prints::*;
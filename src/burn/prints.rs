@@ -0,0 +1,128 @@
+use anyhow::anyhow;
+use borsh::BorshDeserialize;
+use metaboss_lib::snapshot::get_edition_accounts_by_master;
+use mpl_token_metadata::{accounts::MasterEdition, types::Key};
+
+use crate::rpc_cache;
+use crate::spinner::create_progress_bar;
+
+use super::*;
+
+// Edition numbers listed per master before the rest are only counted.
+const MAX_LISTED_EDITIONS: usize = 20;
+
+/// A master edition with prints still on chain.
+#[derive(Debug)]
+pub struct MasterWithPrints {
+    pub mint: String,
+    /// Editions printed so far, as recorded on the master edition.
+    pub supply: u64,
+    /// Edition numbers of the prints still on chain, in order.
+    pub live_editions: Vec<u64>,
+}
+
+/// The mints of the list that are master editions with prints still on chain.
+/// Burned prints close their edition account, so every edition account left is a live print.
+///
+/// The edition accounts of the whole list are read in batches first, so only master
+/// editions are scanned for prints. Each scan is a getProgramAccounts call over the Token
+/// Metadata program, which is slow on public RPCs, so a long `burn all` list with many
+/// masters costs one such scan per master.
+pub fn masters_with_prints(
+    client: &RpcClient,
+    mints: &[String],
+) -> AnyResult<Vec<MasterWithPrints>> {
+    let pubkeys = mints
+        .iter()
+        .map(|m| Pubkey::from_str(m).map_err(|_| anyhow!("Invalid mint: {m}")))
+        .collect::<AnyResult<Vec<_>>>()?;
+    let editions: Vec<Pubkey> = pubkeys.iter().map(derive_edition_pda).collect();
+    let accounts = rpc_cache::get_multiple_accounts(client, &editions)?;
+
+    let mut candidates = vec![];
+    for ((mint, edition), account) in mints.iter().zip(editions).zip(accounts) {
+        let Some(account) = account else { continue };
+        let is_master = matches!(
+            account.data.first().copied(),
+            Some(key) if key == Key::MasterEditionV2 as u8 || key == Key::MasterEditionV1 as u8
+        );
+        if !is_master {
+            continue;
+        }
+        // Both versions start with the key, supply and max supply.
+        let supply = MasterEdition::deserialize(&mut account.data.as_slice())
+            .map(|master| master.supply)
+            .unwrap_or_default();
+        candidates.push((mint, edition, supply));
+    }
+    if candidates.is_empty() {
+        return Ok(vec![]);
+    }
+
+    let mut masters = vec![];
+    let pb = create_progress_bar(
+        "Checking master editions for prints...",
+        candidates.len() as u64,
+    );
+    for (mint, edition, supply) in candidates {
+        let mut live_editions: Vec<u64> =
+            get_edition_accounts_by_master(client, &edition.to_string())?
+                .into_iter()
+                .filter_map(|(_, a)| Edition::deserialize(&mut a.data.as_slice()).ok())
+                .map(|e| e.edition)
+                .collect();
+        if !live_editions.is_empty() {
+            live_editions.sort_unstable();
+            masters.push(MasterWithPrints {
+                mint: mint.clone(),
+                supply,
+                live_editions,
+            });
+        }
+        pb.inc(1);
+    }
+    pb.finish_and_clear();
+    Ok(masters)
+}
+
+/// Refuse to burn master editions that still have prints, which would orphan their holders'
+/// editions, unless forced.
+pub fn check_no_live_prints(client: &RpcClient, mints: &[String], force: bool) -> AnyResult<()> {
+    if force {
+        return Ok(());
+    }
+    let masters = masters_with_prints(client, mints)?;
+    if masters.is_empty() {
+        return Ok(());
+    }
+
+    for master in &masters {
+        let mut listed: Vec<String> = master
+            .live_editions
+            .iter()
+            .take(MAX_LISTED_EDITIONS)
+            .map(|e| e.to_string())
+            .collect();
+        let unlisted = master
+            .live_editions
+            .len()
+            .saturating_sub(MAX_LISTED_EDITIONS);
+        if unlisted > 0 {
+            listed.push(format!("and {unlisted} more"));
+        }
+        eprintln!(
+            "{} is a master edition with {} of its {} printed editions still live: {}",
+            master.mint,
+            master.live_editions.len(),
+            master.supply,
+            listed.join(", ")
+        );
+    }
+    let mint = &masters[0].mint;
+    Err(anyhow!(
+        "Refusing to burn {} master editions that still have prints, as it would orphan their holders' editions. \
+        List the prints with `metaboss snapshot prints -m {mint}` and burn the ones you hold first with \
+        `metaboss burn editions --master {mint}`, or pass --force to burn anyway.",
+        masters.len()
+    ))
+}
@@ -1,31 +1,43 @@
 use anyhow::{anyhow, Result as AnyResult};
 use async_trait::async_trait;
 use indexmap::IndexMap;
-use log::info;
+use log::{info, warn};
 use metaboss_lib::data::Priority;
 use once_cell::sync::Lazy;
 use regex::Regex;
 use serde::{Deserialize, Serialize};
 use solana_client::rpc_client::RpcClient;
-use solana_sdk::signature::Keypair;
+use solana_sdk::signature::{Keypair, Signature};
+use tracing::{info_span, Instrument};
 
 use std::{
     collections::HashMap,
-    fs::{File, OpenOptions},
+    fmt,
     io::Write,
     ops::{Deref, DerefMut},
-    path::Path,
     sync::Arc,
 };
 
+use crate::workspace::output_path;
 use crate::{
-    constants::NANO_SECONDS_IN_SECOND, errors::ActionError,
-    limiter::create_rate_limiter_with_capacity, spinner::create_progress_bar, utils::find_tm_error,
+    changelog::Changelog,
+    checkpoint,
+    constants::NANO_SECONDS_IN_SECOND,
+    errors::ActionError,
+    expect,
+    files::{create_file, read_json},
+    limiter::{
+        create_concurrency_limiter, create_rate_limiter_with_capacity, is_rate_limited,
+        rate_limit_or, rate_limited_backoff, FeeGuard, MAX_RATE_LIMITED_RETRIES,
+    },
+    order,
+    spinner::create_progress_bar,
+    utils::find_tm_error,
 };
 
 #[derive(Debug, Deserialize, Serialize)]
 pub struct Cache(pub IndexMap<String, CacheItem>);
-pub type CacheResults = Vec<Result<(), ActionError>>;
+pub type CacheResults = Vec<Result<ActionOutcome, ActionError>>;
 
 impl Default for Cache {
     fn default() -> Self {
@@ -58,7 +70,7 @@ impl Cache {
         Ok(())
     }
 
-    pub fn update_errors(&mut self, errors: Vec<Result<(), ActionError>>) {
+    pub fn update_errors(&mut self, errors: CacheResults) {
         let errors = errors.iter().map(|r| r.as_ref()).map(Result::unwrap_err);
 
         // Clear out old errors.
@@ -115,6 +127,24 @@ pub struct BatchActionArgs {
     pub priority: Priority,
 }
 
+/// What an action did for a single mint.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ActionOutcome {
+    /// A transaction was sent, or exported when using `--export-unsigned`.
+    Sent(Signature),
+    /// The asset already had the target value so nothing was sent.
+    AlreadyDone,
+}
+
+impl fmt::Display for ActionOutcome {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ActionOutcome::Sent(sig) => write!(f, "{sig}"),
+            ActionOutcome::AlreadyDone => write!(f, "none, already up to date"),
+        }
+    }
+}
+
 pub struct RunActionArgs {
     pub client: Arc<RpcClient>,
     pub keypair: Arc<Keypair>,
@@ -124,9 +154,25 @@ pub struct RunActionArgs {
     pub priority: Priority,
 }
 
+fn is_throttled(result: &Result<ActionOutcome, ActionError>) -> bool {
+    matches!(result, Err(e) if is_rate_limited(&e.to_string()))
+}
+
+// Throttled requests are retried in the same run, so they aren't recorded as failures.
+fn record_checkpoint(command: &str, mint: &str, result: &Result<ActionOutcome, ActionError>) {
+    match result {
+        Ok(ActionOutcome::Sent(sig)) => {
+            checkpoint::record_done(command, mint, Some(sig.to_string()))
+        }
+        Ok(ActionOutcome::AlreadyDone) => checkpoint::record_done(command, mint, None),
+        Err(_) if is_throttled(result) => {}
+        Err(e) => checkpoint::record_failed(command, mint, e),
+    }
+}
+
 #[async_trait]
 pub trait Action {
-    async fn action(args: RunActionArgs) -> Result<(), ActionError>;
+    async fn action(args: RunActionArgs) -> Result<ActionOutcome, ActionError>;
 
     fn name() -> &'static str;
 
@@ -138,104 +184,180 @@ pub trait Action {
         }
 
         // Default name, if we don't get an output_file option or a cache file.
-        let mut cache_file_name = format!("mb-cache-{}.json", Self::name());
+        let mut cache_file_name = output_path(format!("mb-cache-{}.json", Self::name()))
+            .display()
+            .to_string();
         let mut cache = Cache::new();
 
-        let mut mint_list: Vec<String> = if let Some(mint_list) = args.mint_list {
+        let mint_list: Vec<String> = if let Some(mint_list) = args.mint_list {
             mint_list
         } else if let Some(cache_path) = args.cache_file {
             println!("Retrying items from cache file. . .");
             cache_file_name = cache_path;
 
-            let f = File::open(&cache_file_name)?;
-            let cache: Cache = serde_json::from_reader(f)?;
+            let cache: Cache = read_json(&cache_file_name)?;
             cache.0.keys().map(|k| k.to_string()).collect()
         } else {
             return Err(anyhow!(
                 "Please specify either a n mint_list file or a cache file."
             ));
         };
+        let mint_list = order::ordered(checkpoint::remaining(Self::name(), mint_list));
 
-        let f = if !Path::new(&cache_file_name).exists() {
-            File::create(&cache_file_name)?
-        } else {
-            OpenOptions::new()
-                .read(true)
-                .write(true)
-                .truncate(true)
-                .open(&cache_file_name)?
-        };
+        let f = create_file(&cache_file_name)?;
 
         let mut counter = 0u8;
+        let mut already_done = 0;
         let client = Arc::new(args.client);
         let keypair = Arc::new(args.keypair);
         let payer = Arc::new(args.payer);
 
-        let delay = NANO_SECONDS_IN_SECOND / args.rate_limit;
-        let rate_limiter = create_rate_limiter_with_capacity(args.rate_limit as u32, delay as u32);
+        // Read the metadata up front so the changes the run makes can be written out after.
+        let changelog = Arc::new(Changelog::capture(&client, Self::name(), &mint_list)?);
+
+        // Leave out mints that changed since the --expect-file snapshot.
+        let mut mint_list = expect::unchanged(Self::name(), mint_list, changelog.before())?;
+
+        let mut rate_limit = rate_limit_or(args.rate_limit);
+        let delay = NANO_SECONDS_IN_SECOND / rate_limit;
+        let mut rate_limiter = create_rate_limiter_with_capacity(rate_limit as u32, delay as u32);
+        let concurrency = create_concurrency_limiter();
+        let mut fee_guard = FeeGuard::new();
 
         loop {
-            let remaining_mints = mint_list.clone();
-
-            let mint_length = remaining_mints.len();
-
-            info!("Sending network requests...");
-            let mut update_tasks = Vec::new();
-            let pb =
-                create_progress_bar("Sending network requests...", remaining_mints.len() as u64);
-
-            // Create a vector of futures to execute.
-            for mint_address in remaining_mints {
-                let mut rate_limiter = rate_limiter.clone();
-
-                let empty_string = String::new();
-
-                let new_value = match &args.new_value {
-                    NewValue::None => &empty_string,
-                    NewValue::Single(value) => value,
-                    NewValue::List(values) => values.get(&mint_address).unwrap(),
-                };
-
-                // Create task to run the action in a separate thread.
-                let task = tokio::spawn({
-                    rate_limiter.wait();
-                    let fut = Self::action(RunActionArgs {
-                        client: client.clone(),
-                        keypair: keypair.clone(),
-                        payer: payer.clone(),
-                        mint_account: mint_address,
-                        new_value: new_value.to_string(),
-                        priority: args.priority.clone(),
+            let mut remaining_mints = mint_list.clone();
+            let update_tasks_len = remaining_mints.len();
+            let mut update_results = Vec::new();
+            let mut throttled_rounds = 0;
+
+            while !remaining_mints.is_empty() {
+                let mint_length = remaining_mints.len();
+
+                info!("Sending network requests...");
+                let mut update_tasks = Vec::new();
+                let pb = create_progress_bar("Sending network requests...", mint_length as u64);
+
+                // Create a vector of futures to execute.
+                for mint_address in remaining_mints {
+                    let mut rate_limiter = rate_limiter.clone();
+
+                    let empty_string = String::new();
+
+                    let new_value = match &args.new_value {
+                        NewValue::None => &empty_string,
+                        NewValue::Single(value) => value,
+                        NewValue::List(values) => values.get(&mint_address).unwrap(),
+                    };
+
+                    // Hold off while network fees are above --max-network-fee.
+                    fee_guard.wait(&client);
+
+                    // Wait for a free slot when --concurrency caps the requests in flight.
+                    let permit = concurrency.clone().acquire_owned().await?;
+
+                    // Create task to run the action in a separate thread.
+                    let task = tokio::spawn({
+                        rate_limiter.wait();
+                        let mint = mint_address.clone();
+                        let name = Self::name();
+                        let changelog = changelog.clone();
+                        let fut = Self::action(RunActionArgs {
+                            client: client.clone(),
+                            keypair: keypair.clone(),
+                            payer: payer.clone(),
+                            mint_account: mint_address,
+                            new_value: new_value.to_string(),
+                            priority: args.priority.clone(),
+                        });
+
+                        pb.inc(1);
+
+                        // Logs from the action carry the mint, so interleaved tasks can be
+                        // told apart.
+                        let span = info_span!("action", command = name, mint = %mint);
+                        async move {
+                            let result = fut.await;
+                            drop(permit);
+                            record_checkpoint(name, &mint, &result);
+                            match &result {
+                                Ok(outcome) => {
+                                    if let ActionOutcome::Sent(sig) = outcome {
+                                        changelog.record(&mint, *sig);
+                                    }
+                                    info!("Done, transaction: {outcome}");
+                                }
+                                Err(e) => info!("Failed: {e}"),
+                            }
+                            result
+                        }
+                        .instrument(span)
                     });
 
-                    pb.inc(1);
+                    // Collect all the tasks in our futures vector.
+                    update_tasks.push(task);
+                }
 
-                    fut
-                });
+                pb.finish_and_clear();
 
-                // Collect all the tasks in our futures vector.
-                update_tasks.push(task);
-            }
+                let pb =
+                    create_progress_bar("Waiting for requests to resolve...", mint_length as u64);
 
-            pb.finish_and_clear();
+                // Wait for all the tasks to resolve and push the results to our results vector
+                let mut round_results = Vec::new();
+                for task in update_tasks {
+                    round_results.push(task.await.unwrap());
+                    // Increment the counter and update the progress bar.
+                    pb.inc(1);
+                }
+                pb.finish_and_clear();
 
-            let update_tasks_len = update_tasks.len();
+                // Requests the RPC rejected for rate limiting never reached the chain, so they
+                // are sent again more slowly instead of being cached as failures.
+                let (throttled, results): (CacheResults, CacheResults) =
+                    round_results.into_iter().partition(is_throttled);
+                update_results.extend(results);
 
-            let pb = create_progress_bar("Waiting for requests to resolve...", mint_length as u64);
+                if throttled.is_empty() {
+                    break;
+                }
+                if throttled_rounds == MAX_RATE_LIMITED_RETRIES {
+                    update_results.extend(throttled);
+                    break;
+                }
 
-            // Wait for all the tasks to resolve and push the results to our results vector
-            let mut update_results = Vec::new();
-            for task in update_tasks {
-                update_results.push(task.await.unwrap());
-                // Increment the counter and update the progress bar.
-                pb.inc(1);
+                rate_limit = (rate_limit / 2).max(1);
+                let delay = NANO_SECONDS_IN_SECOND / rate_limit;
+                rate_limiter = create_rate_limiter_with_capacity(rate_limit as u32, delay as u32);
+
+                let backoff = rate_limited_backoff(throttled_rounds);
+                throttled_rounds += 1;
+                println!(
+                    "{} requests were rate limited by the RPC. Retrying them in {backoff:?} at {rate_limit} requests per second. . .",
+                    throttled.len()
+                );
+                std::thread::sleep(backoff);
+
+                remaining_mints = throttled
+                    .into_iter()
+                    .filter_map(|r| match r {
+                        Err(ActionError::ActionFailed(mint, _)) => Some(mint),
+                        Ok(_) => None,
+                    })
+                    .collect();
             }
-            pb.finish_and_clear();
 
             // Partition migration results.
-            let (_update_successful, update_failed): (CacheResults, CacheResults) =
+            let (update_successful, update_failed): (CacheResults, CacheResults) =
                 update_results.into_iter().partition(Result::is_ok);
 
+            // Items that already had the target value count as done without a transaction,
+            // so re-running a partially completed job only pays for what's left.
+            already_done += update_successful
+                .iter()
+                .filter(|r| matches!(r, Ok(ActionOutcome::AlreadyDone)))
+                .count();
+
+            println!("Already done: {already_done}");
             println!("Updates failed: {}", update_failed.len());
 
             // If some of the migrations failed, check the retry count and re-run if appropriate,
@@ -261,6 +383,13 @@ pub trait Action {
             }
         }
 
+        // The changes are already on chain, so failing to write them out isn't an error.
+        match changelog.write(&client) {
+            Ok(Some(path)) => println!("Changelog written to {path}"),
+            Ok(None) => {}
+            Err(e) => warn!("Failed to write the changelog: {e}"),
+        }
+
         Ok(())
     }
 }
@@ -0,0 +1,189 @@
+//! Before and after changelog of the metadata a mutating batch command changed.
+//!
+//! The metadata of every mint is read before the run and read again, straight from the
+//! RPC, for the mints a transaction was sent for. Each changed field is written out with
+//! both values, so the file shows exactly what a run changed and that nothing else was
+//! touched.
+
+use std::{collections::HashMap, fs::File, str::FromStr, sync::Mutex};
+
+use anyhow::Result;
+use log::warn;
+use mpl_token_metadata::accounts::Metadata;
+use serde::Serialize;
+use serde_json::Value;
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::{pubkey::Pubkey, signature::Signature};
+
+use crate::constants::MAX_MULTIPLE_ACCOUNTS;
+use crate::decode::get_metadata_pda;
+use crate::workspace::output_path;
+
+#[derive(Debug, Serialize)]
+pub struct FieldChange {
+    /// Path of the field in the metadata, e.g. `creators[1].verified`.
+    pub field: String,
+    pub before: Value,
+    pub after: Value,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ChangelogEntry {
+    pub mint: String,
+    pub signature: String,
+    /// The metadata account no longer exists, as after a burn.
+    pub closed: bool,
+    pub changes: Vec<FieldChange>,
+}
+
+#[derive(Debug, Serialize)]
+struct ChangelogFile<'a> {
+    command: &'a str,
+    started_at: String,
+    finished_at: String,
+    entries: Vec<ChangelogEntry>,
+}
+
+pub struct Changelog {
+    command: &'static str,
+    started_at: String,
+    before: HashMap<String, Value>,
+    sent: Mutex<Vec<(String, Signature)>>,
+}
+
+/// Read the metadata of each mint as JSON, bypassing the on-disk cache so the values are
+/// current. Mints without metadata are left out.
+fn fetch_metadata(client: &RpcClient, mints: &[String]) -> Result<HashMap<String, Value>> {
+    let mints: Vec<(&String, Pubkey)> = mints
+        .iter()
+        .filter_map(|mint| Some((mint, get_metadata_pda(Pubkey::from_str(mint).ok()?))))
+        .collect();
+
+    let mut metadata = HashMap::new();
+    for chunk in mints.chunks(MAX_MULTIPLE_ACCOUNTS) {
+        let pdas: Vec<Pubkey> = chunk.iter().map(|(_, pda)| *pda).collect();
+        let accounts = client.get_multiple_accounts(&pdas)?;
+
+        for ((mint, _), account) in chunk.iter().zip(accounts) {
+            if let Some(md) = account.and_then(|a| Metadata::safe_deserialize(&a.data).ok()) {
+                metadata.insert(mint.to_string(), serde_json::to_value(md)?);
+            }
+        }
+    }
+    Ok(metadata)
+}
+
+/// Every field that differs between two values, with nested objects and same-length
+/// arrays compared field by field.
+fn diff(path: &str, before: &Value, after: &Value, changes: &mut Vec<FieldChange>) {
+    match (before, after) {
+        (Value::Object(b), Value::Object(a)) => {
+            let mut keys: Vec<&String> = b.keys().chain(a.keys()).collect();
+            keys.sort();
+            keys.dedup();
+            for key in keys {
+                let field = if path.is_empty() {
+                    key.to_string()
+                } else {
+                    format!("{path}.{key}")
+                };
+                diff(
+                    &field,
+                    b.get(key).unwrap_or(&Value::Null),
+                    a.get(key).unwrap_or(&Value::Null),
+                    changes,
+                );
+            }
+        }
+        (Value::Array(b), Value::Array(a)) if b.len() == a.len() => {
+            for (i, (b, a)) in b.iter().zip(a).enumerate() {
+                diff(&format!("{path}[{i}]"), b, a, changes);
+            }
+        }
+        _ if before != after => changes.push(FieldChange {
+            field: path.to_string(),
+            before: before.clone(),
+            after: after.clone(),
+        }),
+        _ => {}
+    }
+}
+
+impl Changelog {
+    /// Read the current metadata of the mints a command is about to change.
+    pub fn capture(client: &RpcClient, command: &'static str, mints: &[String]) -> Result<Self> {
+        Ok(Self {
+            command,
+            started_at: chrono::Local::now().to_rfc3339(),
+            before: fetch_metadata(client, mints)?,
+            sent: Mutex::new(vec![]),
+        })
+    }
+
+    /// The metadata read before the run, as JSON, keyed by mint.
+    pub fn before(&self) -> &HashMap<String, Value> {
+        &self.before
+    }
+
+    /// Record that a transaction changing the mint landed.
+    pub fn record(&self, mint: &str, signature: Signature) {
+        self.sent
+            .lock()
+            .unwrap()
+            .push((mint.to_string(), signature));
+    }
+
+    /// Read the changed mints again and write the diffs to `mb-changelog-<command>-<time>.json`.
+    /// Nothing is written if no transactions were sent.
+    pub fn write(&self, client: &RpcClient) -> Result<Option<String>> {
+        let sent = self.sent.lock().unwrap();
+        if sent.is_empty() {
+            return Ok(None);
+        }
+
+        let mints: Vec<String> = sent.iter().map(|(mint, _)| mint.clone()).collect();
+        let after = fetch_metadata(client, &mints)?;
+
+        let mut entries = vec![];
+        for (mint, signature) in sent.iter() {
+            let before = match self.before.get(mint) {
+                Some(before) => before,
+                None => {
+                    warn!("No metadata was read for {mint} before the run, leaving it out of the changelog");
+                    continue;
+                }
+            };
+            let mut changes = vec![];
+            if let Some(after) = after.get(mint) {
+                diff("", before, after, &mut changes);
+            }
+            entries.push(ChangelogEntry {
+                mint: mint.clone(),
+                signature: signature.to_string(),
+                closed: !after.contains_key(mint),
+                changes,
+            });
+        }
+        entries.sort_by(|a, b| a.mint.cmp(&b.mint));
+
+        let timestamp = chrono::Local::now();
+        let path = output_path(format!(
+            "mb-changelog-{}-{}.json",
+            self.command,
+            timestamp.format("%Y-%m-%d-%H-%M-%S")
+        ))
+        .display()
+        .to_string();
+        serde_json::to_writer_pretty(
+            File::create(&path)?,
+            &ChangelogFile {
+                command: self.command,
+                started_at: self.started_at.clone(),
+                finished_at: timestamp.to_rfc3339(),
+                entries,
+            },
+        )?;
+
+        Ok(Some(path))
+    }
+}
@@ -0,0 +1,387 @@
+use std::{
+    fs::File,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+use anyhow::{anyhow, Result};
+use rayon::prelude::*;
+use serde::Serialize;
+use serde_json::Value;
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::pubkey::Pubkey;
+
+use crate::decode::decode_metadata_batch;
+use crate::files::read_json;
+use crate::parse::is_only_one_option;
+use crate::rpc_cache;
+use crate::workspace::output_path;
+
+pub struct CheckMetadataJsonArgs {
+    pub client: RpcClient,
+    pub metadata_files_dir: Option<PathBuf>,
+    pub mint_list: Option<String>,
+}
+
+/// Problems found in one metadata JSON. Errors break the standard, warnings are likely
+/// mistakes that wallets and marketplaces tolerate.
+#[derive(Debug, Default, Serialize)]
+pub struct MetadataJsonReport {
+    /// The file, or the mint whose URI was fetched.
+    pub source: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub uri: Option<String>,
+    pub errors: Vec<String>,
+    pub warnings: Vec<String>,
+}
+
+const CATEGORIES: &[&str] = &["image", "video", "audio", "vr", "html"];
+
+/// The MIME type prefix files of a category are expected to have.
+fn category_mime_prefix(category: &str) -> Option<&'static str> {
+    match category {
+        "image" => Some("image/"),
+        "video" => Some("video/"),
+        "audio" => Some("audio/"),
+        "vr" => Some("model/"),
+        "html" => Some("text/html"),
+        _ => None,
+    }
+}
+
+fn check_string(json: &Value, field: &str, required: bool, report: &mut MetadataJsonReport) {
+    match json.get(field) {
+        Some(Value::String(s)) if s.trim().is_empty() && required => {
+            report.errors.push(format!("{field} is empty"))
+        }
+        Some(Value::String(_)) => {}
+        Some(Value::Null) | None if required => report.errors.push(format!("{field} is missing")),
+        Some(Value::Null) | None => {}
+        Some(_) => report.errors.push(format!("{field} must be a string")),
+    }
+}
+
+fn check_attributes(json: &Value, report: &mut MetadataJsonReport) {
+    let attributes = match json.get("attributes") {
+        None | Some(Value::Null) => return,
+        Some(Value::Array(attributes)) => attributes,
+        Some(_) => {
+            report.errors.push("attributes must be a list".to_string());
+            return;
+        }
+    };
+
+    let mut trait_types = vec![];
+    for (i, attribute) in attributes.iter().enumerate() {
+        let trait_type = match attribute.get("trait_type") {
+            Some(Value::String(t)) => Some(t.as_str()),
+            Some(_) => {
+                report
+                    .errors
+                    .push(format!("attributes[{i}].trait_type must be a string"));
+                None
+            }
+            None if attribute.is_object() => {
+                report
+                    .warnings
+                    .push(format!("attributes[{i}] has no trait_type"));
+                None
+            }
+            None => {
+                report
+                    .errors
+                    .push(format!("attributes[{i}] must be an object"));
+                continue;
+            }
+        };
+        match attribute.get("value") {
+            Some(Value::String(_) | Value::Number(_) | Value::Bool(_)) => {}
+            Some(_) => report.errors.push(format!(
+                "attributes[{i}].value must be a string, number or boolean"
+            )),
+            None => report
+                .errors
+                .push(format!("attributes[{i}] is missing a value")),
+        }
+        if let Some(trait_type) = trait_type {
+            if trait_types.contains(&trait_type) {
+                report
+                    .warnings
+                    .push(format!("trait_type {trait_type} appears more than once"));
+            }
+            trait_types.push(trait_type);
+        }
+    }
+}
+
+fn check_creators(creators: &Value, report: &mut MetadataJsonReport) {
+    let creators = match creators {
+        Value::Array(creators) => creators,
+        _ => {
+            report
+                .errors
+                .push("properties.creators must be a list".to_string());
+            return;
+        }
+    };
+
+    let mut total = 0;
+    for (i, creator) in creators.iter().enumerate() {
+        match creator.get("address").and_then(Value::as_str) {
+            Some(address) if Pubkey::from_str(address).is_err() => report.errors.push(format!(
+                "properties.creators[{i}].address is not a valid address"
+            )),
+            Some(_) => {}
+            None => report
+                .errors
+                .push(format!("properties.creators[{i}] is missing an address")),
+        }
+        match creator.get("share").and_then(Value::as_u64) {
+            Some(share) => total += share,
+            None => report.errors.push(format!(
+                "properties.creators[{i}].share must be a whole number"
+            )),
+        }
+    }
+    if !creators.is_empty() && total != 100 {
+        report
+            .errors
+            .push(format!("creator shares add up to {total}, not 100"));
+    }
+}
+
+fn check_properties(json: &Value, report: &mut MetadataJsonReport) {
+    let properties = match json.get("properties") {
+        None | Some(Value::Null) => return,
+        Some(properties) if properties.is_object() => properties,
+        Some(_) => {
+            report
+                .errors
+                .push("properties must be an object".to_string());
+            return;
+        }
+    };
+
+    let category = match properties.get("category") {
+        None | Some(Value::Null) => None,
+        Some(Value::String(category)) if CATEGORIES.contains(&category.as_str()) => {
+            Some(category.as_str())
+        }
+        Some(category) => {
+            report.errors.push(format!(
+                "properties.category {category} is not one of {}",
+                CATEGORIES.join(", ")
+            ));
+            None
+        }
+    };
+
+    if let Some(creators) = properties.get("creators") {
+        check_creators(creators, report);
+    }
+
+    let files = match properties.get("files") {
+        None | Some(Value::Null) => return,
+        Some(Value::Array(files)) => files,
+        Some(_) => {
+            report
+                .errors
+                .push("properties.files must be a list".to_string());
+            return;
+        }
+    };
+
+    let mut uris = vec![];
+    for (i, file) in files.iter().enumerate() {
+        match file.get("uri").and_then(Value::as_str) {
+            Some(uri) if !uri.trim().is_empty() => uris.push(uri),
+            _ => report
+                .errors
+                .push(format!("properties.files[{i}] is missing a uri")),
+        }
+        match file.get("type").and_then(Value::as_str) {
+            Some(mime) if !mime.contains('/') => report.errors.push(format!(
+                "properties.files[{i}].type {mime} is not a MIME type"
+            )),
+            Some(_) => {}
+            None => report
+                .warnings
+                .push(format!("properties.files[{i}] has no type")),
+        }
+    }
+
+    // The category describes the main asset, so some file should be of its kind.
+    if let Some(prefix) = category.and_then(category_mime_prefix) {
+        let matches = files.iter().any(|f| {
+            f.get("type")
+                .and_then(Value::as_str)
+                .is_some_and(|t| t.starts_with(prefix))
+        });
+        if !files.is_empty() && !matches {
+            report.warnings.push(format!(
+                "properties.category is {} but no file has a {prefix} type",
+                category.unwrap_or_default()
+            ));
+        }
+    }
+
+    for field in ["image", "animation_url"] {
+        if let Some(uri) = json.get(field).and_then(Value::as_str) {
+            if !uri.is_empty() && !uris.contains(&uri) {
+                report
+                    .warnings
+                    .push(format!("{field} is not listed in properties.files"));
+            }
+        }
+    }
+}
+
+/// Check off-chain metadata JSON against the Metaplex JSON standard.
+pub fn validate_metadata_json(json: &Value, report: &mut MetadataJsonReport) {
+    if !json.is_object() {
+        report
+            .errors
+            .push("metadata must be a JSON object".to_string());
+        return;
+    }
+
+    check_string(json, "name", true, report);
+    check_string(json, "image", true, report);
+    check_string(json, "symbol", false, report);
+    check_string(json, "description", false, report);
+    check_string(json, "animation_url", false, report);
+    check_string(json, "external_url", false, report);
+
+    match json.get("seller_fee_basis_points") {
+        None | Some(Value::Null) => {}
+        Some(sfbp) => match sfbp.as_u64() {
+            Some(sfbp) if sfbp <= 10_000 => {}
+            _ => report
+                .errors
+                .push("seller_fee_basis_points must be a whole number up to 10000".to_string()),
+        },
+    }
+
+    check_attributes(json, report);
+    check_properties(json, report);
+}
+
+fn check_files(dir: &Path) -> Result<Vec<MetadataJsonReport>> {
+    let mut paths = vec![];
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        let name = path.to_string_lossy();
+        if name.ends_with(".json") || name.ends_with(".json.zst") {
+            paths.push(path);
+        }
+    }
+    paths.sort();
+
+    Ok(paths
+        .into_par_iter()
+        .map(|path| {
+            let mut report = MetadataJsonReport {
+                source: path.display().to_string(),
+                ..Default::default()
+            };
+            match read_json::<Value, _>(&path) {
+                Ok(json) => validate_metadata_json(&json, &mut report),
+                Err(e) => report.errors.push(e.to_string()),
+            }
+            report
+        })
+        .collect())
+}
+
+fn check_mints(client: &RpcClient, mint_list: &str) -> Result<Vec<MetadataJsonReport>> {
+    let mint_list: Vec<String> = read_json(mint_list)?;
+
+    Ok(decode_metadata_batch(client, &mint_list)
+        .into_par_iter()
+        .map(|(mint, md)| {
+            let mut report = MetadataJsonReport {
+                source: mint,
+                ..Default::default()
+            };
+            let md = match md {
+                Ok(md) => md,
+                Err(e) => {
+                    report
+                        .errors
+                        .push(format!("Failed to decode metadata: {e}"));
+                    return report;
+                }
+            };
+            let uri = md.uri.trim_matches(char::from(0)).to_string();
+            report.uri = Some(uri.clone());
+
+            let json = match rpc_cache::get_json(&uri) {
+                Ok(json) => json,
+                Err(e) => {
+                    report.errors.push(format!("Failed to fetch the JSON: {e}"));
+                    return report;
+                }
+            };
+            validate_metadata_json(&json, &mut report);
+
+            // Wallets show the on-chain name, so a different off-chain one is confusing.
+            let on_chain_name = md.name.trim_matches(char::from(0));
+            if let Some(name) = json.get("name").and_then(Value::as_str) {
+                if name != on_chain_name {
+                    report.warnings.push(format!(
+                        "name {name} doesn't match the on-chain name {on_chain_name}"
+                    ));
+                }
+            }
+            report
+        })
+        .collect())
+}
+
+pub fn check_metadata_json(args: CheckMetadataJsonArgs) -> Result<()> {
+    if !is_only_one_option(&args.metadata_files_dir, &args.mint_list) {
+        return Err(anyhow!(
+            "Please specify either a metadata files directory or a mint list, but not both."
+        ));
+    }
+
+    let reports = if let Some(dir) = &args.metadata_files_dir {
+        check_files(dir)?
+    } else if let Some(mint_list) = &args.mint_list {
+        check_mints(&args.client, mint_list)?
+    } else {
+        unreachable!()
+    };
+
+    let checked = reports.len();
+    let flagged: Vec<MetadataJsonReport> = reports
+        .into_iter()
+        .filter(|r| !r.errors.is_empty() || !r.warnings.is_empty())
+        .collect();
+    let invalid = flagged.iter().filter(|r| !r.errors.is_empty()).count();
+
+    if flagged.is_empty() {
+        println!("All {checked} metadata JSON files are valid!");
+        return Ok(());
+    }
+
+    for report in &flagged {
+        println!("{}", report.source);
+        for error in &report.errors {
+            println!("  error: {error}");
+        }
+        for warning in &report.warnings {
+            println!("  warning: {warning}");
+        }
+    }
+
+    let file_name = output_path("mb_check_metadata_json.json");
+    let f = File::create(&file_name)?;
+    serde_json::to_writer_pretty(f, &flagged)?;
+    println!(
+        "{invalid} of {checked} metadata JSON files have errors, {} more have warnings. Written to {file_name:?}.",
+        flagged.len() - invalid
+    );
+
+    Ok(())
+}
@@ -6,6 +6,15 @@ use mpl_token_metadata::accounts::Metadata;
 use solana_client::rpc_client::RpcClient;
 use structopt::StructOpt;
 
+use crate::workspace::output_path;
+
+mod metadata_json;
+mod placeholder_uris;
+mod uris;
+pub use metadata_json::*;
+pub use placeholder_uris::*;
+pub use uris::*;
+
 #[derive(Debug, StructOpt)]
 pub enum CheckSubcommands {
     /// Check downloaded metadata files for a specific value
@@ -18,14 +27,80 @@ pub enum CheckSubcommands {
         /// Metadata value
         value: MetadataValue,
     },
+    /// Flag URIs that look like placeholders: localhost, example.com, REPLACE_ME, empty or large data URIs
+    #[structopt(name = "placeholder-uris")]
+    PlaceholderUris {
+        /// Path to the mint list file
+        #[structopt(short = "L", long)]
+        mint_list: String,
+
+        /// Flag data URIs larger than this many bytes
+        #[structopt(long, default_value = "1024")]
+        max_data_uri_bytes: usize,
+    },
+    /// Validate off-chain metadata JSON against the Metaplex JSON standard
+    #[structopt(name = "metadata-json")]
+    MetadataJson {
+        /// Path to a directory of metadata JSON files
+        #[structopt(short = "d", long)]
+        metadata_files_dir: Option<PathBuf>,
+
+        /// Path to a mint list file, to fetch and check each mint's URI
+        #[structopt(short = "L", long)]
+        mint_list: Option<String>,
+    },
+    /// Request every token URI and image of a mint list and report broken links
+    #[structopt(name = "uris")]
+    Uris {
+        /// Path to the mint list file
+        #[structopt(short = "L", long)]
+        mint_list: String,
+
+        /// Gateway to request ipfs:// URIs through
+        #[structopt(long, default_value = "https://ipfs.io")]
+        ipfs_gateway: String,
+
+        /// Seconds to wait for each response
+        #[structopt(long, default_value = "30")]
+        request_timeout: u64,
+    },
 }
 
-pub async fn process_check(commands: CheckSubcommands) -> Result<()> {
+pub async fn process_check(client: RpcClient, commands: CheckSubcommands) -> Result<()> {
     match commands {
         CheckSubcommands::MetadataValue {
             metadata_files_dir,
             value,
         } => check_value_all(metadata_files_dir, value),
+        CheckSubcommands::PlaceholderUris {
+            mint_list,
+            max_data_uri_bytes,
+        } => check_placeholder_uris(CheckPlaceholderUrisArgs {
+            client,
+            mint_list,
+            max_data_uri_bytes,
+        }),
+        CheckSubcommands::MetadataJson {
+            metadata_files_dir,
+            mint_list,
+        } => check_metadata_json(CheckMetadataJsonArgs {
+            client,
+            metadata_files_dir,
+            mint_list,
+        }),
+        CheckSubcommands::Uris {
+            mint_list,
+            ipfs_gateway,
+            request_timeout,
+        } => {
+            check_uris(CheckUrisArgs {
+                client,
+                mint_list,
+                ipfs_gateway,
+                timeout: request_timeout,
+            })
+            .await
+        }
     }
 }
 
@@ -63,10 +138,10 @@ fn check_value_all(metadata_file_dir: PathBuf, value: MetadataValue) -> Result<(
         for path in paths {
             println!("{}", path.display());
         }
-        let file_name = format!(
+        let file_name = output_path(format!(
             "mb_check_mints_{}.json",
             value.to_string().split('=').next().unwrap()
-        );
+        ));
         let f = std::fs::File::create(&file_name)?;
         serde_json::to_writer_pretty(f, &mints)?;
         println!("Mints written to {:?}.", file_name);
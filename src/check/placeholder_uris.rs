@@ -0,0 +1,124 @@
+use std::fs::File;
+
+use anyhow::Result;
+use serde::Serialize;
+use solana_client::rpc_client::RpcClient;
+
+use crate::checkpoint;
+use crate::decode::decode_metadata_batch;
+use crate::files::read_json;
+use crate::workspace::output_path;
+
+/// Name the outcome of each mint is recorded under. Mints whose metadata couldn't be
+/// read count as failed, flagged ones as checked.
+const CHECK_PLACEHOLDER_URIS_COMMAND: &str = "check-placeholder-uris";
+
+pub struct CheckPlaceholderUrisArgs {
+    pub client: RpcClient,
+    pub mint_list: String,
+    pub max_data_uri_bytes: usize,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PlaceholderUri {
+    pub mint: String,
+    pub uri: String,
+    pub reason: String,
+}
+
+const PLACEHOLDER_HOSTS: &[&str] = &[
+    "localhost",
+    "127.0.0.1",
+    "0.0.0.0",
+    "example.com",
+    "example.org",
+    "example.net",
+];
+
+const PLACEHOLDER_MARKERS: &[&str] = &["replace_me", "replaceme", "replace-me"];
+
+/// Return why a URI looks like a placeholder, if it does.
+pub fn placeholder_reason(uri: &str, max_data_uri_bytes: usize) -> Option<String> {
+    let uri = uri.trim_matches(char::from(0)).trim();
+
+    if uri.is_empty() {
+        return Some(String::from("empty uri"));
+    }
+
+    if uri.starts_with("data:") {
+        return (uri.len() > max_data_uri_bytes)
+            .then(|| format!("data uri of {} bytes", uri.len()));
+    }
+
+    let lower = uri.to_lowercase();
+
+    // Compare against the host only so paths like /localhost-art/ don't match.
+    let host = lower
+        .split("://")
+        .nth(1)
+        .unwrap_or(&lower)
+        .split(['/', '?', '#'])
+        .next()
+        .unwrap_or_default();
+    let host = host.rsplit('@').next().unwrap_or(host);
+    let host = host.split(':').next().unwrap_or(host);
+
+    if let Some(placeholder) = PLACEHOLDER_HOSTS
+        .iter()
+        .find(|h| host == **h || host.ends_with(&format!(".{h}")))
+    {
+        return Some(format!("placeholder host {placeholder}"));
+    }
+
+    PLACEHOLDER_MARKERS
+        .iter()
+        .find(|m| lower.contains(*m))
+        .map(|m| format!("contains {}", m.to_uppercase()))
+}
+
+pub fn check_placeholder_uris(args: CheckPlaceholderUrisArgs) -> Result<()> {
+    let mint_list: Vec<String> = read_json(&args.mint_list)?;
+
+    let mut flagged = Vec::new();
+    let mut failed = Vec::new();
+
+    for (mint, md) in decode_metadata_batch(&args.client, &mint_list) {
+        match md {
+            Ok(md) => {
+                checkpoint::record_done(CHECK_PLACEHOLDER_URIS_COMMAND, &mint, None);
+                if let Some(reason) = placeholder_reason(&md.uri, args.max_data_uri_bytes) {
+                    flagged.push(PlaceholderUri {
+                        mint,
+                        uri: md.uri.trim_matches(char::from(0)).to_string(),
+                        reason,
+                    });
+                }
+            }
+            Err(e) => {
+                checkpoint::record_failed(CHECK_PLACEHOLDER_URIS_COMMAND, &mint, &e);
+                failed.push((mint, e));
+            }
+        }
+    }
+
+    for (mint, e) in &failed {
+        println!("Failed to decode metadata for {mint}: {e}");
+    }
+
+    if flagged.is_empty() {
+        println!("No placeholder URIs found!");
+        return Ok(());
+    }
+
+    println!("Mints with placeholder URIs:");
+    for item in &flagged {
+        println!("{}\t{}\t{}", item.mint, item.reason, item.uri);
+    }
+
+    let file_name = output_path("mb_check_placeholder_uris.json");
+    let f = File::create(&file_name)?;
+    serde_json::to_writer_pretty(f, &flagged)?;
+    println!("{} mints written to {:?}.", flagged.len(), file_name);
+
+    Ok(())
+}
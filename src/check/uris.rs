@@ -0,0 +1,201 @@
+use std::{collections::HashSet, fs::File, time::Duration};
+
+use anyhow::{anyhow, Result};
+use futures::stream::{self, StreamExt};
+use reqwest::{header::CONTENT_TYPE, Client, StatusCode};
+use serde::Serialize;
+use serde_json::Value;
+use solana_client::rpc_client::RpcClient;
+
+use crate::decode::decode_metadata_batch;
+use crate::download::to_http_uri;
+use crate::files::read_json;
+use crate::limiter::concurrency_or;
+use crate::spinner::create_progress_bar;
+use crate::workspace::output_path;
+
+// Requests in flight at once when --concurrency isn't set.
+const DEFAULT_REQUESTS: usize = 20;
+
+pub struct CheckUrisArgs {
+    pub client: RpcClient,
+    pub mint_list: String,
+    pub ipfs_gateway: String,
+    pub timeout: u64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct BrokenUri {
+    pub mint: String,
+    /// Which link is broken: `uri` for the token URI, `image` for the image it links to, or
+    /// `metadata` when the mint's metadata couldn't be read.
+    pub link: String,
+    pub url: String,
+    /// HTTP status code, if the server responded at all.
+    pub status: Option<u16>,
+    pub reason: String,
+}
+
+enum Response {
+    Ok(Option<Value>),
+    Broken(Option<StatusCode>, String),
+}
+
+/// GET the URL and parse the body as a JSON object.
+async fn fetch_json(http: &Client, url: &str) -> Response {
+    let response = match http.get(url).send().await {
+        Ok(response) => response,
+        Err(e) => return Response::Broken(e.status(), format!("unreachable: {e}")),
+    };
+    let status = response.status();
+    if !status.is_success() {
+        return Response::Broken(Some(status), format!("HTTP {status}"));
+    }
+    match response.json::<Value>().await {
+        Ok(json) if json.is_object() => Response::Ok(Some(json)),
+        Ok(_) => Response::Broken(Some(status), "not a JSON object".to_string()),
+        Err(_) => Response::Broken(Some(status), "not JSON".to_string()),
+    }
+}
+
+/// HEAD the URL, falling back to a GET for servers that don't allow HEAD.
+async fn check_reachable(http: &Client, url: &str) -> Response {
+    let response = match http.head(url).send().await {
+        Ok(response)
+            if response.status() == StatusCode::METHOD_NOT_ALLOWED
+                || response.status() == StatusCode::NOT_IMPLEMENTED =>
+        {
+            http.get(url).send().await
+        }
+        response => response,
+    };
+    match response {
+        Ok(response) if response.status().is_success() => {
+            // Gateways serve their error pages as HTML with a 200 when a file is missing.
+            let content_type = response
+                .headers()
+                .get(CONTENT_TYPE)
+                .and_then(|c| c.to_str().ok())
+                .unwrap_or_default();
+            if content_type.starts_with("text/html") {
+                Response::Broken(Some(response.status()), "served an HTML page".to_string())
+            } else {
+                Response::Ok(None)
+            }
+        }
+        Ok(response) => Response::Broken(
+            Some(response.status()),
+            format!("HTTP {}", response.status()),
+        ),
+        Err(e) => Response::Broken(e.status(), format!("unreachable: {e}")),
+    }
+}
+
+async fn check_mint(
+    http: &Client,
+    mint: String,
+    uri: String,
+    ipfs_gateway: &str,
+) -> Vec<BrokenUri> {
+    let broken = |link: &str, url: &str, status: Option<StatusCode>, reason: String| BrokenUri {
+        mint: mint.clone(),
+        link: link.to_string(),
+        url: url.to_string(),
+        status: status.map(|s| s.as_u16()),
+        reason,
+    };
+
+    if uri.trim().is_empty() {
+        return vec![broken("uri", &uri, None, "empty uri".to_string())];
+    }
+
+    let json = match fetch_json(http, &to_http_uri(&uri, ipfs_gateway)).await {
+        Response::Ok(json) => json.unwrap_or_default(),
+        Response::Broken(status, reason) => return vec![broken("uri", &uri, status, reason)],
+    };
+
+    match json.get("image").and_then(Value::as_str) {
+        Some(image) if !image.trim().is_empty() => {
+            match check_reachable(http, &to_http_uri(image, ipfs_gateway)).await {
+                Response::Ok(_) => vec![],
+                Response::Broken(status, reason) => vec![broken("image", image, status, reason)],
+            }
+        }
+        _ => vec![broken(
+            "image",
+            "",
+            None,
+            "no image in the JSON".to_string(),
+        )],
+    }
+}
+
+/// Request every token URI of the mint list, and the image each one links to, and report
+/// the ones that are unreachable, return an error status or don't serve what they should.
+pub async fn check_uris(args: CheckUrisArgs) -> Result<()> {
+    let mint_list: Vec<String> = read_json(&args.mint_list)?;
+
+    let mut broken = vec![];
+    let mut uris = vec![];
+    for (mint, md) in decode_metadata_batch(&args.client, &mint_list) {
+        match md {
+            Ok(md) => uris.push((mint, md.uri.trim_matches(char::from(0)).to_string())),
+            Err(e) => broken.push(BrokenUri {
+                mint,
+                link: "metadata".to_string(),
+                url: String::new(),
+                status: None,
+                reason: e.to_string(),
+            }),
+        }
+    }
+
+    let http = Client::builder()
+        .timeout(Duration::from_secs(args.timeout))
+        .build()
+        .map_err(|e| anyhow!("Failed to create HTTP client: {e}"))?;
+    let pb = create_progress_bar("Checking URIs...", uris.len() as u64);
+    let checked = uris.len();
+    let results: Vec<Vec<BrokenUri>> = stream::iter(uris)
+        .map(|(mint, uri)| {
+            let http = &http;
+            let pb = &pb;
+            let ipfs_gateway = args.ipfs_gateway.as_str();
+            async move {
+                let result = check_mint(http, mint, uri, ipfs_gateway).await;
+                pb.inc(1);
+                result
+            }
+        })
+        .buffer_unordered(concurrency_or(DEFAULT_REQUESTS))
+        .collect()
+        .await;
+    pb.finish_and_clear();
+    broken.extend(results.into_iter().flatten());
+
+    if broken.is_empty() {
+        println!("All {checked} URIs and images are reachable!");
+        return Ok(());
+    }
+
+    broken.sort_by(|a, b| a.mint.cmp(&b.mint));
+    for b in &broken {
+        match b.status {
+            Some(status) => println!("{} {} {} ({status}): {}", b.mint, b.link, b.url, b.reason),
+            None => println!("{} {} {}: {}", b.mint, b.link, b.url, b.reason),
+        }
+    }
+
+    let file_name = output_path("mb_check_uris.json");
+    let f = File::create(&file_name)?;
+    serde_json::to_writer_pretty(f, &broken)?;
+    let mints: HashSet<&str> = broken.iter().map(|b| b.mint.as_str()).collect();
+    println!(
+        "Found broken links on {} of {} mints. Written to {:?}.",
+        mints.len(),
+        mint_list.len(),
+        file_name
+    );
+
+    Ok(())
+}
@@ -0,0 +1,236 @@
+//! Checkpoint file from the global `--resume` option, shared by batch commands so a crashed
+//! run can be picked up where it stopped.
+//!
+//! The file is a journal with one JSON object per line, appended as each item finishes, so
+//! nothing is lost if the process is killed. The last line for an item wins. Items are
+//! tracked per command, so one file can be reused across the steps of a job.
+
+use std::{
+    collections::HashMap,
+    fs::{File, OpenOptions},
+    io::{BufRead, BufReader, Read, Seek, SeekFrom, Write},
+    path::Path,
+    sync::Mutex,
+};
+
+use anyhow::Result;
+use log::warn;
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+
+use crate::output;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ItemStatus {
+    Done,
+    Failed,
+}
+
+/// One line of the checkpoint file.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CheckpointEntry {
+    pub command: String,
+    pub item: String,
+    pub status: ItemStatus,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signature: Option<String>,
+    /// The mint created for the item, for commands that mint.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mint: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+struct Checkpoint {
+    file: File,
+    // Latest status of each item, keyed by command and then item.
+    status: HashMap<String, HashMap<String, ItemStatus>>,
+}
+
+static CHECKPOINT: Lazy<Mutex<Option<Checkpoint>>> = Lazy::new(|| Mutex::new(None));
+
+/// Load the checkpoint file, creating it if it doesn't exist, and record to it from now on.
+pub fn open_checkpoint<P: AsRef<Path>>(path: P) -> Result<()> {
+    let path = path.as_ref();
+    let mut status: HashMap<String, HashMap<String, ItemStatus>> = HashMap::new();
+
+    if path.exists() {
+        for line in BufReader::new(File::open(path)?).lines() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            // A line cut short by a crash is the only one that can fail to parse.
+            let entry: CheckpointEntry = match serde_json::from_str(&line) {
+                Ok(entry) => entry,
+                Err(e) => {
+                    warn!("Skipping unreadable checkpoint line: {e}");
+                    continue;
+                }
+            };
+            status
+                .entry(entry.command)
+                .or_default()
+                .insert(entry.item, entry.status);
+        }
+    }
+
+    let mut file = OpenOptions::new()
+        .create(true)
+        .read(true)
+        .append(true)
+        .open(path)?;
+    // End a line cut short by a crash, so the next entry isn't appended to it.
+    if file.metadata()?.len() > 0 {
+        let mut last = [0u8];
+        file.seek(SeekFrom::End(-1))?;
+        file.read_exact(&mut last)?;
+        if last[0] != b'\n' {
+            writeln!(file)?;
+        }
+    }
+    *CHECKPOINT.lock().unwrap() = Some(Checkpoint { file, status });
+    Ok(())
+}
+
+/// Whether a checkpoint file is being recorded to.
+pub fn is_open() -> bool {
+    CHECKPOINT.lock().unwrap().is_some()
+}
+
+/// Drop the items a previous run of the command already finished.
+pub fn remaining(command: &str, items: Vec<String>) -> Vec<String> {
+    let guard = CHECKPOINT.lock().unwrap();
+    let done = match guard.as_ref().and_then(|c| c.status.get(command)) {
+        Some(done) => done,
+        None => return items,
+    };
+
+    let total = items.len();
+    let remaining: Vec<String> = items
+        .into_iter()
+        .filter(|item| done.get(item) != Some(&ItemStatus::Done))
+        .collect();
+    if remaining.len() < total {
+        println!(
+            "Skipping {} items already done according to the checkpoint",
+            total - remaining.len()
+        );
+    }
+    remaining
+}
+
+fn record(entry: CheckpointEntry) {
+    output::record_item(&entry);
+
+    let mut guard = CHECKPOINT.lock().unwrap();
+    let checkpoint = match guard.as_mut() {
+        Some(checkpoint) => checkpoint,
+        None => return,
+    };
+
+    let result = serde_json::to_string(&entry)
+        .map_err(anyhow::Error::from)
+        .and_then(|line| Ok(writeln!(checkpoint.file, "{line}")?));
+    if let Err(e) = result {
+        warn!("Failed to write checkpoint for {}: {e}", entry.item);
+    }
+    checkpoint
+        .status
+        .entry(entry.command)
+        .or_default()
+        .insert(entry.item, entry.status);
+}
+
+/// Record that an item finished, with the signature of its transaction if one was sent.
+pub fn record_done(command: &str, item: &str, signature: Option<String>) {
+    record(CheckpointEntry {
+        command: command.to_string(),
+        item: item.to_string(),
+        status: ItemStatus::Done,
+        signature,
+        mint: None,
+        error: None,
+    });
+}
+
+/// Record that an item was minted, along with the new mint.
+pub fn record_minted(command: &str, item: &str, mint: &str) {
+    record(CheckpointEntry {
+        command: command.to_string(),
+        item: item.to_string(),
+        status: ItemStatus::Done,
+        signature: None,
+        mint: Some(mint.to_string()),
+        error: None,
+    });
+}
+
+/// Record that an item failed, so it's retried when the command is run again.
+pub fn record_failed<E: ToString>(command: &str, item: &str, error: E) {
+    record(CheckpointEntry {
+        command: command.to_string(),
+        item: item.to_string(),
+        status: ItemStatus::Failed,
+        signature: None,
+        mint: None,
+        error: Some(error.to_string()),
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn items(items: &[&str]) -> Vec<String> {
+        items.iter().map(|item| item.to_string()).collect()
+    }
+
+    // The checkpoint is global, so resuming is covered by one test rather than several
+    // that would race on it.
+    #[test]
+    fn resumes_only_unfinished_items() {
+        let path =
+            std::env::temp_dir().join(format!("mb-checkpoint-test-{}.jsonl", std::process::id()));
+        let lines = [
+            r#"{"command":"update-uri-all","item":"a","status":"done","signature":"sig"}"#,
+            r#"{"command":"update-uri-all","item":"b","status":"failed","error":"boom"}"#,
+            r#"{"command":"update-uri-all","item":"c","status":"done"}"#,
+            r#"{"command":"update-uri-all","item":"c","status":"failed","error":"boom"}"#,
+            r#"{"command":"update-uri-all","item":"d","status":"failed","error":"boom"}"#,
+            r#"{"command":"update-uri-all","item":"d","status":"done"}"#,
+            r#"{"command":"burn-all","item":"e","status":"done"}"#,
+            // Cut short by a crash.
+            r#"{"command":"update-uri-all","item":"#,
+        ];
+        std::fs::write(&path, lines.join("\n")).unwrap();
+
+        open_checkpoint(&path).unwrap();
+
+        // The last line for an item wins, and items are tracked per command.
+        assert_eq!(
+            remaining("update-uri-all", items(&["a", "b", "c", "d", "e", "f"])),
+            items(&["b", "c", "e", "f"])
+        );
+        assert_eq!(remaining("burn-all", items(&["a", "e"])), items(&["a"]));
+        assert_eq!(remaining("set-immutable-all", items(&["a"])), items(&["a"]));
+
+        record_done("update-uri-all", "b", None);
+        record_failed("update-uri-all", "e", "boom");
+        assert_eq!(
+            remaining("update-uri-all", items(&["b", "c", "e"])),
+            items(&["c", "e"])
+        );
+
+        // Reopening picks up what was recorded.
+        open_checkpoint(&path).unwrap();
+        assert_eq!(
+            remaining("update-uri-all", items(&["a", "b", "c", "e"])),
+            items(&["c", "e"])
+        );
+
+        *CHECKPOINT.lock().unwrap() = None;
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,369 @@
+use std::path::PathBuf;
+
+use anyhow::{anyhow, Result};
+use borsh::{BorshDeserialize, BorshSerialize};
+use metaboss_lib::data::Priority;
+use serde::Deserialize;
+use solana_client::rpc_client::RpcClient;
+use solana_program::{
+    hash::hash,
+    instruction::{AccountMeta, Instruction},
+    pubkey,
+    pubkey::Pubkey,
+};
+use solana_sdk::{
+    compute_budget::ComputeBudgetInstruction, packet::PACKET_DATA_SIZE, signature::Keypair,
+    signer::Signer, transaction::Transaction,
+};
+
+use crate::{
+    checkpoint,
+    files::read_json,
+    parse::{parse_keypair, parse_solana_config},
+    pipeline::Pipeline,
+    spinner::create_progress_bar,
+};
+
+pub const CANDY_MACHINE_CORE_PROGRAM_ID: Pubkey =
+    pubkey!("CndyV3LdqHUfDLmE5naZjVN8rBZz4tqhdefbAnjHG3JR");
+
+const MAX_SYMBOL_LENGTH: usize = 10;
+const MAX_NAME_LENGTH: usize = 32;
+const MAX_URI_LENGTH: usize = 200;
+const MAX_CREATOR_LIMIT: usize = 5;
+const MAX_CREATOR_LEN: usize = 32 + 1 + 1;
+
+/// Name the outcome of each manifest item is recorded under.
+const ADD_ITEMS_COMMAND: &str = "cm-add-items";
+
+// Offset of the items-loaded counter, after the fixed-size CandyMachine struct.
+const HIDDEN_SECTION: usize = 8 // discriminator
+    + 8 // version, token standard and features
+    + 32 // authority
+    + 32 // mint authority
+    + 32 // collection mint
+    + 8 // items redeemed
+    + 8 // items available
+    + 4 + MAX_SYMBOL_LENGTH
+    + 2 // seller fee basis points
+    + 8 // max supply
+    + 1 // is mutable
+    + 4 + MAX_CREATOR_LIMIT * MAX_CREATOR_LEN
+    + 1 // config line settings option
+    + 4 + MAX_NAME_LENGTH // prefix name
+    + 4 // name length
+    + 4 + MAX_URI_LENGTH // prefix uri
+    + 4 // uri length
+    + 1 // is sequential
+    + 1 // hidden settings option
+    + 4 + MAX_NAME_LENGTH
+    + 4 + MAX_URI_LENGTH
+    + 32; // hash
+
+pub struct AddItemsArgs {
+    pub client: RpcClient,
+    pub keypair: Option<String>,
+    pub candy_machine: Pubkey,
+    pub manifest: PathBuf,
+    pub priority: Priority,
+}
+
+/// One config line per item, in candy machine index order.
+#[derive(Debug, Clone, Deserialize)]
+pub struct ManifestItem {
+    pub name: String,
+    pub uri: String,
+}
+
+#[derive(Debug, Clone, BorshSerialize)]
+pub struct ConfigLine {
+    pub name: String,
+    pub uri: String,
+}
+
+#[derive(BorshDeserialize)]
+struct CandyMachineHeader {
+    _version: u8,
+    _token_standard: u8,
+    _features: [u8; 6],
+    authority: Pubkey,
+    _mint_authority: Pubkey,
+    _collection_mint: Pubkey,
+    _items_redeemed: u64,
+    data: CandyMachineData,
+}
+
+#[derive(BorshDeserialize)]
+struct CandyMachineData {
+    items_available: u64,
+    _symbol: String,
+    _seller_fee_basis_points: u16,
+    _max_supply: u64,
+    _is_mutable: bool,
+    _creators: Vec<CandyMachineCreator>,
+    config_line_settings: Option<ConfigLineSettings>,
+    hidden_settings: Option<HiddenSettings>,
+}
+
+#[derive(BorshDeserialize)]
+struct CandyMachineCreator {
+    _address: Pubkey,
+    _verified: bool,
+    _percentage_share: u8,
+}
+
+#[derive(BorshDeserialize)]
+struct ConfigLineSettings {
+    prefix_name: String,
+    name_length: u32,
+    prefix_uri: String,
+    uri_length: u32,
+    _is_sequential: bool,
+}
+
+#[derive(BorshDeserialize)]
+struct HiddenSettings {
+    _name: String,
+    _uri: String,
+    _hash: [u8; 32],
+}
+
+/// The parts of a candy machine needed to load config lines.
+struct CandyMachineState {
+    authority: Pubkey,
+    items_available: usize,
+    settings: ConfigLineSettings,
+    loaded: Vec<bool>,
+}
+
+fn fetch_candy_machine(client: &RpcClient, candy_machine: &Pubkey) -> Result<CandyMachineState> {
+    let account = client.get_account(candy_machine)?;
+    if account.owner != CANDY_MACHINE_CORE_PROGRAM_ID {
+        return Err(anyhow!("{candy_machine} is not a Candy Machine v3 account"));
+    }
+
+    let header = CandyMachineHeader::deserialize(&mut &account.data[8..])?;
+    if header.data.hidden_settings.is_some() {
+        return Err(anyhow!(
+            "Candy machine uses hidden settings and does not take config lines"
+        ));
+    }
+    let settings = header
+        .data
+        .config_line_settings
+        .ok_or_else(|| anyhow!("Candy machine has no config line settings"))?;
+
+    let items_available = header.data.items_available as usize;
+    let line_size = (settings.name_length + settings.uri_length) as usize;
+
+    // Bitmask of loaded indices, most significant bit first.
+    let mask_start = HIDDEN_SECTION + 4 + items_available * line_size;
+    let mask = account
+        .data
+        .get(mask_start..mask_start + items_available / 8 + 1)
+        .ok_or_else(|| anyhow!("Candy machine account data is too short"))?;
+    let loaded = (0..items_available)
+        .map(|i| mask[i / 8] & (1 << (7 - i % 8)) != 0)
+        .collect();
+
+    Ok(CandyMachineState {
+        authority: header.authority,
+        items_available,
+        settings,
+        loaded,
+    })
+}
+
+/// Strip the shared prefix the candy machine adds back at mint time and check the
+/// remainder fits in the configured length.
+fn to_config_line(
+    index: usize,
+    item: &ManifestItem,
+    settings: &ConfigLineSettings,
+) -> Result<ConfigLine> {
+    let trim = |value: &str, prefix: &str, length: u32, field: &str| -> Result<String> {
+        if length == 0 {
+            return Ok(String::new());
+        }
+        let value = value.strip_prefix(prefix).ok_or_else(|| {
+            anyhow!("Item {index}: {field} '{value}' does not start with prefix '{prefix}'")
+        })?;
+        if value.len() > length as usize {
+            return Err(anyhow!(
+                "Item {index}: {field} '{value}' is longer than {length} bytes"
+            ));
+        }
+        Ok(value.to_string())
+    };
+
+    Ok(ConfigLine {
+        name: trim(
+            &item.name,
+            &settings.prefix_name,
+            settings.name_length,
+            "name",
+        )?,
+        uri: trim(&item.uri, &settings.prefix_uri, settings.uri_length, "uri")?,
+    })
+}
+
+pub fn add_config_lines_ix(
+    candy_machine: &Pubkey,
+    authority: &Pubkey,
+    index: u32,
+    config_lines: &[ConfigLine],
+) -> Result<Instruction> {
+    let mut data = hash(b"global:add_config_lines").to_bytes()[..8].to_vec();
+    data.extend(index.try_to_vec()?);
+    data.extend(config_lines.to_vec().try_to_vec()?);
+
+    Ok(Instruction {
+        program_id: CANDY_MACHINE_CORE_PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new(*candy_machine, false),
+            AccountMeta::new_readonly(*authority, true),
+        ],
+        data,
+    })
+}
+
+fn fits_in_transaction(payer: &Pubkey, ix: &Instruction) -> Result<bool> {
+    let ixs = [
+        ComputeBudgetInstruction::set_compute_unit_limit(0),
+        ComputeBudgetInstruction::set_compute_unit_price(0),
+        ix.clone(),
+    ];
+    let tx = Transaction::new_with_payer(&ixs, Some(payer));
+    Ok(bincode::serialized_size(&tx)? as usize <= PACKET_DATA_SIZE)
+}
+
+/// Group the lines that still need loading into runs of consecutive indices, each small
+/// enough to fit in one transaction.
+fn chunk_config_lines(
+    candy_machine: &Pubkey,
+    authority: &Pubkey,
+    lines: &[ConfigLine],
+    loaded: &[bool],
+) -> Result<Vec<(usize, Vec<ConfigLine>)>> {
+    let mut chunks: Vec<(usize, Vec<ConfigLine>)> = Vec::new();
+
+    for (index, line) in lines.iter().enumerate() {
+        if loaded[index] {
+            continue;
+        }
+
+        if let Some((start, chunk)) = chunks.last_mut() {
+            if *start + chunk.len() == index {
+                chunk.push(line.clone());
+                let ix = add_config_lines_ix(candy_machine, authority, *start as u32, chunk)?;
+                if fits_in_transaction(authority, &ix)? {
+                    continue;
+                }
+                chunk.pop();
+            }
+        }
+
+        let chunk = vec![line.clone()];
+        let ix = add_config_lines_ix(candy_machine, authority, index as u32, &chunk)?;
+        if !fits_in_transaction(authority, &ix)? {
+            return Err(anyhow!("Item {index} is too large to fit in a transaction"));
+        }
+        chunks.push((index, chunk));
+    }
+
+    Ok(chunks)
+}
+
+pub fn add_items(args: AddItemsArgs) -> Result<()> {
+    let solana_opts = parse_solana_config();
+    let keypair: Keypair = parse_keypair(args.keypair, solana_opts);
+
+    let manifest: Vec<ManifestItem> = read_json(&args.manifest)?;
+
+    let state = fetch_candy_machine(&args.client, &args.candy_machine)?;
+    if state.authority != keypair.pubkey() {
+        return Err(anyhow!(
+            "Keypair {} is not the candy machine authority {}",
+            keypair.pubkey(),
+            state.authority
+        ));
+    }
+    if manifest.len() > state.items_available {
+        return Err(anyhow!(
+            "Manifest has {} items but the candy machine only holds {}",
+            manifest.len(),
+            state.items_available
+        ));
+    }
+
+    // Validate everything up front so a bad item doesn't leave a half-loaded machine.
+    let lines = manifest
+        .iter()
+        .enumerate()
+        .map(|(index, item)| to_config_line(index, item, &state.settings))
+        .collect::<Result<Vec<_>>>()?;
+
+    let already_loaded = state.loaded[..lines.len()].iter().filter(|l| **l).count();
+    if already_loaded > 0 {
+        println!("Skipping {already_loaded} items already loaded on-chain.");
+    }
+
+    let chunks = chunk_config_lines(
+        &args.candy_machine,
+        &keypair.pubkey(),
+        &lines,
+        &state.loaded,
+    )?;
+    if chunks.is_empty() {
+        println!("All items are already loaded.");
+        return Ok(());
+    }
+
+    let mut failed = Vec::new();
+    let pb = create_progress_bar("Adding config lines...", chunks.len() as u64);
+
+    for (start, chunk) in &chunks {
+        let result =
+            add_config_lines_ix(&args.candy_machine, &keypair.pubkey(), *start as u32, chunk)
+                .and_then(|ix| {
+                    Pipeline::with_priority(args.priority.clone())
+                        .with_active_nonce()?
+                        .run(&args.client, &[&keypair], vec![ix])
+                });
+
+        // Items are recorded by their index in the manifest.
+        let items = *start..start + chunk.len();
+        match result {
+            Ok(sig) => items.for_each(|index| {
+                checkpoint::record_done(
+                    ADD_ITEMS_COMMAND,
+                    &index.to_string(),
+                    Some(sig.to_string()),
+                )
+            }),
+            Err(e) => {
+                items.for_each(|index| {
+                    checkpoint::record_failed(ADD_ITEMS_COMMAND, &index.to_string(), &e)
+                });
+                failed.push(format!("Items {start}-{}: {e}", start + chunk.len() - 1));
+            }
+        }
+        pb.inc(1);
+    }
+    pb.finish_and_clear();
+
+    let sent = chunks.len() - failed.len();
+    println!("Sent {sent} of {} transactions.", chunks.len());
+
+    if !failed.is_empty() {
+        for failure in &failed {
+            println!("{failure}");
+        }
+        println!(
+            "{} transactions failed. Run the command again to resume.",
+            failed.len()
+        );
+    }
+
+    Ok(())
+}
@@ -0,0 +1,52 @@
+mod add_items;
+
+pub use add_items::*;
+
+use std::path::PathBuf;
+
+use anyhow::Result;
+use metaboss_lib::data::Priority;
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::pubkey::Pubkey;
+use structopt::StructOpt;
+
+#[derive(Debug, StructOpt)]
+pub enum CmSubcommands {
+    /// Load config lines from a manifest into a Candy Machine v3
+    #[structopt(name = "add-items")]
+    AddItems {
+        /// Path to the candy machine authority keypair file
+        #[structopt(short, long)]
+        keypair: Option<String>,
+
+        /// Candy machine address
+        #[structopt(short, long)]
+        candy_machine: Pubkey,
+
+        /// JSON file with a list of { "name", "uri" } items, in index order
+        #[structopt(short, long)]
+        manifest: PathBuf,
+
+        /// Priority of the transaction: higher priority costs more.
+        /// See https://metaboss.rs/priority-fees.html for more details.
+        #[structopt(short = "P", long, default_value = "none")]
+        priority: Priority,
+    },
+}
+
+pub fn process_cm(client: RpcClient, commands: CmSubcommands) -> Result<()> {
+    match commands {
+        CmSubcommands::AddItems {
+            keypair,
+            candy_machine,
+            manifest,
+            priority,
+        } => add_items(AddItemsArgs {
+            client,
+            keypair,
+            candy_machine,
+            manifest,
+            priority,
+        }),
+    }
+}
@@ -0,0 +1,151 @@
+use std::str::FromStr;
+
+use anyhow::{anyhow, Result};
+use serde::{de::DeserializeOwned, Deserialize};
+use serde_json::{json, Value};
+use solana_sdk::pubkey::Pubkey;
+
+/// The parts of a DAS `getAsset` response needed to modify a compressed NFT.
+#[derive(Debug, Deserialize)]
+pub struct DasAsset {
+    pub id: String,
+    pub compression: DasCompression,
+    pub ownership: DasOwnership,
+    #[serde(default)]
+    pub burnt: bool,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DasCompression {
+    pub compressed: bool,
+    pub data_hash: String,
+    pub creator_hash: String,
+    pub leaf_id: u64,
+    pub tree: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DasOwnership {
+    pub owner: String,
+    pub delegate: Option<String>,
+}
+
+/// A DAS `getAssetProof` response.
+#[derive(Debug, Deserialize)]
+pub struct DasAssetProof {
+    pub root: String,
+    pub proof: Vec<String>,
+    pub node_index: u64,
+    pub leaf: String,
+    pub tree_id: String,
+}
+
+/// Everything needed to prove ownership of a compressed NFT's leaf.
+#[derive(Debug)]
+pub struct LeafProof {
+    pub asset_id: Pubkey,
+    pub tree: Pubkey,
+    pub owner: Pubkey,
+    pub delegate: Pubkey,
+    pub root: [u8; 32],
+    pub data_hash: [u8; 32],
+    pub creator_hash: [u8; 32],
+    pub nonce: u64,
+    pub index: u32,
+    pub proof: Vec<Pubkey>,
+}
+
+/// Send a DAS API request and deserialize its result.
+pub fn das_request<T: DeserializeOwned>(das_url: &str, method: &str, params: Value) -> Result<T> {
+    let body = json!({
+        "jsonrpc": "2.0",
+        "id": 1,
+        "method": method,
+        "params": params,
+    });
+
+    let response: Value = reqwest::blocking::Client::new()
+        .post(das_url)
+        .json(&body)
+        .send()?
+        .json()?;
+
+    if let Some(err) = response.get("error") {
+        return Err(anyhow!("{method} failed: {err}"));
+    }
+    let result = response
+        .get("result")
+        .cloned()
+        .ok_or_else(|| anyhow!("{method} returned no result"))?;
+
+    serde_json::from_value(result).map_err(|e| anyhow!("Unexpected {method} response: {e}"))
+}
+
+fn decode_hash(value: &str) -> Result<[u8; 32]> {
+    bs58::decode(value)
+        .into_vec()
+        .ok()
+        .and_then(|bytes| bytes.try_into().ok())
+        .ok_or_else(|| anyhow!("Invalid hash: {value}"))
+}
+
+fn parse_pubkey(value: &str) -> Result<Pubkey> {
+    Pubkey::from_str(value).map_err(|_| anyhow!("Invalid pubkey: {value}"))
+}
+
+pub fn get_asset(das_url: &str, asset_id: &Pubkey) -> Result<DasAsset> {
+    das_request(das_url, "getAsset", json!({ "id": asset_id.to_string() }))
+}
+
+pub fn get_asset_proof(das_url: &str, asset_id: &Pubkey) -> Result<DasAssetProof> {
+    das_request(
+        das_url,
+        "getAssetProof",
+        json!({ "id": asset_id.to_string() }),
+    )
+}
+
+/// Fetch the asset and its proof, and check they describe a live compressed NFT.
+pub fn get_leaf_proof(das_url: &str, asset_id: &Pubkey) -> Result<LeafProof> {
+    let asset = get_asset(das_url, asset_id)?;
+    if !asset.compression.compressed {
+        return Err(anyhow!("{asset_id} is not a compressed NFT"));
+    }
+    if asset.burnt {
+        return Err(anyhow!("{asset_id} has already been burned"));
+    }
+
+    let proof = get_asset_proof(das_url, asset_id)?;
+    if proof.tree_id != asset.compression.tree {
+        return Err(anyhow!(
+            "Proof is for tree {} but {asset_id} is in tree {}",
+            proof.tree_id,
+            asset.compression.tree
+        ));
+    }
+
+    let owner = parse_pubkey(&asset.ownership.owner)?;
+    // An asset with no delegate is its own owner's delegate.
+    let delegate = match asset.ownership.delegate.as_deref() {
+        Some(delegate) => parse_pubkey(delegate)?,
+        None => owner,
+    };
+
+    Ok(LeafProof {
+        asset_id: *asset_id,
+        tree: parse_pubkey(&asset.compression.tree)?,
+        owner,
+        delegate,
+        root: decode_hash(&proof.root)?,
+        data_hash: decode_hash(&asset.compression.data_hash)?,
+        creator_hash: decode_hash(&asset.compression.creator_hash)?,
+        nonce: asset.compression.leaf_id,
+        index: u32::try_from(asset.compression.leaf_id)
+            .map_err(|_| anyhow!("Leaf index out of range: {}", asset.compression.leaf_id))?,
+        proof: proof
+            .proof
+            .iter()
+            .map(|node| parse_pubkey(node))
+            .collect::<Result<_>>()?,
+    })
+}
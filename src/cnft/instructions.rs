@@ -0,0 +1,253 @@
+use anyhow::Result;
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::{
+    hash::hash,
+    instruction::{AccountMeta, Instruction},
+    pubkey,
+    pubkey::Pubkey,
+    system_program,
+};
+
+use super::LeafProof;
+use crate::derive::{derive_edition_pda, derive_metadata_pda};
+
+pub const BUBBLEGUM_PROGRAM_ID: Pubkey = pubkey!("BGUMAp9Gq7iTEuizy4pqaxsTyUCBK68MDfK752saRPUY");
+pub const SPL_ACCOUNT_COMPRESSION_ID: Pubkey =
+    pubkey!("cmtDvXumGCrqC1Age74AVPhSRVXJMd8PJS91L8KbNCK");
+pub const SPL_NOOP_ID: Pubkey = pubkey!("noopb9bkMVfRPU8AsbpTUg8AQkHtKwMYZiFUjNRtMmV");
+
+const ASSET_PREFIX: &str = "asset";
+const COLLECTION_CPI_PREFIX: &str = "collection_cpi";
+
+#[derive(Debug, Clone, BorshSerialize, BorshDeserialize)]
+pub struct Creator {
+    pub address: Pubkey,
+    pub verified: bool,
+    pub share: u8,
+}
+
+#[derive(Debug, Clone, BorshSerialize, BorshDeserialize)]
+pub struct Collection {
+    pub verified: bool,
+    pub key: Pubkey,
+}
+
+#[derive(Debug, Clone, BorshSerialize, BorshDeserialize)]
+pub struct Uses {
+    pub use_method: u8,
+    pub remaining: u64,
+    pub total: u64,
+}
+
+/// Bubblegum's `MetadataArgs`, the data hashed into each leaf. Token standard and
+/// token program version are stored as their enum index.
+#[derive(Debug, Clone, BorshSerialize, BorshDeserialize)]
+pub struct MetadataArgs {
+    pub name: String,
+    pub symbol: String,
+    pub uri: String,
+    pub seller_fee_basis_points: u16,
+    pub primary_sale_happened: bool,
+    pub is_mutable: bool,
+    pub edition_nonce: Option<u8>,
+    pub token_standard: Option<u8>,
+    pub collection: Option<Collection>,
+    pub uses: Option<Uses>,
+    pub token_program_version: u8,
+    pub creators: Vec<Creator>,
+}
+
+/// Bubblegum's tree config account, stored at the tree authority PDA.
+#[derive(Debug, BorshDeserialize)]
+pub struct TreeConfig {
+    pub tree_creator: Pubkey,
+    pub tree_delegate: Pubkey,
+    pub total_mint_capacity: u64,
+    pub num_minted: u64,
+    pub is_public: bool,
+}
+
+impl TreeConfig {
+    pub fn from_account_data(data: &[u8]) -> Result<Self> {
+        // Skip the anchor account discriminator.
+        Ok(TreeConfig::deserialize(
+            &mut data.get(8..).unwrap_or_default(),
+        )?)
+    }
+}
+
+fn anchor_discriminator(name: &str) -> Vec<u8> {
+    hash(format!("global:{name}").as_bytes()).to_bytes()[..8].to_vec()
+}
+
+pub fn derive_tree_authority(merkle_tree: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[merkle_tree.as_ref()], &BUBBLEGUM_PROGRAM_ID).0
+}
+
+/// The asset id of the leaf minted with `nonce`, which is the tree's mint count at the time.
+pub fn derive_asset_id(merkle_tree: &Pubkey, nonce: u64) -> Pubkey {
+    Pubkey::find_program_address(
+        &[
+            ASSET_PREFIX.as_bytes(),
+            merkle_tree.as_ref(),
+            &nonce.to_le_bytes(),
+        ],
+        &BUBBLEGUM_PROGRAM_ID,
+    )
+    .0
+}
+
+pub fn derive_bubblegum_signer() -> Pubkey {
+    Pubkey::find_program_address(&[COLLECTION_CPI_PREFIX.as_bytes()], &BUBBLEGUM_PROGRAM_ID).0
+}
+
+pub fn create_tree_ix(
+    merkle_tree: &Pubkey,
+    payer: &Pubkey,
+    tree_creator: &Pubkey,
+    max_depth: u32,
+    max_buffer_size: u32,
+    public: bool,
+) -> Result<Instruction> {
+    let mut data = anchor_discriminator("create_tree");
+    data.extend(max_depth.try_to_vec()?);
+    data.extend(max_buffer_size.try_to_vec()?);
+    data.extend(Some(public).try_to_vec()?);
+
+    Ok(Instruction {
+        program_id: BUBBLEGUM_PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new(derive_tree_authority(merkle_tree), false),
+            AccountMeta::new(*merkle_tree, false),
+            AccountMeta::new(*payer, true),
+            AccountMeta::new_readonly(*tree_creator, true),
+            AccountMeta::new_readonly(SPL_NOOP_ID, false),
+            AccountMeta::new_readonly(SPL_ACCOUNT_COMPRESSION_ID, false),
+            AccountMeta::new_readonly(system_program::ID, false),
+        ],
+        data,
+    })
+}
+
+pub fn mint_v1_ix(
+    merkle_tree: &Pubkey,
+    leaf_owner: &Pubkey,
+    payer: &Pubkey,
+    tree_delegate: &Pubkey,
+    metadata: &MetadataArgs,
+) -> Result<Instruction> {
+    let mut data = anchor_discriminator("mint_v1");
+    data.extend(metadata.try_to_vec()?);
+
+    Ok(Instruction {
+        program_id: BUBBLEGUM_PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new(derive_tree_authority(merkle_tree), false),
+            AccountMeta::new_readonly(*leaf_owner, false),
+            AccountMeta::new_readonly(*leaf_owner, false),
+            AccountMeta::new(*merkle_tree, false),
+            AccountMeta::new(*payer, true),
+            AccountMeta::new_readonly(*tree_delegate, true),
+            AccountMeta::new_readonly(SPL_NOOP_ID, false),
+            AccountMeta::new_readonly(SPL_ACCOUNT_COMPRESSION_ID, false),
+            AccountMeta::new_readonly(system_program::ID, false),
+        ],
+        data,
+    })
+}
+
+/// Mint into a verified collection. The collection authority must sign, and the
+/// collection in `metadata` must be set to the collection mint.
+pub fn mint_to_collection_v1_ix(
+    merkle_tree: &Pubkey,
+    leaf_owner: &Pubkey,
+    payer: &Pubkey,
+    tree_delegate: &Pubkey,
+    collection_authority: &Pubkey,
+    collection_mint: &Pubkey,
+    metadata: &MetadataArgs,
+) -> Result<Instruction> {
+    let mut data = anchor_discriminator("mint_to_collection_v1");
+    data.extend(metadata.try_to_vec()?);
+
+    Ok(Instruction {
+        program_id: BUBBLEGUM_PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new(derive_tree_authority(merkle_tree), false),
+            AccountMeta::new_readonly(*leaf_owner, false),
+            AccountMeta::new_readonly(*leaf_owner, false),
+            AccountMeta::new(*merkle_tree, false),
+            AccountMeta::new(*payer, true),
+            AccountMeta::new_readonly(*tree_delegate, true),
+            AccountMeta::new_readonly(*collection_authority, true),
+            // No collection authority record: the update authority signs directly.
+            AccountMeta::new_readonly(BUBBLEGUM_PROGRAM_ID, false),
+            AccountMeta::new_readonly(*collection_mint, false),
+            AccountMeta::new(derive_metadata_pda(collection_mint), false),
+            AccountMeta::new_readonly(derive_edition_pda(collection_mint), false),
+            AccountMeta::new_readonly(derive_bubblegum_signer(), false),
+            AccountMeta::new_readonly(SPL_NOOP_ID, false),
+            AccountMeta::new_readonly(SPL_ACCOUNT_COMPRESSION_ID, false),
+            AccountMeta::new_readonly(mpl_token_metadata::ID, false),
+            AccountMeta::new_readonly(system_program::ID, false),
+        ],
+        data,
+    })
+}
+
+/// Accounts and data shared by instructions that modify an existing leaf. The leaf owner
+/// or delegate must sign, and the proof nodes not covered by the tree's canopy follow as
+/// remaining accounts.
+fn leaf_instruction(
+    name: &str,
+    leaf: &LeafProof,
+    signer: &Pubkey,
+    new_leaf_owner: Option<&Pubkey>,
+    proof: &[Pubkey],
+) -> Result<Instruction> {
+    let mut data = anchor_discriminator(name);
+    data.extend(leaf.root.try_to_vec()?);
+    data.extend(leaf.data_hash.try_to_vec()?);
+    data.extend(leaf.creator_hash.try_to_vec()?);
+    data.extend(leaf.nonce.try_to_vec()?);
+    data.extend(leaf.index.try_to_vec()?);
+
+    let mut accounts = vec![
+        AccountMeta::new_readonly(derive_tree_authority(&leaf.tree), false),
+        AccountMeta::new_readonly(leaf.owner, leaf.owner == *signer),
+        AccountMeta::new_readonly(leaf.delegate, leaf.owner != *signer),
+    ];
+    if let Some(new_leaf_owner) = new_leaf_owner {
+        accounts.push(AccountMeta::new_readonly(*new_leaf_owner, false));
+    }
+    accounts.extend([
+        AccountMeta::new(leaf.tree, false),
+        AccountMeta::new_readonly(SPL_NOOP_ID, false),
+        AccountMeta::new_readonly(SPL_ACCOUNT_COMPRESSION_ID, false),
+        AccountMeta::new_readonly(system_program::ID, false),
+    ]);
+    accounts.extend(
+        proof
+            .iter()
+            .map(|node| AccountMeta::new_readonly(*node, false)),
+    );
+
+    Ok(Instruction {
+        program_id: BUBBLEGUM_PROGRAM_ID,
+        accounts,
+        data,
+    })
+}
+
+pub fn transfer_ix(
+    leaf: &LeafProof,
+    signer: &Pubkey,
+    new_leaf_owner: &Pubkey,
+    proof: &[Pubkey],
+) -> Result<Instruction> {
+    leaf_instruction("transfer", leaf, signer, Some(new_leaf_owner), proof)
+}
+
+pub fn burn_ix(leaf: &LeafProof, signer: &Pubkey, proof: &[Pubkey]) -> Result<Instruction> {
+    leaf_instruction("burn", leaf, signer, None, proof)
+}
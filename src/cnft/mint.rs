@@ -0,0 +1,288 @@
+use std::{
+    fs::File,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+use anyhow::{anyhow, Result};
+use metaboss_lib::data::{NftData, Priority};
+use serde::Serialize;
+use serde_json::Value;
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::{pubkey::Pubkey, signer::Signer};
+
+use super::{
+    create_tree, derive_asset_id, fetch_tree_config, mint_to_collection_v1_ix, mint_v1_ix,
+    Collection, Creator, MetadataArgs, TreeParams,
+};
+use crate::workspace::output_path;
+use crate::{
+    checkpoint,
+    files::read_json,
+    parse::{parse_keypair, parse_solana_config},
+    pipeline::Pipeline,
+    spinner::create_progress_bar,
+};
+
+/// Name the outcome of each cNFT is recorded under.
+const MINT_CNFT_COMMAND: &str = "mint-cnft";
+
+pub struct MintCnftArgs {
+    pub client: RpcClient,
+    pub keypair: Option<String>,
+    pub receiver: Option<String>,
+    pub receiver_list: Option<PathBuf>,
+    pub nft_data: PathBuf,
+    pub tree: Option<Pubkey>,
+    pub tree_params: TreeParams,
+    pub public: bool,
+    pub collection: Option<Pubkey>,
+    pub immutable: bool,
+    pub primary_sale_happened: bool,
+    pub output: PathBuf,
+    pub priority: Priority,
+}
+
+#[derive(Debug, Serialize)]
+pub struct MintedCnft {
+    pub asset_id: String,
+    pub receiver: String,
+    pub name: String,
+    pub signature: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct FailedCnft {
+    pub receiver: String,
+    pub name: String,
+    pub error: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct MintCnftResults {
+    pub tree: String,
+    pub minted: Vec<MintedCnft>,
+    pub failed: Vec<FailedCnft>,
+}
+
+fn read_nft_data(path: &Path) -> Result<Vec<NftData>> {
+    let value: Value = read_json(path)?;
+    let items = match value {
+        Value::Array(items) => items,
+        value => vec![value],
+    };
+    items
+        .into_iter()
+        .map(|item| serde_json::from_value(item).map_err(Into::into))
+        .collect()
+}
+
+/// Pair each NFT with a receiver: one NFT minted to every receiver in the list,
+/// or each NFT to the receiver at the same index.
+fn pair_receivers(
+    nfts: Vec<MetadataArgs>,
+    receivers: Vec<Pubkey>,
+    default_receiver: Pubkey,
+) -> Result<Vec<(Pubkey, MetadataArgs)>> {
+    if receivers.is_empty() {
+        return Ok(nfts
+            .into_iter()
+            .map(|nft| (default_receiver, nft))
+            .collect());
+    }
+
+    match nfts.len() {
+        1 => Ok(receivers
+            .into_iter()
+            .map(|r| (r, nfts[0].clone()))
+            .collect()),
+        n if n == receivers.len() => Ok(receivers.into_iter().zip(nfts).collect()),
+        n => Err(anyhow!(
+            "{n} NFTs can't be paired with {} receivers: use one NFT or one per receiver",
+            receivers.len()
+        )),
+    }
+}
+
+fn metadata_args(
+    nft: &NftData,
+    signer: &Pubkey,
+    collection: Option<Pubkey>,
+    immutable: bool,
+    primary_sale_happened: bool,
+) -> Result<MetadataArgs> {
+    let creators = nft
+        .creators
+        .as_ref()
+        .map(|creators| {
+            creators
+                .iter()
+                .map(|c| {
+                    let address = Pubkey::from_str(&c.address)
+                        .map_err(|_| anyhow!("Invalid creator address: {}", c.address))?;
+                    // Only the signer can be verified at mint time.
+                    Ok(Creator {
+                        address,
+                        verified: address == *signer,
+                        share: c.share,
+                    })
+                })
+                .collect::<Result<Vec<_>>>()
+        })
+        .transpose()?
+        .unwrap_or_default();
+
+    Ok(MetadataArgs {
+        name: nft.name.clone(),
+        symbol: nft.symbol.clone(),
+        uri: nft.uri.clone(),
+        seller_fee_basis_points: nft.seller_fee_basis_points,
+        primary_sale_happened,
+        is_mutable: !immutable,
+        edition_nonce: None,
+        // NonFungible
+        token_standard: Some(0),
+        // Bubblegum verifies the collection as part of minting to it.
+        collection: collection.map(|key| Collection {
+            verified: false,
+            key,
+        }),
+        uses: None,
+        // Original token program
+        token_program_version: 0,
+        creators,
+    })
+}
+
+pub fn mint_cnft(args: MintCnftArgs) -> Result<()> {
+    let keypair = parse_keypair(args.keypair, parse_solana_config());
+
+    let receiver = match args.receiver {
+        Some(r) => Pubkey::from_str(&r).map_err(|_| anyhow!("Invalid receiver: {r}"))?,
+        None => keypair.pubkey(),
+    };
+    let receivers: Vec<Pubkey> = match &args.receiver_list {
+        Some(path) => {
+            let list: Vec<String> = read_json(path)?;
+            list.iter()
+                .map(|r| Pubkey::from_str(r).map_err(|_| anyhow!("Invalid receiver: {r}")))
+                .collect::<Result<_>>()?
+        }
+        None => vec![],
+    };
+
+    let nfts = read_nft_data(&args.nft_data)?
+        .iter()
+        .map(|nft| {
+            metadata_args(
+                nft,
+                &keypair.pubkey(),
+                args.collection,
+                args.immutable,
+                args.primary_sale_happened,
+            )
+        })
+        .collect::<Result<Vec<_>>>()?;
+    let mints = pair_receivers(nfts, receivers, receiver)?;
+
+    let tree = match args.tree {
+        Some(tree) => tree,
+        None => {
+            // Check the tree is big enough before paying for it.
+            args.tree_params.validate()?;
+            if (mints.len() as u64) > args.tree_params.capacity() {
+                return Err(anyhow!(
+                    "A tree of depth {} holds {} cNFTs, too few for {} mints",
+                    args.tree_params.max_depth,
+                    args.tree_params.capacity(),
+                    mints.len()
+                ));
+            }
+            create_tree(&args.client, &keypair, args.tree_params, args.public)?
+        }
+    };
+
+    let tree_config = fetch_tree_config(&args.client, &tree)?;
+    let remaining = tree_config.total_mint_capacity - tree_config.num_minted;
+    if (mints.len() as u64) > remaining {
+        return Err(anyhow!(
+            "Tree {tree} has room for {remaining} more cNFTs, too few for {} mints",
+            mints.len()
+        ));
+    }
+
+    // Mints are sent one at a time, so each leaf's nonce is the count before it.
+    let mut nonce = tree_config.num_minted;
+    let mut results = MintCnftResults {
+        tree: tree.to_string(),
+        minted: vec![],
+        failed: vec![],
+    };
+
+    let pb = create_progress_bar("Minting cNFTs...", mints.len() as u64);
+    for (i, (receiver, md)) in mints.iter().enumerate() {
+        // Items are recorded by their position in the NFT data, since names can repeat.
+        let item = i.to_string();
+        let ix = match args.collection {
+            Some(collection) => mint_to_collection_v1_ix(
+                &tree,
+                receiver,
+                &keypair.pubkey(),
+                &keypair.pubkey(),
+                &keypair.pubkey(),
+                &collection,
+                md,
+            ),
+            None => mint_v1_ix(&tree, receiver, &keypair.pubkey(), &keypair.pubkey(), md),
+        };
+
+        let result = ix.and_then(|ix| {
+            Pipeline::with_priority(args.priority.clone())
+                .with_active_lookup_table()?
+                .with_active_nonce()?
+                .run(&args.client, &[&keypair], vec![ix])
+        });
+
+        match result {
+            Ok(sig) => {
+                let asset_id = derive_asset_id(&tree, nonce).to_string();
+                checkpoint::record_minted(MINT_CNFT_COMMAND, &item, &asset_id);
+                results.minted.push(MintedCnft {
+                    asset_id,
+                    receiver: receiver.to_string(),
+                    name: md.name.clone(),
+                    signature: sig.to_string(),
+                });
+                nonce += 1;
+            }
+            Err(e) => {
+                // A failed send may still have landed, so re-read the count.
+                if let Ok(config) = fetch_tree_config(&args.client, &tree) {
+                    nonce = config.num_minted;
+                }
+                checkpoint::record_failed(MINT_CNFT_COMMAND, &item, &e);
+                results.failed.push(FailedCnft {
+                    receiver: receiver.to_string(),
+                    name: md.name.clone(),
+                    error: e.to_string(),
+                });
+            }
+        }
+        pb.inc(1);
+    }
+    pb.finish_and_clear();
+
+    let output = output_path(&args.output);
+    std::fs::create_dir_all(&output)?;
+    let output_path = output.join("mb_cnft_mints.json");
+    serde_json::to_writer_pretty(File::create(&output_path)?, &results)?;
+
+    println!(
+        "Minted {} of {} cNFTs into tree {tree}",
+        results.minted.len(),
+        mints.len()
+    );
+    println!("Results written to {}", output_path.display());
+
+    Ok(())
+}
@@ -0,0 +1,11 @@
+mod das;
+mod instructions;
+mod mint;
+mod modify;
+mod tree;
+
+pub use das::*;
+pub use instructions::*;
+pub use mint::*;
+pub use modify::*;
+pub use tree::*;
@@ -0,0 +1,91 @@
+use anyhow::{anyhow, Result};
+use metaboss_lib::data::Priority;
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::{pubkey::Pubkey, signature::Keypair, signer::Signer};
+
+use super::{burn_ix, fetch_tree_params, get_leaf_proof, transfer_ix, LeafProof};
+use crate::{
+    parse::{parse_keypair, parse_solana_config},
+    pipeline::Pipeline,
+};
+
+pub struct TransferCnftArgs {
+    pub client: RpcClient,
+    pub keypair: Option<String>,
+    pub asset_id: Pubkey,
+    pub receiver: Pubkey,
+    pub das_url: Option<String>,
+    pub priority: Priority,
+}
+
+pub struct BurnCnftArgs {
+    pub client: RpcClient,
+    pub keypair: Option<String>,
+    pub asset_id: Pubkey,
+    pub das_url: Option<String>,
+    pub priority: Priority,
+}
+
+/// Fetch the leaf proof, check the keypair can sign for the leaf and trim the proof
+/// nodes the tree's canopy already stores.
+fn prepare_leaf(
+    client: &RpcClient,
+    das_url: Option<String>,
+    keypair: &Keypair,
+    asset_id: &Pubkey,
+) -> Result<(LeafProof, Vec<Pubkey>)> {
+    // Most DAS providers serve the API from the same endpoint as RPC.
+    let das_url = das_url.unwrap_or_else(|| client.url());
+    let leaf = get_leaf_proof(&das_url, asset_id)?;
+
+    if keypair.pubkey() != leaf.owner && keypair.pubkey() != leaf.delegate {
+        return Err(anyhow!(
+            "{} is neither the owner ({}) nor the delegate of {asset_id}",
+            keypair.pubkey(),
+            leaf.owner
+        ));
+    }
+
+    let params = fetch_tree_params(client, &leaf.tree)?;
+    let proof_len = (params.max_depth - params.canopy_depth) as usize;
+    let proof = leaf.proof.iter().take(proof_len).copied().collect();
+
+    Ok((leaf, proof))
+}
+
+pub fn transfer_cnft(args: TransferCnftArgs) -> Result<()> {
+    let keypair = parse_keypair(args.keypair, parse_solana_config());
+    let (leaf, proof) = prepare_leaf(&args.client, args.das_url, &keypair, &args.asset_id)?;
+
+    if leaf.owner == args.receiver {
+        return Err(anyhow!("{} already owns {}", args.receiver, args.asset_id));
+    }
+
+    let ix = transfer_ix(&leaf, &keypair.pubkey(), &args.receiver, &proof)?;
+    let sig = Pipeline::with_priority(args.priority)
+        .with_active_lookup_table()?
+        .with_active_nonce()?
+        .run(&args.client, &[&keypair], vec![ix])?;
+
+    println!(
+        "Transferred {} to {} in tx {sig}",
+        args.asset_id, args.receiver
+    );
+
+    Ok(())
+}
+
+pub fn burn_cnft(args: BurnCnftArgs) -> Result<()> {
+    let keypair = parse_keypair(args.keypair, parse_solana_config());
+    let (leaf, proof) = prepare_leaf(&args.client, args.das_url, &keypair, &args.asset_id)?;
+
+    let ix = burn_ix(&leaf, &keypair.pubkey(), &proof)?;
+    let sig = Pipeline::with_priority(args.priority)
+        .with_active_lookup_table()?
+        .with_active_nonce()?
+        .run(&args.client, &[&keypair], vec![ix])?;
+
+    println!("Burned {} in tx {sig}", args.asset_id);
+
+    Ok(())
+}
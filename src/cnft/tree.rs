@@ -0,0 +1,190 @@
+use anyhow::{anyhow, Result};
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::{
+    native_token::lamports_to_sol, pubkey::Pubkey, signature::Keypair, signer::Signer,
+    system_instruction::create_account,
+};
+
+use super::{create_tree_ix, derive_tree_authority, TreeConfig, SPL_ACCOUNT_COMPRESSION_ID};
+use crate::pipeline::Pipeline;
+
+// Depth and buffer size pairs supported by spl-account-compression.
+const VALID_DEPTH_SIZE_PAIRS: &[(u32, u32)] = &[
+    (3, 8),
+    (5, 8),
+    (6, 16),
+    (7, 16),
+    (8, 16),
+    (9, 16),
+    (10, 32),
+    (11, 32),
+    (12, 32),
+    (13, 32),
+    (14, 64),
+    (14, 256),
+    (14, 1024),
+    (14, 2048),
+    (15, 64),
+    (16, 64),
+    (17, 64),
+    (18, 64),
+    (19, 64),
+    (20, 64),
+    (20, 256),
+    (20, 1024),
+    (20, 2048),
+    (24, 64),
+    (24, 256),
+    (24, 512),
+    (24, 1024),
+    (24, 2048),
+    (26, 512),
+    (26, 1024),
+    (26, 2048),
+    (30, 512),
+    (30, 1024),
+    (30, 2048),
+];
+
+// Account type, version and the v1 header.
+const TREE_HEADER_SIZE: usize = 2 + 54;
+
+#[derive(Debug, Clone, Copy)]
+pub struct TreeParams {
+    pub max_depth: u32,
+    pub max_buffer_size: u32,
+    pub canopy_depth: u32,
+}
+
+impl TreeParams {
+    pub fn validate(&self) -> Result<()> {
+        if !VALID_DEPTH_SIZE_PAIRS.contains(&(self.max_depth, self.max_buffer_size)) {
+            let valid = VALID_DEPTH_SIZE_PAIRS
+                .iter()
+                .filter(|(depth, _)| *depth == self.max_depth)
+                .map(|(_, size)| size.to_string())
+                .collect::<Vec<_>>();
+            return Err(if valid.is_empty() {
+                anyhow!("Unsupported max depth: {}", self.max_depth)
+            } else {
+                anyhow!(
+                    "Unsupported max buffer size {} for depth {}, expected one of {}",
+                    self.max_buffer_size,
+                    self.max_depth,
+                    valid.join(", ")
+                )
+            });
+        }
+        if self.canopy_depth >= self.max_depth {
+            return Err(anyhow!(
+                "Canopy depth must be less than the max depth of {}",
+                self.max_depth
+            ));
+        }
+        Ok(())
+    }
+
+    /// How many leaves the tree can hold.
+    pub fn capacity(&self) -> u64 {
+        1 << self.max_depth
+    }
+
+    /// Read a tree's parameters from its account, working out the canopy depth from
+    /// the space left after the tree itself.
+    pub fn from_account_data(data: &[u8]) -> Result<Self> {
+        let field = |offset: usize| {
+            data.get(offset..offset + 4)
+                .map(|b| u32::from_le_bytes(b.try_into().unwrap()))
+                .ok_or_else(|| anyhow!("Tree account is too small"))
+        };
+        let mut params = TreeParams {
+            max_buffer_size: field(2)?,
+            max_depth: field(6)?,
+            canopy_depth: 0,
+        };
+
+        let canopy_bytes = data
+            .len()
+            .checked_sub(params.account_size())
+            .ok_or_else(|| anyhow!("Tree account is too small"))?;
+        // A canopy of depth d stores 2^(d+1) - 2 nodes.
+        let canopy_nodes = canopy_bytes / 32;
+        params.canopy_depth = (canopy_nodes + 2).ilog2().saturating_sub(1);
+
+        Ok(params)
+    }
+
+    /// Size of the tree account, matching `getConcurrentMerkleTreeAccountSize`.
+    pub fn account_size(&self) -> usize {
+        let depth = self.max_depth as usize;
+        let path = 32 * depth;
+        // Root, path, index and padding.
+        let changelog = 32 + path + 4 + 4;
+        // Proof, leaf, index and padding.
+        let rightmost_proof = path + 32 + 4 + 4;
+        // Sequence number, active index and buffer size.
+        let tree = 8 + 8 + 8 + changelog * self.max_buffer_size as usize + rightmost_proof;
+        let canopy = ((1 << (self.canopy_depth + 1)) - 2) * 32;
+
+        TREE_HEADER_SIZE + tree + canopy
+    }
+}
+
+pub fn fetch_tree_config(client: &RpcClient, tree: &Pubkey) -> Result<TreeConfig> {
+    let data = client
+        .get_account_data(&derive_tree_authority(tree))
+        .map_err(|_| anyhow!("{tree} is not a Bubblegum tree"))?;
+    TreeConfig::from_account_data(&data)
+}
+
+pub fn fetch_tree_params(client: &RpcClient, tree: &Pubkey) -> Result<TreeParams> {
+    let data = client
+        .get_account_data(tree)
+        .map_err(|_| anyhow!("Tree {tree} not found"))?;
+    TreeParams::from_account_data(&data)
+}
+
+/// Create a Bubblegum tree owned by `keypair`, returning its address.
+pub fn create_tree(
+    client: &RpcClient,
+    keypair: &Keypair,
+    params: TreeParams,
+    public: bool,
+) -> Result<Pubkey> {
+    params.validate()?;
+
+    let tree = Keypair::new();
+    let size = params.account_size();
+    let rent = client.get_minimum_balance_for_rent_exemption(size)?;
+
+    println!(
+        "Creating tree for {} cNFTs, {size} bytes, {} SOL rent",
+        params.capacity(),
+        lamports_to_sol(rent)
+    );
+
+    let ixs = vec![
+        create_account(
+            &keypair.pubkey(),
+            &tree.pubkey(),
+            rent,
+            size as u64,
+            &SPL_ACCOUNT_COMPRESSION_ID,
+        ),
+        create_tree_ix(
+            &tree.pubkey(),
+            &keypair.pubkey(),
+            &keypair.pubkey(),
+            params.max_depth,
+            params.max_buffer_size,
+            public,
+        )?,
+    ];
+
+    let sig = Pipeline::standard()
+        .with_active_nonce()?
+        .run(client, &[keypair, &tree], ixs)?;
+    println!("Created tree {} in tx {sig}", tree.pubkey());
+
+    Ok(tree.pubkey())
+}
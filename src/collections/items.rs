@@ -1,6 +1,8 @@
 use super::*;
 use crate::collections::data::*;
 use crate::derive::derive_metadata_pda;
+use crate::files::read_json;
+use crate::limiter::concurrency_or;
 use crate::spinner::create_alt_spinner;
 use crate::theindexio::THE_INDEX_MAINNET;
 use borsh::BorshDeserialize;
@@ -60,8 +62,7 @@ pub async fn check_collection_items(
     mint_list_path: String,
     _debug: bool,
 ) -> AnyResult<()> {
-    let f = File::open(mint_list_path)?;
-    let mut mint_list: Vec<String> = serde_json::from_reader(f)?;
+    let mut mint_list: Vec<String> = read_json(mint_list_path)?;
     let mint_list_length = mint_list.len();
 
     let mut collections: HashMap<String, Vec<String>> = HashMap::new();
@@ -70,8 +71,10 @@ pub async fn check_collection_items(
 
     let client = Arc::new(async_client);
 
+    let parallel_limit = concurrency_or(PARALLEL_LIMIT);
+
     let spinner = create_alt_spinner("Sending network requests and awaiting results...");
-    for mint in mint_list.drain(0..cmp::min(mint_list.len(), PARALLEL_LIMIT)) {
+    for mint in mint_list.drain(0..cmp::min(mint_list.len(), parallel_limit)) {
         let client = client.clone();
         handles.push(tokio::spawn(async move {
             get_mint_collection(&client, mint.to_string()).await
@@ -111,10 +114,10 @@ pub async fn check_collection_items(
 
         if !mint_list.is_empty() {
             // if we are half way through, let spawn more transactions
-            if (PARALLEL_LIMIT - handles.len()) > (PARALLEL_LIMIT / 2) {
+            if (parallel_limit - handles.len()) > (parallel_limit / 2) {
                 // syncs cache (checkpoint)
 
-                for mint in mint_list.drain(0..cmp::min(mint_list.len(), PARALLEL_LIMIT)) {
+                for mint in mint_list.drain(0..cmp::min(mint_list.len(), parallel_limit)) {
                     let client = client.clone();
                     handles.push(tokio::spawn(async move {
                         get_mint_collection(&client, mint.to_string()).await
@@ -150,7 +153,7 @@ pub async fn check_collection_items(
     Ok(())
 }
 
-async fn get_mint_collection<'a>(
+async fn get_mint_collection(
     client: &AsyncRpcClient,
     mint: String,
 ) -> AnyResult<(String, Option<MdCollection>)> {
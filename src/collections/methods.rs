@@ -1,9 +1,13 @@
 use super::*;
 
+use crate::derive::derive_collection_authority_record;
+use crate::offline::reject_signer_derivation;
 use crate::parse::parse_keypair;
+use crate::squads::{active_multisig, propose_with_vault_authority};
 use crate::{parse::parse_solana_config, utils::send_and_confirm_transaction};
 use metaboss_lib::{
     data::Priority,
+    decode::decode_metadata_from_mint,
     delegate::{delegate_asset, DelegateAssetArgs},
     derive::derive_metadata_pda,
     revoke::{revoke_asset, RevokeAssetArgs},
@@ -13,9 +17,34 @@ use metaboss_lib::{
 };
 use mpl_token_metadata::types::SetCollectionSizeArgs;
 use mpl_token_metadata::{
-    instructions::SetCollectionSizeBuilder,
-    types::{CollectionToggle, DelegateArgs, RevokeArgs},
+    accounts::{CollectionAuthorityRecord, MetadataDelegateRecord},
+    hooked::MetadataDelegateRoleSeed,
+    instructions::{
+        ApproveCollectionAuthorityBuilder, RevokeCollectionAuthorityBuilder,
+        SetCollectionSizeBuilder,
+    },
+    types::{CollectionToggle, DelegateArgs, Key, MetadataDelegateRole, RevokeArgs},
 };
+use solana_account_decoder::UiAccountEncoding;
+use solana_client::{
+    rpc_config::{RpcAccountInfoConfig, RpcProgramAccountsConfig},
+    rpc_filter::{Memcmp, MemcmpEncodedBytes, RpcFilterType},
+};
+
+// Metadata delegate records start with the key and bump, then the mint.
+const DELEGATE_RECORD_MINT_OFFSET: usize = 2;
+
+// Roles a metadata delegate record can be for, to find which one a record was derived with.
+const METADATA_DELEGATE_ROLES: [MetadataDelegateRole; 8] = [
+    MetadataDelegateRole::Collection,
+    MetadataDelegateRole::CollectionItem,
+    MetadataDelegateRole::Data,
+    MetadataDelegateRole::DataItem,
+    MetadataDelegateRole::ProgrammableConfig,
+    MetadataDelegateRole::ProgrammableConfigItem,
+    MetadataDelegateRole::AuthorityItem,
+    MetadataDelegateRole::Use,
+];
 
 pub const OPEN_FILES_LIMIT: usize = 1024;
 
@@ -29,6 +58,10 @@ pub fn set_and_verify_nft_collection(
     let solana_opts = parse_solana_config();
     let keypair = parse_keypair(keypair_path, solana_opts);
 
+    if is_delegate_present {
+        reject_signer_derivation("Verifying as a collection delegate")?;
+    }
+
     let collection_pubkey = Pubkey::from_str(&collection_mint)?;
 
     let mut instructions = vec![];
@@ -81,6 +114,10 @@ pub fn unverify_nft_collection(
     let solana_opts = parse_solana_config();
     let keypair = parse_keypair(keypair_path, solana_opts);
 
+    if is_delegate_present {
+        reject_signer_derivation("Unverifying as a collection delegate")?;
+    }
+
     let unverify_args = UnverifyCollectionArgs::V1 {
         authority: &keypair,
         mint: nft_mint,
@@ -105,6 +142,27 @@ pub fn verify_nft_collection(
     let solana_opts = parse_solana_config();
     let keypair = parse_keypair(keypair_path, solana_opts);
 
+    if is_delegate_present {
+        reject_signer_derivation("Verifying as a collection delegate")?;
+    }
+
+    if let Some(target) = active_multisig()? {
+        if is_delegate_present {
+            return Err(anyhow!(
+                "Verifying as a collection delegate is not supported with --multisig"
+            ));
+        }
+        return propose_with_vault_authority(&client, keypair, &target, |authority| {
+            let verify_args = VerifyCollectionArgs::V1 {
+                authority,
+                mint: nft_mint,
+                collection_mint,
+                is_delegate: false,
+            };
+            Ok(vec![verify_collection_ix(&client, verify_args)?])
+        });
+    }
+
     let verify_args = VerifyCollectionArgs::V1 {
         authority: &keypair,
         mint: nft_mint,
@@ -124,12 +182,32 @@ pub fn approve_delegate(
     keypair_path: Option<String>,
     collection_mint: String,
     delegate_authority: String,
+    legacy: bool,
 ) -> AnyResult<()> {
     let solana_opts = parse_solana_config();
     let keypair = parse_keypair(keypair_path, solana_opts);
 
     let delegate_pubkey = Pubkey::from_str(&delegate_authority)?;
 
+    if legacy {
+        let mint_pubkey = Pubkey::from_str(&collection_mint)?;
+        let (record, _) = derive_collection_authority_record(&mint_pubkey, &delegate_pubkey);
+        let ix = ApproveCollectionAuthorityBuilder::new()
+            .collection_authority_record(record)
+            .new_collection_authority(delegate_pubkey)
+            .update_authority(keypair.pubkey())
+            .payer(keypair.pubkey())
+            .metadata(derive_metadata_pda(&mint_pubkey))
+            .mint(mint_pubkey)
+            .instruction();
+        send_and_confirm_transaction(&client, keypair, &[ix])?;
+        println!("Collection authority record: {record}");
+        return Ok(());
+    }
+
+    // The delegate record is derived from the update authority.
+    reject_signer_derivation("Approving a collection delegate")?;
+
     let delegate_args = DelegateAssetArgs::V1 {
         payer: None,
         authority: &keypair,
@@ -153,12 +231,30 @@ pub fn revoke_delegate(
     keypair_path: Option<String>,
     collection_mint: String,
     delegate_authority: String,
+    legacy: bool,
 ) -> AnyResult<()> {
     let solana_opts = parse_solana_config();
     let keypair = parse_keypair(keypair_path, solana_opts);
 
     let delegate_pubkey = Pubkey::from_str(&delegate_authority)?;
 
+    if legacy {
+        let mint_pubkey = Pubkey::from_str(&collection_mint)?;
+        let (record, _) = derive_collection_authority_record(&mint_pubkey, &delegate_pubkey);
+        let ix = RevokeCollectionAuthorityBuilder::new()
+            .collection_authority_record(record)
+            .delegate_authority(delegate_pubkey)
+            .revoke_authority(keypair.pubkey())
+            .metadata(derive_metadata_pda(&mint_pubkey))
+            .mint(mint_pubkey)
+            .instruction();
+        send_and_confirm_transaction(&client, keypair, &[ix])?;
+        return Ok(());
+    }
+
+    // The delegate record is derived from the update authority.
+    reject_signer_derivation("Revoking a collection delegate")?;
+
     let revoke_args = RevokeAssetArgs::V1 {
         payer: None,
         authority: &keypair,
@@ -175,6 +271,96 @@ pub fn revoke_delegate(
     Ok(())
 }
 
+/// List the metadata delegates of a collection and the role each was approved for, and check
+/// the given addresses for legacy collection authority records.
+///
+/// Legacy records don't store the mint, so they can't be found without the delegate address.
+pub fn list_authorities(
+    client: RpcClient,
+    collection_mint: String,
+    check_legacy: Vec<String>,
+) -> AnyResult<()> {
+    let mint_pubkey = Pubkey::from_str(&collection_mint)?;
+    let md = decode_metadata_from_mint(&client, collection_mint.clone())?;
+    println!("Update authority: {}", md.update_authority);
+
+    #[allow(deprecated)]
+    let filters = vec![
+        RpcFilterType::Memcmp(Memcmp {
+            offset: 0,
+            bytes: MemcmpEncodedBytes::Bytes(vec![Key::MetadataDelegate as u8]),
+            encoding: None,
+        }),
+        RpcFilterType::Memcmp(Memcmp {
+            offset: DELEGATE_RECORD_MINT_OFFSET,
+            bytes: MemcmpEncodedBytes::Bytes(mint_pubkey.to_bytes().to_vec()),
+            encoding: None,
+        }),
+    ];
+    let config = RpcProgramAccountsConfig {
+        filters: Some(filters),
+        account_config: RpcAccountInfoConfig {
+            encoding: Some(UiAccountEncoding::Base64),
+            ..Default::default()
+        },
+        with_context: None,
+    };
+    let accounts = client.get_program_accounts_with_config(&metadata_program_id, config)?;
+
+    println!("Metadata delegates: {}", accounts.len());
+    for (address, account) in accounts {
+        let record = match <MetadataDelegateRecord as BorshDeserialize>::deserialize(
+            &mut account.data.as_slice(),
+        ) {
+            Ok(record) => record,
+            Err(e) => {
+                println!("  {address}: failed to decode: {e}");
+                continue;
+            }
+        };
+        let role = METADATA_DELEGATE_ROLES.into_iter().find(|role| {
+            MetadataDelegateRecord::find_pda(
+                &mint_pubkey,
+                MetadataDelegateRoleSeed::from(role.clone()),
+                &record.update_authority,
+                &record.delegate,
+            )
+            .0 == address
+        });
+        let role = role.map_or("unknown".to_string(), |r| format!("{r:?}"));
+        // Records approved by a previous update authority no longer grant anything.
+        let stale = if record.update_authority != md.update_authority {
+            format!(
+                " (approved by previous update authority {})",
+                record.update_authority
+            )
+        } else {
+            String::new()
+        };
+        println!("  {} {role} record {address}{stale}", record.delegate);
+    }
+
+    if !check_legacy.is_empty() {
+        println!("Legacy collection authority records:");
+    }
+    for delegate in check_legacy {
+        let delegate_pubkey = Pubkey::from_str(&delegate)?;
+        let (record, _) = derive_collection_authority_record(&mint_pubkey, &delegate_pubkey);
+        let account = client
+            .get_account_with_commitment(&record, client.commitment())?
+            .value;
+        match account.map(|a| {
+            <CollectionAuthorityRecord as BorshDeserialize>::deserialize(&mut a.data.as_slice())
+        }) {
+            Some(Ok(_)) => println!("  {delegate} record {record}"),
+            Some(Err(e)) => println!("  {delegate} record {record} failed to decode: {e}"),
+            None => println!("  {delegate} has no record"),
+        }
+    }
+
+    Ok(())
+}
+
 pub fn set_size(
     client: RpcClient,
     keypair_path: Option<String>,
@@ -187,6 +373,15 @@ pub fn set_size(
     let collection_mint_pubkey = Pubkey::from_str(&collection_mint)?;
     let collection_md_pubkey = derive_metadata_pda(&collection_mint_pubkey);
 
+    // Token Metadata only sets the size of unsized collections, the sized ones keep count
+    // themselves as items are verified.
+    let collection_md = decode_metadata_from_mint(&client, collection_mint.clone())?;
+    if let Some(details) = collection_md.collection_details {
+        return Err(anyhow!(
+            "Collection {collection_mint} is already sized ({details:?}), set-size only works on unsized collections"
+        ));
+    }
+
     let set_collection_size_ix = SetCollectionSizeBuilder::new()
         .collection_metadata(collection_md_pubkey)
         .collection_authority(keypair.pubkey())
@@ -1,10 +1,19 @@
 use super::*;
+use crate::workspace::output_path;
 
+use crate::changelog::Changelog;
+use crate::checkpoint;
 use crate::constants::NANO_SECONDS_IN_SECOND;
-use crate::limiter::create_rate_limiter_with_capacity;
+use crate::files::read_json;
+use crate::limiter::{
+    create_concurrency_limiter, create_rate_limiter_with_capacity, rate_limit_or,
+};
+use crate::pack::{pack_instruction_groups, send_packed, InstructionGroup};
+use crate::pipeline::Pipeline;
 use crate::spinner::create_progress_bar;
 use crate::{derive::derive_metadata_pda, errors::MigrateError, parse::parse_solana_config};
 use crate::{parse::parse_keypair, snapshot::get_mint_accounts};
+use log::warn;
 use metaboss_lib::{
     data::Priority,
     unverify::{unverify_collection_ix, UnverifyCollectionArgs},
@@ -13,9 +22,9 @@ use metaboss_lib::{
 };
 use mpl_token_metadata::types::CollectionToggle;
 use solana_sdk::{
+    instruction::Instruction,
     signature::{Keypair, Signature},
     signer::Signer,
-    transaction::Transaction,
 };
 use std::ops::{Deref, DerefMut};
 
@@ -30,6 +39,7 @@ pub struct MigrateArgs {
     pub retries: u8,
     pub rate_limit: usize,
     pub output_file: Option<String>,
+    pub pack: bool,
 }
 
 #[derive(Debug, Deserialize, Serialize)]
@@ -89,6 +99,12 @@ pub struct CacheItem {
     pub error: Option<String>,
 }
 
+/// Name the outcome of each mint is recorded under.
+const MIGRATE_COMMAND: &str = "collections-migrate";
+
+/// Estimate of the compute units to unverify, set and verify one item's collection.
+const SET_AND_VERIFY_COMPUTE_UNITS: u32 = 150_000;
+
 async fn set_and_verify(
     client: Arc<RpcClient>,
     authority_keypair: Arc<Keypair>,
@@ -96,6 +112,31 @@ async fn set_and_verify(
     collection_mint: String,
     is_delegate: bool,
 ) -> Result<Signature, MigrateError> {
+    let instructions = set_and_verify_ixs(
+        &client,
+        &authority_keypair,
+        &nft_mint,
+        collection_mint,
+        is_delegate,
+    )?;
+
+    // Update and verify together can overflow a legacy transaction, so honor --lut.
+    let sig = Pipeline::standard()
+        .with_active_lookup_table()
+        .and_then(|pipeline| pipeline.run(&client, &[&*authority_keypair], instructions))
+        .map_err(|e| MigrateError::MigrationFailed(nft_mint.clone(), e.to_string()))?;
+
+    Ok(sig)
+}
+
+fn set_and_verify_ixs(
+    client: &RpcClient,
+    authority_keypair: &Keypair,
+    nft_mint: &str,
+    collection_mint: String,
+    is_delegate: bool,
+) -> Result<Vec<Instruction>, MigrateError> {
+    let nft_mint = nft_mint.to_string();
     let nft_metadata_pubkey = derive_metadata_pda(
         &Pubkey::from_str(&nft_mint).unwrap_or_else(|_| panic!("invalid pubkey: {nft_mint:?}")),
     );
@@ -116,14 +157,14 @@ async fn set_and_verify(
             let current_collection_mint = current_collection.key;
 
             let unverify_args = UnverifyCollectionArgs::V1 {
-                authority: &authority_keypair,
+                authority: authority_keypair,
                 mint: nft_mint.clone(),
                 collection_mint: current_collection_mint,
                 is_delegate,
             };
 
             // This instruction handles both the case where the collection NFT exists and the case where it doesn't.
-            let ix = unverify_collection_ix(&client, unverify_args)
+            let ix = unverify_collection_ix(client, unverify_args)
                 .map_err(|e| MigrateError::MigrationFailed(nft_mint.clone(), e.to_string()))?;
             instructions.push(ix);
         }
@@ -140,9 +181,9 @@ async fn set_and_verify(
     };
 
     let update_ix = update_asset_ix(
-        &client,
+        client,
         UpdateAssetArgs::V1 {
-            authority: &authority_keypair,
+            authority: authority_keypair,
             mint: nft_mint.clone(),
             payer: None,
             token: None::<String>,
@@ -157,34 +198,67 @@ async fn set_and_verify(
 
     // Add verify instruction to verify the collection.
     let verify_args = VerifyCollectionArgs::V1 {
-        authority: &authority_keypair,
+        authority: authority_keypair,
         mint: nft_mint.clone(),
         collection_mint,
         is_delegate,
     };
 
     // This instruction handles both the case where the collection NFT exists and the case where it doesn't.
-    let verify_ix = verify_collection_ix(&client, verify_args)
+    let verify_ix = verify_collection_ix(client, verify_args)
         .map_err(|e| MigrateError::MigrationFailed(nft_mint.clone(), e.to_string()))?;
 
     instructions.push(verify_ix);
 
-    let recent_blockhash = client
-        .get_latest_blockhash()
-        .map_err(|e| MigrateError::MigrationFailed(nft_mint.clone(), e.to_string()))?;
+    Ok(instructions)
+}
 
-    let tx = Transaction::new_signed_with_payer(
-        &instructions,
-        Some(&authority_keypair.pubkey()),
-        &[&*authority_keypair],
-        recent_blockhash,
-    );
+/// Migrate the mints with as many items packed into each transaction as fit,
+/// returning the mints that failed.
+fn migrate_packed(
+    client: &RpcClient,
+    authority_keypair: &Keypair,
+    mints: Vec<String>,
+    collection_mint: &str,
+    changelog: &Changelog,
+) -> AnyResult<Vec<MigrateError>> {
+    let mut migrate_failed = Vec::new();
+    let mut groups = Vec::with_capacity(mints.len());
+
+    let pb = create_progress_bar("Building instructions...", mints.len() as u64);
+    for mint in mints {
+        match set_and_verify_ixs(
+            client,
+            authority_keypair,
+            &mint,
+            collection_mint.to_string(),
+            false,
+        ) {
+            Ok(instructions) => groups.push(InstructionGroup {
+                key: mint,
+                instructions,
+                compute_units: SET_AND_VERIFY_COMPUTE_UNITS,
+            }),
+            Err(e) => migrate_failed.push(e),
+        }
+        pb.inc(1);
+    }
+    pb.finish_and_clear();
 
-    let sig = client
-        .send_and_confirm_transaction(&tx)
-        .map_err(|e| MigrateError::MigrationFailed(nft_mint.clone(), e.to_string()))?;
+    let packed = pack_instruction_groups(client, &authority_keypair.pubkey(), groups)?;
+    let (sent, failed_txs) = send_packed(client, &[authority_keypair], &Priority::None, packed)?;
+    for (mints, signature) in sent {
+        for mint in mints {
+            changelog.record(&mint, signature);
+        }
+    }
+    for (mints, e) in failed_txs {
+        for mint in mints {
+            migrate_failed.push(MigrateError::MigrationFailed(mint, e.to_string()));
+        }
+    }
 
-    Ok(sig)
+    Ok(migrate_failed)
 }
 
 pub async fn migrate_collection(args: MigrateArgs) -> AnyResult<()> {
@@ -201,7 +275,7 @@ pub async fn migrate_collection(args: MigrateArgs) -> AnyResult<()> {
     }
 
     // Default name, if we don't get an output_file option or a cache file.
-    let mut cache_file_name = String::from("mb-cache-migrate.json");
+    let mut cache_file_name = output_path("mb-cache-migrate.json").display().to_string();
     let mut cache = MigrateCache::new();
 
     let solana_opts = parse_solana_config();
@@ -219,14 +293,12 @@ pub async fn migrate_collection(args: MigrateArgs) -> AnyResult<()> {
             false,
         )?
     } else if let Some(mint_list) = args.mint_list {
-        let f = File::open(mint_list)?;
-        serde_json::from_reader(f)?
+        read_json(mint_list)?
     } else if let Some(cache_path) = args.cache_file {
         println!("Retrying items from cache file. . .");
         cache_file_name = cache_path;
 
-        let f = File::open(&cache_file_name)?;
-        let cache: MigrateCache = serde_json::from_reader(f)?;
+        let cache: MigrateCache = read_json(&cache_file_name)?;
         cache.0.keys().map(|k| k.to_string()).collect()
     } else {
         return Err(anyhow!(
@@ -252,52 +324,84 @@ pub async fn migrate_collection(args: MigrateArgs) -> AnyResult<()> {
     let client = Arc::new(args.client);
 
     let mut counter = 0u8;
-    let delay = NANO_SECONDS_IN_SECOND / args.rate_limit;
-    let rate_limiter = create_rate_limiter_with_capacity(args.rate_limit as u32, delay as u32);
+    let rate_limit = rate_limit_or(args.rate_limit);
+    let delay = NANO_SECONDS_IN_SECOND / rate_limit;
+    let rate_limiter = create_rate_limiter_with_capacity(rate_limit as u32, delay as u32);
+    let concurrency = create_concurrency_limiter();
+
+    let all_mints = mint_accounts.clone();
+
+    // Read the metadata up front so the changes the run makes can be written out after.
+    let changelog = Arc::new(Changelog::capture(&client, MIGRATE_COMMAND, &all_mints)?);
 
     // Loop over migrate process so we can retry repeatedly until the user exits.
     loop {
         let remaining_mints = mint_accounts.clone();
 
-        info!("Sending network requests...");
-        let pb = create_progress_bar("Sending network requests...", remaining_mints.len() as u64);
-
-        // Create a vector of futures to execute.
-        let mut migrate_tasks = Vec::new();
+        let (migrate_failed, migrate_tasks_len) = if args.pack {
+            let migrate_tasks_len = remaining_mints.len();
+            let migrate_failed = migrate_packed(
+                &client,
+                &keypair,
+                remaining_mints,
+                &args.mint_address,
+                &changelog,
+            )?;
+            (migrate_failed, migrate_tasks_len)
+        } else {
+            info!("Sending network requests...");
+            let pb =
+                create_progress_bar("Sending network requests...", remaining_mints.len() as u64);
+
+            // Create a vector of futures to execute.
+            let mut migrate_tasks = Vec::new();
+
+            for mint in remaining_mints {
+                let client = client.clone();
+                let keypair = keypair.clone();
+                let mint_address = args.mint_address.clone();
+                let mut rate_limiter = rate_limiter.clone();
+                let changelog = changelog.clone();
+
+                let permit = concurrency.clone().acquire_owned().await?;
+
+                migrate_tasks.push(tokio::spawn({
+                    rate_limiter.wait();
+
+                    let fut = set_and_verify(client, keypair, mint.clone(), mint_address, false);
+                    pb.inc(1);
+                    async move {
+                        let result = fut.await;
+                        if let Ok(signature) = &result {
+                            changelog.record(&mint, *signature);
+                        }
+                        drop(permit);
+                        result
+                    }
+                }));
+            }
+            pb.finish_and_clear();
 
-        for mint in remaining_mints {
-            let client = client.clone();
-            let keypair = keypair.clone();
-            let mint_address = args.mint_address.clone();
-            let mut rate_limiter = rate_limiter.clone();
+            let migrate_tasks_len = migrate_tasks.len();
 
-            migrate_tasks.push(tokio::spawn({
-                rate_limiter.wait();
+            // Wait for all the tasks to resolve and push the results to our results vector
+            let mut migrate_failed = Vec::new();
+            let pb = create_progress_bar(
+                "Waiting for requests to resolve...",
+                migrate_tasks.len() as u64,
+            );
 
-                let fut = set_and_verify(client, keypair, mint, mint_address, false);
+            for task in migrate_tasks {
+                match task.await.unwrap() {
+                    Ok(_) => (),
+                    Err(e) => migrate_failed.push(e),
+                }
                 pb.inc(1);
-                fut
-            }));
-        }
-        pb.finish_and_clear();
-
-        let migrate_tasks_len = migrate_tasks.len();
-
-        // Wait for all the tasks to resolve and push the results to our results vector
-        let mut migrate_failed = Vec::new();
-        let pb = create_progress_bar(
-            "Waiting for requests to resolve...",
-            migrate_tasks.len() as u64,
-        );
-
-        for task in migrate_tasks {
-            match task.await.unwrap() {
-                Ok(_) => (),
-                Err(e) => migrate_failed.push(e),
             }
-            pb.inc(1);
-        }
-        pb.finish_and_clear();
+            pb.finish_and_clear();
+
+            (migrate_failed, migrate_tasks_len)
+        };
 
         // If some of the migrations failed, ask user if they wish to retry and the loop starts again.
         // Otherwise, break out of the loop and write the cache to disk.
@@ -313,6 +417,7 @@ pub async fn migrate_collection(args: MigrateArgs) -> AnyResult<()> {
         } else if migrate_failed.is_empty() {
             // None failed so we exit the loop.
             println!("All items successfully migrated!");
+            cache.clear();
             break;
         } else {
             println!("Reached max retries. Writing remaining items to cache.");
@@ -322,5 +427,24 @@ pub async fn migrate_collection(args: MigrateArgs) -> AnyResult<()> {
         }
     }
 
+    // The cache holds the mints that still failed after the last retry.
+    for mint in &all_mints {
+        match cache.get(mint) {
+            Some(item) => checkpoint::record_failed(
+                MIGRATE_COMMAND,
+                mint,
+                item.error.as_deref().unwrap_or("Migration failed"),
+            ),
+            None => checkpoint::record_done(MIGRATE_COMMAND, mint, None),
+        }
+    }
+
+    // The changes are already on chain, so failing to write them out isn't an error.
+    match changelog.write(&client) {
+        Ok(Some(path)) => println!("Changelog written to {path}"),
+        Ok(None) => {}
+        Err(e) => warn!("Failed to write the changelog: {e}"),
+    }
+
     Ok(())
 }
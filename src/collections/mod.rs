@@ -2,10 +2,12 @@ mod data;
 mod items;
 mod methods;
 mod migrate;
+mod unverify_all;
 pub use data::*;
 pub use items::*;
 pub use methods::*;
 pub use migrate::*;
+pub use unverify_all::*;
 
 pub use anyhow::{anyhow, Result as AnyResult};
 pub use borsh::BorshDeserialize;
@@ -0,0 +1,104 @@
+use async_trait::async_trait;
+use metaboss_lib::{
+    data::Priority,
+    decode::decode_metadata_from_mint,
+    unverify::{unverify_collection_ix, UnverifyCollectionArgs},
+};
+
+use crate::{
+    cache::{Action, ActionOutcome, BatchActionArgs, NewValue, RunActionArgs},
+    errors::ActionError,
+    offline::{reject_signer_derivation, signer_pubkey},
+    parse::{parse_keypair, parse_solana_config},
+    pipeline::Pipeline,
+    update::parse_mint_list,
+};
+
+use super::*;
+
+pub struct UnverifyCollectionAllArgs {
+    pub client: RpcClient,
+    pub keypair: Option<String>,
+    pub collection_mint: String,
+    pub mint_list: Option<String>,
+    pub cache_file: Option<String>,
+    pub rate_limit: usize,
+    pub retries: u8,
+    pub priority: Priority,
+}
+
+pub struct UnverifyCollectionAll {}
+
+#[async_trait]
+impl Action for UnverifyCollectionAll {
+    fn name() -> &'static str {
+        "unverify-collection-all"
+    }
+
+    async fn action(args: RunActionArgs) -> Result<ActionOutcome, ActionError> {
+        let failed = |e: String| ActionError::ActionFailed(args.mint_account.clone(), e);
+        let collection_mint =
+            Pubkey::from_str(&args.new_value).map_err(|e| failed(e.to_string()))?;
+
+        let md = decode_metadata_from_mint(&args.client, args.mint_account.clone())
+            .map_err(|e| failed(e.to_string()))?;
+        match md.collection {
+            Some(collection) if collection.key == collection_mint && collection.verified => {}
+            Some(collection) if collection.key != collection_mint => {
+                return Err(failed(format!(
+                    "in collection {}, not {collection_mint}",
+                    collection.key
+                )))
+            }
+            _ => return Ok(ActionOutcome::AlreadyDone),
+        }
+
+        // Anyone other than the update authority unverifies through their collection delegate.
+        let signer = signer_pubkey(&args.keypair).map_err(|e| failed(e.to_string()))?;
+        let is_delegate = md.update_authority != signer;
+        if is_delegate {
+            reject_signer_derivation("Unverifying as a collection delegate")
+                .map_err(|e| failed(e.to_string()))?;
+        }
+        let ix = unverify_collection_ix(
+            &args.client,
+            UnverifyCollectionArgs::V1 {
+                authority: &args.keypair,
+                mint: args.mint_account.clone(),
+                collection_mint: args.new_value.clone(),
+                is_delegate,
+            },
+        )
+        .map_err(|e| failed(e.to_string()))?;
+
+        Pipeline::with_priority(args.priority)
+            .run(&args.client, &[&*args.keypair], vec![ix])
+            .map(ActionOutcome::Sent)
+            .map_err(|e| failed(e.to_string()))
+    }
+}
+
+pub async fn unverify_collection_all(args: UnverifyCollectionAllArgs) -> AnyResult<()> {
+    let solana_opts = parse_solana_config();
+    let keypair = parse_keypair(args.keypair, solana_opts);
+
+    Pubkey::from_str(&args.collection_mint)
+        .map_err(|_| anyhow!("Invalid collection mint: {}", args.collection_mint))?;
+    let mint_list = parse_mint_list(args.mint_list, &args.cache_file)?;
+
+    // We don't support an optional payer for this action currently.
+    let payer = None;
+
+    let args = BatchActionArgs {
+        client: args.client,
+        keypair,
+        payer,
+        mint_list,
+        cache_file: args.cache_file,
+        new_value: NewValue::Single(args.collection_mint),
+        rate_limit: args.rate_limit,
+        retries: args.retries,
+        priority: args.priority,
+    };
+    UnverifyCollectionAll::run(args).await
+}
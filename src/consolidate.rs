@@ -0,0 +1,319 @@
+//! Sweep fungible tokens, and optionally SOL, from many wallets into one.
+
+use std::{
+    fs::{self, File},
+    path::{Path, PathBuf},
+};
+
+use anyhow::{anyhow, Result};
+use log::warn;
+use metaboss_lib::data::Priority;
+use serde::Serialize;
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::{
+    instruction::Instruction,
+    native_token::{lamports_to_sol, sol_to_lamports},
+    pubkey::Pubkey,
+    signature::{read_keypair_file, Keypair},
+    signer::Signer,
+    system_instruction,
+};
+use spl_associated_token_account::{
+    get_associated_token_address_with_program_id,
+    instruction::create_associated_token_account_idempotent,
+};
+use spl_token_2022::{
+    extension::StateWithExtensions,
+    state::{Account as TokenAccount, Mint},
+};
+
+use crate::checkpoint;
+use crate::constants::MAX_MULTIPLE_ACCOUNTS;
+use crate::pack::{pack_instruction_groups, InstructionGroup};
+use crate::parse::{parse_keypair, parse_solana_config};
+use crate::pipeline::Pipeline;
+use crate::spinner::{create_progress_bar, create_spinner};
+use crate::utils::get_token_program;
+use crate::workspace::output_path;
+
+/// Name the outcome of each wallet is recorded under.
+const CONSOLIDATE_COMMAND: &str = "consolidate";
+
+// Estimates of the compute units each instruction uses, padded a bit.
+const TOKEN_TRANSFER_COMPUTE_UNITS: u32 = 10_000;
+const SOL_TRANSFER_COMPUTE_UNITS: u32 = 1_000;
+
+pub struct ConsolidateArgs {
+    pub client: RpcClient,
+    pub keypair: Option<String>,
+    pub keypair_dir: PathBuf,
+    pub destination: Pubkey,
+    pub mint: Option<Pubkey>,
+    pub sol_reserve: Option<f64>,
+    pub priority: Priority,
+}
+
+#[derive(Debug, Serialize)]
+struct FailedWallet {
+    wallet: String,
+    error: String,
+}
+
+/// What is swept from a single wallet.
+struct Sweep {
+    wallet: Keypair,
+    source_token: Pubkey,
+    tokens: u64,
+    lamports: u64,
+}
+
+fn read_keypairs(dir: &Path) -> Result<Vec<Keypair>> {
+    let mut paths: Vec<PathBuf> = fs::read_dir(dir)?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| path.extension().map(|e| e == "json") == Some(true))
+        .collect();
+    paths.sort();
+
+    paths
+        .iter()
+        .map(|path| {
+            read_keypair_file(path)
+                .map_err(|e| anyhow!("Failed to read keypair {}: {e}", path.display()))
+        })
+        .collect()
+}
+
+/// Sweep the balance of a fungible token, and SOL above a reserve if one is set, from every
+/// keypair in a directory into the destination wallet.
+///
+/// Transfers from several wallets share a transaction, signed by each of them, and the
+/// fee payer covers the fees so wallets without SOL can still be swept.
+pub fn consolidate(args: ConsolidateArgs) -> Result<()> {
+    if args.mint.is_none() && args.sol_reserve.is_none() {
+        return Err(anyhow!(
+            "Nothing to consolidate: pass --mint, --sol-reserve or both"
+        ));
+    }
+
+    let payer = parse_keypair(args.keypair, parse_solana_config());
+    let client = &args.client;
+
+    let wallets: Vec<Keypair> = read_keypairs(&args.keypair_dir)?
+        .into_iter()
+        .filter(|wallet| wallet.pubkey() != args.destination)
+        .collect();
+    if wallets.is_empty() {
+        return Err(anyhow!(
+            "No keypairs found in {}",
+            args.keypair_dir.display()
+        ));
+    }
+
+    let token = match args.mint {
+        Some(mint) => {
+            let token_program = get_token_program(client, &mint)?;
+            let mint_account = client.get_account(&mint)?;
+            let decimals = StateWithExtensions::<Mint>::unpack(&mint_account.data)?
+                .base
+                .decimals;
+            Some((mint, token_program, decimals))
+        }
+        None => None,
+    };
+    let reserve = args.sol_reserve.map(sol_to_lamports);
+
+    let spinner = create_spinner("Getting balances...");
+    let mut sweeps = vec![];
+    for chunk in wallets.chunks(MAX_MULTIPLE_ACCOUNTS / 2) {
+        let sources: Vec<Pubkey> = match token {
+            Some((mint, token_program, _)) => chunk
+                .iter()
+                .map(|wallet| {
+                    get_associated_token_address_with_program_id(
+                        &wallet.pubkey(),
+                        &mint,
+                        &token_program,
+                    )
+                })
+                .collect(),
+            None => vec![],
+        };
+        let mut keys: Vec<Pubkey> = chunk.iter().map(|wallet| wallet.pubkey()).collect();
+        keys.extend(&sources);
+        let accounts = client.get_multiple_accounts(&keys)?;
+        let (wallet_accounts, token_accounts) = accounts.split_at(chunk.len());
+
+        for (i, wallet) in chunk.iter().enumerate() {
+            let tokens = match token_accounts.get(i) {
+                Some(Some(account)) => {
+                    StateWithExtensions::<TokenAccount>::unpack(&account.data)?
+                        .base
+                        .amount
+                }
+                _ => 0,
+            };
+
+            let balance = wallet_accounts[i].as_ref().map_or(0, |a| a.lamports);
+            let lamports = match reserve {
+                // The fee payer has to keep enough to pay for every transaction.
+                Some(_) if wallet.pubkey() == payer.pubkey() => {
+                    warn!("Not sweeping SOL from the fee payer {}", payer.pubkey());
+                    0
+                }
+                Some(reserve) => balance.saturating_sub(reserve),
+                None => 0,
+            };
+
+            if tokens > 0 || lamports > 0 {
+                sweeps.push(Sweep {
+                    wallet: wallet.insecure_clone(),
+                    source_token: sources.get(i).copied().unwrap_or_default(),
+                    tokens,
+                    lamports,
+                });
+            }
+        }
+    }
+    spinner.finish_and_clear();
+
+    if sweeps.is_empty() {
+        println!("Nothing to sweep from {} wallets", wallets.len());
+        return Ok(());
+    }
+
+    if let Some((mint, token_program, _)) = token {
+        if sweeps.iter().any(|s| s.tokens > 0) {
+            let create_ata = create_associated_token_account_idempotent(
+                &payer.pubkey(),
+                &args.destination,
+                &mint,
+                &token_program,
+            );
+            Pipeline::with_priority(args.priority.clone())
+                .with_active_lookup_table()?
+                .with_active_nonce()?
+                .run(client, &[&payer], vec![create_ata])?;
+        }
+    }
+
+    let groups: Vec<InstructionGroup<usize>> = sweeps
+        .iter()
+        .enumerate()
+        .map(|(i, sweep)| {
+            let owner = sweep.wallet.pubkey();
+            let mut instructions: Vec<Instruction> = vec![];
+            let mut compute_units = 0;
+
+            if let Some((mint, token_program, decimals)) = token {
+                if sweep.tokens > 0 {
+                    let destination_token = get_associated_token_address_with_program_id(
+                        &args.destination,
+                        &mint,
+                        &token_program,
+                    );
+                    instructions.push(spl_token_2022::instruction::transfer_checked(
+                        &token_program,
+                        &sweep.source_token,
+                        &mint,
+                        &destination_token,
+                        &owner,
+                        &[],
+                        sweep.tokens,
+                        decimals,
+                    )?);
+                    compute_units += TOKEN_TRANSFER_COMPUTE_UNITS;
+                }
+            }
+            if sweep.lamports > 0 {
+                instructions.push(system_instruction::transfer(
+                    &owner,
+                    &args.destination,
+                    sweep.lamports,
+                ));
+                compute_units += SOL_TRANSFER_COMPUTE_UNITS;
+            }
+
+            Ok(InstructionGroup {
+                key: i,
+                instructions,
+                compute_units,
+            })
+        })
+        .collect::<Result<_>>()?;
+
+    let packed = pack_instruction_groups(client, &payer.pubkey(), groups)?;
+
+    let mut swept_tokens = 0;
+    let mut swept_lamports = 0;
+    let mut swept_wallets = 0;
+    let mut failed = vec![];
+
+    let pb = create_progress_bar("Sweeping wallets...", packed.len() as u64);
+    for tx in packed {
+        let mut signers: Vec<&Keypair> = vec![&payer];
+        signers.extend(
+            tx.keys
+                .iter()
+                .map(|i| &sweeps[*i].wallet)
+                .filter(|wallet| wallet.pubkey() != payer.pubkey()),
+        );
+
+        let result = Pipeline::with_priority(args.priority.clone())
+            .with_active_lookup_table()?
+            .with_active_nonce()?
+            .run(client, &signers, tx.instructions);
+
+        match result {
+            Ok(signature) => {
+                for i in &tx.keys {
+                    swept_tokens += sweeps[*i].tokens;
+                    swept_lamports += sweeps[*i].lamports;
+                    swept_wallets += 1;
+                    checkpoint::record_done(
+                        CONSOLIDATE_COMMAND,
+                        &sweeps[*i].wallet.pubkey().to_string(),
+                        Some(signature.to_string()),
+                    );
+                }
+            }
+            Err(e) => {
+                for i in &tx.keys {
+                    checkpoint::record_failed(
+                        CONSOLIDATE_COMMAND,
+                        &sweeps[*i].wallet.pubkey().to_string(),
+                        &e,
+                    );
+                    failed.push(FailedWallet {
+                        wallet: sweeps[*i].wallet.pubkey().to_string(),
+                        error: e.to_string(),
+                    });
+                }
+            }
+        }
+        pb.inc(1);
+    }
+    pb.finish_and_clear();
+
+    println!(
+        "Swept {swept_wallets} of {} wallets into {}",
+        sweeps.len(),
+        args.destination
+    );
+    if let Some((mint, _, decimals)) = token {
+        println!(
+            "Tokens: {} of {mint}",
+            swept_tokens as f64 / 10f64.powi(decimals as i32)
+        );
+    }
+    if reserve.is_some() {
+        println!("SOL: {}", lamports_to_sol(swept_lamports));
+    }
+
+    if !failed.is_empty() {
+        let path = output_path("mb-consolidate-failed.json");
+        serde_json::to_writer_pretty(File::create(&path)?, &failed)?;
+        println!("{} wallets failed, see {}", failed.len(), path.display());
+    }
+
+    Ok(())
+}
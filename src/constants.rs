@@ -9,6 +9,7 @@ pub const MAX_CREATOR_LEN: usize = 32 + 1 + 1;
 pub const METADATA_PREFIX: &str = "metadata";
 pub const MASTER_EDITION_PREFIX: &str = "edition";
 pub const USER_PREFIX: &str = "user";
+pub const BURN_PREFIX: &str = "burn";
 pub const ERROR_FILES_DIR: &str = ".error_files";
 
 pub const METAPLEX_PROGRAM_ID: &str = "metaqbxxUerdq28cj1RbAWkYQm3ybzjb6a8bt518x1s";
@@ -26,6 +27,21 @@ pub const DEFAULT_RPC_DELAY_MS: u32 = 200;
 // These are strs so they can be used in Structopt arguments
 pub const DEFAULT_RATE_LIMIT: &str = "10";
 pub const NANO_SECONDS_IN_SECOND: usize = 1_000_000_000;
+pub const DEFAULT_JITO_TIP: &str = "10000";
+
+// Jito limits bundles to five transactions.
+pub const MAX_BUNDLE_SIZE: usize = 5;
+
+pub const JITO_TIP_ACCOUNTS: &[&str] = &[
+    "96gYZGLnJYVFmbjzopPSU6QiEV5fGqZNyN9nmNhvrZU5",
+    "HFqU5x63VTqvQss8hp11i4wVV8bD44PvwucfZ2bU7gRe",
+    "Cw8CFyM9FkoMi7K7Crf6HNQqf4uEMzpKw6QNghXLvLkY",
+    "ADaUMid9yfUytqMBgopwjb2DTLSokTSzL1zt6iGPaS49",
+    "DfXygSm4jCyNCybVYYK6DwvWqjKee8pbDmJGcLWNDXjh",
+    "ADuUkR4vqLUMWXxW9gh6D6L8pMSawimctcNZ5pGwDcEt",
+    "DttWaMuVvTiduZRnguLF7jNxTgiMBZ1hyAumKUiL2KRL",
+    "3AVi9Tg9Uo68tJfuvoKvqKNWKkC5wPdSSdeBnizKZ6jT",
+];
 
 pub const ERROR_FILE_BEGIN: &str = r#"#![allow(unused)]
 use phf::phf_map;
@@ -35,6 +51,20 @@ use phf::phf_map;
 lazy_static! {
     pub static ref USE_RATE_LIMIT: RwLock<bool> = RwLock::new(false);
     pub static ref RPC_DELAY_NS: RwLock<u32> = RwLock::new(DEFAULT_RPC_DELAY_MS * 1_000_000);
+    pub static ref JITO_BLOCK_ENGINE: RwLock<Option<String>> = RwLock::new(None);
+    pub static ref JITO_TIP_LAMPORTS: RwLock<u64> = RwLock::new(10_000);
+    pub static ref SQUADS_MULTISIG: RwLock<Option<String>> = RwLock::new(None);
+    pub static ref SQUADS_VAULT_INDEX: RwLock<u8> = RwLock::new(0);
+    pub static ref EXPORT_UNSIGNED_DIR: RwLock<Option<String>> = RwLock::new(None);
+    pub static ref OFFLINE_AUTHORITY: RwLock<Option<String>> = RwLock::new(None);
+    pub static ref NONCE_ACCOUNT: RwLock<Option<String>> = RwLock::new(None);
+    pub static ref NONCE_AUTHORITY: RwLock<Option<String>> = RwLock::new(None);
+    pub static ref LOOKUP_TABLE: RwLock<Option<String>> = RwLock::new(None);
+    pub static ref RECEIPTS_FILE: RwLock<Option<String>> = RwLock::new(None);
+    pub static ref GLOBAL_RATE_LIMIT: RwLock<Option<usize>> = RwLock::new(None);
+    pub static ref CONCURRENCY: RwLock<Option<usize>> = RwLock::new(None);
+    pub static ref MAX_NETWORK_FEE: RwLock<Option<u64>> = RwLock::new(None);
+    pub static ref RPC_CACHE: RwLock<bool> = RwLock::new(false);
     pub static ref RATE_LIMIT_DELAYS: HashMap<&'static str, u32> =
         [("https://ssc-dao.genesysgo.net", 25),]
             .iter()
@@ -43,3 +73,6 @@ lazy_static! {
 }
 
 pub const MINT_LAYOUT: u64 = 82;
+
+// Maximum number of accounts the RPC returns from a single getMultipleAccounts call.
+pub const MAX_MULTIPLE_ACCOUNTS: usize = 100;
@@ -0,0 +1,107 @@
+use std::{fs::File, path::Path};
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+
+use super::{read_items, ToolFormat};
+
+/// Holder entry as written by `snapshot holders`.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MetabossHolder {
+    pub owner: String,
+    pub mint: String,
+    pub metadata: String,
+    pub ata: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct UmiHolder {
+    pub owner: String,
+    pub mint: String,
+    pub metadata: String,
+    pub token: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct JsHolder {
+    pub owner_address: String,
+    pub mint_address: String,
+    pub metadata_address: String,
+    pub token_address: String,
+}
+
+fn read_holders(from: ToolFormat, input: &Path) -> Result<Vec<MetabossHolder>> {
+    let holders = match from {
+        ToolFormat::Metaboss => read_items::<MetabossHolder>(input)?
+            .into_iter()
+            .map(|(_, h)| h)
+            .collect(),
+        ToolFormat::Umi => read_items::<UmiHolder>(input)?
+            .into_iter()
+            .map(|(_, h)| MetabossHolder {
+                owner: h.owner,
+                mint: h.mint,
+                metadata: h.metadata,
+                ata: h.token,
+            })
+            .collect(),
+        ToolFormat::Js => read_items::<JsHolder>(input)?
+            .into_iter()
+            .map(|(_, h)| MetabossHolder {
+                owner: h.owner_address,
+                mint: h.mint_address,
+                metadata: h.metadata_address,
+                ata: h.token_address,
+            })
+            .collect(),
+    };
+
+    Ok(holders)
+}
+
+pub fn convert_holders(
+    from: ToolFormat,
+    to: ToolFormat,
+    input: &Path,
+    output: &Path,
+) -> Result<()> {
+    let holders = read_holders(from, input)?;
+    let count = holders.len();
+    let f = File::create(output)?;
+
+    match to {
+        ToolFormat::Metaboss => serde_json::to_writer_pretty(f, &holders)?,
+        ToolFormat::Umi => {
+            let holders: Vec<UmiHolder> = holders
+                .into_iter()
+                .map(|h| UmiHolder {
+                    owner: h.owner,
+                    mint: h.mint,
+                    metadata: h.metadata,
+                    token: h.ata,
+                })
+                .collect();
+            serde_json::to_writer_pretty(f, &holders)?
+        }
+        ToolFormat::Js => {
+            let holders: Vec<JsHolder> = holders
+                .into_iter()
+                .map(|h| JsHolder {
+                    owner_address: h.owner,
+                    mint_address: h.mint,
+                    metadata_address: h.metadata,
+                    token_address: h.ata,
+                })
+                .collect();
+            serde_json::to_writer_pretty(f, &holders)?
+        }
+    }
+
+    println!(
+        "Converted {count} holders from {from} to {to}, written to {}",
+        output.display()
+    );
+
+    Ok(())
+}
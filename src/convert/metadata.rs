@@ -0,0 +1,385 @@
+use std::{fs, fs::File, path::Path, str::FromStr};
+
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use solana_sdk::pubkey::Pubkey;
+
+use super::{read_items, ToolFormat};
+use crate::derive::derive_metadata_pda;
+
+// Umi's JSON form of an `Option`: `{ "__option": "Some", "value": ... }` or `{ "__option": "None" }`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "__option", content = "value")]
+pub enum UmiOption<T> {
+    Some(T),
+    None,
+}
+
+impl<T> From<Option<T>> for UmiOption<T> {
+    fn from(option: Option<T>) -> Self {
+        match option {
+            Some(value) => UmiOption::Some(value),
+            None => UmiOption::None,
+        }
+    }
+}
+
+impl<T> From<UmiOption<T>> for Option<T> {
+    fn from(option: UmiOption<T>) -> Self {
+        match option {
+            UmiOption::Some(value) => Some(value),
+            UmiOption::None => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreatorRecord {
+    pub address: String,
+    pub verified: bool,
+    pub share: u8,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CollectionRecord {
+    pub key: String,
+    pub verified: bool,
+}
+
+/// Metadata fields shared by every format.
+#[derive(Debug, Clone)]
+pub struct MetadataRecord {
+    pub mint: Option<String>,
+    pub update_authority: Option<String>,
+    pub name: String,
+    pub symbol: String,
+    pub uri: String,
+    pub seller_fee_basis_points: u16,
+    pub creators: Option<Vec<CreatorRecord>>,
+    pub primary_sale_happened: Option<bool>,
+    pub is_mutable: Option<bool>,
+    pub edition_nonce: Option<u8>,
+    pub token_standard: Option<String>,
+    pub collection: Option<CollectionRecord>,
+}
+
+/// Both `decode mint` outputs: the default short form and `--full`.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MetabossMetadata {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mint: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub update_authority: Option<String>,
+    pub name: String,
+    pub symbol: String,
+    pub uri: String,
+    pub seller_fee_basis_points: u16,
+    #[serde(default)]
+    pub creators: Option<Vec<CreatorRecord>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub primary_sale_happened: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub is_mutable: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub edition_nonce: Option<u8>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token_standard: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub collection: Option<CollectionRecord>,
+}
+
+/// The `Metadata` account as returned by Umi's `fetchMetadata`.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UmiMetadata {
+    #[serde(default)]
+    pub public_key: Option<String>,
+    pub mint: String,
+    pub update_authority: String,
+    pub name: String,
+    pub symbol: String,
+    pub uri: String,
+    pub seller_fee_basis_points: u16,
+    pub creators: UmiOption<Vec<CreatorRecord>>,
+    pub primary_sale_happened: bool,
+    pub is_mutable: bool,
+    pub edition_nonce: UmiOption<u8>,
+    pub token_standard: UmiOption<u8>,
+    pub collection: UmiOption<CollectionRecord>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct JsCollection {
+    pub address: String,
+    pub verified: bool,
+}
+
+/// The `Metadata` model from the old `@metaplex-foundation/js` SDK.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct JsMetadata {
+    #[serde(default = "js_metadata_model")]
+    pub model: String,
+    #[serde(default)]
+    pub address: Option<String>,
+    pub mint_address: String,
+    pub update_authority_address: String,
+    pub name: String,
+    pub symbol: String,
+    pub uri: String,
+    pub seller_fee_basis_points: u16,
+    #[serde(default)]
+    pub creators: Vec<CreatorRecord>,
+    pub primary_sale_happened: bool,
+    pub is_mutable: bool,
+    pub edition_nonce: Option<u8>,
+    pub token_standard: Option<u8>,
+    pub collection: Option<JsCollection>,
+}
+
+fn js_metadata_model() -> String {
+    String::from("metadata")
+}
+
+// Umi and the JS SDK use the program's enum index; Metaboss uses the variant name.
+const TOKEN_STANDARDS: [&str; 6] = [
+    "NonFungible",
+    "FungibleAsset",
+    "Fungible",
+    "NonFungibleEdition",
+    "ProgrammableNonFungible",
+    "ProgrammableNonFungibleEdition",
+];
+
+fn token_standard_index(name: &str) -> Result<u8> {
+    TOKEN_STANDARDS
+        .iter()
+        .position(|s| *s == name)
+        .map(|i| i as u8)
+        .ok_or_else(|| anyhow!("Unknown token standard: {name}"))
+}
+
+fn token_standard_name(index: u8) -> Result<String> {
+    TOKEN_STANDARDS
+        .get(index as usize)
+        .map(|s| s.to_string())
+        .ok_or_else(|| anyhow!("Unknown token standard: {index}"))
+}
+
+fn metadata_address(mint: &str) -> Result<String> {
+    let mint = Pubkey::from_str(mint).map_err(|_| anyhow!("Invalid mint address: {mint}"))?;
+    Ok(derive_metadata_pda(&mint).to_string())
+}
+
+impl MetadataRecord {
+    fn required_mint(&self) -> Result<String> {
+        self.mint
+            .clone()
+            .ok_or_else(|| anyhow!("Metadata for '{}' has no mint address", self.name))
+    }
+
+    fn required_update_authority(&self) -> Result<String> {
+        self.update_authority.clone().ok_or_else(|| {
+            anyhow!(
+                "Metadata for '{}' has no update authority, decode it with --full",
+                self.name
+            )
+        })
+    }
+
+    fn from_metaboss(md: MetabossMetadata, file_stem: &str) -> Self {
+        // Short decode output has no mint, but the file is named after it.
+        let mint = md.mint.or_else(|| {
+            Pubkey::from_str(file_stem)
+                .ok()
+                .map(|_| file_stem.to_string())
+        });
+
+        MetadataRecord {
+            mint,
+            update_authority: md.update_authority,
+            // `--full` output keeps the on-chain null padding.
+            name: md.name.trim_matches(char::from(0)).to_string(),
+            symbol: md.symbol.trim_matches(char::from(0)).to_string(),
+            uri: md.uri.trim_matches(char::from(0)).to_string(),
+            seller_fee_basis_points: md.seller_fee_basis_points,
+            creators: md.creators,
+            primary_sale_happened: md.primary_sale_happened,
+            is_mutable: md.is_mutable,
+            edition_nonce: md.edition_nonce,
+            token_standard: md.token_standard,
+            collection: md.collection,
+        }
+    }
+
+    fn from_umi(md: UmiMetadata) -> Result<Self> {
+        let token_standard: Option<u8> = md.token_standard.into();
+
+        Ok(MetadataRecord {
+            mint: Some(md.mint),
+            update_authority: Some(md.update_authority),
+            name: md.name,
+            symbol: md.symbol,
+            uri: md.uri,
+            seller_fee_basis_points: md.seller_fee_basis_points,
+            creators: md.creators.into(),
+            primary_sale_happened: Some(md.primary_sale_happened),
+            is_mutable: Some(md.is_mutable),
+            edition_nonce: md.edition_nonce.into(),
+            token_standard: token_standard.map(token_standard_name).transpose()?,
+            collection: md.collection.into(),
+        })
+    }
+
+    fn from_js(md: JsMetadata) -> Result<Self> {
+        Ok(MetadataRecord {
+            mint: Some(md.mint_address),
+            update_authority: Some(md.update_authority_address),
+            name: md.name,
+            symbol: md.symbol,
+            uri: md.uri,
+            seller_fee_basis_points: md.seller_fee_basis_points,
+            creators: (!md.creators.is_empty()).then_some(md.creators),
+            primary_sale_happened: Some(md.primary_sale_happened),
+            is_mutable: Some(md.is_mutable),
+            edition_nonce: md.edition_nonce,
+            token_standard: md.token_standard.map(token_standard_name).transpose()?,
+            collection: md.collection.map(|c| CollectionRecord {
+                key: c.address,
+                verified: c.verified,
+            }),
+        })
+    }
+
+    fn into_metaboss(self) -> MetabossMetadata {
+        MetabossMetadata {
+            mint: self.mint,
+            update_authority: self.update_authority,
+            name: self.name,
+            symbol: self.symbol,
+            uri: self.uri,
+            seller_fee_basis_points: self.seller_fee_basis_points,
+            creators: self.creators,
+            primary_sale_happened: self.primary_sale_happened,
+            is_mutable: self.is_mutable,
+            edition_nonce: self.edition_nonce,
+            token_standard: self.token_standard,
+            collection: self.collection,
+        }
+    }
+
+    // Short decode output doesn't include the flags, so fall back to the on-chain defaults.
+    fn into_umi(self) -> Result<UmiMetadata> {
+        let mint = self.required_mint()?;
+
+        Ok(UmiMetadata {
+            public_key: Some(metadata_address(&mint)?),
+            update_authority: self.required_update_authority()?,
+            mint,
+            name: self.name,
+            symbol: self.symbol,
+            uri: self.uri,
+            seller_fee_basis_points: self.seller_fee_basis_points,
+            creators: self.creators.into(),
+            primary_sale_happened: self.primary_sale_happened.unwrap_or(false),
+            is_mutable: self.is_mutable.unwrap_or(true),
+            edition_nonce: self.edition_nonce.into(),
+            token_standard: self
+                .token_standard
+                .as_deref()
+                .map(token_standard_index)
+                .transpose()?
+                .into(),
+            collection: self.collection.into(),
+        })
+    }
+
+    fn into_js(self) -> Result<JsMetadata> {
+        let mint = self.required_mint()?;
+
+        Ok(JsMetadata {
+            model: js_metadata_model(),
+            address: Some(metadata_address(&mint)?),
+            update_authority_address: self.required_update_authority()?,
+            mint_address: mint,
+            name: self.name,
+            symbol: self.symbol,
+            uri: self.uri,
+            seller_fee_basis_points: self.seller_fee_basis_points,
+            creators: self.creators.unwrap_or_default(),
+            primary_sale_happened: self.primary_sale_happened.unwrap_or(false),
+            is_mutable: self.is_mutable.unwrap_or(true),
+            edition_nonce: self.edition_nonce,
+            token_standard: self
+                .token_standard
+                .as_deref()
+                .map(token_standard_index)
+                .transpose()?,
+            collection: self.collection.map(|c| JsCollection {
+                address: c.key,
+                verified: c.verified,
+            }),
+        })
+    }
+}
+
+fn read_metadata(from: ToolFormat, input: &Path) -> Result<Vec<MetadataRecord>> {
+    match from {
+        ToolFormat::Metaboss => Ok(read_items::<MetabossMetadata>(input)?
+            .into_iter()
+            .map(|(stem, md)| MetadataRecord::from_metaboss(md, &stem))
+            .collect()),
+        ToolFormat::Umi => read_items::<UmiMetadata>(input)?
+            .into_iter()
+            .map(|(_, md)| MetadataRecord::from_umi(md))
+            .collect(),
+        ToolFormat::Js => read_items::<JsMetadata>(input)?
+            .into_iter()
+            .map(|(_, md)| MetadataRecord::from_js(md))
+            .collect(),
+    }
+}
+
+/// Convert metadata between formats. Metaboss output is one `<mint>.json` file per item,
+/// the same layout `decode mint` writes and `update data-all` reads.
+pub fn convert_metadata(
+    from: ToolFormat,
+    to: ToolFormat,
+    input: &Path,
+    output: &Path,
+) -> Result<()> {
+    let records = read_metadata(from, input)?;
+    let count = records.len();
+
+    match to {
+        ToolFormat::Metaboss => {
+            fs::create_dir_all(output)?;
+            for record in records {
+                let mint = record.required_mint()?;
+                let file = File::create(output.join(format!("{mint}.json")))?;
+                serde_json::to_writer_pretty(file, &record.into_metaboss())?;
+            }
+        }
+        ToolFormat::Umi => {
+            let items = records
+                .into_iter()
+                .map(MetadataRecord::into_umi)
+                .collect::<Result<Vec<_>>>()?;
+            serde_json::to_writer_pretty(File::create(output)?, &items)?;
+        }
+        ToolFormat::Js => {
+            let items = records
+                .into_iter()
+                .map(MetadataRecord::into_js)
+                .collect::<Result<Vec<_>>>()?;
+            serde_json::to_writer_pretty(File::create(output)?, &items)?;
+        }
+    }
+
+    println!(
+        "Converted {count} metadata items from {from} to {to}, written to {}",
+        output.display()
+    );
+
+    Ok(())
+}
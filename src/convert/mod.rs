@@ -0,0 +1,145 @@
+mod holders;
+mod metadata;
+
+pub use holders::*;
+pub use metadata::*;
+
+use std::{
+    fmt::{self, Display},
+    fs,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+use anyhow::{anyhow, Result};
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+use structopt::StructOpt;
+
+use crate::files::{has_extension, read_json};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ToolFormat {
+    Metaboss,
+    Umi,
+    Js,
+}
+
+impl FromStr for ToolFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "metaboss" => Ok(ToolFormat::Metaboss),
+            "umi" => Ok(ToolFormat::Umi),
+            "js" => Ok(ToolFormat::Js),
+            _ => Err(format!("Invalid format: {s}, expected metaboss, umi or js")),
+        }
+    }
+}
+
+impl Display for ToolFormat {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ToolFormat::Metaboss => write!(f, "metaboss"),
+            ToolFormat::Umi => write!(f, "umi"),
+            ToolFormat::Js => write!(f, "js"),
+        }
+    }
+}
+
+#[derive(Debug, StructOpt)]
+pub enum ConvertSubcommands {
+    /// Convert decoded metadata between Metaboss, Umi and JS SDK formats
+    #[structopt(name = "metadata")]
+    Metadata {
+        /// Format of the input: metaboss, umi or js
+        #[structopt(short, long)]
+        from: ToolFormat,
+
+        /// Format to convert to: metaboss, umi or js
+        #[structopt(short, long)]
+        to: ToolFormat,
+
+        /// JSON file with one item or a list, or a directory of JSON files
+        #[structopt(short, long)]
+        input: PathBuf,
+
+        /// Output file, or directory when converting to metaboss
+        #[structopt(short, long)]
+        output: PathBuf,
+    },
+    /// Convert holder snapshots between Metaboss, Umi and JS SDK formats
+    #[structopt(name = "holders")]
+    Holders {
+        /// Format of the input: metaboss, umi or js
+        #[structopt(short, long)]
+        from: ToolFormat,
+
+        /// Format to convert to: metaboss, umi or js
+        #[structopt(short, long)]
+        to: ToolFormat,
+
+        /// JSON file with a list of holders
+        #[structopt(short, long)]
+        input: PathBuf,
+
+        /// Output file
+        #[structopt(short, long)]
+        output: PathBuf,
+    },
+}
+
+pub fn process_convert(commands: ConvertSubcommands) -> Result<()> {
+    match commands {
+        ConvertSubcommands::Metadata {
+            from,
+            to,
+            input,
+            output,
+        } => convert_metadata(from, to, &input, &output),
+        ConvertSubcommands::Holders {
+            from,
+            to,
+            input,
+            output,
+        } => convert_holders(from, to, &input, &output),
+    }
+}
+
+/// Read every item from a JSON file holding one object or a list, or from every JSON file
+/// in a directory. Each item is paired with the file stem it came from.
+fn read_items<T: DeserializeOwned>(input: &Path) -> Result<Vec<(String, T)>> {
+    let paths = if input.is_dir() {
+        let mut paths: Vec<PathBuf> = fs::read_dir(input)?
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .filter(|path| has_extension(path, "json"))
+            .collect();
+        paths.sort();
+        paths
+    } else {
+        vec![input.to_path_buf()]
+    };
+
+    let mut items = Vec::new();
+    for path in paths {
+        let stem = path
+            .file_stem()
+            .map(|s| s.to_string_lossy().to_string())
+            .unwrap_or_default();
+
+        let value: Value = read_json(&path)?;
+        let values = match value {
+            Value::Array(values) => values,
+            value => vec![value],
+        };
+
+        for value in values {
+            let item = serde_json::from_value(value)
+                .map_err(|e| anyhow!("Failed to parse {}: {e}", path.display()))?;
+            items.push((stem.clone(), item));
+        }
+    }
+
+    Ok(items)
+}
@@ -0,0 +1,81 @@
+use anyhow::Result;
+use metaboss_lib::data::Priority;
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::{pubkey::Pubkey, signer::Signer};
+
+use super::{burn_v1, fetch_asset, send, transfer_v1, update_v1};
+use crate::parse::{parse_keypair, parse_solana_config};
+
+pub struct CoreUpdateUriArgs {
+    pub client: RpcClient,
+    pub keypair: Option<String>,
+    pub asset: Pubkey,
+    pub new_uri: String,
+    pub priority: Priority,
+}
+
+pub struct CoreTransferArgs {
+    pub client: RpcClient,
+    pub keypair: Option<String>,
+    pub asset: Pubkey,
+    pub receiver: Pubkey,
+    pub priority: Priority,
+}
+
+pub struct CoreBurnArgs {
+    pub client: RpcClient,
+    pub keypair: Option<String>,
+    pub asset: Pubkey,
+    pub priority: Priority,
+}
+
+pub fn update_core_uri(args: CoreUpdateUriArgs) -> Result<()> {
+    let keypair = parse_keypair(args.keypair, parse_solana_config());
+    let asset = fetch_asset(&args.client, &args.asset)?;
+
+    let ix = update_v1(
+        &args.asset,
+        asset.collection(),
+        &keypair.pubkey(),
+        &keypair.pubkey(),
+        None,
+        Some(args.new_uri),
+    );
+    send(&args.client, &keypair, args.priority, ix)?;
+    println!("Updated URI of {}", args.asset);
+
+    Ok(())
+}
+
+pub fn transfer_core_asset(args: CoreTransferArgs) -> Result<()> {
+    let keypair = parse_keypair(args.keypair, parse_solana_config());
+    let asset = fetch_asset(&args.client, &args.asset)?;
+
+    let ix = transfer_v1(
+        &args.asset,
+        asset.collection(),
+        &keypair.pubkey(),
+        &keypair.pubkey(),
+        &args.receiver,
+    );
+    send(&args.client, &keypair, args.priority, ix)?;
+    println!("Transferred {} to {}", args.asset, args.receiver);
+
+    Ok(())
+}
+
+pub fn burn_core_asset(args: CoreBurnArgs) -> Result<()> {
+    let keypair = parse_keypair(args.keypair, parse_solana_config());
+    let asset = fetch_asset(&args.client, &args.asset)?;
+
+    let ix = burn_v1(
+        &args.asset,
+        asset.collection(),
+        &keypair.pubkey(),
+        &keypair.pubkey(),
+    );
+    send(&args.client, &keypair, args.priority, ix)?;
+    println!("Burned {}", args.asset);
+
+    Ok(())
+}
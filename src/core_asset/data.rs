@@ -0,0 +1,309 @@
+use anyhow::{anyhow, Result};
+use borsh::{BorshDeserialize, BorshSerialize};
+use serde::{Deserialize, Serialize};
+use solana_program::{pubkey, pubkey::Pubkey};
+
+pub const MPL_CORE_PROGRAM_ID: Pubkey = pubkey!("CoREENxT6tW1HoK8ypY1SxRMZTcVPm7R94rH4PZNhX7d");
+
+// Account discriminators, the first byte of every Core account.
+pub const ASSET_V1_KEY: u8 = 1;
+pub const PLUGIN_HEADER_V1_KEY: u8 = 3;
+pub const PLUGIN_REGISTRY_V1_KEY: u8 = 4;
+pub const COLLECTION_V1_KEY: u8 = 5;
+
+/// Serialize pubkeys as base58 strings rather than byte arrays.
+mod pubkey_string {
+    use std::str::FromStr;
+
+    use serde::{de::Error, Deserialize, Deserializer, Serializer};
+    use solana_program::pubkey::Pubkey;
+
+    pub fn serialize<S: Serializer>(pubkey: &Pubkey, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&pubkey.to_string())
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Pubkey, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        Pubkey::from_str(&s).map_err(|_| D::Error::custom(format!("Invalid pubkey: {s}")))
+    }
+}
+
+mod pubkey_string_vec {
+    use std::str::FromStr;
+
+    use serde::{de::Error, ser::SerializeSeq, Deserialize, Deserializer, Serializer};
+    use solana_program::pubkey::Pubkey;
+
+    pub fn serialize<S: Serializer>(pubkeys: &[Pubkey], serializer: S) -> Result<S::Ok, S::Error> {
+        let mut seq = serializer.serialize_seq(Some(pubkeys.len()))?;
+        for pubkey in pubkeys {
+            seq.serialize_element(&pubkey.to_string())?;
+        }
+        seq.end()
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Vec<Pubkey>, D::Error> {
+        Vec::<String>::deserialize(deserializer)?
+            .iter()
+            .map(|s| {
+                Pubkey::from_str(s).map_err(|_| D::Error::custom(format!("Invalid pubkey: {s}")))
+            })
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone, BorshSerialize, BorshDeserialize, Serialize, Deserialize)]
+pub enum UpdateAuthority {
+    None,
+    Address(#[serde(with = "pubkey_string")] Pubkey),
+    Collection(#[serde(with = "pubkey_string")] Pubkey),
+}
+
+#[derive(Debug, Clone, BorshDeserialize, Serialize)]
+pub struct AssetV1 {
+    #[serde(skip)]
+    pub key: u8,
+    #[serde(with = "pubkey_string")]
+    pub owner: Pubkey,
+    pub update_authority: UpdateAuthority,
+    pub name: String,
+    pub uri: String,
+    pub seq: Option<u64>,
+}
+
+impl AssetV1 {
+    /// The collection the asset belongs to, which must be passed to every instruction.
+    pub fn collection(&self) -> Option<Pubkey> {
+        match self.update_authority {
+            UpdateAuthority::Collection(collection) => Some(collection),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, BorshDeserialize, Serialize)]
+pub struct CollectionV1 {
+    #[serde(skip)]
+    pub key: u8,
+    #[serde(with = "pubkey_string")]
+    pub update_authority: Pubkey,
+    pub name: String,
+    pub uri: String,
+    pub num_minted: u32,
+    pub current_size: u32,
+}
+
+#[derive(Debug, Clone, BorshSerialize, BorshDeserialize, Serialize, Deserialize)]
+pub enum Authority {
+    None,
+    Owner,
+    UpdateAuthority,
+    Address {
+        #[serde(with = "pubkey_string")]
+        address: Pubkey,
+    },
+}
+
+#[derive(Debug, BorshDeserialize)]
+struct PluginHeaderV1 {
+    key: u8,
+    plugin_registry_offset: u64,
+}
+
+#[derive(Debug, BorshDeserialize)]
+struct RegistryRecord {
+    plugin_type: u8,
+    authority: Authority,
+    offset: u64,
+}
+
+// Only the internal plugin registry is read; external plugin records follow it.
+#[derive(Debug, BorshDeserialize)]
+struct PluginRegistryV1 {
+    key: u8,
+    registry: Vec<RegistryRecord>,
+}
+
+/// Plugin types in program order. The index is the `PluginType` discriminator.
+pub const PLUGIN_TYPES: [&str; 15] = [
+    "Royalties",
+    "FreezeDelegate",
+    "BurnDelegate",
+    "TransferDelegate",
+    "UpdateDelegate",
+    "PermanentFreezeDelegate",
+    "Attributes",
+    "PermanentTransferDelegate",
+    "PermanentBurnDelegate",
+    "Edition",
+    "MasterEdition",
+    "AddBlocker",
+    "ImmutableMetadata",
+    "VerifiedCreators",
+    "Autograph",
+];
+
+pub fn plugin_type_index(name: &str) -> Result<u8> {
+    PLUGIN_TYPES
+        .iter()
+        .position(|t| t.eq_ignore_ascii_case(name))
+        .map(|i| i as u8)
+        .ok_or_else(|| {
+            anyhow!(
+                "Unknown plugin type: {name}, expected one of {}",
+                PLUGIN_TYPES.join(", ")
+            )
+        })
+}
+
+#[derive(Debug, Clone, BorshSerialize, BorshDeserialize, Serialize, Deserialize)]
+pub struct Creator {
+    #[serde(with = "pubkey_string")]
+    pub address: Pubkey,
+    pub percentage: u8,
+}
+
+#[derive(Debug, Clone, BorshSerialize, BorshDeserialize, Serialize, Deserialize)]
+pub enum RuleSet {
+    None,
+    ProgramAllowList(#[serde(with = "pubkey_string_vec")] Vec<Pubkey>),
+    ProgramDenyList(#[serde(with = "pubkey_string_vec")] Vec<Pubkey>),
+}
+
+#[derive(Debug, Clone, BorshSerialize, BorshDeserialize, Serialize, Deserialize)]
+pub struct Attribute {
+    pub key: String,
+    pub value: String,
+}
+
+#[derive(Debug, Clone, BorshSerialize, BorshDeserialize, Serialize, Deserialize)]
+pub struct VerifiedCreatorsSignature {
+    #[serde(with = "pubkey_string")]
+    pub address: Pubkey,
+    pub verified: bool,
+}
+
+#[derive(Debug, Clone, BorshSerialize, BorshDeserialize, Serialize, Deserialize)]
+pub struct AutographSignature {
+    #[serde(with = "pubkey_string")]
+    pub address: Pubkey,
+    pub message: String,
+}
+
+/// A Core plugin. In JSON each variant is an object keyed by the plugin type, e.g.
+/// `{ "FreezeDelegate": { "frozen": false } }`.
+#[derive(Debug, Clone, BorshSerialize, BorshDeserialize, Serialize, Deserialize)]
+pub enum Plugin {
+    Royalties {
+        basis_points: u16,
+        creators: Vec<Creator>,
+        rule_set: RuleSet,
+    },
+    FreezeDelegate {
+        frozen: bool,
+    },
+    BurnDelegate {},
+    TransferDelegate {},
+    UpdateDelegate {
+        #[serde(default, with = "pubkey_string_vec")]
+        additional_delegates: Vec<Pubkey>,
+    },
+    PermanentFreezeDelegate {
+        frozen: bool,
+    },
+    Attributes {
+        attribute_list: Vec<Attribute>,
+    },
+    PermanentTransferDelegate {},
+    PermanentBurnDelegate {},
+    Edition {
+        number: u32,
+    },
+    MasterEdition {
+        max_supply: Option<u32>,
+        name: Option<String>,
+        uri: Option<String>,
+    },
+    AddBlocker {},
+    ImmutableMetadata {},
+    VerifiedCreators {
+        signatures: Vec<VerifiedCreatorsSignature>,
+    },
+    Autograph {
+        signatures: Vec<AutographSignature>,
+    },
+}
+
+#[derive(Debug, Serialize)]
+pub struct PluginRecord {
+    pub plugin_type: String,
+    pub authority: Authority,
+    /// `None` for plugin types this version of Metaboss doesn't know how to decode.
+    pub plugin: Option<Plugin>,
+}
+
+/// Read the plugins stored after the base asset or collection data, which ends at `base_len`.
+pub fn decode_plugins(data: &[u8], base_len: usize) -> Result<Vec<PluginRecord>> {
+    if data.len() <= base_len {
+        return Ok(Vec::new());
+    }
+
+    let header = PluginHeaderV1::deserialize(&mut &data[base_len..])?;
+    if header.key != PLUGIN_HEADER_V1_KEY {
+        return Err(anyhow!("Invalid plugin header key: {}", header.key));
+    }
+
+    let registry_data = data
+        .get(header.plugin_registry_offset as usize..)
+        .ok_or_else(|| anyhow!("Plugin registry offset is out of bounds"))?;
+    let registry = PluginRegistryV1::deserialize(&mut &registry_data[..])?;
+    if registry.key != PLUGIN_REGISTRY_V1_KEY {
+        return Err(anyhow!("Invalid plugin registry key: {}", registry.key));
+    }
+
+    registry
+        .registry
+        .into_iter()
+        .map(|record| {
+            let plugin_type = PLUGIN_TYPES
+                .get(record.plugin_type as usize)
+                .map(|t| t.to_string())
+                .unwrap_or_else(|| format!("Unknown({})", record.plugin_type));
+
+            let plugin = match data.get(record.offset as usize..) {
+                Some(mut plugin_data) if (record.plugin_type as usize) < PLUGIN_TYPES.len() => {
+                    Some(<Plugin as BorshDeserialize>::deserialize(&mut plugin_data)?)
+                }
+                _ => None,
+            };
+
+            Ok(PluginRecord {
+                plugin_type,
+                authority: record.authority,
+                plugin,
+            })
+        })
+        .collect()
+}
+
+/// Decode an asset account, returning the asset and how many bytes its base data uses.
+pub fn decode_asset(data: &[u8]) -> Result<(AssetV1, usize)> {
+    let mut slice = data;
+    let asset = AssetV1::deserialize(&mut slice)?;
+    if asset.key != ASSET_V1_KEY {
+        return Err(anyhow!("Account is not an MPL Core asset"));
+    }
+    Ok((asset, data.len() - slice.len()))
+}
+
+/// Decode a collection account, returning the collection and how many bytes its base data uses.
+pub fn decode_collection(data: &[u8]) -> Result<(CollectionV1, usize)> {
+    let mut slice = data;
+    let collection = CollectionV1::deserialize(&mut slice)?;
+    if collection.key != COLLECTION_V1_KEY {
+        return Err(anyhow!("Account is not an MPL Core collection"));
+    }
+    Ok((collection, data.len() - slice.len()))
+}
@@ -0,0 +1,67 @@
+use std::{fs::File, path::PathBuf};
+
+use anyhow::{anyhow, Result};
+use serde::Serialize;
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::pubkey::Pubkey;
+
+use super::{
+    decode_asset, decode_collection, decode_plugins, AssetV1, CollectionV1, PluginRecord,
+    ASSET_V1_KEY, COLLECTION_V1_KEY, MPL_CORE_PROGRAM_ID,
+};
+
+#[derive(Debug, Serialize)]
+#[serde(untagged)]
+pub enum CoreAccount {
+    Asset(AssetV1),
+    Collection(CollectionV1),
+}
+
+#[derive(Debug, Serialize)]
+pub struct DecodedCoreAccount {
+    pub address: String,
+    #[serde(flatten)]
+    pub account: CoreAccount,
+    pub plugins: Vec<PluginRecord>,
+}
+
+pub fn decode_core_account(
+    client: &RpcClient,
+    address: &Pubkey,
+    output: Option<PathBuf>,
+) -> Result<()> {
+    let account = client.get_account(address)?;
+    if account.owner != MPL_CORE_PROGRAM_ID {
+        return Err(anyhow!("{address} is not owned by the MPL Core program"));
+    }
+
+    let (core_account, base_len) = match account.data.first() {
+        Some(&ASSET_V1_KEY) => {
+            let (asset, len) = decode_asset(&account.data)?;
+            (CoreAccount::Asset(asset), len)
+        }
+        Some(&COLLECTION_V1_KEY) => {
+            let (collection, len) = decode_collection(&account.data)?;
+            (CoreAccount::Collection(collection), len)
+        }
+        _ => return Err(anyhow!("{address} is not an MPL Core asset or collection")),
+    };
+
+    let decoded = DecodedCoreAccount {
+        address: address.to_string(),
+        account: core_account,
+        plugins: decode_plugins(&account.data, base_len)?,
+    };
+
+    match output {
+        Some(dir) => {
+            std::fs::create_dir_all(&dir)?;
+            let path = dir.join(format!("{address}.json"));
+            serde_json::to_writer_pretty(File::create(&path)?, &decoded)?;
+            println!("Decoded account written to {}", path.display());
+        }
+        None => println!("{}", serde_json::to_string_pretty(&decoded)?),
+    }
+
+    Ok(())
+}
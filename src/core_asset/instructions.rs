@@ -0,0 +1,230 @@
+use borsh::BorshSerialize;
+use solana_program::{
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+    system_program,
+};
+
+use super::data::{Authority, Plugin, UpdateAuthority, MPL_CORE_PROGRAM_ID};
+
+// Instruction discriminators, the index of each instruction in the program.
+const ADD_PLUGIN_V1: u8 = 2;
+const REMOVE_PLUGIN_V1: u8 = 4;
+const UPDATE_PLUGIN_V1: u8 = 6;
+const UPDATE_COLLECTION_PLUGIN_V1: u8 = 7;
+const BURN_V1: u8 = 12;
+const TRANSFER_V1: u8 = 14;
+const UPDATE_V1: u8 = 15;
+
+#[derive(BorshSerialize)]
+struct AddPluginV1Args {
+    plugin: Plugin,
+    init_authority: Option<Authority>,
+}
+
+#[derive(BorshSerialize)]
+struct RemovePluginV1Args {
+    plugin_type: u8,
+}
+
+#[derive(BorshSerialize)]
+struct UpdatePluginV1Args {
+    plugin: Plugin,
+}
+
+// Neither burn nor transfer is used on compressed assets, so the proof is always `None`.
+#[derive(BorshSerialize)]
+struct CompressionProofArgs {
+    compression_proof: Option<()>,
+}
+
+#[derive(BorshSerialize)]
+struct UpdateV1Args {
+    new_name: Option<String>,
+    new_uri: Option<String>,
+    new_update_authority: Option<UpdateAuthority>,
+}
+
+// Omitted optional accounts are passed as the program id.
+fn optional(account: Option<Pubkey>, writable: bool) -> AccountMeta {
+    match account {
+        Some(pubkey) if writable => AccountMeta::new(pubkey, false),
+        Some(pubkey) => AccountMeta::new_readonly(pubkey, false),
+        None => AccountMeta::new_readonly(MPL_CORE_PROGRAM_ID, false),
+    }
+}
+
+/// Accounts shared by every asset instruction, before any instruction-specific ones.
+fn asset_accounts(
+    asset: &Pubkey,
+    collection: Option<Pubkey>,
+    collection_writable: bool,
+    payer: &Pubkey,
+    authority: &Pubkey,
+) -> Vec<AccountMeta> {
+    vec![
+        AccountMeta::new(*asset, false),
+        optional(collection, collection_writable),
+        AccountMeta::new(*payer, true),
+        AccountMeta::new_readonly(*authority, true),
+    ]
+}
+
+fn instruction_data<T: BorshSerialize>(discriminator: u8, args: &T) -> Vec<u8> {
+    let mut data = vec![discriminator];
+    data.extend(args.try_to_vec().expect("instruction args serialize"));
+    data
+}
+
+pub fn update_v1(
+    asset: &Pubkey,
+    collection: Option<Pubkey>,
+    payer: &Pubkey,
+    authority: &Pubkey,
+    new_name: Option<String>,
+    new_uri: Option<String>,
+) -> Instruction {
+    let mut accounts = asset_accounts(asset, collection, false, payer, authority);
+    accounts.push(AccountMeta::new_readonly(system_program::ID, false));
+    accounts.push(optional(None, false));
+
+    Instruction {
+        program_id: MPL_CORE_PROGRAM_ID,
+        accounts,
+        data: instruction_data(
+            UPDATE_V1,
+            &UpdateV1Args {
+                new_name,
+                new_uri,
+                new_update_authority: None,
+            },
+        ),
+    }
+}
+
+pub fn transfer_v1(
+    asset: &Pubkey,
+    collection: Option<Pubkey>,
+    payer: &Pubkey,
+    authority: &Pubkey,
+    new_owner: &Pubkey,
+) -> Instruction {
+    let mut accounts = asset_accounts(asset, collection, false, payer, authority);
+    accounts.push(AccountMeta::new_readonly(*new_owner, false));
+    accounts.push(AccountMeta::new_readonly(system_program::ID, false));
+    accounts.push(optional(None, false));
+
+    Instruction {
+        program_id: MPL_CORE_PROGRAM_ID,
+        accounts,
+        data: instruction_data(
+            TRANSFER_V1,
+            &CompressionProofArgs {
+                compression_proof: None,
+            },
+        ),
+    }
+}
+
+pub fn burn_v1(
+    asset: &Pubkey,
+    collection: Option<Pubkey>,
+    payer: &Pubkey,
+    authority: &Pubkey,
+) -> Instruction {
+    let mut accounts = asset_accounts(asset, collection, true, payer, authority);
+    accounts.push(AccountMeta::new_readonly(system_program::ID, false));
+    accounts.push(optional(None, false));
+
+    Instruction {
+        program_id: MPL_CORE_PROGRAM_ID,
+        accounts,
+        data: instruction_data(
+            BURN_V1,
+            &CompressionProofArgs {
+                compression_proof: None,
+            },
+        ),
+    }
+}
+
+pub fn add_plugin_v1(
+    asset: &Pubkey,
+    collection: Option<Pubkey>,
+    payer: &Pubkey,
+    authority: &Pubkey,
+    plugin: Plugin,
+    init_authority: Option<Authority>,
+) -> Instruction {
+    let mut accounts = asset_accounts(asset, collection, true, payer, authority);
+    accounts.push(AccountMeta::new_readonly(system_program::ID, false));
+    accounts.push(optional(None, false));
+
+    Instruction {
+        program_id: MPL_CORE_PROGRAM_ID,
+        accounts,
+        data: instruction_data(
+            ADD_PLUGIN_V1,
+            &AddPluginV1Args {
+                plugin,
+                init_authority,
+            },
+        ),
+    }
+}
+
+pub fn remove_plugin_v1(
+    asset: &Pubkey,
+    collection: Option<Pubkey>,
+    payer: &Pubkey,
+    authority: &Pubkey,
+    plugin_type: u8,
+) -> Instruction {
+    let mut accounts = asset_accounts(asset, collection, true, payer, authority);
+    accounts.push(AccountMeta::new_readonly(system_program::ID, false));
+    accounts.push(optional(None, false));
+
+    Instruction {
+        program_id: MPL_CORE_PROGRAM_ID,
+        accounts,
+        data: instruction_data(REMOVE_PLUGIN_V1, &RemovePluginV1Args { plugin_type }),
+    }
+}
+
+pub fn update_plugin_v1(
+    asset: &Pubkey,
+    collection: Option<Pubkey>,
+    payer: &Pubkey,
+    authority: &Pubkey,
+    plugin: Plugin,
+) -> Instruction {
+    let mut accounts = asset_accounts(asset, collection, true, payer, authority);
+    accounts.push(AccountMeta::new_readonly(system_program::ID, false));
+    accounts.push(optional(None, false));
+
+    Instruction {
+        program_id: MPL_CORE_PROGRAM_ID,
+        accounts,
+        data: instruction_data(UPDATE_PLUGIN_V1, &UpdatePluginV1Args { plugin }),
+    }
+}
+
+/// Update a plugin on the collection itself, which applies to every asset in it.
+pub fn update_collection_plugin_v1(
+    collection: &Pubkey,
+    payer: &Pubkey,
+    authority: &Pubkey,
+    plugin: Plugin,
+) -> Instruction {
+    Instruction {
+        program_id: MPL_CORE_PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new(*collection, false),
+            AccountMeta::new(*payer, true),
+            AccountMeta::new_readonly(*authority, true),
+            AccountMeta::new_readonly(system_program::ID, false),
+            optional(None, false),
+        ],
+        data: instruction_data(UPDATE_COLLECTION_PLUGIN_V1, &UpdatePluginV1Args { plugin }),
+    }
+}
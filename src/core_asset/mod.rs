@@ -0,0 +1,216 @@
+mod assets;
+mod data;
+mod decode;
+mod instructions;
+mod plugins;
+
+pub use assets::*;
+pub use data::*;
+pub use decode::*;
+pub use instructions::*;
+pub use plugins::*;
+
+use std::path::PathBuf;
+
+use anyhow::{anyhow, Result};
+use metaboss_lib::data::Priority;
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::{instruction::Instruction, pubkey::Pubkey, signature::Keypair};
+use structopt::StructOpt;
+
+use crate::pipeline::Pipeline;
+
+#[derive(Debug, StructOpt)]
+pub enum CoreSubcommands {
+    /// Decode an MPL Core asset or collection and its plugins into JSON
+    #[structopt(name = "decode")]
+    Decode {
+        /// Asset or collection address
+        account: Pubkey,
+
+        /// Directory to write the JSON file to, prints to stdout if not set
+        #[structopt(short, long)]
+        output: Option<PathBuf>,
+    },
+    /// Update the URI of an MPL Core asset
+    #[structopt(name = "update-uri")]
+    UpdateUri {
+        /// Path to the update authority keypair file
+        #[structopt(short, long)]
+        keypair: Option<String>,
+
+        /// Asset address
+        #[structopt(short, long)]
+        asset: Pubkey,
+
+        /// New URI
+        #[structopt(short = "u", long)]
+        new_uri: String,
+
+        /// Priority of the transaction: higher priority costs more.
+        /// See https://metaboss.rs/priority-fees.html for more details.
+        #[structopt(short = "P", long, default_value = "none")]
+        priority: Priority,
+    },
+    /// Transfer an MPL Core asset to a new owner
+    #[structopt(name = "transfer")]
+    Transfer {
+        /// Path to the owner or transfer delegate keypair file
+        #[structopt(short, long)]
+        keypair: Option<String>,
+
+        /// Asset address
+        #[structopt(short, long)]
+        asset: Pubkey,
+
+        /// Wallet to transfer the asset to
+        #[structopt(short = "R", long)]
+        receiver: Pubkey,
+
+        /// Priority of the transaction: higher priority costs more.
+        /// See https://metaboss.rs/priority-fees.html for more details.
+        #[structopt(short = "P", long, default_value = "none")]
+        priority: Priority,
+    },
+    /// Burn an MPL Core asset
+    #[structopt(name = "burn")]
+    Burn {
+        /// Path to the owner or burn delegate keypair file
+        #[structopt(short, long)]
+        keypair: Option<String>,
+
+        /// Asset address
+        #[structopt(short, long)]
+        asset: Pubkey,
+
+        /// Priority of the transaction: higher priority costs more.
+        /// See https://metaboss.rs/priority-fees.html for more details.
+        #[structopt(short = "P", long, default_value = "none")]
+        priority: Priority,
+    },
+    /// Add a plugin to an MPL Core asset
+    #[structopt(name = "add-plugin")]
+    AddPlugin {
+        /// Path to the keypair file of the plugin's default authority
+        #[structopt(short, long)]
+        keypair: Option<String>,
+
+        /// Asset address
+        #[structopt(short, long)]
+        asset: Pubkey,
+
+        /// JSON file with the plugin, e.g. { "FreezeDelegate": { "frozen": false } }
+        #[structopt(short = "p", long)]
+        plugin: PathBuf,
+
+        /// Priority of the transaction: higher priority costs more.
+        /// See https://metaboss.rs/priority-fees.html for more details.
+        #[structopt(short = "P", long, default_value = "none")]
+        priority: Priority,
+    },
+    /// Remove a plugin from an MPL Core asset
+    #[structopt(name = "remove-plugin")]
+    RemovePlugin {
+        /// Path to the plugin authority keypair file
+        #[structopt(short, long)]
+        keypair: Option<String>,
+
+        /// Asset address
+        #[structopt(short, long)]
+        asset: Pubkey,
+
+        /// Plugin type to remove, e.g. FreezeDelegate
+        #[structopt(short = "p", long)]
+        plugin_type: String,
+
+        /// Priority of the transaction: higher priority costs more.
+        /// See https://metaboss.rs/priority-fees.html for more details.
+        #[structopt(short = "P", long, default_value = "none")]
+        priority: Priority,
+    },
+}
+
+pub fn process_core(client: RpcClient, commands: CoreSubcommands) -> Result<()> {
+    match commands {
+        CoreSubcommands::Decode { account, output } => {
+            decode_core_account(&client, &account, output)
+        }
+        CoreSubcommands::UpdateUri {
+            keypair,
+            asset,
+            new_uri,
+            priority,
+        } => update_core_uri(CoreUpdateUriArgs {
+            client,
+            keypair,
+            asset,
+            new_uri,
+            priority,
+        }),
+        CoreSubcommands::Transfer {
+            keypair,
+            asset,
+            receiver,
+            priority,
+        } => transfer_core_asset(CoreTransferArgs {
+            client,
+            keypair,
+            asset,
+            receiver,
+            priority,
+        }),
+        CoreSubcommands::Burn {
+            keypair,
+            asset,
+            priority,
+        } => burn_core_asset(CoreBurnArgs {
+            client,
+            keypair,
+            asset,
+            priority,
+        }),
+        CoreSubcommands::AddPlugin {
+            keypair,
+            asset,
+            plugin,
+            priority,
+        } => add_core_plugin(CoreAddPluginArgs {
+            client,
+            keypair,
+            asset,
+            plugin,
+            priority,
+        }),
+        CoreSubcommands::RemovePlugin {
+            keypair,
+            asset,
+            plugin_type,
+            priority,
+        } => remove_core_plugin(CoreRemovePluginArgs {
+            client,
+            keypair,
+            asset,
+            plugin_type,
+            priority,
+        }),
+    }
+}
+
+/// Fetch and decode an asset, checking it's owned by the Core program.
+pub fn fetch_asset(client: &RpcClient, asset: &Pubkey) -> Result<AssetV1> {
+    let account = client.get_account(asset)?;
+    if account.owner != MPL_CORE_PROGRAM_ID {
+        return Err(anyhow!("{asset} is not an MPL Core asset"));
+    }
+    let (asset, _) = decode_asset(&account.data)?;
+    Ok(asset)
+}
+
+fn send(client: &RpcClient, keypair: &Keypair, priority: Priority, ix: Instruction) -> Result<()> {
+    let sig = Pipeline::with_priority(priority)
+        .with_active_lookup_table()?
+        .with_active_nonce()?
+        .run(client, &[keypair], vec![ix])?;
+    println!("Tx sig: {sig}");
+    Ok(())
+}
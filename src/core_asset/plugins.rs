@@ -0,0 +1,67 @@
+use std::{fs::File, path::PathBuf};
+
+use anyhow::{anyhow, Result};
+use metaboss_lib::data::Priority;
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::{pubkey::Pubkey, signer::Signer};
+
+use super::{add_plugin_v1, fetch_asset, plugin_type_index, remove_plugin_v1, send, Plugin};
+use crate::parse::{parse_keypair, parse_solana_config};
+
+pub struct CoreAddPluginArgs {
+    pub client: RpcClient,
+    pub keypair: Option<String>,
+    pub asset: Pubkey,
+    pub plugin: PathBuf,
+    pub priority: Priority,
+}
+
+pub struct CoreRemovePluginArgs {
+    pub client: RpcClient,
+    pub keypair: Option<String>,
+    pub asset: Pubkey,
+    pub plugin_type: String,
+    pub priority: Priority,
+}
+
+pub fn add_core_plugin(args: CoreAddPluginArgs) -> Result<()> {
+    let keypair = parse_keypair(args.keypair, parse_solana_config());
+
+    let f = File::open(&args.plugin)?;
+    let plugin: Plugin = serde_json::from_reader(f)
+        .map_err(|e| anyhow!("Failed to parse {}: {e}", args.plugin.display()))?;
+
+    let asset = fetch_asset(&args.client, &args.asset)?;
+
+    // Leave the authority to the program, which picks the plugin type's default.
+    let ix = add_plugin_v1(
+        &args.asset,
+        asset.collection(),
+        &keypair.pubkey(),
+        &keypair.pubkey(),
+        plugin,
+        None,
+    );
+    send(&args.client, &keypair, args.priority, ix)?;
+    println!("Added plugin to {}", args.asset);
+
+    Ok(())
+}
+
+pub fn remove_core_plugin(args: CoreRemovePluginArgs) -> Result<()> {
+    let keypair = parse_keypair(args.keypair, parse_solana_config());
+    let plugin_type = plugin_type_index(&args.plugin_type)?;
+    let asset = fetch_asset(&args.client, &args.asset)?;
+
+    let ix = remove_plugin_v1(
+        &args.asset,
+        asset.collection(),
+        &keypair.pubkey(),
+        &keypair.pubkey(),
+        plugin_type,
+    );
+    send(&args.client, &keypair, args.priority, ix)?;
+    println!("Removed {} plugin from {}", args.plugin_type, args.asset);
+
+    Ok(())
+}
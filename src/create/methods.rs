@@ -10,6 +10,7 @@ use mpl_token_metadata::{
     instructions::{CreateBuilder, CreateMasterEditionV3Builder},
     types::{CreateArgs, DataV2, TokenStandard},
 };
+use solana_program::program_option::COption;
 use solana_sdk::{
     commitment_config::CommitmentConfig, compute_budget::ComputeBudgetInstruction,
     signature::read_keypair_file, system_instruction::create_account,
@@ -19,7 +20,7 @@ use spl_associated_token_account::{
     instruction::create_associated_token_account,
 };
 use spl_pod::optional_keys::OptionalNonZeroPubkey;
-use spl_token::instruction::mint_to;
+use spl_token::instruction::{mint_to, set_authority, AuthorityType};
 use spl_token_2022::{
     extension::{
         cpi_guard::instruction::enable_cpi_guard,
@@ -43,6 +44,7 @@ use spl_token_metadata_interface::{
     state::{Field, TokenMetadata},
 };
 
+use crate::output::record_transaction;
 use crate::utils::create_token_if_missing_instruction;
 
 use super::*;
@@ -59,6 +61,31 @@ pub struct CreateMetadataArgs {
     pub full_compute: bool,
 }
 
+/// Check the mint exists and the keypair can sign for it as its mint authority, which
+/// Token Metadata requires to create metadata. Returns the mint's token program.
+fn check_mint_authority(client: &RpcClient, mint: &Pubkey, authority: &Pubkey) -> Result<Pubkey> {
+    let account = client
+        .get_account(mint)
+        .map_err(|_| anyhow!("Mint account {mint} not found"))?;
+    if account.owner != spl_token::ID && account.owner != TOKEN_22_PROGRAM_ID {
+        return Err(anyhow!(
+            "{mint} is owned by {}, not a token program",
+            account.owner
+        ));
+    }
+
+    let mint_account = StateWithExtensions::<Mint>::unpack(&account.data)?.base;
+    match mint_account.mint_authority {
+        COption::Some(mint_authority) if mint_authority == *authority => Ok(account.owner),
+        COption::Some(mint_authority) => Err(anyhow!(
+            "Mint authority of {mint} is {mint_authority}, not the keypair {authority}"
+        )),
+        COption::None => Err(anyhow!(
+            "Mint authority of {mint} has been revoked, so Token Metadata can't create metadata for it"
+        )),
+    }
+}
+
 pub fn create_metadata(args: CreateMetadataArgs) -> Result<()> {
     let mint_pubkey = Pubkey::from_str(&args.mint)?;
     let metadata_pubkey = derive_metadata_pda(&mint_pubkey);
@@ -66,6 +93,17 @@ pub fn create_metadata(args: CreateMetadataArgs) -> Result<()> {
     let solana_opts = parse_solana_config();
     let keypair = parse_keypair(args.keypair, solana_opts);
 
+    let token_program = check_mint_authority(&args.client, &mint_pubkey, &keypair.pubkey())?;
+    let existing = args
+        .client
+        .get_account_with_commitment(&metadata_pubkey, args.client.commitment())?
+        .value;
+    if existing.is_some() {
+        return Err(anyhow!(
+            "{mint_pubkey} already has metadata at {metadata_pubkey}, use `metaboss update` to change it"
+        ));
+    }
+
     let f = File::open(args.metadata)?;
     let data: FungibleFields = serde_json::from_reader(f)?;
 
@@ -102,6 +140,7 @@ pub fn create_metadata(args: CreateMetadataArgs) -> Result<()> {
         .authority(keypair.pubkey())
         .payer(keypair.pubkey())
         .update_authority(keypair.pubkey(), true)
+        .spl_token_program(token_program)
         .create_args(create_args)
         .instruction();
 
@@ -117,8 +156,9 @@ pub fn create_metadata(args: CreateMetadataArgs) -> Result<()> {
 
     if !args.full_compute {
         // Only set the compute unit limit if we're not doing a full compute
-        let compute_units = get_compute_units(&args.client, &[create_ix.clone()], &[&keypair])?
-            .unwrap_or(DEFAULT_COMPUTE_UNITS);
+        let compute_units =
+            get_compute_units(&args.client, std::slice::from_ref(&create_ix), &[&keypair])?
+                .unwrap_or(DEFAULT_COMPUTE_UNITS);
 
         instructions.push(ComputeBudgetInstruction::set_compute_unit_limit(
             compute_units as u32,
@@ -146,6 +186,8 @@ pub struct CreateFungibleArgs {
     pub decimals: u8,
     pub initial_supply: Option<f64>,
     pub immutable: bool,
+    pub revoke_mint_authority: bool,
+    pub revoke_freeze_authority: bool,
     pub priority: Priority,
     pub full_compute: bool,
 }
@@ -251,6 +293,12 @@ pub fn parse_pubkey(pubkey_str: &str) -> Result<Pubkey> {
 }
 
 pub fn create_fungible(args: CreateFungibleArgs) -> Result<()> {
+    if args.revoke_mint_authority && args.initial_supply.is_none() {
+        return Err(anyhow!(
+            "Revoking the mint authority without an initial supply would leave the token with no supply"
+        ));
+    }
+
     let solana_opts = parse_solana_config();
     let keypair = parse_keypair(args.keypair, solana_opts);
 
@@ -322,6 +370,28 @@ pub fn create_fungible(args: CreateFungibleArgs) -> Result<()> {
         instructions.push(mint_to_ix);
     }
 
+    // Token Metadata makes the keypair both the mint and freeze authority.
+    if args.revoke_mint_authority {
+        instructions.push(set_authority(
+            &spl_token::ID,
+            &mint.pubkey(),
+            None,
+            AuthorityType::MintTokens,
+            &keypair.pubkey(),
+            &[],
+        )?);
+    }
+    if args.revoke_freeze_authority {
+        instructions.push(set_authority(
+            &spl_token::ID,
+            &mint.pubkey(),
+            None,
+            AuthorityType::FreezeAccount,
+            &keypair.pubkey(),
+            &[],
+        )?);
+    }
+
     let signers = vec![&keypair, &mint];
 
     let micro_lamports = match args.priority {
@@ -352,10 +422,17 @@ pub fn create_fungible(args: CreateFungibleArgs) -> Result<()> {
     instructions.splice(0..0, extra_instructions);
 
     let sig = send_and_confirm_tx(&args.client, &signers, &instructions)?;
+    record_transaction(&sig, &instructions);
 
     println!("Signature: {sig}");
     println!("Mint: {}", mint.pubkey());
     println!("Metadata: {metadata_pubkey}");
+    if args.revoke_mint_authority {
+        println!("Mint authority revoked");
+    }
+    if args.revoke_freeze_authority {
+        println!("Freeze authority revoked");
+    }
 
     Ok(())
 }
@@ -687,6 +764,7 @@ pub fn create_fungible_22(args: CreateFungible22Args) -> Result<()> {
     instructions.splice(0..0, extra_instructions);
 
     let sig = send_and_confirm_tx(&args.client, &signers, &instructions)?;
+    record_transaction(&sig, &instructions);
 
     println!("Signature: {sig}");
     println!("Mint: {}", mint.pubkey());
@@ -827,6 +905,7 @@ pub fn create_fungible_22_token(args: CreateFungible22TokenArgs) -> Result<()> {
         || args.client.send_and_confirm_transaction(&tx),
     );
     let sig = res?;
+    record_transaction(&sig, &final_instructions);
 
     println!(
         "Token: {:?} created successfully!",
@@ -876,8 +955,8 @@ pub fn create_master_edition(args: CreateMasterEditionArgs) -> Result<()> {
 
     let signers = vec![&keypair, &mint_authority];
 
-    let compute_units =
-        get_compute_units(&args.client, &[ix.clone()], &signers)?.unwrap_or(DEFAULT_COMPUTE_UNITS);
+    let compute_units = get_compute_units(&args.client, std::slice::from_ref(&ix), &signers)?
+        .unwrap_or(DEFAULT_COMPUTE_UNITS);
 
     let micro_lamports = match args.priority {
         Priority::None => 20,
@@ -908,6 +987,7 @@ pub fn create_master_edition(args: CreateMasterEditionArgs) -> Result<()> {
     );
 
     let sig = res?;
+    record_transaction(&sig, &instructions);
     println!("Signature: {sig}");
     println!("Edition: {edition_pubkey}");
 
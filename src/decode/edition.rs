@@ -0,0 +1,326 @@
+use anyhow::{anyhow, Result};
+use borsh::BorshDeserialize;
+use metaboss_lib::derive::{derive_edition_marker_pda, derive_edition_pda};
+use mpl_token_metadata::{
+    accounts::{Edition, EditionMarker, MasterEdition},
+    types::Key,
+    ID as TOKEN_METADATA_PROGRAM_ID,
+};
+use serde::Serialize;
+use solana_client::rpc_config::RpcTransactionConfig;
+use solana_sdk::{pubkey::Pubkey, signature::Signature};
+use solana_transaction_status::UiTransactionEncoding;
+
+use crate::history::crawl_signatures;
+
+use super::*;
+
+// Each V1 edition marker tracks 31 bytes worth of edition numbers.
+const EDITIONS_PER_MARKER: u64 = 248;
+
+#[derive(Debug, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum EditionKind {
+    Master,
+    Print,
+    None,
+}
+
+/// Edition fields computed from the edition and edition marker accounts, along with
+/// anything about them that doesn't add up.
+#[derive(Debug, Serialize)]
+pub struct EditionInfo {
+    pub kind: EditionKind,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub supply: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_supply: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub supply_exhausted: Option<bool>,
+    /// Editions marked as printed across the master's edition marker accounts.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub marked_editions: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub edition_number: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub parent_edition: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub parent_mint: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub marker_consistent: Option<bool>,
+    pub anomalies: Vec<String>,
+}
+
+impl EditionInfo {
+    fn new(kind: EditionKind) -> Self {
+        Self {
+            kind,
+            supply: None,
+            max_supply: None,
+            supply_exhausted: None,
+            marked_editions: None,
+            edition_number: None,
+            parent_edition: None,
+            parent_mint: None,
+            marker_consistent: None,
+            anomalies: vec![],
+        }
+    }
+}
+
+/// The single edition marker pNFT masters use instead of one marker per 248 editions.
+fn derive_edition_marker_v2_pda(mint: &Pubkey) -> Pubkey {
+    let seeds = &[
+        "metadata".as_bytes(),
+        TOKEN_METADATA_PROGRAM_ID.as_ref(),
+        mint.as_ref(),
+        "edition".as_bytes(),
+        "marker".as_bytes(),
+    ];
+    Pubkey::find_program_address(seeds, &TOKEN_METADATA_PROGRAM_ID).0
+}
+
+fn is_marked(ledger: &[u8], index: u64) -> bool {
+    ledger
+        .get((index / 8) as usize)
+        .map(|byte| byte & (0b1000_0000 >> (index % 8)) != 0)
+        .unwrap_or(false)
+}
+
+fn count_marked(ledger: &[u8]) -> u64 {
+    ledger.iter().map(|byte| byte.count_ones() as u64).sum()
+}
+
+fn marker_v2_ledger(data: &[u8]) -> Result<Vec<u8>> {
+    // Skip the account key, the rest is the borsh encoded ledger.
+    let mut data = data
+        .get(1..)
+        .ok_or_else(|| anyhow!("Edition marker account is empty"))?;
+    Ok(Vec::<u8>::deserialize(&mut data)?)
+}
+
+/// Count the editions marked as printed across every edition marker of a master.
+fn count_marked_editions(client: &RpcClient, mint: &Pubkey, supply: u64) -> Result<u64> {
+    if let Some(account) = client
+        .get_account_with_commitment(&derive_edition_marker_v2_pda(mint), client.commitment())?
+        .value
+    {
+        return Ok(count_marked(&marker_v2_ledger(&account.data)?));
+    }
+
+    // Burned prints leave gaps, so keep going past the supply until a whole batch of
+    // markers is missing.
+    let mut marked = 0;
+    let mut marker_num = 0;
+    loop {
+        let pdas: Vec<Pubkey> = (marker_num..marker_num + MAX_MULTIPLE_ACCOUNTS as u64)
+            .map(|num| derive_edition_marker_pda(mint, num * EDITIONS_PER_MARKER))
+            .collect();
+        let accounts = client.get_multiple_accounts(&pdas)?;
+
+        let mut found = false;
+        for account in accounts.into_iter().flatten() {
+            let marker = EditionMarker::deserialize(&mut account.data.as_slice())?;
+            marked += count_marked(&marker.ledger);
+            found = true;
+        }
+
+        marker_num += MAX_MULTIPLE_ACCOUNTS as u64;
+        if !found && marker_num * EDITIONS_PER_MARKER > supply {
+            return Ok(marked);
+        }
+    }
+}
+
+/// Find the mint whose edition account is `edition` among the accounts of the transaction
+/// that created `account`, since edition accounts don't store their mint.
+///
+/// The transaction that created a print's edition account has both the print mint and the
+/// master mint in it.
+pub fn find_edition_mint_in_creation(
+    client: &RpcClient,
+    account: &Pubkey,
+    edition: &Pubkey,
+) -> Result<Pubkey> {
+    let signatures = crawl_signatures(client, account, None)?;
+    let creation = signatures
+        .first()
+        .ok_or_else(|| anyhow!("No transactions found for {account}"))?;
+
+    let tx = client.get_transaction_with_config(
+        &Signature::from_str(&creation.signature)?,
+        RpcTransactionConfig {
+            encoding: Some(UiTransactionEncoding::Base64),
+            commitment: Some(client.commitment()),
+            max_supported_transaction_version: Some(0),
+        },
+    )?;
+    let versioned = tx
+        .transaction
+        .transaction
+        .decode()
+        .ok_or_else(|| anyhow!("Failed to decode transaction {}", creation.signature))?;
+
+    versioned
+        .message
+        .static_account_keys()
+        .iter()
+        .find(|key| derive_edition_pda(key) == *edition)
+        .copied()
+        .ok_or_else(|| anyhow!("Mint not found in transaction {}", creation.signature))
+}
+
+fn master_edition_info(client: &RpcClient, mint: &Pubkey, master: MasterEdition) -> EditionInfo {
+    let mut info = EditionInfo::new(EditionKind::Master);
+    info.supply = Some(master.supply);
+    info.max_supply = master.max_supply;
+    info.supply_exhausted = Some(master.max_supply == Some(master.supply));
+
+    if let Some(max_supply) = master.max_supply {
+        if master.supply > max_supply {
+            info.anomalies.push(format!(
+                "supply {} is greater than max supply {max_supply}",
+                master.supply
+            ));
+        }
+    }
+
+    match count_marked_editions(client, mint, master.supply) {
+        Ok(marked) => {
+            info.marked_editions = Some(marked);
+            info.marker_consistent = Some(marked == master.supply);
+            if marked != master.supply {
+                info.anomalies.push(format!(
+                    "{marked} editions are marked as printed but supply is {}",
+                    master.supply
+                ));
+            }
+        }
+        Err(e) => info
+            .anomalies
+            .push(format!("failed to read edition markers: {e}")),
+    }
+
+    info
+}
+
+fn print_edition_info(client: &RpcClient, edition_pda: &Pubkey, edition: Edition) -> EditionInfo {
+    let mut info = EditionInfo::new(EditionKind::Print);
+    info.edition_number = Some(edition.edition);
+    info.parent_edition = Some(edition.parent.to_string());
+
+    let parent_account = match client
+        .get_account_with_commitment(&edition.parent, client.commitment())
+        .map(|response| response.value)
+    {
+        Ok(Some(account)) => account,
+        Ok(None) => {
+            info.anomalies
+                .push("parent master edition account no longer exists".to_string());
+            return info;
+        }
+        Err(e) => {
+            info.anomalies
+                .push(format!("failed to fetch parent master edition: {e}"));
+            return info;
+        }
+    };
+
+    match MasterEdition::deserialize(&mut parent_account.data.as_slice()) {
+        Ok(master) => {
+            info.supply = Some(master.supply);
+            info.max_supply = master.max_supply;
+            info.supply_exhausted = Some(master.max_supply == Some(master.supply));
+            if let Some(max_supply) = master.max_supply {
+                if edition.edition > max_supply {
+                    info.anomalies.push(format!(
+                        "edition number {} is greater than the parent's max supply {max_supply}",
+                        edition.edition
+                    ));
+                }
+            }
+        }
+        Err(e) => info
+            .anomalies
+            .push(format!("failed to decode parent master edition: {e}")),
+    }
+
+    let parent_mint = match find_edition_mint_in_creation(client, edition_pda, &edition.parent) {
+        Ok(mint) => mint,
+        Err(e) => {
+            info.anomalies.push(format!(
+                "couldn't find the parent mint to check markers: {e}"
+            ));
+            return info;
+        }
+    };
+    info.parent_mint = Some(parent_mint.to_string());
+
+    let marked = match client
+        .get_account_with_commitment(
+            &derive_edition_marker_v2_pda(&parent_mint),
+            client.commitment(),
+        )
+        .map(|response| response.value)
+    {
+        Ok(Some(account)) => {
+            marker_v2_ledger(&account.data).map(|ledger| is_marked(&ledger, edition.edition))
+        }
+        Ok(None) => client
+            .get_account_with_commitment(
+                &derive_edition_marker_pda(&parent_mint, edition.edition),
+                client.commitment(),
+            )
+            .map_err(Into::into)
+            .and_then(|response| match response.value {
+                Some(account) => {
+                    let marker = EditionMarker::deserialize(&mut account.data.as_slice())?;
+                    Ok(is_marked(
+                        &marker.ledger,
+                        edition.edition % EDITIONS_PER_MARKER,
+                    ))
+                }
+                None => Ok(false),
+            }),
+        Err(e) => Err(e.into()),
+    };
+
+    match marked {
+        Ok(marked) => {
+            info.marker_consistent = Some(marked);
+            if !marked {
+                info.anomalies.push(format!(
+                    "edition {} is not marked as printed in the parent's edition marker",
+                    edition.edition
+                ));
+            }
+        }
+        Err(e) => info
+            .anomalies
+            .push(format!("failed to read edition marker: {e}")),
+    }
+
+    info
+}
+
+/// Work out whether a mint is a master or print edition and check its edition accounts
+/// agree with each other.
+pub fn edition_info(client: &RpcClient, mint: &Pubkey) -> Result<EditionInfo> {
+    let edition_pda = derive_edition_pda(mint);
+    let account = match rpc_cache::get_account(client, &edition_pda)? {
+        Some(account) => account,
+        None => return Ok(EditionInfo::new(EditionKind::None)),
+    };
+
+    match account.data.first() {
+        Some(key) if *key == Key::MasterEditionV2 as u8 || *key == Key::MasterEditionV1 as u8 => {
+            let master = MasterEdition::deserialize(&mut account.data.as_slice())?;
+            Ok(master_edition_info(client, mint, master))
+        }
+        Some(key) if *key == Key::EditionV1 as u8 => {
+            let edition = Edition::deserialize(&mut account.data.as_slice())?;
+            Ok(print_edition_info(client, &edition_pda, edition))
+        }
+        _ => Err(anyhow!("Unrecognized edition account {edition_pda}")),
+    }
+}
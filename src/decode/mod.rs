@@ -1,6 +1,6 @@
 use anyhow::{anyhow, Result as AnyResult};
 use indicatif::ParallelProgressIterator;
-use log::{debug, error, info};
+use log::{debug, error, info, warn};
 use metaboss_lib::data::NftData;
 use metaboss_lib::decode::{
     decode_bpf_loader_upgradeable_state, decode_edition_from_mint, decode_edition_marker_from_mint,
@@ -10,17 +10,23 @@ use mpl_token_metadata::accounts::Metadata;
 use rayon::prelude::*;
 use retry::{delay::Exponential, retry};
 use serde::Serialize;
+use serde_json::Value;
 use solana_client::rpc_client::RpcClient;
 use solana_sdk::pubkey::Pubkey;
 use std::fs::File;
+use std::io::Write;
 use std::str::FromStr;
 
 use crate::constants::*;
 use crate::errors::*;
+use crate::files::read_json;
 use crate::limiter::create_default_rate_limiter;
 use crate::parse::is_only_one_option;
+use crate::rpc_cache;
 
+mod edition;
 mod rule_set;
+pub use edition::*;
 pub use rule_set::*;
 
 #[derive(Debug, Serialize)]
@@ -48,14 +54,57 @@ pub struct JSONUses {
     pub total: u64,
 }
 
+/// Write a mint's metadata as JSON, optionally in full, with its computed edition info and
+/// with the off-chain JSON its URI points to.
+pub fn write_metadata<W: Write>(
+    client: &RpcClient,
+    writer: W,
+    metadata: Metadata,
+    full: bool,
+    with_edition_info: bool,
+    with_off_chain: bool,
+) -> AnyResult<()> {
+    let info = if with_edition_info {
+        Some(edition_info(client, &metadata.mint)?)
+    } else {
+        None
+    };
+    let uri = metadata.uri.trim_matches(char::from(0)).to_string();
+
+    let mut value = if full {
+        serde_json::to_value(&metadata)?
+    } else {
+        serde_json::to_value(NftData::from(metadata))?
+    };
+    if let Some(info) = info {
+        value["edition_info"] = serde_json::to_value(info)?;
+    }
+    if with_off_chain {
+        // A dead URI is worth knowing about, so it's reported in the output rather than
+        // failing the decode.
+        match rpc_cache::get_json(&uri) {
+            Ok(json) => value["off_chain"] = json,
+            Err(err) => {
+                warn!("Failed to fetch off-chain JSON from {uri}: {err}");
+                value["off_chain"] = Value::Null;
+                value["off_chain_error"] = Value::String(err.to_string());
+            }
+        }
+    }
+    serde_json::to_writer_pretty(writer, &value)?;
+
+    Ok(())
+}
+
 pub fn decode_metadata_all(
     client: &RpcClient,
     json_file: &str,
     full: bool,
+    with_edition_info: bool,
+    with_off_chain: bool,
     output: &str,
 ) -> AnyResult<()> {
-    let file = File::open(json_file)?;
-    let mint_accounts: Vec<String> = serde_json::from_reader(file)?;
+    let mint_accounts: Vec<String> = read_json(json_file)?;
     let use_rate_limit = *USE_RATE_LIMIT.read().unwrap();
     let handle = create_default_rate_limiter();
 
@@ -108,28 +157,18 @@ pub fn decode_metadata_all(
             };
 
             debug!("Writing to file for mint account: {}", mint_account);
-            if full {
-                match serde_json::to_writer_pretty(&mut file, &metadata) {
-                    Ok(_) => {}
-                    Err(err) => {
-                        error!(
-                            "Failed to write to JSON file for mint account: {}, error: {}",
-                            mint_account, err
-                        );
-                    }
-                }
-            } else {
-                let data = NftData::from(metadata);
-
-                match serde_json::to_writer_pretty(&mut file, &data) {
-                    Ok(_) => {}
-                    Err(err) => {
-                        error!(
-                            "Failed to write to JSON file for mint account: {}, error: {}",
-                            mint_account, err
-                        );
-                    }
-                }
+            if let Err(err) = write_metadata(
+                client,
+                &mut file,
+                metadata,
+                full,
+                with_edition_info,
+                with_off_chain,
+            ) {
+                error!(
+                    "Failed to write to JSON file for mint account: {}, error: {}",
+                    mint_account, err
+                );
             }
         });
 
@@ -140,6 +179,9 @@ pub fn decode_master_edition(client: &RpcClient, mint_account: &str) -> AnyResul
     let master_edition = decode_master_edition_from_mint(client, mint_account)?;
     println!("{master_edition:?}");
 
+    let info = edition_info(client, &Pubkey::from_str(mint_account)?)?;
+    println!("{}", serde_json::to_string_pretty(&info)?);
+
     Ok(())
 }
 
@@ -147,6 +189,9 @@ pub fn decode_print_edition(client: &RpcClient, mint_account: &str) -> AnyResult
     let print_edition = decode_edition_from_mint(client, mint_account)?;
     println!("{print_edition:?}");
 
+    let info = edition_info(client, &Pubkey::from_str(mint_account)?)?;
+    println!("{}", serde_json::to_string_pretty(&info)?);
+
     Ok(())
 }
 
@@ -170,12 +215,15 @@ pub fn decode_edition_marker(
     Ok(())
 }
 
+#[allow(clippy::too_many_arguments)]
 pub fn decode_metadata_from_mint(
     client: &RpcClient,
     account: Option<&String>,
     full: bool,
     list_path: Option<&String>,
     raw: bool,
+    with_edition_info: bool,
+    with_off_chain: bool,
     output: &str,
 ) -> AnyResult<()> {
     // Explicitly warn the user if they provide incorrect options combinations
@@ -196,16 +244,24 @@ pub fn decode_metadata_from_mint(
         metadata.uri = metadata.uri.replace('\u{0}', "");
         metadata.symbol = metadata.symbol.replace('\u{0}', "");
 
-        let mut file = File::create(format!("{output}/{mint_account}.json"))?;
-
-        if full {
-            serde_json::to_writer_pretty(&mut file, &metadata)?;
-        } else {
-            let data = NftData::from(metadata);
-            serde_json::to_writer_pretty(&mut file, &data)?;
-        }
+        let file = File::create(format!("{output}/{mint_account}.json"))?;
+        write_metadata(
+            client,
+            file,
+            metadata,
+            full,
+            with_edition_info,
+            with_off_chain,
+        )?;
     } else if let Some(list_path) = list_path {
-        decode_metadata_all(client, list_path, full, output)?;
+        decode_metadata_all(
+            client,
+            list_path,
+            full,
+            with_edition_info,
+            with_off_chain,
+            output,
+        )?;
     } else {
         return Err(anyhow!(
             "Please specify either a mint account or a list of mint accounts, but not both."
@@ -274,7 +330,7 @@ pub fn decode(client: &RpcClient, mint_account: &str) -> Result<Metadata, Decode
 
     let account_data = match retry(
         Exponential::from_millis_with_factor(250, 2.0).take(3),
-        || client.get_account_data(&metadata_pda),
+        || rpc_cache::get_account_data(client, &metadata_pda),
     ) {
         Ok(data) => data,
         Err(err) => {
@@ -286,6 +342,59 @@ pub fn decode(client: &RpcClient, mint_account: &str) -> Result<Metadata, Decode
         .map_err(|e| DecodeError::DecodeMetadataFailed(e.to_string()))
 }
 
+/// Fetch and decode the metadata for a list of mints, batching the account lookups.
+/// Mints without a valid metadata account are returned with the error.
+pub fn decode_metadata_batch(
+    client: &RpcClient,
+    mint_accounts: &[String],
+) -> Vec<(String, Result<Metadata, DecodeError>)> {
+    let mut results = Vec::with_capacity(mint_accounts.len());
+
+    for chunk in mint_accounts.chunks(MAX_MULTIPLE_ACCOUNTS) {
+        let mut pdas = Vec::with_capacity(chunk.len());
+        let mut valid = Vec::with_capacity(chunk.len());
+
+        for mint in chunk {
+            match Pubkey::from_str(mint) {
+                Ok(pubkey) => {
+                    pdas.push(get_metadata_pda(pubkey));
+                    valid.push(mint);
+                }
+                Err(_) => results.push((
+                    mint.clone(),
+                    Err(DecodeError::PubkeyParseFailed(mint.clone())),
+                )),
+            }
+        }
+
+        let accounts = match retry(
+            Exponential::from_millis_with_factor(250, 2.0).take(3),
+            || rpc_cache::get_multiple_accounts(client, &pdas),
+        ) {
+            Ok(accounts) => accounts,
+            Err(err) => {
+                for mint in valid {
+                    results.push((mint.clone(), Err(DecodeError::NetworkError(err.to_string()))));
+                }
+                continue;
+            }
+        };
+
+        for (mint, account) in valid.into_iter().zip(accounts) {
+            let md = match account {
+                Some(account) => Metadata::safe_deserialize(account.data.as_slice())
+                    .map_err(|e| DecodeError::DecodeMetadataFailed(e.to_string())),
+                None => Err(DecodeError::DecodeMetadataFailed(String::from(
+                    "Metadata account not found",
+                ))),
+            };
+            results.push((mint.clone(), md));
+        }
+    }
+
+    results
+}
+
 pub fn process_decode_bpf_loader_upgradable_state(
     client: &RpcClient,
     address: &str,
@@ -0,0 +1,397 @@
+//! Approve and revoke delegates on assets: Token Metadata's token and metadata delegate roles,
+//! and plain SPL token delegates.
+//!
+//! Token delegates (transfer, utility, staking, sale, standard) act on the holder's tokens and
+//! are approved by the holder. Metadata delegates (collection, data) act on the metadata and
+//! are approved by the update authority.
+
+use std::{collections::HashMap, fmt, path::PathBuf, str::FromStr};
+
+use anyhow::{anyhow, Result};
+use metaboss_lib::{
+    data::Priority,
+    delegate::{delegate_asset_ix, DelegateAssetArgs},
+    revoke::{revoke_asset_ix, RevokeAssetArgs},
+};
+use mpl_token_metadata::types::{DelegateArgs, RevokeArgs};
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::{
+    instruction::Instruction,
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+};
+use structopt::StructOpt;
+
+use crate::{
+    checkpoint,
+    files::{read_json, write_json},
+    order,
+    pack::{pack_instruction_groups, send_packed, InstructionGroup},
+    parse::{is_only_one_option, parse_keypair, parse_solana_config},
+    pipeline::Pipeline,
+    spinner::create_progress_bar,
+    utils::{get_associated_token_address_for_mint, get_token_program},
+    workspace::output_path,
+};
+
+// Estimates of the compute units each instruction uses, for packing batches.
+const TOKEN_METADATA_DELEGATE_COMPUTE_UNITS: u32 = 80_000;
+const SPL_DELEGATE_COMPUTE_UNITS: u32 = 5_000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DelegateRole {
+    Transfer,
+    Utility,
+    Staking,
+    Sale,
+    Standard,
+    Collection,
+    Data,
+    Spl,
+}
+
+impl FromStr for DelegateRole {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "transfer" => Ok(DelegateRole::Transfer),
+            "utility" => Ok(DelegateRole::Utility),
+            "staking" => Ok(DelegateRole::Staking),
+            "sale" => Ok(DelegateRole::Sale),
+            "standard" => Ok(DelegateRole::Standard),
+            "collection" => Ok(DelegateRole::Collection),
+            "data" => Ok(DelegateRole::Data),
+            "spl" => Ok(DelegateRole::Spl),
+            _ => Err(format!(
+                "Invalid delegate role {s}, expected one of transfer, utility, staking, sale, standard, collection, data or spl"
+            )),
+        }
+    }
+}
+
+impl fmt::Display for DelegateRole {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let role = match self {
+            DelegateRole::Transfer => "transfer",
+            DelegateRole::Utility => "utility",
+            DelegateRole::Staking => "staking",
+            DelegateRole::Sale => "sale",
+            DelegateRole::Standard => "standard",
+            DelegateRole::Collection => "collection",
+            DelegateRole::Data => "data",
+            DelegateRole::Spl => "spl",
+        };
+        write!(f, "{role}")
+    }
+}
+
+#[derive(Debug, StructOpt)]
+pub enum DelegateSubcommands {
+    /// Approve a delegate on one mint or a list of mints
+    #[structopt(name = "approve")]
+    Approve {
+        /// Path to the holder's keypair, or the update authority's for collection and data delegates
+        #[structopt(short, long)]
+        keypair: Option<String>,
+
+        /// Delegate role: transfer, utility, staking, sale, standard, collection, data or spl
+        #[structopt(long)]
+        role: DelegateRole,
+
+        /// Address to approve as the delegate
+        #[structopt(short, long)]
+        delegate: Pubkey,
+
+        /// Mint to approve the delegate on
+        #[structopt(short = "a", long)]
+        mint: Option<String>,
+
+        /// Path to a JSON list of mints to approve the delegate on
+        #[structopt(short = "L", long)]
+        mint_list: Option<PathBuf>,
+
+        /// Amount of tokens the delegate can act on, for token delegates
+        #[structopt(long, default_value = "1")]
+        amount: u64,
+
+        /// Priority of the transaction: higher priority costs more.
+        /// See https://metaboss.rs/priority-fees.html for more details.
+        #[structopt(short = "P", long, default_value = "none")]
+        priority: Priority,
+    },
+    /// Revoke a delegate on one mint or a list of mints
+    #[structopt(name = "revoke")]
+    Revoke {
+        /// Path to the holder's keypair, or the update authority's for collection and data delegates
+        #[structopt(short, long)]
+        keypair: Option<String>,
+
+        /// Delegate role: transfer, utility, staking, sale, standard, collection, data or spl
+        #[structopt(long)]
+        role: DelegateRole,
+
+        /// Address of the delegate to revoke
+        #[structopt(short, long)]
+        delegate: Pubkey,
+
+        /// Mint to revoke the delegate on
+        #[structopt(short = "a", long)]
+        mint: Option<String>,
+
+        /// Path to a JSON list of mints to revoke the delegate on
+        #[structopt(short = "L", long)]
+        mint_list: Option<PathBuf>,
+
+        /// Priority of the transaction: higher priority costs more.
+        /// See https://metaboss.rs/priority-fees.html for more details.
+        #[structopt(short = "P", long, default_value = "none")]
+        priority: Priority,
+    },
+}
+
+pub struct DelegateActionArgs {
+    pub client: RpcClient,
+    pub keypair: Option<String>,
+    pub role: DelegateRole,
+    pub delegate: Pubkey,
+    pub mint: Option<String>,
+    pub mint_list: Option<PathBuf>,
+    /// Tokens to delegate when approving, `None` when revoking.
+    pub approve_amount: Option<u64>,
+    pub priority: Priority,
+}
+
+pub fn process_delegate(client: RpcClient, commands: DelegateSubcommands) -> Result<()> {
+    match commands {
+        DelegateSubcommands::Approve {
+            keypair,
+            role,
+            delegate,
+            mint,
+            mint_list,
+            amount,
+            priority,
+        } => delegate_mints(DelegateActionArgs {
+            client,
+            keypair,
+            role,
+            delegate,
+            mint,
+            mint_list,
+            approve_amount: Some(amount),
+            priority,
+        }),
+        DelegateSubcommands::Revoke {
+            keypair,
+            role,
+            delegate,
+            mint,
+            mint_list,
+            priority,
+        } => delegate_mints(DelegateActionArgs {
+            client,
+            keypair,
+            role,
+            delegate,
+            mint,
+            mint_list,
+            approve_amount: None,
+            priority,
+        }),
+    }
+}
+
+fn approve_args(role: DelegateRole, amount: u64) -> DelegateArgs {
+    match role {
+        DelegateRole::Transfer => DelegateArgs::TransferV1 {
+            amount,
+            authorization_data: None,
+        },
+        DelegateRole::Utility => DelegateArgs::UtilityV1 {
+            amount,
+            authorization_data: None,
+        },
+        DelegateRole::Staking => DelegateArgs::StakingV1 {
+            amount,
+            authorization_data: None,
+        },
+        DelegateRole::Sale => DelegateArgs::SaleV1 {
+            amount,
+            authorization_data: None,
+        },
+        DelegateRole::Standard => DelegateArgs::StandardV1 { amount },
+        DelegateRole::Collection => DelegateArgs::CollectionV1 {
+            authorization_data: None,
+        },
+        DelegateRole::Data => DelegateArgs::DataV1 {
+            authorization_data: None,
+        },
+        DelegateRole::Spl => unreachable!("SPL delegates don't go through Token Metadata"),
+    }
+}
+
+fn revoke_args(role: DelegateRole) -> RevokeArgs {
+    match role {
+        DelegateRole::Transfer => RevokeArgs::TransferV1,
+        DelegateRole::Utility => RevokeArgs::UtilityV1,
+        DelegateRole::Staking => RevokeArgs::StakingV1,
+        DelegateRole::Sale => RevokeArgs::SaleV1,
+        DelegateRole::Standard => RevokeArgs::StandardV1,
+        DelegateRole::Collection => RevokeArgs::CollectionV1,
+        DelegateRole::Data => RevokeArgs::DataV1,
+        DelegateRole::Spl => unreachable!("SPL delegates don't go through Token Metadata"),
+    }
+}
+
+/// The approve or revoke instruction for a single mint.
+fn build_delegate_ix(
+    client: &RpcClient,
+    keypair: &Keypair,
+    args: &DelegateActionArgs,
+    mint: &str,
+) -> Result<Instruction> {
+    let mint_pubkey = Pubkey::from_str(mint).map_err(|_| anyhow!("Invalid mint: {mint}"))?;
+
+    // Token delegates act on the holder's token account, metadata delegates don't use one.
+    let token = match args.role {
+        DelegateRole::Collection | DelegateRole::Data => None,
+        _ => Some(get_associated_token_address_for_mint(
+            client,
+            &keypair.pubkey(),
+            &mint_pubkey,
+        )?),
+    };
+
+    if args.role == DelegateRole::Spl {
+        let token_program = get_token_program(client, &mint_pubkey)?;
+        // Always set for token delegates above.
+        let token = token.unwrap();
+        let ix = match args.approve_amount {
+            Some(amount) => spl_token_2022::instruction::approve(
+                &token_program,
+                &token,
+                &args.delegate,
+                &keypair.pubkey(),
+                &[],
+                amount,
+            )?,
+            None => {
+                spl_token_2022::instruction::revoke(&token_program, &token, &keypair.pubkey(), &[])?
+            }
+        };
+        return Ok(ix);
+    }
+
+    let ix = match args.approve_amount {
+        Some(amount) => delegate_asset_ix(
+            client,
+            DelegateAssetArgs::V1 {
+                payer: None,
+                authority: keypair,
+                mint: mint_pubkey,
+                token,
+                delegate: args.delegate,
+                delegate_args: approve_args(args.role, amount),
+            },
+        )?,
+        None => revoke_asset_ix(
+            client,
+            RevokeAssetArgs::V1 {
+                payer: None,
+                authority: keypair,
+                mint: mint_pubkey,
+                token,
+                delegate: args.delegate,
+                revoke_args: revoke_args(args.role),
+            },
+        )?,
+    };
+    Ok(ix)
+}
+
+/// Approve or revoke the delegate on a single mint, or on every mint in the list.
+pub fn delegate_mints(args: DelegateActionArgs) -> Result<()> {
+    if !is_only_one_option(&args.mint, &args.mint_list) {
+        return Err(anyhow!("Please specify either a mint or a mint list"));
+    }
+    let keypair = parse_keypair(args.keypair.clone(), parse_solana_config());
+    let action = match args.approve_amount {
+        Some(_) => "Approved",
+        None => "Revoked",
+    };
+
+    if let Some(mint) = &args.mint {
+        let ix = build_delegate_ix(&args.client, &keypair, &args, mint)?;
+        let signature = Pipeline::with_priority(args.priority.clone()).run(
+            &args.client,
+            &[&keypair],
+            vec![ix],
+        )?;
+        println!(
+            "{action} {} delegate {} on {mint}",
+            args.role, args.delegate
+        );
+        println!("Signature: {signature}");
+        return Ok(());
+    }
+
+    let command = match args.approve_amount {
+        Some(_) => "delegate-approve",
+        None => "delegate-revoke",
+    };
+    // Checked above that there is a mint list when there's no mint.
+    let mint_list: Vec<String> = read_json(args.mint_list.as_ref().unwrap())?;
+    let mints = order::ordered(checkpoint::remaining(command, mint_list));
+    let compute_units = match args.role {
+        DelegateRole::Spl => SPL_DELEGATE_COMPUTE_UNITS,
+        _ => TOKEN_METADATA_DELEGATE_COMPUTE_UNITS,
+    };
+
+    let mut errors = HashMap::new();
+    let mut groups = Vec::with_capacity(mints.len());
+    let pb = create_progress_bar("Building instructions...", mints.len() as u64);
+    for mint in mints {
+        match build_delegate_ix(&args.client, &keypair, &args, &mint) {
+            Ok(ix) => groups.push(InstructionGroup {
+                key: mint,
+                instructions: vec![ix],
+                compute_units,
+            }),
+            Err(e) => {
+                checkpoint::record_failed(command, &mint, &e);
+                errors.insert(mint, e.to_string());
+            }
+        }
+        pb.inc(1);
+    }
+    pb.finish_and_clear();
+
+    let packed = pack_instruction_groups(&args.client, &keypair.pubkey(), groups)?;
+    let (sent, failed) = send_packed(&args.client, &[&keypair], &args.priority, packed)?;
+    let mut done = 0;
+    for (mints, signature) in sent {
+        for mint in mints {
+            checkpoint::record_done(command, &mint, Some(signature.to_string()));
+            done += 1;
+        }
+    }
+    for (mints, e) in failed {
+        for mint in mints {
+            checkpoint::record_failed(command, &mint, &e);
+            errors.insert(mint, e.to_string());
+        }
+    }
+
+    println!(
+        "{action} {} delegate {} on {done} mints",
+        args.role, args.delegate
+    );
+    if !errors.is_empty() {
+        let path = output_path("mb-delegate-errors.json");
+        write_json(&path, &errors)?;
+        println!("{} mints failed, see {}", errors.len(), path.display());
+    }
+
+    Ok(())
+}
@@ -1,7 +1,10 @@
-use crate::constants::{MASTER_EDITION_PREFIX, METADATA_PREFIX, USER_PREFIX};
+use crate::constants::{BURN_PREFIX, MASTER_EDITION_PREFIX, METADATA_PREFIX, USER_PREFIX};
 use crate::update::{parse_keypair, parse_solana_config};
 use metaboss_lib::derive::{derive_collection_delegate_pda, derive_token_record_pda};
-use mpl_token_metadata::ID;
+use mpl_token_metadata::{
+    accounts::MetadataDelegateRecord, hooked::MetadataDelegateRoleSeed,
+    types::MetadataDelegateRole, ID,
+};
 use solana_sdk::pubkey::Pubkey;
 use solana_sdk::signer::Signer;
 use spl_associated_token_account::get_associated_token_address_with_program_id;
@@ -83,15 +86,77 @@ pub fn get_cmv3_pda(candy_machine_id: String) {
     println!("{}", derive_cmv3_pda(&pubkey));
 }
 
-pub fn get_token_record_pda(mint_account: String, token_account: String) {
+/// Derive the token record from the token account, or from the owner's associated token
+/// account when only the owner is given.
+pub fn get_token_record_pda(
+    mint_account: String,
+    token_account: Option<String>,
+    owner: Option<String>,
+) {
     let mint_pubkey =
         Pubkey::from_str(&mint_account).expect("Failed to parse pubkey from mint account!");
-    let token_pubkey =
-        Pubkey::from_str(&token_account).expect("Failed to parse pubkey from token account!");
+    let token_pubkey = match (token_account, owner) {
+        (Some(token_account), None) => {
+            Pubkey::from_str(&token_account).expect("Failed to parse pubkey from token account!")
+        }
+        (None, Some(owner)) => {
+            let owner = Pubkey::from_str(&owner).expect("Failed to parse pubkey from owner!");
+            derive_token_account_pda(&mint_pubkey, &owner, &token_program_id)
+        }
+        _ => {
+            eprintln!("Please specify either a token account or an owner, but not both.");
+            std::process::exit(1);
+        }
+    };
 
     println!("{}", derive_token_record_pda(&mint_pubkey, &token_pubkey));
 }
 
+pub fn get_collection_authority_record_pda(mint: Pubkey, collection_authority: Pubkey) {
+    let (pda, _) = derive_collection_authority_record(&mint, &collection_authority);
+    println!("{pda}");
+}
+
+pub fn get_use_authority_record_pda(mint: Pubkey, use_authority: Pubkey) {
+    let (pda, _) = derive_use_authority_record(&mint, &use_authority);
+    println!("{pda}");
+}
+
+pub fn get_metadata_delegate_record_pda(
+    mint: Pubkey,
+    role: String,
+    update_authority: Pubkey,
+    delegate: Pubkey,
+) {
+    let role = parse_metadata_delegate_role(&role).unwrap_or_else(|e| {
+        eprintln!("{e}");
+        std::process::exit(1);
+    });
+    let (pda, _) = MetadataDelegateRecord::find_pda(
+        &mint,
+        MetadataDelegateRoleSeed::from(role),
+        &update_authority,
+        &delegate,
+    );
+    println!("{pda}");
+}
+
+fn parse_metadata_delegate_role(role: &str) -> Result<MetadataDelegateRole, String> {
+    match role {
+        "authority-item" => Ok(MetadataDelegateRole::AuthorityItem),
+        "collection" => Ok(MetadataDelegateRole::Collection),
+        "collection-item" => Ok(MetadataDelegateRole::CollectionItem),
+        "data" => Ok(MetadataDelegateRole::Data),
+        "data-item" => Ok(MetadataDelegateRole::DataItem),
+        "programmable-config" => Ok(MetadataDelegateRole::ProgrammableConfig),
+        "programmable-config-item" => Ok(MetadataDelegateRole::ProgrammableConfigItem),
+        "use" => Ok(MetadataDelegateRole::Use),
+        _ => Err(format!(
+            "Invalid metadata delegate role {role}, expected one of authority-item, collection, collection-item, data, data-item, programmable-config, programmable-config-item or use"
+        )),
+    }
+}
+
 pub fn get_collection_delegate(mint: Pubkey, authority: Pubkey, delegate: Pubkey) {
     println!(
         "{:?}",
@@ -201,6 +266,19 @@ pub fn derive_use_authority_record(mint: &Pubkey, use_authority: &Pubkey) -> (Pu
     Pubkey::find_program_address(use_authority_seeds, &metaplex_pubkey)
 }
 
+/// The Token Metadata PDA passed as the burner when approving a use authority.
+pub fn derive_use_burner_pda() -> Pubkey {
+    let metaplex_pubkey = ID;
+
+    let seeds = &[
+        METADATA_PREFIX.as_bytes(),
+        metaplex_pubkey.as_ref(),
+        BURN_PREFIX.as_bytes(),
+    ];
+    let (pda, _bump) = Pubkey::find_program_address(seeds, &metaplex_pubkey);
+    pda
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -0,0 +1,256 @@
+use std::{collections::HashSet, fs, path::PathBuf, str::FromStr};
+
+use anyhow::{anyhow, Result};
+use futures::stream::{self, StreamExt};
+use indexmap::IndexMap;
+use mpl_token_metadata::accounts::Metadata;
+use reqwest::{header::CONTENT_TYPE, Client};
+use serde::Serialize;
+use serde_json::Value;
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::pubkey::Pubkey;
+
+use crate::{
+    checkpoint, decode::get_metadata_pda, files::read_json, limiter::concurrency_or, order,
+    rpc_cache, spinner::create_progress_bar,
+};
+
+const DOWNLOAD_ASSETS_COMMAND: &str = "download-assets";
+
+// Downloads in flight at once when --concurrency isn't set.
+const DEFAULT_DOWNLOADS: usize = 10;
+
+pub struct DownloadAssetsArgs {
+    pub client: RpcClient,
+    pub mint_list: String,
+    pub output: PathBuf,
+    pub ipfs_gateway: String,
+}
+
+/// The files written for one mint, relative to the output directory.
+#[derive(Debug, Default, Serialize)]
+pub struct DownloadedAsset {
+    pub uri: String,
+    pub json: Option<String>,
+    pub image: Option<String>,
+    pub animation: Option<String>,
+    pub files: Vec<String>,
+    pub error: Option<String>,
+}
+
+/// HTTP URL for a URI, resolving `ar://` through arweave.net and `ipfs://` through the gateway.
+pub fn to_http_uri(uri: &str, ipfs_gateway: &str) -> String {
+    if let Some(id) = uri.strip_prefix("ar://") {
+        format!("https://arweave.net/{id}")
+    } else if let Some(path) = uri.strip_prefix("ipfs://") {
+        let path = path.strip_prefix("ipfs/").unwrap_or(path);
+        format!("{}/ipfs/{path}", ipfs_gateway.trim_end_matches('/'))
+    } else {
+        uri.to_string()
+    }
+}
+
+/// File extension for a download, from the URI when it has one, like `image.png` or
+/// Arweave's `?ext=png`, and otherwise from the content type.
+fn extension(uri: &str, content_type: Option<&str>) -> String {
+    let (path, query) = uri.split_once('?').unwrap_or((uri, ""));
+    let from_query = query
+        .split('&')
+        .find_map(|param| param.strip_prefix("ext="));
+    let from_path = path
+        .rsplit('/')
+        .next()
+        .and_then(|name| name.rsplit_once('.'))
+        .map(|(_, ext)| ext);
+
+    if let Some(ext) = from_query.or(from_path) {
+        if !ext.is_empty() && ext.len() <= 5 && ext.chars().all(|c| c.is_ascii_alphanumeric()) {
+            return ext.to_lowercase();
+        }
+    }
+
+    let subtype = content_type
+        .and_then(|c| c.split(';').next())
+        .and_then(|c| c.trim().split_once('/'))
+        .map(|(_, subtype)| subtype.to_lowercase());
+    match subtype.as_deref() {
+        Some("jpeg") => "jpg".to_string(),
+        Some("svg+xml") => "svg".to_string(),
+        Some("gltf-binary") => "glb".to_string(),
+        Some("quicktime") => "mov".to_string(),
+        Some(subtype) if subtype.chars().all(|c| c.is_ascii_alphanumeric()) => subtype.to_string(),
+        _ => "bin".to_string(),
+    }
+}
+
+async fn fetch(http: &Client, uri: &str, ipfs_gateway: &str) -> Result<(Vec<u8>, Option<String>)> {
+    let response = http.get(to_http_uri(uri, ipfs_gateway)).send().await?;
+    if !response.status().is_success() {
+        return Err(anyhow!("Failed to download {uri}: {}", response.status()));
+    }
+    let content_type = response
+        .headers()
+        .get(CONTENT_TYPE)
+        .and_then(|c| c.to_str().ok())
+        .map(str::to_string);
+    Ok((response.bytes().await?.to_vec(), content_type))
+}
+
+/// Download a file the JSON links to as `<name>.<ext>`, returning the file name.
+async fn download_file(
+    http: &Client,
+    uri: &str,
+    name: &str,
+    args: &DownloadAssetsArgs,
+) -> Result<String> {
+    let (bytes, content_type) = fetch(http, uri, &args.ipfs_gateway).await?;
+    let file = format!("{name}.{}", extension(uri, content_type.as_deref()));
+    fs::write(args.output.join(&file), bytes)?;
+    Ok(file)
+}
+
+async fn download_asset(
+    http: &Client,
+    mint: &str,
+    uri: String,
+    args: &DownloadAssetsArgs,
+) -> DownloadedAsset {
+    let mut asset = DownloadedAsset {
+        uri,
+        ..Default::default()
+    };
+    if let Err(e) = download_asset_files(http, mint, &mut asset, args).await {
+        asset.error = Some(e.to_string());
+    }
+    asset
+}
+
+async fn download_asset_files(
+    http: &Client,
+    mint: &str,
+    asset: &mut DownloadedAsset,
+    args: &DownloadAssetsArgs,
+) -> Result<()> {
+    // The JSON is saved exactly as served, so it can be re-uploaded byte for byte.
+    let (bytes, _) = fetch(http, &asset.uri, &args.ipfs_gateway).await?;
+    let json: Value = serde_json::from_slice(&bytes)
+        .map_err(|e| anyhow!("{} is not valid JSON: {e}", asset.uri))?;
+    let file = format!("{mint}.json");
+    fs::write(args.output.join(&file), &bytes)?;
+    asset.json = Some(file);
+
+    let mut seen = HashSet::new();
+    if let Some(uri) = json.get("image").and_then(Value::as_str) {
+        seen.insert(uri.to_string());
+        asset.image = Some(download_file(http, uri, mint, args).await?);
+    }
+    if let Some(uri) = json.get("animation_url").and_then(Value::as_str) {
+        if seen.insert(uri.to_string()) {
+            let name = format!("{mint}_animation");
+            asset.animation = Some(download_file(http, uri, &name, args).await?);
+        }
+    }
+
+    // Other files the JSON lists, skipping the ones already downloaded as the image or
+    // animation.
+    let files = json
+        .get("properties")
+        .and_then(|p| p.get("files"))
+        .and_then(Value::as_array);
+    for uri in files
+        .into_iter()
+        .flatten()
+        .filter_map(|f| f.get("uri")?.as_str())
+    {
+        if seen.insert(uri.to_string()) {
+            let name = format!("{mint}_file{}", asset.files.len() + 1);
+            asset
+                .files
+                .push(download_file(http, uri, &name, args).await?);
+        }
+    }
+
+    Ok(())
+}
+
+/// Download the off-chain JSON of every mint in the list along with the image, animation
+/// and other files it links to, named by mint.
+pub async fn download_assets(args: DownloadAssetsArgs) -> Result<()> {
+    let mint_list: Vec<String> = read_json(&args.mint_list)?;
+    let mint_list = order::ordered(checkpoint::remaining(DOWNLOAD_ASSETS_COMMAND, mint_list));
+    fs::create_dir_all(&args.output)?;
+
+    let mut errors: IndexMap<String, String> = IndexMap::new();
+    let mut uris = vec![];
+    let mut pdas = vec![];
+    for mint in &mint_list {
+        match Pubkey::from_str(mint) {
+            Ok(pubkey) => pdas.push((mint.clone(), get_metadata_pda(pubkey))),
+            Err(_) => {
+                errors.insert(mint.clone(), "Invalid mint address".to_string());
+            }
+        }
+    }
+    let keys: Vec<Pubkey> = pdas.iter().map(|(_, pda)| *pda).collect();
+    let accounts = rpc_cache::get_multiple_accounts(&args.client, &keys)?;
+    for ((mint, _), account) in pdas.into_iter().zip(accounts) {
+        match account.and_then(|a| Metadata::safe_deserialize(&a.data).ok()) {
+            Some(md) => uris.push((mint, md.uri.trim_matches(char::from(0)).to_string())),
+            None => {
+                errors.insert(mint, "Metadata account not found".to_string());
+            }
+        }
+    }
+
+    let http = Client::new();
+    let pb = create_progress_bar("Downloading assets...", uris.len() as u64);
+    let downloads: Vec<(String, DownloadedAsset)> = stream::iter(uris)
+        .map(|(mint, uri)| {
+            let http = &http;
+            let args = &args;
+            let pb = &pb;
+            async move {
+                let asset = download_asset(http, &mint, uri, args).await;
+                pb.inc(1);
+                (mint, asset)
+            }
+        })
+        .buffer_unordered(concurrency_or(DEFAULT_DOWNLOADS))
+        .collect()
+        .await;
+    pb.finish_and_clear();
+
+    let mut manifest: IndexMap<String, DownloadedAsset> = IndexMap::new();
+    for (mint, asset) in downloads {
+        match &asset.error {
+            Some(error) => {
+                checkpoint::record_failed(DOWNLOAD_ASSETS_COMMAND, &mint, error);
+                errors.insert(mint.clone(), error.clone());
+            }
+            None => checkpoint::record_done(DOWNLOAD_ASSETS_COMMAND, &mint, None),
+        }
+        manifest.insert(mint, asset);
+    }
+    manifest.sort_keys();
+
+    let manifest_path = args.output.join("mb-download-manifest.json");
+    serde_json::to_writer_pretty(fs::File::create(&manifest_path)?, &manifest)?;
+    println!(
+        "Downloaded the assets of {} mints to {}",
+        manifest.len() - manifest.values().filter(|a| a.error.is_some()).count(),
+        args.output.display()
+    );
+    println!("Manifest written to {}", manifest_path.display());
+
+    if !errors.is_empty() {
+        let errors_path = args.output.join("mb-download-errors.json");
+        serde_json::to_writer_pretty(fs::File::create(&errors_path)?, &errors)?;
+        println!(
+            "{} mints failed to download. Errors written to {}",
+            errors.len(),
+            errors_path.display()
+        );
+    }
+
+    Ok(())
+}
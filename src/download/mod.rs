@@ -0,0 +1,48 @@
+mod assets;
+
+pub use assets::*;
+
+use std::path::PathBuf;
+
+use anyhow::Result;
+use solana_client::rpc_client::RpcClient;
+use structopt::StructOpt;
+
+use crate::workspace::output_path;
+
+#[derive(Debug, StructOpt)]
+pub enum DownloadSubcommands {
+    /// Download the off-chain JSON, images and animations of a list of mints
+    #[structopt(name = "assets")]
+    Assets {
+        /// Path to the mint list file
+        #[structopt(short = "L", long)]
+        mint_list: String,
+
+        /// Directory to write the files to
+        #[structopt(short, long, default_value = "assets")]
+        output: PathBuf,
+
+        /// IPFS gateway to download ipfs:// URIs through
+        #[structopt(long, default_value = "https://ipfs.io")]
+        ipfs_gateway: String,
+    },
+}
+
+pub async fn process_download(client: RpcClient, commands: DownloadSubcommands) -> Result<()> {
+    match commands {
+        DownloadSubcommands::Assets {
+            mint_list,
+            output,
+            ipfs_gateway,
+        } => {
+            download_assets(DownloadAssetsArgs {
+                client,
+                mint_list,
+                output: output_path(output),
+                ipfs_gateway,
+            })
+            .await
+        }
+    }
+}
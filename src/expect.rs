@@ -0,0 +1,228 @@
+//! Optimistic concurrency check from the global `--expect-file` option.
+//!
+//! Before a mutating batch command sends anything, the current metadata of each mint is
+//! compared to the state an earlier snapshot expects. Mints that changed in the meantime,
+//! because another operator or a bot updated them, are skipped or abort the whole run
+//! instead of having those changes overwritten.
+//!
+//! The snapshot is either a directory of `<mint>.json` files as written by `decode mint`,
+//! a JSON object of mint to metadata, or a JSON list of metadata objects with a `mint`
+//! field. Only the fields the snapshot has are compared, so it can be trimmed down to the
+//! fields that matter.
+
+use std::{
+    collections::HashMap,
+    fmt::{self, Display},
+    fs::File,
+    path::Path,
+    str::FromStr,
+    sync::RwLock,
+};
+
+use anyhow::{anyhow, Result};
+use log::warn;
+use once_cell::sync::Lazy;
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::checkpoint;
+use crate::files::read_json;
+use crate::workspace::output_path;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OnMismatch {
+    Skip,
+    Abort,
+}
+
+impl FromStr for OnMismatch {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "skip" => Ok(OnMismatch::Skip),
+            "abort" => Ok(OnMismatch::Abort),
+            _ => Err(format!(
+                "Invalid mismatch action: {s}, expected skip or abort"
+            )),
+        }
+    }
+}
+
+impl Display for OnMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            OnMismatch::Skip => write!(f, "skip"),
+            OnMismatch::Abort => write!(f, "abort"),
+        }
+    }
+}
+
+struct Expected {
+    state: HashMap<String, Value>,
+    on_mismatch: OnMismatch,
+}
+
+static EXPECTED: Lazy<RwLock<Option<Expected>>> = Lazy::new(|| RwLock::new(None));
+
+#[derive(Debug, Serialize)]
+pub struct FieldMismatch {
+    /// Path of the field in the metadata, e.g. `creators[1].verified`.
+    pub field: String,
+    pub expected: Value,
+    pub current: Value,
+}
+
+#[derive(Debug, Serialize)]
+pub struct MintMismatch {
+    pub mint: String,
+    pub fields: Vec<FieldMismatch>,
+}
+
+fn read_expected(path: &Path) -> Result<HashMap<String, Value>> {
+    if path.is_dir() {
+        let mut state = HashMap::new();
+        for entry in std::fs::read_dir(path)? {
+            let file = entry?.path();
+            let name = file
+                .file_name()
+                .and_then(|n| n.to_str())
+                .unwrap_or_default();
+            let mint = match name.split_once('.') {
+                Some((mint, "json" | "json.zst")) => mint.to_string(),
+                _ => continue,
+            };
+            state.insert(mint, read_json(&file)?);
+        }
+        return Ok(state);
+    }
+
+    match read_json(path)? {
+        Value::Object(state) => Ok(state.into_iter().collect()),
+        Value::Array(items) => items
+            .into_iter()
+            .map(|item| {
+                let mint = item
+                    .get("mint")
+                    .and_then(Value::as_str)
+                    .ok_or_else(|| anyhow!("Every item of the expected list needs a mint"))?;
+                Ok((mint.to_string(), item))
+            })
+            .collect(),
+        _ => Err(anyhow!(
+            "Expected file must be a directory, an object of mint to metadata or a list"
+        )),
+    }
+}
+
+/// Load the expected state, checked by mutating batch commands from now on.
+pub fn load_expected<P: AsRef<Path>>(path: P, on_mismatch: OnMismatch) -> Result<()> {
+    let path = path.as_ref();
+    let state = read_expected(path)
+        .map_err(|e| anyhow!("Failed to read expected state {}: {e}", path.display()))?;
+    *EXPECTED.write().unwrap() = Some(Expected { state, on_mismatch });
+    Ok(())
+}
+
+// On-chain strings are padded with null bytes, which snapshots may not have.
+fn trimmed(value: &Value) -> Value {
+    match value {
+        Value::String(s) => Value::String(s.trim_end_matches('\0').to_string()),
+        value => value.clone(),
+    }
+}
+
+/// Every field of `expected` whose current value differs. Fields only `current` has are
+/// ignored.
+fn compare(path: &str, expected: &Value, current: &Value, mismatches: &mut Vec<FieldMismatch>) {
+    match (expected, current) {
+        (Value::Object(e), Value::Object(c)) => {
+            for (key, expected) in e {
+                let field = if path.is_empty() {
+                    key.to_string()
+                } else {
+                    format!("{path}.{key}")
+                };
+                compare(
+                    &field,
+                    expected,
+                    c.get(key).unwrap_or(&Value::Null),
+                    mismatches,
+                );
+            }
+        }
+        (Value::Array(e), Value::Array(c)) if e.len() == c.len() => {
+            for (i, (e, c)) in e.iter().zip(c).enumerate() {
+                compare(&format!("{path}[{i}]"), e, c, mismatches);
+            }
+        }
+        _ if trimmed(expected) != trimmed(current) => mismatches.push(FieldMismatch {
+            field: path.to_string(),
+            expected: expected.clone(),
+            current: current.clone(),
+        }),
+        _ => {}
+    }
+}
+
+/// Drop the mints whose current metadata doesn't match the expected state, or fail if the
+/// run should abort on a mismatch. Mints missing from the snapshot count as mismatches.
+/// Does nothing without `--expect-file`.
+pub fn unchanged(
+    command: &str,
+    mints: Vec<String>,
+    current: &HashMap<String, Value>,
+) -> Result<Vec<String>> {
+    let guard = EXPECTED.read().unwrap();
+    let expected = match guard.as_ref() {
+        Some(expected) => expected,
+        None => return Ok(mints),
+    };
+
+    let mut matching = vec![];
+    let mut mismatched = vec![];
+    for mint in mints {
+        let mut fields = vec![];
+        match (expected.state.get(&mint), current.get(&mint)) {
+            (Some(e), Some(c)) => compare("", e, c, &mut fields),
+            (e, c) => fields.push(FieldMismatch {
+                field: String::new(),
+                expected: e.cloned().unwrap_or(Value::Null),
+                current: c.cloned().unwrap_or(Value::Null),
+            }),
+        }
+        if fields.is_empty() {
+            matching.push(mint);
+        } else {
+            mismatched.push(MintMismatch { mint, fields });
+        }
+    }
+
+    if mismatched.is_empty() {
+        return Ok(matching);
+    }
+
+    let report = output_path(format!("mb-expect-mismatches-{command}.json"))
+        .display()
+        .to_string();
+    serde_json::to_writer_pretty(File::create(&report)?, &mismatched)?;
+
+    if expected.on_mismatch == OnMismatch::Abort {
+        return Err(anyhow!(
+            "{} mints don't match the expected state, nothing was sent. See {report}",
+            mismatched.len()
+        ));
+    }
+
+    // Recorded as failed so a re-run with a fresh snapshot picks them up again.
+    for mismatch in &mismatched {
+        warn!("Skipping {}, it changed since the snapshot", mismatch.mint);
+        checkpoint::record_failed(command, &mismatch.mint, "changed since the snapshot");
+    }
+    println!(
+        "Skipping {} mints that changed since the snapshot, see {report}",
+        mismatched.len()
+    );
+
+    Ok(matching)
+}
@@ -0,0 +1,32 @@
+use std::{fs::File, path::PathBuf};
+
+use anyhow::Result;
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::pubkey::Pubkey;
+
+use crate::{history::crawl_signatures, spinner::create_spinner};
+
+pub struct ExportActivityArgs {
+    pub client: RpcClient,
+    pub address: Pubkey,
+    pub out: PathBuf,
+    pub store: PathBuf,
+}
+
+/// Write every transaction signature of an account, oldest first, crawling the history in
+/// pages that are stored so an interrupted export picks up where it stopped.
+pub fn export_activity(args: ExportActivityArgs) -> Result<()> {
+    let spinner = create_spinner("Fetching signatures...");
+    let signatures = crawl_signatures(&args.client, &args.address, Some(&args.store))?;
+    spinner.finish_and_clear();
+
+    serde_json::to_writer_pretty(File::create(&args.out)?, &signatures)?;
+    println!(
+        "Exported {} signatures for {} to {}",
+        signatures.len(),
+        args.address,
+        args.out.display()
+    );
+
+    Ok(())
+}
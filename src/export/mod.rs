@@ -0,0 +1,70 @@
+mod activity;
+mod site;
+
+pub use activity::*;
+pub use site::*;
+
+use std::path::PathBuf;
+
+use anyhow::Result;
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::pubkey::Pubkey;
+use structopt::StructOpt;
+
+#[derive(Debug, StructOpt)]
+pub enum ExportSubcommands {
+    /// Export the full transaction signature history of an account
+    #[structopt(name = "activity")]
+    Activity {
+        /// Account to export the history of, e.g. a collection creator or a mint
+        #[structopt(short, long)]
+        address: Pubkey,
+
+        /// File to write the signatures to
+        #[structopt(short, long, default_value = "activity.json")]
+        out: PathBuf,
+
+        /// Directory crawl progress is stored in, so an interrupted export can resume
+        #[structopt(long, default_value = ".mb-history")]
+        store: PathBuf,
+    },
+    /// Export a collection's items, holders, traits and provenance as a static site
+    #[structopt(name = "site")]
+    Site {
+        /// Collection mint or MPL Core collection address
+        #[structopt(short, long)]
+        collection: Pubkey,
+
+        /// Directory to write the site to
+        #[structopt(short, long, default_value = "site")]
+        out: PathBuf,
+
+        /// DAS API url to read the collection from, defaults to the RPC url
+        #[structopt(long)]
+        das_url: Option<String>,
+    },
+}
+
+pub fn process_export(client: RpcClient, commands: ExportSubcommands) -> Result<()> {
+    match commands {
+        ExportSubcommands::Activity {
+            address,
+            out,
+            store,
+        } => export_activity(ExportActivityArgs {
+            client,
+            address,
+            out,
+            store,
+        }),
+        ExportSubcommands::Site {
+            collection,
+            out,
+            das_url,
+        } => export_site(ExportSiteArgs {
+            das_url: das_url.unwrap_or_else(|| client.url()),
+            collection,
+            out,
+        }),
+    }
+}
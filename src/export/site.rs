@@ -0,0 +1,329 @@
+use std::{
+    collections::BTreeMap,
+    fs::{self, File},
+    path::{Path, PathBuf},
+};
+
+use anyhow::{anyhow, Result};
+use serde::Serialize;
+use serde_json::{json, Value};
+use solana_sdk::pubkey::Pubkey;
+
+use crate::{
+    cnft::das_request,
+    snapshot::{DasResult, Item},
+    spinner::create_spinner,
+};
+
+pub struct ExportSiteArgs {
+    pub das_url: String,
+    pub collection: Pubkey,
+    pub out: PathBuf,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SiteAttribute {
+    pub trait_type: String,
+    pub value: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SiteCreator {
+    pub address: String,
+    pub share: u8,
+    pub verified: bool,
+}
+
+/// Where an item comes from and who controls it, so anyone can check it against the chain.
+#[derive(Debug, Serialize)]
+pub struct Provenance {
+    pub update_authority: Option<String>,
+    pub creators: Vec<SiteCreator>,
+    pub collection: Option<String>,
+    pub royalty_basis_points: Option<u64>,
+    pub mutable: bool,
+    pub compressed: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SiteItem {
+    pub id: String,
+    pub name: String,
+    pub image: Option<String>,
+    pub json_uri: Option<String>,
+    pub owner: String,
+    pub frozen: bool,
+    pub attributes: Vec<SiteAttribute>,
+    pub provenance: Provenance,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SiteHolder {
+    pub owner: String,
+    pub count: usize,
+    pub items: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SiteSummary {
+    pub collection: String,
+    pub generated_at: String,
+    pub item_count: usize,
+    pub holder_count: usize,
+    pub trait_count: usize,
+}
+
+fn value_string(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+fn str_field(value: &Value, pointer: &str) -> Option<String> {
+    value
+        .pointer(pointer)
+        .and_then(Value::as_str)
+        .map(String::from)
+}
+
+impl From<&Item> for SiteItem {
+    fn from(item: &Item) -> Self {
+        let attributes = item
+            .content
+            .pointer("/metadata/attributes")
+            .and_then(Value::as_array)
+            .map(|attributes| {
+                attributes
+                    .iter()
+                    .filter_map(|a| {
+                        Some(SiteAttribute {
+                            trait_type: value_string(a.get("trait_type")?),
+                            value: value_string(a.get("value")?),
+                        })
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        let collection = item.grouping.as_array().and_then(|groups| {
+            groups
+                .iter()
+                .find(|g| g["group_key"] == "collection")
+                .and_then(|g| str_field(g, "/group_value"))
+        });
+
+        SiteItem {
+            id: item.id.clone(),
+            name: str_field(&item.content, "/metadata/name").unwrap_or_default(),
+            image: str_field(&item.content, "/links/image")
+                .or_else(|| str_field(&item.content, "/files/0/uri")),
+            json_uri: str_field(&item.content, "/json_uri").filter(|uri| !uri.is_empty()),
+            owner: item.ownership.owner.clone(),
+            frozen: item.ownership.frozen,
+            attributes,
+            provenance: Provenance {
+                update_authority: item
+                    .authorities
+                    .first()
+                    .and_then(|a| str_field(a, "/address")),
+                creators: item
+                    .creators
+                    .iter()
+                    .map(|c| SiteCreator {
+                        address: c.address.to_string(),
+                        share: c.share,
+                        verified: c.verified,
+                    })
+                    .collect(),
+                collection,
+                royalty_basis_points: item.royalty.get("basis_points").and_then(Value::as_u64),
+                mutable: item.mutable,
+                compressed: item.compression["compressed"].as_bool() == Some(true),
+            },
+        }
+    }
+}
+
+fn collection_items(das_url: &str, collection: &Pubkey) -> Result<Vec<Item>> {
+    let mut items = vec![];
+    let mut page = 1;
+    loop {
+        let result: DasResult = das_request(
+            das_url,
+            "getAssetsByGroup",
+            json!({
+                "groupKey": "collection",
+                "groupValue": collection.to_string(),
+                "page": page,
+                "limit": 1000
+            }),
+        )?;
+        if result.items.is_empty() {
+            break;
+        }
+        items.extend(result.items);
+        page += 1;
+    }
+    Ok(items)
+}
+
+fn write_json<T: Serialize>(path: &Path, value: &T) -> Result<()> {
+    serde_json::to_writer_pretty(File::create(path)?, value)?;
+    Ok(())
+}
+
+/// Export a collection's current state as a static bundle of JSON files and an
+/// `index.html` that renders them, ready to publish on any static host.
+pub fn export_site(args: ExportSiteArgs) -> Result<()> {
+    let spinner = create_spinner("Getting assets...");
+    let items: Vec<SiteItem> = collection_items(&args.das_url, &args.collection)?
+        .iter()
+        .filter(|item| !item.burnt)
+        .map(SiteItem::from)
+        .collect();
+    spinner.finish_and_clear();
+
+    if items.is_empty() {
+        return Err(anyhow!("No assets found in collection {}", args.collection));
+    }
+
+    let mut holders: BTreeMap<&str, Vec<String>> = BTreeMap::new();
+    let mut traits: BTreeMap<&str, BTreeMap<&str, usize>> = BTreeMap::new();
+    for item in &items {
+        holders
+            .entry(item.owner.as_str())
+            .or_default()
+            .push(item.id.clone());
+        for attribute in &item.attributes {
+            *traits
+                .entry(attribute.trait_type.as_str())
+                .or_default()
+                .entry(attribute.value.as_str())
+                .or_default() += 1;
+        }
+    }
+
+    let mut holders: Vec<SiteHolder> = holders
+        .into_iter()
+        .map(|(owner, items)| SiteHolder {
+            owner: owner.to_string(),
+            count: items.len(),
+            items,
+        })
+        .collect();
+    holders.sort_by_key(|holder| std::cmp::Reverse(holder.count));
+
+    let summary = SiteSummary {
+        collection: args.collection.to_string(),
+        generated_at: chrono::Utc::now().to_rfc3339(),
+        item_count: items.len(),
+        holder_count: holders.len(),
+        trait_count: traits.len(),
+    };
+
+    let items_dir = args.out.join("items");
+    fs::create_dir_all(&items_dir)?;
+
+    write_json(&args.out.join("collection.json"), &summary)?;
+    write_json(&args.out.join("items.json"), &items)?;
+    write_json(&args.out.join("holders.json"), &holders)?;
+    write_json(&args.out.join("traits.json"), &traits)?;
+    for item in &items {
+        write_json(&items_dir.join(format!("{}.json", item.id)), item)?;
+    }
+    fs::write(args.out.join("index.html"), INDEX_HTML)?;
+
+    println!(
+        "Exported {} items and {} holders to {}",
+        summary.item_count,
+        summary.holder_count,
+        args.out.display()
+    );
+
+    Ok(())
+}
+
+// Renders the JSON files in the browser, so the bundle needs no build step or backend.
+const INDEX_HTML: &str = r#"<!DOCTYPE html>
+<html lang="en">
+<head>
+<meta charset="utf-8">
+<meta name="viewport" content="width=device-width, initial-scale=1">
+<title>Collection</title>
+<style>
+  body { font-family: system-ui, sans-serif; margin: 2rem; color: #222; }
+  h1 { font-size: 1.4rem; word-break: break-all; }
+  nav a { margin-right: 1rem; cursor: pointer; }
+  .grid { display: grid; grid-template-columns: repeat(auto-fill, minmax(180px, 1fr)); gap: 1rem; }
+  .card { border: 1px solid #ddd; border-radius: 6px; padding: .5rem; font-size: .85rem; word-break: break-all; }
+  .card img { width: 100%; border-radius: 4px; }
+  table { border-collapse: collapse; font-size: .85rem; }
+  td, th { border-bottom: 1px solid #eee; padding: .3rem .6rem; text-align: left; }
+  .muted { color: #777; }
+</style>
+</head>
+<body>
+<h1 id="title">Loading...</h1>
+<p class="muted" id="summary"></p>
+<nav><a data-view="items">Items</a><a data-view="holders">Holders</a><a data-view="traits">Traits</a></nav>
+<div id="view"></div>
+<script>
+const el = (tag, text) => { const e = document.createElement(tag); if (text !== undefined) e.textContent = text; return e; };
+const load = (file) => fetch(file).then((r) => r.json());
+
+function table(headers, rows) {
+  const t = el("table");
+  const head = el("tr");
+  headers.forEach((h) => head.appendChild(el("th", h)));
+  t.appendChild(head);
+  rows.forEach((row) => {
+    const tr = el("tr");
+    row.forEach((cell) => tr.appendChild(el("td", cell)));
+    t.appendChild(tr);
+  });
+  return t;
+}
+
+const views = {
+  items: async () => {
+    const grid = el("div");
+    grid.className = "grid";
+    (await load("items.json")).forEach((item) => {
+      const card = el("div");
+      card.className = "card";
+      if (item.image) { const img = el("img"); img.src = item.image; img.loading = "lazy"; card.appendChild(img); }
+      const link = el("a", item.name || item.id);
+      link.href = "items/" + item.id + ".json";
+      card.appendChild(link);
+      card.appendChild(el("div", "Owner: " + item.owner));
+      grid.appendChild(card);
+    });
+    return grid;
+  },
+  holders: async () => table(["Owner", "Items"], (await load("holders.json")).map((h) => [h.owner, h.count])),
+  traits: async () => {
+    const rows = [];
+    Object.entries(await load("traits.json")).forEach(([type, values]) =>
+      Object.entries(values).forEach(([value, count]) => rows.push([type, value, count])));
+    return table(["Trait", "Value", "Count"], rows);
+  },
+};
+
+async function show(name) {
+  const view = document.getElementById("view");
+  view.replaceChildren(await views[name]());
+}
+
+document.querySelectorAll("nav a").forEach((a) => a.addEventListener("click", () => show(a.dataset.view)));
+
+load("collection.json").then((c) => {
+  document.getElementById("title").textContent = "Collection " + c.collection;
+  document.getElementById("summary").textContent =
+    c.item_count + " items, " + c.holder_count + " holders. Generated " + c.generated_at + ".";
+  show("items");
+});
+</script>
+</body>
+</html>
+"#;
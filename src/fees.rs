@@ -0,0 +1,506 @@
+//! Break down what a run of transactions cost into base fees, priority fees and rent,
+//! grouped by the kind of operation each transaction performed.
+
+use std::{
+    collections::BTreeMap,
+    fs::File,
+    io::{BufRead, Write},
+    path::{Path, PathBuf},
+    str::FromStr,
+    sync::Mutex,
+};
+
+use anyhow::{anyhow, Result};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use solana_client::{rpc_client::RpcClient, rpc_config::RpcTransactionConfig};
+use solana_program::hash::hash;
+use solana_sdk::{
+    compute_budget,
+    instruction::Instruction,
+    native_token::{lamports_to_sol, LAMPORTS_PER_SOL},
+    pubkey::Pubkey,
+    signature::Signature,
+    system_program,
+};
+use solana_transaction_status::UiTransactionEncoding;
+use structopt::StructOpt;
+
+use crate::workspace::output_path;
+use crate::{
+    cm::CANDY_MACHINE_CORE_PROGRAM_ID,
+    cnft::BUBBLEGUM_PROGRAM_ID,
+    constants::RECEIPTS_FILE,
+    core_asset::MPL_CORE_PROGRAM_ID,
+    files::{append_file, open_file},
+    spinner::create_progress_bar,
+};
+
+const LAMPORTS_PER_SIGNATURE: u64 = 5_000;
+
+// Serializes appends from concurrent sends.
+static RECEIPTS_LOCK: Lazy<Mutex<()>> = Lazy::new(|| Mutex::new(()));
+
+#[derive(Debug, StructOpt)]
+pub enum FeesSubcommands {
+    /// Report base fees, priority fees and rent for a list of transactions
+    #[structopt(name = "report")]
+    Report {
+        /// Receipts file written with --receipts, or a JSON list of signatures
+        #[structopt(short, long)]
+        signatures: PathBuf,
+
+        /// Directory to write the report to
+        #[structopt(short, long, default_value = ".")]
+        output: PathBuf,
+    },
+}
+
+pub fn process_fees(client: RpcClient, commands: FeesSubcommands) -> Result<()> {
+    match commands {
+        FeesSubcommands::Report { signatures, output } => fee_report(&client, &signatures, &output),
+    }
+}
+
+/// One line of the receipts file.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Receipt {
+    pub signature: String,
+    pub operation: Option<String>,
+}
+
+/// Append a confirmed transaction to the receipts file from the global `--receipts` option.
+pub fn record_receipt(signature: &Signature, instructions: &[Instruction]) -> Result<()> {
+    let path = match RECEIPTS_FILE.read().unwrap().as_ref() {
+        Some(path) => path.clone(),
+        None => return Ok(()),
+    };
+
+    let operation = label_operation(
+        instructions
+            .iter()
+            .map(|ix| (&ix.program_id, ix.data.as_slice())),
+    );
+    let line = serde_json::to_string(&Receipt {
+        signature: signature.to_string(),
+        operation: Some(operation),
+    })?;
+
+    let _guard = RECEIPTS_LOCK.lock().unwrap();
+    let mut f = append_file(path)?;
+    writeln!(f, "{line}")?;
+    Ok(())
+}
+
+const TOKEN_METADATA_INSTRUCTIONS: [&str; 58] = [
+    "CreateMetadataAccount",
+    "UpdateMetadataAccount",
+    "DeprecatedCreateMasterEdition",
+    "DeprecatedMintNewEditionFromMasterEditionViaPrintingToken",
+    "UpdatePrimarySaleHappenedViaToken",
+    "DeprecatedSetReservationList",
+    "DeprecatedCreateReservationList",
+    "SignMetadata",
+    "DeprecatedMintPrintingTokensViaToken",
+    "DeprecatedMintPrintingTokens",
+    "CreateMasterEdition",
+    "MintNewEditionFromMasterEditionViaToken",
+    "ConvertMasterEditionV1ToV2",
+    "MintNewEditionFromMasterEditionViaVaultProxy",
+    "PuffMetadata",
+    "UpdateMetadataAccountV2",
+    "CreateMetadataAccountV2",
+    "CreateMasterEditionV3",
+    "VerifyCollection",
+    "Utilize",
+    "ApproveUseAuthority",
+    "RevokeUseAuthority",
+    "UnverifyCollection",
+    "ApproveCollectionAuthority",
+    "RevokeCollectionAuthority",
+    "SetAndVerifyCollection",
+    "FreezeDelegatedAccount",
+    "ThawDelegatedAccount",
+    "RemoveCreatorVerification",
+    "BurnNft",
+    "VerifySizedCollectionItem",
+    "UnverifySizedCollectionItem",
+    "SetAndVerifySizedCollectionItem",
+    "CreateMetadataAccountV3",
+    "SetCollectionSize",
+    "SetTokenStandard",
+    "BubblegumSetCollectionSize",
+    "BurnEditionNft",
+    "CreateEscrowAccount",
+    "CloseEscrowAccount",
+    "TransferOutOfEscrow",
+    "Burn",
+    "Create",
+    "Mint",
+    "Delegate",
+    "Revoke",
+    "Lock",
+    "Unlock",
+    "Migrate",
+    "Transfer",
+    "Update",
+    "Use",
+    "Verify",
+    "Unverify",
+    "Collect",
+    "Print",
+    "Resize",
+    "CloseAccounts",
+];
+
+const SYSTEM_INSTRUCTIONS: [&str; 13] = [
+    "CreateAccount",
+    "Assign",
+    "Transfer",
+    "CreateAccountWithSeed",
+    "AdvanceNonceAccount",
+    "WithdrawNonceAccount",
+    "InitializeNonceAccount",
+    "AuthorizeNonceAccount",
+    "Allocate",
+    "AllocateWithSeed",
+    "AssignWithSeed",
+    "TransferWithSeed",
+    "UpgradeNonceAccount",
+];
+
+const TOKEN_INSTRUCTIONS: [&str; 21] = [
+    "InitializeMint",
+    "InitializeAccount",
+    "InitializeMultisig",
+    "Transfer",
+    "Approve",
+    "Revoke",
+    "SetAuthority",
+    "MintTo",
+    "Burn",
+    "CloseAccount",
+    "FreezeAccount",
+    "ThawAccount",
+    "TransferChecked",
+    "ApproveChecked",
+    "MintToChecked",
+    "BurnChecked",
+    "InitializeAccount2",
+    "SyncNative",
+    "InitializeAccount3",
+    "InitializeMultisig2",
+    "InitializeMint2",
+];
+
+const CORE_INSTRUCTIONS: [&str; 17] = [
+    "CreateV1",
+    "CreateCollectionV1",
+    "AddPluginV1",
+    "AddCollectionPluginV1",
+    "RemovePluginV1",
+    "RemoveCollectionPluginV1",
+    "UpdatePluginV1",
+    "UpdateCollectionPluginV1",
+    "ApprovePluginAuthorityV1",
+    "ApproveCollectionPluginAuthorityV1",
+    "RevokePluginAuthorityV1",
+    "RevokeCollectionPluginAuthorityV1",
+    "BurnV1",
+    "BurnCollectionV1",
+    "TransferV1",
+    "UpdateV1",
+    "UpdateCollectionV1",
+];
+
+// Anchor programs are matched by the hash of the instruction name.
+const BUBBLEGUM_INSTRUCTIONS: [&str; 10] = [
+    "create_tree",
+    "mint_v1",
+    "mint_to_collection_v1",
+    "transfer",
+    "burn",
+    "delegate",
+    "redeem",
+    "verify_creator",
+    "verify_collection",
+    "set_and_verify_collection",
+];
+
+const CANDY_MACHINE_INSTRUCTIONS: [&str; 4] =
+    ["initialize", "add_config_lines", "mint", "withdraw"];
+
+fn indexed_name(names: &[&str], index: Option<usize>) -> String {
+    index
+        .and_then(|i| names.get(i))
+        .map(|name| name.to_string())
+        .unwrap_or_else(|| String::from("Unknown"))
+}
+
+fn anchor_name(names: &[&str], data: &[u8]) -> String {
+    names
+        .iter()
+        .find(|name| {
+            data.len() >= 8
+                && hash(format!("global:{name}").as_bytes()).to_bytes()[..8] == data[..8]
+        })
+        .map(|name| name.to_string())
+        .unwrap_or_else(|| String::from("Unknown"))
+}
+
+/// Name an instruction as `Program.Instruction`, or `None` for compute budget instructions,
+/// which are accounted for as priority fees.
+fn label_instruction(program_id: &Pubkey, data: &[u8]) -> Option<String> {
+    if *program_id == compute_budget::ID {
+        return None;
+    }
+    let first = data.first().map(|b| *b as usize);
+
+    let label = if *program_id == mpl_token_metadata::ID {
+        format!(
+            "TokenMetadata.{}",
+            indexed_name(&TOKEN_METADATA_INSTRUCTIONS, first)
+        )
+    } else if *program_id == system_program::ID {
+        let index = data
+            .get(..4)
+            .map(|b| u32::from_le_bytes(b.try_into().unwrap()) as usize);
+        format!("System.{}", indexed_name(&SYSTEM_INSTRUCTIONS, index))
+    } else if *program_id == spl_token::ID {
+        format!("Token.{}", indexed_name(&TOKEN_INSTRUCTIONS, first))
+    } else if *program_id == spl_token_2022::ID {
+        format!("Token2022.{}", indexed_name(&TOKEN_INSTRUCTIONS, first))
+    } else if *program_id == spl_associated_token_account::ID {
+        match first {
+            None | Some(0) => String::from("AssociatedToken.Create"),
+            Some(1) => String::from("AssociatedToken.CreateIdempotent"),
+            _ => String::from("AssociatedToken.Unknown"),
+        }
+    } else if *program_id == MPL_CORE_PROGRAM_ID {
+        format!("Core.{}", indexed_name(&CORE_INSTRUCTIONS, first))
+    } else if *program_id == BUBBLEGUM_PROGRAM_ID {
+        format!("Bubblegum.{}", anchor_name(&BUBBLEGUM_INSTRUCTIONS, data))
+    } else if *program_id == CANDY_MACHINE_CORE_PROGRAM_ID {
+        format!(
+            "CandyMachine.{}",
+            anchor_name(&CANDY_MACHINE_INSTRUCTIONS, data)
+        )
+    } else {
+        program_id.to_string()
+    };
+
+    Some(label)
+}
+
+/// Label a transaction by its distinct instructions, in order, e.g.
+/// `System.Transfer + TokenMetadata.Update`.
+pub fn label_operation<'a>(instructions: impl Iterator<Item = (&'a Pubkey, &'a [u8])>) -> String {
+    let mut labels: Vec<String> = Vec::new();
+    for (program_id, data) in instructions {
+        if let Some(label) = label_instruction(program_id, data) {
+            if !labels.contains(&label) {
+                labels.push(label);
+            }
+        }
+    }
+
+    if labels.is_empty() {
+        String::from("ComputeBudget")
+    } else {
+        labels.join(" + ")
+    }
+}
+
+#[derive(Debug, Default, Clone, Serialize)]
+pub struct FeeBreakdown {
+    pub base_fee: u64,
+    pub priority_fee: u64,
+    /// Lamports moved into accounts that didn't exist before the transaction.
+    pub rent_paid: u64,
+    /// Lamports returned from accounts the transaction closed.
+    pub rent_reclaimed: u64,
+}
+
+impl FeeBreakdown {
+    pub fn net_cost(&self) -> i128 {
+        self.base_fee as i128 + self.priority_fee as i128 + self.rent_paid as i128
+            - self.rent_reclaimed as i128
+    }
+
+    fn add(&mut self, other: &FeeBreakdown) {
+        self.base_fee += other.base_fee;
+        self.priority_fee += other.priority_fee;
+        self.rent_paid += other.rent_paid;
+        self.rent_reclaimed += other.rent_reclaimed;
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct TransactionFees {
+    pub signature: String,
+    pub operation: String,
+    #[serde(flatten)]
+    pub fees: FeeBreakdown,
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct OperationFees {
+    pub transactions: usize,
+    #[serde(flatten)]
+    pub fees: FeeBreakdown,
+    pub net_cost: i128,
+}
+
+#[derive(Debug, Serialize)]
+pub struct FeeReport {
+    pub totals: OperationFees,
+    pub operations: BTreeMap<String, OperationFees>,
+    pub transactions: Vec<TransactionFees>,
+    pub failed: Vec<String>,
+}
+
+/// Read a receipts file, or a plain JSON list of signatures.
+fn read_receipts(path: &Path) -> Result<Vec<Receipt>> {
+    if let Ok(signatures) = serde_json::from_reader::<_, Vec<String>>(open_file(path)?) {
+        return Ok(signatures
+            .into_iter()
+            .map(|signature| Receipt {
+                signature,
+                operation: None,
+            })
+            .collect());
+    }
+
+    open_file(path)?
+        .lines()
+        .filter(|line| !matches!(line, Ok(l) if l.trim().is_empty()))
+        .map(|line| Ok(serde_json::from_str(&line?)?))
+        .collect()
+}
+
+fn transaction_fees(client: &RpcClient, receipt: &Receipt) -> Result<TransactionFees> {
+    let signature = Signature::from_str(&receipt.signature)
+        .map_err(|_| anyhow!("Invalid signature: {}", receipt.signature))?;
+
+    let tx = client.get_transaction_with_config(
+        &signature,
+        RpcTransactionConfig {
+            encoding: Some(UiTransactionEncoding::Base64),
+            commitment: Some(client.commitment()),
+            max_supported_transaction_version: Some(0),
+        },
+    )?;
+
+    let meta = tx
+        .transaction
+        .meta
+        .ok_or_else(|| anyhow!("Transaction has no status metadata"))?;
+    let versioned = tx
+        .transaction
+        .transaction
+        .decode()
+        .ok_or_else(|| anyhow!("Failed to decode transaction"))?;
+
+    let base_fee = versioned.signatures.len() as u64 * LAMPORTS_PER_SIGNATURE;
+    let mut fees = FeeBreakdown {
+        base_fee,
+        priority_fee: meta.fee.saturating_sub(base_fee),
+        ..Default::default()
+    };
+
+    for (pre, post) in meta.pre_balances.iter().zip(meta.post_balances.iter()) {
+        if *pre == 0 && *post > 0 {
+            fees.rent_paid += post;
+        } else if *pre > 0 && *post == 0 {
+            fees.rent_reclaimed += pre;
+        }
+    }
+
+    let operation = match &receipt.operation {
+        Some(operation) => operation.clone(),
+        None => {
+            let keys = versioned.message.static_account_keys();
+            label_operation(
+                versioned
+                    .message
+                    .instructions()
+                    .iter()
+                    .map(|ix| (&keys[ix.program_id_index as usize], ix.data.as_slice())),
+            )
+        }
+    };
+
+    Ok(TransactionFees {
+        signature: receipt.signature.clone(),
+        operation,
+        fees,
+    })
+}
+
+fn print_row(name: &str, op: &OperationFees) {
+    let rent = op.fees.rent_paid as i128 - op.fees.rent_reclaimed as i128;
+    println!(
+        "{:<50} {:>6} {:>12.6} {:>12.6} {:>12.6} {:>12.6}",
+        name,
+        op.transactions,
+        lamports_to_sol(op.fees.base_fee),
+        lamports_to_sol(op.fees.priority_fee),
+        rent as f64 / LAMPORTS_PER_SOL as f64,
+        op.net_cost as f64 / LAMPORTS_PER_SOL as f64,
+    );
+}
+
+pub fn fee_report(client: &RpcClient, signatures: &Path, output: &Path) -> Result<()> {
+    let receipts = read_receipts(signatures)?;
+
+    let pb = create_progress_bar("Fetching transactions...", receipts.len() as u64);
+    let mut transactions = Vec::new();
+    let mut failed = Vec::new();
+    for receipt in &receipts {
+        match transaction_fees(client, receipt) {
+            Ok(fees) => transactions.push(fees),
+            Err(e) => failed.push(format!("{}: {e}", receipt.signature)),
+        }
+        pb.inc(1);
+    }
+    pb.finish_and_clear();
+
+    let mut totals = OperationFees::default();
+    let mut operations: BTreeMap<String, OperationFees> = BTreeMap::new();
+    for tx in &transactions {
+        for entry in [
+            &mut totals,
+            operations.entry(tx.operation.clone()).or_default(),
+        ] {
+            entry.transactions += 1;
+            entry.fees.add(&tx.fees);
+            entry.net_cost = entry.fees.net_cost();
+        }
+    }
+
+    println!(
+        "{:<50} {:>6} {:>12} {:>12} {:>12} {:>12}",
+        "Operation", "Txs", "Base", "Priority", "Rent", "Net (SOL)"
+    );
+    for (name, op) in &operations {
+        print_row(name, op);
+    }
+    print_row("Total", &totals);
+    if !failed.is_empty() {
+        println!("{} transactions could not be fetched", failed.len());
+    }
+
+    let output = output_path(output);
+    std::fs::create_dir_all(&output)?;
+    let report_path = output.join("mb_fee_report.json");
+    let report = FeeReport {
+        totals,
+        operations,
+        transactions,
+        failed,
+    };
+    serde_json::to_writer_pretty(File::create(&report_path)?, &report)?;
+    println!("Report written to {}", report_path.display());
+
+    Ok(())
+}
@@ -0,0 +1,183 @@
+//! Reading and writing the JSON files commands take and produce.
+//!
+//! Paths ending in `.zst` are compressed with zstd, and paths ending in `.ndjson` or
+//! `.jsonl` (before any `.zst`) hold one JSON value per line instead of a single JSON
+//! array. Both are picked up from the file name, so mint lists, snapshots, cache files
+//! and receipts can be kept compressed without converting them around every command.
+
+use std::{
+    fs::{File, OpenOptions},
+    io::{BufRead, BufReader, BufWriter, Write},
+    path::Path,
+};
+
+use anyhow::{anyhow, Result};
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::Value;
+
+// Level 3 is zstd's default, a good balance of speed and size for large snapshots.
+const ZSTD_LEVEL: i32 = 3;
+
+/// Whether the file is zstd compressed, going by its name.
+pub fn is_zstd<P: AsRef<Path>>(path: P) -> bool {
+    path.as_ref().extension().is_some_and(|e| e == "zst")
+}
+
+/// Whether the file has the extension, ignoring a trailing `.zst`.
+pub fn has_extension<P: AsRef<Path>>(path: P, extension: &str) -> bool {
+    let path = path.as_ref();
+    let path = if is_zstd(path) {
+        path.with_extension("")
+    } else {
+        path.to_path_buf()
+    };
+    path.extension().is_some_and(|e| e == extension)
+}
+
+/// Whether the file holds one JSON value per line, going by its name.
+pub fn is_ndjson<P: AsRef<Path>>(path: P) -> bool {
+    has_extension(&path, "ndjson") || has_extension(&path, "jsonl")
+}
+
+/// Open a file for reading, decompressing it if it's zstd compressed.
+pub fn open_file<P: AsRef<Path>>(path: P) -> Result<Box<dyn BufRead>> {
+    let path = path.as_ref();
+    let f = File::open(path).map_err(|e| anyhow!("Failed to open {}: {e}", path.display()))?;
+    if is_zstd(path) {
+        Ok(Box::new(BufReader::new(zstd::Decoder::new(f)?)))
+    } else {
+        Ok(Box::new(BufReader::new(f)))
+    }
+}
+
+/// Create or truncate a file for writing, compressing it if the name ends in `.zst`. The
+/// compressed stream is finished when the writer is dropped.
+pub fn create_file<P: AsRef<Path>>(path: P) -> Result<Box<dyn Write + Send>> {
+    let path = path.as_ref();
+    let f = File::create(path).map_err(|e| anyhow!("Failed to create {}: {e}", path.display()))?;
+    if is_zstd(path) {
+        Ok(Box::new(
+            zstd::Encoder::new(BufWriter::new(f), ZSTD_LEVEL)?.auto_finish(),
+        ))
+    } else {
+        Ok(Box::new(BufWriter::new(f)))
+    }
+}
+
+/// Open a file for appending, creating it if it doesn't exist. Appends to a compressed
+/// file are written as a new zstd frame, which readers decompress along with the rest.
+pub fn append_file<P: AsRef<Path>>(path: P) -> Result<Box<dyn Write + Send>> {
+    let path = path.as_ref();
+    let f = OpenOptions::new().create(true).append(true).open(path)?;
+    if is_zstd(path) {
+        Ok(Box::new(zstd::Encoder::new(f, ZSTD_LEVEL)?.auto_finish()))
+    } else {
+        Ok(Box::new(f))
+    }
+}
+
+/// Read a JSON file. A `.ndjson` file is read as a list of its lines.
+pub fn read_json<T: DeserializeOwned, P: AsRef<Path>>(path: P) -> Result<T> {
+    let path = path.as_ref();
+    let reader = open_file(path)?;
+    let parsed = if is_ndjson(path) {
+        let values = reader
+            .lines()
+            .filter(|line| !matches!(line, Ok(l) if l.trim().is_empty()))
+            .map(|line| Ok(serde_json::from_str(&line?)?))
+            .collect::<Result<Vec<Value>>>()?;
+        serde_json::from_value(Value::Array(values))
+    } else {
+        serde_json::from_reader(reader)
+    };
+    parsed.map_err(|e| anyhow!("Failed to parse {}: {e}", path.display()))
+}
+
+/// Write a JSON file, pretty printed. A list written to a `.ndjson` file gets one item
+/// per line.
+pub fn write_json<T: Serialize + ?Sized, P: AsRef<Path>>(path: P, value: &T) -> Result<()> {
+    let path = path.as_ref();
+    let mut writer = create_file(path)?;
+    if is_ndjson(path) {
+        match serde_json::to_value(value)? {
+            Value::Array(items) => {
+                for item in items {
+                    writeln!(writer, "{}", serde_json::to_string(&item)?)?;
+                }
+            }
+            value => writeln!(writer, "{}", serde_json::to_string(&value)?)?,
+        }
+    } else {
+        serde_json::to_writer_pretty(&mut writer, value)?;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use serde_json::json;
+
+    use super::*;
+
+    fn temp_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("mb-files-test-{}-{name}", std::process::id()))
+    }
+
+    #[test]
+    fn detects_formats_from_the_name() {
+        assert!(is_zstd("mints.json.zst"));
+        assert!(!is_zstd("mints.json"));
+        assert!(is_ndjson("mints.ndjson"));
+        assert!(is_ndjson("receipts.jsonl.zst"));
+        assert!(!is_ndjson("mints.json.zst"));
+        assert!(has_extension("recipients.csv.zst", "csv"));
+        assert!(!has_extension("recipients.zst", "csv"));
+    }
+
+    #[test]
+    fn round_trips_every_format() {
+        let items = vec![
+            json!({ "mint": "a", "uri": "https://example.com/a.json" }),
+            json!("b"),
+        ];
+
+        for name in [
+            "items.json",
+            "items.json.zst",
+            "items.ndjson",
+            "items.jsonl.zst",
+        ] {
+            let path = temp_path(name);
+            write_json(&path, &items).unwrap();
+            let read: Vec<Value> = read_json(&path).unwrap();
+            assert_eq!(read, items, "{name}");
+            std::fs::remove_file(&path).unwrap();
+        }
+    }
+
+    #[test]
+    fn writes_one_item_per_line_to_ndjson() {
+        let path = temp_path("lines.ndjson");
+        write_json(&path, &vec![json!({ "a": 1 }), json!({ "b": 2 })]).unwrap();
+
+        let contents = std::fs::read_to_string(&path).unwrap();
+        assert_eq!(contents, "{\"a\":1}\n{\"b\":2}\n");
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn reads_appended_zstd_frames() {
+        let path = temp_path("appended.ndjson.zst");
+        for line in ["\"a\"", "", "\"b\""] {
+            let mut writer = append_file(&path).unwrap();
+            writeln!(writer, "{line}").unwrap();
+        }
+
+        let read: Vec<String> = read_json(&path).unwrap();
+        assert_eq!(read, vec!["a", "b"]);
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -1,28 +1,164 @@
-use anyhow::Result;
+use anyhow::{anyhow, Result};
 use borsh::BorshDeserialize;
-use metaboss_lib::{derive::derive_edition_pda, snapshot::get_edition_accounts_by_master};
-use mpl_token_metadata::accounts::Edition;
+use metaboss_lib::{
+    decode::{decode_master_edition_from_mint, decode_token_record},
+    derive::{derive_edition_pda, derive_metadata_pda, derive_token_record_pda},
+    snapshot::get_edition_accounts_by_master,
+};
+use mpl_token_metadata::{accounts::Edition, types::TokenState};
+use serde::{Deserialize, Serialize};
 use solana_client::rpc_client::RpcClient;
+use solana_program::program_option::COption;
 use solana_sdk::pubkey::Pubkey;
-use std::str::FromStr;
+use spl_token_2022::{
+    extension::StateWithExtensions,
+    state::{Account as TokenAccount, AccountState, Mint},
+};
+use std::{
+    path::{Path, PathBuf},
+    str::FromStr,
+};
 
-use crate::{errors::DecodeError, spinner::create_spinner};
+use crate::{
+    constants::MAX_MULTIPLE_ACCOUNTS,
+    decode::decode_metadata_batch,
+    errors::DecodeError,
+    files::{read_json, write_json},
+    parse::is_only_one_option,
+    rpc_cache,
+    spinner::{create_progress_bar, create_spinner},
+};
+
+/// The missing editions of a single master edition.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MissingEditionsReport {
+    pub master: String,
+    pub supply: u64,
+    pub max_supply: Option<u64>,
+    pub missing: Vec<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// A mint whose largest token account is frozen.
+#[derive(Debug, Serialize)]
+pub struct FrozenToken {
+    pub mint: String,
+    pub token_account: String,
+    pub owner: String,
+    pub freeze_authority: Option<String>,
+    pub delegate: Option<String>,
+}
+
+/// A mint from the list that has been burned, and how it was found to be.
+#[derive(Debug, Serialize)]
+pub struct BurnedMint {
+    pub mint: String,
+    pub reason: String,
+}
+
+pub fn find_missing_editions_process(
+    client: &RpcClient,
+    account: Option<String>,
+    mint_list: Option<String>,
+    output: Option<PathBuf>,
+) -> Result<()> {
+    if !is_only_one_option(&account, &mint_list) {
+        return Err(anyhow!(
+            "Please specify either a master mint or a mint list, but not both."
+        ));
+    }
+
+    let reports = match mint_list {
+        Some(mint_list) => {
+            let masters: Vec<String> = read_json(mint_list)?;
+            let pb = create_progress_bar("Finding missing editions...", masters.len() as u64);
+            let reports: Vec<MissingEditionsReport> = masters
+                .into_iter()
+                .map(|master| {
+                    let report = missing_editions_report(client, &master).unwrap_or_else(|e| {
+                        MissingEditionsReport {
+                            master,
+                            supply: 0,
+                            max_supply: None,
+                            missing: vec![],
+                            error: Some(e.to_string()),
+                        }
+                    });
+                    pb.inc(1);
+                    report
+                })
+                .collect();
+            pb.finish_and_clear();
+
+            for report in &reports {
+                match &report.error {
+                    Some(e) => println!("{}: {e}", report.master),
+                    None => println!("{}: {} missing", report.master, report.missing.len()),
+                }
+            }
+            reports
+        }
+        None => {
+            let master = account.unwrap();
+            let missing = find_missing_editions(client, &master)?;
+            let master_edition = decode_master_edition_from_mint(client, master.as_str())?;
+            vec![MissingEditionsReport {
+                master,
+                supply: master_edition.supply,
+                max_supply: master_edition.max_supply,
+                missing,
+                error: None,
+            }]
+        }
+    };
+
+    if let Some(path) = output {
+        write_json(&path, &reports)?;
+        println!("Missing editions written to {}", path.display());
+    }
 
-pub fn find_missing_editions_process(client: &RpcClient, mint: &str) -> Result<()> {
-    find_missing_editions(client, mint)?;
     Ok(())
 }
 
+/// The missing editions of a master edition, along with its supply, without printing.
+pub fn missing_editions_report(client: &RpcClient, master: &str) -> Result<MissingEditionsReport> {
+    let master_edition = decode_master_edition_from_mint(client, master)?;
+    let (_, missing) = edition_numbers(client, master)?;
+
+    Ok(MissingEditionsReport {
+        master: master.to_string(),
+        supply: master_edition.supply,
+        max_supply: master_edition.max_supply,
+        missing,
+        error: None,
+    })
+}
+
 pub fn find_missing_editions(client: &RpcClient, mint: &str) -> Result<Vec<u64>> {
+    let spinner = create_spinner("Getting accounts...");
+    let (edition_nums, missing_nums) = edition_numbers(client, mint)?;
+    spinner.finish();
+
+    println!("Edition numbers: {edition_nums:?}");
+    println!("Missing numbers: {missing_nums:?}");
+
+    Ok(missing_nums)
+}
+
+/// The printed edition numbers of a master edition, and the numbers missing between 1 and
+/// the largest one printed.
+fn edition_numbers(client: &RpcClient, mint: &str) -> Result<(Vec<u64>, Vec<u64>)> {
     let master_edition_pubkey = derive_edition_pda(&Pubkey::from_str(mint)?);
 
     let mut edition_nums = Vec::new();
     let mut missing_nums = Vec::new();
 
-    let spinner = create_spinner("Getting accounts...");
     let editions = get_edition_accounts_by_master(client, &master_edition_pubkey.to_string())?;
     for (_, edition_account) in editions {
-        let edition: Edition = match Edition::deserialize(&mut edition_account.data.as_slice()) {
+        let edition: Edition = match <Edition as BorshDeserialize>::deserialize(
+            &mut edition_account.data.as_slice(),
+        ) {
             Ok(e) => e,
             Err(err) => return Err(DecodeError::DecodeMetadataFailed(err.to_string()).into()),
         };
@@ -33,15 +169,216 @@ pub fn find_missing_editions(client: &RpcClient, mint: &str) -> Result<Vec<u64>>
     // Find any missing editions between 1 and the largest edition number currently printed.
     let largest_edition_number = edition_nums.last().unwrap_or(&0);
     for i in 1..=*largest_edition_number {
-        if !edition_nums.contains(&i) {
+        if edition_nums.binary_search(&i).is_err() {
             missing_nums.push(i);
         }
     }
 
-    spinner.finish();
+    Ok((edition_nums, missing_nums))
+}
 
-    println!("Edition numbers: {edition_nums:?}");
-    println!("Missing numbers: {missing_nums:?}");
+/// Report the mints in the list where the creator is listed but hasn't verified, e.g. to
+/// see what's left after a partial `sign all` run.
+pub fn find_unverified_creators(
+    client: &RpcClient,
+    mint_list: String,
+    creator: Pubkey,
+    output: Option<PathBuf>,
+) -> Result<()> {
+    let mints: Vec<String> = read_json(mint_list)?;
 
-    Ok(missing_nums)
+    let spinner = create_spinner("Decoding metadata...");
+    let decoded = decode_metadata_batch(client, &mints);
+    spinner.finish_and_clear();
+
+    let mut unverified = vec![];
+    let mut failed = 0;
+    for (mint, result) in decoded {
+        match result {
+            Ok(md) => {
+                let is_unverified = md
+                    .creators
+                    .unwrap_or_default()
+                    .iter()
+                    .any(|c| c.address == creator && !c.verified);
+                if is_unverified {
+                    unverified.push(mint);
+                }
+            }
+            Err(e) => {
+                println!("{mint}: {e}");
+                failed += 1;
+            }
+        }
+    }
+    unverified.sort();
+
+    println!(
+        "{} of {} mints have {creator} as an unverified creator",
+        unverified.len(),
+        mints.len()
+    );
+    if failed > 0 {
+        println!("{failed} mints couldn't be decoded");
+    }
+    match output {
+        Some(path) => {
+            write_json(&path, &unverified)?;
+            println!("Mints written to {}", path.display());
+        }
+        None => unverified.iter().for_each(|mint| println!("{mint}")),
+    }
+
+    Ok(())
+}
+
+/// The largest token account of the mint if it's frozen. pNFT token accounts are always
+/// frozen by Token Metadata, so they only count when their token record is locked.
+fn frozen_token(client: &RpcClient, mint: &str) -> Result<Option<FrozenToken>> {
+    let mint_pubkey = Pubkey::from_str(mint)?;
+    let largest = client.get_token_largest_accounts(&mint_pubkey)?;
+    let token = match largest.first() {
+        Some(account) => Pubkey::from_str(&account.address)?,
+        None => return Err(anyhow!("no token accounts")),
+    };
+
+    let token_data = client.get_account_data(&token)?;
+    let token_account = StateWithExtensions::<TokenAccount>::unpack(&token_data)?.base;
+    if token_account.state != AccountState::Frozen {
+        return Ok(None);
+    }
+
+    let token_record = derive_token_record_pda(&mint_pubkey, &token);
+    let is_programmable = client
+        .get_account_with_commitment(&token_record, client.commitment())?
+        .value
+        .is_some();
+    if is_programmable && decode_token_record(client, token_record)?.state != TokenState::Locked {
+        return Ok(None);
+    }
+
+    let mint_data = client.get_account_data(&mint_pubkey)?;
+    let mint_account = StateWithExtensions::<Mint>::unpack(&mint_data)?.base;
+    let to_string = |key: COption<Pubkey>| match key {
+        COption::Some(key) => Some(key.to_string()),
+        COption::None => None,
+    };
+
+    Ok(Some(FrozenToken {
+        mint: mint.to_string(),
+        token_account: token.to_string(),
+        owner: token_account.owner.to_string(),
+        freeze_authority: to_string(mint_account.freeze_authority),
+        delegate: to_string(token_account.delegate),
+    }))
+}
+
+/// Report the mints in the list whose largest token account is frozen or locked, e.g. to
+/// see how many holders are staked.
+pub fn find_frozen(client: &RpcClient, mint_list: String, output: Option<PathBuf>) -> Result<()> {
+    let mints: Vec<String> = read_json(mint_list)?;
+
+    let mut frozen = vec![];
+    let mut failed = vec![];
+    let pb = create_progress_bar("Checking token accounts...", mints.len() as u64);
+    for mint in &mints {
+        match frozen_token(client, mint) {
+            Ok(Some(token)) => frozen.push(token),
+            Ok(None) => {}
+            Err(e) => failed.push(format!("{mint}: {e}")),
+        }
+        pb.inc(1);
+    }
+    pb.finish_and_clear();
+
+    for failure in &failed {
+        println!("{failure}");
+    }
+    println!("{} of {} mints are frozen", frozen.len(), mints.len());
+    if !failed.is_empty() {
+        println!("{} mints couldn't be checked", failed.len());
+    }
+
+    match output {
+        Some(path) => {
+            write_json(&path, &frozen)?;
+            println!("Frozen tokens written to {}", path.display());
+        }
+        None => {
+            for token in &frozen {
+                println!(
+                    "{} owner {} freeze authority {}",
+                    token.mint,
+                    token.owner,
+                    token.freeze_authority.as_deref().unwrap_or("none")
+                );
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Why the mint counts as burned, or `None` if it's still live.
+fn burned_reason(mint: Option<&[u8]>, metadata_exists: bool) -> Option<String> {
+    let Some(data) = mint else {
+        return Some("mint account closed".to_string());
+    };
+    match StateWithExtensions::<Mint>::unpack(data) {
+        Ok(state) if state.base.supply == 0 => Some("zero supply".to_string()),
+        Ok(_) if !metadata_exists => Some("metadata account closed".to_string()),
+        Ok(_) => None,
+        Err(_) => Some("not a mint account".to_string()),
+    }
+}
+
+/// Split a hash list into the burned mints and a cleaned list without them, so airdrops
+/// and other batch runs don't waste transactions on burned tokens.
+pub fn find_burned(client: &RpcClient, mint_list: String, output: PathBuf) -> Result<()> {
+    let mints: Vec<String> = read_json(&mint_list)?;
+    let pubkeys = mints
+        .iter()
+        .map(|m| Pubkey::from_str(m).map_err(|_| anyhow!("Invalid mint address: {m}")))
+        .collect::<Result<Vec<_>>>()?;
+
+    let mut burned = vec![];
+    let mut clean = vec![];
+    // Each mint is fetched along with its metadata account.
+    let chunk_size = MAX_MULTIPLE_ACCOUNTS / 2;
+    let pb = create_progress_bar("Checking mints...", mints.len() as u64);
+    for (mints, pubkeys) in mints.chunks(chunk_size).zip(pubkeys.chunks(chunk_size)) {
+        let mut keys = pubkeys.to_vec();
+        keys.extend(pubkeys.iter().map(derive_metadata_pda));
+        let accounts = rpc_cache::get_multiple_accounts(client, &keys)?;
+        let (mint_accounts, metadata_accounts) = accounts.split_at(pubkeys.len());
+
+        for ((mint, account), metadata) in mints.iter().zip(mint_accounts).zip(metadata_accounts) {
+            let data = account.as_ref().map(|a| a.data.as_slice());
+            match burned_reason(data, metadata.is_some()) {
+                Some(reason) => burned.push(BurnedMint {
+                    mint: mint.clone(),
+                    reason,
+                }),
+                None => clean.push(mint.clone()),
+            }
+        }
+        pb.inc(mints.len() as u64);
+    }
+    pb.finish_and_clear();
+
+    std::fs::create_dir_all(&output)?;
+    let stem = Path::new(&mint_list)
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or("mints");
+    let burned_path = output.join(format!("{stem}-burned.json"));
+    let clean_path = output.join(format!("{stem}-clean.json"));
+    write_json(&burned_path, &burned)?;
+    write_json(&clean_path, &clean)?;
+
+    println!("{} of {} mints are burned", burned.len(), mints.len());
+    println!("Burned mints written to {}", burned_path.display());
+    println!("Cleaned list written to {}", clean_path.display());
+
+    Ok(())
 }
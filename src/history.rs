@@ -0,0 +1,203 @@
+//! Crawl the full signature history of an account.
+//!
+//! `getSignaturesForAddress` returns at most 1,000 signatures per call, newest first, so
+//! busy accounts like collection creators take hundreds of pages. The crawler walks back
+//! with the `before` cursor until it reaches the account's first transaction, and on later
+//! runs only fetches what's newer than the newest signature it has, using `until`.
+//!
+//! With a store directory, each page is appended to `<address>.signatures.jsonl` and the
+//! cursors are saved to `<address>.state.json` as soon as the page is fetched, so an
+//! interrupted crawl resumes from the last page instead of starting over.
+
+use std::{
+    collections::HashSet,
+    fs::{self, File, OpenOptions},
+    io::{BufRead, BufReader, Write},
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+use anyhow::Result;
+use log::{info, warn};
+use retry::{delay::Exponential, retry};
+use serde::{Deserialize, Serialize};
+use solana_client::{
+    rpc_client::{GetConfirmedSignaturesForAddress2Config, RpcClient},
+    rpc_response::RpcConfirmedTransactionStatusWithSignature,
+};
+use solana_sdk::{pubkey::Pubkey, signature::Signature};
+
+// The most signatures the RPC returns per call.
+const SIGNATURES_PAGE_LIMIT: usize = 1_000;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SignatureRecord {
+    pub signature: String,
+    pub slot: u64,
+    pub block_time: Option<i64>,
+    /// The transaction error, if the transaction failed.
+    pub err: Option<String>,
+    pub memo: Option<String>,
+}
+
+impl From<RpcConfirmedTransactionStatusWithSignature> for SignatureRecord {
+    fn from(status: RpcConfirmedTransactionStatusWithSignature) -> Self {
+        SignatureRecord {
+            signature: status.signature,
+            slot: status.slot,
+            block_time: status.block_time,
+            err: status.err.map(|e| e.to_string()),
+            memo: status.memo,
+        }
+    }
+}
+
+/// Cursors of a crawl, saved after every page.
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct CrawlState {
+    /// Newest signature of the last finished crawl, fetched up to on the next run.
+    newest: Option<String>,
+    /// Newest signature seen by the crawl in progress.
+    head: Option<String>,
+    /// Oldest signature seen by the crawl in progress, where it continues from.
+    before: Option<String>,
+}
+
+struct Store {
+    state_path: PathBuf,
+    signatures_path: PathBuf,
+}
+
+impl Store {
+    fn new(dir: &Path, address: &Pubkey) -> Result<Self> {
+        fs::create_dir_all(dir)?;
+        Ok(Store {
+            state_path: dir.join(format!("{address}.state.json")),
+            signatures_path: dir.join(format!("{address}.signatures.jsonl")),
+        })
+    }
+
+    fn load(&self) -> Result<(CrawlState, Vec<SignatureRecord>)> {
+        let state = if self.state_path.exists() {
+            serde_json::from_reader(File::open(&self.state_path)?)?
+        } else {
+            CrawlState::default()
+        };
+
+        let mut records = vec![];
+        if self.signatures_path.exists() {
+            for line in BufReader::new(File::open(&self.signatures_path)?).lines() {
+                let line = line?;
+                if line.trim().is_empty() {
+                    continue;
+                }
+                // A line cut short by a crash is the only one that can fail to parse.
+                match serde_json::from_str(&line) {
+                    Ok(record) => records.push(record),
+                    Err(e) => warn!("Skipping unreadable history line: {e}"),
+                }
+            }
+        }
+        Ok((state, records))
+    }
+
+    fn save(&self, state: &CrawlState, page: &[SignatureRecord]) -> Result<()> {
+        let mut f = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.signatures_path)?;
+        for record in page {
+            writeln!(f, "{}", serde_json::to_string(record)?)?;
+        }
+
+        // Write the state to a temporary file first so a crash never leaves it half written.
+        let tmp = self.state_path.with_extension("json.tmp");
+        serde_json::to_writer(File::create(&tmp)?, state)?;
+        fs::rename(tmp, &self.state_path)?;
+        Ok(())
+    }
+}
+
+fn parse_cursor(cursor: &Option<String>) -> Result<Option<Signature>> {
+    Ok(cursor.as_deref().map(Signature::from_str).transpose()?)
+}
+
+/// Fetch one page of signatures, newest first.
+pub fn fetch_page(
+    client: &RpcClient,
+    address: &Pubkey,
+    before: Option<Signature>,
+    until: Option<Signature>,
+) -> Result<Vec<SignatureRecord>> {
+    let page = retry(
+        Exponential::from_millis_with_factor(250, 2.0).take(3),
+        || {
+            client.get_signatures_for_address_with_config(
+                address,
+                GetConfirmedSignaturesForAddress2Config {
+                    before,
+                    until,
+                    limit: Some(SIGNATURES_PAGE_LIMIT),
+                    commitment: Some(client.commitment()),
+                },
+            )
+        },
+    )?;
+    Ok(page.into_iter().map(SignatureRecord::from).collect())
+}
+
+/// Fetch every signature of the address, oldest first.
+///
+/// When `store` is set, progress is persisted there and previously crawled signatures are
+/// reused, so only new signatures are fetched.
+pub fn crawl_signatures(
+    client: &RpcClient,
+    address: &Pubkey,
+    store: Option<&Path>,
+) -> Result<Vec<SignatureRecord>> {
+    let store = store.map(|dir| Store::new(dir, address)).transpose()?;
+    let (mut state, mut records) = match &store {
+        Some(store) => store.load()?,
+        None => (CrawlState::default(), vec![]),
+    };
+    if !records.is_empty() {
+        info!("Loaded {} stored signatures for {address}", records.len());
+    }
+
+    let until = parse_cursor(&state.newest)?;
+    loop {
+        let page = fetch_page(client, address, parse_cursor(&state.before)?, until)?;
+        let done = page.len() < SIGNATURES_PAGE_LIMIT;
+
+        if state.head.is_none() {
+            state.head = page.first().map(|r| r.signature.clone());
+        }
+        if let Some(last) = page.last() {
+            state.before = Some(last.signature.clone());
+        }
+        if done {
+            // Next time, only fetch what's newer than this crawl.
+            if state.head.is_some() {
+                state.newest = state.head.take();
+            }
+            state.before = None;
+        }
+        if let Some(store) = &store {
+            store.save(&state, &page)?;
+        }
+        records.extend(page);
+        info!("Fetched {} signatures for {address}", records.len());
+
+        if done {
+            break;
+        }
+    }
+
+    // A crawl interrupted after its page was written but before the state was saved
+    // fetches that page again.
+    let mut seen = HashSet::new();
+    records.retain(|r| seen.insert(r.signature.clone()));
+    records.sort_by_key(|r| r.slot);
+
+    Ok(records)
+}
@@ -0,0 +1,147 @@
+//! Error maps generated from programs' on-chain Anchor IDLs.
+//!
+//! `parse-errors update` caches one map per program, and `find_errors` reads them alongside
+//! the built-in maps so codes from newer program releases still resolve.
+
+use std::{
+    collections::BTreeMap,
+    fs,
+    io::Read,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+use anyhow::{anyhow, Result};
+use flate2::read::ZlibDecoder;
+use serde::{Deserialize, Serialize};
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::pubkey::Pubkey;
+
+use crate::parse::known_program_domain;
+
+const IDL_SEED: &str = "anchor:idl";
+
+// IDL accounts start with the discriminator, authority and length of the compressed IDL.
+const IDL_HEADER_LEN: usize = 8 + 32 + 4;
+
+/// A program's errors, keyed by hex code like the built-in maps.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CachedErrors {
+    pub program_id: String,
+    pub domain: String,
+    pub errors: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Idl {
+    name: Option<String>,
+    metadata: Option<IdlMetadata>,
+    #[serde(default)]
+    errors: Vec<IdlError>,
+}
+
+#[derive(Debug, Deserialize)]
+struct IdlMetadata {
+    name: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct IdlError {
+    code: u32,
+    name: String,
+    msg: Option<String>,
+}
+
+fn cache_dir() -> Result<PathBuf> {
+    let dir = dirs::cache_dir()
+        .ok_or_else(|| anyhow!("Couldn't find a cache directory"))?
+        .join("metaboss")
+        .join("errors");
+    fs::create_dir_all(&dir)?;
+    Ok(dir)
+}
+
+/// The account Anchor publishes a program's IDL to.
+pub fn idl_address(program_id: &Pubkey) -> Result<Pubkey> {
+    let (base, _) = Pubkey::find_program_address(&[], program_id);
+    Ok(Pubkey::create_with_seed(&base, IDL_SEED, program_id)?)
+}
+
+fn fetch_idl(client: &RpcClient, program_id: &Pubkey) -> Result<Idl> {
+    let address = idl_address(program_id)?;
+    let data = client
+        .get_account_data(&address)
+        .map_err(|_| anyhow!("No Anchor IDL published for {program_id} at {address}"))?;
+
+    let len = data
+        .get(IDL_HEADER_LEN - 4..IDL_HEADER_LEN)
+        .map(|b| u32::from_le_bytes(b.try_into().unwrap()) as usize)
+        .ok_or_else(|| anyhow!("IDL account {address} is too short"))?;
+    let compressed = data
+        .get(IDL_HEADER_LEN..IDL_HEADER_LEN + len)
+        .ok_or_else(|| anyhow!("IDL account {address} is shorter than its IDL"))?;
+
+    let mut json = String::new();
+    ZlibDecoder::new(compressed).read_to_string(&mut json)?;
+    Ok(serde_json::from_str(&json)?)
+}
+
+/// Fetch a program's Anchor IDL and cache its errors for `find_errors`.
+pub fn update_error_map(client: &RpcClient, program: &str, name: Option<String>) -> Result<()> {
+    let program_id =
+        Pubkey::from_str(program).map_err(|_| anyhow!("Invalid program id: {program}"))?;
+    let idl = fetch_idl(client, &program_id)?;
+    if idl.errors.is_empty() {
+        return Err(anyhow!("The IDL of {program_id} has no errors"));
+    }
+
+    // Known programs keep the name the built-in maps use, so the cached map replaces them.
+    let domain = name
+        .or_else(|| known_program_domain(program).map(String::from))
+        .or(idl.metadata.and_then(|m| m.name))
+        .or(idl.name)
+        .unwrap_or_else(|| program_id.to_string());
+
+    let errors = idl
+        .errors
+        .into_iter()
+        .map(|e| {
+            let message = match e.msg {
+                Some(msg) => format!("{}: {msg}", e.name),
+                None => e.name,
+            };
+            (format!("{:X}", e.code), message)
+        })
+        .collect::<BTreeMap<_, _>>();
+
+    let count = errors.len();
+    let cached = CachedErrors {
+        program_id: program_id.to_string(),
+        domain,
+        errors,
+    };
+    let path = cache_dir()?.join(format!("{program_id}.json"));
+    fs::write(&path, serde_json::to_string_pretty(&cached)?)?;
+
+    println!(
+        "Cached {count} errors for {} ({program_id}) to {}",
+        cached.domain,
+        path.display()
+    );
+    Ok(())
+}
+
+fn read_cached(path: &Path) -> Option<CachedErrors> {
+    serde_json::from_str(&fs::read_to_string(path).ok()?).ok()
+}
+
+/// Every cached error map. Unreadable entries are skipped.
+pub fn cached_error_maps() -> Vec<CachedErrors> {
+    let Ok(entries) = cache_dir().and_then(|dir| Ok(fs::read_dir(dir)?)) else {
+        return vec![];
+    };
+    entries
+        .flatten()
+        .filter_map(|entry| read_cached(&entry.path()))
+        .collect()
+}
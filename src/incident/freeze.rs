@@ -0,0 +1,466 @@
+use std::fs::File;
+
+use anyhow::{anyhow, Result};
+use indexmap::IndexMap;
+use metaboss_lib::{
+    data::Priority,
+    decode::{decode_metadata_from_mint, decode_token_record},
+    derive::derive_token_record_pda,
+    nft::get_nft_token_account,
+};
+use mpl_token_metadata::{
+    instructions::{
+        FreezeDelegatedAccountBuilder, LockV1Builder, ThawDelegatedAccountBuilder, UnlockV1Builder,
+    },
+    types::{ProgrammableConfig, TokenStandard, TokenState},
+};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use solana_client::rpc_client::RpcClient;
+use solana_program::{program_option::COption, program_pack::Pack, pubkey};
+use solana_sdk::{instruction::Instruction, pubkey::Pubkey, signature::Keypair, signer::Signer};
+use spl_token::state::{Account as TokenAccount, AccountState, Mint};
+
+use super::send;
+use crate::checkpoint;
+use crate::workspace::output_path;
+use crate::{
+    cnft::das_request,
+    core_asset::{
+        decode_asset, decode_collection, decode_plugins, update_collection_plugin_v1,
+        update_plugin_v1, Authority, Plugin, UpdateAuthority, MPL_CORE_PROGRAM_ID,
+    },
+    derive::{derive_edition_pda, derive_metadata_pda},
+    parse::{parse_keypair, parse_solana_config},
+    spinner::create_progress_bar,
+};
+
+const MPL_TOKEN_AUTH_RULES_ID: Pubkey = pubkey!("auth9SigNpDKz4sJJ1DfCTuZrZNSAgh9sFD3rboVmgg");
+
+const RESULTS_SAVE_INTERVAL: usize = 100;
+
+pub struct FreezeCollectionArgs {
+    pub client: RpcClient,
+    pub keypair: Option<String>,
+    pub collection: Pubkey,
+    pub das_url: Option<String>,
+    pub priority: Priority,
+    /// Freeze when true, thaw when false.
+    pub freeze: bool,
+}
+
+#[derive(Debug, Deserialize)]
+struct AssetPage {
+    items: Vec<CollectionItem>,
+}
+
+#[derive(Debug, Deserialize)]
+struct CollectionItem {
+    id: String,
+    #[serde(default)]
+    compression: Value,
+    #[serde(default)]
+    burnt: bool,
+}
+
+/// What happened to a single item.
+pub(super) enum ItemOutcome {
+    Sent(String),
+    AlreadyDone,
+    NotPermitted(String),
+}
+
+/// Find every asset in the collection with the DAS API.
+fn collection_items(das_url: &str, collection: &Pubkey) -> Result<Vec<CollectionItem>> {
+    let mut items = vec![];
+    let mut page = 1;
+    loop {
+        let result: AssetPage = das_request(
+            das_url,
+            "getAssetsByGroup",
+            json!({
+                "groupKey": "collection",
+                "groupValue": collection.to_string(),
+                "page": page,
+                "limit": 1000
+            }),
+        )?;
+        if result.items.is_empty() {
+            break;
+        }
+        items.extend(result.items);
+        page += 1;
+    }
+    Ok(items)
+}
+
+/// Whether the signer can update a Core plugin with the given authority.
+fn controls_plugin(
+    authority: &Authority,
+    signer: &Pubkey,
+    owner: Option<&Pubkey>,
+    update_authority: Option<&Pubkey>,
+) -> bool {
+    match authority {
+        Authority::Address { address } => address == signer,
+        Authority::Owner => owner == Some(signer),
+        Authority::UpdateAuthority => update_authority == Some(signer),
+        Authority::None => false,
+    }
+}
+
+fn frozen_plugin(plugin: &Plugin) -> Option<(bool, bool)> {
+    match plugin {
+        Plugin::FreezeDelegate { frozen } => Some((*frozen, false)),
+        Plugin::PermanentFreezeDelegate { frozen } => Some((*frozen, true)),
+        _ => None,
+    }
+}
+
+fn set_frozen(permanent: bool, frozen: bool) -> Plugin {
+    if permanent {
+        Plugin::PermanentFreezeDelegate { frozen }
+    } else {
+        Plugin::FreezeDelegate { frozen }
+    }
+}
+
+/// Freeze or thaw a whole Core collection at once through its permanent freeze plugin.
+/// Returns `None` if the collection has no such plugin the signer controls.
+fn freeze_core_collection(
+    args: &FreezeCollectionArgs,
+    keypair: &Keypair,
+    data: &[u8],
+) -> Result<Option<ItemOutcome>> {
+    let (collection, base_len) = decode_collection(data)?;
+    let signer = keypair.pubkey();
+
+    for record in decode_plugins(data, base_len)? {
+        let Some(Plugin::PermanentFreezeDelegate { frozen }) = record.plugin else {
+            continue;
+        };
+        if !controls_plugin(
+            &record.authority,
+            &signer,
+            None,
+            Some(&collection.update_authority),
+        ) {
+            continue;
+        }
+        if frozen == args.freeze {
+            return Ok(Some(ItemOutcome::AlreadyDone));
+        }
+
+        let ix = update_collection_plugin_v1(
+            &args.collection,
+            &signer,
+            &signer,
+            Plugin::PermanentFreezeDelegate {
+                frozen: args.freeze,
+            },
+        );
+        let sig = send(&args.client, keypair, args.priority.clone(), ix)?;
+        return Ok(Some(ItemOutcome::Sent(sig.to_string())));
+    }
+
+    Ok(None)
+}
+
+fn core_asset_ix(
+    args: &FreezeCollectionArgs,
+    signer: &Pubkey,
+    asset: &Pubkey,
+    data: &[u8],
+) -> Result<std::result::Result<Instruction, ItemOutcome>> {
+    let (core_asset, base_len) = decode_asset(data)?;
+    let collection = core_asset.collection();
+    let update_authority = match &core_asset.update_authority {
+        UpdateAuthority::Address(address) => Some(*address),
+        UpdateAuthority::Collection(collection) => {
+            let data = args.client.get_account_data(collection)?;
+            Some(decode_collection(&data)?.0.update_authority)
+        }
+        UpdateAuthority::None => None,
+    };
+
+    for record in decode_plugins(data, base_len)? {
+        let Some((frozen, permanent)) = record.plugin.as_ref().and_then(frozen_plugin) else {
+            continue;
+        };
+        if !controls_plugin(
+            &record.authority,
+            signer,
+            Some(&core_asset.owner),
+            update_authority.as_ref(),
+        ) {
+            continue;
+        }
+        if frozen == args.freeze {
+            return Ok(Err(ItemOutcome::AlreadyDone));
+        }
+
+        return Ok(Ok(update_plugin_v1(
+            asset,
+            collection,
+            signer,
+            signer,
+            set_frozen(permanent, args.freeze),
+        )));
+    }
+
+    Ok(Err(ItemOutcome::NotPermitted(
+        "no freeze plugin the keypair controls".to_string(),
+    )))
+}
+
+pub(super) fn programmable_ix(
+    client: &RpcClient,
+    freeze: bool,
+    signer: &Pubkey,
+    mint: &Pubkey,
+    token: &Pubkey,
+    token_account: &TokenAccount,
+    programmable_config: Option<ProgrammableConfig>,
+) -> Result<std::result::Result<Instruction, ItemOutcome>> {
+    let token_record = derive_token_record_pda(mint, token);
+    let record = decode_token_record(client, token_record)?;
+
+    // Locking a pNFT needs a utility style delegate on its token record.
+    if record.delegate != Some(*signer) {
+        return Ok(Err(ItemOutcome::NotPermitted(
+            "keypair is not the token record delegate".to_string(),
+        )));
+    }
+    let locked = record.state == TokenState::Locked;
+    if locked == freeze {
+        return Ok(Err(ItemOutcome::AlreadyDone));
+    }
+
+    let rule_set = match programmable_config {
+        Some(ProgrammableConfig::V1 { rule_set }) => rule_set,
+        None => None,
+    };
+    let rules_program = rule_set.map(|_| MPL_TOKEN_AUTH_RULES_ID);
+
+    let ix = if freeze {
+        LockV1Builder::new()
+            .authority(*signer)
+            .token_owner(Some(token_account.owner))
+            .token(*token)
+            .mint(*mint)
+            .metadata(derive_metadata_pda(mint))
+            .edition(Some(derive_edition_pda(mint)))
+            .token_record(Some(token_record))
+            .payer(*signer)
+            .spl_token_program(Some(spl_token::ID))
+            .authorization_rules_program(rules_program)
+            .authorization_rules(rule_set)
+            .instruction()
+    } else {
+        UnlockV1Builder::new()
+            .authority(*signer)
+            .token_owner(Some(token_account.owner))
+            .token(*token)
+            .mint(*mint)
+            .metadata(derive_metadata_pda(mint))
+            .edition(Some(derive_edition_pda(mint)))
+            .token_record(Some(token_record))
+            .payer(*signer)
+            .spl_token_program(Some(spl_token::ID))
+            .authorization_rules_program(rules_program)
+            .authorization_rules(rule_set)
+            .instruction()
+    };
+
+    Ok(Ok(ix))
+}
+
+pub(super) fn legacy_ix(
+    client: &RpcClient,
+    freeze: bool,
+    signer: &Pubkey,
+    mint: &Pubkey,
+    token: &Pubkey,
+    token_account: &TokenAccount,
+) -> Result<std::result::Result<Instruction, ItemOutcome>> {
+    let frozen = token_account.state == AccountState::Frozen;
+    let mint_account = Mint::unpack(&client.get_account_data(mint)?)?;
+
+    let permitted = token_account.delegate == COption::Some(*signer)
+        || mint_account.freeze_authority == COption::Some(*signer);
+    if !permitted {
+        return Ok(Err(ItemOutcome::NotPermitted(
+            "keypair is neither the token delegate nor the freeze authority".to_string(),
+        )));
+    }
+    if frozen == freeze {
+        return Ok(Err(ItemOutcome::AlreadyDone));
+    }
+
+    // NFTs with an edition have the edition as their freeze authority, so only the
+    // token delegate can freeze them, through Token Metadata.
+    let ix = if token_account.delegate == COption::Some(*signer) {
+        if freeze {
+            FreezeDelegatedAccountBuilder::new()
+                .delegate(*signer)
+                .token_account(*token)
+                .edition(derive_edition_pda(mint))
+                .mint(*mint)
+                .instruction()
+        } else {
+            ThawDelegatedAccountBuilder::new()
+                .delegate(*signer)
+                .token_account(*token)
+                .edition(derive_edition_pda(mint))
+                .mint(*mint)
+                .instruction()
+        }
+    } else if freeze {
+        spl_token::instruction::freeze_account(&spl_token::ID, token, mint, signer, &[])?
+    } else {
+        spl_token::instruction::thaw_account(&spl_token::ID, token, mint, signer, &[])?
+    };
+
+    Ok(Ok(ix))
+}
+
+fn freeze_item(args: &FreezeCollectionArgs, keypair: &Keypair, id: &str) -> Result<ItemOutcome> {
+    let signer = keypair.pubkey();
+    let asset: Pubkey = id.parse()?;
+    let account = args.client.get_account(&asset)?;
+
+    let ix = if account.owner == MPL_CORE_PROGRAM_ID {
+        core_asset_ix(args, &signer, &asset, &account.data)?
+    } else {
+        let token = get_nft_token_account(&args.client, id)?;
+        let token_account = TokenAccount::unpack(&args.client.get_account_data(&token)?)?;
+        let metadata = decode_metadata_from_mint(&args.client, id.to_string())?;
+
+        match metadata.token_standard {
+            Some(TokenStandard::ProgrammableNonFungible)
+            | Some(TokenStandard::ProgrammableNonFungibleEdition) => programmable_ix(
+                &args.client,
+                args.freeze,
+                &signer,
+                &asset,
+                &token,
+                &token_account,
+                metadata.programmable_config,
+            )?,
+            _ => legacy_ix(
+                &args.client,
+                args.freeze,
+                &signer,
+                &asset,
+                &token,
+                &token_account,
+            )?,
+        }
+    };
+
+    match ix {
+        Ok(ix) => {
+            let sig = send(&args.client, keypair, args.priority.clone(), ix)?;
+            Ok(ItemOutcome::Sent(sig.to_string()))
+        }
+        Err(outcome) => Ok(outcome),
+    }
+}
+
+/// Freeze or thaw every item in a collection, as far as the keypair's freeze rights
+/// allow: legacy NFTs through their token delegate or freeze authority, pNFTs through
+/// a utility delegate lock and Core assets through their freeze plugins.
+///
+/// Results for every item are written to `mb-incident-<action>-<collection>.json`.
+pub fn freeze_collection(args: FreezeCollectionArgs) -> Result<()> {
+    let keypair = parse_keypair(args.keypair.clone(), parse_solana_config());
+    let action = if args.freeze { "freeze" } else { "thaw" };
+    // Items left as they were count as failed, since the incident isn't covered for them.
+    let command = format!("incident-{action}-collection");
+
+    let mut results: IndexMap<String, String> = IndexMap::new();
+    let results_file = output_path(format!("mb-incident-{action}-{}.json", args.collection))
+        .display()
+        .to_string();
+
+    // A Core collection with a permanent freeze plugin covers every asset in one transaction.
+    let collection_account = args.client.get_account(&args.collection)?;
+    if collection_account.owner == MPL_CORE_PROGRAM_ID {
+        if let Some(outcome) = freeze_core_collection(&args, &keypair, &collection_account.data)? {
+            let result = match outcome {
+                ItemOutcome::Sent(sig) => sig,
+                _ => "already done".to_string(),
+            };
+            checkpoint::record_done(&command, &args.collection.to_string(), None);
+            println!("Collection {action} through its permanent freeze plugin: {result}");
+            results.insert(args.collection.to_string(), result);
+            serde_json::to_writer_pretty(File::create(&results_file)?, &results)?;
+            return Ok(());
+        }
+    }
+
+    let das_url = args.das_url.clone().unwrap_or_else(|| args.client.url());
+    let items: Vec<CollectionItem> = collection_items(&das_url, &args.collection)?
+        .into_iter()
+        .filter(|item| !item.burnt)
+        .collect();
+
+    if items.is_empty() {
+        return Err(anyhow!("No assets found in collection {}", args.collection));
+    }
+
+    let (mut sent, mut already_done, mut not_permitted, mut failed) = (0, 0, 0, 0);
+
+    let pb = create_progress_bar("Processing collection...", items.len() as u64);
+    for item in &items {
+        // Compressed assets have no token account or plugins to freeze.
+        if item.compression["compressed"].as_bool() == Some(true) {
+            not_permitted += 1;
+            let result = "skipped: compressed assets can't be frozen".to_string();
+            checkpoint::record_failed(&command, &item.id, &result);
+            results.insert(item.id.clone(), result);
+            pb.inc(1);
+            continue;
+        }
+
+        let result = match freeze_item(&args, &keypair, &item.id) {
+            Ok(ItemOutcome::Sent(sig)) => {
+                sent += 1;
+                checkpoint::record_done(&command, &item.id, Some(sig.clone()));
+                sig
+            }
+            Ok(ItemOutcome::AlreadyDone) => {
+                already_done += 1;
+                checkpoint::record_done(&command, &item.id, None);
+                "already done".to_string()
+            }
+            Ok(ItemOutcome::NotPermitted(reason)) => {
+                not_permitted += 1;
+                let result = format!("skipped: {reason}");
+                checkpoint::record_failed(&command, &item.id, &result);
+                result
+            }
+            Err(e) => {
+                failed += 1;
+                checkpoint::record_failed(&command, &item.id, &e);
+                format!("error: {e}")
+            }
+        };
+        results.insert(item.id.clone(), result);
+
+        // Save progress regularly so an interrupted run shows how far it got.
+        if results.len().is_multiple_of(RESULTS_SAVE_INTERVAL) {
+            serde_json::to_writer_pretty(File::create(&results_file)?, &results)?;
+        }
+        pb.inc(1);
+    }
+    pb.finish_and_clear();
+
+    serde_json::to_writer_pretty(File::create(&results_file)?, &results)?;
+
+    println!(
+        "{action}: {sent} sent, {already_done} already done, {not_permitted} not permitted, {failed} failed. Results written to {results_file}"
+    );
+
+    Ok(())
+}
@@ -0,0 +1,173 @@
+use std::{fs::File, path::PathBuf};
+
+use anyhow::{anyhow, Result};
+use indexmap::IndexMap;
+use metaboss_lib::{data::Priority, decode::decode_metadata_from_mint, nft::get_nft_token_account};
+use mpl_token_metadata::types::TokenStandard;
+use solana_client::rpc_client::RpcClient;
+use solana_program::program_pack::Pack;
+use solana_sdk::{pubkey::Pubkey, signature::Keypair, signer::Signer};
+use spl_token::state::Account as TokenAccount;
+
+use super::{legacy_ix, programmable_ix, send, ItemOutcome};
+use crate::{
+    checkpoint,
+    files::read_json,
+    order,
+    parse::{parse_keypair, parse_solana_config},
+    spinner::create_progress_bar,
+    workspace::output_path,
+};
+
+pub struct FreezeAllArgs {
+    pub client: RpcClient,
+    pub keypair: Option<String>,
+    pub mint_list: PathBuf,
+    pub priority: Priority,
+    /// Freeze or lock when true, thaw or unlock when false.
+    pub freeze: bool,
+}
+
+fn is_programmable(token_standard: &Option<TokenStandard>) -> bool {
+    matches!(
+        token_standard,
+        Some(TokenStandard::ProgrammableNonFungible)
+            | Some(TokenStandard::ProgrammableNonFungibleEdition)
+    )
+}
+
+fn freeze_mint(args: &FreezeAllArgs, keypair: &Keypair, mint: &str) -> Result<ItemOutcome> {
+    let mint_pubkey: Pubkey = mint.parse()?;
+    let metadata = decode_metadata_from_mint(&args.client, mint.to_string())?;
+    if is_programmable(&metadata.token_standard) {
+        return Ok(ItemOutcome::NotPermitted(
+            "pNFTs are locked rather than frozen, use lock-all".to_string(),
+        ));
+    }
+
+    let token = get_nft_token_account(&args.client, mint)?;
+    let token_account = TokenAccount::unpack(&args.client.get_account_data(&token)?)?;
+
+    match legacy_ix(
+        &args.client,
+        args.freeze,
+        &keypair.pubkey(),
+        &mint_pubkey,
+        &token,
+        &token_account,
+    )? {
+        Ok(ix) => {
+            let sig = send(&args.client, keypair, args.priority.clone(), ix)?;
+            Ok(ItemOutcome::Sent(sig.to_string()))
+        }
+        Err(outcome) => Ok(outcome),
+    }
+}
+
+fn lock_mint(args: &FreezeAllArgs, keypair: &Keypair, mint: &str) -> Result<ItemOutcome> {
+    let mint_pubkey: Pubkey = mint.parse()?;
+    let metadata = decode_metadata_from_mint(&args.client, mint.to_string())?;
+    if !is_programmable(&metadata.token_standard) {
+        return Ok(ItemOutcome::NotPermitted(
+            "not a pNFT, use freeze-all".to_string(),
+        ));
+    }
+
+    // The token record is derived from the holder's token account.
+    let token = get_nft_token_account(&args.client, mint)?;
+    let token_account = TokenAccount::unpack(&args.client.get_account_data(&token)?)?;
+
+    match programmable_ix(
+        &args.client,
+        args.freeze,
+        &keypair.pubkey(),
+        &mint_pubkey,
+        &token,
+        &token_account,
+        metadata.programmable_config,
+    )? {
+        Ok(ix) => {
+            let sig = send(&args.client, keypair, args.priority.clone(), ix)?;
+            Ok(ItemOutcome::Sent(sig.to_string()))
+        }
+        Err(outcome) => Ok(outcome),
+    }
+}
+
+/// Freeze or thaw the token account holding each mint in the list, as its token delegate
+/// or the mint's freeze authority.
+///
+/// Results are written to `mb-<action>-all.json`, and with `--resume` mints already done
+/// are skipped on the next run.
+pub fn freeze_all(args: FreezeAllArgs) -> Result<()> {
+    let action = if args.freeze { "freeze" } else { "thaw" };
+    run_all(args, action, freeze_mint)
+}
+
+/// Lock or unlock each pNFT in the list through Token Metadata, as the utility, staking or
+/// other delegate on its token record.
+///
+/// Results are written to `mb-<action>-all.json`, and with `--resume` mints already done
+/// are skipped on the next run.
+pub fn lock_all(args: FreezeAllArgs) -> Result<()> {
+    let action = if args.freeze { "lock" } else { "unlock" };
+    run_all(args, action, lock_mint)
+}
+
+fn run_all(
+    args: FreezeAllArgs,
+    action: &str,
+    process_mint: fn(&FreezeAllArgs, &Keypair, &str) -> Result<ItemOutcome>,
+) -> Result<()> {
+    let keypair = parse_keypair(args.keypair.clone(), parse_solana_config());
+    let command = format!("{action}-all");
+
+    let mint_list: Vec<String> = read_json(&args.mint_list)?;
+    if mint_list.is_empty() {
+        return Err(anyhow!("No mints in {}", args.mint_list.display()));
+    }
+    let mints = order::ordered(checkpoint::remaining(&command, mint_list));
+
+    let mut results: IndexMap<String, String> = IndexMap::new();
+    let results_file = output_path(format!("mb-{command}.json"))
+        .display()
+        .to_string();
+    let (mut sent, mut already_done, mut not_permitted, mut failed) = (0, 0, 0, 0);
+
+    let pb = create_progress_bar("Processing mints...", mints.len() as u64);
+    for mint in &mints {
+        let result = match process_mint(&args, &keypair, mint) {
+            Ok(ItemOutcome::Sent(sig)) => {
+                checkpoint::record_done(&command, mint, Some(sig.clone()));
+                sent += 1;
+                sig
+            }
+            Ok(ItemOutcome::AlreadyDone) => {
+                checkpoint::record_done(&command, mint, None);
+                already_done += 1;
+                "already done".to_string()
+            }
+            Ok(ItemOutcome::NotPermitted(reason)) => {
+                checkpoint::record_failed(&command, mint, &reason);
+                not_permitted += 1;
+                format!("skipped: {reason}")
+            }
+            Err(e) => {
+                checkpoint::record_failed(&command, mint, &e);
+                failed += 1;
+                format!("error: {e}")
+            }
+        };
+        results.insert(mint.clone(), result);
+        pb.inc(1);
+    }
+    pb.finish_and_clear();
+
+    serde_json::to_writer_pretty(File::create(&results_file)?, &results)?;
+
+    println!(
+        "{action}: {sent} sent, {already_done} already done, {not_permitted} not permitted, {failed} failed. Results written to {results_file}"
+    );
+
+    Ok(())
+}
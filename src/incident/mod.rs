@@ -0,0 +1,104 @@
+mod freeze;
+mod freeze_all;
+
+pub use freeze::*;
+pub use freeze_all::*;
+
+use anyhow::Result;
+use metaboss_lib::data::Priority;
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::{
+    instruction::Instruction,
+    pubkey::Pubkey,
+    signature::{Keypair, Signature},
+};
+use structopt::StructOpt;
+
+use crate::pipeline::Pipeline;
+
+#[derive(Debug, StructOpt)]
+pub enum IncidentSubcommands {
+    /// Freeze every item in a collection that the keypair has freeze rights over
+    #[structopt(name = "freeze-collection")]
+    FreezeCollection {
+        /// Path to the freeze authority or delegate keypair file
+        #[structopt(short, long)]
+        keypair: Option<String>,
+
+        /// Collection mint or MPL Core collection address
+        #[structopt(short, long)]
+        collection: Pubkey,
+
+        /// DAS API url to list the collection with, defaults to the RPC url
+        #[structopt(long)]
+        das_url: Option<String>,
+
+        /// Priority of the transaction: higher priority costs more.
+        /// See https://metaboss.rs/priority-fees.html for more details.
+        #[structopt(short = "P", long, default_value = "none")]
+        priority: Priority,
+    },
+    /// Thaw every item in a collection frozen with `freeze-collection`
+    #[structopt(name = "thaw-collection")]
+    ThawCollection {
+        /// Path to the freeze authority or delegate keypair file
+        #[structopt(short, long)]
+        keypair: Option<String>,
+
+        /// Collection mint or MPL Core collection address
+        #[structopt(short, long)]
+        collection: Pubkey,
+
+        /// DAS API url to list the collection with, defaults to the RPC url
+        #[structopt(long)]
+        das_url: Option<String>,
+
+        /// Priority of the transaction: higher priority costs more.
+        /// See https://metaboss.rs/priority-fees.html for more details.
+        #[structopt(short = "P", long, default_value = "none")]
+        priority: Priority,
+    },
+}
+
+pub fn process_incident(client: RpcClient, commands: IncidentSubcommands) -> Result<()> {
+    match commands {
+        IncidentSubcommands::FreezeCollection {
+            keypair,
+            collection,
+            das_url,
+            priority,
+        } => freeze_collection(FreezeCollectionArgs {
+            client,
+            keypair,
+            collection,
+            das_url,
+            priority,
+            freeze: true,
+        }),
+        IncidentSubcommands::ThawCollection {
+            keypair,
+            collection,
+            das_url,
+            priority,
+        } => freeze_collection(FreezeCollectionArgs {
+            client,
+            keypair,
+            collection,
+            das_url,
+            priority,
+            freeze: false,
+        }),
+    }
+}
+
+fn send(
+    client: &RpcClient,
+    keypair: &Keypair,
+    priority: Priority,
+    ix: Instruction,
+) -> Result<Signature> {
+    Pipeline::with_priority(priority)
+        .with_active_lookup_table()?
+        .with_active_nonce()?
+        .run(client, &[keypair], vec![ix])
+}
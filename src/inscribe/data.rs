@@ -0,0 +1,153 @@
+use anyhow::{anyhow, Result};
+use borsh::BorshDeserialize;
+use serde::Serialize;
+use solana_program::{pubkey, pubkey::Pubkey};
+
+pub const MPL_INSCRIPTION_PROGRAM_ID: Pubkey =
+    pubkey!("1NSCRfGeyo7wPUazGbaPBUsTM49e1k2aXewHGARfzSo");
+
+pub const INSCRIPTION_PREFIX: &str = "Inscription";
+pub const SHARD_PREFIX: &str = "Shard";
+pub const ASSOCIATION_PREFIX: &str = "Association";
+
+/// Ranks are handed out by a set of shard counters so inscriptions don't all
+/// contend for the same account.
+pub const SHARD_COUNT: u8 = 32;
+
+pub const KEYS: [&str; 4] = [
+    "Uninitialized",
+    "InscriptionMetadataAccount",
+    "MintInscriptionMetadataAccount",
+    "InscriptionShardAccount",
+];
+
+pub const STATES: [&str; 2] = ["Unlocked", "Locked"];
+
+pub const DATA_TYPES: [&str; 6] = ["Uninitialized", "Binary", "Json", "Png", "Jpeg", "Svg"];
+
+#[derive(Debug, BorshDeserialize)]
+pub struct AssociatedInscription {
+    pub tag: String,
+    pub bump: u8,
+    pub data_type: u8,
+}
+
+#[derive(Debug, BorshDeserialize)]
+pub struct InscriptionMetadata {
+    pub key: u8,
+    pub bump: u8,
+    pub state: u8,
+    pub inscription_rank: u64,
+    pub inscription_bump: Option<u8>,
+    pub update_authorities: Vec<Pubkey>,
+    pub associated_inscriptions: Vec<AssociatedInscription>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct AssociatedInscriptionRecord {
+    pub tag: String,
+    pub address: String,
+    pub data_type: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct InscriptionMetadataRecord {
+    pub key: String,
+    pub state: String,
+    pub inscription_rank: u64,
+    pub update_authorities: Vec<String>,
+    pub associated_inscriptions: Vec<AssociatedInscriptionRecord>,
+}
+
+fn name(names: &[&str], index: u8) -> String {
+    names
+        .get(index as usize)
+        .map(|name| name.to_string())
+        .unwrap_or_else(|| format!("Unknown({index})"))
+}
+
+pub fn data_type_name(data_type: u8) -> String {
+    name(&DATA_TYPES, data_type)
+}
+
+/// The inscription account for a mint, which holds the inscribed JSON.
+pub fn derive_mint_inscription(mint: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[
+            INSCRIPTION_PREFIX.as_bytes(),
+            MPL_INSCRIPTION_PROGRAM_ID.as_ref(),
+            mint.as_ref(),
+        ],
+        &MPL_INSCRIPTION_PROGRAM_ID,
+    )
+    .0
+}
+
+pub fn derive_inscription_metadata(inscription: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[
+            INSCRIPTION_PREFIX.as_bytes(),
+            MPL_INSCRIPTION_PROGRAM_ID.as_ref(),
+            inscription.as_ref(),
+        ],
+        &MPL_INSCRIPTION_PROGRAM_ID,
+    )
+    .0
+}
+
+pub fn derive_inscription_shard(shard_number: u8) -> Pubkey {
+    Pubkey::find_program_address(
+        &[
+            INSCRIPTION_PREFIX.as_bytes(),
+            SHARD_PREFIX.as_bytes(),
+            MPL_INSCRIPTION_PROGRAM_ID.as_ref(),
+            &[shard_number],
+        ],
+        &MPL_INSCRIPTION_PROGRAM_ID,
+    )
+    .0
+}
+
+/// An associated inscription holds extra data, such as an image, under a tag.
+pub fn derive_associated_inscription(tag: &str, inscription_metadata: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[
+            INSCRIPTION_PREFIX.as_bytes(),
+            ASSOCIATION_PREFIX.as_bytes(),
+            tag.as_bytes(),
+            inscription_metadata.as_ref(),
+        ],
+        &MPL_INSCRIPTION_PROGRAM_ID,
+    )
+    .0
+}
+
+pub fn decode_inscription_metadata(data: &[u8]) -> Result<InscriptionMetadata> {
+    InscriptionMetadata::deserialize(&mut &data[..])
+        .map_err(|e| anyhow!("Failed to decode inscription metadata: {e}"))
+}
+
+impl InscriptionMetadata {
+    pub fn to_record(&self, inscription_metadata: &Pubkey) -> InscriptionMetadataRecord {
+        InscriptionMetadataRecord {
+            key: name(&KEYS, self.key),
+            state: name(&STATES, self.state),
+            inscription_rank: self.inscription_rank,
+            update_authorities: self
+                .update_authorities
+                .iter()
+                .map(|a| a.to_string())
+                .collect(),
+            associated_inscriptions: self
+                .associated_inscriptions
+                .iter()
+                .map(|a| AssociatedInscriptionRecord {
+                    tag: a.tag.clone(),
+                    address: derive_associated_inscription(&a.tag, inscription_metadata)
+                        .to_string(),
+                    data_type: data_type_name(a.data_type),
+                })
+                .collect(),
+        }
+    }
+}
@@ -0,0 +1,60 @@
+use std::{fs::File, path::PathBuf};
+
+use anyhow::{anyhow, Result};
+use serde::Serialize;
+use serde_json::Value;
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::pubkey::Pubkey;
+
+use super::{
+    decode_inscription_metadata, derive_inscription_metadata, derive_mint_inscription,
+    InscriptionMetadataRecord,
+};
+
+#[derive(Debug, Serialize)]
+pub struct DecodedInscription {
+    pub mint: String,
+    pub inscription: String,
+    pub inscription_metadata: String,
+    #[serde(flatten)]
+    pub metadata: InscriptionMetadataRecord,
+    pub data_size: usize,
+    /// The inscribed data when it's valid JSON.
+    pub data: Option<Value>,
+}
+
+pub fn decode_inscription(
+    client: &RpcClient,
+    mint: &Pubkey,
+    output: Option<PathBuf>,
+) -> Result<()> {
+    let inscription = derive_mint_inscription(mint);
+    let inscription_metadata = derive_inscription_metadata(&inscription);
+
+    let metadata_data = client
+        .get_account_data(&inscription_metadata)
+        .map_err(|_| anyhow!("{mint} has no inscription"))?;
+    let metadata = decode_inscription_metadata(&metadata_data)?;
+    let data = client.get_account_data(&inscription)?;
+
+    let decoded = DecodedInscription {
+        mint: mint.to_string(),
+        inscription: inscription.to_string(),
+        inscription_metadata: inscription_metadata.to_string(),
+        metadata: metadata.to_record(&inscription_metadata),
+        data_size: data.len(),
+        data: serde_json::from_slice(&data).ok(),
+    };
+
+    match output {
+        Some(dir) => {
+            std::fs::create_dir_all(&dir)?;
+            let path = dir.join(format!("{mint}_inscription.json"));
+            serde_json::to_writer_pretty(File::create(&path)?, &decoded)?;
+            println!("Decoded inscription written to {}", path.display());
+        }
+        None => println!("{}", serde_json::to_string_pretty(&decoded)?),
+    }
+
+    Ok(())
+}
@@ -0,0 +1,192 @@
+use std::{fs, path::PathBuf};
+
+use anyhow::{anyhow, Result};
+use metaboss_lib::{data::Priority, decode::decode_metadata_from_mint};
+use serde_json::Value;
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::{pubkey::Pubkey, signer::Signer};
+
+use super::{
+    allocate_ix, clear_data_ix, create_shard_ix, decode_inscription_metadata,
+    derive_associated_inscription, derive_inscription_metadata, derive_inscription_shard,
+    derive_mint_inscription, initialize_associated_inscription_ix, initialize_from_mint_ix, send,
+    write_data_ix, MAX_REALLOC_SIZE, SHARD_COUNT,
+};
+use crate::{
+    parse::{parse_keypair, parse_solana_config},
+    rpc_cache,
+    spinner::create_progress_bar,
+    utils::get_associated_token_address_for_mint,
+};
+
+pub struct InitInscriptionArgs {
+    pub client: RpcClient,
+    pub keypair: Option<String>,
+    pub mint: Pubkey,
+    pub priority: Priority,
+}
+
+pub struct WriteInscriptionArgs {
+    pub client: RpcClient,
+    pub keypair: Option<String>,
+    pub mint: Pubkey,
+    pub file: Option<PathBuf>,
+    pub tag: Option<String>,
+    pub chunk_size: usize,
+    pub priority: Priority,
+}
+
+pub fn init_inscription(args: InitInscriptionArgs) -> Result<()> {
+    let keypair = parse_keypair(args.keypair, parse_solana_config());
+
+    let inscription = derive_mint_inscription(&args.mint);
+    if args.client.get_account(&inscription).is_ok() {
+        return Err(anyhow!(
+            "{} is already inscribed at {inscription}",
+            args.mint
+        ));
+    }
+
+    // Spread inscriptions across the shards without needing a random number generator.
+    let shard_number = args.mint.to_bytes()[0] % SHARD_COUNT;
+    let mut ixs = vec![];
+    if args
+        .client
+        .get_account(&derive_inscription_shard(shard_number))
+        .is_err()
+    {
+        ixs.push(create_shard_ix(&keypair.pubkey(), shard_number)?);
+    }
+
+    let token_account =
+        get_associated_token_address_for_mint(&args.client, &keypair.pubkey(), &args.mint)?;
+    ixs.push(initialize_from_mint_ix(
+        &args.mint,
+        &token_account,
+        &keypair.pubkey(),
+        shard_number,
+    )?);
+
+    let sig = send(&args.client, &keypair, args.priority, ixs)?;
+
+    let data = args
+        .client
+        .get_account_data(&derive_inscription_metadata(&inscription))?;
+    let metadata = decode_inscription_metadata(&data)?;
+
+    println!(
+        "Initialized inscription {inscription} with rank {} in tx {sig}",
+        metadata.inscription_rank
+    );
+
+    Ok(())
+}
+
+/// Read the data to inscribe, defaulting to the off-chain JSON the mint's metadata points to.
+fn read_data(client: &RpcClient, mint: &Pubkey, file: Option<PathBuf>) -> Result<Vec<u8>> {
+    if let Some(file) = file {
+        return Ok(fs::read(file)?);
+    }
+
+    let metadata = decode_metadata_from_mint(client, mint.to_string())?;
+    let uri = metadata.uri.trim_matches(char::from(0));
+    let json: Value = rpc_cache::get_json(uri)?;
+    Ok(serde_json::to_vec(&json)?)
+}
+
+pub fn write_inscription(args: WriteInscriptionArgs) -> Result<()> {
+    let keypair = parse_keypair(args.keypair, parse_solana_config());
+    let payer = keypair.pubkey();
+
+    if args.chunk_size == 0 {
+        return Err(anyhow!("Chunk size must be greater than zero"));
+    }
+
+    let data = read_data(&args.client, &args.mint, args.file)?;
+    if data.is_empty() {
+        return Err(anyhow!("Nothing to inscribe: the data is empty"));
+    }
+
+    let inscription = derive_mint_inscription(&args.mint);
+    let inscription_metadata = derive_inscription_metadata(&inscription);
+    let metadata = args
+        .client
+        .get_account_data(&inscription_metadata)
+        .map_err(|_| {
+            anyhow!(
+                "{} has no inscription, run `inscribe init` first",
+                args.mint
+            )
+        })
+        .and_then(|data| decode_inscription_metadata(&data))?;
+
+    // Tagged data, such as an image, goes in an associated inscription.
+    let target = match &args.tag {
+        Some(tag) => {
+            let associated = derive_associated_inscription(tag, &inscription_metadata);
+            if !metadata
+                .associated_inscriptions
+                .iter()
+                .any(|a| &a.tag == tag)
+            {
+                let ix = initialize_associated_inscription_ix(
+                    &inscription_metadata,
+                    &associated,
+                    &payer,
+                    tag,
+                )?;
+                send(&args.client, &keypair, args.priority.clone(), vec![ix])?;
+                println!("Created associated inscription {associated} for tag {tag}");
+            }
+            associated
+        }
+        None => inscription,
+    };
+
+    // Clear any previous data so none of it is left past the end of the new data.
+    let current_size = args.client.get_account_data(&target)?.len();
+    if current_size > 0 {
+        let ix = clear_data_ix(&target, &inscription_metadata, &payer, args.tag.clone())?;
+        send(&args.client, &keypair, args.priority.clone(), vec![ix])?;
+    }
+
+    let total_size =
+        u32::try_from(data.len()).map_err(|_| anyhow!("{} bytes is too large", data.len()))?;
+    let mut size = 0;
+    while size < total_size {
+        size = (size + MAX_REALLOC_SIZE).min(total_size);
+        let ix = allocate_ix(
+            &target,
+            &inscription_metadata,
+            &payer,
+            args.tag.clone(),
+            size,
+        )?;
+        send(&args.client, &keypair, args.priority.clone(), vec![ix])?;
+    }
+
+    let chunks = data.chunks(args.chunk_size).collect::<Vec<_>>();
+    let pb = create_progress_bar("Writing inscription...", chunks.len() as u64);
+    for (i, chunk) in chunks.into_iter().enumerate() {
+        let ix = write_data_ix(
+            &target,
+            &inscription_metadata,
+            &payer,
+            args.tag.clone(),
+            (i * args.chunk_size) as u32,
+            chunk.to_vec(),
+        )?;
+        send(&args.client, &keypair, args.priority.clone(), vec![ix]).map_err(|e| {
+            anyhow!(
+                "Failed writing bytes at offset {}: {e}",
+                i * args.chunk_size
+            )
+        })?;
+        pb.inc(1);
+    }
+    pb.finish_and_clear();
+
+    println!("Inscribed {} bytes to {target}", data.len());
+
+    Ok(())
+}
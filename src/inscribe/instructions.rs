@@ -0,0 +1,186 @@
+use anyhow::Result;
+use borsh::BorshSerialize;
+use solana_program::{
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+    system_program,
+};
+
+use super::data::{
+    derive_inscription_metadata, derive_inscription_shard, derive_mint_inscription,
+    MPL_INSCRIPTION_PROGRAM_ID,
+};
+use crate::derive::derive_metadata_pda;
+
+// Instruction discriminators, the index of each instruction in the program.
+const INITIALIZE_FROM_MINT: u8 = 1;
+const WRITE_DATA: u8 = 3;
+const CLEAR_DATA: u8 = 4;
+const CREATE_SHARD: u8 = 7;
+const ALLOCATE: u8 = 8;
+const INITIALIZE_ASSOCIATED_INSCRIPTION: u8 = 10;
+
+/// Accounts can grow by at most this many bytes per instruction.
+pub const MAX_REALLOC_SIZE: u32 = 10_240;
+
+#[derive(BorshSerialize)]
+struct WriteDataArgs {
+    associated_tag: Option<String>,
+    offset: u32,
+    value: Vec<u8>,
+}
+
+#[derive(BorshSerialize)]
+struct ClearDataArgs {
+    associated_tag: Option<String>,
+}
+
+#[derive(BorshSerialize)]
+struct AllocateArgs {
+    associated_tag: Option<String>,
+    target_size: u32,
+}
+
+#[derive(BorshSerialize)]
+struct AssociateArgs {
+    association_tag: String,
+}
+
+fn instruction<T: BorshSerialize>(
+    discriminator: u8,
+    args: &T,
+    accounts: Vec<AccountMeta>,
+) -> Result<Instruction> {
+    let mut data = vec![discriminator];
+    data.extend(args.try_to_vec()?);
+
+    Ok(Instruction {
+        program_id: MPL_INSCRIPTION_PROGRAM_ID,
+        accounts,
+        data,
+    })
+}
+
+/// Accounts for the instructions that modify an inscription's data. The payer is the
+/// authority, so the optional authority account is omitted.
+fn data_accounts(
+    inscription: &Pubkey,
+    inscription_metadata: &Pubkey,
+    payer: &Pubkey,
+) -> Vec<AccountMeta> {
+    vec![
+        AccountMeta::new(*inscription, false),
+        AccountMeta::new(*inscription_metadata, false),
+        AccountMeta::new(*payer, true),
+        AccountMeta::new_readonly(MPL_INSCRIPTION_PROGRAM_ID, false),
+        AccountMeta::new_readonly(system_program::ID, false),
+    ]
+}
+
+pub fn create_shard_ix(payer: &Pubkey, shard_number: u8) -> Result<Instruction> {
+    instruction(
+        CREATE_SHARD,
+        &shard_number,
+        vec![
+            AccountMeta::new(derive_inscription_shard(shard_number), false),
+            AccountMeta::new(*payer, true),
+            AccountMeta::new_readonly(system_program::ID, false),
+        ],
+    )
+}
+
+/// Create the inscription for a mint, taking its rank from `shard_number`.
+pub fn initialize_from_mint_ix(
+    mint: &Pubkey,
+    token_account: &Pubkey,
+    payer: &Pubkey,
+    shard_number: u8,
+) -> Result<Instruction> {
+    let inscription = derive_mint_inscription(mint);
+
+    instruction(
+        INITIALIZE_FROM_MINT,
+        &(),
+        vec![
+            AccountMeta::new(inscription, false),
+            AccountMeta::new(derive_inscription_metadata(&inscription), false),
+            AccountMeta::new_readonly(*mint, false),
+            AccountMeta::new_readonly(derive_metadata_pda(mint), false),
+            AccountMeta::new_readonly(*token_account, false),
+            AccountMeta::new(derive_inscription_shard(shard_number), false),
+            AccountMeta::new(*payer, true),
+            AccountMeta::new_readonly(MPL_INSCRIPTION_PROGRAM_ID, false),
+            AccountMeta::new_readonly(system_program::ID, false),
+        ],
+    )
+}
+
+pub fn initialize_associated_inscription_ix(
+    inscription_metadata: &Pubkey,
+    associated_inscription: &Pubkey,
+    payer: &Pubkey,
+    tag: &str,
+) -> Result<Instruction> {
+    instruction(
+        INITIALIZE_ASSOCIATED_INSCRIPTION,
+        &AssociateArgs {
+            association_tag: tag.to_string(),
+        },
+        vec![
+            AccountMeta::new(*inscription_metadata, false),
+            AccountMeta::new(*associated_inscription, false),
+            AccountMeta::new(*payer, true),
+            AccountMeta::new_readonly(MPL_INSCRIPTION_PROGRAM_ID, false),
+            AccountMeta::new_readonly(system_program::ID, false),
+        ],
+    )
+}
+
+pub fn clear_data_ix(
+    inscription: &Pubkey,
+    inscription_metadata: &Pubkey,
+    payer: &Pubkey,
+    associated_tag: Option<String>,
+) -> Result<Instruction> {
+    instruction(
+        CLEAR_DATA,
+        &ClearDataArgs { associated_tag },
+        data_accounts(inscription, inscription_metadata, payer),
+    )
+}
+
+pub fn allocate_ix(
+    inscription: &Pubkey,
+    inscription_metadata: &Pubkey,
+    payer: &Pubkey,
+    associated_tag: Option<String>,
+    target_size: u32,
+) -> Result<Instruction> {
+    instruction(
+        ALLOCATE,
+        &AllocateArgs {
+            associated_tag,
+            target_size,
+        },
+        data_accounts(inscription, inscription_metadata, payer),
+    )
+}
+
+pub fn write_data_ix(
+    inscription: &Pubkey,
+    inscription_metadata: &Pubkey,
+    payer: &Pubkey,
+    associated_tag: Option<String>,
+    offset: u32,
+    value: Vec<u8>,
+) -> Result<Instruction> {
+    instruction(
+        WRITE_DATA,
+        &WriteDataArgs {
+            associated_tag,
+            offset,
+            value,
+        },
+        data_accounts(inscription, inscription_metadata, payer),
+    )
+}
@@ -0,0 +1,126 @@
+mod data;
+mod decode;
+mod inscription;
+mod instructions;
+
+pub use data::*;
+pub use decode::*;
+pub use inscription::*;
+pub use instructions::*;
+
+use std::path::PathBuf;
+
+use anyhow::Result;
+use metaboss_lib::data::Priority;
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::{
+    instruction::Instruction,
+    pubkey::Pubkey,
+    signature::{Keypair, Signature},
+};
+use structopt::StructOpt;
+
+use crate::pipeline::Pipeline;
+
+#[derive(Debug, StructOpt)]
+pub enum InscribeSubcommands {
+    /// Initialize the inscription for a mint
+    #[structopt(name = "init")]
+    Init {
+        /// Path to the keypair file of the NFT's holder
+        #[structopt(short, long)]
+        keypair: Option<String>,
+
+        /// Mint account of the NFT
+        #[structopt(short = "a", long)]
+        mint: Pubkey,
+
+        /// Priority of the transaction: higher priority costs more.
+        /// See https://metaboss.rs/priority-fees.html for more details.
+        #[structopt(short = "P", long, default_value = "none")]
+        priority: Priority,
+    },
+    /// Write data to a mint's inscription, or to an associated inscription with --tag
+    #[structopt(name = "write")]
+    Write {
+        /// Path to the keypair file of the inscription's authority
+        #[structopt(short, long)]
+        keypair: Option<String>,
+
+        /// Mint account of the NFT
+        #[structopt(short = "a", long)]
+        mint: Pubkey,
+
+        /// File to inscribe, defaults to the JSON at the metadata URI
+        #[structopt(short, long)]
+        file: Option<PathBuf>,
+
+        /// Write to the associated inscription with this tag, e.g. image
+        #[structopt(short, long)]
+        tag: Option<String>,
+
+        /// Bytes written per transaction
+        #[structopt(long, default_value = "800")]
+        chunk_size: usize,
+
+        /// Priority of the transaction: higher priority costs more.
+        /// See https://metaboss.rs/priority-fees.html for more details.
+        #[structopt(short = "P", long, default_value = "none")]
+        priority: Priority,
+    },
+    /// Decode a mint's inscription into JSON
+    #[structopt(name = "decode")]
+    Decode {
+        /// Mint account of the NFT
+        #[structopt(short = "a", long)]
+        mint: Pubkey,
+
+        /// Directory to write the JSON file to, prints to stdout if not set
+        #[structopt(short, long)]
+        output: Option<PathBuf>,
+    },
+}
+
+pub fn process_inscribe(client: RpcClient, commands: InscribeSubcommands) -> Result<()> {
+    match commands {
+        InscribeSubcommands::Init {
+            keypair,
+            mint,
+            priority,
+        } => init_inscription(InitInscriptionArgs {
+            client,
+            keypair,
+            mint,
+            priority,
+        }),
+        InscribeSubcommands::Write {
+            keypair,
+            mint,
+            file,
+            tag,
+            chunk_size,
+            priority,
+        } => write_inscription(WriteInscriptionArgs {
+            client,
+            keypair,
+            mint,
+            file,
+            tag,
+            chunk_size,
+            priority,
+        }),
+        InscribeSubcommands::Decode { mint, output } => decode_inscription(&client, &mint, output),
+    }
+}
+
+fn send(
+    client: &RpcClient,
+    keypair: &Keypair,
+    priority: Priority,
+    ixs: Vec<Instruction>,
+) -> Result<Signature> {
+    Pipeline::with_priority(priority)
+        .with_active_lookup_table()?
+        .with_active_nonce()?
+        .run(client, &[keypair], ixs)
+}
@@ -1,25 +1,66 @@
+// RPC errors from solana-client are large and returned by value throughout.
+#![allow(clippy::result_large_err)]
+
 pub mod airdrop;
+pub mod announce;
+pub mod audit;
+pub mod bench;
 pub mod burn;
 pub mod cache;
+pub mod changelog;
 pub mod check;
+pub mod checkpoint;
+pub mod cm;
+pub mod cnft;
 pub mod collections;
+pub mod consolidate;
 pub mod constants;
+pub mod convert;
+pub mod core_asset;
 pub mod create;
 pub mod data;
 pub mod decode;
+pub mod delegate;
 pub mod derive;
+pub mod download;
 pub mod errors;
+pub mod expect;
+pub mod export;
 pub mod extend_program;
+pub mod fees;
+pub mod files;
 pub mod find;
+pub mod history;
+pub mod idl_errors;
+pub mod incident;
+pub mod inscribe;
 pub mod limiter;
+pub mod logging;
+pub mod lut;
+pub mod migrate;
 pub mod mint;
+pub mod nonce;
+pub mod offline;
 pub mod opt;
+pub mod order;
+pub mod output;
+pub mod pack;
 pub mod parse;
+pub mod pipeline;
+pub mod plan;
+pub mod plugin;
 pub mod process_subcommands;
+pub mod profile;
+pub mod proxy;
+pub mod rpc_cache;
+pub mod rules;
+pub mod selftest;
+pub mod serve;
 pub mod setup;
 pub mod sign;
 pub mod snapshot;
 pub mod spinner;
+pub mod squads;
 pub mod theindexio;
 pub mod transfer;
 pub mod unverify;
@@ -27,4 +68,5 @@ pub mod update;
 pub mod uses;
 pub mod utils;
 pub mod verify;
+pub mod workspace;
 pub mod wtf_errors;
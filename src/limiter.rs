@@ -1,8 +1,25 @@
+use log::{info, warn};
+use once_cell::sync::Lazy;
 use ratelimit::Handle;
-use std::{thread, time::Duration};
+use regex::Regex;
+use solana_client::rpc_client::RpcClient;
+use std::{
+    fmt::Display,
+    sync::Arc,
+    thread,
+    time::{Duration, Instant},
+};
+use tokio::sync::Semaphore;
 
 use crate::constants::*;
 
+/// Times to back off and retry a request the RPC rejected for exceeding its rate limit.
+pub const MAX_RATE_LIMITED_RETRIES: u32 = 6;
+
+// Matches a standalone 429 so addresses that happen to contain the digits don't count.
+static RATE_LIMITED: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"(?i)\b429\b|too many requests|rate limit").unwrap());
+
 pub fn create_default_rate_limiter() -> Handle {
     let num_cpus = num_cpus::get();
 
@@ -46,3 +63,131 @@ pub fn create_rate_limiter_with_capacity(capacity: u32, delay: u32) -> Handle {
     });
     handle
 }
+
+/// Whether an error is the RPC node rejecting a request for exceeding its rate limit,
+/// rather than the request itself failing.
+pub fn is_rate_limited(error: &str) -> bool {
+    RATE_LIMITED.is_match(error)
+}
+
+/// Exponential backoff for the given retry attempt, capped at 30 seconds.
+pub fn rate_limited_backoff(attempt: u32) -> Duration {
+    Duration::from_millis(500u64.saturating_mul(1 << attempt.min(6)).min(30_000))
+}
+
+/// Run an RPC request, backing off and retrying while the RPC reports it is rate limited.
+/// Any other error is returned straight away.
+pub fn with_rate_limit_backoff<T, E: Display>(mut f: impl FnMut() -> Result<T, E>) -> Result<T, E> {
+    let mut attempt = 0;
+    loop {
+        match f() {
+            Err(e) if attempt < MAX_RATE_LIMITED_RETRIES && is_rate_limited(&e.to_string()) => {
+                let backoff = rate_limited_backoff(attempt);
+                warn!("Rate limited by the RPC, retrying in {backoff:?}: {e}");
+                thread::sleep(backoff);
+                attempt += 1;
+            }
+            result => return result,
+        }
+    }
+}
+
+/// Requests per second for a batch command: the global `--rate-limit` when set, which
+/// takes precedence over the command's own.
+pub fn rate_limit_or(command_rate_limit: usize) -> usize {
+    GLOBAL_RATE_LIMIT
+        .read()
+        .unwrap()
+        .unwrap_or(command_rate_limit)
+        .max(1)
+}
+
+/// Most requests a batch command keeps in flight at once: the global `--concurrency`
+/// when set, otherwise the command's own default.
+pub fn concurrency_or(default: usize) -> usize {
+    CONCURRENCY.read().unwrap().unwrap_or(default).max(1)
+}
+
+/// A semaphore with a permit per request the global `--concurrency` allows in flight,
+/// unlimited when it isn't set.
+pub fn create_concurrency_limiter() -> Arc<Semaphore> {
+    Arc::new(Semaphore::new(concurrency_or(Semaphore::MAX_PERMITS)))
+}
+
+// How often the fee guard samples fees while sending, and while paused.
+const FEE_SAMPLE_INTERVAL: Duration = Duration::from_secs(15);
+const FEE_PAUSED_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Median of the priority fees, in microlamports per compute unit, the RPC reports for
+/// recent slots.
+pub fn recent_network_fee(client: &RpcClient) -> anyhow::Result<u64> {
+    let mut fees: Vec<u64> = client
+        .get_recent_prioritization_fees(&[])?
+        .into_iter()
+        .map(|f| f.prioritization_fee)
+        .collect();
+    fees.sort_unstable();
+    Ok(fees.get(fees.len() / 2).copied().unwrap_or_default())
+}
+
+/// Holds back batch sends while network priority fees are above the global
+/// `--max-network-fee`, and lets them through again once fees come back down.
+///
+/// Fees are sampled at most every 15 seconds, so the guard can be checked before every send.
+pub struct FeeGuard {
+    max_fee: Option<u64>,
+    last_sample: Option<Instant>,
+}
+
+impl Default for FeeGuard {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl FeeGuard {
+    pub fn new() -> Self {
+        Self {
+            max_fee: *MAX_NETWORK_FEE.read().unwrap(),
+            last_sample: None,
+        }
+    }
+
+    /// Block until network fees are at or below the maximum. Returns straight away when no
+    /// maximum is set or fees were sampled recently.
+    pub fn wait(&mut self, client: &RpcClient) {
+        let max_fee = match self.max_fee {
+            Some(max_fee) => max_fee,
+            None => return,
+        };
+        if matches!(self.last_sample, Some(t) if t.elapsed() < FEE_SAMPLE_INTERVAL) {
+            return;
+        }
+
+        let mut paused = false;
+        loop {
+            self.last_sample = Some(Instant::now());
+            // Don't stall the run if fees can't be read, the next sample may work.
+            let fee = match recent_network_fee(client) {
+                Ok(fee) => fee,
+                Err(e) => {
+                    warn!("Failed to sample network fees: {e}");
+                    return;
+                }
+            };
+            if fee <= max_fee {
+                if paused {
+                    info!("Network fees down to {fee} microlamports, resuming");
+                }
+                return;
+            }
+            if !paused {
+                warn!(
+                    "Network fees at {fee} microlamports are above --max-network-fee {max_fee}, pausing"
+                );
+                paused = true;
+            }
+            thread::sleep(FEE_PAUSED_INTERVAL);
+        }
+    }
+}
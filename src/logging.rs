@@ -0,0 +1,52 @@
+//! Logging through `tracing`, set up from the global `--log-level` and `--log-path` options.
+//!
+//! Events from `log` macros, here and in dependencies, go to the same subscriber. Logs are
+//! written to stderr so they don't mix with command output, or to the log file, where each
+//! line has its timestamp, thread and span so output from concurrent tasks can be untangled
+//! after a long run.
+
+use std::{fs::OpenOptions, path::Path, sync::Mutex};
+
+use anyhow::{anyhow, Result};
+use tracing_subscriber::{fmt, EnvFilter};
+
+// Crates `--log-level` applies to. Logs from other crates are only enabled through RUST_LOG.
+const LOG_TARGETS: &[&str] = &["metaboss", "jib", "solana"];
+
+fn env_filter(level: &str) -> Result<EnvFilter> {
+    if let Ok(filter) = EnvFilter::try_from_default_env() {
+        return Ok(filter);
+    }
+    let directives = LOG_TARGETS
+        .iter()
+        .map(|target| format!("{target}={level}"))
+        .collect::<Vec<_>>()
+        .join(",");
+    EnvFilter::try_new(directives).map_err(|_| anyhow!("Invalid log level: {level}"))
+}
+
+/// Install the global subscriber. RUST_LOG, when set, takes precedence over the level.
+pub fn init(level: &str, log_path: Option<&Path>) -> Result<()> {
+    let builder = fmt()
+        .with_env_filter(env_filter(level)?)
+        .with_thread_ids(true);
+
+    let result = match log_path {
+        Some(path) => {
+            let file = OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(path)
+                .map_err(|e| anyhow!("Failed to open log file {}: {e}", path.display()))?;
+            builder
+                .with_ansi(false)
+                .with_writer(Mutex::new(file))
+                .try_init()
+        }
+        None => builder
+            .without_time()
+            .with_writer(std::io::stderr)
+            .try_init(),
+    };
+    result.map_err(|e| anyhow!("Failed to set up logging: {e}"))
+}
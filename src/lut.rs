@@ -0,0 +1,216 @@
+//! Manage address lookup tables for use with the global `--lut` option.
+
+use std::{
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+use anyhow::{anyhow, Result};
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::{
+    address_lookup_table::{
+        instruction::{
+            close_lookup_table, create_lookup_table, deactivate_lookup_table, extend_lookup_table,
+        },
+        state::AddressLookupTable,
+    },
+    instruction::Instruction,
+    pubkey::Pubkey,
+    signature::{Keypair, Signature},
+    signer::Signer,
+};
+use structopt::StructOpt;
+
+use crate::{
+    files::read_json,
+    parse::{parse_keypair, parse_solana_config},
+    pipeline::Pipeline,
+};
+
+// Keeps each extend transaction well under the size limit.
+const ADDRESSES_PER_EXTEND: usize = 20;
+
+#[derive(Debug, StructOpt)]
+pub enum LutSubcommands {
+    /// Create a lookup table, optionally filling it with addresses
+    #[structopt(name = "create")]
+    Create {
+        /// Path to the authority keypair file, which also pays the rent
+        #[structopt(short, long)]
+        keypair: Option<String>,
+
+        /// JSON file with a list of addresses to add
+        #[structopt(short, long)]
+        addresses: Option<PathBuf>,
+    },
+    /// Add addresses to a lookup table
+    #[structopt(name = "extend")]
+    Extend {
+        /// Path to the authority keypair file
+        #[structopt(short, long)]
+        keypair: Option<String>,
+
+        /// Lookup table address
+        lookup_table: Pubkey,
+
+        /// JSON file with a list of addresses to add
+        #[structopt(short, long)]
+        addresses: PathBuf,
+    },
+    /// Deactivate a lookup table so it can be closed once the cooldown ends
+    #[structopt(name = "deactivate")]
+    Deactivate {
+        /// Path to the authority keypair file
+        #[structopt(short, long)]
+        keypair: Option<String>,
+
+        /// Lookup table address
+        lookup_table: Pubkey,
+    },
+    /// Close a deactivated lookup table and reclaim its rent
+    #[structopt(name = "close")]
+    Close {
+        /// Path to the authority keypair file
+        #[structopt(short, long)]
+        keypair: Option<String>,
+
+        /// Lookup table address
+        lookup_table: Pubkey,
+
+        /// Account to send the rent to, defaults to the authority
+        #[structopt(long)]
+        recipient: Option<Pubkey>,
+    },
+}
+
+pub fn process_lut(client: RpcClient, commands: LutSubcommands) -> Result<()> {
+    match commands {
+        LutSubcommands::Create { keypair, addresses } => {
+            create_lut(&client, keypair, addresses.as_deref())
+        }
+        LutSubcommands::Extend {
+            keypair,
+            lookup_table,
+            addresses,
+        } => {
+            let keypair = parse_keypair(keypair, parse_solana_config());
+            extend_lut(
+                &client,
+                &keypair,
+                &lookup_table,
+                &read_addresses(&addresses)?,
+            )
+        }
+        LutSubcommands::Deactivate {
+            keypair,
+            lookup_table,
+        } => deactivate_lut(&client, keypair, &lookup_table),
+        LutSubcommands::Close {
+            keypair,
+            lookup_table,
+            recipient,
+        } => close_lut(&client, keypair, &lookup_table, recipient),
+    }
+}
+
+fn read_addresses(path: &Path) -> Result<Vec<Pubkey>> {
+    let addresses: Vec<String> = read_json(path)?;
+
+    addresses
+        .iter()
+        .map(|a| Pubkey::from_str(a).map_err(|_| anyhow!("Invalid address: {a}")))
+        .collect()
+}
+
+fn send(client: &RpcClient, keypair: &Keypair, ixs: Vec<Instruction>) -> Result<Signature> {
+    let sig = Pipeline::standard().run(client, &[keypair], ixs)?;
+    println!("Tx sig: {sig}");
+    Ok(sig)
+}
+
+pub fn create_lut(
+    client: &RpcClient,
+    keypair: Option<String>,
+    addresses: Option<&Path>,
+) -> Result<()> {
+    let keypair = parse_keypair(keypair, parse_solana_config());
+
+    // Read the addresses first so a bad file doesn't leave an empty table behind.
+    let addresses = addresses.map(read_addresses).transpose()?;
+
+    let recent_slot = client.get_slot()?;
+    let (ix, lookup_table) = create_lookup_table(keypair.pubkey(), keypair.pubkey(), recent_slot);
+    send(client, &keypair, vec![ix])?;
+    println!("Created lookup table {lookup_table}");
+
+    if let Some(addresses) = addresses {
+        extend_lut(client, &keypair, &lookup_table, &addresses)?;
+    }
+
+    Ok(())
+}
+
+pub fn extend_lut(
+    client: &RpcClient,
+    keypair: &Keypair,
+    lookup_table: &Pubkey,
+    addresses: &[Pubkey],
+) -> Result<()> {
+    for chunk in addresses.chunks(ADDRESSES_PER_EXTEND) {
+        let ix = extend_lookup_table(
+            *lookup_table,
+            keypair.pubkey(),
+            Some(keypair.pubkey()),
+            chunk.to_vec(),
+        );
+        send(client, keypair, vec![ix])?;
+    }
+    println!(
+        "Added {} addresses to lookup table {lookup_table}",
+        addresses.len()
+    );
+
+    Ok(())
+}
+
+pub fn deactivate_lut(
+    client: &RpcClient,
+    keypair: Option<String>,
+    lookup_table: &Pubkey,
+) -> Result<()> {
+    let keypair = parse_keypair(keypair, parse_solana_config());
+
+    let ix = deactivate_lookup_table(*lookup_table, keypair.pubkey());
+    send(client, &keypair, vec![ix])?;
+    println!("Deactivated lookup table {lookup_table}. It can be closed in about 513 slots.");
+
+    Ok(())
+}
+
+pub fn close_lut(
+    client: &RpcClient,
+    keypair: Option<String>,
+    lookup_table: &Pubkey,
+    recipient: Option<Pubkey>,
+) -> Result<()> {
+    let keypair = parse_keypair(keypair, parse_solana_config());
+
+    let account = client.get_account(lookup_table)?;
+    let table = AddressLookupTable::deserialize(&account.data)
+        .map_err(|e| anyhow!("{lookup_table} is not an address lookup table: {e}"))?;
+    if table.meta.deactivation_slot == u64::MAX {
+        return Err(anyhow!(
+            "Lookup table {lookup_table} must be deactivated before it can be closed"
+        ));
+    }
+
+    let recipient = recipient.unwrap_or_else(|| keypair.pubkey());
+    let ix = close_lookup_table(*lookup_table, keypair.pubkey(), recipient);
+    send(client, &keypair, vec![ix])?;
+    println!(
+        "Closed lookup table {lookup_table}, {} lamports sent to {recipient}",
+        account.lamports
+    );
+
+    Ok(())
+}
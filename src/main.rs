@@ -1,11 +1,33 @@
 #[macro_use]
 extern crate log;
 
-use anyhow::Result;
+use anyhow::{anyhow, Result};
 use metaboss::airdrop::process_airdrop;
+use metaboss::announce::{announce, announcements, AnnounceArgs, AnnouncementsArgs};
+use metaboss::audit::process_audit;
+use metaboss::bench::process_bench;
 use metaboss::check::process_check;
+use metaboss::checkpoint::open_checkpoint;
+use metaboss::cm::process_cm;
+use metaboss::consolidate::{consolidate, ConsolidateArgs};
 use metaboss::constants::PUBLIC_RPC_URLS;
+use metaboss::convert::process_convert;
+use metaboss::core_asset::process_core;
+use metaboss::delegate::process_delegate;
+use metaboss::download::process_download;
+use metaboss::expect::load_expected;
+use metaboss::export::process_export;
 use metaboss::extend_program::process_extend_program;
+use metaboss::fees::process_fees;
+use metaboss::incident::{freeze_all, lock_all, process_incident, FreezeAllArgs};
+use metaboss::inscribe::process_inscribe;
+use metaboss::logging;
+use metaboss::lut::process_lut;
+use metaboss::migrate::process_migrate;
+use metaboss::offline::submit_signed;
+use metaboss::order::set_order;
+use metaboss::plan::process_plan;
+use metaboss::plugin::{global_options, run_plugin, PluginContext};
 use solana_client::{nonblocking::rpc_client::RpcClient as AsyncRpcClient, rpc_client::RpcClient};
 use solana_sdk::commitment_config::CommitmentConfig;
 use std::str::FromStr;
@@ -14,23 +36,53 @@ use structopt::StructOpt;
 
 use metaboss::constants::*;
 use metaboss::opt::*;
-use metaboss::parse::parse_solana_config;
+use metaboss::output;
+use metaboss::parse::{keypair_override, parse_solana_config, set_seed_keypair};
 use metaboss::process_subcommands::*;
+use metaboss::profile::load_profile;
+use metaboss::proxy::{run_proxy, ProxyArgs};
+use metaboss::rules::process_rules;
+use metaboss::selftest::{selftest, SelftestArgs};
+use metaboss::serve::{serve, ServeArgs};
 use metaboss::snapshot::process_snapshot;
+use metaboss::workspace::{command_name, open_workspace, run_dir};
 
 #[tokio::main]
 async fn main() -> Result<()> {
-    let options = Opt::from_args();
+    let matches = Opt::clap().get_matches();
+    let options = Opt::from_clap(&matches);
+    let plugin_options = global_options(&options);
+    output::init(options.output_format, options.quiet)?;
+    let command = command_name(&matches);
 
-    let log_level = format!("solana={}", options.log_level);
-    solana_logger::setup_with_default(&log_level);
+    logging::init(&options.log_level, options.log_path.as_deref())?;
 
+    // Command-line options and their METABOSS_* environment variables take precedence over
+    // the profile, and the profile over the Solana CLI config.
+    let profile = load_profile(options.profile.as_deref())?.unwrap_or_default();
     let sol_config = parse_solana_config();
+    if let Some(phrase) = &options.keypair_seed {
+        set_seed_keypair(phrase, options.derivation.as_deref())?;
+    } else if options.derivation.is_some() {
+        return Err(anyhow!(
+            "--derivation needs a seed phrase from --keypair-seed"
+        ));
+    }
+    let config_keypair =
+        keypair_override().or_else(|| sol_config.as_ref().map(|c| c.keypair_path.clone()));
 
-    let (rpc, commitment) = if let Some(cli_rpc) = options.rpc {
-        (cli_rpc, String::from("confirmed"))
+    let (rpc, commitment) = if let Some(cli_rpc) = options.rpc.or(profile.rpc) {
+        (
+            cli_rpc,
+            profile
+                .commitment
+                .unwrap_or_else(|| String::from("confirmed")),
+        )
     } else if let Some(config) = sol_config {
-        (config.json_rpc_url, config.commitment)
+        (
+            config.json_rpc_url,
+            profile.commitment.unwrap_or(config.commitment),
+        )
     } else {
         info!(
             "Could not find a valid Solana-CLI config file. Defaulting to https://devnet.genesysgo.net devnet node."
@@ -53,60 +105,356 @@ async fn main() -> Result<()> {
         *RPC_DELAY_NS.write().unwrap() = RATE_LIMIT_DELAYS[&rpc.as_str()];
     }
 
-    let commitment = CommitmentConfig::from_str(&commitment)?;
+    if options.jito.is_some() && !supports_jito(&options.cmd) {
+        return Err(anyhow!(
+            "{command} can't send Jito bundles and would send its transactions individually. --jito is supported by update data-all"
+        ));
+    }
+    if let Some(block_engine) = options.jito {
+        *JITO_BLOCK_ENGINE.write().unwrap() = Some(block_engine);
+        *JITO_TIP_LAMPORTS.write().unwrap() = options.jito_tip;
+    }
+
+    if options.multisig.is_some() && !supports_multisig(&options.cmd) {
+        return Err(anyhow!(
+            "{command} can't propose to a multisig and would sign and send directly instead. --multisig is supported by set update-authority, update data and collections verify"
+        ));
+    }
+    if let Some(multisig) = options.multisig {
+        *SQUADS_MULTISIG.write().unwrap() = Some(multisig);
+        *SQUADS_VAULT_INDEX.write().unwrap() = options.vault_index;
+    }
+
+    if options.export_unsigned.is_some() && !supports_export_unsigned(&options.cmd) {
+        return Err(anyhow!(
+            "{command} can't export unsigned transactions and would sign and send them instead. --export-unsigned is supported by the update, set, collections and uses commands"
+        ));
+    }
+    if let Some(dir) = options.export_unsigned {
+        *EXPORT_UNSIGNED_DIR.write().unwrap() = Some(dir);
+        *OFFLINE_AUTHORITY.write().unwrap() = options.offline_authority;
+    }
+
+    // Every transaction signs with the nonce account's current value and advances it, so
+    // only one can be in flight at a time.
+    let concurrency = match (options.concurrency, &options.nonce_account) {
+        (Some(concurrency), Some(_)) if concurrency > 1 => {
+            return Err(anyhow!(
+                "--nonce-account can't be used with --concurrency above 1: transactions sharing a nonce account have to be sent one at a time"
+            ));
+        }
+        (None, Some(_)) => Some(1),
+        (concurrency, _) => concurrency,
+    };
+
+    if let Some(nonce_account) = options.nonce_account {
+        *NONCE_ACCOUNT.write().unwrap() = Some(nonce_account);
+        *NONCE_AUTHORITY.write().unwrap() = options.nonce_authority;
+    }
+
+    if let Some(lut) = options.lut {
+        *LOOKUP_TABLE.write().unwrap() = Some(lut);
+    }
+
+    if let Some(root) = &options.workspace {
+        let dir = open_workspace(root, &command)?;
+        println!("Writing outputs to {}", dir.display());
+        if options.receipts.is_none() {
+            *RECEIPTS_FILE.write().unwrap() =
+                Some(dir.join("receipts.jsonl").display().to_string());
+        }
+    }
+
+    if let Some(receipts) = options.receipts {
+        *RECEIPTS_FILE.write().unwrap() = Some(receipts);
+    }
+
+    if let Some(rate_limit) = options.rate_limit.or(profile.rate_limit) {
+        if rate_limit == 0 {
+            return Err(anyhow!("--rate-limit must be at least 1"));
+        }
+        *GLOBAL_RATE_LIMIT.write().unwrap() = Some(rate_limit);
+        *USE_RATE_LIMIT.write().unwrap() = true;
+        *RPC_DELAY_NS.write().unwrap() = (NANO_SECONDS_IN_SECOND / rate_limit) as u32;
+    }
+
+    if let Some(concurrency) = concurrency {
+        if concurrency == 0 {
+            return Err(anyhow!("--concurrency must be at least 1"));
+        }
+        *CONCURRENCY.write().unwrap() = Some(concurrency);
+        rayon::ThreadPoolBuilder::new()
+            .num_threads(concurrency)
+            .build_global()?;
+    }
+
+    *MAX_NETWORK_FEE.write().unwrap() = options.max_network_fee;
+    *RPC_CACHE.write().unwrap() = options.cache && !options.no_cache;
+
+    if let Some(path) = &options.resume {
+        open_checkpoint(path)?;
+    }
+    set_order(options.order, options.seed);
+    if let Some(path) = &options.expect_file {
+        load_expected(path, options.on_mismatch)?;
+    }
+
+    let commitment = options.commitment.unwrap_or(commitment);
+    let commitment = CommitmentConfig::from_str(&commitment)
+        .map_err(|_| anyhow!("Invalid commitment: {commitment}"))?;
     let timeout = Duration::from_secs(options.timeout);
 
     let client = RpcClient::new_with_timeout_and_commitment(rpc.clone(), timeout, commitment);
     let async_client =
         AsyncRpcClient::new_with_timeout_and_commitment(rpc.clone(), timeout, commitment);
 
-    match options.cmd {
-        Command::Collections {
-            collections_subcommands,
-        } => process_collections(client, async_client, collections_subcommands).await?,
-        Command::Airdrop {
-            airdrop_subcommands,
-        } => process_airdrop(client, airdrop_subcommands).await?,
-        Command::Burn { burn_subcommands } => process_burn_asset(client, burn_subcommands).await?,
-        Command::BurnNft {
-            burn_nft_subcommands,
-        } => process_burn_nft(client, burn_nft_subcommands).await?,
-        Command::BurnPrint {
-            burn_print_subcommands,
-        } => process_burn_print(client, burn_print_subcommands).await?,
-        Command::Check { check_subcommands } => process_check(check_subcommands).await?,
-        Command::Create { create_subcommands } => process_create(client, create_subcommands)?,
-        Command::Decode { decode_subcommands } => process_decode(&client, decode_subcommands)?,
-        Command::Derive { derive_subcommands } => process_derive(derive_subcommands),
-        Command::ExtendProgram {
-            keypair_path,
-            program_address,
-            additional_bytes,
-        } => process_extend_program(client, keypair_path, program_address, additional_bytes)?,
-        Command::Find { find_subcommands } => process_find(&client, find_subcommands)?,
-        Command::Mint { mint_subcommands } => process_mint(client, mint_subcommands)?,
-        Command::ParseErrors {
-            parse_errors_file_subcommands,
-        } => process_parse_errors_file(parse_errors_file_subcommands)?,
-        Command::Set { set_subcommands } => process_set(client, set_subcommands).await?,
-        Command::Sign { sign_subcommands } => process_sign(&client, sign_subcommands)?,
-        Command::Snapshot {
-            snapshot_subcommands,
-        } => process_snapshot(client, rpc, snapshot_subcommands).await?,
-        Command::Transfer {
-            transfer_subcommands,
-        } => process_transfer(client, transfer_subcommands)?,
-        Command::Update { update_subcommands } => {
-            process_update(client, update_subcommands).await?
-        }
-        Command::Uses { uses_subcommands } => process_uses(&client, uses_subcommands)?,
-        Command::Verify { verify_subcommands } => {
-            process_verify(client, verify_subcommands).await?
+    let result = async move {
+        match options.cmd {
+            Command::Collections {
+                collections_subcommands,
+            } => process_collections(client, async_client, collections_subcommands).await?,
+            Command::Airdrop {
+                airdrop_subcommands,
+            } => process_airdrop(client, airdrop_subcommands).await?,
+            Command::Announce {
+                keypair,
+                message,
+                collection,
+                chunk_size,
+                priority,
+            } => announce(AnnounceArgs {
+                client,
+                keypair,
+                message,
+                collection,
+                chunk_size,
+                priority,
+            })?,
+            Command::Announcements {
+                authority,
+                collection,
+                limit,
+                output,
+            } => announcements(AnnouncementsArgs {
+                client,
+                authority,
+                collection,
+                limit,
+                output,
+            })?,
+            Command::Audit { audit_subcommands } => process_audit(client, audit_subcommands)?,
+            Command::Bench { bench_subcommands } => process_bench(bench_subcommands)?,
+            Command::Burn { burn_subcommands } => {
+                process_burn_asset(client, burn_subcommands).await?
+            }
+            Command::BurnNft {
+                burn_nft_subcommands,
+            } => process_burn_nft(client, burn_nft_subcommands).await?,
+            Command::BurnPrint {
+                burn_print_subcommands,
+            } => process_burn_print(client, burn_print_subcommands).await?,
+            Command::Check { check_subcommands } => {
+                process_check(client, check_subcommands).await?
+            }
+            Command::Cm { cm_subcommands } => process_cm(client, cm_subcommands)?,
+            Command::Consolidate {
+                keypair,
+                keypair_dir,
+                destination,
+                mint,
+                sol_reserve,
+                priority,
+            } => consolidate(ConsolidateArgs {
+                client,
+                keypair,
+                keypair_dir,
+                destination,
+                mint,
+                sol_reserve,
+                priority,
+            })?,
+            Command::Convert {
+                convert_subcommands,
+            } => process_convert(convert_subcommands)?,
+            Command::Core { core_subcommands } => process_core(client, core_subcommands)?,
+            Command::Create { create_subcommands } => process_create(client, create_subcommands)?,
+            Command::Decode { decode_subcommands } => process_decode(&client, decode_subcommands)?,
+            Command::Delegate {
+                delegate_subcommands,
+            } => process_delegate(client, delegate_subcommands)?,
+            Command::Derive { derive_subcommands } => process_derive(derive_subcommands),
+            Command::Download {
+                download_subcommands,
+            } => process_download(client, download_subcommands).await?,
+            Command::Export { export_subcommands } => process_export(client, export_subcommands)?,
+            Command::ExtendProgram {
+                keypair_path,
+                program_address,
+                additional_bytes,
+            } => process_extend_program(client, keypair_path, program_address, additional_bytes)?,
+            Command::Fees { fees_subcommands } => process_fees(client, fees_subcommands)?,
+            Command::Find { find_subcommands } => process_find(&client, find_subcommands)?,
+            Command::FreezeAll {
+                keypair,
+                mint_list,
+                priority,
+            } => freeze_all(FreezeAllArgs {
+                client,
+                keypair,
+                mint_list,
+                priority,
+                freeze: true,
+            })?,
+            Command::ThawAll {
+                keypair,
+                mint_list,
+                priority,
+            } => freeze_all(FreezeAllArgs {
+                client,
+                keypair,
+                mint_list,
+                priority,
+                freeze: false,
+            })?,
+            Command::Incident {
+                incident_subcommands,
+            } => process_incident(client, incident_subcommands)?,
+            Command::Inscribe {
+                inscribe_subcommands,
+            } => process_inscribe(client, inscribe_subcommands)?,
+            Command::LockAll {
+                keypair,
+                mint_list,
+                priority,
+            } => lock_all(FreezeAllArgs {
+                client,
+                keypair,
+                mint_list,
+                priority,
+                freeze: true,
+            })?,
+            Command::UnlockAll {
+                keypair,
+                mint_list,
+                priority,
+            } => lock_all(FreezeAllArgs {
+                client,
+                keypair,
+                mint_list,
+                priority,
+                freeze: false,
+            })?,
+            Command::Lut { lut_subcommands } => process_lut(client, lut_subcommands)?,
+            Command::Migrate {
+                migrate_subcommands,
+            } => process_migrate(client, migrate_subcommands).await?,
+            Command::Mint { mint_subcommands } => process_mint(client, mint_subcommands)?,
+            Command::Plan { plan_subcommands } => process_plan(client, plan_subcommands)?,
+            Command::ParseErrors {
+                parse_errors_file_subcommands,
+            } => process_parse_errors_file(&client, parse_errors_file_subcommands)?,
+            Command::Proxy {
+                listen,
+                rate_limit,
+                account_ttl,
+            } => run_proxy(ProxyArgs {
+                rpc,
+                listen,
+                rate_limit,
+                account_ttl,
+                timeout,
+            })?,
+            Command::Rules { rules_subcommands } => process_rules(client, rules_subcommands)?,
+            Command::Selftest { keypair, output } => selftest(SelftestArgs {
+                client,
+                keypair,
+                output,
+            })?,
+            Command::Serve {
+                port,
+                host,
+                token,
+                signing_token,
+                keypair,
+            } => serve(ServeArgs {
+                client,
+                listen: format!("{host}:{port}"),
+                token,
+                signing_token,
+                keypair,
+            })?,
+            Command::Set { set_subcommands } => process_set(client, set_subcommands).await?,
+            Command::SubmitSigned { dir } => submit_signed(&client, dir)?,
+            Command::Sign { sign_subcommands } => process_sign(&client, sign_subcommands)?,
+            Command::Snapshot {
+                snapshot_subcommands,
+            } => process_snapshot(client, rpc, snapshot_subcommands).await?,
+            Command::Transfer {
+                transfer_subcommands,
+            } => process_transfer(client, transfer_subcommands)?,
+            Command::Update { update_subcommands } => {
+                process_update(client, update_subcommands).await?
+            }
+            Command::Uses { uses_subcommands } => process_uses(&client, uses_subcommands)?,
+            Command::Verify { verify_subcommands } => {
+                process_verify(client, verify_subcommands).await?
+            }
+            Command::Unverify {
+                unverify_subcommands,
+            } => process_unverify(client, unverify_subcommands).await?,
+            Command::External(args) => run_plugin(
+                args,
+                &PluginContext {
+                    rpc,
+                    commitment: commitment.commitment.to_string(),
+                    timeout: options.timeout,
+                    keypair: config_keypair,
+                    ws_url: profile.ws_url,
+                    run_dir: run_dir(),
+                    options: plugin_options,
+                },
+            )?,
         }
-        Command::Unverify {
-            unverify_subcommands,
-        } => process_unverify(client, unverify_subcommands).await?,
+        Ok::<(), anyhow::Error>(())
     }
+    .await;
 
-    Ok(())
+    output::finish(&command, &result);
+    output::exit(result)
+}
+
+/// Commands whose transactions go through the export path. Any other command would load the
+/// keypair and send live, so `--export-unsigned` is rejected for it up front.
+fn supports_export_unsigned(cmd: &Command) -> bool {
+    matches!(
+        cmd,
+        Command::Update { .. }
+            | Command::Set { .. }
+            | Command::Collections { .. }
+            | Command::Uses { .. }
+    )
+}
+
+/// Commands that propose their transactions to a Squads multisig when `--multisig` is set.
+fn supports_multisig(cmd: &Command) -> bool {
+    matches!(
+        cmd,
+        Command::Set {
+            set_subcommands: SetSubcommands::UpdateAuthority { .. }
+        } | Command::Update {
+            update_subcommands: UpdateSubcommands::Data { .. }
+        } | Command::Collections {
+            collections_subcommands: CollectionsSubcommands::VerifyCollection { .. }
+        }
+    )
+}
+
+/// Commands that send their transactions as bundles when `--jito` is set.
+fn supports_jito(cmd: &Command) -> bool {
+    matches!(
+        cmd,
+        Command::Update {
+            update_subcommands: UpdateSubcommands::DataAll { .. }
+        }
+    )
 }
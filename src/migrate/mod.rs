@@ -0,0 +1,84 @@
+mod storage;
+
+pub use storage::*;
+
+use std::path::PathBuf;
+
+use anyhow::Result;
+use metaboss_lib::data::Priority;
+use solana_client::rpc_client::RpcClient;
+use structopt::StructOpt;
+
+#[derive(Debug, StructOpt)]
+pub enum MigrateSubcommands {
+    /// Move off-chain metadata and images to a new storage backend and update the URIs
+    #[structopt(name = "storage")]
+    Storage {
+        /// Path to the update authority keypair file
+        #[structopt(short, long)]
+        keypair: Option<String>,
+
+        /// Storage backend the assets currently live on
+        #[structopt(long, default_value = "arweave")]
+        from: StorageBackend,
+
+        /// Storage backend to move the assets to
+        #[structopt(long, default_value = "ipfs")]
+        to: StorageBackend,
+
+        /// Path to the mint list file
+        #[structopt(short = "L", long)]
+        mint_list: String,
+
+        /// IPFS HTTP API endpoint used for uploads
+        #[structopt(long, default_value = "http://127.0.0.1:5001")]
+        ipfs_api: String,
+
+        /// Bearer token for the IPFS API, if required
+        #[structopt(long)]
+        ipfs_token: Option<String>,
+
+        /// IPFS gateway used for the new URIs and for verifying uploads
+        #[structopt(long, default_value = "https://ipfs.io")]
+        ipfs_gateway: String,
+
+        /// Directory to write the migration and rollback reports to
+        #[structopt(short, long, default_value = ".")]
+        output: PathBuf,
+
+        /// Priority of the transaction: higher priority costs more.
+        /// See https://metaboss.rs/priority-fees.html for more details.
+        #[structopt(short = "P", long, default_value = "none")]
+        priority: Priority,
+    },
+}
+
+pub async fn process_migrate(client: RpcClient, commands: MigrateSubcommands) -> Result<()> {
+    match commands {
+        MigrateSubcommands::Storage {
+            keypair,
+            from,
+            to,
+            mint_list,
+            ipfs_api,
+            ipfs_token,
+            ipfs_gateway,
+            output,
+            priority,
+        } => {
+            migrate_storage(MigrateStorageArgs {
+                client,
+                keypair,
+                from,
+                to,
+                mint_list,
+                ipfs_api,
+                ipfs_token,
+                ipfs_gateway,
+                output,
+                priority,
+            })
+            .await
+        }
+    }
+}
@@ -0,0 +1,405 @@
+use std::{
+    collections::HashMap,
+    fmt::{self, Display},
+    fs::File,
+    io::Write,
+    path::PathBuf,
+    str::FromStr,
+    sync::Arc,
+};
+
+use anyhow::{anyhow, Result};
+use indexmap::IndexMap;
+use log::debug;
+use metaboss_lib::{
+    data::{Priority, UpdateUriData},
+    decode::decode_metadata_from_mint,
+};
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::hash::hash;
+
+use crate::workspace::output_path;
+use crate::{
+    cache::ActionOutcome,
+    checkpoint,
+    files::{append_file, read_json},
+    parse::{parse_keypair, parse_solana_config},
+    spinner::create_progress_bar,
+    update::{update_uri, UpdateUriArgs},
+};
+
+const ARWEAVE_GATEWAY: &str = "https://arweave.net";
+const MULTIPART_BOUNDARY: &str = "----metaboss-storage-migration";
+
+/// Name the outcome of each mint is recorded under.
+const MIGRATE_STORAGE_COMMAND: &str = "migrate-storage";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StorageBackend {
+    Arweave,
+    Ipfs,
+}
+
+impl FromStr for StorageBackend {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "arweave" => Ok(StorageBackend::Arweave),
+            "ipfs" => Ok(StorageBackend::Ipfs),
+            _ => Err(format!("Invalid storage backend: {s}")),
+        }
+    }
+}
+
+impl Display for StorageBackend {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            StorageBackend::Arweave => write!(f, "arweave"),
+            StorageBackend::Ipfs => write!(f, "ipfs"),
+        }
+    }
+}
+
+impl StorageBackend {
+    /// Whether a URI points at this backend.
+    pub fn hosts(&self, uri: &str) -> bool {
+        match self {
+            StorageBackend::Arweave => uri.starts_with("ar://") || uri.contains("arweave.net"),
+            StorageBackend::Ipfs => uri.starts_with("ipfs://") || uri.contains("/ipfs/"),
+        }
+    }
+}
+
+pub struct MigrateStorageArgs {
+    pub client: RpcClient,
+    pub keypair: Option<String>,
+    pub from: StorageBackend,
+    pub to: StorageBackend,
+    pub mint_list: String,
+    pub ipfs_api: String,
+    pub ipfs_token: Option<String>,
+    pub ipfs_gateway: String,
+    pub output: PathBuf,
+    pub priority: Priority,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MigratedFile {
+    pub old_uri: String,
+    pub new_uri: String,
+    pub sha256: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StorageMigrationRecord {
+    pub mint: String,
+    pub old_uri: String,
+    pub new_uri: String,
+    pub files: Vec<MigratedFile>,
+}
+
+struct IpfsUploader {
+    http: Client,
+    api: String,
+    token: Option<String>,
+    gateway: String,
+}
+
+#[derive(Deserialize)]
+struct IpfsAddResponse {
+    #[serde(rename = "Hash")]
+    hash: String,
+}
+
+impl IpfsUploader {
+    async fn add(&self, bytes: &[u8]) -> Result<String> {
+        // Build the multipart body by hand so we don't need reqwest's multipart feature.
+        let mut body = Vec::with_capacity(bytes.len() + 256);
+        body.extend_from_slice(
+            format!(
+                "--{MULTIPART_BOUNDARY}\r\nContent-Disposition: form-data; name=\"file\"; filename=\"file\"\r\nContent-Type: application/octet-stream\r\n\r\n"
+            )
+            .as_bytes(),
+        );
+        body.extend_from_slice(bytes);
+        body.extend_from_slice(format!("\r\n--{MULTIPART_BOUNDARY}--\r\n").as_bytes());
+
+        let url = format!(
+            "{}/api/v0/add?cid-version=1&pin=true",
+            self.api.trim_end_matches('/')
+        );
+        let mut request = self
+            .http
+            .post(url)
+            .header(
+                "Content-Type",
+                format!("multipart/form-data; boundary={MULTIPART_BOUNDARY}"),
+            )
+            .body(body);
+
+        if let Some(token) = &self.token {
+            request = request.bearer_auth(token);
+        }
+
+        let response = request.send().await?;
+        if !response.status().is_success() {
+            return Err(anyhow!("IPFS upload failed: {}", response.status()));
+        }
+        let added: IpfsAddResponse = response.json().await?;
+
+        Ok(added.hash)
+    }
+
+    fn gateway_uri(&self, cid: &str) -> String {
+        format!("{}/ipfs/{cid}", self.gateway.trim_end_matches('/'))
+    }
+}
+
+fn to_http_uri(uri: &str) -> String {
+    match uri.strip_prefix("ar://") {
+        Some(id) => format!("{ARWEAVE_GATEWAY}/{id}"),
+        None => uri.to_string(),
+    }
+}
+
+fn sha256_hex(bytes: &[u8]) -> String {
+    hash(bytes)
+        .to_bytes()
+        .iter()
+        .map(|b| format!("{b:02x}"))
+        .collect()
+}
+
+async fn download(http: &Client, uri: &str) -> Result<Vec<u8>> {
+    let response = http.get(to_http_uri(uri)).send().await?;
+    if !response.status().is_success() {
+        return Err(anyhow!("Failed to download {uri}: {}", response.status()));
+    }
+    Ok(response.bytes().await?.to_vec())
+}
+
+/// Upload bytes to IPFS and read them back through the gateway, failing unless
+/// the content hashes match exactly.
+async fn upload_verified(uploader: &IpfsUploader, bytes: &[u8]) -> Result<(String, String)> {
+    let cid = uploader.add(bytes).await?;
+    let new_uri = uploader.gateway_uri(&cid);
+
+    let expected = sha256_hex(bytes);
+    let actual = sha256_hex(&download(&uploader.http, &new_uri).await?);
+
+    if expected != actual {
+        return Err(anyhow!(
+            "Content hash mismatch for {new_uri}: expected {expected}, got {actual}"
+        ));
+    }
+
+    Ok((new_uri, expected))
+}
+
+async fn migrate_file(
+    uploader: &IpfsUploader,
+    from: StorageBackend,
+    uri: &str,
+    migrated: &mut HashMap<String, MigratedFile>,
+) -> Result<Option<String>> {
+    if !from.hosts(uri) {
+        return Ok(None);
+    }
+    if let Some(file) = migrated.get(uri) {
+        return Ok(Some(file.new_uri.clone()));
+    }
+
+    let bytes = download(&uploader.http, uri).await?;
+    let (new_uri, sha256) = upload_verified(uploader, &bytes).await?;
+
+    migrated.insert(
+        uri.to_string(),
+        MigratedFile {
+            old_uri: uri.to_string(),
+            new_uri: new_uri.clone(),
+            sha256,
+        },
+    );
+
+    Ok(Some(new_uri))
+}
+
+/// Append a record as one line and flush it, so it survives the run being interrupted.
+fn append_record<T: Serialize>(writer: &mut dyn Write, record: &T) -> Result<()> {
+    writeln!(writer, "{}", serde_json::to_string(record)?)?;
+    writer.flush()?;
+    Ok(())
+}
+
+async fn migrate_json(
+    uploader: &IpfsUploader,
+    from: StorageBackend,
+    old_uri: &str,
+) -> Result<(String, Vec<MigratedFile>)> {
+    let mut json: Value = serde_json::from_slice(&download(&uploader.http, old_uri).await?)?;
+    let mut migrated = HashMap::new();
+
+    for key in ["image", "animation_url"] {
+        if let Some(uri) = json.get(key).and_then(Value::as_str).map(str::to_string) {
+            if let Some(new_uri) = migrate_file(uploader, from, &uri, &mut migrated).await? {
+                json[key] = Value::String(new_uri);
+            }
+        }
+    }
+
+    if let Some(files) = json
+        .get_mut("properties")
+        .and_then(|p| p.get_mut("files"))
+        .and_then(Value::as_array_mut)
+    {
+        for file in files {
+            if let Some(uri) = file.get("uri").and_then(Value::as_str).map(str::to_string) {
+                if let Some(new_uri) = migrate_file(uploader, from, &uri, &mut migrated).await? {
+                    file["uri"] = Value::String(new_uri);
+                }
+            }
+        }
+    }
+
+    let bytes = serde_json::to_vec(&json)?;
+    let (new_uri, sha256) = upload_verified(uploader, &bytes).await?;
+
+    let mut files: Vec<MigratedFile> = migrated.into_values().collect();
+    files.push(MigratedFile {
+        old_uri: old_uri.to_string(),
+        new_uri: new_uri.clone(),
+        sha256,
+    });
+
+    Ok((new_uri, files))
+}
+
+pub async fn migrate_storage(args: MigrateStorageArgs) -> Result<()> {
+    if args.to != StorageBackend::Ipfs {
+        return Err(anyhow!(
+            "Only ipfs is currently supported as a target backend"
+        ));
+    }
+    if args.from == args.to {
+        return Err(anyhow!("Source and target backends must be different"));
+    }
+
+    let solana_opts = parse_solana_config();
+    let keypair = Arc::new(parse_keypair(args.keypair, solana_opts));
+    let client = Arc::new(args.client);
+
+    let mint_list: Vec<String> = read_json(&args.mint_list)?;
+
+    let uploader = IpfsUploader {
+        http: Client::new(),
+        api: args.ipfs_api,
+        token: args.ipfs_token,
+        gateway: args.ipfs_gateway,
+    };
+
+    let output = output_path(&args.output);
+    std::fs::create_dir_all(&output)?;
+
+    // Records are appended as each update confirms, so a crash or Ctrl-C can't lose the
+    // URIs of assets that were already changed on-chain. The rollback file is in the
+    // format `update uri-all` expects.
+    let report_path = output.join("mb-storage-migration.ndjson");
+    let rollback_path = output.join("mb-storage-rollback.ndjson");
+    let mut report = append_file(&report_path)?;
+    let mut rollback = append_file(&rollback_path)?;
+
+    let mut migrated = 0;
+    let mut errors: IndexMap<String, String> = IndexMap::new();
+
+    let pb = create_progress_bar("Migrating storage...", mint_list.len() as u64);
+
+    for mint in mint_list {
+        pb.inc(1);
+
+        let old_uri = match decode_metadata_from_mint(&client, mint.clone()) {
+            Ok(md) => md.uri.trim_matches(char::from(0)).to_string(),
+            Err(e) => {
+                checkpoint::record_failed(MIGRATE_STORAGE_COMMAND, &mint, &e);
+                errors.insert(mint, e.to_string());
+                continue;
+            }
+        };
+
+        if !args.from.hosts(&old_uri) {
+            debug!("Skipping {mint}: {old_uri} is not on {}", args.from);
+            checkpoint::record_done(MIGRATE_STORAGE_COMMAND, &mint, None);
+            continue;
+        }
+
+        let (new_uri, files) = match migrate_json(&uploader, args.from, &old_uri).await {
+            Ok(res) => res,
+            Err(e) => {
+                checkpoint::record_failed(MIGRATE_STORAGE_COMMAND, &mint, &e);
+                errors.insert(mint, e.to_string());
+                continue;
+            }
+        };
+
+        // Only touch the chain once every file has been verified on the new backend.
+        let outcome = match update_uri(UpdateUriArgs {
+            client: client.clone(),
+            keypair: keypair.clone(),
+            mint_account: mint.clone(),
+            new_uri: new_uri.clone(),
+            priority: args.priority.clone(),
+        })
+        .await
+        {
+            Ok(outcome) => outcome,
+            Err(e) => {
+                checkpoint::record_failed(MIGRATE_STORAGE_COMMAND, &mint, &e);
+                errors.insert(mint, e.to_string());
+                continue;
+            }
+        };
+        let sig = match outcome {
+            ActionOutcome::Sent(sig) => Some(sig.to_string()),
+            ActionOutcome::AlreadyDone => None,
+        };
+        checkpoint::record_done(MIGRATE_STORAGE_COMMAND, &mint, sig);
+
+        append_record(
+            &mut rollback,
+            &UpdateUriData {
+                mint_account: mint.clone(),
+                new_uri: old_uri.clone(),
+            },
+        )?;
+        append_record(
+            &mut report,
+            &StorageMigrationRecord {
+                mint,
+                old_uri,
+                new_uri,
+                files,
+            },
+        )?;
+        migrated += 1;
+    }
+    pb.finish_and_clear();
+
+    println!("Migrated {migrated} assets.");
+    println!("Mapping report written to {}", report_path.display());
+    println!("Rollback URIs written to {}", rollback_path.display());
+
+    if !errors.is_empty() {
+        let errors_path = output.join("mb-storage-migration-errors.json");
+        serde_json::to_writer_pretty(File::create(&errors_path)?, &errors)?;
+        println!(
+            "{} assets failed to migrate. Errors written to {}",
+            errors.len(),
+            errors_path.display()
+        );
+    }
+
+    Ok(())
+}
@@ -5,30 +5,36 @@ use log::{error, info};
 use metaboss_lib::{
     data::Priority,
     decode::*,
-    derive::derive_edition_marker_pda,
+    derive::{derive_edition_marker_pda, derive_token_record_pda},
     mint::{mint_asset, AssetData, MintAssetArgs},
     transaction::get_compute_units,
+    unverify::{unverify_collection_ix, UnverifyCollectionArgs},
+    update::{update_asset_ix, UpdateAssetArgs, V1UpdateArgs},
+    verify::{verify_collection_ix, VerifyCollectionArgs},
 };
 use mpl_token_metadata::{
-    accounts::EditionMarker,
+    accounts::{EditionMarker, Metadata},
     instructions::{
-        CreateMasterEditionV3Builder, CreateMetadataAccountV3Builder,
-        MintNewEditionFromMasterEditionViaTokenBuilder, UpdateMetadataAccountV2Builder,
+        CreateMasterEditionV3Builder, CreateMetadataAccountV3Builder, CreateV1Builder,
+        MintNewEditionFromMasterEditionViaTokenBuilder, MintV1Builder,
+        UpdateMetadataAccountV2Builder,
     },
     types::{
-        CollectionDetails, Data, DataV2, MintNewEditionFromMasterEditionViaTokenArgs, PrintSupply,
+        Collection, CollectionDetails, CollectionToggle, Data, DataV2,
+        MintNewEditionFromMasterEditionViaTokenArgs, PrintSupply, TokenStandard,
     },
 };
 use rayon::prelude::*;
 use reqwest;
 use retry::{delay::Exponential, retry};
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use solana_client::rpc_client::RpcClient;
 use solana_program::program_pack::Pack;
 use solana_sdk::{
     commitment_config::CommitmentConfig,
     compute_budget::ComputeBudgetInstruction,
+    instruction::{AccountMeta, Instruction},
     pubkey::Pubkey,
     signature::Signature,
     signer::{keypair::Keypair, Signer},
@@ -55,12 +61,25 @@ use std::{
     str::FromStr,
 };
 
+use crate::checkpoint;
 use crate::constants::*;
+use crate::decode::decode_metadata_batch;
 use crate::derive::derive_edition_pda;
 use crate::derive::derive_metadata_pda;
+use crate::files::{read_json, write_json};
+use crate::order;
+use crate::output::record_transaction;
+use crate::pipeline::Pipeline;
 use crate::sign::sign_one;
-use crate::{find::find_missing_editions, parse::*};
-use crate::{limiter::create_default_rate_limiter, spinner::create_spinner};
+use crate::workspace::output_path;
+use crate::{
+    find::{find_missing_editions, MissingEditionsReport},
+    parse::*,
+};
+use crate::{
+    limiter::create_default_rate_limiter,
+    spinner::{create_progress_bar, create_spinner},
+};
 
 const MINT_LAYOUT: u64 = 82;
 
@@ -119,6 +138,9 @@ pub fn mint_list(
     Ok(())
 }
 
+/// Name that `mint list` records its progress under in the checkpoint file.
+const MINT_LIST_COMMAND: &str = "mint-list";
+
 #[allow(clippy::too_many_arguments)]
 pub fn mint_from_files(
     client: &RpcClient,
@@ -142,7 +164,11 @@ pub fn mint_from_files(
 
     let (paths, errors): (Vec<_>, Vec<_>) = glob(pattern)?.partition(Result::is_ok);
 
-    let paths: Vec<_> = paths.into_iter().map(Result::unwrap).collect();
+    let paths: Vec<String> = paths
+        .into_iter()
+        .map(|path| path.unwrap().display().to_string())
+        .collect();
+    let paths = order::ordered(checkpoint::remaining(MINT_LIST_COMMAND, paths));
     let errors: Vec<_> = errors.into_iter().map(Result::unwrap_err).collect();
 
     paths.par_iter().for_each(|path| {
@@ -165,8 +191,11 @@ pub fn mint_from_files(
             sized,
             &priority,
         ) {
-            Ok(_) => (),
-            Err(e) => error!("Failed to mint {:?}: {}", &path, e),
+            Ok(mint) => checkpoint::record_minted(MINT_LIST_COMMAND, path, &mint),
+            Err(e) => {
+                error!("Failed to mint {:?}: {}", &path, e);
+                checkpoint::record_failed(MINT_LIST_COMMAND, path, e);
+            }
         }
     });
 
@@ -194,8 +223,11 @@ pub fn mint_from_uris(
     track: bool,
     priority: Priority,
 ) -> Result<()> {
-    let f = File::open(&external_metadata_uris_path)?;
-    let external_metadata_uris: Vec<String> = serde_json::from_reader(f)?;
+    let external_metadata_uris: Vec<String> = read_json(&external_metadata_uris_path)?;
+    let external_metadata_uris = order::ordered(checkpoint::remaining(
+        MINT_LIST_COMMAND,
+        external_metadata_uris,
+    ));
 
     if !track {
         external_metadata_uris
@@ -216,8 +248,11 @@ pub fn mint_from_uris(
                     false,
                     &priority,
                 ) {
-                    Ok(_) => (),
-                    Err(e) => println!("Failed to mint {:?}: {}", &uri, e),
+                    Ok(mint) => checkpoint::record_minted(MINT_LIST_COMMAND, uri, &mint),
+                    Err(e) => {
+                        println!("Failed to mint {:?}: {}", &uri, e);
+                        checkpoint::record_failed(MINT_LIST_COMMAND, uri, e);
+                    }
                 }
             });
     } else {
@@ -251,12 +286,16 @@ pub fn mint_from_uris(
                     false,
                     &priority,
                 ) {
-                    Ok(m) => MintResult {
-                        uri: uri.clone(),
-                        mint_account: Some(m),
-                    },
+                    Ok(m) => {
+                        checkpoint::record_minted(MINT_LIST_COMMAND, uri, &m);
+                        MintResult {
+                            uri: uri.clone(),
+                            mint_account: Some(m),
+                        }
+                    }
                     Err(e) => {
                         error!("Failed to mint {:?}: {}", &uri, e);
+                        checkpoint::record_failed(MINT_LIST_COMMAND, uri, e);
                         MintResult {
                             uri: uri.clone(),
                             mint_account: None,
@@ -409,6 +448,7 @@ pub fn mint_fungible(
         || client.send_and_confirm_transaction(&tx),
     );
     let sig = res?;
+    record_transaction(&sig, &final_instructions);
 
     println!(
         "Mint: {:?} minted {:?} tokens successfully!",
@@ -451,8 +491,7 @@ pub fn mint_one<P: AsRef<Path>>(
     };
 
     let nft_data: Data = if let Some(nft_data_file) = nft_data_file {
-        let f = File::open(nft_data_file)?;
-        serde_json::from_reader(f)?
+        read_json(nft_data_file)?
     } else if let Some(external_metadata_uri) = external_metadata_uri {
         let body: Value = reqwest::blocking::get(external_metadata_uri)?.json()?;
         let creators_json = body
@@ -500,6 +539,20 @@ pub fn mint_one<P: AsRef<Path>>(
     Ok(mint_account.to_string())
 }
 
+/// Name that `mint editions --recipients` records its progress under in the checkpoint file.
+const MINT_EDITIONS_COMMAND: &str = "mint-editions";
+
+/// The edition printed for one entry of the recipient list.
+#[derive(Debug, Serialize)]
+pub struct EditionDelivery {
+    pub recipient: String,
+    pub edition: Option<u64>,
+    pub mint: Option<String>,
+    pub signature: Option<String>,
+    pub error: Option<String>,
+}
+
+#[allow(clippy::too_many_arguments)]
 pub fn mint_editions(
     client: &RpcClient,
     keypair_path: Option<String>,
@@ -507,33 +560,158 @@ pub fn mint_editions(
     receiver: &Option<String>,
     next_editions: Option<u64>,
     specific_editions: Option<Vec<u64>>,
+    recipients: Option<PathBuf>,
+    verify: bool,
     priority: Priority,
 ) -> Result<()> {
+    if let Some(recipients) = recipients {
+        if receiver.is_some() || next_editions.is_some() || specific_editions.is_some() {
+            return Err(anyhow!(
+                "--recipients can't be combined with --receiver, --next-editions or --specific-editions"
+            ));
+        }
+        let minted =
+            mint_editions_to_recipients(client, &keypair_path, &account, &recipients, &priority)?;
+        if verify && !minted.is_empty() {
+            let divergences = check_editions(client, &account, &minted, None)?;
+            report_edition_divergences(&account, &divergences)?;
+        }
+        return Ok(());
+    }
+
     let spinner = create_spinner("Minting...");
+    let mut minted = vec![];
     if let Some(next_editions) = next_editions {
         for _ in 0..next_editions {
-            mint_next_edition(client, &keypair_path, &account, receiver, &priority)?;
+            let (_, mint) =
+                mint_next_edition(client, &keypair_path, &account, receiver, &priority)?;
+            minted.push(mint.to_string());
         }
-        return Ok(());
-    }
-    if let Some(specific_editions) = specific_editions {
+    } else if let Some(specific_editions) = specific_editions {
         for num in specific_editions {
-            mint_edition(client, &keypair_path, &account, num, receiver, &priority)?;
+            let (_, mint) =
+                mint_edition(client, &keypair_path, &account, num, receiver, &priority)?;
+            minted.push(mint.to_string());
         }
-        return Ok(());
     }
     spinner.finish();
 
+    if verify && !minted.is_empty() {
+        let divergences = check_editions(client, &account, &minted, None)?;
+        report_edition_divergences(&account, &divergences)?;
+    }
+
     Ok(())
 }
 
-fn mint_next_edition(
+/// Print the next edition for each address in a JSON list, one edition per entry.
+///
+/// Each recipient's edition number, mint and signature are written to
+/// `mb-edition-recipients-<master>.json`, and with `--resume` entries already delivered are
+/// skipped on the next run. Returns the mints of the editions printed.
+fn mint_editions_to_recipients(
+    client: &RpcClient,
+    keypair_path: &Option<String>,
+    account: &str,
+    recipients_file: &Path,
+    priority: &Priority,
+) -> Result<Vec<String>> {
+    let recipients: Vec<String> = read_json(recipients_file)?;
+    if recipients.is_empty() {
+        return Err(anyhow!("No recipients in {}", recipients_file.display()));
+    }
+    for recipient in &recipients {
+        Pubkey::from_str(recipient).map_err(|_| anyhow!("Invalid recipient: {recipient}"))?;
+    }
+
+    // The same address can appear more than once to receive several editions, so entries
+    // are tracked by their position in the list.
+    let keys: Vec<String> = recipients
+        .iter()
+        .enumerate()
+        .map(|(i, recipient)| format!("{i}:{recipient}"))
+        .collect();
+    let remaining = checkpoint::remaining(MINT_EDITIONS_COMMAND, keys.clone());
+    let skipped = keys.len() - remaining.len();
+
+    let mut deliveries = vec![];
+    let mut minted = vec![];
+    let pb = create_progress_bar("Printing editions...", remaining.len() as u64);
+    for key in &remaining {
+        let (_, recipient) = key.split_once(':').expect("key is index:recipient");
+        let mut delivery = EditionDelivery {
+            recipient: recipient.to_string(),
+            edition: None,
+            mint: None,
+            signature: None,
+            error: None,
+        };
+        let result = next_edition_number(client, account).and_then(|edition| {
+            delivery.edition = Some(edition);
+            mint_edition(
+                client,
+                keypair_path,
+                account,
+                edition,
+                &Some(recipient.to_string()),
+                priority,
+            )
+        });
+        match result {
+            Ok((signature, mint)) => {
+                checkpoint::record_minted(MINT_EDITIONS_COMMAND, key, &mint.to_string());
+                delivery.mint = Some(mint.to_string());
+                delivery.signature = Some(signature.to_string());
+                minted.push(mint.to_string());
+            }
+            Err(e) => {
+                checkpoint::record_failed(MINT_EDITIONS_COMMAND, key, &e);
+                pb.println(format!("Failed to print an edition for {recipient}: {e}"));
+                delivery.error = Some(e.to_string());
+            }
+        }
+        deliveries.push(delivery);
+        pb.inc(1);
+    }
+    pb.finish_and_clear();
+
+    let results_file = output_path(format!("mb-edition-recipients-{account}.json"));
+    write_json(&results_file, &deliveries)?;
+
+    if skipped > 0 {
+        println!("Skipped {skipped} recipients already delivered");
+    }
+    println!(
+        "Printed {} editions, {} failed. Results written to {}",
+        minted.len(),
+        deliveries.len() - minted.len(),
+        results_file.display()
+    );
+
+    Ok(minted)
+}
+
+pub fn mint_next_edition(
     client: &RpcClient,
     keypair_path: &Option<String>,
     account: &str,
     receiver: &Option<String>,
     priority: &Priority,
-) -> Result<()> {
+) -> Result<(Signature, Pubkey)> {
+    let edition_num = next_edition_number(client, account)?;
+
+    mint_edition(
+        client,
+        keypair_path,
+        account,
+        edition_num,
+        receiver,
+        priority,
+    )
+}
+
+/// The lowest edition number of the master that hasn't been printed yet.
+fn next_edition_number(client: &RpcClient, account: &str) -> Result<u64> {
     // Send tx with retries.
     let master_edition = retry(
         Exponential::from_millis_with_factor(250, 2.0).take(3),
@@ -558,7 +736,9 @@ fn mint_next_edition(
             break;
         }
 
-        let marker = EditionMarker::deserialize(&mut account.unwrap().data.as_slice())?;
+        let marker = <EditionMarker as BorshDeserialize>::deserialize(
+            &mut account.unwrap().data.as_slice(),
+        )?;
 
         if let Some((index, bit)) = find_first_zero_bit(marker.ledger, edition_num == 0) {
             edition_num += index * 8 + bit as usize;
@@ -585,16 +765,7 @@ fn mint_next_edition(
         edition_num += 1;
     }
 
-    mint_edition(
-        client,
-        keypair_path,
-        account,
-        edition_num,
-        receiver,
-        priority,
-    )?;
-
-    Ok(())
+    Ok(edition_num)
 }
 
 fn find_first_zero_bit(arr: [u8; 31], first_marker: bool) -> Option<(usize, u8)> {
@@ -740,12 +911,88 @@ fn mint_edition(
         || client.send_and_confirm_transaction(&tx),
     );
     let sig = res?;
+    record_transaction(&sig, &final_instructions);
     println!("Edition with mint: {:?}", &new_mint);
     println!("Created in tx: {:?}", &sig);
 
     Ok((sig, new_mint))
 }
 
+/// Name the outcome of each missing edition is recorded under, as `<master>:<edition>`.
+const MINT_MISSING_EDITIONS_COMMAND: &str = "mint-missing-editions";
+
+/// Print the missing editions of a master, or of the masters in a `find missing-editions`
+/// output file, by their edition numbers.
+pub fn mint_missing_editions_process(
+    client: &RpcClient,
+    keypair_path: &Option<String>,
+    account: Option<String>,
+    missing_file: Option<PathBuf>,
+    priority: Priority,
+) -> Result<()> {
+    let Some(missing_file) = missing_file else {
+        let account = account.ok_or_else(|| {
+            anyhow!("Please specify a master mint, a missing editions file, or both.")
+        })?;
+        return mint_missing_editions(client, keypair_path, &account, priority);
+    };
+
+    let reports: Vec<MissingEditionsReport> = read_json(&missing_file)?;
+    let reports: Vec<MissingEditionsReport> = match &account {
+        Some(master) => {
+            let reports: Vec<_> = reports
+                .into_iter()
+                .filter(|r| &r.master == master)
+                .collect();
+            if reports.is_empty() {
+                return Err(anyhow!("{master} isn't in {}", missing_file.display()));
+            }
+            reports
+        }
+        None => reports,
+    };
+
+    let (mut printed, mut failed) = (0, 0);
+    for report in reports {
+        if let Some(e) = report.error {
+            println!("Skipping {}, it couldn't be checked: {e}", report.master);
+            checkpoint::record_failed(MINT_MISSING_EDITIONS_COMMAND, &report.master, e);
+            continue;
+        }
+        for edition in report.missing {
+            let item = format!("{}:{edition}", report.master);
+            match mint_edition(
+                client,
+                keypair_path,
+                &report.master,
+                edition,
+                &None,
+                &priority,
+            ) {
+                Ok((_, mint)) => {
+                    checkpoint::record_minted(
+                        MINT_MISSING_EDITIONS_COMMAND,
+                        &item,
+                        &mint.to_string(),
+                    );
+                    printed += 1;
+                }
+                Err(e) => {
+                    println!(
+                        "Failed to print edition {edition} of {}: {e}",
+                        report.master
+                    );
+                    checkpoint::record_failed(MINT_MISSING_EDITIONS_COMMAND, &item, &e);
+                    failed += 1;
+                }
+            }
+        }
+    }
+    println!("Printed {printed} missing editions, {failed} failed");
+
+    Ok(())
+}
+
 pub fn mint_missing_editions(
     client: &RpcClient,
     keypair_path: &Option<String>,
@@ -770,6 +1017,279 @@ pub fn mint_missing_editions(
     Ok(())
 }
 
+/// A print whose creators, royalties or collection no longer match its master's.
+#[derive(Debug, Serialize)]
+pub struct EditionDivergence {
+    pub mint: String,
+    pub fields: Vec<String>,
+    pub repaired: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// The fields a print inherits from its master that differ from the master's values.
+pub fn edition_divergences(master: &Metadata, print: &Metadata) -> Vec<String> {
+    let mut fields = vec![];
+    if print.seller_fee_basis_points != master.seller_fee_basis_points {
+        fields.push(format!(
+            "seller_fee_basis_points: {} instead of {}",
+            print.seller_fee_basis_points, master.seller_fee_basis_points
+        ));
+    }
+
+    let creators = |md: &Metadata| {
+        md.creators
+            .iter()
+            .flatten()
+            .map(|c| (c.address, c.share, c.verified))
+            .collect::<Vec<_>>()
+    };
+    let (master_creators, print_creators) = (creators(master), creators(print));
+    if print_creators.len() != master_creators.len()
+        || print_creators
+            .iter()
+            .zip(&master_creators)
+            .any(|(p, m)| p.0 != m.0 || p.1 != m.1)
+    {
+        fields.push("creators: addresses or shares differ".to_string());
+    } else {
+        for ((address, _, verified), (_, _, expected)) in
+            print_creators.iter().zip(&master_creators)
+        {
+            if verified != expected {
+                fields.push(format!("creators: {address} verified is {verified}"));
+            }
+        }
+    }
+
+    let collection = |md: &Metadata| md.collection.as_ref().map(|c| (c.key, c.verified));
+    match (collection(master), collection(print)) {
+        (m, p) if m == p => {}
+        (Some((key, _)), Some((print_key, _))) if key != print_key => {
+            fields.push(format!("collection: {print_key} instead of {key}"))
+        }
+        (Some((key, verified)), Some(_)) => {
+            fields.push(format!("collection: {key} verified is {}", !verified))
+        }
+        (Some((key, _)), None) => fields.push(format!("collection: none instead of {key}")),
+        (None, Some((key, _))) => fields.push(format!("collection: {key} instead of none")),
+        (None, None) => {}
+    }
+
+    fields
+}
+
+/// Instructions that bring a print's creators, royalties and collection back in line with
+/// its master. Creators can only be newly verified by the authority itself, so other
+/// creators keep the print's current verification.
+fn repair_edition_ixs(
+    client: &RpcClient,
+    authority: &Keypair,
+    master: &Metadata,
+    print: &Metadata,
+) -> Result<Vec<Instruction>> {
+    let mint = print.mint.to_string();
+    let creators = master.creators.clone().map(|creators| {
+        creators
+            .into_iter()
+            .map(|mut creator| {
+                let already_verified = print
+                    .creators
+                    .iter()
+                    .flatten()
+                    .any(|c| c.address == creator.address && c.verified);
+                creator.verified =
+                    creator.verified && (already_verified || creator.address == authority.pubkey());
+                creator
+            })
+            .collect()
+    });
+
+    let mut update_args = V1UpdateArgs {
+        data: Some(Data {
+            name: print.name.clone(),
+            symbol: print.symbol.clone(),
+            uri: print.uri.clone(),
+            seller_fee_basis_points: master.seller_fee_basis_points,
+            creators,
+        }),
+        ..Default::default()
+    };
+
+    let master_collection = master.collection.as_ref();
+    let print_collection = print.collection.as_ref();
+    let same_key = master_collection.map(|c| c.key) == print_collection.map(|c| c.key);
+
+    let mut instructions = vec![];
+    if let Some(current) = print_collection.filter(|c| c.verified) {
+        if !same_key || !master_collection.is_some_and(|c| c.verified) {
+            instructions.push(unverify_collection_ix(
+                client,
+                UnverifyCollectionArgs::V1 {
+                    authority,
+                    mint: mint.clone(),
+                    collection_mint: current.key,
+                    is_delegate: false,
+                },
+            )?);
+        }
+    }
+    if !same_key {
+        update_args.collection = match master_collection {
+            Some(collection) => CollectionToggle::Set(Collection {
+                key: collection.key,
+                verified: false,
+            }),
+            None => CollectionToggle::Clear,
+        };
+    }
+    instructions.push(update_asset_ix(
+        client,
+        UpdateAssetArgs::V1 {
+            payer: None,
+            authority,
+            mint: mint.clone(),
+            token: None::<String>,
+            delegate_record: None::<String>,
+            update_args,
+            priority: Priority::None,
+        },
+    )?);
+    if let Some(collection) = master_collection.filter(|c| c.verified) {
+        let verified = print_collection.is_some_and(|c| c.verified);
+        if !same_key || !verified {
+            instructions.push(verify_collection_ix(
+                client,
+                VerifyCollectionArgs::V1 {
+                    authority,
+                    mint,
+                    collection_mint: collection.key.to_string(),
+                    is_delegate: false,
+                },
+            )?);
+        }
+    }
+
+    Ok(instructions)
+}
+
+/// Compare prints with their master, repairing the ones that diverged when an authority
+/// is given.
+fn check_editions(
+    client: &RpcClient,
+    master_mint: &str,
+    print_mints: &[String],
+    repair: Option<(&Keypair, &Priority)>,
+) -> Result<Vec<EditionDivergence>> {
+    let master = decode_metadata_from_mint(client, master_mint)?;
+
+    let mut divergences = vec![];
+    let pb = create_progress_bar("Checking editions...", print_mints.len() as u64);
+    for (mint, print) in decode_metadata_batch(client, print_mints) {
+        pb.inc(1);
+        let print = match print {
+            Ok(print) => print,
+            Err(e) => {
+                divergences.push(EditionDivergence {
+                    mint,
+                    fields: vec![],
+                    repaired: false,
+                    error: Some(e.to_string()),
+                });
+                continue;
+            }
+        };
+        let fields = edition_divergences(&master, &print);
+        if fields.is_empty() {
+            continue;
+        }
+
+        let mut divergence = EditionDivergence {
+            mint,
+            fields,
+            repaired: false,
+            error: None,
+        };
+        if let Some((authority, priority)) = repair {
+            let result = repair_edition_ixs(client, authority, &master, &print).and_then(|ixs| {
+                Pipeline::with_priority(priority.clone())
+                    .with_active_lookup_table()?
+                    .with_active_nonce()?
+                    .run(client, &[authority], ixs)
+            });
+            match result {
+                Ok(_) => divergence.repaired = true,
+                Err(e) => divergence.error = Some(e.to_string()),
+            }
+        }
+        divergences.push(divergence);
+    }
+    pb.finish_and_clear();
+
+    Ok(divergences)
+}
+
+fn report_edition_divergences(master_mint: &str, divergences: &[EditionDivergence]) -> Result<()> {
+    if divergences.is_empty() {
+        println!("All editions match the creators, royalties and collection of {master_mint}");
+        return Ok(());
+    }
+
+    for divergence in divergences {
+        let status = match (&divergence.error, divergence.repaired) {
+            (Some(error), _) => format!(" (error: {error})"),
+            (None, true) => " (repaired)".to_string(),
+            (None, false) => String::new(),
+        };
+        println!("{}{status}", divergence.mint);
+        for field in &divergence.fields {
+            println!("  {field}");
+        }
+    }
+
+    let file_name = output_path(format!("mb-edition-divergences-{master_mint}.json"));
+    serde_json::to_writer_pretty(File::create(&file_name)?, divergences)?;
+    println!(
+        "{} editions diverged from their master. Written to {}",
+        divergences.len(),
+        file_name.display()
+    );
+    Ok(())
+}
+
+/// Check that prints of a master inherited its creators, royalties and collection, and
+/// optionally repair the ones that diverged. The print list is a list of mints or the
+/// edition to mint map `snapshot prints` writes.
+pub fn verify_editions(
+    client: &RpcClient,
+    keypair_path: Option<String>,
+    master_mint: &str,
+    print_list: &Path,
+    repair: bool,
+    priority: Priority,
+) -> Result<()> {
+    let print_mints: Vec<String> = match read_json(print_list)? {
+        Value::Array(mints) => serde_json::from_value(Value::Array(mints))?,
+        Value::Object(editions) => serde_json::from_value(Value::Array(
+            editions.into_iter().map(|(_, mint)| mint).collect(),
+        ))?,
+        _ => {
+            return Err(anyhow!(
+                "The print list must be a list of mints or an object"
+            ))
+        }
+    };
+
+    let authority = repair.then(|| parse_keypair(keypair_path, parse_solana_config()));
+    let divergences = check_editions(
+        client,
+        master_mint,
+        &print_mints,
+        authority.as_ref().map(|a| (a, &priority)),
+    )?;
+    report_edition_divergences(master_mint, &divergences)
+}
+
 #[allow(clippy::too_many_arguments)]
 pub fn mint(
     client: &RpcClient,
@@ -950,6 +1470,7 @@ pub fn mint(
         || client.send_and_confirm_transaction(&tx),
     );
     let sig = res?;
+    record_transaction(&sig, &final_instructions);
 
     Ok((sig, mint.pubkey()))
 }
@@ -963,6 +1484,8 @@ pub struct MintAssetParams {
     pub decimals: u8,
     pub amount: u64,
     pub max_print_edition_supply: Option<Supply>,
+    pub extra_account_metas: Option<PathBuf>,
+    pub additional_instructions: Option<PathBuf>,
     pub priority: Priority,
 }
 
@@ -976,6 +1499,8 @@ pub fn process_mint_asset(args: MintAssetParams) -> Result<()> {
         decimals,
         amount,
         max_print_edition_supply,
+        extra_account_metas,
+        additional_instructions,
         priority,
     } = args;
 
@@ -996,6 +1521,41 @@ pub fn process_mint_asset(args: MintAssetParams) -> Result<()> {
 
     let mint = mint_path.map(|path| read_keypair(&path).expect("Invalid mint keypair path"));
 
+    if extra_account_metas.is_some() || additional_instructions.is_some() {
+        let hooks = MintHooks {
+            extra_account_metas: extra_account_metas
+                .map(|path| -> Result<Vec<HookAccountMeta>> {
+                    Ok(serde_json::from_reader(File::open(path)?)?)
+                })
+                .transpose()?
+                .unwrap_or_default(),
+            additional_instructions: additional_instructions
+                .map(|path| -> Result<Vec<HookInstruction>> {
+                    Ok(serde_json::from_reader(File::open(path)?)?)
+                })
+                .transpose()?
+                .unwrap_or_default(),
+        };
+
+        let (sig, mint) = mint_asset_with_hooks(
+            &client,
+            &authority,
+            receiver,
+            mint.unwrap_or_else(Keypair::new),
+            asset_data,
+            decimals,
+            amount,
+            print_supply,
+            hooks,
+            priority,
+        )?;
+
+        println!("Minted asset: {:?}", mint);
+        println!("Transaction signature: {:?}", sig);
+
+        return Ok(());
+    }
+
     let args = MintAssetArgs::V1 {
         payer: None,
         authority: &authority,
@@ -1017,6 +1577,200 @@ pub fn process_mint_asset(args: MintAssetParams) -> Result<()> {
     Ok(())
 }
 
+/// An account meta read from a hooks file. The pubkey may be one of the placeholders
+/// `$MINT`, `$METADATA`, `$EDITION`, `$TOKEN`, `$RECEIVER` or `$AUTHORITY`, which are
+/// resolved against the asset being minted. Only the mint and authority can be signers.
+#[derive(Debug, Deserialize)]
+pub struct HookAccountMeta {
+    pub pubkey: String,
+    #[serde(default)]
+    pub is_signer: bool,
+    #[serde(default)]
+    pub is_writable: bool,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct HookInstruction {
+    pub program_id: String,
+    pub accounts: Vec<HookAccountMeta>,
+    /// Base58 encoded instruction data.
+    #[serde(default)]
+    pub data: String,
+}
+
+#[derive(Debug, Default)]
+pub struct MintHooks {
+    /// Appended to the mint instruction as remaining accounts.
+    pub extra_account_metas: Vec<HookAccountMeta>,
+    /// Appended after the mint instruction in the same transaction.
+    pub additional_instructions: Vec<HookInstruction>,
+}
+
+struct HookContext {
+    mint: Pubkey,
+    metadata: Pubkey,
+    edition: Pubkey,
+    token: Pubkey,
+    receiver: Pubkey,
+    authority: Pubkey,
+}
+
+impl HookContext {
+    fn resolve(&self, key: &str) -> Result<Pubkey> {
+        match key {
+            "$MINT" => Ok(self.mint),
+            "$METADATA" => Ok(self.metadata),
+            "$EDITION" => Ok(self.edition),
+            "$TOKEN" => Ok(self.token),
+            "$RECEIVER" => Ok(self.receiver),
+            "$AUTHORITY" => Ok(self.authority),
+            _ => Pubkey::from_str(key).map_err(|_| anyhow!("Invalid pubkey in hooks file: {key}")),
+        }
+    }
+
+    fn account_meta(&self, meta: &HookAccountMeta) -> Result<AccountMeta> {
+        let pubkey = self.resolve(&meta.pubkey)?;
+
+        // We only hold keypairs for the mint and the authority, so hooks can't require
+        // any other signer.
+        if meta.is_signer && pubkey != self.mint && pubkey != self.authority {
+            return Err(anyhow!(
+                "{pubkey} is marked as a signer but only the mint and authority can sign"
+            ));
+        }
+
+        Ok(if meta.is_writable {
+            AccountMeta::new(pubkey, meta.is_signer)
+        } else {
+            AccountMeta::new_readonly(pubkey, meta.is_signer)
+        })
+    }
+}
+
+/// Mint an asset like `mint_asset`, but build the transaction here so that custom
+/// instructions and accounts can be added atomically.
+#[allow(clippy::too_many_arguments)]
+fn mint_asset_with_hooks(
+    client: &RpcClient,
+    authority: &Keypair,
+    receiver: Pubkey,
+    mint_signer: Keypair,
+    asset_data: AssetData,
+    decimals: u8,
+    amount: u64,
+    print_supply: Option<PrintSupply>,
+    hooks: MintHooks,
+    priority: Priority,
+) -> Result<(Signature, Pubkey)> {
+    if decimals > 9 {
+        return Err(anyhow!("Decimals must be less than or equal to 9"));
+    }
+
+    let mint = mint_signer.pubkey();
+    let metadata = derive_metadata_pda(&mint);
+    let edition = derive_edition_pda(&mint);
+    let token = get_associated_token_address(&receiver, &mint);
+
+    let token_standard = asset_data.token_standard.clone();
+
+    let mut create_builder = CreateV1Builder::new();
+    create_builder
+        .mint(mint, true)
+        .metadata(metadata)
+        .authority(authority.pubkey())
+        .payer(authority.pubkey())
+        .update_authority(authority.pubkey(), true)
+        .name(asset_data.name)
+        .symbol(asset_data.symbol)
+        .uri(asset_data.uri)
+        .seller_fee_basis_points(asset_data.seller_fee_basis_points)
+        .primary_sale_happened(asset_data.primary_sale_happened)
+        .is_mutable(asset_data.is_mutable)
+        .token_standard(token_standard.clone())
+        .decimals(decimals);
+
+    if let Some(creators) = asset_data.creators {
+        create_builder.creators(creators);
+    }
+    if let Some(collection) = asset_data.collection {
+        create_builder.collection(collection);
+    }
+    if let Some(uses) = asset_data.uses {
+        create_builder.uses(uses);
+    }
+    if let Some(details) = asset_data.collection_details {
+        create_builder.collection_details(details);
+    }
+    if let Some(rule_set) = asset_data.rule_set {
+        create_builder.rule_set(rule_set);
+    }
+    if let Some(print_supply) = print_supply {
+        create_builder.print_supply(print_supply);
+    }
+
+    let mut mint_builder = MintV1Builder::new();
+    mint_builder
+        .metadata(metadata)
+        .token(token)
+        .token_owner(Some(receiver))
+        .mint(mint)
+        .authority(authority.pubkey())
+        .payer(authority.pubkey())
+        .amount(amount);
+
+    if matches!(
+        token_standard,
+        TokenStandard::NonFungible | TokenStandard::ProgrammableNonFungible
+    ) {
+        if amount != 1 {
+            return Err(anyhow!("Non-fungible assets must have an amount of 1"));
+        }
+        create_builder.master_edition(Some(edition));
+        mint_builder.master_edition(Some(edition));
+    }
+    // Only programmable assets have a token record.
+    if token_standard == TokenStandard::ProgrammableNonFungible {
+        mint_builder.token_record(Some(derive_token_record_pda(&mint, &token)));
+    }
+
+    let ctx = HookContext {
+        mint,
+        metadata,
+        edition,
+        token,
+        receiver,
+        authority: authority.pubkey(),
+    };
+
+    let mut mint_ix = mint_builder.instruction();
+    for meta in &hooks.extra_account_metas {
+        mint_ix.accounts.push(ctx.account_meta(meta)?);
+    }
+
+    let mut instructions = vec![create_builder.instruction(), mint_ix];
+
+    for ix in &hooks.additional_instructions {
+        instructions.push(Instruction {
+            program_id: ctx.resolve(&ix.program_id)?,
+            accounts: ix
+                .accounts
+                .iter()
+                .map(|meta| ctx.account_meta(meta))
+                .collect::<Result<Vec<_>>>()?,
+            data: bs58::decode(&ix.data)
+                .into_vec()
+                .map_err(|e| anyhow!("Invalid instruction data: {e}"))?,
+        });
+    }
+
+    let sig = Pipeline::with_priority(priority)
+        .with_active_lookup_table()?
+        .with_active_nonce()?
+        .run(client, &[authority, &mint_signer], instructions)?;
+
+    Ok((sig, mint))
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum Supply {
     Zero,
@@ -0,0 +1,80 @@
+//! Durable nonce support: transactions use the stored nonce instead of a recent
+//! blockhash so they stay valid until submitted.
+
+use anyhow::{anyhow, Result};
+use solana_client::{nonce_utils, rpc_client::RpcClient};
+use solana_sdk::{
+    hash::Hash, pubkey::Pubkey, signature::Keypair, signer::Signer,
+    system_instruction::advance_nonce_account,
+};
+use std::{path::Path, str::FromStr};
+
+use crate::constants::{NONCE_ACCOUNT, NONCE_AUTHORITY};
+use crate::parse::read_keypair;
+use crate::pipeline::{Middleware, TxContext};
+
+pub enum NonceAuthority {
+    /// The first signer of the transaction.
+    Payer,
+    Keypair(Keypair),
+    /// Only the pubkey is known, e.g. when exporting for offline signing.
+    Pubkey(Pubkey),
+}
+
+pub struct NonceConfig {
+    pub account: Pubkey,
+    pub authority: NonceAuthority,
+}
+
+/// The nonce set with the global `--nonce-account` option, if any.
+pub fn active_nonce() -> Result<Option<NonceConfig>> {
+    let account = match NONCE_ACCOUNT.read().unwrap().as_ref() {
+        Some(account) => {
+            Pubkey::from_str(account).map_err(|_| anyhow!("Invalid nonce account: {account}"))?
+        }
+        None => return Ok(None),
+    };
+
+    // The authority can be a keypair file or, if it signs elsewhere, a pubkey.
+    let authority = match NONCE_AUTHORITY.read().unwrap().as_ref() {
+        None => NonceAuthority::Payer,
+        Some(authority) if Path::new(authority).exists() => {
+            NonceAuthority::Keypair(read_keypair(authority)?)
+        }
+        Some(authority) => NonceAuthority::Pubkey(
+            Pubkey::from_str(authority)
+                .map_err(|_| anyhow!("Nonce authority is not a keypair file or pubkey"))?,
+        ),
+    };
+
+    Ok(Some(NonceConfig { account, authority }))
+}
+
+/// The blockhash currently stored in a nonce account.
+pub fn get_nonce_blockhash(client: &RpcClient, nonce_account: &Pubkey) -> Result<Hash> {
+    let account = nonce_utils::get_account(client, nonce_account)?;
+    let data = nonce_utils::data_from_account(&account)?;
+    Ok(data.blockhash())
+}
+
+/// Pipeline stage that switches the transaction to the durable nonce. Must run before
+/// [`Stage::Sign`](crate::pipeline::Stage::Sign).
+pub struct DurableNonce(pub NonceConfig);
+
+impl Middleware for DurableNonce {
+    fn process(&self, ctx: &mut TxContext) -> Result<()> {
+        let authority = match &self.0.authority {
+            NonceAuthority::Payer => ctx.payer()?.pubkey(),
+            NonceAuthority::Keypair(keypair) => {
+                ctx.extra_signers
+                    .push(Keypair::from_bytes(&keypair.to_bytes())?);
+                keypair.pubkey()
+            }
+            NonceAuthority::Pubkey(pubkey) => *pubkey,
+        };
+
+        ctx.recent_blockhash = Some(get_nonce_blockhash(ctx.client, &self.0.account)?);
+        ctx.advance_nonce = Some(advance_nonce_account(&self.0.account, &authority));
+        Ok(())
+    }
+}
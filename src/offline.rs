@@ -0,0 +1,223 @@
+//! Offline signing: write unsigned transactions to disk instead of sending them, and
+//! broadcast transactions that were signed elsewhere.
+
+use anyhow::{anyhow, Result};
+use indexmap::IndexMap;
+use once_cell::sync::Lazy;
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::{
+    instruction::Instruction,
+    pubkey::Pubkey,
+    signature::{Keypair, Signature},
+    signer::Signer,
+    system_instruction::advance_nonce_account,
+    transaction::Transaction,
+};
+use std::{
+    fs::{self, File},
+    path::{Path, PathBuf},
+    str::FromStr,
+    sync::atomic::{AtomicBool, AtomicUsize, Ordering},
+};
+
+use crate::constants::{EXPORT_UNSIGNED_DIR, OFFLINE_AUTHORITY};
+use crate::nonce::{active_nonce, get_nonce_blockhash, NonceAuthority};
+use crate::pipeline::{Middleware, TxContext};
+use crate::spinner::create_progress_bar;
+use crate::workspace::output_path;
+
+pub const UNSIGNED_TX_EXTENSION: &str = "tx";
+
+// Stands in for the authority when it isn't available on this machine. Every reference
+// to it is swapped for the real authority pubkey before a transaction is exported.
+static PLACEHOLDER: Lazy<Keypair> = Lazy::new(Keypair::new);
+static EXPORT_COUNTER: AtomicUsize = AtomicUsize::new(0);
+// Set once a transaction has been exported with the durable nonce.
+static NONCE_EXPORTED: AtomicBool = AtomicBool::new(false);
+
+pub fn export_dir() -> Option<PathBuf> {
+    EXPORT_UNSIGNED_DIR
+        .read()
+        .unwrap()
+        .as_ref()
+        .map(PathBuf::from)
+}
+
+/// The authority pubkey to export transactions for, if the keypair isn't available.
+pub fn offline_authority() -> Result<Option<Pubkey>> {
+    OFFLINE_AUTHORITY
+        .read()
+        .unwrap()
+        .as_ref()
+        .map(|key| Pubkey::from_str(key).map_err(|_| anyhow!("Invalid offline authority: {key}")))
+        .transpose()
+}
+
+pub fn placeholder_keypair() -> Keypair {
+    Keypair::from_bytes(&PLACEHOLDER.to_bytes()).expect("valid placeholder keypair")
+}
+
+/// The pubkey of the signer transactions are built for. The placeholder stands for the
+/// offline authority, so addresses derived from the signer must use this instead.
+pub fn signer_pubkey(keypair: &Keypair) -> Result<Pubkey> {
+    match offline_authority()? {
+        Some(authority) if keypair.pubkey() == PLACEHOLDER.pubkey() => Ok(authority),
+        _ => Ok(keypair.pubkey()),
+    }
+}
+
+/// Refuse to export transactions whose addresses metaboss_lib derives from the signer,
+/// which would be derived from the placeholder rather than the offline authority.
+pub fn reject_signer_derivation(action: &str) -> Result<()> {
+    if export_dir().is_some() && offline_authority()?.is_some() {
+        return Err(anyhow!(
+            "{action} derives addresses from the signer and can't be exported with \
+             --offline-authority. Export it with the authority's keypair instead"
+        ));
+    }
+    Ok(())
+}
+
+/// Write an unsigned transaction for the instructions to the export directory.
+///
+/// The file is named after `name` if given, otherwise a running counter. If a durable
+/// nonce is set, the transaction advances it and stays valid until the nonce is used.
+/// Only one transaction can be exported per nonce, since whichever lands first advances
+/// the nonce and invalidates the rest.
+pub fn export_unsigned(
+    client: &RpcClient,
+    payer: &Pubkey,
+    instructions: &[Instruction],
+    name: Option<&str>,
+) -> Result<PathBuf> {
+    let dir = export_dir().ok_or_else(|| anyhow!("No export directory set"))?;
+    fs::create_dir_all(&dir)?;
+
+    let authority = offline_authority()?;
+    let retarget = |key: &Pubkey| match authority {
+        Some(authority) if *key == PLACEHOLDER.pubkey() => authority,
+        _ => *key,
+    };
+
+    let nonce = active_nonce()?;
+    let mut instructions: Vec<Instruction> = instructions
+        .iter()
+        .cloned()
+        .map(|mut ix| {
+            for meta in ix.accounts.iter_mut() {
+                meta.pubkey = retarget(&meta.pubkey);
+            }
+            ix
+        })
+        .collect();
+
+    let recent_blockhash = match &nonce {
+        Some(nonce) => {
+            if NONCE_EXPORTED.swap(true, Ordering::SeqCst) {
+                return Err(anyhow!(
+                    "Nonce account {} was already used for an exported transaction and can only \
+                     back one. Export this batch without --nonce-account, or one transaction \
+                     per nonce account",
+                    nonce.account
+                ));
+            }
+            let nonce_authority = match &nonce.authority {
+                NonceAuthority::Payer => retarget(payer),
+                NonceAuthority::Keypair(keypair) => keypair.pubkey(),
+                NonceAuthority::Pubkey(pubkey) => *pubkey,
+            };
+            instructions.insert(0, advance_nonce_account(&nonce.account, &nonce_authority));
+            get_nonce_blockhash(client, &nonce.account)?
+        }
+        None => client.get_latest_blockhash()?,
+    };
+
+    let mut tx = Transaction::new_with_payer(&instructions, Some(&retarget(payer)));
+    tx.message.recent_blockhash = recent_blockhash;
+
+    let index = EXPORT_COUNTER.fetch_add(1, Ordering::SeqCst);
+    let file_name = match name {
+        Some(name) => format!("{index:05}-{name}.{UNSIGNED_TX_EXTENSION}"),
+        None => format!("{index:05}.{UNSIGNED_TX_EXTENSION}"),
+    };
+    let path = dir.join(file_name);
+
+    fs::write(&path, base64::encode(bincode::serialize(&tx)?))?;
+
+    Ok(path)
+}
+
+/// Pipeline stage that exports the transaction in place of signing and sending it.
+pub struct ExportUnsigned;
+
+impl Middleware for ExportUnsigned {
+    fn process(&self, ctx: &mut TxContext) -> Result<()> {
+        let payer = ctx.payer()?.pubkey();
+        let path = export_unsigned(ctx.client, &payer, &ctx.final_instructions(), None)?;
+        println!("Unsigned transaction written to {}", path.display());
+
+        // Nothing was sent, so there is no real signature to report.
+        ctx.signature = Some(Signature::default());
+        Ok(())
+    }
+}
+
+fn read_transaction(path: &Path) -> Result<Transaction> {
+    let contents = fs::read_to_string(path)?;
+    let bytes = base64::decode(contents.trim())?;
+    Ok(bincode::deserialize(&bytes)?)
+}
+
+/// Broadcast every signed transaction in a directory, writing the results to
+/// `mb-submit-signed-results.json`.
+pub fn submit_signed(client: &RpcClient, dir: PathBuf) -> Result<()> {
+    let mut paths: Vec<PathBuf> = fs::read_dir(&dir)?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| path.extension().map(|e| e == UNSIGNED_TX_EXTENSION) == Some(true))
+        .collect();
+    paths.sort();
+
+    if paths.is_empty() {
+        return Err(anyhow!(
+            "No .{UNSIGNED_TX_EXTENSION} files found in {dir:?}"
+        ));
+    }
+
+    let mut results: IndexMap<String, String> = IndexMap::new();
+    let mut failed = 0;
+
+    let pb = create_progress_bar("Submitting transactions...", paths.len() as u64);
+    for path in paths {
+        let name = path.display().to_string();
+
+        let result = read_transaction(&path).and_then(|tx| {
+            tx.verify()
+                .map_err(|_| anyhow!("Transaction is missing signatures or they are invalid"))?;
+            Ok(client.send_and_confirm_transaction(&tx)?)
+        });
+
+        match result {
+            Ok(sig) => {
+                results.insert(name, sig.to_string());
+            }
+            Err(e) => {
+                failed += 1;
+                results.insert(name, format!("error: {e}"));
+            }
+        }
+        pb.inc(1);
+    }
+    pb.finish_and_clear();
+
+    let results_file = output_path("mb-submit-signed-results.json")
+        .display()
+        .to_string();
+    serde_json::to_writer_pretty(File::create(&results_file)?, &results)?;
+
+    println!(
+        "Submitted {} transactions, {failed} failed. Results written to {results_file}",
+        results.len() - failed
+    );
+
+    Ok(())
+}
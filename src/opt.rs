@@ -6,28 +6,166 @@ use structopt::StructOpt;
 
 use crate::{
     airdrop::AirdropSubcommands,
+    audit::AuditSubcommands,
+    bench::BenchSubcommands,
     check::CheckSubcommands,
+    cm::CmSubcommands,
     collections::GetCollectionItemsMethods,
-    constants::DEFAULT_RATE_LIMIT,
+    constants::{DEFAULT_JITO_TIP, DEFAULT_RATE_LIMIT},
+    convert::ConvertSubcommands,
+    core_asset::CoreSubcommands,
+    delegate::DelegateSubcommands,
+    download::DownloadSubcommands,
+    expect::OnMismatch,
+    export::ExportSubcommands,
+    fees::FeesSubcommands,
+    incident::IncidentSubcommands,
+    inscribe::InscribeSubcommands,
+    lut::LutSubcommands,
+    migrate::MigrateSubcommands,
     mint::Supply,
-    snapshot::{HolderGroupKey, MintsGroupKey},
+    order::BatchOrder,
+    output::OutputFormat,
+    plan::PlanSubcommands,
+    rules::RulesSubcommands,
+    snapshot::{DiffFormat, HolderGroupKey, MintsGroupKey, SnapshotFormat},
 };
 
 #[derive(Debug, StructOpt)]
 #[structopt(name = "Metaboss", about = "Metaplex NFT 'Swiss Army Knife' tool.")]
 pub struct Opt {
     /// RPC endpoint url to override using the Solana config or the hard-coded default
-    #[structopt(short, long, global = true)]
+    #[structopt(short, long, global = true, env = "METABOSS_RPC")]
     pub rpc: Option<String>,
 
+    /// Commitment level to override using the Solana config: processed, confirmed or finalized
+    #[structopt(long, global = true, env = "METABOSS_COMMITMENT")]
+    pub commitment: Option<String>,
+
     /// Timeout to override default value of 90 seconds
     #[structopt(short = "T", long, global = true, default_value = "90")]
     pub timeout: u64,
 
+    /// Profile from ~/.config/metaboss/config.toml with the RPC url, keypair and other settings
+    #[structopt(long, global = true)]
+    pub profile: Option<String>,
+
+    /// Seed phrase to derive the keypair from for commands that aren't given a keypair
+    #[structopt(
+        long,
+        global = true,
+        env = "METABOSS_KEYPAIR_SEED",
+        hide_env_values = true
+    )]
+    pub keypair_seed: Option<String>,
+
+    /// Derivation path for --keypair-seed [default: m/44'/501'/0'/0']
+    #[structopt(long, global = true)]
+    pub derivation: Option<String>,
+
     /// Log level
     #[structopt(short, long, global = true, default_value = "off")]
     pub log_level: String,
 
+    /// File to write logs to, with timestamps and thread ids, instead of stderr
+    #[structopt(long, global = true)]
+    pub log_path: Option<PathBuf>,
+
+    /// Jito block engine url: batch transactions are sent as bundles instead of individually
+    #[structopt(long, global = true)]
+    pub jito: Option<String>,
+
+    /// Tip in lamports paid to Jito for each bundle
+    #[structopt(long, global = true, default_value = DEFAULT_JITO_TIP)]
+    pub jito_tip: u64,
+
+    /// Squads v4 multisig address: supported commands create a proposal instead of signing directly
+    #[structopt(long, global = true)]
+    pub multisig: Option<String>,
+
+    /// Index of the multisig vault that holds the authority
+    #[structopt(long, global = true, default_value = "0")]
+    pub vault_index: u8,
+
+    /// Write unsigned transactions to this directory instead of sending them
+    #[structopt(long, global = true)]
+    pub export_unsigned: Option<String>,
+
+    /// Authority pubkey to build exported transactions for, when its keypair is kept offline
+    #[structopt(long, global = true, requires = "export-unsigned")]
+    pub offline_authority: Option<String>,
+
+    /// Durable nonce account to use instead of a recent blockhash
+    #[structopt(long, global = true)]
+    pub nonce_account: Option<String>,
+
+    /// Nonce authority keypair path or pubkey, defaults to the fee payer
+    #[structopt(long, global = true, requires = "nonce-account")]
+    pub nonce_authority: Option<String>,
+
+    /// Address lookup table to build v0 transactions with, to fit more accounts per transaction
+    #[structopt(long, global = true)]
+    pub lut: Option<String>,
+
+    /// Append the signature and operation of every confirmed transaction to this file,
+    /// for use with `fees report`
+    #[structopt(long, global = true)]
+    pub receipts: Option<String>,
+
+    /// Requests per second for every batch command, overriding the command's own --rate-limit.
+    /// Pass it before the command name, e.g. `metaboss --rate-limit 50 update uri-all ...`
+    #[structopt(long = "rate-limit")]
+    pub rate_limit: Option<usize>,
+
+    /// Most requests a batch command keeps in flight at once, and threads for parallel commands
+    #[structopt(long, global = true)]
+    pub concurrency: Option<usize>,
+
+    /// Pause batch sends while the median network priority fee, in microlamports per compute
+    /// unit, is above this, resuming when fees come back down
+    #[structopt(long, global = true)]
+    pub max_network_fee: Option<u64>,
+
+    /// Cache account data and off-chain JSON on disk so repeated reads don't refetch them
+    #[structopt(long, global = true)]
+    pub cache: bool,
+
+    /// Bypass the on-disk cache, even when --cache is set
+    #[structopt(long, global = true)]
+    pub no_cache: bool,
+
+    /// Checkpoint file batch commands record finished items to, skipping them on re-run
+    #[structopt(long, global = true)]
+    pub resume: Option<String>,
+
+    /// Order batch commands process items in: sorted, or shuffled with --seed
+    #[structopt(long, global = true, default_value = "sorted")]
+    pub order: BatchOrder,
+
+    /// Seed for --order shuffled, so a shuffled run can be reproduced
+    #[structopt(long, global = true, default_value = "0")]
+    pub seed: u64,
+
+    /// Snapshot of the metadata batch commands expect, skipping mints that changed since
+    #[structopt(long, global = true)]
+    pub expect_file: Option<PathBuf>,
+
+    /// What to do when a mint doesn't match --expect-file: skip it, or abort the run
+    #[structopt(long, global = true, default_value = "skip")]
+    pub on_mismatch: OnMismatch,
+
+    /// Directory to organize the outputs of related runs in, one subdirectory per run
+    #[structopt(long, global = true)]
+    pub workspace: Option<PathBuf>,
+
+    /// Output format: text, or json for one JSON document of results on stdout
+    #[structopt(long, global = true, default_value = "text")]
+    pub output_format: OutputFormat,
+
+    /// Print only errors and logs, and report the outcome through the exit code
+    #[structopt(long, global = true)]
+    pub quiet: bool,
+
     #[structopt(subcommand)]
     pub cmd: Command,
 }
@@ -58,6 +196,65 @@ pub enum Command {
         #[structopt(subcommand)]
         airdrop_subcommands: AirdropSubcommands,
     },
+    /// Publish an announcement as memos signed by the collection's update authority
+    #[structopt(name = "announce")]
+    Announce {
+        /// Path to the update authority keypair file
+        #[structopt(short, long)]
+        keypair: Option<String>,
+
+        /// Path to the file with the announcement text
+        #[structopt(short, long)]
+        message: PathBuf,
+
+        /// Collection mint the announcement is for
+        #[structopt(long)]
+        collection: Option<String>,
+
+        /// Most bytes of the message to put in each transaction
+        #[structopt(long, default_value = "500")]
+        chunk_size: usize,
+
+        /// Priority of the transaction: higher priority costs more.
+        /// See https://metaboss.rs/priority-fees.html for more details.
+        #[structopt(
+            short = "P",
+            long,
+            default_value = "none",
+            env = "METABOSS_PRIORITY_FEE"
+        )]
+        priority: Priority,
+    },
+    /// Read the announcements an authority published
+    #[structopt(name = "announcements")]
+    Announcements {
+        /// Address of the authority that published the announcements
+        authority: String,
+
+        /// Only show announcements for this collection mint
+        #[structopt(long)]
+        collection: Option<String>,
+
+        /// Most announcements to show, newest first
+        #[structopt(long, default_value = "10")]
+        limit: usize,
+
+        /// Write the announcements to this JSON file instead of printing them
+        #[structopt(short, long)]
+        output: Option<PathBuf>,
+    },
+    /// Audit collections against previous snapshots
+    #[structopt(name = "audit")]
+    Audit {
+        #[structopt(subcommand)]
+        audit_subcommands: AuditSubcommands,
+    },
+    /// Benchmark RPC endpoints and priority fees
+    #[structopt(name = "bench")]
+    Bench {
+        #[structopt(subcommand)]
+        bench_subcommands: BenchSubcommands,
+    },
     /// Full Burn an asset
     #[structopt(name = "burn")]
     Burn {
@@ -82,6 +279,57 @@ pub enum Command {
         #[structopt(subcommand)]
         check_subcommands: CheckSubcommands,
     },
+    /// Candy machine commands
+    #[structopt(name = "cm")]
+    Cm {
+        #[structopt(subcommand)]
+        cm_subcommands: CmSubcommands,
+    },
+    /// Sweep fungible tokens and SOL from many wallets into one
+    #[structopt(name = "consolidate")]
+    Consolidate {
+        /// Fee payer keypair, defaults to the Solana config keypair
+        #[structopt(short, long)]
+        keypair: Option<String>,
+
+        /// Directory of keypair files for the wallets to sweep
+        #[structopt(short = "d", long)]
+        keypair_dir: PathBuf,
+
+        /// Wallet to send everything to
+        #[structopt(short = "D", long)]
+        destination: Pubkey,
+
+        /// Fungible token mint to sweep the full balance of
+        #[structopt(short, long)]
+        mint: Option<Pubkey>,
+
+        /// Also sweep SOL, leaving this much in each wallet
+        #[structopt(long)]
+        sol_reserve: Option<f64>,
+
+        /// Priority of the transactions: higher priority costs more.
+        /// See https://metaboss.rs/priority-fees.html for more details.
+        #[structopt(
+            short = "P",
+            long,
+            default_value = "none",
+            env = "METABOSS_PRIORITY_FEE"
+        )]
+        priority: Priority,
+    },
+    /// Convert Metaboss outputs to and from Umi and JS SDK formats
+    #[structopt(name = "convert")]
+    Convert {
+        #[structopt(subcommand)]
+        convert_subcommands: ConvertSubcommands,
+    },
+    /// MPL Core asset commands
+    #[structopt(name = "core")]
+    Core {
+        #[structopt(subcommand)]
+        core_subcommands: CoreSubcommands,
+    },
     /// Create accounts
     #[structopt(name = "create")]
     Create {
@@ -94,11 +342,29 @@ pub enum Command {
         #[structopt(subcommand)]
         decode_subcommands: DecodeSubcommands,
     },
+    /// Approve and revoke Token Metadata and SPL token delegates
+    #[structopt(name = "delegate")]
+    Delegate {
+        #[structopt(subcommand)]
+        delegate_subcommands: DelegateSubcommands,
+    },
     /// Derive PDAs for various account types
     Derive {
         #[structopt(subcommand)]
         derive_subcommands: DeriveSubcommands,
     },
+    /// Download off-chain assets
+    #[structopt(name = "download")]
+    Download {
+        #[structopt(subcommand)]
+        download_subcommands: DownloadSubcommands,
+    },
+    /// Export collection data for publishing
+    #[structopt(name = "export")]
+    Export {
+        #[structopt(subcommand)]
+        export_subcommands: ExportSubcommands,
+    },
     /// Extend an on-chain program's binary size
     ExtendProgram {
         program_address: Pubkey,
@@ -107,18 +373,193 @@ pub enum Command {
         #[structopt(short, long)]
         keypair_path: Option<String>,
     },
+    /// Fee reports for sent transactions
+    #[structopt(name = "fees")]
+    Fees {
+        #[structopt(subcommand)]
+        fees_subcommands: FeesSubcommands,
+    },
     /// Find things.
     #[structopt(name = "find")]
     Find {
         #[structopt(subcommand)]
         find_subcommands: FindSubcommands,
     },
+    /// Freeze the token account of every mint in a list as its token delegate
+    #[structopt(name = "freeze-all")]
+    FreezeAll {
+        /// Path to the token delegate or freeze authority keypair file
+        #[structopt(short, long)]
+        keypair: Option<String>,
+
+        /// Path to a JSON list of mints
+        #[structopt(short = "L", long)]
+        mint_list: PathBuf,
+
+        /// Priority of the transaction: higher priority costs more.
+        /// See https://metaboss.rs/priority-fees.html for more details.
+        #[structopt(
+            short = "P",
+            long,
+            default_value = "none",
+            env = "METABOSS_PRIORITY_FEE"
+        )]
+        priority: Priority,
+    },
+    /// Thaw the token account of every mint in a list as its token delegate
+    #[structopt(name = "thaw-all")]
+    ThawAll {
+        /// Path to the token delegate or freeze authority keypair file
+        #[structopt(short, long)]
+        keypair: Option<String>,
+
+        /// Path to a JSON list of mints
+        #[structopt(short = "L", long)]
+        mint_list: PathBuf,
+
+        /// Priority of the transaction: higher priority costs more.
+        /// See https://metaboss.rs/priority-fees.html for more details.
+        #[structopt(
+            short = "P",
+            long,
+            default_value = "none",
+            env = "METABOSS_PRIORITY_FEE"
+        )]
+        priority: Priority,
+    },
+    /// Incident response: freeze or thaw a whole collection
+    #[structopt(name = "incident")]
+    Incident {
+        #[structopt(subcommand)]
+        incident_subcommands: IncidentSubcommands,
+    },
+    /// MPL Inscription commands
+    #[structopt(name = "inscribe")]
+    Inscribe {
+        #[structopt(subcommand)]
+        inscribe_subcommands: InscribeSubcommands,
+    },
+    /// Lock every pNFT in a list as the delegate on its token record
+    #[structopt(name = "lock-all")]
+    LockAll {
+        /// Path to the token record delegate keypair file
+        #[structopt(short, long)]
+        keypair: Option<String>,
+
+        /// Path to a JSON list of pNFT mints
+        #[structopt(short = "L", long)]
+        mint_list: PathBuf,
+
+        /// Priority of the transaction: higher priority costs more.
+        /// See https://metaboss.rs/priority-fees.html for more details.
+        #[structopt(
+            short = "P",
+            long,
+            default_value = "none",
+            env = "METABOSS_PRIORITY_FEE"
+        )]
+        priority: Priority,
+    },
+    /// Unlock every pNFT in a list as the delegate on its token record
+    #[structopt(name = "unlock-all")]
+    UnlockAll {
+        /// Path to the token record delegate keypair file
+        #[structopt(short, long)]
+        keypair: Option<String>,
+
+        /// Path to a JSON list of pNFT mints
+        #[structopt(short = "L", long)]
+        mint_list: PathBuf,
+
+        /// Priority of the transaction: higher priority costs more.
+        /// See https://metaboss.rs/priority-fees.html for more details.
+        #[structopt(
+            short = "P",
+            long,
+            default_value = "none",
+            env = "METABOSS_PRIORITY_FEE"
+        )]
+        priority: Priority,
+    },
+    /// Address lookup table commands
+    #[structopt(name = "lut")]
+    Lut {
+        #[structopt(subcommand)]
+        lut_subcommands: LutSubcommands,
+    },
+    /// Migrate assets between storage backends
+    #[structopt(name = "migrate")]
+    Migrate {
+        #[structopt(subcommand)]
+        migrate_subcommands: MigrateSubcommands,
+    },
     /// Mint new NFTs from JSON files
     #[structopt(name = "mint")]
     Mint {
         #[structopt(subcommand)]
         mint_subcommands: MintSubcommands,
     },
+    /// Run several update steps on each mint in a single transaction
+    #[structopt(name = "plan")]
+    Plan {
+        #[structopt(subcommand)]
+        plan_subcommands: PlanSubcommands,
+    },
+    /// Run a local RPC proxy that lets parallel Metaboss runs share one rate limit
+    #[structopt(name = "proxy")]
+    Proxy {
+        /// Address to listen on
+        #[structopt(long, default_value = "127.0.0.1:8899")]
+        listen: String,
+
+        /// Maximum number of upstream requests per second, shared by every client
+        #[structopt(short = "R", long, default_value = DEFAULT_RATE_LIMIT)]
+        rate_limit: usize,
+
+        /// Seconds to cache finalized account reads for
+        #[structopt(long, default_value = "5")]
+        account_ttl: u64,
+    },
+    /// Create, update and decode Token Auth Rules rule sets for pNFTs
+    #[structopt(name = "rules")]
+    Rules {
+        #[structopt(subcommand)]
+        rules_subcommands: RulesSubcommands,
+    },
+    /// Mint, update, snapshot and burn an NFT on a local test validator to check this build
+    #[structopt(name = "selftest")]
+    Selftest {
+        /// Path to the keypair to mint with, airdropped SOL if it runs low
+        #[structopt(short, long)]
+        keypair: Option<String>,
+
+        /// Write the results as JSON to this file
+        #[structopt(short, long)]
+        output: Option<PathBuf>,
+    },
+    /// Serve read-only Metaboss operations, and optionally signing ones, over HTTP
+    #[structopt(name = "serve")]
+    Serve {
+        /// Port to listen on
+        #[structopt(short, long, default_value = "8080")]
+        port: u16,
+
+        /// Address to bind to
+        #[structopt(long, default_value = "127.0.0.1")]
+        host: String,
+
+        /// Bearer token every request must send
+        #[structopt(short, long, env = "METABOSS_SERVE_TOKEN", hide_env_values = true)]
+        token: String,
+
+        /// Extra token that enables the signing endpoints, sent in the X-Signing-Token header
+        #[structopt(long, env = "METABOSS_SERVE_SIGNING_TOKEN", hide_env_values = true)]
+        signing_token: Option<String>,
+
+        /// Keypair the signing endpoints sign with: a path, or an inline base58 or JSON key
+        #[structopt(short, long)]
+        keypair: Option<String>,
+    },
     /// Update various aspects of NFTs
     #[structopt(name = "update")]
     Update {
@@ -131,6 +572,12 @@ pub enum Command {
         #[structopt(subcommand)]
         set_subcommands: SetSubcommands,
     },
+    /// Broadcast transactions that were exported with --export-unsigned and signed offline
+    #[structopt(name = "submit-signed")]
+    SubmitSigned {
+        /// Directory of signed transaction files
+        dir: PathBuf,
+    },
     /// Sign metadata for an unverified creator
     #[structopt(name = "sign")]
     Sign {
@@ -161,6 +608,9 @@ pub enum Command {
         #[structopt(subcommand)]
         unverify_subcommands: UnverifySubcommands,
     },
+    /// Any other subcommand runs the `metaboss-<name>` plugin on the PATH
+    #[structopt(external_subcommand)]
+    External(Vec<String>),
 }
 
 #[derive(Debug, StructOpt)]
@@ -184,9 +634,18 @@ pub enum BurnSubcommands {
         #[structopt(long, default_value = "1")]
         amount: u64,
 
+        /// Burn master editions even if they still have prints
+        #[structopt(long)]
+        force: bool,
+
         /// Priority of the transaction: higher priority costs more.
         /// See https://metaboss.rs/priority-fees.html for more details.
-        #[structopt(short = "P", long, default_value = "none")]
+        #[structopt(
+            short = "P",
+            long,
+            default_value = "none",
+            env = "METABOSS_PRIORITY_FEE"
+        )]
         priority: Priority,
     },
     /// Burn a batch of assets.
@@ -212,9 +671,76 @@ pub enum BurnSubcommands {
         #[structopt(long, default_value = "0")]
         retries: u8,
 
+        /// Burn master editions even if they still have prints
+        #[structopt(long)]
+        force: bool,
+
+        /// Priority of the transaction: higher priority costs more.
+        /// See https://metaboss.rs/priority-fees.html for more details.
+        #[structopt(
+            short = "P",
+            long,
+            default_value = "none",
+            env = "METABOSS_PRIORITY_FEE"
+        )]
+        priority: Priority,
+    },
+    /// Burn every print edition of a master edition held by the keypair.
+    #[structopt(name = "editions")]
+    Editions {
+        /// Path to the owner keypair file
+        #[structopt(short, long)]
+        keypair: Option<String>,
+
+        /// Master edition mint account
+        #[structopt(short, long)]
+        master: String,
+
+        /// Cache file
+        #[structopt(short, long)]
+        cache_file: Option<String>,
+
+        /// Maximum number of requests per second
+        #[structopt(short = "R", long, default_value = DEFAULT_RATE_LIMIT)]
+        rate_limit: usize,
+
+        /// Maximum retries: retry failed items up to this many times.
+        #[structopt(long, default_value = "0")]
+        retries: u8,
+
+        /// Priority of the transaction: higher priority costs more.
+        /// See https://metaboss.rs/priority-fees.html for more details.
+        #[structopt(
+            short = "P",
+            long,
+            default_value = "none",
+            env = "METABOSS_PRIORITY_FEE"
+        )]
+        priority: Priority,
+    },
+    /// Burn a compressed NFT.
+    #[structopt(name = "cnft")]
+    Cnft {
+        /// Path to the owner or delegate keypair file
+        #[structopt(short, long)]
+        keypair: Option<String>,
+
+        /// Asset id of the compressed NFT
+        #[structopt(short, long)]
+        asset_id: Pubkey,
+
+        /// DAS API endpoint to fetch the asset proof from, defaults to the RPC URL
+        #[structopt(long)]
+        das_url: Option<String>,
+
         /// Priority of the transaction: higher priority costs more.
         /// See https://metaboss.rs/priority-fees.html for more details.
-        #[structopt(short = "P", long, default_value = "none")]
+        #[structopt(
+            short = "P",
+            long,
+            default_value = "none",
+            env = "METABOSS_PRIORITY_FEE"
+        )]
         priority: Priority,
     },
 }
@@ -232,9 +758,18 @@ pub enum BurnNftSubcommands {
         #[structopt(short = "a", long)]
         mint_account: String,
 
+        /// Burn master editions even if they still have prints
+        #[structopt(long)]
+        force: bool,
+
         /// Priority of the transaction: higher priority costs more.
         /// See https://metaboss.rs/priority-fees.html for more details.
-        #[structopt(short = "P", long, default_value = "none")]
+        #[structopt(
+            short = "P",
+            long,
+            default_value = "none",
+            env = "METABOSS_PRIORITY_FEE"
+        )]
         priority: Priority,
     },
     /// Burn a batch of NFTs.
@@ -260,9 +795,18 @@ pub enum BurnNftSubcommands {
         #[structopt(long, default_value = "0")]
         retries: u8,
 
+        /// Burn master editions even if they still have prints
+        #[structopt(long)]
+        force: bool,
+
         /// Priority of the transaction: higher priority costs more.
         /// See https://metaboss.rs/priority-fees.html for more details.
-        #[structopt(short = "P", long, default_value = "none")]
+        #[structopt(
+            short = "P",
+            long,
+            default_value = "none",
+            env = "METABOSS_PRIORITY_FEE"
+        )]
         priority: Priority,
     },
 }
@@ -286,7 +830,12 @@ pub enum BurnPrintSubcommands {
 
         /// Priority of the transaction: higher priority costs more.
         /// See https://metaboss.rs/priority-fees.html for more details.
-        #[structopt(short = "P", long, default_value = "none")]
+        #[structopt(
+            short = "P",
+            long,
+            default_value = "none",
+            env = "METABOSS_PRIORITY_FEE"
+        )]
         priority: Priority,
     },
     /// Burn a batch of NFTs.
@@ -318,7 +867,12 @@ pub enum BurnPrintSubcommands {
 
         /// Priority of the transaction: higher priority costs more.
         /// See https://metaboss.rs/priority-fees.html for more details.
-        #[structopt(short = "P", long, default_value = "none")]
+        #[structopt(
+            short = "P",
+            long,
+            default_value = "none",
+            env = "METABOSS_PRIORITY_FEE"
+        )]
         priority: Priority,
     },
 }
@@ -327,7 +881,7 @@ pub enum BurnPrintSubcommands {
 pub enum CreateSubcommands {
     /// Create a metadata account for an existing SPL token mint.
     Metadata {
-        /// Path to the update authority keypair file
+        /// Path to the mint authority keypair file, which becomes the update authority
         #[structopt(short, long)]
         keypair: Option<String>,
 
@@ -345,7 +899,12 @@ pub enum CreateSubcommands {
 
         /// Priority of the transaction: higher priority costs more.
         /// See https://metaboss.rs/priority-fees.html for more details.
-        #[structopt(short = "P", long, default_value = "none")]
+        #[structopt(
+            short = "P",
+            long,
+            default_value = "none",
+            env = "METABOSS_PRIORITY_FEE"
+        )]
         priority: Priority,
 
         /// Use full compute units, 200k CU, instead of simulating the expected amount.
@@ -379,9 +938,22 @@ pub enum CreateSubcommands {
         #[structopt(long)]
         immutable: bool,
 
+        /// Revoke the mint authority after minting the initial supply, fixing the supply.
+        #[structopt(long)]
+        revoke_mint_authority: bool,
+
+        /// Revoke the freeze authority, so token accounts can never be frozen.
+        #[structopt(long)]
+        revoke_freeze_authority: bool,
+
         /// Priority of the transaction: higher priority costs more.
         /// See https://metaboss.rs/priority-fees.html for more details.
-        #[structopt(short = "P", long, default_value = "none")]
+        #[structopt(
+            short = "P",
+            long,
+            default_value = "none",
+            env = "METABOSS_PRIORITY_FEE"
+        )]
         priority: Priority,
 
         /// Use full compute units, 200k CU, instead of simulating the expected amount.
@@ -413,7 +985,12 @@ pub enum CreateSubcommands {
 
         /// Priority of the transaction: higher priority costs more.
         /// See https://metaboss.rs/priority-fees.html for more details.
-        #[structopt(short = "P", long, default_value = "none")]
+        #[structopt(
+            short = "P",
+            long,
+            default_value = "none",
+            env = "METABOSS_PRIORITY_FEE"
+        )]
         priority: Priority,
     },
 
@@ -434,7 +1011,12 @@ pub enum CreateSubcommands {
 
         /// Priority of the transaction: higher priority costs more.
         /// See https://metaboss.rs/priority-fees.html for more details.
-        #[structopt(short = "P", long, default_value = "none")]
+        #[structopt(
+            short = "P",
+            long,
+            default_value = "none",
+            env = "METABOSS_PRIORITY_FEE"
+        )]
         priority: Priority,
     },
 
@@ -459,7 +1041,12 @@ pub enum CreateSubcommands {
 
         /// Priority of the transaction: higher priority costs more.
         /// See https://metaboss.rs/priority-fees.html for more details.
-        #[structopt(short = "P", long, default_value = "none")]
+        #[structopt(
+            short = "P",
+            long,
+            default_value = "none",
+            env = "METABOSS_PRIORITY_FEE"
+        )]
         priority: Priority,
     },
 }
@@ -467,7 +1054,7 @@ pub enum CreateSubcommands {
 #[derive(Debug, StructOpt)]
 pub enum UsesSubcommands {
     /// Approve a delegate authority that is allowed to make changes to the NFT's Use data.
-    #[structopt(name = "approve-authority")]
+    #[structopt(name = "approve-authority", alias = "approve")]
     ApproveAuthority {
         /// Path to the update authority keypair file
         #[structopt(short, long)]
@@ -481,9 +1068,9 @@ pub enum UsesSubcommands {
         #[structopt(short, long)]
         delegate_use_authority: String,
 
-        /// NFT Owner Token Account
+        /// NFT Owner Token Account, defaults to the keypair's associated token account
         #[structopt(short, long)]
-        token_account_nft: String,
+        token_account_nft: Option<String>,
 
         /// Burner Program ID
         #[structopt(short, long)]
@@ -493,8 +1080,53 @@ pub enum UsesSubcommands {
         #[structopt(short, long)]
         number_of_uses: u64,
     },
+    /// Generate burner keypairs and approve each one as a use authority on a set of NFTs.
+    #[structopt(name = "approve-burners")]
+    ApproveBurners {
+        /// Path to the NFT owner keypair file, which also pays for the use authority records
+        #[structopt(short, long)]
+        keypair: Option<String>,
+
+        /// NFT mint address
+        #[structopt(short, long)]
+        mint_nft: Option<String>,
+
+        /// JSON file with a list of NFT mint addresses
+        #[structopt(short = "L", long)]
+        mint_list: Option<String>,
+
+        /// Number of burner keypairs to generate
+        #[structopt(short, long)]
+        count: usize,
+
+        /// Number of uses each burner is allowed on each NFT
+        #[structopt(short, long)]
+        number_of_uses: u64,
+
+        /// SOL to send each burner so it can pay for its own utilize transactions
+        #[structopt(short, long)]
+        fund: Option<f64>,
+
+        /// Burner Program ID, defaults to the Token Metadata burner
+        #[structopt(short, long)]
+        burner_program_id: Option<String>,
+
+        /// Directory to write the burner keypairs to
+        #[structopt(short, long, default_value = ".")]
+        output: String,
+
+        /// Priority of the transaction: higher priority costs more.
+        /// See https://metaboss.rs/priority-fees.html for more details.
+        #[structopt(
+            short = "P",
+            long,
+            default_value = "none",
+            env = "METABOSS_PRIORITY_FEE"
+        )]
+        priority: Priority,
+    },
     /// Revoke a delegate authority from being allowed to make changes to the NFT's Use data.
-    #[structopt(name = "revoke-authority")]
+    #[structopt(name = "revoke-authority", alias = "revoke")]
     RevokeAuthority {
         /// Path to the update authority keypair file
         #[structopt(short, long)]
@@ -508,9 +1140,9 @@ pub enum UsesSubcommands {
         #[structopt(short, long)]
         delegate_use_authority: String,
 
-        /// NFT Owner Token Account
+        /// NFT Owner Token Account, defaults to the keypair's associated token account
         #[structopt(short, long)]
-        token_account_nft: String,
+        token_account_nft: Option<String>,
     },
     /// Use a NFT, following the on-chain logic for burning it if set.
     #[structopt(name = "utilize")]
@@ -523,13 +1155,13 @@ pub enum UsesSubcommands {
         #[structopt(short, long)]
         mint_nft: String,
 
-        /// NFT Holder/Owner address
+        /// NFT Holder/Owner address, defaults to the owner of the token account
         #[structopt(short, long)]
-        holder_nft: String,
+        holder_nft: Option<String>,
 
-        /// NFT Owner Token Account
+        /// NFT Owner Token Account, defaults to the account holding the NFT
         #[structopt(short, long)]
-        token_account_nft: String,
+        token_account_nft: Option<String>,
 
         /// Optional Burner Program ID (if token use is set to Burn)
         #[structopt(short, long)]
@@ -539,6 +1171,21 @@ pub enum UsesSubcommands {
         #[structopt(short = "d", long)]
         is_delegate: bool,
     },
+    /// Print the remaining uses of an NFT or a list of NFTs.
+    #[structopt(name = "remaining")]
+    Remaining {
+        /// NFT mint address
+        #[structopt(short, long)]
+        mint_nft: Option<String>,
+
+        /// JSON file with a list of NFT mint addresses
+        #[structopt(short = "L", long)]
+        mint_list: Option<String>,
+
+        /// Write the uses as JSON to this file
+        #[structopt(short, long)]
+        output: Option<PathBuf>,
+    },
 }
 
 #[derive(Debug, StructOpt)]
@@ -581,6 +1228,43 @@ pub enum CollectionsSubcommands {
         #[structopt(short = "d", long)]
         is_delegate: bool,
     },
+    /// Unverify a list of NFTs from a collection.
+    #[structopt(name = "unverify-all")]
+    UnverifyCollectionAll {
+        /// Path to the update authority or collection delegate keypair file
+        #[structopt(short, long)]
+        keypair: Option<String>,
+
+        /// Collection mint address
+        #[structopt(short, long)]
+        collection_mint: String,
+
+        /// Path to the mint list file
+        #[structopt(short = "L", long)]
+        mint_list: Option<String>,
+
+        /// Cache file
+        #[structopt(long)]
+        cache_file: Option<String>,
+
+        /// Maximum number of requests per second
+        #[structopt(short = "R", long, default_value = DEFAULT_RATE_LIMIT)]
+        rate_limit: usize,
+
+        /// Maximum retries: retry failed items up to this many times.
+        #[structopt(long, default_value = "0")]
+        retries: u8,
+
+        /// Priority of the transaction: higher priority costs more.
+        /// See https://metaboss.rs/priority-fees.html for more details.
+        #[structopt(
+            short = "P",
+            long,
+            default_value = "none",
+            env = "METABOSS_PRIORITY_FEE"
+        )]
+        priority: Priority,
+    },
     /// Set collection value on NFT and verify in the same step.
     #[structopt(name = "set-and-verify")]
     SetAndVerifyCollection {
@@ -619,6 +1303,10 @@ pub enum CollectionsSubcommands {
         /// Delegate authority address
         #[structopt(short, long)]
         delegate_authority: String,
+
+        /// Use a legacy CollectionAuthorityRecord instead of a collection metadata delegate
+        #[structopt(long)]
+        legacy: bool,
     },
     /// Revoke the delegate authority from being allowed to change collection data on the NFT.
     #[structopt(name = "revoke-authority")]
@@ -634,6 +1322,21 @@ pub enum CollectionsSubcommands {
         /// Delegate authority address
         #[structopt(short, long)]
         delegate_authority: String,
+
+        /// Use a legacy CollectionAuthorityRecord instead of a collection metadata delegate
+        #[structopt(long)]
+        legacy: bool,
+    },
+    /// List the delegate authorities of a collection.
+    #[structopt(name = "list-authorities")]
+    ListAuthorities {
+        /// Collection mint address
+        #[structopt(short, long, alias = "collection")]
+        collection_mint: String,
+
+        /// Also check these addresses for legacy CollectionAuthorityRecords
+        #[structopt(long)]
+        check_legacy: Vec<String>,
     },
     /// Set the size of a collection that doesn't already have the size set.
     #[structopt(name = "set-size")]
@@ -643,7 +1346,7 @@ pub enum CollectionsSubcommands {
         keypair: Option<String>,
 
         /// Collection mint address
-        #[structopt(short, long)]
+        #[structopt(short, long, alias = "collection")]
         collection_mint: String,
 
         /// Collection size
@@ -684,6 +1387,10 @@ pub enum CollectionsSubcommands {
         /// Output file path for the cache file. Defaults to mb-cache-migrate.json.
         #[structopt(short, long)]
         output_file: Option<String>,
+
+        /// Pack as many items as fit into each transaction instead of one per mint
+        #[structopt(long)]
+        pack: bool,
     },
     /// Get all items belonging to a collection parent.
     #[structopt(name = "get-items")]
@@ -790,6 +1497,14 @@ pub enum DecodeSubcommands {
         #[structopt(long)]
         raw: bool,
 
+        /// Add computed edition fields and any edition anomalies to the output
+        #[structopt(long)]
+        edition_info: bool,
+
+        /// Fetch each mint's URI and add the off-chain JSON to the output
+        #[structopt(long)]
+        off_chain: bool,
+
         /// Path to directory to save output files.
         #[structopt(short, long, default_value = ".")]
         output: String,
@@ -873,10 +1588,44 @@ pub enum DeriveSubcommands {
     #[structopt(name = "cmv2-creator")]
     CMV2Creator { candy_machine_id: String },
 
+    /// Derive CMV3 Creator PDA, the candy machine's authority PDA
+    #[structopt(name = "candy-machine-creator", alias = "cmv3-creator")]
+    CandyMachineCreator { candy_machine_id: String },
+
+    /// Derive Token Record PDA of a pNFT token account
     #[structopt(name = "token-record")]
     TokenRecord {
         mint_account: String,
-        token_account: String,
+        /// Token account holding the pNFT
+        token_account: Option<String>,
+        /// Derive from the owner's associated token account instead
+        #[structopt(long)]
+        owner: Option<String>,
+    },
+
+    /// Derive legacy Collection Authority Record PDA
+    #[structopt(name = "collection-authority-record")]
+    CollectionAuthorityRecord {
+        mint_account: Pubkey,
+        collection_authority: Pubkey,
+    },
+
+    /// Derive Use Authority Record PDA
+    #[structopt(name = "use-authority-record")]
+    UseAuthorityRecord {
+        mint_account: Pubkey,
+        use_authority: Pubkey,
+    },
+
+    /// Derive Metadata Delegate Record PDA
+    #[structopt(name = "metadata-delegate-record")]
+    MetadataDelegateRecord {
+        mint_account: Pubkey,
+        /// authority-item, collection, collection-item, data, data-item, programmable-config,
+        /// programmable-config-item or use
+        role: String,
+        update_authority: Pubkey,
+        delegate: Pubkey,
     },
 
     #[structopt(name = "collection-delegate")]
@@ -889,11 +1638,57 @@ pub enum DeriveSubcommands {
 
 #[derive(Debug, StructOpt)]
 pub enum FindSubcommands {
-    /// Find any missing editions for a Master NFT mint account.
+    /// Find any missing editions for a Master NFT mint account, or a list of them.
     #[structopt(name = "missing-editions")]
     MissingEditions {
+        /// Master NFT mint account
         #[structopt(short, long)]
-        account: String,
+        account: Option<String>,
+
+        /// JSON file with a list of master NFT mint accounts
+        #[structopt(short = "L", long)]
+        mint_list: Option<String>,
+
+        /// Write the missing editions of each master as JSON to this file
+        #[structopt(short, long)]
+        output: Option<PathBuf>,
+    },
+    /// Find the mints in a list where a creator is listed but unverified.
+    #[structopt(name = "unverified-creators")]
+    UnverifiedCreators {
+        /// JSON file with a list of mint accounts
+        #[structopt(short = "L", long)]
+        mint_list: String,
+
+        /// Creator to look for
+        #[structopt(short, long)]
+        creator: Pubkey,
+
+        /// Write the mints as a JSON list to this file, e.g. to pass to `sign all`
+        #[structopt(short, long)]
+        output: Option<PathBuf>,
+    },
+    /// Find the mints in a list whose largest token account is frozen or locked.
+    #[structopt(name = "frozen")]
+    Frozen {
+        /// JSON file with a list of mint accounts
+        #[structopt(short = "L", long)]
+        mint_list: String,
+
+        /// Write the frozen tokens as JSON to this file
+        #[structopt(short, long)]
+        output: Option<PathBuf>,
+    },
+    /// Find the burned mints in a hash list and write a cleaned list without them.
+    #[structopt(name = "burned")]
+    Burned {
+        /// JSON file with a list of mint accounts
+        #[structopt(short = "L", long)]
+        mint_list: String,
+
+        /// Directory to write the burned and cleaned lists to
+        #[structopt(short, long, default_value = ".")]
+        output: PathBuf,
     },
     #[structopt(name = "error")]
     Error {
@@ -928,7 +1723,12 @@ pub enum MintSubcommands {
 
         /// Priority of the transaction: higher priority costs more.
         /// See https://metaboss.rs/priority-fees.html for more details.
-        #[structopt(short = "P", long, default_value = "none")]
+        #[structopt(
+            short = "P",
+            long,
+            default_value = "none",
+            env = "METABOSS_PRIORITY_FEE"
+        )]
         priority: Priority,
     },
     /// Mint an asset from the new Token Metadata Program unified handlers.
@@ -962,9 +1762,22 @@ pub enum MintSubcommands {
         #[structopt(short = "s", long)]
         max_print_edition_supply: Option<Supply>,
 
+        /// JSON file of extra account metas to append to the mint instruction.
+        #[structopt(long)]
+        extra_account_metas: Option<PathBuf>,
+
+        /// JSON file of instructions to append to the mint transaction.
+        #[structopt(long)]
+        additional_instructions: Option<PathBuf>,
+
         /// Priority of the transaction: higher priority costs more.
         /// See https://metaboss.rs/priority-fees.html for more details.
-        #[structopt(short = "P", long, default_value = "none")]
+        #[structopt(
+            short = "P",
+            long,
+            default_value = "none",
+            env = "METABOSS_PRIORITY_FEE"
+        )]
         priority: Priority,
     },
     /// Mint a single NFT from a JSON file
@@ -1012,7 +1825,12 @@ pub enum MintSubcommands {
 
         /// Priority of the transaction: higher priority costs more.
         /// See https://metaboss.rs/priority-fees.html for more details.
-        #[structopt(short = "P", long, default_value = "none")]
+        #[structopt(
+            short = "P",
+            long,
+            default_value = "none",
+            env = "METABOSS_PRIORITY_FEE"
+        )]
         priority: Priority,
     },
     /// Mint one or more editions from a Master NFT.
@@ -1023,13 +1841,17 @@ pub enum MintSubcommands {
         keypair: Option<String>,
 
         /// Master Edition NFT mint account/token address.
-        #[structopt(short, long)]
+        #[structopt(short, long, alias = "master")]
         account: String,
 
         /// Receiving address, if different from update authority.
         #[structopt(short = "R", long)]
         receiver: Option<String>,
 
+        /// JSON list of addresses to each receive the next edition in order
+        #[structopt(long)]
+        recipients: Option<PathBuf>,
+
         /// Mint the next n editions in order.
         #[structopt(short, long)]
         next_editions: Option<u64>,
@@ -1038,9 +1860,47 @@ pub enum MintSubcommands {
         #[structopt(short = "s", long)]
         specific_editions: Option<Vec<u64>>,
 
+        /// Check the new editions inherited the master's creators, royalties and collection
+        #[structopt(long)]
+        verify: bool,
+
+        /// Priority of the transaction: higher priority costs more.
+        /// See https://metaboss.rs/priority-fees.html for more details.
+        #[structopt(
+            short = "P",
+            long,
+            default_value = "none",
+            env = "METABOSS_PRIORITY_FEE"
+        )]
+        priority: Priority,
+    },
+    /// Check editions still match their master's creators, royalties and collection.
+    #[structopt(name = "verify-editions")]
+    VerifyEditions {
+        /// Path to the editions' update authority keypair file, used with --repair
+        #[structopt(short, long)]
+        keypair: Option<String>,
+
+        /// Master Edition NFT mint account/token address.
+        #[structopt(short, long)]
+        account: String,
+
+        /// Mint list of the prints, or the edition to mint file written by `snapshot prints`
+        #[structopt(short = "L", long)]
+        print_list: PathBuf,
+
+        /// Update the editions that diverged to match their master
+        #[structopt(long)]
+        repair: bool,
+
         /// Priority of the transaction: higher priority costs more.
         /// See https://metaboss.rs/priority-fees.html for more details.
-        #[structopt(short = "P", long, default_value = "none")]
+        #[structopt(
+            short = "P",
+            long,
+            default_value = "none",
+            env = "METABOSS_PRIORITY_FEE"
+        )]
         priority: Priority,
     },
     /// Find any missing editions for a Master NFT.
@@ -1050,12 +1910,88 @@ pub enum MintSubcommands {
         #[structopt(short, long)]
         keypair: Option<String>,
 
+        /// Master Edition NFT mint account
+        #[structopt(short, long, alias = "master")]
+        account: Option<String>,
+
+        /// Print the missing editions listed in a `find missing-editions` output file
+        #[structopt(short = "f", long)]
+        missing_file: Option<PathBuf>,
+
+        /// Priority of the transaction: higher priority costs more.
+        /// See https://metaboss.rs/priority-fees.html for more details.
+        #[structopt(
+            short = "P",
+            long,
+            default_value = "none",
+            env = "METABOSS_PRIORITY_FEE"
+        )]
+        priority: Priority,
+    },
+    /// Mint compressed NFTs into a Bubblegum tree, creating the tree if needed
+    #[structopt(name = "cnft")]
+    Cnft {
+        /// Path to the keypair file that pays, owns the tree and signs as tree delegate
         #[structopt(short, long)]
-        account: String,
+        keypair: Option<String>,
+
+        /// Receiving address, if different from the keypair
+        #[structopt(short = "R", long)]
+        receiver: Option<String>,
+
+        /// JSON file with a list of receiving addresses
+        #[structopt(long)]
+        receiver_list: Option<PathBuf>,
+
+        /// JSON file with one NFT's on-chain formatted metadata, or a list of them
+        #[structopt(short = "d", long)]
+        nft_data: PathBuf,
+
+        /// Existing tree to mint into. A new tree is created if not set.
+        #[structopt(short, long)]
+        tree: Option<Pubkey>,
+
+        /// Max depth of a new tree: it holds 2^depth cNFTs
+        #[structopt(long, default_value = "14")]
+        max_depth: u32,
+
+        /// Max buffer size of a new tree: how many concurrent changes it allows
+        #[structopt(long, default_value = "64")]
+        max_buffer_size: u32,
+
+        /// Canopy depth of a new tree: larger canopies cost more rent but make
+        /// transfers and burns cheaper
+        #[structopt(long, default_value = "0")]
+        canopy_depth: u32,
+
+        /// Allow anyone to mint into a new tree
+        #[structopt(long)]
+        public: bool,
+
+        /// Collection mint to mint into and verify. The keypair must be its update authority.
+        #[structopt(short, long)]
+        collection: Option<Pubkey>,
+
+        /// Mint the cNFTs with immutable data fields
+        #[structopt(short, long)]
+        immutable: bool,
+
+        /// Mint the cNFTs with primary_sale_happened set to true
+        #[structopt(short, long)]
+        primary_sale_happened: bool,
+
+        /// Directory to write the minted asset ids to
+        #[structopt(short, long, default_value = ".")]
+        output: PathBuf,
 
         /// Priority of the transaction: higher priority costs more.
         /// See https://metaboss.rs/priority-fees.html for more details.
-        #[structopt(short = "P", long, default_value = "none")]
+        #[structopt(
+            short = "P",
+            long,
+            default_value = "none",
+            env = "METABOSS_PRIORITY_FEE"
+        )]
         priority: Priority,
     },
     #[structopt(name = "list")]
@@ -1097,7 +2033,12 @@ pub enum MintSubcommands {
 
         /// Priority of the transaction: higher priority costs more.
         /// See https://metaboss.rs/priority-fees.html for more details.
-        #[structopt(short = "P", long, default_value = "none")]
+        #[structopt(
+            short = "P",
+            long,
+            default_value = "none",
+            env = "METABOSS_PRIORITY_FEE"
+        )]
         priority: Priority,
     },
 }
@@ -1105,7 +2046,7 @@ pub enum MintSubcommands {
 #[derive(Debug, StructOpt)]
 pub enum SetSubcommands {
     /// Set primary sale happened to true, enabling secondary sale royalties.
-    #[structopt(name = "secondary-sale")]
+    #[structopt(name = "secondary-sale", alias = "primary-sale-happened")]
     PrimarySaleHappened {
         /// Path to the update authority's keypair file
         #[structopt(short, long)]
@@ -1117,11 +2058,16 @@ pub enum SetSubcommands {
 
         /// Priority of the transaction: higher priority costs more.
         /// See https://metaboss.rs/priority-fees.html for more details.
-        #[structopt(short = "P", long, default_value = "none")]
+        #[structopt(
+            short = "P",
+            long,
+            default_value = "none",
+            env = "METABOSS_PRIORITY_FEE"
+        )]
         priority: Priority,
     },
     /// Set primary sale happened to true for a list of mint addresses, enabling secondary sale royalties.
-    #[structopt(name = "secondary-sale-all")]
+    #[structopt(name = "secondary-sale-all", alias = "primary-sale-happened-all")]
     PrimarySaleHappenedAll {
         /// Path to the update authority's keypair file
         #[structopt(short, long)]
@@ -1145,7 +2091,12 @@ pub enum SetSubcommands {
 
         /// Priority of the transaction: higher priority costs more.
         /// See https://metaboss.rs/priority-fees.html for more details.
-        #[structopt(short = "P", long, default_value = "none")]
+        #[structopt(
+            short = "P",
+            long,
+            default_value = "none",
+            env = "METABOSS_PRIORITY_FEE"
+        )]
         priority: Priority,
     },
     /// Set update authority to a new account
@@ -1169,7 +2120,12 @@ pub enum SetSubcommands {
 
         /// Priority of the transaction: higher priority costs more.
         /// See https://metaboss.rs/priority-fees.html for more details.
-        #[structopt(short = "P", long, default_value = "none")]
+        #[structopt(
+            short = "P",
+            long,
+            default_value = "none",
+            env = "METABOSS_PRIORITY_FEE"
+        )]
         priority: Priority,
     },
     /// Set update authority on multiple accounts to a new account
@@ -1205,7 +2161,12 @@ pub enum SetSubcommands {
 
         /// Priority of the transaction: higher priority costs more.
         /// See https://metaboss.rs/priority-fees.html for more details.
-        #[structopt(short = "P", long, default_value = "none")]
+        #[structopt(
+            short = "P",
+            long,
+            default_value = "none",
+            env = "METABOSS_PRIORITY_FEE"
+        )]
         priority: Priority,
     },
     /// Set is-mutable to false, preventing any future updates to the NFT
@@ -1221,7 +2182,12 @@ pub enum SetSubcommands {
 
         /// Priority of the transaction: higher priority costs more.
         /// See https://metaboss.rs/priority-fees.html for more details.
-        #[structopt(short = "P", long, default_value = "none")]
+        #[structopt(
+            short = "P",
+            long,
+            default_value = "none",
+            env = "METABOSS_PRIORITY_FEE"
+        )]
         priority: Priority,
     },
     ImmutableAll {
@@ -1247,8 +2213,17 @@ pub enum SetSubcommands {
 
         /// Priority of the transaction: higher priority costs more.
         /// See https://metaboss.rs/priority-fees.html for more details.
-        #[structopt(short = "P", long, default_value = "none")]
+        #[structopt(
+            short = "P",
+            long,
+            default_value = "none",
+            env = "METABOSS_PRIORITY_FEE"
+        )]
         priority: Priority,
+
+        /// Print which mints are mutable, already immutable or have another update authority, without sending anything
+        #[structopt(long)]
+        dry_run: bool,
     },
     /// Set an asset to the correct Token Standard.
     TokenStandard {
@@ -1262,7 +2237,12 @@ pub enum SetSubcommands {
 
         /// Priority of the transaction: higher priority costs more.
         /// See https://metaboss.rs/priority-fees.html for more details.
-        #[structopt(short = "P", long, default_value = "none")]
+        #[structopt(
+            short = "P",
+            long,
+            default_value = "none",
+            env = "METABOSS_PRIORITY_FEE"
+        )]
         priority: Priority,
     },
     /// Set all assets in a list to be the correct Token Standard.
@@ -1289,7 +2269,12 @@ pub enum SetSubcommands {
 
         /// Priority of the transaction: higher priority costs more.
         /// See https://metaboss.rs/priority-fees.html for more details.
-        #[structopt(short = "P", long, default_value = "none")]
+        #[structopt(
+            short = "P",
+            long,
+            default_value = "none",
+            env = "METABOSS_PRIORITY_FEE"
+        )]
         priority: Priority,
     },
 }
@@ -1333,6 +2318,53 @@ pub enum SignSubcommands {
         /// Path to JSON file with list of mint accounts to sign
         #[structopt(short, long)]
         mint_accounts_file: Option<String>,
+
+        /// Pack as many signatures as fit into each transaction instead of one per mint
+        #[structopt(long)]
+        pack: bool,
+
+        /// Paths to several creators' keypair files, to sign with each in one pass
+        #[structopt(long, conflicts_with = "keypair")]
+        keypairs: Vec<String>,
+    },
+    /// Build unsigned verification transactions for creators who sign offline
+    #[structopt(name = "collect")]
+    Collect {
+        /// Path to the keypair file of the fee payer, which also owns the nonce accounts
+        #[structopt(short, long)]
+        keypair: Option<String>,
+
+        /// Path to JSON file with list of mint accounts
+        #[structopt(short = "L", long)]
+        mint_list: PathBuf,
+
+        /// File to write the pending transactions to
+        #[structopt(short, long, default_value = "pending.json")]
+        out: PathBuf,
+    },
+    /// Add a creator's signature to their transactions in a pending file
+    #[structopt(name = "pending")]
+    Pending {
+        /// Path to the creator's keypair file
+        #[structopt(short, long)]
+        keypair: Option<String>,
+
+        /// Pending file written by `sign collect`
+        file: PathBuf,
+
+        /// File to write the signed transactions to, defaults to updating the file in place
+        #[structopt(short, long)]
+        out: Option<PathBuf>,
+    },
+    /// Submit the signed transactions in a pending file
+    #[structopt(name = "apply")]
+    Apply {
+        /// Path to the keypair file of the fee payer used with `sign collect`
+        #[structopt(short, long)]
+        keypair: Option<String>,
+
+        /// Pending file with the creators' signatures
+        file: PathBuf,
     },
 }
 
@@ -1351,6 +2383,10 @@ pub enum SnapshotSubcommands {
         #[structopt(short, long, default_value = ".")]
         output: PathBuf,
 
+        /// Output format: json, json.zst, ndjson, ndjson.zst, csv or sqlite
+        #[structopt(short, long, default_value = "json")]
+        format: SnapshotFormat,
+
         /// Delay between DAS API requests in milliseconds; defaults to 500
         #[structopt(short = "D", long, default_value = "500")]
         delay: u64,
@@ -1373,6 +2409,10 @@ pub enum SnapshotSubcommands {
         #[structopt(short, long, default_value = ".")]
         output: PathBuf,
 
+        /// Output format: json, json.zst, ndjson, ndjson.zst, csv or sqlite
+        #[structopt(short, long, default_value = "json")]
+        format: SnapshotFormat,
+
         /// Delay between DAS API requests in milliseconds; defaults to 500
         #[structopt(short = "D", long, default_value = "500")]
         delay: u64,
@@ -1386,6 +2426,10 @@ pub enum SnapshotSubcommands {
         #[structopt(short, long, default_value = ".")]
         output: PathBuf,
 
+        /// Output format: json, json.zst, ndjson, ndjson.zst, csv or sqlite
+        #[structopt(short, long, default_value = "json")]
+        format: SnapshotFormat,
+
         /// Delay between DAS API requests in milliseconds; defaults to 500
         #[structopt(short = "D", long, default_value = "500")]
         delay: u64,
@@ -1399,6 +2443,48 @@ pub enum SnapshotSubcommands {
         #[structopt(short, long, default_value = ".")]
         output: PathBuf,
 
+        /// Output format: json, json.zst, ndjson, ndjson.zst, csv or sqlite
+        #[structopt(short, long, default_value = "json")]
+        format: SnapshotFormat,
+
+        /// Delay between DAS API requests in milliseconds; defaults to 500
+        #[structopt(short = "D", long, default_value = "500")]
+        delay: u64,
+    },
+    /// Get all holders of a fungible token with their balances
+    #[structopt(name = "fungible-holders")]
+    FungibleHolders {
+        /// Mint of the fungible token.
+        #[structopt(short, long)]
+        mint: Pubkey,
+
+        /// Only include owners holding at least this many tokens, in UI units
+        #[structopt(long, default_value = "0")]
+        min_amount: f64,
+
+        /// Path to directory to save output file
+        #[structopt(short, long, default_value = ".")]
+        output: PathBuf,
+
+        /// Output format: json, json.zst, ndjson, ndjson.zst, csv or sqlite
+        #[structopt(short, long, default_value = "json")]
+        format: SnapshotFormat,
+    },
+    /// Get all current holders of compressed NFTs in a collection
+    #[structopt(name = "cnft-holders")]
+    CnftHolders {
+        /// Collection parent mint address.
+        #[structopt(short, long)]
+        collection: Pubkey,
+
+        /// Path to directory to save output file
+        #[structopt(short, long, default_value = ".")]
+        output: PathBuf,
+
+        /// Output format: json, json.zst, ndjson, ndjson.zst, csv or sqlite
+        #[structopt(short, long, default_value = "json")]
+        format: SnapshotFormat,
+
         /// Delay between DAS API requests in milliseconds; defaults to 500
         #[structopt(short = "D", long, default_value = "500")]
         delay: u64,
@@ -1434,9 +2520,25 @@ pub enum SnapshotSubcommands {
         #[structopt(long)]
         allow_unverified: bool,
 
+        /// Program to query instead of Token Metadata, for forks with a similar account layout
+        #[structopt(long)]
+        program_id: Option<Pubkey>,
+
+        /// Byte offset to match the creator or update authority at, overriding the Token Metadata layout
+        #[structopt(long)]
+        offset: Option<usize>,
+
+        /// Only match accounts of exactly this many bytes
+        #[structopt(long)]
+        data_size: Option<u64>,
+
         /// Path to directory to save output files.
         #[structopt(short, long, default_value = ".")]
         output: String,
+
+        /// Output format: json, json.zst, ndjson, ndjson.zst, csv or sqlite
+        #[structopt(short, long, default_value = "json")]
+        format: SnapshotFormat,
     },
     /// Get all mint accounts using legacy getProgramAccounts call
     #[structopt(name = "mints-gpa")]
@@ -1465,14 +2567,30 @@ pub enum SnapshotSubcommands {
         #[structopt(long)]
         allow_unverified: bool,
 
+        /// Program to query instead of Token Metadata, for forks with a similar account layout
+        #[structopt(long)]
+        program_id: Option<Pubkey>,
+
+        /// Byte offset to match the creator or update authority at, overriding the Token Metadata layout
+        #[structopt(long)]
+        offset: Option<usize>,
+
+        /// Only match accounts of exactly this many bytes
+        #[structopt(long)]
+        data_size: Option<u64>,
+
         /// Path to directory to save output file
         #[structopt(short, long, default_value = ".")]
         output: String,
+
+        /// Output format: json, json.zst, ndjson, ndjson.zst, csv or sqlite
+        #[structopt(short, long, default_value = "json")]
+        format: SnapshotFormat,
     },
     /// Get all print edition mint accounts for a given master edition mint
     Prints {
         /// Master edition mint address.
-        #[structopt(short = "m", long)]
+        #[structopt(short = "m", long, alias = "master")]
         master_mint: String,
 
         /// First verified creator of editions, defaults to master edition FVC
@@ -1482,6 +2600,27 @@ pub enum SnapshotSubcommands {
         /// Path to directory to save output file
         #[structopt(short, long, default_value = ".")]
         output: String,
+
+        /// Output format: json, json.zst, ndjson, ndjson.zst, csv or sqlite
+        #[structopt(short, long, default_value = "json")]
+        format: SnapshotFormat,
+    },
+    /// Compare two holder snapshots: owner changes, new holders and exited holders
+    #[structopt(name = "diff")]
+    Diff {
+        /// Earlier holder snapshot file
+        old: PathBuf,
+
+        /// Later holder snapshot file
+        new: PathBuf,
+
+        /// Output format: json or csv
+        #[structopt(short, long, default_value = "json")]
+        format: DiffFormat,
+
+        /// Path to directory to save output files
+        #[structopt(short, long, default_value = ".")]
+        output: PathBuf,
     },
 }
 
@@ -1503,7 +2642,12 @@ pub enum UpdateSubcommands {
 
         /// Priority of the transaction: higher priority costs more.
         /// See https://metaboss.rs/priority-fees.html for more details.
-        #[structopt(short = "P", long, default_value = "none")]
+        #[structopt(
+            short = "P",
+            long,
+            default_value = "none",
+            env = "METABOSS_PRIORITY_FEE"
+        )]
         priority: Priority,
     },
     /// Update the rule set of a batch of pNFTs.
@@ -1521,7 +2665,7 @@ pub enum UpdateSubcommands {
         cache_file: Option<String>,
 
         /// New rule set pubkey
-        #[structopt(short, long)]
+        #[structopt(short, long, alias = "rule-set")]
         new_rule_set: String,
 
         /// Maximum number of requests per second
@@ -1534,7 +2678,12 @@ pub enum UpdateSubcommands {
 
         /// Priority of the transaction: higher priority costs more.
         /// See https://metaboss.rs/priority-fees.html for more details.
-        #[structopt(short = "P", long, default_value = "none")]
+        #[structopt(
+            short = "P",
+            long,
+            default_value = "none",
+            env = "METABOSS_PRIORITY_FEE"
+        )]
         priority: Priority,
     },
     /// Remove the rule set of a pNFT.
@@ -1549,7 +2698,12 @@ pub enum UpdateSubcommands {
 
         /// Priority of the transaction: higher priority costs more.
         /// See https://metaboss.rs/priority-fees.html for more details.
-        #[structopt(short = "P", long, default_value = "none")]
+        #[structopt(
+            short = "P",
+            long,
+            default_value = "none",
+            env = "METABOSS_PRIORITY_FEE"
+        )]
         priority: Priority,
     },
     /// Remove the rule set from a batch of pNFTs.
@@ -1576,7 +2730,12 @@ pub enum UpdateSubcommands {
 
         /// Priority of the transaction: higher priority costs more.
         /// See https://metaboss.rs/priority-fees.html for more details.
-        #[structopt(short = "P", long, default_value = "none")]
+        #[structopt(
+            short = "P",
+            long,
+            default_value = "none",
+            env = "METABOSS_PRIORITY_FEE"
+        )]
         priority: Priority,
     },
     /// Update the seller fee basis points field inside the data struct on an NFT
@@ -1596,7 +2755,12 @@ pub enum UpdateSubcommands {
 
         /// Priority of the transaction: higher priority costs more.
         /// See https://metaboss.rs/priority-fees.html for more details.
-        #[structopt(short = "P", long, default_value = "none")]
+        #[structopt(
+            short = "P",
+            long,
+            default_value = "none",
+            env = "METABOSS_PRIORITY_FEE"
+        )]
         priority: Priority,
     },
     /// Update the seller fee basis points field inside the data struct on an NFT
@@ -1628,7 +2792,12 @@ pub enum UpdateSubcommands {
 
         /// Priority of the transaction: higher priority costs more.
         /// See https://metaboss.rs/priority-fees.html for more details.
-        #[structopt(short = "P", long, default_value = "none")]
+        #[structopt(
+            short = "P",
+            long,
+            default_value = "none",
+            env = "METABOSS_PRIORITY_FEE"
+        )]
         priority: Priority,
     },
     /// Update the name field inside the data struct on an NFT
@@ -1648,7 +2817,12 @@ pub enum UpdateSubcommands {
 
         /// Priority of the transaction: higher priority costs more.
         /// See https://metaboss.rs/priority-fees.html for more details.
-        #[structopt(short = "P", long, default_value = "none")]
+        #[structopt(
+            short = "P",
+            long,
+            default_value = "none",
+            env = "METABOSS_PRIORITY_FEE"
+        )]
         priority: Priority,
     },
     /// Update the symbol field inside the data struct on an NFT
@@ -1668,7 +2842,12 @@ pub enum UpdateSubcommands {
 
         /// Priority of the transaction: higher priority costs more.
         /// See https://metaboss.rs/priority-fees.html for more details.
-        #[structopt(short = "P", long, default_value = "none")]
+        #[structopt(
+            short = "P",
+            long,
+            default_value = "none",
+            env = "METABOSS_PRIORITY_FEE"
+        )]
         priority: Priority,
     },
     /// Update all symbols for a list of mint addresses.
@@ -1699,7 +2878,12 @@ pub enum UpdateSubcommands {
 
         /// Priority of the transaction: higher priority costs more
         /// See https://metaboss.rs/priority-fees.html for more details.
-        #[structopt(short = "P", long, default_value = "none")]
+        #[structopt(
+            short = "P",
+            long,
+            default_value = "none",
+            env = "METABOSS_PRIORITY_FEE"
+        )]
         priority: Priority,
     },
     /// Update the creators field by position inside the data struct on an NFT
@@ -1723,7 +2907,12 @@ pub enum UpdateSubcommands {
 
         /// Priority of the transaction: higher priority costs more.
         /// See https://metaboss.rs/priority-fees.html for more details.
-        #[structopt(short = "P", long, default_value = "none")]
+        #[structopt(
+            short = "P",
+            long,
+            default_value = "none",
+            env = "METABOSS_PRIORITY_FEE"
+        )]
         priority: Priority,
     },
     /// Update all the creators fields for a list of mint addresses.
@@ -1749,6 +2938,10 @@ pub enum UpdateSubcommands {
         #[structopt(short = "A", long = "append")]
         append: bool,
 
+        /// Update even if the new creators remove a verified creator
+        #[structopt(long)]
+        force: bool,
+
         /// Maximum number of requests per second
         #[structopt(short = "R", long, default_value = DEFAULT_RATE_LIMIT)]
         rate_limit: usize,
@@ -1759,7 +2952,12 @@ pub enum UpdateSubcommands {
 
         /// Priority of the transaction: higher priority costs more.
         /// See https://metaboss.rs/priority-fees.html for more details.
-        #[structopt(short = "P", long, default_value = "none")]
+        #[structopt(
+            short = "P",
+            long,
+            default_value = "none",
+            env = "METABOSS_PRIORITY_FEE"
+        )]
         priority: Priority,
     },
     /// Update the data struct on a NFT
@@ -1779,7 +2977,12 @@ pub enum UpdateSubcommands {
 
         /// Priority of the transaction: higher priority costs more.
         /// See https://metaboss.rs/priority-fees.html for more details.
-        #[structopt(short = "P", long, default_value = "none")]
+        #[structopt(
+            short = "P",
+            long,
+            default_value = "none",
+            env = "METABOSS_PRIORITY_FEE"
+        )]
         priority: Priority,
     },
     /// Update the data struct on a list of NFTs
@@ -1807,7 +3010,12 @@ pub enum UpdateSubcommands {
 
         /// Priority of the transaction: higher priority costs more.
         /// See https://metaboss.rs/priority-fees.html for more details.
-        #[structopt(short = "P", long, default_value = "none")]
+        #[structopt(
+            short = "P",
+            long,
+            default_value = "none",
+            env = "METABOSS_PRIORITY_FEE"
+        )]
         priority: Priority,
     },
     /// Update the metadata URI, keeping the rest of the data the same
@@ -1827,7 +3035,12 @@ pub enum UpdateSubcommands {
 
         /// Priority of the transaction: higher priority costs more.
         /// See https://metaboss.rs/priority-fees.html for more details.
-        #[structopt(short = "P", long, default_value = "none")]
+        #[structopt(
+            short = "P",
+            long,
+            default_value = "none",
+            env = "METABOSS_PRIORITY_FEE"
+        )]
         priority: Priority,
     },
     /// Update the metadata URI on a list of mint accounts
@@ -1843,7 +3056,23 @@ pub enum UpdateSubcommands {
 
         /// JSON file with list of mint accounts and new URIs
         #[structopt(short = "u", long)]
-        new_uris_file: String,
+        new_uris_file: Option<String>,
+
+        /// Mint list whose current URIs --pattern rewrites
+        #[structopt(short = "L", long)]
+        mint_list: Option<String>,
+
+        /// Text to replace in each current URI, instead of a new URIs file
+        #[structopt(long)]
+        pattern: Option<String>,
+
+        /// Replacement for --pattern; with --regex it can refer to groups as $1
+        #[structopt(long)]
+        replace: Option<String>,
+
+        /// Treat --pattern as a regular expression
+        #[structopt(long)]
+        regex: bool,
 
         /// Maximum number of requests per second
         #[structopt(short = "R", long, default_value = DEFAULT_RATE_LIMIT)]
@@ -1855,8 +3084,17 @@ pub enum UpdateSubcommands {
 
         /// Priority of the transaction: higher priority costs more.
         /// See https://metaboss.rs/priority-fees.html for more details.
-        #[structopt(short = "P", long, default_value = "none")]
+        #[structopt(
+            short = "P",
+            long,
+            default_value = "none",
+            env = "METABOSS_PRIORITY_FEE"
+        )]
         priority: Priority,
+
+        /// Pack as many updates as fit into each transaction instead of one per mint
+        #[structopt(long)]
+        pack: bool,
     },
     /// Update the Uses data on a NFT
     #[structopt(name = "uses")]
@@ -1887,7 +3125,12 @@ pub enum UpdateSubcommands {
 
         /// Priority of the transaction: higher priority costs more.
         /// See https://metaboss.rs/priority-fees.html for more details.
-        #[structopt(short = "P", long, default_value = "none")]
+        #[structopt(
+            short = "P",
+            long,
+            default_value = "none",
+            env = "METABOSS_PRIORITY_FEE"
+        )]
         priority: Priority,
     },
 }
@@ -1915,6 +3158,56 @@ pub enum TransferSubcommands {
         #[structopt(long)]
         receiver_account: Option<String>,
     },
+    /// Transfer a compressed NFT.
+    #[structopt(name = "cnft")]
+    Cnft {
+        /// Path to the owner or delegate keypair file
+        #[structopt(short, long)]
+        keypair: Option<String>,
+
+        /// Receiving address
+        #[structopt(short = "R", long)]
+        receiver: Pubkey,
+
+        /// Asset id of the compressed NFT
+        #[structopt(short, long)]
+        asset_id: Pubkey,
+
+        /// DAS API endpoint to fetch the asset proof from, defaults to the RPC URL
+        #[structopt(long)]
+        das_url: Option<String>,
+
+        /// Priority of the transaction: higher priority costs more.
+        /// See https://metaboss.rs/priority-fees.html for more details.
+        #[structopt(
+            short = "P",
+            long,
+            default_value = "none",
+            env = "METABOSS_PRIORITY_FEE"
+        )]
+        priority: Priority,
+    },
+    /// Transfer tokens to many recipients from a CSV of mint,recipient[,amount] rows.
+    #[structopt(name = "batch")]
+    Batch {
+        /// Path to the owner keypair file, which also pays for new token accounts
+        #[structopt(short, long)]
+        keypair: Option<String>,
+
+        /// CSV file with mint, recipient and optional amount columns
+        #[structopt(short, long)]
+        file: PathBuf,
+
+        /// Priority of the transaction: higher priority costs more.
+        /// See https://metaboss.rs/priority-fees.html for more details.
+        #[structopt(
+            short = "P",
+            long,
+            default_value = "none",
+            env = "METABOSS_PRIORITY_FEE"
+        )]
+        priority: Priority,
+    },
 }
 
 #[derive(Debug, StructOpt)]
@@ -1989,4 +3282,28 @@ pub enum UnverifySubcommands {
 pub enum ParseErrorsSubCommands {
     #[structopt(name = "file")]
     File,
+    /// Resolve the error of a failed transaction
+    #[structopt(name = "tx")]
+    Tx {
+        /// Signature of the failed transaction
+        signature: String,
+    },
+    /// Cache a program's error map from its on-chain Anchor IDL
+    #[structopt(name = "update")]
+    Update {
+        /// Program id to fetch the IDL of
+        #[structopt(short, long)]
+        program: String,
+
+        /// Name to report the program's errors under, defaults to the IDL's name
+        #[structopt(short, long)]
+        name: Option<String>,
+    },
+    /// Decode every custom program error in a simulation or transaction log dump
+    #[structopt(name = "logs")]
+    Logs {
+        /// File with the logs, read from stdin if not given
+        #[structopt(long = "log-file", alias = "file")]
+        file: Option<PathBuf>,
+    },
 }
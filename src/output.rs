@@ -0,0 +1,289 @@
+//! Machine-readable output from the global `--output-format json` and `--quiet` options,
+//! and the exit code a command ends with.
+//!
+//! In JSON mode the free-form text commands print is moved to stderr, and a single JSON
+//! document with the transactions sent and the batch items processed is written to stdout
+//! when the command finishes, so scripts don't have to scrape the text. In quiet mode the
+//! text is dropped instead.
+
+use std::{
+    collections::HashMap,
+    fmt::{self, Display},
+    fs::File,
+    io::{self, Write},
+    str::FromStr,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Mutex,
+    },
+};
+
+use anyhow::{anyhow, Result};
+use once_cell::sync::Lazy;
+use serde::Serialize;
+use serde_json::{json, Value};
+use solana_client::client_error::{ClientError, ClientErrorKind};
+use solana_sdk::{instruction::Instruction, pubkey::Pubkey, signature::Signature};
+
+use crate::{
+    checkpoint::{CheckpointEntry, ItemStatus},
+    fees::label_operation,
+};
+
+pub const EXIT_SUCCESS: i32 = 0;
+/// Any error that isn't one of the ones below, such as invalid input.
+pub const EXIT_FAILURE: i32 = 1;
+/// The command finished but one or more batch items failed.
+pub const EXIT_PARTIAL_FAILURE: i32 = 2;
+pub const EXIT_RPC_ERROR: i32 = 3;
+/// A transaction failed on-chain or in simulation.
+pub const EXIT_PROGRAM_ERROR: i32 = 4;
+
+// Errors that lost their type on the way up are classified by their message.
+const PROGRAM_ERROR_MARKERS: &[&str] = &[
+    "custom program error",
+    "error processing instruction",
+    "instructionerror",
+    "transaction simulation failed",
+];
+const RPC_ERROR_MARKERS: &[&str] = &[
+    "rpc response error",
+    "rpc request error",
+    "error sending request",
+    "error trying to connect",
+    "operation timed out",
+    "too many requests",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputFormat {
+    Text,
+    Json,
+}
+
+impl FromStr for OutputFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "text" => Ok(OutputFormat::Text),
+            "json" => Ok(OutputFormat::Json),
+            _ => Err(format!("Invalid output format: {s}, expected text or json")),
+        }
+    }
+}
+
+impl Display for OutputFormat {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            OutputFormat::Text => write!(f, "text"),
+            OutputFormat::Json => write!(f, "json"),
+        }
+    }
+}
+
+/// A confirmed transaction and the accounts it wrote to.
+#[derive(Debug, Serialize)]
+struct TransactionRecord {
+    signature: String,
+    operation: String,
+    accounts: Vec<String>,
+}
+
+#[derive(Debug, Default)]
+struct Report {
+    transactions: Vec<TransactionRecord>,
+    items: Vec<Value>,
+}
+
+// The real stdout in JSON mode, after the process's stdout was pointed at stderr.
+static JSON_STDOUT: Lazy<Mutex<Option<File>>> = Lazy::new(|| Mutex::new(None));
+static REPORT: Lazy<Mutex<Report>> = Lazy::new(|| Mutex::new(Report::default()));
+static QUIET: AtomicBool = AtomicBool::new(false);
+// Latest status of each batch item, keyed by command and item, for the exit code.
+static ITEM_STATUS: Lazy<Mutex<HashMap<(String, String), ItemStatus>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// Set the output format and quiet mode for the rest of the run.
+pub fn init(format: OutputFormat, quiet: bool) -> Result<()> {
+    QUIET.store(quiet, Ordering::Relaxed);
+    if format == OutputFormat::Json || quiet {
+        let stdout = redirect_stdout(quiet)?;
+        if format == OutputFormat::Json {
+            *JSON_STDOUT.lock().unwrap() = Some(stdout);
+        }
+    }
+    Ok(())
+}
+
+pub fn json_output() -> bool {
+    JSON_STDOUT.lock().unwrap().is_some()
+}
+
+pub fn quiet() -> bool {
+    QUIET.load(Ordering::Relaxed)
+}
+
+/// Point the process's stdout at stderr, or at `/dev/null` when quiet, and return the
+/// original stdout.
+#[cfg(unix)]
+fn redirect_stdout(quiet: bool) -> Result<File> {
+    use std::{
+        fs::OpenOptions,
+        os::unix::io::{AsRawFd, FromRawFd},
+    };
+
+    io::stdout().flush()?;
+    let null = if quiet {
+        Some(OpenOptions::new().write(true).open("/dev/null")?)
+    } else {
+        None
+    };
+    let target = null
+        .as_ref()
+        .map_or(libc::STDERR_FILENO, |null| null.as_raw_fd());
+
+    // SAFETY: the duplicated descriptor is owned by the returned file and nothing else.
+    unsafe {
+        let stdout = libc::dup(libc::STDOUT_FILENO);
+        if stdout < 0 || libc::dup2(target, libc::STDOUT_FILENO) < 0 {
+            return Err(io::Error::last_os_error().into());
+        }
+        Ok(File::from_raw_fd(stdout))
+    }
+}
+
+#[cfg(not(unix))]
+fn redirect_stdout(_quiet: bool) -> Result<File> {
+    Err(anyhow!(
+        "--output-format json and --quiet are only supported on Unix"
+    ))
+}
+
+/// Record a confirmed transaction for the JSON output.
+pub fn record_transaction(signature: &Signature, instructions: &[Instruction]) {
+    if !json_output() {
+        return;
+    }
+
+    let operation = label_operation(
+        instructions
+            .iter()
+            .map(|ix| (&ix.program_id, ix.data.as_slice())),
+    );
+    let mut accounts: Vec<Pubkey> = vec![];
+    for meta in instructions.iter().flat_map(|ix| &ix.accounts) {
+        if meta.is_writable && !accounts.contains(&meta.pubkey) {
+            accounts.push(meta.pubkey);
+        }
+    }
+    record_signature(signature, &operation, &accounts);
+}
+
+/// Record a confirmed transaction whose instructions aren't available, such as one built and
+/// sent by `metaboss_lib`, with its operation label and the accounts it wrote to.
+pub fn record_signature(signature: &Signature, operation: &str, accounts: &[Pubkey]) {
+    if !json_output() {
+        return;
+    }
+
+    REPORT.lock().unwrap().transactions.push(TransactionRecord {
+        signature: signature.to_string(),
+        operation: operation.to_string(),
+        accounts: accounts.iter().map(|account| account.to_string()).collect(),
+    });
+}
+
+/// Record a finished batch item for the exit code and the JSON output, whether or not
+/// `--resume` is set.
+pub fn record_item(entry: &CheckpointEntry) {
+    ITEM_STATUS
+        .lock()
+        .unwrap()
+        .insert((entry.command.clone(), entry.item.clone()), entry.status);
+
+    if !json_output() {
+        return;
+    }
+    if let Ok(item) = serde_json::to_value(entry) {
+        REPORT.lock().unwrap().items.push(item);
+    }
+}
+
+/// Write the JSON document for the command to stdout, if in JSON mode.
+pub fn finish(command: &str, result: &Result<()>) {
+    let mut stdout = JSON_STDOUT.lock().unwrap();
+    let Some(stdout) = stdout.as_mut() else {
+        return;
+    };
+
+    let report = REPORT.lock().unwrap();
+    let document = json!({
+        "command": command,
+        "success": result.is_ok(),
+        "error": result.as_ref().err().map(|e| e.to_string()),
+        "transactions": report.transactions,
+        "items": report.items,
+    });
+    let written = serde_json::to_writer(&mut *stdout, &document)
+        .map_err(|e| anyhow!(e))
+        .and_then(|_| Ok(writeln!(stdout)?));
+    if let Err(e) = written {
+        eprintln!("Failed to write JSON output: {e}");
+    }
+}
+
+fn failed_items() -> usize {
+    ITEM_STATUS
+        .lock()
+        .unwrap()
+        .values()
+        .filter(|status| **status == ItemStatus::Failed)
+        .count()
+}
+
+// Transaction errors include failed preflight simulations.
+fn client_error_code(kind: &ClientErrorKind) -> i32 {
+    if kind.get_transaction_error().is_some() {
+        return EXIT_PROGRAM_ERROR;
+    }
+    match kind {
+        ClientErrorKind::SigningError(_) | ClientErrorKind::Custom(_) => EXIT_FAILURE,
+        _ => EXIT_RPC_ERROR,
+    }
+}
+
+fn error_code(error: &anyhow::Error) -> i32 {
+    if let Some(e) = error.chain().find_map(|e| e.downcast_ref::<ClientError>()) {
+        return client_error_code(e.kind());
+    }
+
+    let message = format!("{error:#}").to_lowercase();
+    if PROGRAM_ERROR_MARKERS.iter().any(|m| message.contains(m)) {
+        EXIT_PROGRAM_ERROR
+    } else if RPC_ERROR_MARKERS.iter().any(|m| message.contains(m)) {
+        EXIT_RPC_ERROR
+    } else {
+        EXIT_FAILURE
+    }
+}
+
+/// The exit code for how the command ended. A command that finished is a partial failure
+/// when any batch item's latest outcome is a failure.
+pub fn exit_code(result: &Result<()>) -> i32 {
+    match result {
+        Ok(()) if failed_items() > 0 => EXIT_PARTIAL_FAILURE,
+        Ok(()) => EXIT_SUCCESS,
+        Err(e) => error_code(e),
+    }
+}
+
+/// Print the command's error, if any, and exit with its exit code.
+pub fn exit(result: Result<()>) -> ! {
+    let code = exit_code(&result);
+    if let Err(e) = result {
+        eprintln!("Error: {e:?}");
+    }
+    let _ = io::stdout().flush();
+    std::process::exit(code)
+}
@@ -0,0 +1,242 @@
+//! Packs the instructions for many items into as few transactions as will fit,
+//! instead of sending one transaction per item.
+
+use std::{mem, str::FromStr};
+
+use anyhow::{anyhow, Error, Result};
+use metaboss_lib::data::Priority;
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::{
+    address_lookup_table::AddressLookupTableAccount,
+    instruction::Instruction,
+    packet::PACKET_DATA_SIZE,
+    pubkey::Pubkey,
+    signature::{Keypair, Signature},
+    signer::Signer,
+    system_instruction::advance_nonce_account,
+};
+
+use crate::constants::LOOKUP_TABLE;
+use crate::limiter::FeeGuard;
+use crate::nonce::{active_nonce, NonceAuthority};
+use crate::pipeline::{fetch_lookup_table, transaction_size, Pipeline};
+use crate::spinner::create_progress_bar;
+
+/// The most compute units a single transaction can request.
+pub const MAX_TX_COMPUTE_UNITS: u32 = 1_400_000;
+
+/// The instructions for a single item, which are always kept in the same transaction.
+pub struct InstructionGroup<K> {
+    pub key: K,
+    pub instructions: Vec<Instruction>,
+    /// Estimate of the compute units the instructions use.
+    pub compute_units: u32,
+}
+
+/// A transaction's worth of instruction groups.
+pub struct PackedTransaction<K> {
+    pub keys: Vec<K>,
+    pub instructions: Vec<Instruction>,
+    pub compute_units: u32,
+}
+
+impl<K> Default for PackedTransaction<K> {
+    fn default() -> Self {
+        Self {
+            keys: vec![],
+            instructions: vec![],
+            compute_units: 0,
+        }
+    }
+}
+
+/// The lookup table from the global `--lut` option, fetched so packing can size
+/// transactions the same way the pipeline will build them.
+pub fn active_lookup_tables(client: &RpcClient) -> Result<Vec<AddressLookupTableAccount>> {
+    match LOOKUP_TABLE.read().unwrap().as_ref() {
+        Some(address) => {
+            let key = Pubkey::from_str(address)
+                .map_err(|_| anyhow!("Invalid lookup table address: {address}"))?;
+            Ok(vec![fetch_lookup_table(client, &key)?])
+        }
+        None => Ok(vec![]),
+    }
+}
+
+/// Greedily packs the groups, in order, into transactions that stay under both the
+/// packet size and the compute unit limit. A group too large to share a transaction
+/// is packed on its own. Transactions are sized with the lookup table and nonce the
+/// pipeline will add.
+pub fn pack_instruction_groups<K>(
+    client: &RpcClient,
+    payer: &Pubkey,
+    groups: Vec<InstructionGroup<K>>,
+) -> Result<Vec<PackedTransaction<K>>> {
+    let lookup_tables = active_lookup_tables(client)?;
+
+    // Leave room for the advance nonce instruction the pipeline prepends.
+    let reserved = match active_nonce()? {
+        Some(nonce) => {
+            let authority = match nonce.authority {
+                NonceAuthority::Payer => *payer,
+                NonceAuthority::Keypair(keypair) => keypair.pubkey(),
+                NonceAuthority::Pubkey(pubkey) => pubkey,
+            };
+            vec![advance_nonce_account(&nonce.account, &authority)]
+        }
+        None => vec![],
+    };
+
+    pack_groups(payer, groups, &reserved, &lookup_tables)
+}
+
+/// Packs the groups like `pack_instruction_groups`, for transactions that are sent
+/// outside the pipeline. `reserved` are the instructions each transaction carries
+/// besides the groups, and `lookup_tables` the tables it's compiled with.
+pub fn pack_groups<K>(
+    payer: &Pubkey,
+    groups: Vec<InstructionGroup<K>>,
+    reserved: &[Instruction],
+    lookup_tables: &[AddressLookupTableAccount],
+) -> Result<Vec<PackedTransaction<K>>> {
+    let mut packed = vec![];
+    let mut current = PackedTransaction::default();
+
+    for group in groups {
+        if !current.keys.is_empty() {
+            let mut candidate = reserved.to_vec();
+            candidate.extend(current.instructions.iter().cloned());
+            candidate.extend(group.instructions.iter().cloned());
+
+            let fits = current.compute_units + group.compute_units <= MAX_TX_COMPUTE_UNITS
+                && transaction_size(payer, &candidate, lookup_tables)? <= PACKET_DATA_SIZE;
+            if !fits {
+                packed.push(mem::take(&mut current));
+            }
+        }
+        current.keys.push(group.key);
+        current.instructions.extend(group.instructions);
+        current.compute_units += group.compute_units;
+    }
+    if !current.keys.is_empty() {
+        packed.push(current);
+    }
+
+    Ok(packed)
+}
+
+/// Send each packed transaction in turn. A transaction lands or fails as a whole, so
+/// the keys of every item in it are returned with its signature or its error.
+#[allow(clippy::type_complexity)]
+pub fn send_packed<K>(
+    client: &RpcClient,
+    signers: &[&Keypair],
+    priority: &Priority,
+    packed: Vec<PackedTransaction<K>>,
+) -> Result<(Vec<(Vec<K>, Signature)>, Vec<(Vec<K>, Error)>)> {
+    let mut signatures = vec![];
+    let mut failed = vec![];
+
+    let mut fee_guard = FeeGuard::new();
+    let pb = create_progress_bar("Sending packed transactions...", packed.len() as u64);
+    for tx in packed {
+        fee_guard.wait(client);
+        let result = Pipeline::with_priority(priority.clone())
+            .with_active_lookup_table()?
+            .with_active_nonce()?
+            .run(client, signers, tx.instructions);
+
+        match result {
+            Ok(signature) => signatures.push((tx.keys, signature)),
+            Err(e) => failed.push((tx.keys, e)),
+        }
+        pb.inc(1);
+    }
+    pb.finish_and_clear();
+
+    Ok((signatures, failed))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn group(key: usize, data_len: usize, compute_units: u32) -> InstructionGroup<usize> {
+        InstructionGroup {
+            key,
+            instructions: vec![Instruction::new_with_bytes(
+                Pubkey::new_unique(),
+                &vec![0; data_len],
+                vec![],
+            )],
+            compute_units,
+        }
+    }
+
+    fn keys(packed: &[PackedTransaction<usize>]) -> Vec<Vec<usize>> {
+        packed.iter().map(|tx| tx.keys.clone()).collect()
+    }
+
+    #[test]
+    fn packs_groups_in_order_under_the_packet_size() {
+        let payer = Pubkey::new_unique();
+        let groups = (0..7).map(|i| group(i, 300, 1_000)).collect();
+
+        let packed = pack_groups(&payer, groups, &[], &[]).unwrap();
+
+        assert_eq!(keys(&packed), vec![vec![0, 1, 2], vec![3, 4, 5], vec![6]]);
+        for tx in &packed {
+            assert!(transaction_size(&payer, &tx.instructions, &[]).unwrap() <= PACKET_DATA_SIZE);
+        }
+    }
+
+    #[test]
+    fn packs_groups_under_the_compute_unit_limit() {
+        let payer = Pubkey::new_unique();
+        let groups = (0..5).map(|i| group(i, 10, 600_000)).collect();
+
+        let packed = pack_groups(&payer, groups, &[], &[]).unwrap();
+
+        assert_eq!(keys(&packed), vec![vec![0, 1], vec![2, 3], vec![4]]);
+        assert!(packed
+            .iter()
+            .all(|tx| tx.compute_units <= MAX_TX_COMPUTE_UNITS));
+    }
+
+    #[test]
+    fn packs_an_oversized_group_on_its_own() {
+        let payer = Pubkey::new_unique();
+        let groups = vec![
+            group(0, 10, 1_000),
+            group(1, 10, MAX_TX_COMPUTE_UNITS + 1),
+            group(2, 10, 1_000),
+        ];
+
+        let packed = pack_groups(&payer, groups, &[], &[]).unwrap();
+
+        assert_eq!(keys(&packed), vec![vec![0], vec![1], vec![2]]);
+    }
+
+    #[test]
+    fn leaves_room_for_the_reserved_instructions() {
+        let payer = Pubkey::new_unique();
+        let reserved = vec![advance_nonce_account(&Pubkey::new_unique(), &payer)];
+        let without =
+            pack_groups(&payer, (0..12).map(|i| group(i, 95, 0)).collect(), &[], &[]).unwrap();
+        let with = pack_groups(
+            &payer,
+            (0..12).map(|i| group(i, 95, 0)).collect(),
+            &reserved,
+            &[],
+        )
+        .unwrap();
+
+        assert!(with[0].keys.len() < without[0].keys.len());
+    }
+
+    #[test]
+    fn packs_nothing_for_no_groups() {
+        let packed = pack_groups::<usize>(&Pubkey::new_unique(), vec![], &[], &[]).unwrap();
+        assert!(packed.is_empty());
+    }
+}
@@ -1,16 +1,38 @@
 use anyhow::{anyhow, Context, Result};
+use bip39::{Language, Mnemonic};
+use indexmap::IndexMap;
 use mpl_token_metadata::types::Creator;
+use once_cell::sync::Lazy;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
+use solana_client::{rpc_client::RpcClient, rpc_config::RpcTransactionConfig};
+use solana_sdk::derivation_path::DerivationPath;
 use solana_sdk::pubkey::Pubkey;
-use solana_sdk::signer::keypair::Keypair;
+use solana_sdk::signature::Signature;
+use solana_sdk::signer::keypair::{
+    generate_seed_from_seed_phrase_and_passphrase, keypair_from_seed_and_derivation_path, Keypair,
+};
+use solana_sdk::{instruction::InstructionError, transaction::TransactionError};
+use solana_transaction_status::UiTransactionEncoding;
 use std::fs::read_to_string;
+use std::io::Read;
 use std::path::PathBuf;
-use std::{env, fs, path::Path, str::FromStr};
+use std::{env, fs, path::Path, str::FromStr, sync::RwLock};
 
-use crate::constants::ERROR_FILE_BEGIN;
+use crate::constants::{ERROR_FILE_BEGIN, OFFLINE_AUTHORITY};
+use crate::idl_errors::cached_error_maps;
+use crate::offline::{export_dir, placeholder_keypair};
+use crate::profile::profile_keypair;
 use crate::utils::{convert_to_wtf_error, find_errors};
 
+const KEYPAIR_ENV: &str = "METABOSS_KEYPAIR";
+
+// The path Phantom and Solflare derive the first account at.
+const DEFAULT_DERIVATION_PATH: &str = "m/44'/501'/0'/0'";
+
+// Keypair from the global `--keypair-seed`, used when a command isn't given one.
+static SEED_KEYPAIR: Lazy<RwLock<Option<[u8; 64]>>> = Lazy::new(|| RwLock::new(None));
+
 #[derive(Debug, Deserialize, Serialize)]
 pub struct SolanaConfig {
     pub json_rpc_url: String,
@@ -54,11 +76,31 @@ pub fn parse_solana_config() -> Option<SolanaConfig> {
     serde_yaml::from_reader(&conf_file).ok()
 }
 
+/// The keypair path used when a command isn't given one: `METABOSS_KEYPAIR`, then the
+/// `--profile`'s keypair. The Solana config comes after these.
+pub fn keypair_override() -> Option<String> {
+    env::var(KEYPAIR_ENV)
+        .ok()
+        .filter(|path| !path.is_empty())
+        .or_else(profile_keypair)
+}
+
 pub fn parse_keypair(
     keypair_opt: Option<String>,
     sol_config_option: Option<SolanaConfig>,
 ) -> Keypair {
-    let keypair = match keypair_opt {
+    // The real keypair isn't needed to build transactions for offline signing.
+    if export_dir().is_some() && OFFLINE_AUTHORITY.read().unwrap().is_some() {
+        return placeholder_keypair();
+    }
+
+    if keypair_opt.is_none() {
+        if let Some(bytes) = *SEED_KEYPAIR.read().unwrap() {
+            return Keypair::from_bytes(&bytes).expect("valid seed keypair");
+        }
+    }
+
+    let keypair = match keypair_opt.or_else(keypair_override) {
         Some(keypair_path) => read_keypair(&keypair_path).expect("Failed to read keypair file."),
         None => match sol_config_option {
             Some(ref sol_config) => {
@@ -71,13 +113,23 @@ pub fn parse_keypair(
     keypair
 }
 
+/// Read a keypair from a file, or from the value itself when it isn't a file: a base58
+/// private key as Phantom exports it, or a JSON byte array as `solana-keygen` writes it.
 pub fn read_keypair(path: &String) -> Result<Keypair> {
-    let secret_string: String = fs::read_to_string(path).context("Can't find key file")?;
+    let secret_string: String = match fs::read_to_string(path) {
+        Ok(contents) => contents,
+        Err(e) => {
+            return keypair_from_secret(path).map_err(|_| anyhow!(e).context("Can't find key file"))
+        }
+    };
+    keypair_from_secret(&secret_string)
+}
 
+fn keypair_from_secret(secret: &str) -> Result<Keypair> {
     // Try to decode the secret string as a JSON array of ints first and then as a base58 encoded string to support Phantom private keys.
-    let secret_bytes: Vec<u8> = match serde_json::from_str(&secret_string) {
+    let secret_bytes: Vec<u8> = match serde_json::from_str(secret) {
         Ok(bytes) => bytes,
-        Err(_) => match bs58::decode(&secret_string.trim()).into_vec() {
+        Err(_) => match bs58::decode(secret.trim()).into_vec() {
             Ok(bytes) => bytes,
             Err(_) => return Err(anyhow!("Unsupported key type!")),
         },
@@ -87,6 +139,29 @@ pub fn read_keypair(path: &String) -> Result<Keypair> {
     Ok(keypair)
 }
 
+/// Derive a keypair from a BIP39 seed phrase, at `m/44'/501'/0'/0'` unless another
+/// derivation path is given.
+pub fn keypair_from_seed_phrase(phrase: &str, derivation: Option<&str>) -> Result<Keypair> {
+    let phrase = phrase.split_whitespace().collect::<Vec<_>>().join(" ");
+    // Checks the word count, that every word is in the English list and the checksum.
+    Mnemonic::from_phrase(&phrase, Language::English)
+        .map_err(|e| anyhow!("Invalid seed phrase: {e}"))?;
+
+    let path = derivation.unwrap_or(DEFAULT_DERIVATION_PATH);
+    let derivation_path = DerivationPath::from_absolute_path_str(path)
+        .map_err(|e| anyhow!("Invalid derivation path {path}: {e}"))?;
+    let seed = generate_seed_from_seed_phrase_and_passphrase(&phrase, "");
+    keypair_from_seed_and_derivation_path(&seed, Some(derivation_path))
+        .map_err(|e| anyhow!("Failed to derive a keypair from the seed phrase: {e}"))
+}
+
+/// Use the keypair derived from a seed phrase for commands that aren't given a keypair.
+pub fn set_seed_keypair(phrase: &str, derivation: Option<&str>) -> Result<()> {
+    let keypair = keypair_from_seed_phrase(phrase, derivation)?;
+    *SEED_KEYPAIR.write().unwrap() = Some(keypair.to_bytes());
+    Ok(())
+}
+
 pub fn parse_creators(creators_json: &Value) -> Result<Vec<Creator>> {
     let mut creators = Vec::new();
 
@@ -225,6 +300,236 @@ pub fn parse_errors_code(error_code: &str) -> Result<()> {
     Ok(())
 }
 
+/// Programs with errors in the error maps, by the domain `find_errors` reports them under.
+const ERROR_PROGRAMS: [(&str, &str); 12] = [
+    (
+        "metaqbxxUerdq28cj1RbAWkYQm3ybzjb6a8bt518x1s",
+        "Token Metadata",
+    ),
+    (
+        "hausS13jsjafwWwGqZTUQRmWyvyxn9EQpqMwV1PBBmk",
+        "Auction House",
+    ),
+    ("neer8g6yJq2mQM6KbnViEDAD4gr3gRZyMMf4F2p3MEh", "Auctioneer"),
+    (
+        "cndy3Z4yapfJBmL3ShUp5exZKqR3z33thTzeNMm2gRZ",
+        "Candy Machine",
+    ),
+    ("CndyV3LdqHUfDLmE5naZjVN8rBZz4tqhdefbAnjHG3JR", "Candy Core"),
+    (
+        "Guard1JwRhJkVH6XZhzoYxeBVQe872VH6QggF4BWmS9g",
+        "Candy Guard",
+    ),
+    ("BGUMAp9Gq7iTEuizy4pqaxsTyUCBK68MDfK752saRPUY", "Bubblegum"),
+    (
+        "auth9SigNpDKz4sJJ1DfCTuZrZNSAgh9sFD3rboVmgg",
+        "Token Auth Rules",
+    ),
+    ("TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA", "SPL Token"),
+    ("TokenzQdBNbLqP5VEhdkAS6EPFLC1PHnBqCXEpPxuEb", "SPL Token"),
+    (
+        "cmtDvXumGCrqC1Age74AVPhSRVXJMd8PJS91L8KbNCK",
+        "Account Compression",
+    ),
+    ("11111111111111111111111111111111", "System Program"),
+];
+
+/// The domain of a program with a built-in error map.
+pub fn known_program_domain(program_id: &str) -> Option<&'static str> {
+    ERROR_PROGRAMS
+        .iter()
+        .find(|(id, _)| *id == program_id)
+        .map(|(_, domain)| *domain)
+}
+
+/// The domain of a program with a built-in error map or one cached from its IDL.
+fn program_domain(program_id: &str) -> Option<String> {
+    known_program_domain(program_id)
+        .map(String::from)
+        .or_else(|| {
+            cached_error_maps()
+                .into_iter()
+                .find(|map| map.program_id == program_id)
+                .map(|map| map.domain)
+        })
+}
+
+/// The program and hex code of the last `Program <id> failed: custom program error: 0x..`
+/// log line, which is the innermost program that failed.
+fn failed_program_from_logs(logs: &[String]) -> Option<(String, String)> {
+    logs.iter().rev().find_map(|line| {
+        let rest = line.strip_prefix("Program ")?;
+        let (program_id, code) = rest.split_once(" failed: custom program error: 0x")?;
+        Some((program_id.to_string(), code.trim().to_string()))
+    })
+}
+
+/// Fetch a failed transaction and resolve its custom error code against the error maps of
+/// the program that failed.
+pub fn parse_errors_tx(client: &RpcClient, signature: &str) -> Result<()> {
+    let tx = client.get_transaction_with_config(
+        &Signature::from_str(signature).map_err(|_| anyhow!("Invalid signature: {signature}"))?,
+        RpcTransactionConfig {
+            encoding: Some(UiTransactionEncoding::Base64),
+            commitment: Some(client.commitment()),
+            max_supported_transaction_version: Some(0),
+        },
+    )?;
+    let meta = tx
+        .transaction
+        .meta
+        .ok_or_else(|| anyhow!("Transaction has no status metadata"))?;
+    let Some(err) = meta.err else {
+        println!("Transaction {signature} succeeded");
+        return Ok(());
+    };
+
+    let logs: Option<Vec<String>> = meta.log_messages.into();
+    let failed = logs.as_deref().and_then(failed_program_from_logs);
+
+    // Without logs, fall back to the top-level instruction the error points at.
+    let failed = match (failed, &err) {
+        (Some(failed), _) => Some(failed),
+        (None, TransactionError::InstructionError(index, InstructionError::Custom(code))) => {
+            let versioned = tx
+                .transaction
+                .transaction
+                .decode()
+                .ok_or_else(|| anyhow!("Failed to decode transaction {signature}"))?;
+            let keys = versioned.message.static_account_keys();
+            let program_id = versioned
+                .message
+                .instructions()
+                .get(*index as usize)
+                .and_then(|ix| keys.get(ix.program_id_index as usize))
+                .map(|key| key.to_string())
+                .unwrap_or_else(|| "unknown program".to_string());
+            Some((program_id, format!("{code:X}")))
+        }
+        _ => None,
+    };
+
+    let Some((program_id, code)) = failed else {
+        println!("Transaction {signature} failed: {err}");
+        return Ok(());
+    };
+
+    let domain = program_domain(&program_id);
+    println!(
+        "Program {program_id} ({}) failed with error 0x{code}",
+        domain.as_deref().unwrap_or("unknown program")
+    );
+    print_program_errors(domain.as_deref(), &code);
+
+    Ok(())
+}
+
+/// Print the messages for a code, limited to the program's errors when it's a known one.
+fn print_program_errors(domain: Option<&str>, code: &str) {
+    // Anchor's own errors apply to any Anchor program, so they're kept alongside the
+    // failing program's.
+    let errors: Vec<_> = find_errors(code)
+        .into_iter()
+        .filter(|e| match domain {
+            Some(domain) => e.domain == domain || e.domain == "Anchor Program",
+            None => true,
+        })
+        .collect();
+    if errors.is_empty() {
+        println!("\tNo known error for code 0x{code}");
+    }
+    for error in errors {
+        println!("\t{:<10} |\t{}", error.domain, error.message);
+    }
+}
+
+/// Every custom error code in a log dump, grouped by the program that raised it.
+///
+/// Errors on `Program <id> failed` lines belong to that program, and any other line with a
+/// custom error, e.g. a simulation summary, belongs to the program running at that point.
+/// The same code from the same program is only kept once.
+fn custom_errors_from_logs(logs: &str) -> IndexMap<String, Vec<String>> {
+    const MARKER: &str = "custom program error: 0x";
+
+    let mut errors: IndexMap<String, Vec<String>> = IndexMap::new();
+    let mut stack: Vec<String> = vec![];
+    for line in logs.lines().map(str::trim) {
+        let program_line = line
+            .strip_prefix("Program ")
+            .and_then(|rest| rest.split_once(' '));
+        let mut failed_program = None;
+        if let Some((program_id, rest)) = program_line {
+            if rest.starts_with("invoke [") {
+                stack.push(program_id.to_string());
+            } else if rest == "success" || rest.starts_with("failed:") {
+                stack.pop();
+                if rest.starts_with("failed:") {
+                    failed_program = Some(program_id.to_string());
+                }
+            }
+        }
+
+        let Some((_, code)) = line.split_once(MARKER) else {
+            continue;
+        };
+        let code: String = code
+            .chars()
+            .take_while(|c| c.is_ascii_hexdigit())
+            .collect::<String>()
+            .to_uppercase();
+        if code.is_empty() {
+            continue;
+        }
+        let program = failed_program
+            .or_else(|| stack.last().cloned())
+            .unwrap_or_else(|| "unknown program".to_string());
+
+        // Summary lines repeat an error already reported by the failing program.
+        if program == "unknown program" && errors.values().flatten().any(|c| *c == code) {
+            continue;
+        }
+        let codes = errors.entry(program).or_default();
+        if !codes.contains(&code) {
+            codes.push(code);
+        }
+    }
+    errors
+}
+
+/// Decode every custom program error in a simulation or transaction log dump, read from a
+/// file or stdin.
+pub fn parse_errors_logs(file: Option<PathBuf>) -> Result<()> {
+    let logs = match file {
+        Some(path) => {
+            read_to_string(&path).with_context(|| format!("Failed to read {}", path.display()))?
+        }
+        None => {
+            let mut logs = String::new();
+            std::io::stdin().read_to_string(&mut logs)?;
+            logs
+        }
+    };
+
+    let errors = custom_errors_from_logs(&logs);
+    if errors.is_empty() {
+        return Err(anyhow!("No custom program errors found in the logs"));
+    }
+
+    for (program_id, codes) in errors {
+        let domain = program_domain(&program_id);
+        println!(
+            "Program {program_id} ({})",
+            domain.as_deref().unwrap_or("unknown program")
+        );
+        for code in codes {
+            println!("  0x{code}");
+            print_program_errors(domain.as_deref(), &code);
+        }
+    }
+
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -246,4 +551,64 @@ mod tests {
         assert!(newline_res.is_ok());
         assert!(phantom_res.is_ok());
     }
+
+    #[test]
+    fn parses_inline_keys() {
+        let keypair = Keypair::new();
+        let base58 = keypair.to_base58_string();
+        let json = serde_json::to_string(&keypair.to_bytes().to_vec()).unwrap();
+
+        assert_eq!(
+            read_keypair(&base58).unwrap().to_bytes(),
+            keypair.to_bytes()
+        );
+        assert_eq!(read_keypair(&json).unwrap().to_bytes(), keypair.to_bytes());
+        assert!(read_keypair(&String::from("./tests/missing_key.json")).is_err());
+    }
+
+    #[test]
+    fn derives_seed_phrase_keys_by_path() {
+        let phrase = "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about";
+
+        let first = keypair_from_seed_phrase(phrase, None).unwrap();
+        let same = keypair_from_seed_phrase(phrase, Some("m/44'/501'/0'/0'")).unwrap();
+        let second = keypair_from_seed_phrase(phrase, Some("m/44'/501'/1'/0'")).unwrap();
+
+        assert_eq!(first.to_bytes(), same.to_bytes());
+        assert_ne!(first.to_bytes(), second.to_bytes());
+        assert!(keypair_from_seed_phrase("abandon about", None).is_err());
+    }
+
+    #[test]
+    fn rejects_invalid_seed_phrases() {
+        // A misspelled word, and valid words with a bad checksum.
+        let typo = "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandn about";
+        let checksum = "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon";
+
+        assert!(keypair_from_seed_phrase(typo, None).is_err());
+        assert!(keypair_from_seed_phrase(checksum, None).is_err());
+    }
+
+    #[test]
+    fn groups_log_errors_by_failing_program() {
+        let logs = "\
+Program Guard1JwRhJkVH6XZhzoYxeBVQe872VH6QggF4BWmS9g invoke [1]
+Program CndyV3LdqHUfDLmE5naZjVN8rBZz4tqhdefbAnjHG3JR invoke [2]
+Program log: Instruction: MintV2
+Program CndyV3LdqHUfDLmE5naZjVN8rBZz4tqhdefbAnjHG3JR failed: custom program error: 0x1779
+Program Guard1JwRhJkVH6XZhzoYxeBVQe872VH6QggF4BWmS9g failed: custom program error: 0x1779
+Error processing Instruction 0: custom program error: 0x1779";
+
+        let errors = custom_errors_from_logs(logs);
+
+        assert_eq!(errors.len(), 2);
+        assert_eq!(
+            errors["CndyV3LdqHUfDLmE5naZjVN8rBZz4tqhdefbAnjHG3JR"],
+            vec!["1779".to_string()]
+        );
+        assert_eq!(
+            errors["Guard1JwRhJkVH6XZhzoYxeBVQe872VH6QggF4BWmS9g"],
+            vec!["1779".to_string()]
+        );
+    }
 }
@@ -0,0 +1,613 @@
+//! Composable transaction send pipeline.
+//!
+//! Sending a transaction is split into ordered stages:
+//! build → lookup-tables → pack → compute-budget → fee → nonce → sign → send → confirm.
+//! Each stage is a [`Middleware`] that reads and mutates a shared [`TxContext`].
+//! Embedders can insert their own steps around any stage, e.g. a custom fee
+//! oracle after [`Stage::Fee`] or audit logging before [`Stage::Send`], or
+//! replace a built-in stage entirely.
+//!
+//! ```ignore
+//! let sig = Pipeline::with_priority(Priority::Medium)
+//!     .insert_after(Stage::Sign, "audit", |ctx: &mut TxContext| {
+//!         println!("sending {:?}", ctx.transaction.as_ref().unwrap().signatures[0]);
+//!         Ok(())
+//!     })
+//!     .run(&client, &[&keypair], instructions)?;
+//! ```
+
+use anyhow::{anyhow, Result};
+use metaboss_lib::{data::Priority, transaction::get_compute_units};
+use once_cell::sync::Lazy;
+use regex::Regex;
+use retry::{delay::Exponential, retry};
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::{
+    address_lookup_table::{state::AddressLookupTable, AddressLookupTableAccount},
+    compute_budget::ComputeBudgetInstruction,
+    hash::Hash,
+    instruction::Instruction,
+    message::{v0, Message, VersionedMessage},
+    packet::PACKET_DATA_SIZE,
+    pubkey::Pubkey,
+    signature::Keypair,
+    signature::Signature,
+    signer::Signer,
+    transaction::VersionedTransaction,
+};
+use std::{
+    str::FromStr,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use crate::constants::LOOKUP_TABLE;
+use crate::fees::record_receipt;
+use crate::limiter::with_rate_limit_backoff;
+use crate::nonce::{active_nonce, DurableNonce};
+use crate::offline::export_dir;
+use crate::output::record_transaction;
+
+const DEFAULT_COMPUTE_UNITS: u64 = 200_000;
+
+/// How long a fetched blockhash is reused. Blockhashes expire after 150 slots, about
+/// a minute, so this leaves plenty of time for a transaction signed at the end of the
+/// window to land.
+const BLOCKHASH_MAX_AGE: Duration = Duration::from_secs(20);
+
+/// Times a transaction is re-signed with a fresh blockhash after the old one expired.
+const MAX_BLOCKHASH_REFRESHES: u32 = 3;
+
+// Shared across every pipeline run so batch commands fetch a blockhash every few
+// seconds rather than once per transaction.
+static BLOCKHASH: Lazy<Mutex<Option<(Hash, Instant)>>> = Lazy::new(|| Mutex::new(None));
+
+static BLOCKHASH_EXPIRED: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?i)blockhash ?not ?found|unable to confirm transaction|block height exceeded")
+        .unwrap()
+});
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Stage {
+    Build,
+    LookupTables,
+    Pack,
+    ComputeBudget,
+    Fee,
+    Nonce,
+    Sign,
+    Send,
+    Confirm,
+    Custom(&'static str),
+}
+
+/// State threaded through every stage of the pipeline.
+pub struct TxContext<'a> {
+    pub client: &'a RpcClient,
+    /// The first signer pays for the transaction.
+    pub signers: Vec<&'a Keypair>,
+    /// Signers added by stages, such as a durable nonce authority.
+    pub extra_signers: Vec<Keypair>,
+    pub instructions: Vec<Instruction>,
+    /// Must be the first instruction when using a durable nonce.
+    pub advance_nonce: Option<Instruction>,
+    pub compute_unit_limit: Option<u32>,
+    pub compute_unit_price: Option<u64>,
+    /// When set, a v0 transaction is built that loads accounts from these tables.
+    pub lookup_tables: Vec<AddressLookupTableAccount>,
+    pub recent_blockhash: Option<Hash>,
+    pub transaction: Option<VersionedTransaction>,
+    pub signature: Option<Signature>,
+}
+
+impl<'a> TxContext<'a> {
+    pub fn payer(&self) -> Result<&'a Keypair> {
+        self.signers
+            .first()
+            .copied()
+            .ok_or_else(|| anyhow!("At least one signer is required"))
+    }
+
+    /// Instructions as they will be sent, with any nonce and compute budget instructions prepended.
+    pub fn final_instructions(&self) -> Vec<Instruction> {
+        let mut ixs = Vec::with_capacity(self.instructions.len() + 3);
+        if let Some(advance) = &self.advance_nonce {
+            ixs.push(advance.clone());
+        }
+        if let Some(units) = self.compute_unit_limit {
+            ixs.push(ComputeBudgetInstruction::set_compute_unit_limit(units));
+        }
+        if let Some(price) = self.compute_unit_price {
+            ixs.push(ComputeBudgetInstruction::set_compute_unit_price(price));
+        }
+        ixs.extend(self.instructions.iter().cloned());
+        ixs
+    }
+
+    /// Compile a legacy message, or a v0 message if any lookup tables are set.
+    pub fn compile_message(
+        &self,
+        instructions: &[Instruction],
+        recent_blockhash: Hash,
+    ) -> Result<VersionedMessage> {
+        let payer = self.payer()?.pubkey();
+        compile_message(&payer, instructions, &self.lookup_tables, recent_blockhash)
+    }
+}
+
+fn compile_message(
+    payer: &Pubkey,
+    instructions: &[Instruction],
+    lookup_tables: &[AddressLookupTableAccount],
+    recent_blockhash: Hash,
+) -> Result<VersionedMessage> {
+    if lookup_tables.is_empty() {
+        return Ok(VersionedMessage::Legacy(Message::new_with_blockhash(
+            instructions,
+            Some(payer),
+            &recent_blockhash,
+        )));
+    }
+
+    Ok(VersionedMessage::V0(v0::Message::try_compile(
+        payer,
+        instructions,
+        lookup_tables,
+        recent_blockhash,
+    )?))
+}
+
+pub trait Middleware {
+    fn process(&self, ctx: &mut TxContext) -> Result<()>;
+}
+
+impl<F> Middleware for F
+where
+    F: Fn(&mut TxContext) -> Result<()>,
+{
+    fn process(&self, ctx: &mut TxContext) -> Result<()> {
+        self(ctx)
+    }
+}
+
+struct Step {
+    stage: Stage,
+    middleware: Box<dyn Middleware>,
+}
+
+#[derive(Default)]
+pub struct Pipeline {
+    steps: Vec<Step>,
+}
+
+impl Pipeline {
+    /// An empty pipeline with no stages.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Pack, sign, send and confirm without touching the compute budget.
+    pub fn standard() -> Self {
+        Self::new()
+            .push(Stage::Pack, Pack)
+            .push(Stage::Sign, Sign)
+            .push(Stage::Send, Submit)
+            .push(Stage::Confirm, Confirm)
+    }
+
+    /// The full pipeline: simulated compute unit limit and a priority fee.
+    pub fn with_priority(priority: Priority) -> Self {
+        Self::new()
+            .push(Stage::Pack, Pack)
+            .push(Stage::ComputeBudget, SimulatedComputeBudget)
+            .push(Stage::Fee, PriorityFee(priority))
+            .push(Stage::Sign, Sign)
+            .push(Stage::Send, Submit)
+            .push(Stage::Confirm, Confirm)
+    }
+
+    /// Use the address lookup table from the global `--lut` option, if one is set.
+    pub fn with_active_lookup_table(self) -> Result<Self> {
+        let address = match LOOKUP_TABLE.read().unwrap().as_ref() {
+            Some(address) => Pubkey::from_str(address)
+                .map_err(|_| anyhow!("Invalid lookup table address: {address}"))?,
+            None => return Ok(self),
+        };
+        let before = if self.position(Stage::Pack).is_some() {
+            Stage::Pack
+        } else {
+            Stage::Sign
+        };
+        Ok(self.insert_stage_before(before, Stage::LookupTables, LookupTables(vec![address])))
+    }
+
+    /// Use the durable nonce from the global `--nonce-account` option, if one is set.
+    pub fn with_active_nonce(self) -> Result<Self> {
+        Ok(match active_nonce()? {
+            Some(nonce) => self.insert_stage_before(Stage::Sign, Stage::Nonce, DurableNonce(nonce)),
+            None => self,
+        })
+    }
+
+    pub fn push<M: Middleware + 'static>(mut self, stage: Stage, middleware: M) -> Self {
+        self.steps.push(Step {
+            stage,
+            middleware: Box::new(middleware),
+        });
+        self
+    }
+
+    /// Insert a named step directly before the first step of `stage`, or at the end if
+    /// the stage isn't present.
+    pub fn insert_before<M: Middleware + 'static>(
+        mut self,
+        stage: Stage,
+        name: &'static str,
+        middleware: M,
+    ) -> Self {
+        let index = self.position(stage).unwrap_or(self.steps.len());
+        self.steps.insert(index, Self::custom(name, middleware));
+        self
+    }
+
+    /// Insert a named step directly after the last step of `stage`, or at the end if
+    /// the stage isn't present.
+    pub fn insert_after<M: Middleware + 'static>(
+        mut self,
+        stage: Stage,
+        name: &'static str,
+        middleware: M,
+    ) -> Self {
+        let index = self
+            .steps
+            .iter()
+            .rposition(|s| s.stage == stage)
+            .map(|i| i + 1)
+            .unwrap_or(self.steps.len());
+        self.steps.insert(index, Self::custom(name, middleware));
+        self
+    }
+
+    /// Replace every step of `stage` with `middleware`, appending it if the stage isn't present.
+    pub fn replace<M: Middleware + 'static>(mut self, stage: Stage, middleware: M) -> Self {
+        match self.position(stage) {
+            Some(index) => {
+                self.steps.retain(|s| s.stage != stage);
+                self.steps.insert(
+                    index,
+                    Step {
+                        stage,
+                        middleware: Box::new(middleware),
+                    },
+                );
+                self
+            }
+            None => self.push(stage, middleware),
+        }
+    }
+
+    pub fn remove(mut self, stage: Stage) -> Self {
+        self.steps.retain(|s| s.stage != stage);
+        self
+    }
+
+    pub fn stages(&self) -> Vec<Stage> {
+        self.steps.iter().map(|s| s.stage).collect()
+    }
+
+    /// Run every stage in order over the given instructions.
+    pub fn run(
+        &self,
+        client: &RpcClient,
+        signers: &[&Keypair],
+        instructions: Vec<Instruction>,
+    ) -> Result<Signature> {
+        let mut ctx = TxContext {
+            client,
+            signers: signers.to_vec(),
+            extra_signers: vec![],
+            instructions,
+            advance_nonce: None,
+            lookup_tables: vec![],
+            compute_unit_limit: None,
+            compute_unit_price: None,
+            recent_blockhash: None,
+            transaction: None,
+            signature: None,
+        };
+
+        let sign_step = self.position(Stage::Sign);
+        let mut refreshes = 0;
+        let mut i = 0;
+        while i < self.steps.len() {
+            match self.steps[i].middleware.process(&mut ctx) {
+                Ok(()) => i += 1,
+                // The blockhash expired before the transaction landed, so re-sign it with
+                // a fresh one and send it again. A durable nonce never expires.
+                Err(e)
+                    if ctx.advance_nonce.is_none()
+                        && refreshes < MAX_BLOCKHASH_REFRESHES
+                        && is_blockhash_expired(&e.to_string()) =>
+                {
+                    let Some(sign_step) = sign_step.filter(|step| *step < i) else {
+                        return Err(e);
+                    };
+                    log::warn!("Blockhash expired, re-signing with a fresh one: {e}");
+                    invalidate_blockhash(ctx.recent_blockhash);
+                    ctx.recent_blockhash = None;
+                    ctx.transaction = None;
+                    ctx.signature = None;
+                    refreshes += 1;
+                    i = sign_step;
+                }
+                Err(e) => return Err(e),
+            }
+        }
+
+        ctx.signature
+            .ok_or_else(|| anyhow!("Pipeline finished without sending a transaction"))
+    }
+
+    fn insert_stage_before<M: Middleware + 'static>(
+        mut self,
+        before: Stage,
+        stage: Stage,
+        middleware: M,
+    ) -> Self {
+        let index = self.position(before).unwrap_or(self.steps.len());
+        self.steps.insert(
+            index,
+            Step {
+                stage,
+                middleware: Box::new(middleware),
+            },
+        );
+        self
+    }
+
+    fn position(&self, stage: Stage) -> Option<usize> {
+        self.steps.iter().position(|s| s.stage == stage)
+    }
+
+    fn custom<M: Middleware + 'static>(name: &'static str, middleware: M) -> Step {
+        Step {
+            stage: Stage::Custom(name),
+            middleware: Box::new(middleware),
+        }
+    }
+}
+
+/// Loads address lookup tables so the transaction is built as v0.
+pub struct LookupTables(pub Vec<Pubkey>);
+
+impl Middleware for LookupTables {
+    fn process(&self, ctx: &mut TxContext) -> Result<()> {
+        for key in &self.0 {
+            ctx.lookup_tables.push(fetch_lookup_table(ctx.client, key)?);
+        }
+        Ok(())
+    }
+}
+
+pub fn fetch_lookup_table(client: &RpcClient, key: &Pubkey) -> Result<AddressLookupTableAccount> {
+    let account = client.get_account(key)?;
+    let table = AddressLookupTable::deserialize(&account.data)
+        .map_err(|e| anyhow!("{key} is not an address lookup table: {e}"))?;
+    Ok(AddressLookupTableAccount {
+        key: *key,
+        addresses: table.addresses.to_vec(),
+    })
+}
+
+/// Fails early if the instructions can't fit in a single transaction, leaving room
+/// for the two compute budget instructions.
+pub struct Pack;
+
+impl Middleware for Pack {
+    fn process(&self, ctx: &mut TxContext) -> Result<()> {
+        let payer = ctx.payer()?.pubkey();
+        let size = transaction_size(&payer, &ctx.instructions, &ctx.lookup_tables)?;
+
+        if size > PACKET_DATA_SIZE {
+            let hint = if ctx.lookup_tables.is_empty() {
+                ", try an address lookup table with --lut"
+            } else {
+                ""
+            };
+            return Err(anyhow!(
+                "Transaction is {size} bytes, over the {PACKET_DATA_SIZE} byte limit{hint}"
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Serialized size of a transaction with these instructions, including the two
+/// compute budget instructions every pipeline adds.
+pub fn transaction_size(
+    payer: &Pubkey,
+    instructions: &[Instruction],
+    lookup_tables: &[AddressLookupTableAccount],
+) -> Result<usize> {
+    let mut ixs = vec![
+        ComputeBudgetInstruction::set_compute_unit_limit(0),
+        ComputeBudgetInstruction::set_compute_unit_price(0),
+    ];
+    ixs.extend(instructions.iter().cloned());
+
+    let message = compile_message(payer, &ixs, lookup_tables, Hash::default())?;
+    let tx = VersionedTransaction {
+        // Signatures are zeroed but still take up their full size.
+        signatures: vec![Signature::default(); message.header().num_required_signatures as usize],
+        message,
+    };
+    Ok(bincode::serialized_size(&tx)? as usize)
+}
+
+/// Sets the compute unit limit from a simulation of the instructions.
+pub struct SimulatedComputeBudget;
+
+impl Middleware for SimulatedComputeBudget {
+    fn process(&self, ctx: &mut TxContext) -> Result<()> {
+        let units = get_compute_units(ctx.client, &ctx.instructions, &ctx.signers)?
+            .unwrap_or(DEFAULT_COMPUTE_UNITS);
+        ctx.compute_unit_limit = Some(units as u32);
+        Ok(())
+    }
+}
+
+/// Sets the compute unit price from a priority level.
+pub struct PriorityFee(pub Priority);
+
+impl Middleware for PriorityFee {
+    fn process(&self, ctx: &mut TxContext) -> Result<()> {
+        ctx.compute_unit_price = Some(priority_micro_lamports(&self.0));
+        Ok(())
+    }
+}
+
+pub fn priority_micro_lamports(priority: &Priority) -> u64 {
+    match priority {
+        Priority::None => 20,
+        Priority::Low => 20_000,
+        Priority::Medium => 200_000,
+        Priority::High => 1_000_000,
+        Priority::Max => 2_000_000,
+    }
+}
+
+/// Commands that export with `--export-unsigned` replace the sign and send stages, so
+/// reaching them means the command would go live despite the flag.
+fn refuse_when_exporting() -> Result<()> {
+    if export_dir().is_some() {
+        return Err(anyhow!(
+            "This command can't export unsigned transactions and would sign and send them instead. Run it without --export-unsigned"
+        ));
+    }
+    Ok(())
+}
+
+/// Fetches a blockhash if one wasn't provided and signs with every signer.
+pub struct Sign;
+
+impl Middleware for Sign {
+    fn process(&self, ctx: &mut TxContext) -> Result<()> {
+        refuse_when_exporting()?;
+
+        let recent_blockhash = match ctx.recent_blockhash {
+            Some(hash) => hash,
+            None => latest_blockhash(ctx.client)?,
+        };
+        ctx.recent_blockhash = Some(recent_blockhash);
+
+        let message = ctx.compile_message(&ctx.final_instructions(), recent_blockhash)?;
+
+        // Signers must match the message's required signers exactly, in order.
+        let available: Vec<&Keypair> = ctx
+            .signers
+            .iter()
+            .copied()
+            .chain(ctx.extra_signers.iter())
+            .collect();
+        let signers = message.static_account_keys()
+            [..message.header().num_required_signatures as usize]
+            .iter()
+            .map(|key| {
+                available
+                    .iter()
+                    .find(|s| s.pubkey() == *key)
+                    .copied()
+                    .ok_or_else(|| anyhow!("Missing signer for {key}"))
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        ctx.transaction = Some(VersionedTransaction::try_new(message, signers.as_slice())?);
+        Ok(())
+    }
+}
+
+/// A recent blockhash, reusing the last one fetched until it gets close to expiring.
+pub fn latest_blockhash(client: &RpcClient) -> Result<Hash> {
+    let mut cached = BLOCKHASH.lock().unwrap();
+    if let Some((hash, fetched)) = *cached {
+        if fetched.elapsed() < BLOCKHASH_MAX_AGE {
+            return Ok(hash);
+        }
+    }
+
+    let hash = with_rate_limit_backoff(|| client.get_latest_blockhash())?;
+    *cached = Some((hash, Instant::now()));
+    Ok(hash)
+}
+
+/// Stop reusing the blockhash after it was rejected, unless another transaction
+/// already replaced it with a newer one.
+fn invalidate_blockhash(expired: Option<Hash>) {
+    let mut cached = BLOCKHASH.lock().unwrap();
+    if cached.map(|(hash, _)| Some(hash) == expired) == Some(true) {
+        *cached = None;
+    }
+}
+
+/// Whether an error means the transaction's blockhash expired before it landed.
+pub fn is_blockhash_expired(error: &str) -> bool {
+    BLOCKHASH_EXPIRED.is_match(error)
+}
+
+/// Submits the signed transaction, retrying with exponential backoff.
+pub struct Submit;
+
+impl Middleware for Submit {
+    fn process(&self, ctx: &mut TxContext) -> Result<()> {
+        refuse_when_exporting()?;
+
+        let tx = ctx
+            .transaction
+            .as_ref()
+            .ok_or_else(|| anyhow!("Transaction must be signed before sending"))?;
+
+        // Rate limited sends are retried separately so they don't use up the retries
+        // meant for real failures.
+        let sig = retry(
+            Exponential::from_millis_with_factor(250, 2.0).take(3),
+            || with_rate_limit_backoff(|| ctx.client.send_transaction(tx)),
+        )?;
+
+        ctx.signature = Some(sig);
+        Ok(())
+    }
+}
+
+/// Waits for the sent transaction to reach the client's commitment level.
+pub struct Confirm;
+
+impl Middleware for Confirm {
+    fn process(&self, ctx: &mut TxContext) -> Result<()> {
+        let sig = ctx
+            .signature
+            .ok_or_else(|| anyhow!("Transaction must be sent before confirming"))?;
+
+        // A nonce never expires like a blockhash does, so just wait on the signature.
+        if ctx.advance_nonce.is_some() {
+            ctx.client
+                .poll_for_signature_with_commitment(&sig, ctx.client.commitment())?;
+        } else {
+            let blockhash = ctx
+                .recent_blockhash
+                .ok_or_else(|| anyhow!("Transaction must be signed before confirming"))?;
+
+            ctx.client.confirm_transaction_with_spinner(
+                &sig,
+                &blockhash,
+                ctx.client.commitment(),
+            )?;
+        }
+
+        // The transaction landed either way, so a receipt that can't be written isn't an error.
+        if let Err(e) = record_receipt(&sig, &ctx.instructions) {
+            log::warn!("Failed to write receipt for {sig}: {e}");
+        }
+        record_transaction(&sig, &ctx.instructions);
+        Ok(())
+    }
+}
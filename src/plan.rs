@@ -0,0 +1,541 @@
+//! Operation plans: several per-mint steps, like setting and verifying a collection and
+//! updating the URI, compiled into one transaction per mint.
+//!
+//! All the metadata updates of a plan are merged into a single Update instruction, with
+//! an Unverify before it when the collection changes and a Verify after it. Sending them
+//! together takes a third of the transactions of running each step as its own command,
+//! and each mint's changes land or fail as a whole.
+
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+use anyhow::{anyhow, Result};
+use indexmap::IndexMap;
+use log::warn;
+use metaboss_lib::{
+    data::{Priority, UpdateUriData},
+    decode::decode_metadata_from_mint,
+    unverify::{unverify_collection_ix, UnverifyCollectionArgs},
+    update::{update_asset_ix, UpdateAssetArgs, V1UpdateArgs},
+    verify::{verify_collection_ix, VerifyCollectionArgs},
+};
+use mpl_token_metadata::types::{Collection, CollectionToggle, Data};
+use serde::{Deserialize, Serialize};
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::{
+    instruction::Instruction,
+    packet::PACKET_DATA_SIZE,
+    pubkey::Pubkey,
+    signature::{Keypair, Signature},
+    signer::Signer,
+};
+use structopt::StructOpt;
+
+use crate::workspace::output_path;
+use crate::{
+    changelog::Changelog,
+    checkpoint, expect,
+    files::read_json,
+    order,
+    pack::{
+        active_lookup_tables, pack_instruction_groups, send_packed, InstructionGroup,
+        PackedTransaction,
+    },
+    parse::{parse_keypair, parse_solana_config},
+    pipeline::{transaction_size, Pipeline},
+    spinner::create_progress_bar,
+};
+
+const PLAN_COMMAND: &str = "plan-run";
+
+/// Estimate of the compute units of each instruction a plan compiles to.
+const PLAN_INSTRUCTION_COMPUTE_UNITS: u32 = 50_000;
+
+#[derive(Debug, StructOpt)]
+pub enum PlanSubcommands {
+    /// Run the steps of a plan on every mint, in one transaction per mint
+    #[structopt(name = "run")]
+    Run {
+        /// Path to the update authority keypair file
+        #[structopt(short, long)]
+        keypair: Option<String>,
+
+        /// Plan file with the steps to run
+        #[structopt(short, long)]
+        plan: PathBuf,
+
+        /// Path to the mint list file
+        #[structopt(short = "L", long)]
+        mint_list: String,
+
+        /// Pack the transactions of several mints together where they fit
+        #[structopt(long)]
+        pack: bool,
+
+        /// Print what each mint would change without sending anything
+        #[structopt(long)]
+        dry_run: bool,
+
+        /// Priority of the transaction: higher priority costs more.
+        /// See https://metaboss.rs/priority-fees.html for more details.
+        #[structopt(short = "P", long, default_value = "none")]
+        priority: Priority,
+    },
+}
+
+pub fn process_plan(client: RpcClient, commands: PlanSubcommands) -> Result<()> {
+    match commands {
+        PlanSubcommands::Run {
+            keypair,
+            plan,
+            mint_list,
+            pack,
+            dry_run,
+            priority,
+        } => run_plan(RunPlanArgs {
+            client,
+            keypair,
+            plan,
+            mint_list,
+            pack,
+            dry_run,
+            priority,
+        }),
+    }
+}
+
+/// One step of a plan.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "op", rename_all = "kebab-case")]
+pub enum PlanStep {
+    /// Set the collection, unverifying the current one first if it's verified.
+    SetCollection {
+        collection: String,
+    },
+    /// Verify the collection, the one set by the plan if none is given.
+    VerifyCollection {
+        collection: Option<String>,
+    },
+    /// Set the URI, either the same for every mint or from a file in the
+    /// `update uri-all` format.
+    UpdateUri {
+        uri: Option<String>,
+        uris: Option<PathBuf>,
+    },
+    UpdateName {
+        name: String,
+    },
+    UpdateSymbol {
+        symbol: String,
+    },
+    UpdateSellerFeeBasisPoints {
+        seller_fee_basis_points: u16,
+    },
+    SetPrimarySaleHappened,
+    SetImmutable,
+    UpdateAuthority {
+        new_update_authority: String,
+    },
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Plan {
+    pub steps: Vec<PlanStep>,
+}
+
+pub struct RunPlanArgs {
+    pub client: RpcClient,
+    pub keypair: Option<String>,
+    pub plan: PathBuf,
+    pub mint_list: String,
+    pub pack: bool,
+    pub dry_run: bool,
+    pub priority: Priority,
+}
+
+/// What the plan changes for one mint.
+#[derive(Debug, Default, Serialize)]
+pub struct MintChanges {
+    pub changes: Vec<String>,
+    pub instructions: usize,
+}
+
+/// The plan with its per-mint values loaded.
+struct CompiledPlan {
+    steps: Vec<PlanStep>,
+    uris: HashMap<String, String>,
+}
+
+fn load_plan(path: &Path) -> Result<CompiledPlan> {
+    let plan: Plan = read_json(path)?;
+    if plan.steps.is_empty() {
+        return Err(anyhow!("The plan has no steps"));
+    }
+
+    let mut uris = HashMap::new();
+    for step in &plan.steps {
+        match step {
+            PlanStep::UpdateUri {
+                uri: None,
+                uris: None,
+            }
+            | PlanStep::UpdateUri {
+                uri: Some(_),
+                uris: Some(_),
+            } => return Err(anyhow!("update-uri needs exactly one of uri or uris")),
+            PlanStep::UpdateUri {
+                uris: Some(file), ..
+            } => {
+                let values: Vec<UpdateUriData> = read_json(file)?;
+                uris.extend(values.into_iter().map(|v| (v.mint_account, v.new_uri)));
+            }
+            PlanStep::VerifyCollection { collection: None }
+                if !plan
+                    .steps
+                    .iter()
+                    .any(|s| matches!(s, PlanStep::SetCollection { .. })) =>
+            {
+                return Err(anyhow!(
+                    "verify-collection needs a collection when the plan doesn't set one"
+                ))
+            }
+            _ => {}
+        }
+    }
+
+    Ok(CompiledPlan {
+        steps: plan.steps,
+        uris,
+    })
+}
+
+fn trim_padding(s: &str) -> &str {
+    s.trim_matches(char::from(0))
+}
+
+/// Compile the plan for one mint into its instructions, leaving out steps the mint
+/// already satisfies.
+fn compile_mint(
+    client: &RpcClient,
+    authority: &Keypair,
+    plan: &CompiledPlan,
+    mint: &str,
+) -> Result<(Vec<Instruction>, MintChanges)> {
+    let md = decode_metadata_from_mint(client, mint.to_string())?;
+    let mut changes = MintChanges::default();
+
+    let mut data = Data {
+        name: md.name.clone(),
+        symbol: md.symbol.clone(),
+        uri: md.uri.clone(),
+        seller_fee_basis_points: md.seller_fee_basis_points,
+        creators: md.creators.clone(),
+    };
+    let mut data_changed = false;
+    let mut update_args = V1UpdateArgs::default();
+    let mut unverify: Option<Pubkey> = None;
+    let mut set_collection: Option<Pubkey> = None;
+    let mut verify: Option<Pubkey> = None;
+
+    for step in &plan.steps {
+        match step {
+            PlanStep::SetCollection { collection } => {
+                let collection = Pubkey::from_str(collection)?;
+                set_collection = Some(collection);
+                match &md.collection {
+                    Some(current) if current.key == collection => continue,
+                    Some(current) if current.verified => unverify = Some(current.key),
+                    _ => {}
+                }
+                update_args.collection = CollectionToggle::Set(Collection {
+                    key: collection,
+                    verified: false,
+                });
+                changes.changes.push(format!("collection: {collection}"));
+            }
+            PlanStep::VerifyCollection { collection } => {
+                let collection = match collection {
+                    Some(collection) => Pubkey::from_str(collection)?,
+                    None => set_collection
+                        .ok_or_else(|| anyhow!("No collection to verify for {mint}"))?,
+                };
+                // Already verified, and not being replaced by an earlier step.
+                let verified =
+                    matches!(&md.collection, Some(c) if c.key == collection && c.verified);
+                if !verified || unverify.is_some() {
+                    verify = Some(collection);
+                    changes
+                        .changes
+                        .push(format!("verify collection: {collection}"));
+                }
+            }
+            PlanStep::UpdateUri { uri, .. } => {
+                let uri = match uri {
+                    Some(uri) => uri.clone(),
+                    None => plan
+                        .uris
+                        .get(mint)
+                        .cloned()
+                        .ok_or_else(|| anyhow!("No new uri found for {mint}"))?,
+                };
+                if trim_padding(&data.uri) != trim_padding(&uri) {
+                    changes.changes.push(format!("uri: {uri}"));
+                    data.uri = uri;
+                    data_changed = true;
+                }
+            }
+            PlanStep::UpdateName { name } => {
+                if trim_padding(&data.name) != trim_padding(name) {
+                    changes.changes.push(format!("name: {name}"));
+                    data.name = name.clone();
+                    data_changed = true;
+                }
+            }
+            PlanStep::UpdateSymbol { symbol } => {
+                if trim_padding(&data.symbol) != trim_padding(symbol) {
+                    changes.changes.push(format!("symbol: {symbol}"));
+                    data.symbol = symbol.clone();
+                    data_changed = true;
+                }
+            }
+            PlanStep::UpdateSellerFeeBasisPoints {
+                seller_fee_basis_points,
+            } => {
+                if data.seller_fee_basis_points != *seller_fee_basis_points {
+                    changes.changes.push(format!(
+                        "seller_fee_basis_points: {seller_fee_basis_points}"
+                    ));
+                    data.seller_fee_basis_points = *seller_fee_basis_points;
+                    data_changed = true;
+                }
+            }
+            PlanStep::SetPrimarySaleHappened => {
+                if !md.primary_sale_happened {
+                    update_args.primary_sale_happened = Some(true);
+                    changes
+                        .changes
+                        .push("primary_sale_happened: true".to_string());
+                }
+            }
+            PlanStep::SetImmutable => {
+                if md.is_mutable {
+                    update_args.is_mutable = Some(false);
+                    changes.changes.push("is_mutable: false".to_string());
+                }
+            }
+            PlanStep::UpdateAuthority {
+                new_update_authority,
+            } => {
+                let new_update_authority = Pubkey::from_str(new_update_authority)?;
+                if md.update_authority != new_update_authority {
+                    update_args.new_update_authority = Some(new_update_authority);
+                    changes
+                        .changes
+                        .push(format!("update_authority: {new_update_authority}"));
+                }
+            }
+        }
+    }
+    if data_changed {
+        update_args.data = Some(data);
+    }
+
+    let mut instructions = vec![];
+    if let Some(collection_mint) = unverify {
+        instructions.push(unverify_collection_ix(
+            client,
+            UnverifyCollectionArgs::V1 {
+                authority,
+                mint: mint.to_string(),
+                collection_mint,
+                is_delegate: false,
+            },
+        )?);
+    }
+    if has_update(&update_args) {
+        instructions.push(update_asset_ix(
+            client,
+            UpdateAssetArgs::V1 {
+                payer: None,
+                authority,
+                mint: mint.to_string(),
+                token: None::<String>,
+                delegate_record: None::<String>,
+                update_args,
+                priority: Priority::None,
+            },
+        )?);
+    }
+    if let Some(collection_mint) = verify {
+        instructions.push(verify_collection_ix(
+            client,
+            VerifyCollectionArgs::V1 {
+                authority,
+                mint: mint.to_string(),
+                collection_mint: collection_mint.to_string(),
+                is_delegate: false,
+            },
+        )?);
+    }
+    changes.instructions = instructions.len();
+
+    Ok((instructions, changes))
+}
+
+fn has_update(args: &V1UpdateArgs) -> bool {
+    args.data.is_some()
+        || args.primary_sale_happened.is_some()
+        || args.is_mutable.is_some()
+        || args.new_update_authority.is_some()
+        || !matches!(args.collection, CollectionToggle::None)
+}
+
+/// Send a mint's instructions that are too large for one transaction, one at a time in
+/// order. The mint's changes are no longer atomic, so a failure part way is reported.
+fn send_split(
+    client: &RpcClient,
+    authority: &Keypair,
+    priority: &Priority,
+    mint: &str,
+    instructions: Vec<Instruction>,
+) -> Result<Signature> {
+    let total = instructions.len();
+    let mut signature = Signature::default();
+    for (i, ix) in instructions.into_iter().enumerate() {
+        signature = Pipeline::with_priority(priority.clone())
+            .with_active_lookup_table()?
+            .with_active_nonce()?
+            .run(client, &[authority], vec![ix])
+            .map_err(|e| anyhow!("Step {} of {total} failed for {mint}: {e}", i + 1))?;
+    }
+    Ok(signature)
+}
+
+pub fn run_plan(args: RunPlanArgs) -> Result<()> {
+    let solana_opts = parse_solana_config();
+    let authority = parse_keypair(args.keypair, solana_opts);
+    let client = &args.client;
+
+    let plan = load_plan(&args.plan)?;
+    let mint_list: Vec<String> = read_json(&args.mint_list)?;
+    let mints = order::ordered(checkpoint::remaining(PLAN_COMMAND, mint_list));
+
+    let changelog = Changelog::capture(client, PLAN_COMMAND, &mints)?;
+    let mints = expect::unchanged(PLAN_COMMAND, mints, changelog.before())?;
+
+    let lookup_tables = active_lookup_tables(client)?;
+    let mut errors: IndexMap<String, String> = IndexMap::new();
+    let mut planned: IndexMap<String, MintChanges> = IndexMap::new();
+    let mut groups = vec![];
+    let mut oversized = vec![];
+    let mut already_done = 0;
+
+    let pb = create_progress_bar("Compiling plan...", mints.len() as u64);
+    for mint in mints {
+        pb.inc(1);
+        let (instructions, changes) = match compile_mint(client, &authority, &plan, &mint) {
+            Ok(compiled) => compiled,
+            Err(e) => {
+                checkpoint::record_failed(PLAN_COMMAND, &mint, &e);
+                errors.insert(mint, e.to_string());
+                continue;
+            }
+        };
+        if instructions.is_empty() {
+            already_done += 1;
+            checkpoint::record_done(PLAN_COMMAND, &mint, None);
+            continue;
+        }
+        let fits = transaction_size(&authority.pubkey(), &instructions, &lookup_tables)?
+            <= PACKET_DATA_SIZE;
+        planned.insert(mint.clone(), changes);
+        if fits {
+            groups.push(InstructionGroup {
+                key: mint,
+                compute_units: PLAN_INSTRUCTION_COMPUTE_UNITS * instructions.len() as u32,
+                instructions,
+            });
+        } else {
+            oversized.push((mint, instructions));
+        }
+    }
+    pb.finish_and_clear();
+
+    println!("Already done: {already_done}");
+    if args.dry_run {
+        println!("{}", serde_json::to_string_pretty(&planned)?);
+        println!(
+            "{} mints to change, {} of them too large for a single transaction",
+            planned.len(),
+            oversized.len()
+        );
+        return Ok(());
+    }
+
+    let packed = if args.pack {
+        pack_instruction_groups(client, &authority.pubkey(), groups)?
+    } else {
+        groups
+            .into_iter()
+            .map(|group| PackedTransaction {
+                keys: vec![group.key],
+                instructions: group.instructions,
+                compute_units: group.compute_units,
+            })
+            .collect()
+    };
+    let (sent, failed) = send_packed(client, &[&authority], &args.priority, packed)?;
+
+    let mut succeeded: Vec<(String, Signature)> = sent
+        .into_iter()
+        .flat_map(|(mints, signature)| mints.into_iter().map(move |mint| (mint, signature)))
+        .collect();
+    for (mints, e) in failed {
+        for mint in mints {
+            checkpoint::record_failed(PLAN_COMMAND, &mint, &e);
+            errors.insert(mint, e.to_string());
+        }
+    }
+
+    if !oversized.is_empty() {
+        println!(
+            "{} mints need more than one transaction, sending their steps one at a time. . .",
+            oversized.len()
+        );
+    }
+    for (mint, instructions) in oversized {
+        match send_split(client, &authority, &args.priority, &mint, instructions) {
+            Ok(signature) => succeeded.push((mint, signature)),
+            Err(e) => {
+                checkpoint::record_failed(PLAN_COMMAND, &mint, &e);
+                errors.insert(mint, e.to_string());
+            }
+        }
+    }
+
+    for (mint, signature) in &succeeded {
+        checkpoint::record_done(PLAN_COMMAND, mint, Some(signature.to_string()));
+        changelog.record(mint, *signature);
+    }
+    println!("Mints changed: {}", succeeded.len());
+
+    match changelog.write(client) {
+        Ok(Some(path)) => println!("Changelog written to {path}"),
+        Ok(None) => {}
+        Err(e) => warn!("Failed to write the changelog: {e}"),
+    }
+
+    if !errors.is_empty() {
+        let errors_path = output_path("mb-plan-errors.json").display().to_string();
+        serde_json::to_writer_pretty(std::fs::File::create(&errors_path)?, &errors)?;
+        println!(
+            "{} mints failed. Errors written to {errors_path}",
+            errors.len()
+        );
+    }
+
+    Ok(())
+}
@@ -0,0 +1,112 @@
+//! Plugins: executables named `metaboss-<name>` on the PATH, run for unknown subcommands.
+//!
+//! `metaboss foo --bar` runs `metaboss-foo --bar`, the same way cargo and git find their
+//! extensions. The plugin gets the resolved RPC url, keypair and global options through
+//! `METABOSS_*` environment variables, so private commands can reuse the same setup.
+
+use std::{
+    io::ErrorKind,
+    path::PathBuf,
+    process::{self, Command},
+};
+
+use anyhow::{anyhow, Result};
+use serde::Serialize;
+use serde_json::{json, Value};
+
+use crate::opt::Opt;
+
+const PLUGIN_PREFIX: &str = "metaboss-";
+
+/// What a plugin needs to talk to the same cluster as the metaboss it was run from.
+#[derive(Debug, Serialize)]
+pub struct PluginContext {
+    pub rpc: String,
+    pub commitment: String,
+    pub timeout: u64,
+    pub keypair: Option<String>,
+    /// Websocket url, from the `--profile`.
+    pub ws_url: Option<String>,
+    /// Run directory, when running under `--workspace`.
+    pub run_dir: Option<PathBuf>,
+    /// Every global option, as passed on the command line.
+    pub options: Value,
+}
+
+/// The global options, for plugins that want to honour more than the RPC and keypair.
+pub fn global_options(opt: &Opt) -> Value {
+    json!({
+        "rpc": opt.rpc,
+        "commitment": opt.commitment,
+        "timeout": opt.timeout,
+        "log_level": opt.log_level,
+        "log_path": opt.log_path,
+        "jito": opt.jito,
+        "jito_tip": opt.jito_tip,
+        "multisig": opt.multisig,
+        "vault_index": opt.vault_index,
+        "export_unsigned": opt.export_unsigned,
+        "offline_authority": opt.offline_authority,
+        "nonce_account": opt.nonce_account,
+        "nonce_authority": opt.nonce_authority,
+        "lut": opt.lut,
+        "receipts": opt.receipts,
+        "rate_limit": opt.rate_limit,
+        "concurrency": opt.concurrency,
+        "max_network_fee": opt.max_network_fee,
+        "cache": opt.cache,
+        "no_cache": opt.no_cache,
+        "resume": opt.resume,
+        "order": opt.order.to_string(),
+        "seed": opt.seed,
+        "expect_file": opt.expect_file,
+        "on_mismatch": format!("{:?}", opt.on_mismatch).to_lowercase(),
+        "workspace": opt.workspace,
+        "output_format": opt.output_format.to_string(),
+        "profile": opt.profile,
+        // The seed phrase is a secret, so only its derivation path is passed on.
+        "derivation": opt.derivation,
+        "quiet": opt.quiet,
+    })
+}
+
+/// Run the `metaboss-<name>` plugin for `args`, the subcommand name followed by its
+/// arguments, and exit with its status.
+pub fn run_plugin(args: Vec<String>, context: &PluginContext) -> Result<()> {
+    let (name, plugin_args) = args
+        .split_first()
+        .ok_or_else(|| anyhow!("No subcommand given"))?;
+    let program = format!("{PLUGIN_PREFIX}{name}");
+
+    let mut command = Command::new(&program);
+    command
+        .args(plugin_args)
+        .env("METABOSS_RPC_URL", &context.rpc)
+        .env("METABOSS_COMMITMENT", &context.commitment)
+        .env("METABOSS_TIMEOUT", context.timeout.to_string())
+        .env("METABOSS_CONTEXT", serde_json::to_string(context)?);
+    if let Some(keypair) = &context.keypair {
+        command.env("METABOSS_KEYPAIR", keypair);
+    }
+    if let Some(ws_url) = &context.ws_url {
+        command.env("METABOSS_WS_URL", ws_url);
+    }
+    if let Some(dir) = &context.run_dir {
+        command.env("METABOSS_RUN_DIR", dir);
+    }
+
+    let status = match command.status() {
+        Ok(status) => status,
+        Err(e) if e.kind() == ErrorKind::NotFound => {
+            return Err(anyhow!(
+                "Unknown command '{name}', and no `{program}` plugin was found on the PATH"
+            ))
+        }
+        Err(e) => return Err(anyhow!("Failed to run {program}: {e}")),
+    };
+
+    if !status.success() {
+        process::exit(status.code().unwrap_or(1));
+    }
+    Ok(())
+}
@@ -10,10 +10,14 @@ use solana_client::{nonblocking::rpc_client::RpcClient as AsyncRpcClient, rpc_cl
 
 use crate::airdrop::*;
 use crate::burn::*;
+use crate::cnft::{
+    burn_cnft, mint_cnft, transfer_cnft, BurnCnftArgs, MintCnftArgs, TransferCnftArgs, TreeParams,
+};
 use crate::collections::{
-    approve_delegate, check_collection_items, get_collection_items, migrate_collection,
-    revoke_delegate, set_and_verify_nft_collection, set_size, unverify_nft_collection,
-    verify_nft_collection, MigrateArgs,
+    approve_delegate, check_collection_items, get_collection_items, list_authorities,
+    migrate_collection, revoke_delegate, set_and_verify_nft_collection, set_size,
+    unverify_collection_all, unverify_nft_collection, verify_nft_collection, MigrateArgs,
+    UnverifyCollectionAllArgs,
 };
 use crate::create::{
     create_fungible, create_fungible_22, create_fungible_22_token, create_master_edition,
@@ -26,23 +30,33 @@ use crate::decode::{
     process_decode_bpf_loader_upgradable_state, process_decode_rule_set,
 };
 use crate::derive::{
-    get_cmv2_pda, get_collection_delegate, get_edition_marker_pda, get_edition_pda,
-    get_generic_pda, get_metadata_pda, get_token_account_pda, get_token_record_pda,
+    get_cmv2_pda, get_cmv3_pda, get_collection_authority_record_pda, get_collection_delegate,
+    get_edition_marker_pda, get_edition_pda, get_generic_pda, get_metadata_delegate_record_pda,
+    get_metadata_pda, get_token_account_pda, get_token_record_pda, get_use_authority_record_pda,
+};
+use crate::find::{
+    find_burned, find_frozen, find_missing_editions_process, find_unverified_creators,
 };
-use crate::find::find_missing_editions_process;
+use crate::idl_errors::update_error_map;
 use crate::mint::{
-    mint_editions, mint_fungible, mint_list, mint_missing_editions, mint_one, process_mint_asset,
-    MintAssetParams,
+    mint_editions, mint_fungible, mint_list, mint_missing_editions_process, mint_one,
+    process_mint_asset, verify_editions, MintAssetParams,
 };
 use crate::opt::*;
-use crate::parse::{is_only_one_option, parse_errors_code, parse_errors_file};
-use crate::sign::{sign_all, sign_one};
-use crate::transfer::process_transfer_asset;
+use crate::parse::{
+    is_only_one_option, parse_errors_code, parse_errors_file, parse_errors_logs, parse_errors_tx,
+};
+use crate::sign::{sign_all, sign_apply, sign_collect, sign_one, sign_pending};
+use crate::squads::{active_multisig, propose_with_vault_authority};
+use crate::transfer::{process_transfer_asset, transfer_batch, TransferBatchArgs};
 use crate::unverify::{
     unverify_creator, unverify_creator_all, UnverifyCreatorAllArgs, UnverifyCreatorArgs,
 };
 use crate::update::*;
-use crate::uses::{approve_use_delegate, revoke_use_delegate, utilize_nft};
+use crate::uses::{
+    approve_burners, approve_use_delegate, remaining_uses, revoke_use_delegate, utilize_nft,
+    ApproveBurnersArgs,
+};
 use crate::verify::{verify_creator, verify_creator_all, VerifyCreatorAllArgs, VerifyCreatorArgs};
 
 pub fn process_uses(client: &RpcClient, commands: UsesSubcommands) -> Result<()> {
@@ -64,6 +78,29 @@ pub fn process_uses(client: &RpcClient, commands: UsesSubcommands) -> Result<()>
             number_of_uses,
         ),
 
+        UsesSubcommands::ApproveBurners {
+            keypair,
+            mint_nft,
+            mint_list,
+            count,
+            number_of_uses,
+            fund,
+            burner_program_id,
+            output,
+            priority,
+        } => approve_burners(ApproveBurnersArgs {
+            client,
+            keypair,
+            mint_nft,
+            mint_list,
+            count,
+            number_of_uses,
+            fund,
+            burner_program_id,
+            output,
+            priority,
+        }),
+
         UsesSubcommands::RevokeAuthority {
             delegate_use_authority,
             keypair,
@@ -93,6 +130,12 @@ pub fn process_uses(client: &RpcClient, commands: UsesSubcommands) -> Result<()>
             burner_program_id,
             is_delegate,
         ),
+
+        UsesSubcommands::Remaining {
+            mint_nft,
+            mint_list,
+            output,
+        } => remaining_uses(client, mint_nft, mint_list, output),
     }
 }
 
@@ -116,6 +159,28 @@ pub async fn process_collections(
             nft_mint,
         } => unverify_nft_collection(client, keypair, nft_mint, collection_mint, is_delegate),
 
+        CollectionsSubcommands::UnverifyCollectionAll {
+            keypair,
+            collection_mint,
+            mint_list,
+            cache_file,
+            rate_limit,
+            retries,
+            priority,
+        } => {
+            unverify_collection_all(UnverifyCollectionAllArgs {
+                client,
+                keypair,
+                collection_mint,
+                mint_list,
+                cache_file,
+                rate_limit,
+                retries,
+                priority,
+            })
+            .await
+        }
+
         #[allow(deprecated)]
         CollectionsSubcommands::SetAndVerifyCollection {
             keypair,
@@ -129,13 +194,20 @@ pub async fn process_collections(
             keypair,
             collection_mint,
             delegate_authority,
-        } => approve_delegate(client, keypair, collection_mint, delegate_authority),
+            legacy,
+        } => approve_delegate(client, keypair, collection_mint, delegate_authority, legacy),
 
         CollectionsSubcommands::RevokeAuthority {
             keypair,
             collection_mint,
             delegate_authority,
-        } => revoke_delegate(client, keypair, collection_mint, delegate_authority),
+            legacy,
+        } => revoke_delegate(client, keypair, collection_mint, delegate_authority, legacy),
+
+        CollectionsSubcommands::ListAuthorities {
+            collection_mint,
+            check_legacy,
+        } => list_authorities(client, collection_mint, check_legacy),
 
         CollectionsSubcommands::SetSize {
             keypair,
@@ -152,6 +224,7 @@ pub async fn process_collections(
             retries,
             rate_limit,
             output_file,
+            pack,
         } => {
             migrate_collection(MigrateArgs {
                 client,
@@ -164,6 +237,7 @@ pub async fn process_collections(
                 retries,
                 rate_limit,
                 output_file,
+                pack,
             })
             .await
         }
@@ -188,8 +262,10 @@ pub async fn process_burn_asset(client: RpcClient, commands: BurnSubcommands) ->
             mint_account,
             token_account,
             amount,
+            force,
             priority,
         } => {
+            check_no_live_prints(&client, std::slice::from_ref(&mint_account), force)?;
             let solana_opts = parse_solana_config();
             let keypair = parse_keypair(keypair, solana_opts);
 
@@ -202,7 +278,7 @@ pub async fn process_burn_asset(client: RpcClient, commands: BurnSubcommands) ->
                 priority,
             };
 
-            let sig = burn_asset(args).await.map_err(Into::<ActionError>::into)?;
+            let sig = burn_asset(args).await?;
 
             info!("Tx sig: {:?}", sig);
             println!("Tx sig: {sig:?}");
@@ -213,6 +289,7 @@ pub async fn process_burn_asset(client: RpcClient, commands: BurnSubcommands) ->
             keypair,
             mint_list,
             cache_file,
+            force,
             rate_limit,
             retries,
             priority,
@@ -222,12 +299,44 @@ pub async fn process_burn_asset(client: RpcClient, commands: BurnSubcommands) ->
                 keypair,
                 mint_list,
                 cache_file,
+                force,
+                rate_limit,
+                retries,
+                priority,
+            })
+            .await
+        }
+        BurnSubcommands::Editions {
+            keypair,
+            master,
+            cache_file,
+            rate_limit,
+            retries,
+            priority,
+        } => {
+            burn_editions(BurnEditionsArgs {
+                client,
+                keypair,
+                master_mint: master,
+                cache_file,
                 rate_limit,
                 retries,
                 priority,
             })
             .await
         }
+        BurnSubcommands::Cnft {
+            keypair,
+            asset_id,
+            das_url,
+            priority,
+        } => burn_cnft(BurnCnftArgs {
+            client,
+            keypair,
+            asset_id,
+            das_url,
+            priority,
+        }),
     }
 }
 
@@ -236,13 +345,15 @@ pub async fn process_burn_nft(client: RpcClient, commands: BurnNftSubcommands) -
         BurnNftSubcommands::One {
             keypair,
             mint_account,
+            force,
             priority,
-        } => burn_one(client, keypair, mint_account, priority).await,
+        } => burn_one(client, keypair, mint_account, force, priority).await,
 
         BurnNftSubcommands::All {
             keypair,
             mint_list,
             cache_file,
+            force,
             rate_limit,
             retries,
             priority,
@@ -252,6 +363,7 @@ pub async fn process_burn_nft(client: RpcClient, commands: BurnNftSubcommands) -
                 keypair,
                 mint_list,
                 cache_file,
+                force,
                 rate_limit,
                 retries,
                 priority,
@@ -318,6 +430,8 @@ pub fn process_create(client: RpcClient, commands: CreateSubcommands) -> Result<
             decimals,
             initial_supply,
             immutable,
+            revoke_mint_authority,
+            revoke_freeze_authority,
             priority,
             full_compute,
         } => create_fungible(CreateFungibleArgs {
@@ -328,6 +442,8 @@ pub fn process_create(client: RpcClient, commands: CreateSubcommands) -> Result<
             decimals,
             initial_supply,
             immutable,
+            revoke_mint_authority,
+            revoke_freeze_authority,
             priority,
             full_compute,
         }),
@@ -430,6 +546,8 @@ pub fn process_decode(client: &RpcClient, commands: DecodeSubcommands) -> Result
             full,
             list_file,
             raw,
+            edition_info,
+            off_chain,
             ref output,
         } => decode_metadata_from_mint(
             client,
@@ -437,6 +555,8 @@ pub fn process_decode(client: &RpcClient, commands: DecodeSubcommands) -> Result
             full,
             list_file.as_ref(),
             raw,
+            edition_info,
+            off_chain,
             output,
         )?,
         DecodeSubcommands::Master { account } => decode_master_edition(client, &account)?,
@@ -482,10 +602,28 @@ pub fn process_derive(commands: DeriveSubcommands) {
             edition_num,
         } => get_edition_marker_pda(mint_account, edition_num),
         DeriveSubcommands::CMV2Creator { candy_machine_id } => get_cmv2_pda(candy_machine_id),
+        DeriveSubcommands::CandyMachineCreator { candy_machine_id } => {
+            get_cmv3_pda(candy_machine_id)
+        }
         DeriveSubcommands::TokenRecord {
             mint_account,
             token_account,
-        } => get_token_record_pda(mint_account, token_account),
+            owner,
+        } => get_token_record_pda(mint_account, token_account, owner),
+        DeriveSubcommands::CollectionAuthorityRecord {
+            mint_account,
+            collection_authority,
+        } => get_collection_authority_record_pda(mint_account, collection_authority),
+        DeriveSubcommands::UseAuthorityRecord {
+            mint_account,
+            use_authority,
+        } => get_use_authority_record_pda(mint_account, use_authority),
+        DeriveSubcommands::MetadataDelegateRecord {
+            mint_account,
+            role,
+            update_authority,
+            delegate,
+        } => get_metadata_delegate_record_pda(mint_account, role, update_authority, delegate),
         DeriveSubcommands::CollectionDelegate {
             collection_mint,
             collection_authority,
@@ -496,9 +634,18 @@ pub fn process_derive(commands: DeriveSubcommands) {
 
 pub fn process_find(client: &RpcClient, commands: FindSubcommands) -> Result<()> {
     match commands {
-        FindSubcommands::MissingEditions { account } => {
-            find_missing_editions_process(client, &account)
-        }
+        FindSubcommands::MissingEditions {
+            account,
+            mint_list,
+            output,
+        } => find_missing_editions_process(client, account, mint_list, output),
+        FindSubcommands::UnverifiedCreators {
+            mint_list,
+            creator,
+            output,
+        } => find_unverified_creators(client, mint_list, creator, output),
+        FindSubcommands::Frozen { mint_list, output } => find_frozen(client, mint_list, output),
+        FindSubcommands::Burned { mint_list, output } => find_burned(client, mint_list, output),
         FindSubcommands::Error { error_code } => parse_errors_code(&error_code),
     }
 }
@@ -529,6 +676,8 @@ pub fn process_mint(client: RpcClient, commands: MintSubcommands) -> Result<()>
             amount,
             decimals,
             max_print_edition_supply,
+            extra_account_metas,
+            additional_instructions,
             priority,
         } => process_mint_asset(MintAssetParams {
             client,
@@ -539,6 +688,8 @@ pub fn process_mint(client: RpcClient, commands: MintSubcommands) -> Result<()>
             decimals,
             amount,
             max_print_edition_supply,
+            extra_account_metas,
+            additional_instructions,
             priority,
         }),
         MintSubcommands::One {
@@ -574,6 +725,8 @@ pub fn process_mint(client: RpcClient, commands: MintSubcommands) -> Result<()>
             receiver,
             next_editions,
             specific_editions,
+            recipients,
+            verify,
             priority,
         } => mint_editions(
             &client,
@@ -582,13 +735,57 @@ pub fn process_mint(client: RpcClient, commands: MintSubcommands) -> Result<()>
             &receiver,
             next_editions,
             specific_editions,
+            recipients,
+            verify,
             priority,
         ),
+        MintSubcommands::VerifyEditions {
+            keypair,
+            account,
+            print_list,
+            repair,
+            priority,
+        } => verify_editions(&client, keypair, &account, &print_list, repair, priority),
         MintSubcommands::MissingEditions {
             keypair,
             account,
+            missing_file,
+            priority,
+        } => mint_missing_editions_process(&client, &keypair, account, missing_file, priority),
+        MintSubcommands::Cnft {
+            keypair,
+            receiver,
+            receiver_list,
+            nft_data,
+            tree,
+            max_depth,
+            max_buffer_size,
+            canopy_depth,
+            public,
+            collection,
+            immutable,
+            primary_sale_happened,
+            output,
+            priority,
+        } => mint_cnft(MintCnftArgs {
+            client,
+            keypair,
+            receiver,
+            receiver_list,
+            nft_data,
+            tree,
+            tree_params: TreeParams {
+                max_depth,
+                max_buffer_size,
+                canopy_depth,
+            },
+            public,
+            collection,
+            immutable,
+            primary_sale_happened,
+            output,
             priority,
-        } => mint_missing_editions(&client, &keypair, &account, priority),
+        }),
         MintSubcommands::List {
             keypair,
             receiver,
@@ -631,11 +828,9 @@ pub async fn process_set(client: RpcClient, commands: SetSubcommands) -> Result<
                 priority,
             };
 
-            let sig = set_primary_sale_happened(args)
-                .await
-                .map_err(Into::<ActionError>::into)?;
-            info!("Tx sig: {:?}", sig);
-            println!("Tx sig: {sig:?}");
+            let sig = set_primary_sale_happened(args).await?;
+            info!("Tx sig: {}", sig);
+            println!("Tx sig: {sig}");
 
             Ok(())
         }
@@ -667,6 +862,27 @@ pub async fn process_set(client: RpcClient, commands: SetSubcommands) -> Result<
         } => {
             let solana_opts = parse_solana_config();
             let keypair = parse_keypair(keypair, solana_opts);
+
+            if let Some(target) = active_multisig()? {
+                // The vault pays for the update when it executes.
+                let new_update_authority = Pubkey::from_str(&new_update_authority)?;
+                return propose_with_vault_authority(&client, keypair, &target, |authority| {
+                    let update_args = UpdateAssetArgs::V1 {
+                        payer: None,
+                        authority,
+                        mint: account,
+                        token: None::<String>,
+                        delegate_record: None::<String>,
+                        update_args: V1UpdateArgs {
+                            new_update_authority: Some(new_update_authority),
+                            ..Default::default()
+                        },
+                        priority,
+                    };
+                    Ok(vec![update_asset_ix(&client, update_args)?])
+                });
+            }
+
             let solana_opts = parse_solana_config();
             let payer = keypair_payer.map(|path| parse_keypair(Some(path), solana_opts));
 
@@ -679,11 +895,9 @@ pub async fn process_set(client: RpcClient, commands: SetSubcommands) -> Result<
                 priority,
             };
 
-            let sig = set_update_authority(args)
-                .await
-                .map_err(Into::<ActionError>::into)?;
-            info!("Tx sig: {:?}", sig);
-            println!("Tx sig: {sig:?}");
+            let sig = set_update_authority(args).await?;
+            info!("Tx sig: {}", sig);
+            println!("Tx sig: {sig}");
 
             Ok(())
         }
@@ -725,11 +939,9 @@ pub async fn process_set(client: RpcClient, commands: SetSubcommands) -> Result<
                 priority,
             };
 
-            let sig = set_immutable(args)
-                .await
-                .map_err(Into::<ActionError>::into)?;
-            info!("Tx sig: {:?}", sig);
-            println!("Tx sig: {sig:?}");
+            let sig = set_immutable(args).await?;
+            info!("Tx sig: {}", sig);
+            println!("Tx sig: {sig}");
 
             Ok(())
         }
@@ -740,6 +952,7 @@ pub async fn process_set(client: RpcClient, commands: SetSubcommands) -> Result<
             rate_limit,
             retries,
             priority,
+            dry_run,
         } => {
             set_immutable_all(SetImmutableAllArgs {
                 client,
@@ -749,6 +962,7 @@ pub async fn process_set(client: RpcClient, commands: SetSubcommands) -> Result<
                 rate_limit,
                 retries,
                 priority,
+                dry_run,
             })
             .await
         }
@@ -767,9 +981,7 @@ pub async fn process_set(client: RpcClient, commands: SetSubcommands) -> Result<
                 priority,
             };
 
-            let sig = set_token_standard_one(args)
-                .await
-                .map_err(Into::<ActionError>::into)?;
+            let sig = set_token_standard_one(args).await?;
             info!("Tx sig: {:?}", sig);
             println!("Tx sig: {sig:?}");
 
@@ -807,6 +1019,8 @@ pub fn process_sign(client: &RpcClient, commands: SignSubcommands) -> Result<()>
             v2,
             v3,
             mint_accounts_file,
+            pack,
+            keypairs,
         } => sign_all(
             client,
             keypair,
@@ -815,7 +1029,16 @@ pub fn process_sign(client: &RpcClient, commands: SignSubcommands) -> Result<()>
             v2,
             v3,
             mint_accounts_file,
+            pack,
+            keypairs,
         ),
+        SignSubcommands::Collect {
+            keypair,
+            mint_list,
+            out,
+        } => sign_collect(client, keypair, mint_list, out),
+        SignSubcommands::Pending { keypair, file, out } => sign_pending(keypair, file, out),
+        SignSubcommands::Apply { keypair, file } => sign_apply(client, keypair, file),
     }
 }
 
@@ -828,6 +1051,30 @@ pub fn process_transfer(client: RpcClient, commands: TransferSubcommands) -> Res
             amount,
             receiver_account,
         } => process_transfer_asset(&client, keypair, receiver, receiver_account, mint, amount),
+        TransferSubcommands::Cnft {
+            keypair,
+            receiver,
+            asset_id,
+            das_url,
+            priority,
+        } => transfer_cnft(TransferCnftArgs {
+            client,
+            keypair,
+            asset_id,
+            receiver,
+            das_url,
+            priority,
+        }),
+        TransferSubcommands::Batch {
+            keypair,
+            file,
+            priority,
+        } => transfer_batch(TransferBatchArgs {
+            client,
+            keypair,
+            file,
+            priority,
+        }),
     }
 }
 
@@ -850,11 +1097,9 @@ pub async fn process_update(client: RpcClient, commands: UpdateSubcommands) -> R
                 priority,
             };
 
-            let sig = update_rule_set(args)
-                .await
-                .map_err(Into::<ActionError>::into)?;
-            info!("Tx sig: {:?}", sig);
-            println!("Tx sig: {sig:?}");
+            let sig = update_rule_set(args).await?;
+            info!("Tx sig: {}", sig);
+            println!("Tx sig: {sig}");
 
             Ok(())
         }
@@ -894,12 +1139,10 @@ pub async fn process_update(client: RpcClient, commands: UpdateSubcommands) -> R
                 priority,
             };
 
-            let sig = clear_rule_set(args)
-                .await
-                .map_err(Into::<ActionError>::into)?;
+            let sig = clear_rule_set(args).await?;
 
-            info!("Tx sig: {:?}", sig);
-            println!("Tx sig: {sig:?}");
+            info!("Tx sig: {}", sig);
+            println!("Tx sig: {sig}");
 
             Ok(())
         }
@@ -939,9 +1182,9 @@ pub async fn process_update(client: RpcClient, commands: UpdateSubcommands) -> R
                 priority,
             };
 
-            let sig = update_sfbp(args).await.map_err(Into::<ActionError>::into)?;
-            info!("Tx sig: {:?}", sig);
-            println!("Tx sig: {sig:?}");
+            let sig = update_sfbp(args).await?;
+            info!("Tx sig: {}", sig);
+            println!("Tx sig: {sig}");
 
             Ok(())
         }
@@ -983,9 +1226,9 @@ pub async fn process_update(client: RpcClient, commands: UpdateSubcommands) -> R
                 priority,
             };
 
-            let sig = update_name(args).await.map_err(Into::<ActionError>::into)?;
-            info!("Tx sig: {:?}", sig);
-            println!("Tx sig: {sig:?}");
+            let sig = update_name(args).await?;
+            info!("Tx sig: {}", sig);
+            println!("Tx sig: {sig}");
 
             Ok(())
         }
@@ -1006,11 +1249,9 @@ pub async fn process_update(client: RpcClient, commands: UpdateSubcommands) -> R
                 priority,
             };
 
-            let sig = update_symbol(args)
-                .await
-                .map_err(Into::<ActionError>::into)?;
-            info!("Tx sig: {:?}", sig);
-            println!("Tx sig: {sig:?}");
+            let sig = update_symbol(args).await?;
+            info!("Tx sig: {}", sig);
+            println!("Tx sig: {sig}");
 
             Ok(())
         }
@@ -1054,11 +1295,9 @@ pub async fn process_update(client: RpcClient, commands: UpdateSubcommands) -> R
                 priority,
             };
 
-            let sig = update_creator(args)
-                .await
-                .map_err(Into::<ActionError>::into)?;
-            info!("Tx sig: {:?}", sig);
-            println!("Tx sig: {sig:?}");
+            let sig = update_creator(args).await?;
+            info!("Tx sig: {}", sig);
+            println!("Tx sig: {sig}");
 
             Ok(())
         }
@@ -1068,6 +1307,7 @@ pub async fn process_update(client: RpcClient, commands: UpdateSubcommands) -> R
             cache_file,
             new_creators,
             append,
+            force,
             rate_limit,
             retries,
             priority,
@@ -1079,6 +1319,7 @@ pub async fn process_update(client: RpcClient, commands: UpdateSubcommands) -> R
                 cache_file,
                 new_creators,
                 should_append: append,
+                force,
                 rate_limit,
                 retries,
                 priority,
@@ -1096,6 +1337,24 @@ pub async fn process_update(client: RpcClient, commands: UpdateSubcommands) -> R
 
             let new_data: Data = serde_json::from_reader(File::open(new_data_file)?)?;
 
+            if let Some(target) = active_multisig()? {
+                return propose_with_vault_authority(&client, keypair, &target, |authority| {
+                    let update_args = UpdateAssetArgs::V1 {
+                        payer: None,
+                        authority,
+                        mint: account,
+                        token: None::<String>,
+                        delegate_record: None::<String>,
+                        update_args: V1UpdateArgs {
+                            data: Some(new_data),
+                            ..Default::default()
+                        },
+                        priority,
+                    };
+                    Ok(vec![update_asset_ix(&client, update_args)?])
+                });
+            }
+
             let args = UpdateDataArgs {
                 client: Arc::new(client),
                 keypair: Arc::new(keypair),
@@ -1104,9 +1363,9 @@ pub async fn process_update(client: RpcClient, commands: UpdateSubcommands) -> R
                 priority,
             };
 
-            let sig = update_data(args).await.map_err(Into::<ActionError>::into)?;
-            info!("Tx sig: {:?}", sig);
-            println!("Tx sig: {sig:?}");
+            let sig = update_data(args).await?;
+            info!("Tx sig: {}", sig);
+            println!("Tx sig: {sig}");
 
             Ok(())
         }
@@ -1146,28 +1405,38 @@ pub async fn process_update(client: RpcClient, commands: UpdateSubcommands) -> R
                 priority,
             };
 
-            let sig = update_uri(args).await.map_err(Into::<ActionError>::into)?;
-            info!("Tx sig: {:?}", sig);
-            println!("Tx sig: {sig:?}");
+            let sig = update_uri(args).await?;
+            info!("Tx sig: {}", sig);
+            println!("Tx sig: {sig}");
 
             Ok(())
         }
         UpdateSubcommands::UriAll {
             keypair,
             new_uris_file,
+            mint_list,
+            pattern,
+            replace,
+            regex,
             cache_file,
             rate_limit,
             retries,
             priority,
+            pack,
         } => {
             update_uri_all(UpdateUriAllArgs {
                 client,
                 keypair,
                 new_uris_file,
+                mint_list,
+                pattern,
+                replace,
+                regex,
                 cache_file,
                 rate_limit,
                 retries,
                 priority,
+                pack,
             })
             .await
         }
@@ -1190,7 +1459,7 @@ pub async fn process_update(client: RpcClient, commands: UpdateSubcommands) -> R
                 overwrite,
                 priority,
             };
-            let sig = update_uses_one(args).map_err(Into::<ActionError>::into)?;
+            let sig = update_uses_one(args)?;
             info!("Tx sig: {:?}", sig);
             println!("Tx sig: {sig:?}");
 
@@ -1210,9 +1479,7 @@ pub async fn process_verify(client: RpcClient, commands: VerifySubcommands) -> R
                 keypair: Arc::new(keypair),
                 mint,
             };
-            let sig = verify_creator(args)
-                .await
-                .map_err(Into::<ActionError>::into)?;
+            let sig = verify_creator(args).await?;
 
             info!("Tx sig: {:?}", sig);
             println!("Tx sig: {sig:?}");
@@ -1250,9 +1517,7 @@ pub async fn process_unverify(client: RpcClient, commands: UnverifySubcommands)
                 keypair: Arc::new(keypair),
                 mint,
             };
-            let sig = unverify_creator(args)
-                .await
-                .map_err(Into::<ActionError>::into)?;
+            let sig = unverify_creator(args).await?;
 
             info!("Tx sig: {:?}", sig);
             println!("Tx sig: {sig:?}");
@@ -1279,8 +1544,16 @@ pub async fn process_unverify(client: RpcClient, commands: UnverifySubcommands)
     }
 }
 
-pub fn process_parse_errors_file(commands: ParseErrorsSubCommands) -> Result<()> {
+pub fn process_parse_errors_file(
+    client: &RpcClient,
+    commands: ParseErrorsSubCommands,
+) -> Result<()> {
     match commands {
         ParseErrorsSubCommands::File => parse_errors_file(),
+        ParseErrorsSubCommands::Tx { signature } => parse_errors_tx(client, &signature),
+        ParseErrorsSubCommands::Logs { file } => parse_errors_logs(file),
+        ParseErrorsSubCommands::Update { program, name } => {
+            update_error_map(client, &program, name)
+        }
     }
 }
@@ -0,0 +1,85 @@
+//! Named RPC profiles from `~/.config/metaboss/config.toml`, selected with `--profile`.
+//!
+//! A profile fills in the RPC url, commitment, keypair and rate limit when they aren't
+//! given on the command line, ahead of the Solana CLI config.
+
+use std::{collections::BTreeMap, fs, path::PathBuf, sync::RwLock};
+
+use anyhow::{anyhow, Context, Result};
+use once_cell::sync::Lazy;
+use serde::Deserialize;
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Profile {
+    pub rpc: Option<String>,
+    /// Websocket url, passed on to plugins.
+    pub ws_url: Option<String>,
+    pub commitment: Option<String>,
+    pub keypair: Option<String>,
+    /// Requests per second, as with `--rate-limit`.
+    pub rate_limit: Option<usize>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ConfigFile {
+    /// Profile used when `--profile` isn't given.
+    default_profile: Option<String>,
+    #[serde(default)]
+    profiles: BTreeMap<String, Profile>,
+}
+
+static ACTIVE_PROFILE: Lazy<RwLock<Option<Profile>>> = Lazy::new(|| RwLock::new(None));
+
+pub fn config_path() -> Option<PathBuf> {
+    dirs::home_dir().map(|home| home.join(".config").join("metaboss").join("config.toml"))
+}
+
+/// Load the named profile, or the config file's default profile when no name is given, and
+/// make it the active profile. Without a config file only a named profile is an error.
+pub fn load_profile(name: Option<&str>) -> Result<Option<Profile>> {
+    let path = config_path().ok_or_else(|| anyhow!("Couldn't find the home directory"))?;
+    if !path.exists() {
+        return match name {
+            Some(name) => Err(anyhow!(
+                "No config file at {} to find profile {name} in",
+                path.display()
+            )),
+            None => Ok(None),
+        };
+    }
+
+    let contents =
+        fs::read_to_string(&path).with_context(|| format!("Failed to read {}", path.display()))?;
+    let config: ConfigFile =
+        toml::from_str(&contents).with_context(|| format!("Invalid config {}", path.display()))?;
+
+    let name = match name.map(String::from).or(config.default_profile) {
+        Some(name) => name,
+        None => return Ok(None),
+    };
+    let mut profile = config.profiles.get(&name).cloned().ok_or_else(|| {
+        let names: Vec<&str> = config.profiles.keys().map(String::as_str).collect();
+        anyhow!(
+            "No profile {name} in {}, expected one of: {}",
+            path.display(),
+            names.join(", ")
+        )
+    })?;
+    profile.keypair = profile
+        .keypair
+        .map(|keypair| shellexpand::tilde(&keypair).to_string());
+
+    *ACTIVE_PROFILE.write().unwrap() = Some(profile.clone());
+    Ok(Some(profile))
+}
+
+/// The active profile's keypair, used when no keypair is passed to a command.
+pub fn profile_keypair() -> Option<String> {
+    ACTIVE_PROFILE
+        .read()
+        .unwrap()
+        .as_ref()
+        .and_then(|profile| profile.keypair.clone())
+}
@@ -0,0 +1,362 @@
+//! Local JSON-RPC proxy that lets several Metaboss processes share one RPC budget.
+//!
+//! Identical requests that are in flight at the same time are sent upstream once,
+//! responses that can't change are cached, and every upstream request waits on a
+//! single rate limiter.
+
+use std::{
+    collections::HashMap,
+    io::{BufRead, BufReader, Read, Write},
+    net::{TcpListener, TcpStream},
+    sync::{Arc, Condvar, Mutex},
+    thread,
+    time::{Duration, Instant},
+};
+
+use anyhow::{anyhow, Result};
+use log::{debug, warn};
+use ratelimit::Handle;
+use serde_json::{json, Value};
+
+use crate::constants::NANO_SECONDS_IN_SECOND;
+use crate::limiter::create_rate_limiter_with_capacity;
+
+/// Methods whose finalized results never change, so they are cached for good.
+const IMMUTABLE_METHODS: [&str; 5] = [
+    "getTransaction",
+    "getBlock",
+    "getBlockTime",
+    "getConfirmedTransaction",
+    "getConfirmedBlock",
+];
+
+/// Account reads whose finalized results are cached for the account TTL.
+const ACCOUNT_METHODS: [&str; 5] = [
+    "getAccountInfo",
+    "getMultipleAccounts",
+    "getProgramAccounts",
+    "getTokenAccountsByOwner",
+    "getTokenLargestAccounts",
+];
+
+/// Methods that must reach the RPC every time they are called.
+const UNIQUE_METHODS: [&str; 1] = ["requestAirdrop"];
+
+// Cleared when full so a long running proxy doesn't grow without bound.
+const MAX_CACHE_ENTRIES: usize = 50_000;
+
+// JSON-RPC bodies larger than this are refused.
+const MAX_BODY_SIZE: usize = 10 * 1024 * 1024;
+// The request line and each header must fit in this many bytes.
+const MAX_LINE_LENGTH: usize = 8 * 1024;
+// Requests with more headers than this are refused.
+const MAX_HEADERS: usize = 100;
+
+pub struct ProxyArgs {
+    pub rpc: String,
+    pub listen: String,
+    pub rate_limit: usize,
+    pub account_ttl: u64,
+    pub timeout: Duration,
+}
+
+type Pending = Arc<(Mutex<Option<Result<Value, String>>>, Condvar)>;
+
+struct CachedResponse {
+    response: Value,
+    expires: Option<Instant>,
+}
+
+struct Proxy {
+    rpc: String,
+    http: reqwest::blocking::Client,
+    limiter: Handle,
+    account_ttl: Duration,
+    in_flight: Mutex<HashMap<String, Pending>>,
+    cache: Mutex<HashMap<String, CachedResponse>>,
+}
+
+impl Proxy {
+    /// Handle one JSON-RPC request object, returning the response with the caller's id.
+    fn handle(&self, request: &Value) -> Value {
+        let id = request.get("id").cloned().unwrap_or(Value::Null);
+        let method = match request.get("method").and_then(Value::as_str) {
+            Some(method) => method,
+            None => return rpc_error(id, -32600, "Invalid request"),
+        };
+        let params = request.get("params").cloned().unwrap_or(Value::Null);
+        let key = format!("{method}:{params}");
+
+        if let Some(response) = self.cached(&key) {
+            debug!("Cache hit for {method}");
+            return with_id(response, id);
+        }
+
+        let response = if UNIQUE_METHODS.contains(&method) {
+            self.forward(method, &params)
+        } else {
+            self.forward_once(&key, method, &params)
+        };
+
+        match response {
+            Ok(response) => {
+                self.store(&key, method, &params, &response);
+                with_id(response, id)
+            }
+            Err(e) => rpc_error(id, -32603, &e),
+        }
+    }
+
+    /// Forward a request unless an identical one is already in flight, in which case
+    /// wait for and share its response.
+    fn forward_once(&self, key: &str, method: &str, params: &Value) -> Result<Value, String> {
+        let (pending, leader) = {
+            let mut in_flight = self.in_flight.lock().unwrap();
+            match in_flight.get(key) {
+                Some(pending) => (pending.clone(), false),
+                None => {
+                    let pending: Pending = Arc::new((Mutex::new(None), Condvar::new()));
+                    in_flight.insert(key.to_string(), pending.clone());
+                    (pending, true)
+                }
+            }
+        };
+
+        let (result, ready) = &*pending;
+        if leader {
+            let response = self.forward(method, params);
+            *result.lock().unwrap() = Some(response.clone());
+            ready.notify_all();
+            self.in_flight.lock().unwrap().remove(key);
+            return response;
+        }
+
+        debug!("Sharing in-flight {method} request");
+        let mut result = result.lock().unwrap();
+        while result.is_none() {
+            result = ready.wait(result).unwrap();
+        }
+        result.clone().unwrap()
+    }
+
+    fn forward(&self, method: &str, params: &Value) -> Result<Value, String> {
+        self.limiter.clone().wait();
+
+        let mut body = json!({ "jsonrpc": "2.0", "id": 1, "method": method });
+        if !params.is_null() {
+            body["params"] = params.clone();
+        }
+
+        self.http
+            .post(&self.rpc)
+            .json(&body)
+            .send()
+            .and_then(|response| response.json::<Value>())
+            .map_err(|e| format!("Upstream request failed: {e}"))
+    }
+
+    fn cached(&self, key: &str) -> Option<Value> {
+        let cache = self.cache.lock().unwrap();
+        let cached = cache.get(key)?;
+        match cached.expires {
+            Some(expires) if expires <= Instant::now() => None,
+            _ => Some(cached.response.clone()),
+        }
+    }
+
+    fn store(&self, key: &str, method: &str, params: &Value, response: &Value) {
+        // Only successful finalized responses are safe to reuse.
+        if response.get("error").is_some() || response["result"].is_null() || !finalized(params) {
+            return;
+        }
+        let expires = if IMMUTABLE_METHODS.contains(&method) {
+            None
+        } else if ACCOUNT_METHODS.contains(&method) {
+            Some(Instant::now() + self.account_ttl)
+        } else {
+            return;
+        };
+
+        let mut cache = self.cache.lock().unwrap();
+        if cache.len() >= MAX_CACHE_ENTRIES {
+            let now = Instant::now();
+            cache.retain(|_, cached| cached.expires.map(|e| e > now) != Some(false));
+            if cache.len() >= MAX_CACHE_ENTRIES {
+                cache.clear();
+            }
+        }
+        cache.insert(
+            key.to_string(),
+            CachedResponse {
+                response: response.clone(),
+                expires,
+            },
+        );
+    }
+}
+
+/// Whether a request reads at finalized commitment, which RPC nodes default to.
+fn finalized(params: &Value) -> bool {
+    let commitment = params
+        .as_array()
+        .and_then(|params| params.last())
+        .and_then(|config| config.get("commitment"))
+        .and_then(Value::as_str);
+    matches!(
+        commitment,
+        None | Some("finalized") | Some("max") | Some("root")
+    )
+}
+
+fn with_id(mut response: Value, id: Value) -> Value {
+    response["id"] = id;
+    response
+}
+
+fn rpc_error(id: Value, code: i64, message: &str) -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "id": id,
+        "error": { "code": code, "message": message },
+    })
+}
+
+/// Read one HTTP request from the connection, returning its body, or `None` once the
+/// client closes it.
+/// Read one line of at most `MAX_LINE_LENGTH` bytes.
+fn read_line(reader: &mut BufReader<TcpStream>, line: &mut String) -> Result<usize> {
+    let read = reader
+        .by_ref()
+        .take(MAX_LINE_LENGTH as u64)
+        .read_line(line)?;
+    if read == MAX_LINE_LENGTH && !line.ends_with('\n') {
+        return Err(anyhow!("Request line or header is too long"));
+    }
+    Ok(read)
+}
+
+fn read_request(reader: &mut BufReader<TcpStream>) -> Result<Option<Vec<u8>>> {
+    let mut request_line = String::new();
+    if read_line(reader, &mut request_line)? == 0 {
+        return Ok(None);
+    }
+    if !request_line.starts_with("POST") {
+        return Err(anyhow!("Only POST requests are supported"));
+    }
+
+    let mut content_length = 0;
+    let mut header_count = 0;
+    loop {
+        let mut header = String::new();
+        read_line(reader, &mut header)?;
+        let header = header.trim_end();
+        if header.is_empty() {
+            break;
+        }
+        header_count += 1;
+        if header_count > MAX_HEADERS {
+            return Err(anyhow!("Too many request headers"));
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            if name.eq_ignore_ascii_case("content-length") {
+                content_length = value.trim().parse()?;
+            }
+        }
+    }
+    if content_length > MAX_BODY_SIZE {
+        return Err(anyhow!("Request body is too large"));
+    }
+
+    let mut body = vec![0; content_length];
+    reader.read_exact(&mut body)?;
+    Ok(Some(body))
+}
+
+fn write_response(stream: &mut TcpStream, status: &str, body: &[u8]) -> Result<()> {
+    write!(
+        stream,
+        "HTTP/1.1 {status}\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\r\n",
+        body.len()
+    )?;
+    stream.write_all(body)?;
+    stream.flush()?;
+    Ok(())
+}
+
+fn serve_connection(proxy: &Proxy, stream: TcpStream) -> Result<()> {
+    let mut writer = stream.try_clone()?;
+    let mut reader = BufReader::new(stream);
+
+    // Keep the connection open for as many requests as the client sends on it.
+    loop {
+        let body = match read_request(&mut reader) {
+            Ok(Some(body)) => body,
+            Ok(None) => return Ok(()),
+            Err(e) => {
+                let error = rpc_error(Value::Null, -32600, &e.to_string());
+                return write_response(
+                    &mut writer,
+                    "400 Bad Request",
+                    error.to_string().as_bytes(),
+                );
+            }
+        };
+
+        let response = match serde_json::from_slice::<Value>(&body) {
+            Ok(Value::Array(requests)) => {
+                Value::Array(requests.iter().map(|r| proxy.handle(r)).collect())
+            }
+            Ok(request) => proxy.handle(&request),
+            Err(e) => rpc_error(Value::Null, -32700, &format!("Parse error: {e}")),
+        };
+        write_response(&mut writer, "200 OK", response.to_string().as_bytes())?;
+    }
+}
+
+/// Run the proxy until the process is stopped.
+pub fn run_proxy(args: ProxyArgs) -> Result<()> {
+    if args.rate_limit == 0 {
+        return Err(anyhow!("--rate-limit must be at least 1"));
+    }
+
+    let listener = TcpListener::bind(&args.listen)?;
+
+    let delay = NANO_SECONDS_IN_SECOND / args.rate_limit;
+    let proxy = Arc::new(Proxy {
+        rpc: args.rpc.clone(),
+        http: reqwest::blocking::Client::builder()
+            .timeout(args.timeout)
+            .build()?,
+        limiter: create_rate_limiter_with_capacity(args.rate_limit as u32, delay as u32),
+        account_ttl: Duration::from_secs(args.account_ttl),
+        in_flight: Mutex::new(HashMap::new()),
+        cache: Mutex::new(HashMap::new()),
+    });
+
+    println!(
+        "Proxying {} on http://{} at up to {} requests per second",
+        args.rpc, args.listen, args.rate_limit
+    );
+    println!(
+        "Point other Metaboss commands at it with --rpc http://{}",
+        args.listen
+    );
+
+    for stream in listener.incoming() {
+        let stream = match stream {
+            Ok(stream) => stream,
+            Err(e) => {
+                warn!("Failed to accept connection: {e}");
+                continue;
+            }
+        };
+        let proxy = proxy.clone();
+        thread::spawn(move || {
+            if let Err(e) = serve_connection(&proxy, stream) {
+                debug!("Connection closed: {e}");
+            }
+        });
+    }
+
+    Ok(())
+}
@@ -0,0 +1,212 @@
+//! On-disk cache of account data and off-chain JSON, enabled with the global `--cache`
+//! option, so re-running a read-heavy command over the same mints doesn't refetch
+//! everything.
+//!
+//! Accounts are stored with the slot they were read at. Accounts that can't change, such
+//! as immutable metadata and print editions, are reused for good. Anything else is reused
+//! until it is `CACHE_MAX_AGE_SLOTS` old. Off-chain JSON is only cached for
+//! content-addressed URIs.
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{anyhow, Result};
+use log::debug;
+use mpl_token_metadata::{accounts::Metadata, types::Key};
+use once_cell::sync::OnceCell;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::{account::Account, hash::hash, pubkey::Pubkey};
+
+use crate::constants::{MAX_MULTIPLE_ACCOUNTS, RPC_CACHE};
+
+/// About an hour of slots.
+pub const CACHE_MAX_AGE_SLOTS: u64 = 9_000;
+
+// Gateways whose content can't change once it's uploaded.
+const CONTENT_ADDRESSED_HOSTS: [&str; 5] = [
+    "arweave.net",
+    "ar-io.net",
+    "ipfs.io",
+    "nftstorage.link",
+    "dweb.link",
+];
+
+static CURRENT_SLOT: OnceCell<u64> = OnceCell::new();
+
+#[derive(Serialize, Deserialize)]
+struct CachedAccount {
+    slot: u64,
+    immutable: bool,
+    account: Account,
+}
+
+pub fn cache_enabled() -> bool {
+    *RPC_CACHE.read().unwrap()
+}
+
+fn cache_dir(kind: &str) -> Result<PathBuf> {
+    let dir = dirs::cache_dir()
+        .ok_or_else(|| anyhow!("Couldn't find a cache directory"))?
+        .join("metaboss")
+        .join(kind);
+    fs::create_dir_all(&dir)?;
+    Ok(dir)
+}
+
+// Write to a temporary file first so a parallel reader never sees a partial entry.
+fn write_entry(path: &Path, contents: &[u8]) -> Result<()> {
+    let tmp = path.with_extension(format!("tmp{}", std::process::id()));
+    fs::write(&tmp, contents)?;
+    fs::rename(tmp, path)?;
+    Ok(())
+}
+
+/// The slot this run reads at, fetched once so every entry is judged against the same slot.
+fn current_slot(client: &RpcClient) -> Result<u64> {
+    CURRENT_SLOT
+        .get_or_try_init(|| client.get_slot())
+        .copied()
+        .map_err(Into::into)
+}
+
+/// Whether an account's data can never change: print editions, and metadata that has
+/// been made immutable.
+fn is_immutable(account: &Account) -> bool {
+    if account.owner != mpl_token_metadata::ID {
+        return false;
+    }
+    match account.data.first() {
+        Some(key) if *key == Key::EditionV1 as u8 => true,
+        Some(key) if *key == Key::MetadataV1 as u8 => Metadata::safe_deserialize(&account.data)
+            .map(|metadata| !metadata.is_mutable)
+            .unwrap_or(false),
+        _ => false,
+    }
+}
+
+fn read_account(client: &RpcClient, pubkey: &Pubkey) -> Option<Account> {
+    let path = cache_dir("accounts").ok()?.join(format!("{pubkey}.bin"));
+    let cached: CachedAccount = bincode::deserialize(&fs::read(path).ok()?).ok()?;
+
+    if cached.immutable {
+        return Some(cached.account);
+    }
+    let slot = current_slot(client).ok()?;
+    if slot.saturating_sub(cached.slot) <= CACHE_MAX_AGE_SLOTS {
+        Some(cached.account)
+    } else {
+        None
+    }
+}
+
+fn write_account(pubkey: &Pubkey, slot: u64, account: &Account) {
+    let result = cache_dir("accounts").and_then(|dir| {
+        let cached = CachedAccount {
+            slot,
+            immutable: is_immutable(account),
+            account: account.clone(),
+        };
+        write_entry(
+            &dir.join(format!("{pubkey}.bin")),
+            &bincode::serialize(&cached)?,
+        )
+    });
+    if let Err(e) = result {
+        debug!("Failed to cache account {pubkey}: {e}");
+    }
+}
+
+/// Fetch an account, from the cache when it's enabled and holds a usable entry.
+/// Accounts that don't exist are never cached.
+pub fn get_account(client: &RpcClient, pubkey: &Pubkey) -> Result<Option<Account>> {
+    if !cache_enabled() {
+        return Ok(client
+            .get_account_with_commitment(pubkey, client.commitment())?
+            .value);
+    }
+    if let Some(account) = read_account(client, pubkey) {
+        return Ok(Some(account));
+    }
+
+    let response = client.get_account_with_commitment(pubkey, client.commitment())?;
+    if let Some(account) = &response.value {
+        write_account(pubkey, response.context.slot, account);
+    }
+    Ok(response.value)
+}
+
+/// Like `RpcClient::get_account_data`, but read through the cache.
+pub fn get_account_data(client: &RpcClient, pubkey: &Pubkey) -> Result<Vec<u8>> {
+    get_account(client, pubkey)?
+        .map(|account| account.data)
+        .ok_or_else(|| anyhow!("Account {pubkey} not found"))
+}
+
+/// Fetch accounts in batches, only asking the RPC for the ones the cache can't serve.
+pub fn get_multiple_accounts(
+    client: &RpcClient,
+    pubkeys: &[Pubkey],
+) -> Result<Vec<Option<Account>>> {
+    let mut accounts: Vec<Option<Account>> = if cache_enabled() {
+        pubkeys.iter().map(|p| read_account(client, p)).collect()
+    } else {
+        vec![None; pubkeys.len()]
+    };
+
+    let missing: Vec<usize> = (0..pubkeys.len())
+        .filter(|i| accounts[*i].is_none())
+        .collect();
+
+    for chunk in missing.chunks(MAX_MULTIPLE_ACCOUNTS) {
+        let keys: Vec<Pubkey> = chunk.iter().map(|i| pubkeys[*i]).collect();
+        let response = client.get_multiple_accounts_with_commitment(&keys, client.commitment())?;
+
+        for (i, account) in chunk.iter().zip(response.value) {
+            if let Some(account) = &account {
+                if cache_enabled() {
+                    write_account(&pubkeys[*i], response.context.slot, account);
+                }
+            }
+            accounts[*i] = account;
+        }
+    }
+
+    Ok(accounts)
+}
+
+fn is_content_addressed(uri: &str) -> bool {
+    uri.starts_with("ipfs://")
+        || uri.starts_with("ar://")
+        || CONTENT_ADDRESSED_HOSTS
+            .iter()
+            .any(|host| uri.contains(&format!("://{host}/")) || uri.contains(&format!(".{host}/")))
+}
+
+/// Fetch off-chain JSON, caching it when the URI is content-addressed.
+pub fn get_json(uri: &str) -> Result<Value> {
+    let path = if cache_enabled() && is_content_addressed(uri) {
+        Some(cache_dir("json")?.join(format!("{}.json", hash(uri.as_bytes()))))
+    } else {
+        None
+    };
+
+    if let Some(json) = path
+        .as_ref()
+        .and_then(|path| fs::read(path).ok())
+        .and_then(|data| serde_json::from_slice::<Value>(&data).ok())
+    {
+        return Ok(json);
+    }
+
+    let json: Value = reqwest::blocking::get(uri)?.error_for_status()?.json()?;
+    if let Some(path) = &path {
+        if let Err(e) = write_entry(path, &serde_json::to_vec(&json)?) {
+            debug!("Failed to cache JSON for {uri}: {e}");
+        }
+    }
+    Ok(json)
+}
@@ -0,0 +1,351 @@
+//! Create, update and decode Token Auth Rules rule sets, the accounts pNFTs point to with
+//! `--rule-set`.
+//!
+//! Rule sets are defined in a JSON or TOML file with the rule set's name and a rule for each
+//! operation, using the same names as the Token Auth Rules `Rule` enum. Pubkeys are written
+//! as base58 strings:
+//!
+//! ```json
+//! {
+//!   "name": "my-royalties",
+//!   "operations": {
+//!     "Transfer:Owner": { "ProgramOwnedList": { "programs": ["..."], "field": "Destination" } },
+//!     "Delegate:Transfer": "Pass"
+//!   }
+//! }
+//! ```
+
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+use anyhow::{anyhow, Result};
+use metaboss_lib::{data::Priority, decode::decode_rule_set};
+use mpl_token_auth_rules::{
+    instruction::{
+        builders::{CreateOrUpdateBuilder, WriteToBufferBuilder},
+        CreateOrUpdateArgs, InstructionBuilder, WriteToBufferArgs,
+    },
+    pda::{find_buffer_address, find_rule_set_address},
+    state::{Rule, RuleSetV1, CHUNK_SIZE},
+    types::MAX_NAME_LENGTH,
+};
+use serde::Deserialize;
+use serde_json::{json, Map, Value};
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::{pubkey::Pubkey, signer::Signer};
+use structopt::StructOpt;
+
+use crate::{
+    files::write_json,
+    parse::{parse_keypair, parse_solana_config},
+    pipeline::Pipeline,
+    spinner::create_progress_bar,
+};
+
+// Rule sets larger than this are written to a buffer account in chunks first.
+const MAX_INLINE_RULE_SET: usize = 800;
+
+// Fields of the `Rule` variants that hold a pubkey or a list of pubkeys.
+const PUBKEY_FIELDS: &[&str] = &["account", "authority", "owner", "program", "pubkey"];
+const PUBKEY_LIST_FIELDS: &[&str] = &["programs", "pubkeys"];
+
+#[derive(Debug, StructOpt)]
+pub enum RulesSubcommands {
+    /// Create a rule set from a JSON or TOML definition
+    #[structopt(name = "create")]
+    Create {
+        /// Path to the keypair that owns the rule set and pays for it
+        #[structopt(short, long)]
+        keypair: Option<String>,
+
+        /// Rule set definition file, .json or .toml
+        definition: PathBuf,
+
+        /// Priority of the transaction: higher priority costs more.
+        /// See https://metaboss.rs/priority-fees.html for more details.
+        #[structopt(short = "P", long, default_value = "none")]
+        priority: Priority,
+    },
+    /// Write a new revision of an existing rule set from a JSON or TOML definition
+    #[structopt(name = "update")]
+    Update {
+        /// Path to the keypair that owns the rule set
+        #[structopt(short, long)]
+        keypair: Option<String>,
+
+        /// Rule set definition file, .json or .toml
+        definition: PathBuf,
+
+        /// Priority of the transaction: higher priority costs more.
+        /// See https://metaboss.rs/priority-fees.html for more details.
+        #[structopt(short = "P", long, default_value = "none")]
+        priority: Priority,
+    },
+    /// Print a rule set account as a definition
+    #[structopt(name = "decode")]
+    Decode {
+        /// Rule set address
+        rule_set: Pubkey,
+
+        /// Revision to decode, defaults to the latest
+        #[structopt(long)]
+        revision: Option<usize>,
+
+        /// Write the definition to this file instead of printing it
+        #[structopt(short, long)]
+        output: Option<PathBuf>,
+    },
+}
+
+pub fn process_rules(client: RpcClient, commands: RulesSubcommands) -> Result<()> {
+    match commands {
+        RulesSubcommands::Create {
+            keypair,
+            definition,
+            priority,
+        } => write_rule_set(&client, keypair, definition, priority, false),
+        RulesSubcommands::Update {
+            keypair,
+            definition,
+            priority,
+        } => write_rule_set(&client, keypair, definition, priority, true),
+        RulesSubcommands::Decode {
+            rule_set,
+            revision,
+            output,
+        } => decode(&client, rule_set, revision, output),
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct RuleSetDefinition {
+    name: String,
+    operations: Map<String, Value>,
+}
+
+/// Read a definition file, JSON or TOML by its extension.
+fn read_definition(path: &Path) -> Result<RuleSetDefinition> {
+    let contents =
+        fs::read_to_string(path).map_err(|e| anyhow!("Failed to read {}: {e}", path.display()))?;
+    let definition = match path.extension().and_then(|e| e.to_str()) {
+        Some("toml") => toml::from_str(&contents)?,
+        _ => serde_json::from_str(&contents)?,
+    };
+    Ok(definition)
+}
+
+/// Swap base58 pubkeys for the byte arrays `Rule` deserializes pubkeys from.
+fn pubkeys_to_bytes(value: &mut Value) -> Result<()> {
+    let to_bytes = |v: &Value| -> Result<Value> {
+        match v {
+            Value::String(s) => {
+                let pubkey =
+                    Pubkey::from_str(s).map_err(|_| anyhow!("Invalid pubkey in rule: {s}"))?;
+                Ok(Value::from(pubkey.to_bytes().to_vec()))
+            }
+            v => Ok(v.clone()),
+        }
+    };
+
+    match value {
+        Value::Object(map) => {
+            for (key, v) in map.iter_mut() {
+                if PUBKEY_FIELDS.contains(&key.as_str()) {
+                    *v = to_bytes(v)?;
+                } else if PUBKEY_LIST_FIELDS.contains(&key.as_str()) {
+                    if let Value::Array(items) = v {
+                        for item in items.iter_mut() {
+                            *item = to_bytes(item)?;
+                        }
+                    }
+                } else {
+                    pubkeys_to_bytes(v)?;
+                }
+            }
+        }
+        Value::Array(items) => {
+            for item in items.iter_mut() {
+                pubkeys_to_bytes(item)?;
+            }
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+/// Swap pubkey byte arrays back to base58 strings for printing.
+fn pubkeys_to_strings(value: &mut Value) {
+    let to_string = |v: &Value| -> Value {
+        let bytes: Option<Vec<u8>> = v.as_array().and_then(|items| {
+            items
+                .iter()
+                .map(|b| b.as_u64().and_then(|b| u8::try_from(b).ok()))
+                .collect()
+        });
+        match bytes.and_then(|b| Pubkey::try_from(b.as_slice()).ok()) {
+            Some(pubkey) => Value::String(pubkey.to_string()),
+            None => v.clone(),
+        }
+    };
+
+    match value {
+        Value::Object(map) => {
+            for (key, v) in map.iter_mut() {
+                if PUBKEY_FIELDS.contains(&key.as_str()) {
+                    *v = to_string(v);
+                } else if PUBKEY_LIST_FIELDS.contains(&key.as_str()) {
+                    if let Value::Array(items) = v {
+                        for item in items.iter_mut() {
+                            *item = to_string(item);
+                        }
+                    }
+                } else {
+                    pubkeys_to_strings(v);
+                }
+            }
+        }
+        Value::Array(items) => items.iter_mut().for_each(pubkeys_to_strings),
+        _ => {}
+    }
+}
+
+/// Build the rule set the definition describes, owned by `owner`.
+fn build_rule_set(definition: RuleSetDefinition, owner: Pubkey) -> Result<RuleSetV1> {
+    if definition.name.len() > MAX_NAME_LENGTH {
+        return Err(anyhow!(
+            "Rule set name is longer than {MAX_NAME_LENGTH} bytes: {}",
+            definition.name
+        ));
+    }
+
+    let mut rule_set = RuleSetV1::new(definition.name, owner);
+    for (operation, mut rule) in definition.operations {
+        pubkeys_to_bytes(&mut rule)?;
+        let rule: Rule = serde_json::from_value(rule)
+            .map_err(|e| anyhow!("Invalid rule for operation {operation}: {e}"))?;
+        rule_set
+            .add(operation.clone(), rule)
+            .map_err(|e| anyhow!("Failed to add operation {operation}: {e}"))?;
+    }
+    Ok(rule_set)
+}
+
+fn write_rule_set(
+    client: &RpcClient,
+    keypair: Option<String>,
+    definition: PathBuf,
+    priority: Priority,
+    update: bool,
+) -> Result<()> {
+    let keypair = parse_keypair(keypair, parse_solana_config());
+    let owner = keypair.pubkey();
+
+    let definition = read_definition(&definition)?;
+    let name = definition.name.clone();
+    let rule_set = build_rule_set(definition, owner)?;
+    let serialized = rmp_serde::to_vec(&rule_set)?;
+    if serialized.len() > CHUNK_SIZE {
+        return Err(anyhow!(
+            "Rule set is {} bytes, larger than the {CHUNK_SIZE} bytes supported",
+            serialized.len()
+        ));
+    }
+
+    let (rule_set_pda, _) = find_rule_set_address(owner, name.clone());
+    let exists = client
+        .get_account_with_commitment(&rule_set_pda, client.commitment())?
+        .value
+        .is_some();
+    match (update, exists) {
+        (false, true) => {
+            return Err(anyhow!(
+                "Rule set {name} already exists at {rule_set_pda}, use `rules update` to change it"
+            ))
+        }
+        (true, false) => {
+            return Err(anyhow!(
+                "No rule set {name} owned by {owner}, use `rules create` to create it"
+            ))
+        }
+        _ => {}
+    }
+
+    let pipeline = Pipeline::with_priority(priority);
+    let mut builder = CreateOrUpdateBuilder::new();
+    builder.payer(owner).rule_set_pda(rule_set_pda);
+
+    // Large rule sets don't fit in a transaction, so they're copied from a buffer account.
+    let serialized_rule_set = if serialized.len() > MAX_INLINE_RULE_SET {
+        let (buffer_pda, _) = find_buffer_address(owner);
+        let chunks: Vec<&[u8]> = serialized.chunks(MAX_INLINE_RULE_SET).collect();
+        let pb = create_progress_bar("Writing rule set to buffer...", chunks.len() as u64);
+        for (i, chunk) in chunks.into_iter().enumerate() {
+            let ix = WriteToBufferBuilder::new()
+                .payer(owner)
+                .buffer_pda(buffer_pda)
+                .build(WriteToBufferArgs::V1 {
+                    serialized_rule_set: chunk.to_vec(),
+                    overwrite: i == 0,
+                })
+                .map_err(|e| anyhow!("Failed to build write to buffer instruction: {e}"))?
+                .instruction();
+            pipeline.run(client, &[&keypair], vec![ix])?;
+            pb.inc(1);
+        }
+        pb.finish_and_clear();
+        builder.buffer_pda(buffer_pda);
+        vec![]
+    } else {
+        serialized
+    };
+
+    let ix = builder
+        .build(CreateOrUpdateArgs::V1 {
+            serialized_rule_set,
+        })
+        .map_err(|e| anyhow!("Failed to build create or update instruction: {e}"))?
+        .instruction();
+    let signature = pipeline.run(client, &[&keypair], vec![ix])?;
+
+    let action = if update { "Updated" } else { "Created" };
+    println!("{action} rule set {name} at {rule_set_pda}");
+    println!("Signature: {signature}");
+
+    Ok(())
+}
+
+fn decode(
+    client: &RpcClient,
+    rule_set: Pubkey,
+    revision: Option<usize>,
+    output: Option<PathBuf>,
+) -> Result<()> {
+    let decoded = decode_rule_set(client, &rule_set, revision)?;
+
+    let operations: BTreeMap<&String, &Rule> = decoded.operations.iter().collect();
+    let mut operations = serde_json::to_value(operations)?;
+    pubkeys_to_strings(&mut operations);
+
+    // The name and owner fields aren't public, so they're read back from the serialized form.
+    let mut definition = serde_json::to_value(&decoded)?;
+    pubkeys_to_strings(&mut definition);
+    let json = json!({
+        "name": definition["ruleSetName"],
+        "owner": definition["owner"],
+        "operations": operations,
+    });
+
+    match output {
+        Some(path) => {
+            write_json(&path, &json)?;
+            println!("Rule set written to {}", path.display());
+        }
+        None => println!("{}", serde_json::to_string_pretty(&json)?),
+    }
+
+    Ok(())
+}
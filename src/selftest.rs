@@ -0,0 +1,349 @@
+//! End-to-end checks of a Metaboss build against a local `solana-test-validator`.
+//!
+//! Mints an NFT, then decodes, updates, snapshots and burns it, checking each step reads
+//! back what was written. The validator needs Token Metadata cloned from mainnet, e.g.
+//! `solana-test-validator --clone metaqbxxUerdq28cj1RbAWkYQm3ybzjb6a8bt518x1s --url mainnet-beta`.
+
+use std::{
+    path::PathBuf,
+    time::{Duration, Instant},
+};
+
+use anyhow::{anyhow, Result};
+use metaboss_lib::{
+    burn::{burn_asset, BurnAssetArgs},
+    data::Priority,
+    decode::decode_metadata_from_mint,
+    derive::derive_metadata_pda,
+    mint::{mint_asset, AssetData, MintAssetArgs},
+    snapshot::get_metadata_accounts_by_update_authority,
+    update::{update_asset, UpdateAssetArgs, V1UpdateArgs},
+};
+use mpl_token_metadata::{
+    accounts::Metadata,
+    types::{Creator, Data, PrintSupply, TokenStandard},
+    ID as TOKEN_METADATA_PROGRAM_ID,
+};
+use serde::Serialize;
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::{
+    native_token::LAMPORTS_PER_SOL,
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+};
+use spl_associated_token_account::get_associated_token_address;
+
+use crate::{
+    files::write_json,
+    parse::{parse_keypair, parse_solana_config},
+    update::trim_padding,
+};
+
+const MAINNET_GENESIS_HASH: &str = "5eykt4UsFv8P8NJdTREpY1vzqKqZKvdpKuc147dw2N9d";
+const MIN_BALANCE: u64 = LAMPORTS_PER_SOL;
+const AIRDROP_AMOUNT: u64 = 2 * LAMPORTS_PER_SOL;
+
+const TEST_NAME: &str = "Metaboss Selftest";
+const TEST_SYMBOL: &str = "MBST";
+const TEST_URI: &str = "https://example.com/selftest.json";
+const TEST_UPDATED_URI: &str = "https://example.com/selftest-updated.json";
+const TEST_SFBP: u16 = 500;
+
+pub struct SelftestArgs {
+    pub client: RpcClient,
+    pub keypair: Option<String>,
+    pub output: Option<PathBuf>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CheckResult {
+    pub check: &'static str,
+    pub passed: bool,
+    pub skipped: bool,
+    pub millis: u128,
+    pub detail: String,
+}
+
+/// Runs each check in turn, skipping the rest once one that they depend on fails.
+struct Runner {
+    results: Vec<CheckResult>,
+    failed: bool,
+}
+
+impl Runner {
+    fn run<T>(
+        &mut self,
+        check: &'static str,
+        f: impl FnOnce() -> Result<(T, String)>,
+    ) -> Option<T> {
+        if self.failed {
+            self.results.push(CheckResult {
+                check,
+                passed: false,
+                skipped: true,
+                millis: 0,
+                detail: "skipped after an earlier failure".to_string(),
+            });
+            return None;
+        }
+
+        let start = Instant::now();
+        let result = f();
+        let millis = start.elapsed().as_millis();
+        let (value, passed, detail) = match result {
+            Ok((value, detail)) => (Some(value), true, detail),
+            Err(e) => (None, false, e.to_string()),
+        };
+        self.failed = !passed;
+        self.results.push(CheckResult {
+            check,
+            passed,
+            skipped: false,
+            millis,
+            detail,
+        });
+        value
+    }
+}
+
+fn check_validator(client: &RpcClient) -> Result<((), String)> {
+    let genesis = client.get_genesis_hash()?;
+    if genesis.to_string() == MAINNET_GENESIS_HASH {
+        return Err(anyhow!(
+            "refusing to run against mainnet, point --rpc at a local solana-test-validator"
+        ));
+    }
+    let version = client.get_version()?;
+    Ok(((), format!("solana-core {}", version.solana_core)))
+}
+
+fn check_programs(client: &RpcClient) -> Result<((), String)> {
+    let program = client
+        .get_account(&TOKEN_METADATA_PROGRAM_ID)
+        .map_err(|_| {
+            anyhow!(
+                "Token Metadata isn't deployed, start the validator with --clone {TOKEN_METADATA_PROGRAM_ID} --url mainnet-beta"
+            )
+        })?;
+    if !program.executable {
+        return Err(anyhow!("{TOKEN_METADATA_PROGRAM_ID} isn't executable"));
+    }
+    Ok(((), format!("Token Metadata at {TOKEN_METADATA_PROGRAM_ID}")))
+}
+
+fn fund(client: &RpcClient, payer: &Pubkey) -> Result<((), String)> {
+    let balance = client.get_balance(payer)?;
+    if balance >= MIN_BALANCE {
+        return Ok(((), format!("{payer} has {balance} lamports")));
+    }
+    let signature = client.request_airdrop(payer, AIRDROP_AMOUNT)?;
+    client.poll_for_signature(&signature)?;
+    Ok((
+        (),
+        format!("airdropped {AIRDROP_AMOUNT} lamports to {payer}"),
+    ))
+}
+
+fn mint(client: &RpcClient, keypair: &Keypair) -> Result<(Pubkey, String)> {
+    let asset_data = AssetData {
+        name: TEST_NAME.to_string(),
+        symbol: TEST_SYMBOL.to_string(),
+        uri: TEST_URI.to_string(),
+        seller_fee_basis_points: TEST_SFBP,
+        creators: Some(vec![Creator {
+            address: keypair.pubkey(),
+            verified: true,
+            share: 100,
+        }]),
+        primary_sale_happened: false,
+        is_mutable: true,
+        token_standard: TokenStandard::NonFungible,
+        collection: None,
+        uses: None,
+        collection_details: None,
+        rule_set: None,
+    };
+    let result = mint_asset(
+        client,
+        MintAssetArgs::V1 {
+            payer: None,
+            authority: keypair,
+            receiver: keypair.pubkey(),
+            mint: None,
+            asset_data,
+            print_supply: Some(PrintSupply::Zero),
+            mint_decimals: Some(0),
+            amount: 1,
+            authorization_data: None,
+            priority: Priority::None,
+        },
+    )?;
+    Ok((result.mint, format!("minted {}", result.mint)))
+}
+
+fn decode(client: &RpcClient, mint: &Pubkey, owner: &Pubkey) -> Result<(Metadata, String)> {
+    let md = decode_metadata_from_mint(client, mint.to_string())?;
+    let mut mismatches = vec![];
+    if trim_padding(&md.name) != TEST_NAME {
+        mismatches.push(format!("name {:?}", trim_padding(&md.name)));
+    }
+    if trim_padding(&md.symbol) != TEST_SYMBOL {
+        mismatches.push(format!("symbol {:?}", trim_padding(&md.symbol)));
+    }
+    if trim_padding(&md.uri) != TEST_URI {
+        mismatches.push(format!("uri {:?}", trim_padding(&md.uri)));
+    }
+    if md.seller_fee_basis_points != TEST_SFBP {
+        mismatches.push(format!("seller fee {}", md.seller_fee_basis_points));
+    }
+    if md.update_authority != *owner {
+        mismatches.push(format!("update authority {}", md.update_authority));
+    }
+    let creator_verified = matches!(
+        md.creators.as_ref().and_then(|c| c.first()),
+        Some(c) if c.address == *owner && c.verified
+    );
+    if !creator_verified {
+        mismatches.push("creator not verified".to_string());
+    }
+
+    if !mismatches.is_empty() {
+        return Err(anyhow!("decoded {}", mismatches.join(", ")));
+    }
+    Ok((md, "metadata matches what was minted".to_string()))
+}
+
+fn update(
+    client: &RpcClient,
+    keypair: &Keypair,
+    mint: &Pubkey,
+    md: Metadata,
+) -> Result<((), String)> {
+    let update_args = V1UpdateArgs {
+        data: Some(Data {
+            name: md.name,
+            symbol: md.symbol,
+            uri: TEST_UPDATED_URI.to_string(),
+            seller_fee_basis_points: md.seller_fee_basis_points,
+            creators: md.creators,
+        }),
+        ..Default::default()
+    };
+    update_asset(
+        client,
+        UpdateAssetArgs::V1 {
+            payer: None,
+            authority: keypair,
+            mint: *mint,
+            token: None::<Pubkey>,
+            delegate_record: None::<Pubkey>,
+            update_args,
+            priority: Priority::None,
+        },
+    )?;
+
+    let updated = decode_metadata_from_mint(client, mint.to_string())?;
+    if trim_padding(&updated.uri) != TEST_UPDATED_URI {
+        return Err(anyhow!(
+            "uri reads back as {:?}",
+            trim_padding(&updated.uri)
+        ));
+    }
+    Ok(((), "uri updated and read back".to_string()))
+}
+
+fn snapshot(client: &RpcClient, authority: &Pubkey, mint: &Pubkey) -> Result<((), String)> {
+    let accounts = get_metadata_accounts_by_update_authority(client, &authority.to_string())?;
+    let metadata = derive_metadata_pda(mint);
+    if !accounts.iter().any(|(pubkey, _)| *pubkey == metadata) {
+        return Err(anyhow!(
+            "{metadata} missing from the {} accounts of update authority {authority}",
+            accounts.len()
+        ));
+    }
+    Ok((
+        (),
+        format!("found among {} metadata accounts", accounts.len()),
+    ))
+}
+
+fn burn(client: &RpcClient, keypair: &Keypair, mint: &Pubkey) -> Result<((), String)> {
+    let token = get_associated_token_address(&keypair.pubkey(), mint);
+    burn_asset(
+        client,
+        BurnAssetArgs::V1 {
+            authority: keypair,
+            mint: *mint,
+            token,
+            amount: 1,
+        },
+    )?;
+
+    let metadata = derive_metadata_pda(mint);
+    let remaining = client
+        .get_account_with_commitment(&metadata, client.commitment())?
+        .value;
+    if matches!(remaining, Some(a) if a.lamports > 0) {
+        return Err(anyhow!("metadata account {metadata} still open after burn"));
+    }
+    Ok(((), "burned and metadata account closed".to_string()))
+}
+
+fn print_matrix(results: &[CheckResult]) {
+    let width = results.iter().map(|r| r.check.len()).max().unwrap_or(0);
+    println!("{:width$}  {:6}  {:>8}  detail", "check", "result", "time");
+    for r in results {
+        let status = match (r.passed, r.skipped) {
+            (true, _) => "pass",
+            (false, true) => "skip",
+            (false, false) => "FAIL",
+        };
+        let time = format!(
+            "{:.2}s",
+            Duration::from_millis(r.millis as u64).as_secs_f64()
+        );
+        println!("{:width$}  {status:6}  {time:>8}  {}", r.check, r.detail);
+    }
+}
+
+/// Run every check against the RPC's validator and print a pass/fail matrix. Fails if any
+/// check didn't pass, so it can gate CI.
+pub fn selftest(args: SelftestArgs) -> Result<()> {
+    let keypair = parse_keypair(args.keypair, parse_solana_config());
+    let client = &args.client;
+    let owner = keypair.pubkey();
+
+    let mut runner = Runner {
+        results: vec![],
+        failed: false,
+    };
+    runner.run("validator", || check_validator(client));
+    runner.run("programs", || check_programs(client));
+    runner.run("fund", || fund(client, &owner));
+    // Later checks are skipped once one fails, so the placeholder mint is never used.
+    let mint_pubkey = runner
+        .run("mint", || mint(client, &keypair))
+        .unwrap_or_default();
+    let md = runner.run("decode", || decode(client, &mint_pubkey, &owner));
+    runner.run("update", || {
+        let md = md.ok_or_else(|| anyhow!("nothing decoded"))?;
+        update(client, &keypair, &mint_pubkey, md)
+    });
+    runner.run("snapshot", || snapshot(client, &owner, &mint_pubkey));
+    runner.run("burn", || burn(client, &keypair, &mint_pubkey));
+
+    print_matrix(&runner.results);
+    if let Some(path) = &args.output {
+        write_json(path, &runner.results)?;
+    }
+
+    let failed = runner.results.iter().filter(|r| !r.passed).count();
+    if failed > 0 {
+        return Err(anyhow!(
+            "{failed} of {} checks didn't pass",
+            runner.results.len()
+        ));
+    }
+    println!("All {} checks passed", runner.results.len());
+    Ok(())
+}
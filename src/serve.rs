@@ -0,0 +1,498 @@
+//! HTTP server exposing Metaboss operations to internal tools, so they can reuse its
+//! account parsing without shelling out.
+//!
+//! Every endpoint except `/health` needs the server token as a bearer token. The
+//! endpoints that sign transactions are only enabled when a signing token is set, and
+//! also need it in the `X-Signing-Token` header.
+
+use std::{
+    collections::HashMap,
+    io::{BufRead, BufReader, Read, Write},
+    net::{TcpListener, TcpStream},
+    str::FromStr,
+    sync::{mpsc, Arc, Mutex},
+    thread,
+    time::Duration,
+};
+
+use anyhow::{anyhow, Result};
+use log::{debug, info, warn};
+use metaboss_lib::data::Priority;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::{pubkey::Pubkey, signature::Keypair, signer::Signer};
+
+use crate::audit::supply_audit;
+use crate::decode::{decode, get_metadata_pda, write_metadata};
+use crate::derive::{
+    derive_edition_marker_pda, derive_edition_pda, derive_metadata_pda, derive_token_account_pda,
+};
+use crate::limiter::concurrency_or;
+use crate::parse::{parse_keypair, parse_solana_config};
+use crate::pipeline::Pipeline;
+use crate::sign::sign;
+use crate::snapshot::get_mint_accounts;
+use crate::update::build_update_uri_ix;
+
+// Request bodies are small JSON documents, anything bigger is refused.
+const MAX_BODY_SIZE: usize = 10 * 1024 * 1024;
+// The request line and each header must fit in this many bytes.
+const MAX_LINE_LENGTH: usize = 8 * 1024;
+// Requests with more headers than this are refused.
+const MAX_HEADERS: usize = 100;
+// Connections served at once unless the global `--concurrency` is set. Further
+// connections wait to be accepted.
+const DEFAULT_WORKERS: usize = 16;
+// Idle or stalled connections are dropped so they can't hold a worker forever.
+const CONNECTION_TIMEOUT: Duration = Duration::from_secs(30);
+
+pub struct ServeArgs {
+    pub client: RpcClient,
+    pub listen: String,
+    pub token: String,
+    pub signing_token: Option<String>,
+    pub keypair: Option<String>,
+}
+
+struct Server {
+    client: RpcClient,
+    token: String,
+    signing_token: Option<String>,
+    keypair: Option<Keypair>,
+}
+
+struct Request {
+    method: String,
+    path: String,
+    query: HashMap<String, String>,
+    headers: HashMap<String, String>,
+    body: Vec<u8>,
+}
+
+impl Request {
+    fn flag(&self, name: &str) -> bool {
+        matches!(
+            self.query.get(name).map(String::as_str),
+            Some("") | Some("true") | Some("1")
+        )
+    }
+
+    fn json<T: for<'de> Deserialize<'de>>(&self) -> Result<T, HttpError> {
+        serde_json::from_slice(&self.body)
+            .map_err(|e| HttpError::bad_request(format!("Invalid request body: {e}")))
+    }
+}
+
+struct HttpError {
+    status: &'static str,
+    message: String,
+}
+
+impl HttpError {
+    fn new<S: ToString>(status: &'static str, message: S) -> Self {
+        Self {
+            status,
+            message: message.to_string(),
+        }
+    }
+
+    fn bad_request<S: ToString>(message: S) -> Self {
+        Self::new("400 Bad Request", message)
+    }
+
+    fn internal<S: ToString>(message: S) -> Self {
+        Self::new("500 Internal Server Error", message)
+    }
+}
+
+fn parse_pubkey(value: &str) -> Result<Pubkey, HttpError> {
+    Pubkey::from_str(value).map_err(|_| HttpError::bad_request(format!("Invalid address: {value}")))
+}
+
+/// Compare tokens without returning early, so their contents can't be guessed from timing.
+fn token_matches(expected: &str, given: Option<&String>) -> bool {
+    let given = match given {
+        Some(given) => given.as_bytes(),
+        None => return false,
+    };
+    let expected = expected.as_bytes();
+    expected.len() == given.len()
+        && expected
+            .iter()
+            .zip(given)
+            .fold(0, |diff, (a, b)| diff | (a ^ b))
+            == 0
+}
+
+#[derive(Deserialize)]
+struct AuditSupplyBody {
+    hashlist: Vec<String>,
+    creator: String,
+    #[serde(default)]
+    position: usize,
+    #[serde(default)]
+    v2: bool,
+    #[serde(default)]
+    v3: bool,
+}
+
+#[derive(Deserialize)]
+struct SignMetadataBody {
+    mint: String,
+}
+
+#[derive(Deserialize)]
+struct UpdateUriBody {
+    mint: String,
+    uri: String,
+    #[serde(default)]
+    priority: Option<String>,
+}
+
+impl Server {
+    fn handle(&self, request: &Request) -> Result<Value, HttpError> {
+        let segments: Vec<&str> = request
+            .path
+            .trim_matches('/')
+            .split('/')
+            .filter(|s| !s.is_empty())
+            .collect();
+
+        if segments == ["health"] {
+            return Ok(json!({ "ok": true }));
+        }
+
+        let bearer = request
+            .headers
+            .get("authorization")
+            .and_then(|h| h.strip_prefix("Bearer "))
+            .map(str::to_string);
+        if !token_matches(&self.token, bearer.as_ref()) {
+            return Err(HttpError::new(
+                "401 Unauthorized",
+                "Missing or invalid token",
+            ));
+        }
+
+        match (request.method.as_str(), segments.as_slice()) {
+            ("GET", ["decode", "mint", mint]) => self.decode_mint(request, mint),
+            ("GET", ["derive", kind, rest @ ..]) => derive(kind, rest),
+            ("GET", ["snapshot", "mints"]) => self.snapshot_mints(request),
+            ("POST", ["audit", "supply"]) => self.audit_supply(request.json()?),
+            ("POST", ["sign", "metadata"]) => {
+                let keypair = self.signing_keypair(request)?;
+                self.sign_metadata(keypair, request.json()?)
+            }
+            ("POST", ["update", "uri"]) => {
+                let keypair = self.signing_keypair(request)?;
+                self.update_uri(keypair, request.json()?)
+            }
+            _ => Err(HttpError::new(
+                "404 Not Found",
+                format!("No endpoint for {} {}", request.method, request.path),
+            )),
+        }
+    }
+
+    fn signing_keypair(&self, request: &Request) -> Result<&Keypair, HttpError> {
+        let (signing_token, keypair) = match (&self.signing_token, &self.keypair) {
+            (Some(signing_token), Some(keypair)) => (signing_token, keypair),
+            _ => {
+                return Err(HttpError::new(
+                    "403 Forbidden",
+                    "Signing endpoints are disabled on this server",
+                ))
+            }
+        };
+        if !token_matches(signing_token, request.headers.get("x-signing-token")) {
+            return Err(HttpError::new(
+                "403 Forbidden",
+                "Missing or invalid signing token",
+            ));
+        }
+        Ok(keypair)
+    }
+
+    fn decode_mint(&self, request: &Request, mint: &str) -> Result<Value, HttpError> {
+        parse_pubkey(mint)?;
+        let metadata = decode(&self.client, mint).map_err(HttpError::internal)?;
+
+        let mut buf = vec![];
+        write_metadata(
+            &self.client,
+            &mut buf,
+            metadata,
+            request.flag("full"),
+            request.flag("edition_info"),
+            request.flag("off_chain"),
+        )
+        .map_err(HttpError::internal)?;
+        serde_json::from_slice(&buf).map_err(HttpError::internal)
+    }
+
+    fn snapshot_mints(&self, request: &Request) -> Result<Value, HttpError> {
+        let creator = request.query.get("creator").cloned();
+        let update_authority = request.query.get("update_authority").cloned();
+        if creator.is_some() == update_authority.is_some() {
+            return Err(HttpError::bad_request(
+                "Pass exactly one of creator or update_authority",
+            ));
+        }
+        let position = match request.query.get("position") {
+            Some(position) => position
+                .parse()
+                .map_err(|_| HttpError::bad_request("Invalid position"))?,
+            None => 0,
+        };
+
+        let mints = get_mint_accounts(
+            &self.client,
+            &creator,
+            position,
+            update_authority,
+            request.flag("allow_unverified"),
+            request.flag("v2"),
+            request.flag("v3"),
+        )
+        .map_err(HttpError::internal)?;
+        Ok(json!(mints))
+    }
+
+    fn audit_supply(&self, body: AuditSupplyBody) -> Result<Value, HttpError> {
+        let audit = supply_audit(
+            &self.client,
+            &body.hashlist,
+            body.creator,
+            body.position,
+            body.v2,
+            body.v3,
+        )
+        .map_err(HttpError::internal)?;
+        serde_json::to_value(audit).map_err(HttpError::internal)
+    }
+
+    fn sign_metadata(&self, keypair: &Keypair, body: SignMetadataBody) -> Result<Value, HttpError> {
+        let mint = parse_pubkey(&body.mint)?;
+        let signature =
+            sign(&self.client, keypair, get_metadata_pda(mint)).map_err(HttpError::internal)?;
+        info!("Signed metadata for {mint}: {signature}");
+        Ok(json!({ "mint": body.mint, "signature": signature.to_string() }))
+    }
+
+    fn update_uri(&self, keypair: &Keypair, body: UpdateUriBody) -> Result<Value, HttpError> {
+        parse_pubkey(&body.mint)?;
+        let priority = match &body.priority {
+            Some(priority) => Priority::from_str(priority).map_err(HttpError::bad_request)?,
+            None => Priority::None,
+        };
+        let mint_values = HashMap::from([(body.mint.clone(), body.uri.clone())]);
+
+        let ix = match build_update_uri_ix(&self.client, keypair, &body.mint, &mint_values)
+            .map_err(HttpError::internal)?
+        {
+            Some(ix) => ix,
+            None => return Ok(json!({ "mint": body.mint, "signature": null })),
+        };
+        let signature = Pipeline::with_priority(priority)
+            .run(&self.client, &[keypair], vec![ix])
+            .map_err(HttpError::internal)?;
+        info!("Updated uri for {}: {signature}", body.mint);
+        Ok(json!({ "mint": body.mint, "signature": signature.to_string() }))
+    }
+}
+
+fn derive(kind: &str, args: &[&str]) -> Result<Value, HttpError> {
+    let address = match (kind, args) {
+        ("metadata", [mint]) => derive_metadata_pda(&parse_pubkey(mint)?),
+        ("edition", [mint]) => derive_edition_pda(&parse_pubkey(mint)?),
+        ("edition-marker", [mint, edition]) => {
+            let edition = edition
+                .parse()
+                .map_err(|_| HttpError::bad_request("Invalid edition number"))?;
+            derive_edition_marker_pda(&parse_pubkey(mint)?, edition)
+        }
+        ("token-account", [mint, owner]) => {
+            derive_token_account_pda(&parse_pubkey(mint)?, &parse_pubkey(owner)?, &spl_token::ID)
+        }
+        _ => {
+            return Err(HttpError::new(
+                "404 Not Found",
+                format!("Unknown derive endpoint: {kind}"),
+            ))
+        }
+    };
+    Ok(json!({ "address": address.to_string() }))
+}
+
+fn parse_query(query: &str) -> HashMap<String, String> {
+    form_urlencoded::parse(query.as_bytes())
+        .into_owned()
+        .collect()
+}
+
+/// Read one line of at most `MAX_LINE_LENGTH` bytes.
+fn read_line(reader: &mut BufReader<TcpStream>, line: &mut String) -> Result<usize> {
+    let read = reader
+        .by_ref()
+        .take(MAX_LINE_LENGTH as u64)
+        .read_line(line)?;
+    if read == MAX_LINE_LENGTH && !line.ends_with('\n') {
+        return Err(anyhow!("Request line or header is too long"));
+    }
+    Ok(read)
+}
+
+/// Read one HTTP request from the connection, or `None` once the client closes it.
+fn read_request(reader: &mut BufReader<TcpStream>) -> Result<Option<Request>> {
+    let mut request_line = String::new();
+    if read_line(reader, &mut request_line)? == 0 {
+        return Ok(None);
+    }
+    let mut parts = request_line.split_whitespace();
+    let method = parts
+        .next()
+        .ok_or_else(|| anyhow!("Malformed request line"))?
+        .to_string();
+    let target = parts
+        .next()
+        .ok_or_else(|| anyhow!("Malformed request line"))?;
+    let (path, query) = target.split_once('?').unwrap_or((target, ""));
+
+    let mut headers = HashMap::new();
+    let mut header_count = 0;
+    loop {
+        let mut header = String::new();
+        read_line(reader, &mut header)?;
+        let header = header.trim_end();
+        if header.is_empty() {
+            break;
+        }
+        header_count += 1;
+        if header_count > MAX_HEADERS {
+            return Err(anyhow!("Too many request headers"));
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            headers.insert(name.trim().to_ascii_lowercase(), value.trim().to_string());
+        }
+    }
+
+    let content_length: usize = match headers.get("content-length") {
+        Some(length) => length.parse()?,
+        None => 0,
+    };
+    if content_length > MAX_BODY_SIZE {
+        return Err(anyhow!("Request body is too large"));
+    }
+    let mut body = vec![0; content_length];
+    reader.read_exact(&mut body)?;
+
+    Ok(Some(Request {
+        method,
+        path: path.to_string(),
+        query: parse_query(query),
+        headers,
+        body,
+    }))
+}
+
+fn write_response(stream: &mut TcpStream, status: &str, body: &[u8]) -> Result<()> {
+    write!(
+        stream,
+        "HTTP/1.1 {status}\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\r\n",
+        body.len()
+    )?;
+    stream.write_all(body)?;
+    stream.flush()?;
+    Ok(())
+}
+
+fn serve_connection(server: &Server, stream: TcpStream) -> Result<()> {
+    stream.set_read_timeout(Some(CONNECTION_TIMEOUT))?;
+    stream.set_write_timeout(Some(CONNECTION_TIMEOUT))?;
+    let mut writer = stream.try_clone()?;
+    let mut reader = BufReader::new(stream);
+
+    loop {
+        let request = match read_request(&mut reader) {
+            Ok(Some(request)) => request,
+            Ok(None) => return Ok(()),
+            Err(e) => {
+                let error = json!({ "error": e.to_string() });
+                return write_response(
+                    &mut writer,
+                    "400 Bad Request",
+                    error.to_string().as_bytes(),
+                );
+            }
+        };
+
+        let (status, body) = match server.handle(&request) {
+            Ok(body) => ("200 OK", body),
+            Err(e) => (e.status, json!({ "error": e.message })),
+        };
+        debug!("{} {} -> {status}", request.method, request.path);
+        write_response(&mut writer, status, body.to_string().as_bytes())?;
+    }
+}
+
+/// Serve the API until the process is stopped.
+pub fn serve(args: ServeArgs) -> Result<()> {
+    if args.token.is_empty() {
+        return Err(anyhow!("--token can't be empty"));
+    }
+
+    let keypair = match (&args.signing_token, &args.keypair) {
+        (Some(_), Some(keypair)) => {
+            Some(parse_keypair(Some(keypair.clone()), parse_solana_config()))
+        }
+        (Some(_), None) => {
+            return Err(anyhow!(
+                "--signing-token needs a --keypair to sign transactions with"
+            ))
+        }
+        (None, _) => None,
+    };
+
+    let listener = TcpListener::bind(&args.listen)?;
+    let server = Arc::new(Server {
+        client: args.client,
+        token: args.token,
+        signing_token: args.signing_token,
+        keypair,
+    });
+
+    println!("Serving the Metaboss API on http://{}", args.listen);
+    match &server.keypair {
+        Some(keypair) => println!("Signing endpoints enabled for {}", keypair.pubkey()),
+        None => println!("Signing endpoints disabled"),
+    }
+
+    // A fixed set of workers takes connections from a bounded queue, so a flood of
+    // connections blocks the accept loop instead of spawning a thread for each.
+    let workers = concurrency_or(DEFAULT_WORKERS);
+    let (sender, receiver) = mpsc::sync_channel::<TcpStream>(workers);
+    let receiver = Arc::new(Mutex::new(receiver));
+    for _ in 0..workers {
+        let server = server.clone();
+        let receiver = receiver.clone();
+        thread::spawn(move || loop {
+            let stream = match receiver.lock().unwrap().recv() {
+                Ok(stream) => stream,
+                Err(_) => return,
+            };
+            if let Err(e) = serve_connection(&server, stream) {
+                debug!("Connection closed: {e}");
+            }
+        });
+    }
+
+    for stream in listener.incoming() {
+        match stream {
+            Ok(stream) => sender.send(stream)?,
+            Err(e) => warn!("Failed to accept connection: {e}"),
+        }
+    }
+
+    Ok(())
+}
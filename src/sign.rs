@@ -1,19 +1,27 @@
 use anyhow::{anyhow, Result};
 use borsh::BorshDeserialize;
+use indexmap::IndexMap;
 use indicatif::ParallelProgressIterator;
-use log::{error, info};
+use log::{error, info, warn};
+use metaboss_lib::{data::Priority, decode::decode_metadata_from_mint};
 use mpl_token_metadata::{accounts::Metadata, instructions::SignMetadata};
 use rayon::prelude::*;
 use retry::{delay::Exponential, retry};
+use serde::{Deserialize, Serialize};
 use solana_client::rpc_client::RpcClient;
 use solana_sdk::{
+    hash::Hash,
+    nonce::State as NonceState,
     pubkey::Pubkey,
     signature::Signature,
     signer::{keypair::Keypair, Signer},
+    system_instruction::{advance_nonce_account, create_nonce_account, withdraw_nonce_account},
     transaction::Transaction,
 };
 use std::{
+    collections::HashSet,
     fs::File,
+    path::{Path, PathBuf},
     str::FromStr,
     sync::{
         atomic::{AtomicBool, Ordering},
@@ -21,11 +29,19 @@ use std::{
     },
 };
 
-use crate::decode::get_metadata_pda;
+use crate::changelog::Changelog;
+use crate::checkpoint;
+use crate::decode::{decode_metadata_batch, get_metadata_pda};
 use crate::derive::{derive_cmv2_pda, derive_cmv3_pda};
+use crate::files::read_json;
 use crate::limiter::create_default_rate_limiter;
+use crate::nonce::get_nonce_blockhash;
+use crate::order;
+use crate::pack::{pack_instruction_groups, send_packed, InstructionGroup};
 use crate::parse::{is_only_one_option, parse_keypair};
 use crate::snapshot::get_cm_creator_accounts;
+use crate::spinner::create_progress_bar;
+use crate::workspace::output_path;
 use crate::{constants::*, parse::parse_solana_config};
 
 pub fn sign_one(client: &RpcClient, keypair_path: Option<String>, account: String) -> Result<()> {
@@ -48,6 +64,10 @@ pub fn sign_one(client: &RpcClient, keypair_path: Option<String>, account: Strin
     Ok(())
 }
 
+/// Name that `sign all` records its progress under in the checkpoint file.
+const SIGN_COMMAND: &str = "sign-all";
+
+#[allow(clippy::too_many_arguments)]
 pub fn sign_all(
     client: &RpcClient,
     keypair_path: Option<String>,
@@ -56,16 +76,34 @@ pub fn sign_all(
     v2: bool,
     v3: bool,
     mint_accounts_file: Option<String>,
+    pack: bool,
+    keypairs: Vec<String>,
 ) -> Result<()> {
-    let solana_opts = parse_solana_config();
-    let creator_keypair = parse_keypair(keypair_path, solana_opts);
-
     if !is_only_one_option(creator, &mint_accounts_file) {
         return Err(anyhow!(
             "Must specify exactly one of --candy-machine-id or --mint-data-dir"
         ));
     }
 
+    if keypairs.len() > 1 {
+        let creators = keypairs
+            .into_iter()
+            .map(|path| parse_keypair(Some(path), None))
+            .collect::<Vec<_>>();
+        return sign_all_creators(
+            client,
+            &creators,
+            creator,
+            position,
+            v2,
+            v3,
+            mint_accounts_file,
+        );
+    }
+
+    let solana_opts = parse_solana_config();
+    let creator_keypair = parse_keypair(keypairs.into_iter().next().or(keypair_path), solana_opts);
+
     if let Some(creator) = creator {
         let creator_pubkey =
             Pubkey::from_str(creator).expect("Failed to parse pubkey from creator!");
@@ -76,6 +114,7 @@ pub fn sign_all(
                 &cmv2_creator.to_string(),
                 creator_keypair,
                 position,
+                pack,
             )?
         } else if v3 {
             let cmv3_creator = derive_cmv3_pda(&creator_pubkey);
@@ -84,15 +123,28 @@ pub fn sign_all(
                 &cmv3_creator.to_string(),
                 creator_keypair,
                 position,
+                pack,
             )?
         } else {
-            sign_candy_machine_accounts(client, creator, creator_keypair, position)?
+            sign_candy_machine_accounts(client, creator, creator_keypair, position, pack)?
         }
     } else if let Some(mint_accounts_file) = mint_accounts_file {
-        let file = File::open(mint_accounts_file)?;
-        let mint_accounts: Vec<String> = serde_json::from_reader(&file)?;
+        let mint_accounts: Vec<String> = read_json(mint_accounts_file)?;
+        let mint_accounts = order::ordered(checkpoint::remaining(SIGN_COMMAND, mint_accounts));
 
-        sign_mint_accounts(client, &creator_keypair, mint_accounts)?;
+        if pack {
+            let metadata_accounts = mint_accounts
+                .into_iter()
+                .map(|mint_account| {
+                    let account_pubkey = Pubkey::from_str(&mint_account)
+                        .map_err(|_| anyhow!("Invalid public key: {mint_account}"))?;
+                    Ok((mint_account, get_metadata_pda(account_pubkey)))
+                })
+                .collect::<Result<Vec<_>>>()?;
+            sign_packed(client, &creator_keypair, metadata_accounts)?;
+        } else {
+            sign_mint_accounts(client, &creator_keypair, mint_accounts)?;
+        }
     } else {
         unreachable!();
     }
@@ -100,6 +152,76 @@ pub fn sign_all(
     Ok(())
 }
 
+/// Sign the mints with every creator keypair that is an unverified creator on them, with all
+/// of a mint's signatures in the same transaction.
+fn sign_all_creators(
+    client: &RpcClient,
+    creators: &[Keypair],
+    creator: &Option<String>,
+    position: usize,
+    v2: bool,
+    v3: bool,
+    mint_accounts_file: Option<String>,
+) -> Result<()> {
+    let metadata: Vec<(Pubkey, Metadata)> = if let Some(creator) = creator {
+        let creator_pubkey =
+            Pubkey::from_str(creator).map_err(|_| anyhow!("Invalid creator: {creator}"))?;
+        let creator = if v2 {
+            derive_cmv2_pda(&creator_pubkey)
+        } else if v3 {
+            derive_cmv3_pda(&creator_pubkey)
+        } else {
+            creator_pubkey
+        };
+        get_cm_creator_accounts(client, &creator.to_string(), position)?
+            .into_iter()
+            .filter_map(|(metadata_pubkey, account)| {
+                let metadata =
+                    <Metadata as BorshDeserialize>::deserialize(&mut account.data.as_slice())
+                        .ok()?;
+                Some((metadata_pubkey, metadata))
+            })
+            .collect()
+    } else if let Some(mint_accounts_file) = mint_accounts_file {
+        let mint_accounts: Vec<String> = read_json(mint_accounts_file)?;
+        let mint_accounts = order::ordered(checkpoint::remaining(SIGN_COMMAND, mint_accounts));
+
+        decode_metadata_batch(client, &mint_accounts)
+            .into_iter()
+            .filter_map(|(mint, metadata)| match metadata {
+                Ok(metadata) => Some((get_metadata_pda(metadata.mint), metadata)),
+                Err(e) => {
+                    error!("Failed to decode metadata for {mint}: {e}");
+                    checkpoint::record_failed(SIGN_COMMAND, &mint, &e);
+                    None
+                }
+            })
+            .collect()
+    } else {
+        unreachable!();
+    };
+
+    let unverified: Vec<(String, Pubkey, Vec<Pubkey>)> = metadata
+        .into_iter()
+        .filter_map(|(metadata_pubkey, metadata)| {
+            let md_creators = metadata.creators?;
+            let signers: Vec<Pubkey> = creators
+                .iter()
+                .map(|c| c.pubkey())
+                .filter(|c| md_creators.iter().any(|m| m.address == *c && !m.verified))
+                .collect();
+            (!signers.is_empty()).then(|| (metadata.mint.to_string(), metadata_pubkey, signers))
+        })
+        .collect();
+
+    if unverified.is_empty() {
+        println!("No unverified metadata for these creators.");
+        return Ok(());
+    }
+    let signers: Vec<&Keypair> = creators.iter().collect();
+    send_signatures_packed(client, &signers, unverified)
+}
+
 pub fn sign(client: &RpcClient, creator: &Keypair, metadata_pubkey: Pubkey) -> Result<Signature> {
     let recent_blockhash = client.get_latest_blockhash()?;
     let ix = SignMetadata {
@@ -154,8 +276,14 @@ pub fn sign_mint_accounts(
 
             // Try to sign all accounts, print any errors that crop up.
             match sign(client, creator, metadata_pubkey) {
-                Ok(sig) => info!("{}", sig),
-                Err(e) => error!("{}", e),
+                Ok(sig) => {
+                    info!("{}", sig);
+                    checkpoint::record_done(SIGN_COMMAND, mint_account, Some(sig.to_string()));
+                }
+                Err(e) => {
+                    error!("{}", e);
+                    checkpoint::record_failed(SIGN_COMMAND, mint_account, e);
+                }
             }
         });
 
@@ -167,9 +295,32 @@ pub fn sign_candy_machine_accounts(
     creator: &str,
     signing_creator: Keypair,
     position: usize,
+    pack: bool,
 ) -> Result<()> {
     let accounts = get_cm_creator_accounts(client, creator, position)?;
 
+    if pack {
+        let unverified: Vec<(String, Pubkey)> = accounts
+            .iter()
+            .filter_map(|(metadata_pubkey, account)| {
+                let metadata =
+                    <Metadata as BorshDeserialize>::deserialize(&mut account.data.as_slice())
+                        .ok()?;
+                metadata
+                    .creators?
+                    .iter()
+                    .any(|c| c.address == signing_creator.pubkey() && !c.verified)
+                    .then(|| (metadata.mint.to_string(), *metadata_pubkey))
+            })
+            .collect();
+
+        if unverified.is_empty() {
+            println!("No unverified metadata for this creator and candy machine.");
+            return Ok(());
+        }
+        return sign_packed(client, &signing_creator, unverified);
+    }
+
     // Only sign accounts that have not been signed yet
     let signed_at_least_one_account = Arc::new(AtomicBool::new(false));
 
@@ -178,14 +329,15 @@ pub fn sign_candy_machine_accounts(
         .progress()
         .for_each(|(metadata_pubkey, account)| {
             let signed_at_least_one_account = signed_at_least_one_account.clone();
-            let metadata: Metadata =
-                match Metadata::deserialize(&mut account.data.clone().as_slice()) {
-                    Ok(metadata) => metadata,
-                    Err(_) => {
-                        error!("Account {} has no metadata", metadata_pubkey);
-                        return;
-                    }
-                };
+            let metadata: Metadata = match <Metadata as BorshDeserialize>::deserialize(
+                &mut account.data.clone().as_slice(),
+            ) {
+                Ok(metadata) => metadata,
+                Err(_) => {
+                    error!("Account {} has no metadata", metadata_pubkey);
+                    return;
+                }
+            };
 
             if let Some(creators) = metadata.creators {
                 // Check whether the specific creator has already signed the account
@@ -221,3 +373,331 @@ pub fn sign_candy_machine_accounts(
 
     Ok(())
 }
+
+/// Estimate of the compute units a single `SignMetadata` instruction uses.
+const SIGN_METADATA_COMPUTE_UNITS: u32 = 25_000;
+
+/// Sign the metadata accounts with as many signatures packed into each transaction as fit.
+pub fn sign_packed(
+    client: &RpcClient,
+    creator: &Keypair,
+    metadata_accounts: Vec<(String, Pubkey)>,
+) -> Result<()> {
+    let signatures = metadata_accounts
+        .into_iter()
+        .map(|(mint, metadata)| (mint, metadata, vec![creator.pubkey()]))
+        .collect();
+    send_signatures_packed(client, &[creator], signatures)
+}
+
+/// Sign each mint's metadata with its listed creators, packing as many mints into each
+/// transaction as fit. A mint's signatures always share a transaction, and the first
+/// signer pays.
+fn send_signatures_packed(
+    client: &RpcClient,
+    signers: &[&Keypair],
+    signatures: Vec<(String, Pubkey, Vec<Pubkey>)>,
+) -> Result<()> {
+    let groups = signatures
+        .into_iter()
+        .map(|(mint, metadata, creators)| InstructionGroup {
+            key: mint,
+            compute_units: SIGN_METADATA_COMPUTE_UNITS * creators.len() as u32,
+            instructions: creators
+                .into_iter()
+                .map(|creator| SignMetadata { metadata, creator }.instruction())
+                .collect(),
+        })
+        .collect::<Vec<_>>();
+    let signature_count: usize = groups.iter().map(|g| g.instructions.len()).sum();
+    let mint_count = groups.len();
+
+    // Read the metadata up front so the creators verified by the run can be written out after.
+    let mints: Vec<String> = groups.iter().map(|g| g.key.clone()).collect();
+    let changelog = Changelog::capture(client, SIGN_COMMAND, &mints)?;
+
+    let payer = signers
+        .first()
+        .ok_or_else(|| anyhow!("At least one creator keypair is required"))?;
+    let packed = pack_instruction_groups(client, &payer.pubkey(), groups)?;
+    println!(
+        "Packed {signature_count} signatures into {} transactions",
+        packed.len()
+    );
+
+    let (sent, failed) = send_packed(client, signers, &Priority::None, packed)?;
+    for (mints, signature) in &sent {
+        info!("{}", signature);
+        for mint in mints {
+            checkpoint::record_done(SIGN_COMMAND, mint, Some(signature.to_string()));
+            changelog.record(mint, *signature);
+        }
+    }
+
+    let failed_count: usize = failed.iter().map(|(mints, _)| mints.len()).sum();
+    for (mints, e) in failed {
+        for mint in mints {
+            error!("Failed to sign {}: {}", mint, e);
+            checkpoint::record_failed(SIGN_COMMAND, &mint, &e);
+        }
+    }
+    println!(
+        "Signed {} mints, {failed_count} failed",
+        mint_count - failed_count
+    );
+
+    // The signatures are already on chain, so failing to write them out isn't an error.
+    match changelog.write(client) {
+        Ok(Some(path)) => println!("Changelog written to {path}"),
+        Ok(None) => {}
+        Err(e) => warn!("Failed to write the changelog: {e}"),
+    }
+
+    Ok(())
+}
+
+/// Creator signatures batched into each pending transaction, chosen so the
+/// transaction stays under the size limit with the nonce accounts included.
+pub const CREATOR_SIGNATURES_PER_TX: usize = 20;
+
+/// A transaction waiting on a creator's signature before it can be applied.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PendingCreatorSignature {
+    pub creator: String,
+    pub nonce_account: String,
+    pub mints: Vec<String>,
+    /// Base64 encoded, bincode serialized transaction.
+    pub transaction: String,
+}
+
+fn encode_transaction(tx: &Transaction) -> Result<String> {
+    Ok(base64::encode(bincode::serialize(tx)?))
+}
+
+fn decode_transaction(encoded: &str) -> Result<Transaction> {
+    Ok(bincode::deserialize(&base64::decode(encoded.trim())?)?)
+}
+
+fn read_pending(path: &Path) -> Result<Vec<PendingCreatorSignature>> {
+    let file = File::open(path).map_err(|e| anyhow!("Failed to open {path:?}: {e}"))?;
+    Ok(serde_json::from_reader(file)?)
+}
+
+/// Create a nonce account for one pending transaction, so it stays valid for as long
+/// as the creator takes to sign it.
+fn create_pending_nonce(client: &RpcClient, payer: &Keypair) -> Result<(Pubkey, Hash)> {
+    let nonce = Keypair::new();
+    let rent = client.get_minimum_balance_for_rent_exemption(NonceState::size())?;
+    let ixs = create_nonce_account(&payer.pubkey(), &nonce.pubkey(), &payer.pubkey(), rent);
+
+    let tx = Transaction::new_signed_with_payer(
+        &ixs,
+        Some(&payer.pubkey()),
+        &[payer, &nonce],
+        client.get_latest_blockhash()?,
+    );
+    client.send_and_confirm_transaction(&tx)?;
+
+    Ok((
+        nonce.pubkey(),
+        get_nonce_blockhash(client, &nonce.pubkey())?,
+    ))
+}
+
+/// Build unsigned creator verification transactions for every unverified creator on
+/// the mints in the list, for the creators to sign offline.
+///
+/// The payer pays the fees and owns a durable nonce per transaction, so transactions
+/// don't expire while they are passed around.
+pub fn sign_collect(
+    client: &RpcClient,
+    keypair_path: Option<String>,
+    mint_list: PathBuf,
+    out: PathBuf,
+) -> Result<()> {
+    let payer = parse_keypair(keypair_path, parse_solana_config());
+
+    let mints: Vec<String> = read_json(&mint_list)?;
+
+    let mut unverified: IndexMap<Pubkey, Vec<Pubkey>> = IndexMap::new();
+    let pb = create_progress_bar("Finding unverified creators...", mints.len() as u64);
+    for mint in &mints {
+        let metadata = decode_metadata_from_mint(client, mint.clone())
+            .map_err(|e| anyhow!("Failed to decode metadata for {mint}: {e}"))?;
+
+        for creator in metadata.creators.unwrap_or_default() {
+            if !creator.verified {
+                unverified
+                    .entry(creator.address)
+                    .or_default()
+                    .push(metadata.mint);
+            }
+        }
+        pb.inc(1);
+    }
+    pb.finish_and_clear();
+
+    if unverified.is_empty() {
+        println!("All creators are already verified, nothing to collect.");
+        return Ok(());
+    }
+
+    let tx_count: usize = unverified
+        .values()
+        .map(|mints| mints.len().div_ceil(CREATOR_SIGNATURES_PER_TX))
+        .sum();
+
+    let mut pending = vec![];
+    let pb = create_progress_bar("Building transactions...", tx_count as u64);
+    for (creator, mints) in unverified {
+        for chunk in mints.chunks(CREATOR_SIGNATURES_PER_TX) {
+            let (nonce_account, nonce_blockhash) = create_pending_nonce(client, &payer)?;
+
+            let mut ixs = vec![advance_nonce_account(&nonce_account, &payer.pubkey())];
+            ixs.extend(chunk.iter().map(|mint| {
+                SignMetadata {
+                    metadata: get_metadata_pda(*mint),
+                    creator,
+                }
+                .instruction()
+            }));
+
+            // The payer signs now; the creator's signature is the only one left.
+            let mut tx = Transaction::new_with_payer(&ixs, Some(&payer.pubkey()));
+            tx.try_partial_sign(&[&payer], nonce_blockhash)?;
+
+            pending.push(PendingCreatorSignature {
+                creator: creator.to_string(),
+                nonce_account: nonce_account.to_string(),
+                mints: chunk.iter().map(|m| m.to_string()).collect(),
+                transaction: encode_transaction(&tx)?,
+            });
+            pb.inc(1);
+        }
+    }
+    pb.finish_and_clear();
+
+    serde_json::to_writer_pretty(File::create(&out)?, &pending)?;
+
+    let creators: HashSet<&str> = pending.iter().map(|p| p.creator.as_str()).collect();
+    println!(
+        "Wrote {} pending transactions for {} creators to {}",
+        pending.len(),
+        creators.len(),
+        out.display()
+    );
+
+    Ok(())
+}
+
+/// Add a creator's signature to their transactions in a pending file.
+pub fn sign_pending(
+    keypair_path: Option<String>,
+    file: PathBuf,
+    out: Option<PathBuf>,
+) -> Result<()> {
+    let creator = parse_keypair(keypair_path, parse_solana_config());
+    let mut pending = read_pending(&file)?;
+
+    let mut signed = 0;
+    for entry in pending
+        .iter_mut()
+        .filter(|entry| entry.creator == creator.pubkey().to_string())
+    {
+        let mut tx = decode_transaction(&entry.transaction)?;
+        let blockhash = tx.message.recent_blockhash;
+        tx.try_partial_sign(&[&creator], blockhash)?;
+        entry.transaction = encode_transaction(&tx)?;
+        signed += 1;
+    }
+
+    if signed == 0 {
+        return Err(anyhow!(
+            "No pending transactions for creator {}",
+            creator.pubkey()
+        ));
+    }
+
+    let out = out.unwrap_or(file);
+    serde_json::to_writer_pretty(File::create(&out)?, &pending)?;
+    println!("Signed {signed} transactions, written to {}", out.display());
+
+    Ok(())
+}
+
+/// Submit every fully signed transaction in a pending file and reclaim the rent from
+/// their nonce accounts. Results are written to `mb-sign-apply-results.json`.
+pub fn sign_apply(client: &RpcClient, keypair_path: Option<String>, file: PathBuf) -> Result<()> {
+    let payer = parse_keypair(keypair_path, parse_solana_config());
+    let pending = read_pending(&file)?;
+
+    let mut results: IndexMap<String, String> = IndexMap::new();
+    let mut sent = 0;
+    let mut awaiting = 0;
+    let mut failed = 0;
+
+    let pb = create_progress_bar("Applying signatures...", pending.len() as u64);
+    for entry in &pending {
+        let tx = decode_transaction(&entry.transaction)?;
+
+        if tx.verify().is_err() {
+            awaiting += 1;
+            results.insert(
+                entry.nonce_account.clone(),
+                format!("awaiting signature from {}", entry.creator),
+            );
+            pb.inc(1);
+            continue;
+        }
+
+        let result = client
+            .send_and_confirm_transaction(&tx)
+            .map_err(|e| anyhow!(e))
+            .and_then(|sig| {
+                let nonce_account = Pubkey::from_str(&entry.nonce_account)?;
+                if let Err(e) = close_nonce(client, &payer, &nonce_account) {
+                    warn!("Failed to close nonce account {nonce_account}: {e}");
+                }
+                Ok(sig)
+            });
+
+        match result {
+            Ok(sig) => {
+                sent += 1;
+                results.insert(entry.nonce_account.clone(), sig.to_string());
+            }
+            Err(e) => {
+                failed += 1;
+                results.insert(entry.nonce_account.clone(), format!("error: {e}"));
+            }
+        }
+        pb.inc(1);
+    }
+    pb.finish_and_clear();
+
+    let results_file = output_path("mb-sign-apply-results.json")
+        .display()
+        .to_string();
+    serde_json::to_writer_pretty(File::create(&results_file)?, &results)?;
+
+    println!(
+        "Submitted {sent} transactions, {awaiting} awaiting signatures, {failed} failed. Results written to {results_file}"
+    );
+
+    Ok(())
+}
+
+fn close_nonce(client: &RpcClient, payer: &Keypair, nonce_account: &Pubkey) -> Result<()> {
+    let lamports = client.get_balance(nonce_account)?;
+    let ix = withdraw_nonce_account(nonce_account, &payer.pubkey(), &payer.pubkey(), lamports);
+
+    let tx = Transaction::new_signed_with_payer(
+        &[ix],
+        Some(&payer.pubkey()),
+        &[payer],
+        client.get_latest_blockhash()?,
+    );
+    client.send_and_confirm_transaction(&tx)?;
+
+    Ok(())
+}
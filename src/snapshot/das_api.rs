@@ -1,4 +1,4 @@
-use std::{fmt::Display, fs::File, path::PathBuf, str::FromStr};
+use std::{fmt::Display, path::PathBuf, str::FromStr};
 
 use anyhow::{bail, Result};
 use metaboss_lib::derive::derive_metadata_pda;
@@ -13,7 +13,7 @@ use crate::{
     spinner::create_spinner,
 };
 
-use super::{DasResponse, Holder, Item};
+use super::{write_snapshot, DasResponse, Holder, Item, SnapshotFormat};
 
 #[derive(Debug)]
 pub enum HolderGroupKey {
@@ -50,6 +50,7 @@ pub struct HoldersArgs {
     pub group_key: HolderGroupKey,
     pub group_value: Pubkey,
     pub output: PathBuf,
+    pub format: SnapshotFormat,
     pub delay: u64,
 }
 
@@ -106,7 +107,7 @@ pub async fn snapshot_holders(args: HoldersArgs) -> Result<()> {
     });
 
     let fvca_filter = |item: &Item| {
-        item.creators.first().is_some()
+        !item.creators.is_empty()
             && item.creators.first().unwrap().address.to_string() == args.group_value.to_string()
     };
 
@@ -158,6 +159,10 @@ pub async fn snapshot_holders(args: HoldersArgs) -> Result<()> {
                     mint: item.id.clone(),
                     metadata: metadata_pubkey.to_string(),
                     ata: ata_pubkey.to_string(),
+                    token_account: None,
+                    is_ata: None,
+                    delegate: item.ownership.delegate.clone(),
+                    frozen: item.ownership.frozen,
                 });
             });
 
@@ -168,11 +173,12 @@ pub async fn snapshot_holders(args: HoldersArgs) -> Result<()> {
     holders.sort();
 
     // Write to file
-    let file = File::create(format!(
-        "{}_{}_holders.json",
-        args.group_value, args.group_key
-    ))?;
-    serde_json::to_writer_pretty(file, &holders)?;
+    write_snapshot(
+        format!("{}_{}_holders", args.group_value, args.group_key),
+        "holders",
+        &holders,
+        args.format,
+    )?;
 
     Ok(())
 }
@@ -213,6 +219,7 @@ pub struct MintsArgs {
     pub group_value: Pubkey,
     pub creator_position: usize,
     pub output: PathBuf,
+    pub format: SnapshotFormat,
     pub delay: u64,
 }
 
@@ -325,11 +332,12 @@ pub async fn snapshot_mints(args: MintsArgs) -> Result<()> {
     mints.sort();
 
     // Write to file
-    let file = File::create(format!(
-        "{}_{}_mints.json",
-        args.group_value, args.group_key
-    ))?;
-    serde_json::to_writer_pretty(file, &mints)?;
+    write_snapshot(
+        format!("{}_{}_mints", args.group_value, args.group_key),
+        "mints",
+        &mints,
+        args.format,
+    )?;
 
     Ok(())
 }
@@ -338,6 +346,7 @@ pub struct FcvaArgs {
     pub rpc_url: String,
     pub creator: Option<Pubkey>,
     pub output: PathBuf,
+    pub format: SnapshotFormat,
     pub delay: u64,
 }
 
@@ -402,7 +411,7 @@ pub async fn fcva_mints(args: FcvaArgs) -> Result<()> {
             .items
             .iter()
             .filter(|item| {
-                item.creators.first().is_some()
+                !item.creators.is_empty()
                     && item.creators.first().unwrap().address.to_string() == creator
             })
             .for_each(|item| {
@@ -416,8 +425,12 @@ pub async fn fcva_mints(args: FcvaArgs) -> Result<()> {
     mints.sort();
 
     // Write to file
-    let file = File::create(format!("{}_fvca_mints.json", creator))?;
-    serde_json::to_writer_pretty(file, &mints)?;
+    write_snapshot(
+        format!("{}_fvca_mints", creator),
+        "mints",
+        &mints,
+        args.format,
+    )?;
 
     Ok(())
 }
@@ -426,6 +439,7 @@ pub struct MccArgs {
     pub rpc_url: String,
     pub mcc_id: Pubkey,
     pub output: PathBuf,
+    pub format: SnapshotFormat,
     pub delay: u64,
 }
 
@@ -490,8 +504,110 @@ pub async fn mcc_mints(args: MccArgs) -> Result<()> {
     mints.sort();
 
     // Write to file
-    let file = File::create(format!("{}_mcc_mints.json", mcc_id))?;
-    serde_json::to_writer_pretty(file, &mints)?;
+    write_snapshot(
+        format!("{}_mcc_mints", mcc_id),
+        "mints",
+        &mints,
+        args.format,
+    )?;
+
+    Ok(())
+}
+
+pub struct CnftHoldersArgs {
+    pub rpc_url: String,
+    pub collection: Pubkey,
+    pub output: PathBuf,
+    pub format: SnapshotFormat,
+    pub delay: u64,
+}
+
+pub async fn snapshot_cnft_holders(args: CnftHoldersArgs) -> Result<()> {
+    let config = CliConfig::new(None, Some(args.rpc_url), ClientType::DAS)?;
+
+    let collection = args.collection.to_string();
+
+    let mut headers = HeaderMap::new();
+    headers.insert("Content-Type", "application/json".parse().unwrap());
+
+    let client = match config.client {
+        ClientLike::DasClient(client) => client,
+        _ => panic!("Wrong client type"),
+    };
+
+    let mut holders = Vec::new();
+    let mut page = 1;
+    let spinner = create_spinner("Getting assets...");
+    loop {
+        let body = json!(
+        {
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "getAssetsByGroup",
+            "params": {
+                "groupKey": "collection",
+                "groupValue": collection,
+                "page": page,
+                "limit": 1000
+            },
+        });
+
+        let response = client
+            .post(config.rpc_url.clone())
+            .headers(headers.clone())
+            .json(&body)
+            .send()
+            .await?;
+
+        let status = response.status();
+        if response.status() != StatusCode::OK {
+            bail!("Status: {status}\nResponse: {}", response.text().await?);
+        }
+
+        let res: DasResponse = response.json().await?;
+
+        if res.result.items.is_empty() {
+            break;
+        }
+
+        page += 1;
+
+        // Collections can mix compressed and uncompressed assets: only the compressed ones
+        // are missed by the gPA-based snapshots.
+        res.result
+            .items
+            .iter()
+            .filter(|item| item.compression["compressed"].as_bool() == Some(true) && !item.burnt)
+            .for_each(|item| {
+                // Compressed assets have no metadata or token accounts.
+                holders.push(Holder {
+                    owner: item.ownership.owner.clone(),
+                    mint: item.id.clone(),
+                    metadata: String::new(),
+                    ata: String::new(),
+                    token_account: None,
+                    is_ata: None,
+                    delegate: item.ownership.delegate.clone(),
+                    frozen: item.ownership.frozen,
+                });
+            });
+
+        std::thread::sleep(std::time::Duration::from_millis(args.delay));
+    }
+    spinner.finish_and_clear();
+
+    holders.sort();
+
+    // Write to file
+    std::fs::create_dir_all(&args.output)?;
+    write_snapshot(
+        args.output.join(format!("{}_cnft_holders", collection)),
+        "holders",
+        &holders,
+        args.format,
+    )?;
+
+    println!("Found {} cNFT holders", holders.len());
 
     Ok(())
 }
@@ -8,7 +8,16 @@ pub struct Holder {
     pub owner: String,
     pub mint: String,
     pub metadata: String,
+    /// The owner's associated token account for the mint.
     pub ata: String,
+    /// The token account that holds the NFT, when the snapshot source reports it.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub token_account: Option<String>,
+    /// Whether the NFT is held in the owner's associated token account.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub is_ata: Option<bool>,
+    pub delegate: Option<String>,
+    pub frozen: bool,
 }
 
 #[derive(Debug, Serialize)]
@@ -29,6 +38,16 @@ pub struct CandyMachineAccount {
     pub data_len: usize,
 }
 
+/// Overrides for the getProgramAccounts query, so forks of Token Metadata with a
+/// slightly different account layout can be snapshotted too.
+#[derive(Clone, Debug, Default)]
+pub struct GpaOverrides {
+    pub program_id: Option<Pubkey>,
+    /// Offset to match the creator or update authority at.
+    pub offset: Option<usize>,
+    pub data_size: Option<u64>,
+}
+
 pub struct SnapshotMintsGpaArgs {
     pub creator: Option<String>,
     pub position: usize,
@@ -36,7 +55,9 @@ pub struct SnapshotMintsGpaArgs {
     pub v2: bool,
     pub v3: bool,
     pub allow_unverified: bool,
+    pub overrides: GpaOverrides,
     pub output: String,
+    pub format: SnapshotFormat,
 }
 
 pub struct SnapshotHoldersGpaArgs {
@@ -47,7 +68,9 @@ pub struct SnapshotHoldersGpaArgs {
     pub v2: bool,
     pub v3: bool,
     pub allow_unverified: bool,
+    pub overrides: GpaOverrides,
     pub output: String,
+    pub format: SnapshotFormat,
 }
 
 use mpl_token_metadata::types::Creator;
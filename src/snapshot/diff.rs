@@ -0,0 +1,351 @@
+use std::{
+    collections::BTreeMap,
+    fmt::{self, Display},
+    io::Write,
+    path::{Path, PathBuf},
+};
+
+use serde::Deserialize;
+
+use super::*;
+use crate::files::read_json;
+use crate::workspace::output_path;
+
+#[derive(Debug, Clone, Copy)]
+pub enum DiffFormat {
+    Json,
+    Csv,
+}
+
+impl FromStr for DiffFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "json" => Ok(DiffFormat::Json),
+            "csv" => Ok(DiffFormat::Csv),
+            _ => Err(format!("Invalid format: {s}, expected json or csv")),
+        }
+    }
+}
+
+impl Display for DiffFormat {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DiffFormat::Json => write!(f, "json"),
+            DiffFormat::Csv => write!(f, "csv"),
+        }
+    }
+}
+
+pub struct SnapshotDiffArgs {
+    pub old: PathBuf,
+    pub new: PathBuf,
+    pub format: DiffFormat,
+    pub output: PathBuf,
+}
+
+/// The fields of a holder snapshot entry the diff needs, so any of the holder snapshots
+/// can be compared.
+#[derive(Deserialize)]
+struct SnapshotEntry {
+    owner: String,
+    mint: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct OwnerChange {
+    pub mint: String,
+    pub old_owner: String,
+    pub new_owner: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct MintHolder {
+    pub mint: String,
+    pub owner: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct HolderChange {
+    pub owner: String,
+    pub mints: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DiffSummary {
+    pub old_mint_count: usize,
+    pub new_mint_count: usize,
+    pub old_holder_count: usize,
+    pub new_holder_count: usize,
+    pub owner_changes: usize,
+    pub holders_joined: usize,
+    pub holders_exited: usize,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SnapshotDiff {
+    pub summary: DiffSummary,
+    /// Mints in both snapshots that are held by a different wallet.
+    pub owner_changes: Vec<OwnerChange>,
+    /// Mints only in the new snapshot, e.g. newly minted.
+    pub added_mints: Vec<MintHolder>,
+    /// Mints only in the old snapshot, e.g. burned.
+    pub removed_mints: Vec<MintHolder>,
+    /// Wallets that hold something now but held nothing before.
+    pub new_holders: Vec<HolderChange>,
+    /// Wallets that held something before but hold nothing now.
+    pub exited_holders: Vec<HolderChange>,
+}
+
+/// Read a holder snapshot as a map of mint to owner.
+fn read_snapshot(path: &Path) -> Result<BTreeMap<String, String>> {
+    let entries: Vec<SnapshotEntry> = read_json(path)
+        .map_err(|e| anyhow!("Failed to read holder snapshot {}: {e}", path.display()))?;
+    Ok(entries.into_iter().map(|e| (e.mint, e.owner)).collect())
+}
+
+fn holdings(snapshot: &BTreeMap<String, String>) -> BTreeMap<&String, Vec<String>> {
+    let mut holdings: BTreeMap<&String, Vec<String>> = BTreeMap::new();
+    for (mint, owner) in snapshot {
+        holdings.entry(owner).or_default().push(mint.clone());
+    }
+    holdings
+}
+
+pub fn diff_snapshots(
+    old: &BTreeMap<String, String>,
+    new: &BTreeMap<String, String>,
+) -> SnapshotDiff {
+    let mut owner_changes = vec![];
+    let mut removed_mints = vec![];
+    for (mint, old_owner) in old {
+        match new.get(mint) {
+            Some(new_owner) if new_owner != old_owner => owner_changes.push(OwnerChange {
+                mint: mint.clone(),
+                old_owner: old_owner.clone(),
+                new_owner: new_owner.clone(),
+            }),
+            Some(_) => {}
+            None => removed_mints.push(MintHolder {
+                mint: mint.clone(),
+                owner: old_owner.clone(),
+            }),
+        }
+    }
+    let added_mints: Vec<MintHolder> = new
+        .iter()
+        .filter(|(mint, _)| !old.contains_key(*mint))
+        .map(|(mint, owner)| MintHolder {
+            mint: mint.clone(),
+            owner: owner.clone(),
+        })
+        .collect();
+
+    let old_holdings = holdings(old);
+    let new_holdings = holdings(new);
+    let only_in = |a: &BTreeMap<&String, Vec<String>>, b: &BTreeMap<&String, Vec<String>>| {
+        a.iter()
+            .filter(|(owner, _)| !b.contains_key(*owner))
+            .map(|(owner, mints)| HolderChange {
+                owner: owner.to_string(),
+                mints: mints.clone(),
+            })
+            .collect::<Vec<_>>()
+    };
+    let new_holders = only_in(&new_holdings, &old_holdings);
+    let exited_holders = only_in(&old_holdings, &new_holdings);
+
+    SnapshotDiff {
+        summary: DiffSummary {
+            old_mint_count: old.len(),
+            new_mint_count: new.len(),
+            old_holder_count: old_holdings.len(),
+            new_holder_count: new_holdings.len(),
+            owner_changes: owner_changes.len(),
+            holders_joined: new_holders.len(),
+            holders_exited: exited_holders.len(),
+        },
+        owner_changes,
+        added_mints,
+        removed_mints,
+        new_holders,
+        exited_holders,
+    }
+}
+
+fn write_csv_mints(path: &Path, diff: &SnapshotDiff) -> Result<()> {
+    let mut f = File::create(path)?;
+    writeln!(f, "change,mint,old_owner,new_owner")?;
+    for c in &diff.owner_changes {
+        writeln!(
+            f,
+            "owner_changed,{},{},{}",
+            c.mint, c.old_owner, c.new_owner
+        )?;
+    }
+    for m in &diff.added_mints {
+        writeln!(f, "added,{},,{}", m.mint, m.owner)?;
+    }
+    for m in &diff.removed_mints {
+        writeln!(f, "removed,{},{},", m.mint, m.owner)?;
+    }
+    Ok(())
+}
+
+fn write_csv_holders(path: &Path, diff: &SnapshotDiff) -> Result<()> {
+    let mut f = File::create(path)?;
+    writeln!(f, "change,owner,mint_count,mints")?;
+    let rows = diff
+        .new_holders
+        .iter()
+        .map(|h| ("joined", h))
+        .chain(diff.exited_holders.iter().map(|h| ("exited", h)));
+    for (change, holder) in rows {
+        // Addresses never contain spaces, so the mints share one column.
+        writeln!(
+            f,
+            "{change},{},{},{}",
+            holder.owner,
+            holder.mints.len(),
+            holder.mints.join(" ")
+        )?;
+    }
+    Ok(())
+}
+
+/// Compare two holder snapshots and report the mints that changed hands and the wallets
+/// that joined or left.
+pub fn snapshot_diff(args: SnapshotDiffArgs) -> Result<()> {
+    let old = read_snapshot(&args.old)?;
+    let new = read_snapshot(&args.new)?;
+    let diff = diff_snapshots(&old, &new);
+
+    let output = output_path(&args.output);
+    std::fs::create_dir_all(&output)?;
+    let written: Vec<PathBuf> = match args.format {
+        DiffFormat::Json => {
+            let path = output.join("snapshot_diff.json");
+            serde_json::to_writer_pretty(File::create(&path)?, &diff)?;
+            vec![path]
+        }
+        DiffFormat::Csv => {
+            let mints = output.join("snapshot_diff_mints.csv");
+            let holders = output.join("snapshot_diff_holders.csv");
+            write_csv_mints(&mints, &diff)?;
+            write_csv_holders(&holders, &diff)?;
+            vec![mints, holders]
+        }
+    };
+
+    let s = &diff.summary;
+    println!(
+        "Mints: {} -> {}, holders: {} -> {}",
+        s.old_mint_count, s.new_mint_count, s.old_holder_count, s.new_holder_count
+    );
+    println!("Owner changes: {}", s.owner_changes);
+    println!("Added mints: {}", diff.added_mints.len());
+    println!("Removed mints: {}", diff.removed_mints.len());
+    println!("New holders: {}", s.holders_joined);
+    println!("Exited holders: {}", s.holders_exited);
+    for path in written {
+        println!("Written to {}", path.display());
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn snapshot(entries: &[(&str, &str)]) -> BTreeMap<String, String> {
+        entries
+            .iter()
+            .map(|(mint, owner)| (mint.to_string(), owner.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn classifies_changes_between_snapshots() {
+        let old = snapshot(&[
+            ("m1", "alice"),
+            ("m2", "bob"),
+            ("m3", "carol"),
+            ("m4", "dave"),
+        ]);
+        let new = snapshot(&[
+            ("m1", "alice"),
+            ("m2", "erin"),
+            ("m4", "dave"),
+            ("m5", "frank"),
+            ("m6", "alice"),
+        ]);
+
+        let diff = diff_snapshots(&old, &new);
+
+        let owner_changes: Vec<_> = diff
+            .owner_changes
+            .iter()
+            .map(|c| (c.mint.as_str(), c.old_owner.as_str(), c.new_owner.as_str()))
+            .collect();
+        assert_eq!(owner_changes, vec![("m2", "bob", "erin")]);
+
+        let mints = |holders: &[MintHolder]| -> Vec<(String, String)> {
+            holders
+                .iter()
+                .map(|h| (h.mint.clone(), h.owner.clone()))
+                .collect()
+        };
+        assert_eq!(
+            mints(&diff.added_mints),
+            vec![
+                ("m5".to_string(), "frank".to_string()),
+                ("m6".to_string(), "alice".to_string())
+            ]
+        );
+        assert_eq!(
+            mints(&diff.removed_mints),
+            vec![("m3".to_string(), "carol".to_string())]
+        );
+
+        // A holder who still has another mint neither joins nor exits.
+        let holders = |holders: &[HolderChange]| -> Vec<(String, Vec<String>)> {
+            holders
+                .iter()
+                .map(|h| (h.owner.clone(), h.mints.clone()))
+                .collect()
+        };
+        assert_eq!(
+            holders(&diff.new_holders),
+            vec![
+                ("erin".to_string(), vec!["m2".to_string()]),
+                ("frank".to_string(), vec!["m5".to_string()])
+            ]
+        );
+        assert_eq!(
+            holders(&diff.exited_holders),
+            vec![
+                ("bob".to_string(), vec!["m2".to_string()]),
+                ("carol".to_string(), vec!["m3".to_string()])
+            ]
+        );
+
+        let summary = &diff.summary;
+        assert_eq!((summary.old_mint_count, summary.new_mint_count), (4, 5));
+        assert_eq!((summary.old_holder_count, summary.new_holder_count), (4, 4));
+        assert_eq!(summary.owner_changes, 1);
+        assert_eq!((summary.holders_joined, summary.holders_exited), (2, 2));
+    }
+
+    #[test]
+    fn finds_no_changes_between_identical_snapshots() {
+        let old = snapshot(&[("m1", "alice"), ("m2", "bob")]);
+
+        let diff = diff_snapshots(&old, &old.clone());
+
+        assert!(diff.owner_changes.is_empty());
+        assert!(diff.added_mints.is_empty() && diff.removed_mints.is_empty());
+        assert!(diff.new_holders.is_empty() && diff.exited_holders.is_empty());
+    }
+}
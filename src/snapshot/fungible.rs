@@ -0,0 +1,149 @@
+use std::{collections::HashMap, path::PathBuf};
+
+use spl_token_2022::{extension::StateWithExtensions, state::Mint};
+
+use crate::{spinner::create_spinner, utils::get_token_program};
+
+use super::*;
+
+// Token accounts start with the mint, then the owner and the amount.
+const TOKEN_ACCOUNT_OWNER_OFFSET: usize = 32;
+const TOKEN_ACCOUNT_LEN: u64 = 165;
+
+pub struct SnapshotFungibleHoldersArgs {
+    pub client: RpcClient,
+    pub mint: Pubkey,
+    pub min_amount: f64,
+    pub output: PathBuf,
+    pub format: SnapshotFormat,
+}
+
+#[derive(Debug, Serialize)]
+pub struct FungibleHolder {
+    pub owner: String,
+    /// Balance in base units, as a string so large balances keep their precision in JSON.
+    pub amount: String,
+    pub ui_amount: f64,
+    pub token_accounts: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct FungibleHolders {
+    pub mint: String,
+    pub decimals: u8,
+    pub holder_count: usize,
+    pub total_amount: String,
+    pub total_ui_amount: f64,
+    pub holders: Vec<FungibleHolder>,
+}
+
+fn ui_amount(amount: u64, decimals: u8) -> f64 {
+    amount as f64 / 10f64.powi(decimals as i32)
+}
+
+/// Snapshot every wallet holding a fungible token, with balances summed across each
+/// wallet's token accounts, largest holders first.
+pub fn snapshot_fungible_holders(args: SnapshotFungibleHoldersArgs) -> Result<()> {
+    let client = &args.client;
+    let token_program = get_token_program(client, &args.mint)?;
+    let decimals = StateWithExtensions::<Mint>::unpack(&client.get_account(&args.mint)?.data)?
+        .base
+        .decimals;
+
+    #[allow(deprecated)]
+    let mut filters = vec![RpcFilterType::Memcmp(Memcmp {
+        offset: 0,
+        bytes: MemcmpEncodedBytes::Bytes(args.mint.to_bytes().to_vec()),
+        encoding: None,
+    })];
+    // Token-2022 accounts grow with their extensions, so only legacy accounts have a fixed size.
+    if token_program == TOKEN_PROGRAM_ID {
+        filters.push(RpcFilterType::DataSize(TOKEN_ACCOUNT_LEN));
+    }
+    let config = RpcProgramAccountsConfig {
+        filters: Some(filters),
+        account_config: RpcAccountInfoConfig {
+            encoding: Some(UiAccountEncoding::Base64),
+            // Only the owner and amount are needed.
+            data_slice: Some(UiDataSliceConfig {
+                offset: TOKEN_ACCOUNT_OWNER_OFFSET,
+                length: 32 + 8,
+            }),
+            commitment: Some(CommitmentConfig {
+                commitment: CommitmentLevel::Confirmed,
+            }),
+            min_context_slot: None,
+        },
+        with_context: None,
+    };
+
+    let spinner = create_spinner("Getting token accounts...");
+    let accounts = client.get_program_accounts_with_config(&token_program, config)?;
+    spinner.finish();
+
+    let mut balances: HashMap<Pubkey, (u64, Vec<String>)> = HashMap::new();
+    for (address, account) in accounts {
+        if account.data.len() < 40 {
+            error!("Token account {address} returned too little data, skipping");
+            continue;
+        }
+        let owner = Pubkey::try_from(&account.data[..32])?;
+        let amount = u64::from_le_bytes(account.data[32..40].try_into()?);
+        if amount == 0 {
+            continue;
+        }
+        let balance = balances.entry(owner).or_default();
+        balance.0 += amount;
+        balance.1.push(address.to_string());
+    }
+
+    let mut holders: Vec<FungibleHolder> = balances
+        .into_iter()
+        .filter(|(_, (amount, _))| ui_amount(*amount, decimals) >= args.min_amount)
+        .map(|(owner, (amount, mut token_accounts))| {
+            token_accounts.sort();
+            FungibleHolder {
+                owner: owner.to_string(),
+                amount: amount.to_string(),
+                ui_amount: ui_amount(amount, decimals),
+                token_accounts,
+            }
+        })
+        .collect();
+    holders.sort_by(|a, b| {
+        let amount = |h: &FungibleHolder| h.amount.parse::<u64>().unwrap_or_default();
+        amount(b)
+            .cmp(&amount(a))
+            .then_with(|| a.owner.cmp(&b.owner))
+    });
+
+    let total: u64 = holders
+        .iter()
+        .map(|h| h.amount.parse::<u64>().unwrap_or_default())
+        .sum();
+    let snapshot = FungibleHolders {
+        mint: args.mint.to_string(),
+        decimals,
+        holder_count: holders.len(),
+        total_amount: total.to_string(),
+        total_ui_amount: ui_amount(total, decimals),
+        holders,
+    };
+
+    println!(
+        "Found {} holders with {} tokens",
+        snapshot.holder_count, snapshot.total_ui_amount
+    );
+
+    std::fs::create_dir_all(&args.output)?;
+    let stem = args.output.join(format!("{}_fungible_holders", args.mint));
+    // Rows are the holders, the totals are only in the JSON.
+    let path = if args.format.is_json() {
+        write_snapshot(stem, "holders", &snapshot, args.format)?
+    } else {
+        write_snapshot(stem, "holders", &snapshot.holders, args.format)?
+    };
+    println!("Written to {}", path.display());
+
+    Ok(())
+}
@@ -1,4 +1,5 @@
 use borsh::BorshDeserialize;
+use spl_associated_token_account::get_associated_token_address;
 
 use super::data::*;
 use super::NftsByCreatorArgs;
@@ -6,12 +7,15 @@ use super::*;
 
 use crate::data::Indexers;
 use crate::derive::{derive_cmv2_pda, derive_cmv3_pda};
+use crate::files::read_json;
 use crate::limiter::create_default_rate_limiter;
-use crate::limiter::create_rate_limiter;
+use crate::limiter::{create_rate_limiter, rate_limit_or};
 use crate::parse::{creator_is_verified, is_only_one_option};
+use crate::rpc_cache;
 use crate::spinner::*;
 use crate::theindexio;
 use crate::theindexio::GPAResult;
+use crate::workspace::output_path;
 use crate::{constants::*, decode::get_metadata_pda};
 
 pub fn snapshot_mints_gpa(client: RpcClient, args: SnapshotMintsGpaArgs) -> Result<()> {
@@ -31,7 +35,7 @@ pub fn snapshot_mints_gpa(client: RpcClient, args: SnapshotMintsGpaArgs) -> Resu
         ));
     };
 
-    let mut mint_addresses = get_mint_accounts(
+    let mut mint_addresses = get_mint_accounts_with_overrides(
         &client,
         &args.creator,
         args.position,
@@ -39,11 +43,16 @@ pub fn snapshot_mints_gpa(client: RpcClient, args: SnapshotMintsGpaArgs) -> Resu
         args.allow_unverified,
         args.v2,
         args.v3,
+        &args.overrides,
     )?;
 
     mint_addresses.sort_unstable();
-    let mut file = File::create(format!("{}/{}_mint_accounts.json", args.output, prefix))?;
-    serde_json::to_writer_pretty(&mut file, &mint_addresses)?;
+    write_snapshot(
+        format!("{}/{}_mint_accounts", args.output, prefix),
+        "mints",
+        &mint_addresses,
+        args.format,
+    )?;
 
     Ok(())
 }
@@ -74,7 +83,9 @@ pub async fn snapshot_indexed_mints(args: NftsByCreatorArgs) -> Result<()> {
     }
 
     mint_addresses.sort_unstable();
-    let mut file = File::create(format!("{output}/{creator}_mint_accounts.json"))?;
+    let mut file = File::create(output_path(format!(
+        "{output}/{creator}_mint_accounts.json"
+    )))?;
     serde_json::to_writer_pretty(&mut file, &mint_addresses)?;
 
     Ok(())
@@ -89,49 +100,156 @@ pub fn get_mint_accounts(
     v2: bool,
     v3: bool,
 ) -> Result<Vec<String>> {
-    let spinner = create_spinner("Getting accounts...");
+    get_mint_accounts_with_overrides(
+        client,
+        creator,
+        position,
+        update_authority,
+        allow_unverified,
+        v2,
+        v3,
+        &GpaOverrides::default(),
+    )
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn get_mint_accounts_with_overrides(
+    client: &RpcClient,
+    creator: &Option<String>,
+    position: usize,
+    update_authority: Option<String>,
+    allow_unverified: bool,
+    v2: bool,
+    v3: bool,
+    overrides: &GpaOverrides,
+) -> Result<Vec<String>> {
+    if position > 4 {
+        return Err(anyhow!("Creator position cannot be greater than 4"));
+    }
 
-    let accounts = if let Some(ref update_authority) = update_authority {
-        get_mints_by_update_authority(client, update_authority)?
+    // Match the update authority or creator, and unless unverified creators are allowed,
+    // the verified flag that follows the creator's address.
+    #[allow(deprecated)]
+    let mut filters = if let Some(ref update_authority) = update_authority {
+        let update_authority = Pubkey::from_str(update_authority)?;
+        let mut filters = vec![RpcFilterType::Memcmp(Memcmp {
+            offset: overrides.offset.unwrap_or(1), // key
+            bytes: MemcmpEncodedBytes::Bytes(update_authority.to_bytes().to_vec()),
+            encoding: None,
+        })];
+        if !allow_unverified {
+            filters.push(RpcFilterType::Memcmp(Memcmp {
+                offset: creator_offset(position) + 32,
+                bytes: MemcmpEncodedBytes::Bytes(vec![1]),
+                encoding: None,
+            }));
+        }
+        filters
     } else if let Some(ref creator) = creator {
         // Support v2 & v3 cm ids
         let creator_pubkey =
             Pubkey::from_str(creator).expect("Failed to parse pubkey from creator!");
-        if v2 {
-            let cmv2_creator = derive_cmv2_pda(&creator_pubkey);
-            get_cm_creator_accounts(client, &cmv2_creator.to_string(), position)?
+        let creator_pubkey = if v2 {
+            derive_cmv2_pda(&creator_pubkey)
         } else if v3 {
-            let cmv3_creator = derive_cmv3_pda(&creator_pubkey);
-            get_cm_creator_accounts(client, &cmv3_creator.to_string(), position)?
+            derive_cmv3_pda(&creator_pubkey)
         } else {
-            get_cm_creator_accounts(client, creator, position)?
+            creator_pubkey
+        };
+        let offset = overrides.offset.unwrap_or_else(|| creator_offset(position));
+        let mut filters = vec![RpcFilterType::Memcmp(Memcmp {
+            offset,
+            bytes: MemcmpEncodedBytes::Bytes(creator_pubkey.to_bytes().to_vec()),
+            encoding: None,
+        })];
+        if !allow_unverified {
+            filters.push(RpcFilterType::Memcmp(Memcmp {
+                offset: offset + 32,
+                bytes: MemcmpEncodedBytes::Bytes(vec![1]),
+                encoding: None,
+            }));
         }
+        filters
     } else {
         return Err(anyhow!(
             "Please specify either a candy machine id or an update authority, but not both."
         ));
     };
+    if let Some(data_size) = overrides.data_size {
+        filters.push(RpcFilterType::DataSize(data_size));
+    }
+
+    let program_id = overrides.program_id.unwrap_or(TOKEN_METADATA_PROGRAM_ID);
+    let spinner = create_spinner("Getting accounts...");
+    let mints = get_sliced_mints(client, &program_id, filters)?;
     spinner.finish();
 
-    info!("Getting metadata and writing to file...");
-    println!("Getting metadata and writing to file...");
-    let mut mint_accounts: Vec<String> = Vec::new();
+    Ok(mints.iter().map(|mint| mint.to_string()).collect())
+}
 
-    for (pubkey, account) in accounts {
-        let metadata: Metadata = match Metadata::deserialize(&mut account.data.as_slice()) {
-            Ok(metadata) => metadata,
-            Err(_) => {
-                error!("Failed to parse metadata for account {}", pubkey);
-                continue;
-            }
-        };
+// The mint follows the key and update authority in the metadata account.
+const METADATA_MINT_OFFSET: usize = 1 + 32;
+
+/// Fetch only the mint of each matching metadata account, so the full accounts don't
+/// travel over the wire.
+///
+/// If the RPC can't return every account in one response, the request is split into 256
+/// pages by the first byte of the mint.
+fn get_sliced_mints(
+    client: &RpcClient,
+    program_id: &Pubkey,
+    filters: Vec<RpcFilterType>,
+) -> Result<Vec<Pubkey>> {
+    let config = |filters: Vec<RpcFilterType>| RpcProgramAccountsConfig {
+        filters: Some(filters),
+        account_config: RpcAccountInfoConfig {
+            encoding: Some(UiAccountEncoding::Base64),
+            data_slice: Some(UiDataSliceConfig {
+                offset: METADATA_MINT_OFFSET,
+                length: 32,
+            }),
+            commitment: Some(CommitmentConfig {
+                commitment: CommitmentLevel::Confirmed,
+            }),
+            min_context_slot: None,
+        },
+        with_context: None,
+    };
+    let to_mints = |accounts: Vec<(Pubkey, Account)>| {
+        accounts
+            .into_iter()
+            .filter_map(|(_, account)| Pubkey::try_from(account.data.as_slice()).ok())
+            .collect::<Vec<_>>()
+    };
 
-        if creator_is_verified(&metadata.creators, position) || allow_unverified {
-            mint_accounts.push(metadata.mint.to_string());
+    match client.get_program_accounts_with_config(program_id, config(filters.clone())) {
+        Ok(accounts) => return Ok(to_mints(accounts)),
+        Err(e) => {
+            info!("Single getProgramAccounts request failed: {e}");
+            println!("The RPC couldn't return every account at once, fetching in pages...");
         }
     }
 
-    Ok(mint_accounts)
+    let mut mints = vec![];
+    let pb = create_progress_bar("Fetching pages...", 256);
+    for first_byte in 0..=u8::MAX {
+        let mut page_filters = filters.clone();
+        #[allow(deprecated)]
+        page_filters.push(RpcFilterType::Memcmp(Memcmp {
+            offset: METADATA_MINT_OFFSET,
+            bytes: MemcmpEncodedBytes::Bytes(vec![first_byte]),
+            encoding: None,
+        }));
+        let accounts = retry(
+            Exponential::from_millis_with_factor(250, 2.0).take(3),
+            || client.get_program_accounts_with_config(program_id, config(page_filters.clone())),
+        )?;
+        mints.extend(to_mints(accounts));
+        pb.inc(1);
+    }
+    pb.finish_and_clear();
+
+    Ok(mints)
 }
 
 pub fn snapshot_holders_gpa(client: RpcClient, args: SnapshotHoldersGpaArgs) -> Result<()> {
@@ -140,23 +258,32 @@ pub fn snapshot_holders_gpa(client: RpcClient, args: SnapshotHoldersGpaArgs) ->
 
     let spinner = create_spinner("Getting accounts...");
     let accounts = if let Some(ref update_authority) = args.update_authority {
-        get_mints_by_update_authority(&client, update_authority)?
+        get_mints_by_update_authority(&client, update_authority, &args.overrides)?
     } else if let Some(ref creator) = args.creator {
         // Support v2 & v3 cm ids
         let creator_pubkey =
             Pubkey::from_str(creator).expect("Failed to parse pubkey from creator!");
         if args.v2 {
             let cmv2_creator = derive_cmv2_pda(&creator_pubkey);
-            get_cm_creator_accounts(&client, &cmv2_creator.to_string(), args.position)?
+            get_creator_accounts(
+                &client,
+                &cmv2_creator.to_string(),
+                args.position,
+                &args.overrides,
+            )?
         } else if args.v3 {
             let cmv3_creator = derive_cmv3_pda(&creator_pubkey);
-            get_cm_creator_accounts(&client, &cmv3_creator.to_string(), args.position)?
+            get_creator_accounts(
+                &client,
+                &cmv3_creator.to_string(),
+                args.position,
+                &args.overrides,
+            )?
         } else {
-            get_cm_creator_accounts(&client, creator, args.position)?
+            get_creator_accounts(&client, creator, args.position, &args.overrides)?
         }
     } else if let Some(ref mint_accounts_file) = args.mint_accounts_file {
-        let file = File::open(mint_accounts_file)?;
-        let mint_accounts: Vec<String> = serde_json::from_reader(&file)?;
+        let mint_accounts: Vec<String> = read_json(mint_accounts_file)?;
         get_mint_account_infos(&client, mint_accounts)?
     } else {
         return Err(anyhow!(
@@ -240,13 +367,13 @@ pub fn snapshot_holders_gpa(client: RpcClient, args: SnapshotHoldersGpaArgs) ->
                             return;
                         }
                     };
-                    let ata = associated_token_address.to_string();
-                    let holder = Holder {
+                    let holder = holder_from_token_account(
                         owner,
-                        ata,
-                        mint: metadata.mint.to_string(),
-                        metadata: metadata_pubkey.to_string(),
-                    };
+                        &metadata.mint,
+                        metadata_pubkey.to_string(),
+                        associated_token_address.to_string(),
+                        &data,
+                    );
                     nft_holders.lock().unwrap().push(holder);
                 }
             }
@@ -265,8 +392,12 @@ pub fn snapshot_holders_gpa(client: RpcClient, args: SnapshotHoldersGpaArgs) ->
     };
 
     nft_holders.lock().unwrap().sort_unstable();
-    let mut file = File::create(format!("{}/{}_holders.json", args.output, prefix))?;
-    serde_json::to_writer_pretty(&mut file, &nft_holders)?;
+    write_snapshot(
+        format!("{}/{}_holders", args.output, prefix),
+        "holders",
+        &nft_holders,
+        args.format,
+    )?;
 
     Ok(())
 }
@@ -286,7 +417,7 @@ pub async fn snapshot_indexed_holders(args: NftsByCreatorArgs) -> Result<()> {
         ..
     } = args;
 
-    let delay = 1_000_000;
+    let delay = (NANO_SECONDS_IN_SECOND / rate_limit_or(1_000)) as u32;
 
     let mut handle = create_rate_limiter(delay);
 
@@ -328,7 +459,7 @@ pub async fn snapshot_indexed_holders(args: NftsByCreatorArgs) -> Result<()> {
             .map(Result::unwrap_err)
             .map(|e| e.to_string())
             .collect::<Vec<_>>();
-        let f = File::create(format!("{output}/{creator}_errors.json"))?;
+        let f = File::create(output_path(format!("{output}/{creator}_errors.json")))?;
         serde_json::to_writer_pretty(&f, &errors)?;
     }
 
@@ -336,7 +467,7 @@ pub async fn snapshot_indexed_holders(args: NftsByCreatorArgs) -> Result<()> {
     let nft_holders: Vec<Holder> = successful_results.into_iter().map(Result::unwrap).collect();
     println!("Found {} holders", nft_holders.len());
 
-    let mut file = File::create(format!("{output}/{creator}_holders.json"))?;
+    let mut file = File::create(output_path(format!("{output}/{creator}_holders.json")))?;
     serde_json::to_writer_pretty(&mut file, &nft_holders)?;
 
     Ok(())
@@ -403,13 +534,13 @@ pub async fn get_holder_from_gpa_result(api_key: String, result: GPAResult) -> R
                     continue;
                 }
             };
-            let ata = token_result.pubkey;
-            let holder = Holder {
+            let holder = holder_from_token_account(
                 owner,
-                ata,
-                mint: metadata.mint.to_string(),
-                metadata: result.pubkey,
-            };
+                &metadata.mint,
+                result.pubkey,
+                token_result.pubkey,
+                &parsed_account,
+            );
 
             return Ok(holder);
         }
@@ -443,9 +574,9 @@ fn get_mint_account_infos(
 
         let metadata_pubkey = get_metadata_pda(mint_pubkey);
 
-        let account_info = match client.get_account(&metadata_pubkey) {
-            Ok(account) => account,
-            Err(_) => {
+        let account_info = match rpc_cache::get_account(client, &metadata_pubkey) {
+            Ok(Some(account)) => account,
+            _ => {
                 error!("Error in fetching metadata for mint {}", mint_account);
                 return;
             }
@@ -461,20 +592,26 @@ fn get_mint_account_infos(
     Ok(res)
 }
 
-fn get_mints_by_update_authority(
+/// Query metadata accounts matching a pubkey at the given offset, unless the offset,
+/// account size or program are overridden.
+fn get_metadata_accounts(
     client: &RpcClient,
-    update_authority: &str,
+    offset: usize,
+    key: &Pubkey,
+    overrides: &GpaOverrides,
 ) -> Result<Vec<(Pubkey, Account)>> {
-    let update_authority = Pubkey::from_str(update_authority)?;
-
     #[allow(deprecated)]
-    let filter = RpcFilterType::Memcmp(Memcmp {
-        offset: 1, // key
-        bytes: MemcmpEncodedBytes::Bytes(update_authority.to_bytes().to_vec()),
+    let mut filters = vec![RpcFilterType::Memcmp(Memcmp {
+        offset: overrides.offset.unwrap_or(offset),
+        bytes: MemcmpEncodedBytes::Bytes(key.to_bytes().to_vec()),
         encoding: None,
-    });
+    })];
+    if let Some(data_size) = overrides.data_size {
+        filters.push(RpcFilterType::DataSize(data_size));
+    }
+
     let config = RpcProgramAccountsConfig {
-        filters: Some(vec![filter]),
+        filters: Some(filters),
         account_config: RpcAccountInfoConfig {
             encoding: Some(UiAccountEncoding::Base64),
             data_slice: None,
@@ -486,11 +623,27 @@ fn get_mints_by_update_authority(
         with_context: None,
     };
 
-    let accounts = client.get_program_accounts_with_config(&TOKEN_METADATA_PROGRAM_ID, config)?;
+    let program_id = overrides.program_id.unwrap_or(TOKEN_METADATA_PROGRAM_ID);
+    let accounts = client.get_program_accounts_with_config(&program_id, config)?;
 
     Ok(accounts)
 }
 
+fn get_mints_by_update_authority(
+    client: &RpcClient,
+    update_authority: &str,
+    overrides: &GpaOverrides,
+) -> Result<Vec<(Pubkey, Account)>> {
+    let update_authority = Pubkey::from_str(update_authority)?;
+
+    get_metadata_accounts(
+        client,
+        1, // key
+        &update_authority,
+        overrides,
+    )
+}
+
 pub fn snapshot_cm_accounts(client: RpcClient, update_authority: &str, output: &str) -> Result<()> {
     let accounts = get_cm_accounts_by_update_authority(&client, update_authority)?;
 
@@ -518,7 +671,9 @@ pub fn snapshot_cm_accounts(client: RpcClient, update_authority: &str, output: &
         candy_machine_accounts,
     };
 
-    let mut file = File::create(format!("{output}/{update_authority}_accounts.json"))?;
+    let mut file = File::create(output_path(format!(
+        "{output}/{update_authority}_accounts.json"
+    )))?;
     serde_json::to_writer_pretty(&mut file, &candy_machine_program_accounts)?;
 
     Ok(())
@@ -559,52 +714,45 @@ pub fn get_cm_creator_accounts(
     client: &RpcClient,
     creator: &str,
     position: usize,
+) -> Result<Vec<(Pubkey, Account)>> {
+    get_creator_accounts(client, creator, position, &GpaOverrides::default())
+}
+
+fn get_creator_accounts(
+    client: &RpcClient,
+    creator: &str,
+    position: usize,
+    overrides: &GpaOverrides,
 ) -> Result<Vec<(Pubkey, Account)>> {
     if position > 4 {
         error!("CM Creator position cannot be greator than 4");
         std::process::exit(1);
     }
     let creator = Pubkey::from_str(creator)?;
-    #[allow(deprecated)]
-    let filter = RpcFilterType::Memcmp(Memcmp {
-        offset: 1 + // key
-            32 + // update auth
-            32 + // mint
-            4 + // name string length
-            MAX_NAME_LENGTH + // name
-            4 + // uri string length
-            MAX_URI_LENGTH + // uri*
-            4 + // symbol string length
-            MAX_SYMBOL_LENGTH + // symbol
-            2 + // seller fee basis points
-            1 + // whether or not there is a creators vec
-            4 + // creators
-            position * // index for each creator
-            (
-                32 + // address
-                1 + // verified
-                1 // share
-            ),
-        bytes: MemcmpEncodedBytes::Bytes(creator.to_bytes().to_vec()),
-        encoding: None,
-    });
-
-    let config = RpcProgramAccountsConfig {
-        filters: Some(vec![filter]),
-        account_config: RpcAccountInfoConfig {
-            encoding: Some(UiAccountEncoding::Base64),
-            data_slice: None,
-            commitment: Some(CommitmentConfig {
-                commitment: CommitmentLevel::Confirmed,
-            }),
-            min_context_slot: None,
-        },
-        with_context: None,
-    };
 
-    let accounts = client.get_program_accounts_with_config(&TOKEN_METADATA_PROGRAM_ID, config)?;
+    get_metadata_accounts(client, creator_offset(position), &creator, overrides)
+}
 
-    Ok(accounts)
+/// Offset of the creator at `position` in a metadata account with padded strings.
+fn creator_offset(position: usize) -> usize {
+    1 + // key
+        32 + // update auth
+        32 + // mint
+        4 + // name string length
+        MAX_NAME_LENGTH + // name
+        4 + // uri string length
+        MAX_URI_LENGTH + // uri*
+        4 + // symbol string length
+        MAX_SYMBOL_LENGTH + // symbol
+        2 + // seller fee basis points
+        1 + // whether or not there is a creators vec
+        4 + // creators
+        position * // index for each creator
+        (
+            32 + // address
+            1 + // verified
+            1 // share
+        )
 }
 
 fn get_holder_token_accounts(
@@ -655,6 +803,47 @@ fn parse_token_amount(data: &ParsedAccount) -> Result<u64> {
     Ok(amount)
 }
 
+fn parse_delegate(data: &ParsedAccount) -> Option<String> {
+    data.parsed
+        .get("info")?
+        .get("delegate")?
+        .as_str()
+        .map(str::to_string)
+}
+
+fn parse_frozen(data: &ParsedAccount) -> bool {
+    data.parsed
+        .get("info")
+        .and_then(|info| info.get("state"))
+        .and_then(|state| state.as_str())
+        == Some("frozen")
+}
+
+/// Build a holder from the token account holding the NFT, noting whether it's the owner's
+/// associated token account and who else can move or has locked the token.
+fn holder_from_token_account(
+    owner: String,
+    mint: &Pubkey,
+    metadata: String,
+    token_account: String,
+    data: &ParsedAccount,
+) -> Holder {
+    let ata = Pubkey::from_str(&owner)
+        .map(|owner| get_associated_token_address(&owner, mint).to_string())
+        .unwrap_or_default();
+    let is_ata = ata == token_account;
+    Holder {
+        owner,
+        mint: mint.to_string(),
+        metadata,
+        ata,
+        token_account: Some(token_account),
+        is_ata: Some(is_ata),
+        delegate: parse_delegate(data),
+        frozen: parse_frozen(data),
+    }
+}
+
 fn parse_owner(data: &ParsedAccount) -> Result<String> {
     let owner = data
         .parsed
@@ -8,7 +8,7 @@ pub use retry::{delay::Exponential, retry};
 pub use serde::Serialize;
 pub use solana_account_decoder::{
     parse_account_data::{parse_account_data, AccountAdditionalData, ParsedAccount},
-    UiAccountEncoding,
+    UiAccountEncoding, UiDataSliceConfig,
 };
 pub use solana_client::{
     rpc_client::RpcClient,
@@ -29,14 +29,20 @@ pub use std::{
 
 mod das_api;
 mod data;
+mod diff;
+mod fungible;
 mod indexer_methods;
 mod methods;
+mod output;
 mod print_editions;
 mod process;
 
 pub use das_api::*;
 pub use data::*;
+pub use diff::*;
+pub use fungible::*;
 pub use indexer_methods::*;
 pub use methods::*;
+pub use output::*;
 pub use print_editions::*;
 pub use process::*;
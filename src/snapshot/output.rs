@@ -0,0 +1,233 @@
+use std::{
+    collections::BTreeSet,
+    fmt::{self, Display},
+    io::Write,
+    path::{Path, PathBuf},
+};
+
+use rusqlite::{types::Value as SqlValue, Connection};
+use serde_json::{Map, Value};
+
+use super::*;
+use crate::files::write_json;
+use crate::workspace::output_path;
+
+/// File format snapshots are written in. NDJSON holds one item per line, and CSV and SQLite
+/// one flat row per item, for importing into spreadsheets and databases. The `.zst` formats
+/// are compressed with zstd.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SnapshotFormat {
+    Json,
+    JsonZst,
+    Ndjson,
+    NdjsonZst,
+    Csv,
+    Sqlite,
+}
+
+impl FromStr for SnapshotFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "json" => Ok(SnapshotFormat::Json),
+            "json.zst" => Ok(SnapshotFormat::JsonZst),
+            "ndjson" => Ok(SnapshotFormat::Ndjson),
+            "ndjson.zst" => Ok(SnapshotFormat::NdjsonZst),
+            "csv" => Ok(SnapshotFormat::Csv),
+            "sqlite" => Ok(SnapshotFormat::Sqlite),
+            _ => Err(format!(
+                "Invalid format: {s}, expected json, json.zst, ndjson, ndjson.zst, csv or sqlite"
+            )),
+        }
+    }
+}
+
+impl Display for SnapshotFormat {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SnapshotFormat::Sqlite => write!(f, "sqlite"),
+            format => write!(f, "{}", format.extension()),
+        }
+    }
+}
+
+impl SnapshotFormat {
+    fn extension(&self) -> &'static str {
+        match self {
+            SnapshotFormat::Json => "json",
+            SnapshotFormat::JsonZst => "json.zst",
+            SnapshotFormat::Ndjson => "ndjson",
+            SnapshotFormat::NdjsonZst => "ndjson.zst",
+            SnapshotFormat::Csv => "csv",
+            SnapshotFormat::Sqlite => "db",
+        }
+    }
+
+    /// Whether the snapshot is written as a single JSON document, so it can hold more than
+    /// a list of items.
+    pub fn is_json(&self) -> bool {
+        matches!(self, SnapshotFormat::Json | SnapshotFormat::JsonZst)
+    }
+}
+
+/// Turn the items into flat rows. Plain values, like the mints of a mint list, become a
+/// single `column`; nested lists are joined with spaces and nested objects kept as JSON.
+fn flatten_rows(items: Value, column: &str) -> Result<(Vec<String>, Vec<Vec<Value>>)> {
+    let items = match items {
+        Value::Array(items) => items,
+        _ => return Err(anyhow!("Only lists of items can be written as rows")),
+    };
+
+    let objects: Vec<Map<String, Value>> = items
+        .into_iter()
+        .map(|item| match item {
+            Value::Object(object) => object,
+            value => Map::from_iter([(column.to_string(), value)]),
+        })
+        .collect();
+
+    // Optional fields can be missing from some items, so take every column any item has,
+    // in alphabetical order.
+    let mut columns: Vec<String> = objects
+        .iter()
+        .flat_map(|object| object.keys().cloned())
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .collect();
+
+    if columns.is_empty() {
+        columns.push(column.to_string());
+    }
+
+    let rows = objects
+        .into_iter()
+        .map(|mut object| {
+            columns
+                .iter()
+                .map(|c| match object.remove(c).unwrap_or(Value::Null) {
+                    Value::Array(values) => Value::String(
+                        values
+                            .iter()
+                            .map(|v| v.as_str().map_or_else(|| v.to_string(), String::from))
+                            .collect::<Vec<_>>()
+                            .join(" "),
+                    ),
+                    Value::Object(object) => Value::String(Value::Object(object).to_string()),
+                    value => value,
+                })
+                .collect()
+        })
+        .collect();
+
+    Ok((columns, rows))
+}
+
+fn csv_field(value: &Value) -> String {
+    let field = match value {
+        Value::Null => String::new(),
+        Value::String(s) => s.clone(),
+        value => value.to_string(),
+    };
+    if field.contains([',', '"', '\n']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field
+    }
+}
+
+fn write_csv(path: &Path, columns: &[String], rows: &[Vec<Value>]) -> Result<()> {
+    let mut f = File::create(path)?;
+    writeln!(f, "{}", columns.join(","))?;
+    for row in rows {
+        let fields: Vec<String> = row.iter().map(csv_field).collect();
+        writeln!(f, "{}", fields.join(","))?;
+    }
+    Ok(())
+}
+
+fn sql_value(value: Value) -> SqlValue {
+    match value {
+        Value::Null => SqlValue::Null,
+        Value::Bool(b) => SqlValue::Integer(b as i64),
+        Value::Number(n) => match n.as_i64() {
+            Some(i) => SqlValue::Integer(i),
+            None => SqlValue::Real(n.as_f64().unwrap_or_default()),
+        },
+        Value::String(s) => SqlValue::Text(s),
+        value => SqlValue::Text(value.to_string()),
+    }
+}
+
+fn write_sqlite(path: &Path, table: &str, columns: &[String], rows: Vec<Vec<Value>>) -> Result<()> {
+    // Each run writes a fresh database, like the JSON and CSV files.
+    if path.exists() {
+        std::fs::remove_file(path)?;
+    }
+    let mut conn = Connection::open(path)?;
+
+    // Columns are left untyped so SQLite keeps each value's own type.
+    let quoted: Vec<String> = columns.iter().map(|c| format!("\"{c}\"")).collect();
+    conn.execute(
+        &format!("CREATE TABLE \"{table}\" ({})", quoted.join(", ")),
+        [],
+    )?;
+
+    let tx = conn.transaction()?;
+    {
+        let placeholders = vec!["?"; columns.len()].join(", ");
+        let mut insert = tx.prepare(&format!(
+            "INSERT INTO \"{table}\" ({}) VALUES ({placeholders})",
+            quoted.join(", ")
+        ))?;
+        for row in rows {
+            let values: Vec<SqlValue> = row.into_iter().map(sql_value).collect();
+            insert.execute(rusqlite::params_from_iter(values))?;
+        }
+    }
+    tx.commit()?;
+    Ok(())
+}
+
+/// Write a snapshot to `<stem>.<extension>` of the format, returning the path.
+///
+/// JSON is written as is. For NDJSON, CSV and SQLite the items must be a list, written as
+/// one line or row each; SQLite puts them in a table named `table`.
+pub fn write_snapshot<T: Serialize + ?Sized>(
+    stem: impl AsRef<Path>,
+    table: &str,
+    items: &T,
+    format: SnapshotFormat,
+) -> Result<PathBuf> {
+    let path = output_path(format!(
+        "{}.{}",
+        stem.as_ref().display(),
+        format.extension()
+    ));
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+
+    // Compression and line splitting follow from the extension.
+    match format {
+        SnapshotFormat::Csv | SnapshotFormat::Sqlite => (),
+        SnapshotFormat::Ndjson | SnapshotFormat::NdjsonZst
+            if !serde_json::to_value(items)?.is_array() =>
+        {
+            return Err(anyhow!("Only lists of items can be written as NDJSON"));
+        }
+        _ => {
+            write_json(&path, items)?;
+            return Ok(path);
+        }
+    }
+
+    // Rows of plain values are mint lists, so the column is named after them.
+    let (columns, rows) = flatten_rows(serde_json::to_value(items)?, "mint")?;
+    match format {
+        SnapshotFormat::Csv => write_csv(&path, &columns, &rows)?,
+        SnapshotFormat::Sqlite => write_sqlite(&path, table, &columns, rows)?,
+        _ => unreachable!(),
+    }
+    Ok(path)
+}
@@ -1,91 +1,205 @@
 use borsh::BorshDeserialize;
 use indexmap::IndexMap;
+use log::warn;
 use metaboss_lib::{
-    decode::decode_metadata_from_mint, derive::derive_edition_pda,
-    snapshot::get_metadata_accounts_by_creator,
+    decode::decode_metadata_from_mint,
+    derive::derive_edition_pda,
+    snapshot::{get_edition_accounts_by_master, get_metadata_accounts_by_creator},
 };
 use mpl_token_metadata::accounts::Edition;
+use spl_token_2022::{extension::StateWithExtensions, state::Account as TokenAccount};
+use std::collections::HashMap;
 
-use crate::spinner::create_spinner;
+use crate::constants::MAX_MULTIPLE_ACCOUNTS;
+use crate::decode::find_edition_mint_in_creation;
+use crate::spinner::{create_progress_bar, create_spinner};
+use crate::workspace::output_path;
 
 use super::*;
 
 // Process
-// Decode master edition NFT metadata account
-// Find first verified creator
-// Snapshot all metadata accounts with the same first verified creator
-// Decode the metadata accounts
-// Get the mint from the metadata accounts
-// Derive the edition for each
-// Decode the edition and check if it's a master or a print
-// If it's a print and it's parent is the master edition, add it to the list
+// Find all edition accounts whose parent is the master edition
+// Snapshot all metadata accounts with the master's first verified creator
+// Match their mints to the edition accounts by deriving each mint's edition
+// For editions still without a mint, find it in the transaction that created the edition
+// Find the token account holding each print and its owner
+
+/// A print of the master edition, with the wallet that currently holds it.
+#[derive(Debug, Serialize)]
+pub struct PrintEdition {
+    pub edition: u64,
+    pub mint: Option<String>,
+    pub edition_account: String,
+    pub owner: Option<String>,
+    pub token_account: Option<String>,
+}
 
 pub struct SnapshotPrintEditionsArgs {
     pub client: RpcClient,
     pub master_mint: String,
     pub creator: Option<String>,
     pub output: String,
+    pub format: SnapshotFormat,
 }
 
-pub async fn snapshot_print_editions<'a>(args: SnapshotPrintEditionsArgs) -> Result<()> {
+pub async fn snapshot_print_editions(args: SnapshotPrintEditionsArgs) -> Result<()> {
     let master_mint_pubkey = Pubkey::from_str(&args.master_mint)?;
     let master_edition_pubkey = derive_edition_pda(&master_mint_pubkey);
 
     let first_verified_creator = if let Some(creator) = args.creator {
-        creator
+        Ok(creator)
     } else {
         let master_nft = decode_metadata_from_mint(&args.client, args.master_mint.clone())?;
 
         master_nft
             .creators
-            .ok_or(anyhow!("No creators found"))?
-            .iter()
-            .find(|c| c.verified)
-            .ok_or_else(|| anyhow!("No verified creators found"))?
-            .address
-            .to_string()
+            .ok_or(anyhow!("No creators found"))
+            .and_then(|creators| {
+                creators
+                    .iter()
+                    .find(|c| c.verified)
+                    .map(|c| c.address.to_string())
+                    .ok_or_else(|| anyhow!("No verified creators found"))
+            })
     };
 
-    let spinner = create_spinner("Fetching metadata accounts...");
-    let accounts = get_metadata_accounts_by_creator(&args.client, &first_verified_creator, 0)?;
+    let spinner = create_spinner("Fetching edition accounts...");
+    let edition_accounts =
+        get_edition_accounts_by_master(&args.client, &master_edition_pubkey.to_string())?;
     spinner.finish();
 
-    let spinner = create_spinner("Converting to mints...");
-    let mints = accounts
-        .into_iter()
-        .map(|(_, mint)| mint)
-        .map(|a| Metadata::deserialize(&mut a.data.as_slice()).unwrap())
-        .map(|m| m.mint)
-        .collect::<Vec<_>>();
-    spinner.finish();
-
-    let mut edition_mints = IndexMap::new();
-
-    let spinner = create_spinner("Finding edition mints...");
-    for m in mints {
-        let edition = derive_edition_pda(&m);
-        let edition_account = &args.client.get_account(&edition)?;
-        let edition = match Edition::deserialize(&mut edition_account.data.as_slice()) {
+    // Every print of the master, by edition account, waiting for its mint.
+    let mut prints: HashMap<Pubkey, PrintEdition> = HashMap::new();
+    for (edition_pubkey, account) in edition_accounts {
+        let edition = match Edition::deserialize(&mut account.data.as_slice()) {
             Ok(e) => e,
-            Err(_) => continue,
+            Err(e) => {
+                error!("Failed to decode edition account {edition_pubkey}: {e}");
+                continue;
+            }
         };
+        prints.insert(
+            edition_pubkey,
+            PrintEdition {
+                edition: edition.edition,
+                mint: None,
+                edition_account: edition_pubkey.to_string(),
+                owner: None,
+                token_account: None,
+            },
+        );
+    }
+    println!("Found {} editions", prints.len());
+
+    // Most prints share the master's first verified creator, so their mints come from a
+    // single metadata query.
+    match first_verified_creator {
+        Ok(creator) => {
+            let spinner = create_spinner("Fetching metadata accounts...");
+            let accounts = get_metadata_accounts_by_creator(&args.client, &creator, 0)?;
+            spinner.finish();
+
+            for (_, account) in accounts {
+                let mint = match Metadata::deserialize(&mut account.data.as_slice()) {
+                    Ok(metadata) => metadata.mint,
+                    Err(_) => continue,
+                };
+                if let Some(print) = prints.get_mut(&derive_edition_pda(&mint)) {
+                    print.mint = Some(mint.to_string());
+                }
+            }
+        }
+        Err(e) => warn!("Not matching prints by creator: {e}"),
+    }
 
-        if edition.parent == master_edition_pubkey {
-            edition_mints.insert(edition.edition, m.to_string());
+    // The rest are found from the transaction that created their edition account.
+    let unresolved: Vec<Pubkey> = prints
+        .iter()
+        .filter(|(_, print)| print.mint.is_none())
+        .map(|(edition_pubkey, _)| *edition_pubkey)
+        .collect();
+    if !unresolved.is_empty() {
+        let pb = create_progress_bar("Resolving remaining mints...", unresolved.len() as u64);
+        for edition_pubkey in unresolved {
+            match find_edition_mint_in_creation(&args.client, &edition_pubkey, &edition_pubkey) {
+                Ok(mint) => prints.get_mut(&edition_pubkey).unwrap().mint = Some(mint.to_string()),
+                Err(e) => warn!("Failed to find the mint of edition {edition_pubkey}: {e}"),
+            }
+            pb.inc(1);
         }
+        pb.finish_and_clear();
     }
-    spinner.finish();
 
-    edition_mints.sort_keys();
+    let mut prints: Vec<PrintEdition> = prints.into_values().collect();
+    prints.sort_by_key(|print| print.edition);
+
+    let pb = create_progress_bar("Finding owners...", prints.len() as u64);
+    for chunk in prints.chunks_mut(MAX_MULTIPLE_ACCOUNTS) {
+        for print in chunk.iter_mut() {
+            pb.inc(1);
+            let mint = match &print.mint {
+                Some(mint) => Pubkey::from_str(mint)?,
+                None => continue,
+            };
+            // A print has a supply of one, so the largest account is the one holding it.
+            let largest = match args.client.get_token_largest_accounts(&mint) {
+                Ok(largest) => largest,
+                Err(e) => {
+                    warn!("Failed to get the token accounts of {mint}: {e}");
+                    continue;
+                }
+            };
+            print.token_account = largest
+                .into_iter()
+                .find(|balance| balance.amount.amount != "0")
+                .map(|balance| balance.address);
+        }
+
+        let token_accounts: Vec<Pubkey> = chunk
+            .iter()
+            .filter_map(|print| print.token_account.as_deref())
+            .map(Pubkey::from_str)
+            .collect::<Result<_, _>>()?;
+        let accounts = args.client.get_multiple_accounts(&token_accounts)?;
+        let mut accounts = accounts.into_iter();
+        for print in chunk
+            .iter_mut()
+            .filter(|print| print.token_account.is_some())
+        {
+            print.owner = accounts.next().flatten().and_then(|a| {
+                StateWithExtensions::<TokenAccount>::unpack(&a.data)
+                    .ok()
+                    .map(|token| token.base.owner.to_string())
+            });
+        }
+    }
+    pb.finish_and_clear();
 
-    println!("Found {} editions", edition_mints.len());
+    let burned = prints.iter().filter(|print| print.owner.is_none()).count();
+    if burned > 0 {
+        println!("{burned} editions have no current owner, their mint wasn't found or was burned");
+    }
 
     println!("Writing to file...");
-    let mut file = File::create(format!(
-        "{}/{}_mint_accounts.json",
-        args.output, &args.master_mint
-    ))?;
-    serde_json::to_writer_pretty(&mut file, &edition_mints)?;
+    let edition_mints: IndexMap<u64, &String> = prints
+        .iter()
+        .filter_map(|print| Some((print.edition, print.mint.as_ref()?)))
+        .collect();
+    // The edition to mint map is only written as JSON, the report has the same columns.
+    if args.format == SnapshotFormat::Json {
+        let mut file = File::create(output_path(format!(
+            "{}/{}_mint_accounts.json",
+            args.output, &args.master_mint
+        )))?;
+        serde_json::to_writer_pretty(&mut file, &edition_mints)?;
+    }
+
+    write_snapshot(
+        format!("{}/{}_prints", args.output, &args.master_mint),
+        "prints",
+        &prints,
+        args.format,
+    )?;
 
     Ok(())
 }
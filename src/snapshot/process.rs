@@ -16,6 +16,7 @@ pub async fn process_snapshot(
             group_key,
             group_value,
             output,
+            format,
             delay,
         } => {
             snapshot_holders(HoldersArgs {
@@ -23,6 +24,7 @@ pub async fn process_snapshot(
                 group_key,
                 group_value,
                 output,
+                format,
                 delay,
             })
             .await
@@ -32,6 +34,7 @@ pub async fn process_snapshot(
             group_value,
             creator_position,
             output,
+            format,
             delay,
         } => {
             snapshot_mints(MintsArgs {
@@ -40,6 +43,7 @@ pub async fn process_snapshot(
                 group_value,
                 creator_position,
                 output,
+                format,
                 delay,
             })
             .await
@@ -47,12 +51,14 @@ pub async fn process_snapshot(
         SnapshotSubcommands::Fvca {
             creator,
             output,
+            format,
             delay,
         } => {
             fcva_mints(FcvaArgs {
                 rpc_url,
                 creator,
                 output,
+                format,
                 delay,
             })
             .await
@@ -60,16 +66,45 @@ pub async fn process_snapshot(
         SnapshotSubcommands::Mcc {
             mcc_id,
             output,
+            format,
             delay,
         } => {
             mcc_mints(MccArgs {
                 rpc_url,
                 mcc_id,
                 output,
+                format,
                 delay,
             })
             .await
         }
+        SnapshotSubcommands::CnftHolders {
+            collection,
+            output,
+            format,
+            delay,
+        } => {
+            snapshot_cnft_holders(CnftHoldersArgs {
+                rpc_url,
+                collection,
+                output,
+                format,
+                delay,
+            })
+            .await
+        }
+        SnapshotSubcommands::FungibleHolders {
+            mint,
+            min_amount,
+            output,
+            format,
+        } => snapshot_fungible_holders(SnapshotFungibleHoldersArgs {
+            client,
+            mint,
+            min_amount,
+            output,
+            format,
+        }),
         SnapshotSubcommands::MintsGpa {
             creator,
             position,
@@ -77,7 +112,11 @@ pub async fn process_snapshot(
             v2,
             v3,
             allow_unverified,
+            program_id,
+            offset,
+            data_size,
             output,
+            format,
         } => snapshot_mints_gpa(
             client,
             SnapshotMintsGpaArgs {
@@ -87,7 +126,13 @@ pub async fn process_snapshot(
                 v2,
                 v3,
                 allow_unverified,
+                overrides: GpaOverrides {
+                    program_id,
+                    offset,
+                    data_size,
+                },
                 output,
+                format,
             },
         ),
         SnapshotSubcommands::HoldersGpa {
@@ -98,7 +143,11 @@ pub async fn process_snapshot(
             v2,
             v3,
             allow_unverified,
+            program_id,
+            offset,
+            data_size,
             output,
+            format,
         } => snapshot_holders_gpa(
             client,
             SnapshotHoldersGpaArgs {
@@ -109,21 +158,40 @@ pub async fn process_snapshot(
                 v2,
                 v3,
                 allow_unverified,
+                overrides: GpaOverrides {
+                    program_id,
+                    offset,
+                    data_size,
+                },
                 output,
+                format,
             },
         ),
         SnapshotSubcommands::Prints {
             master_mint,
             creator,
             output,
+            format,
         } => {
             snapshot_print_editions(SnapshotPrintEditionsArgs {
                 client,
                 master_mint,
                 creator,
                 output,
+                format,
             })
             .await
         }
+        SnapshotSubcommands::Diff {
+            old,
+            new,
+            format,
+            output,
+        } => snapshot_diff(SnapshotDiffArgs {
+            old,
+            new,
+            format,
+            output,
+        }),
     }
 }
@@ -1,4 +1,13 @@
-use indicatif::{ProgressBar, ProgressStyle};
+use indicatif::{ProgressBar, ProgressDrawTarget, ProgressStyle};
+
+use crate::output;
+
+// Progress is drawn to stderr, so it's hidden separately from the text in quiet mode.
+fn hide_when_quiet(bar: &ProgressBar) {
+    if output::quiet() {
+        bar.set_draw_target(ProgressDrawTarget::hidden());
+    }
+}
 
 pub fn create_spinner(msg: &'static str) -> ProgressBar {
     let spinner = ProgressBar::new_spinner();
@@ -9,6 +18,7 @@ pub fn create_spinner(msg: &'static str) -> ProgressBar {
             .template("{spinner:.blue}{msg}"),
     );
     spinner.set_message(msg);
+    hide_when_quiet(&spinner);
     spinner
 }
 
@@ -24,6 +34,7 @@ pub fn create_alt_spinner(msg: &'static str) -> ProgressBar {
             .template("{spinner:.blue} {msg}"),
     );
     spinner.set_message(msg);
+    hide_when_quiet(&spinner);
     spinner
 }
 
@@ -34,5 +45,6 @@ pub fn create_progress_bar(msg: &'static str, len: u64) -> ProgressBar {
             .template("{spinner:.blue} {msg} {wide_bar:.cyan/blue} {pos:>7}/{len:7} {eta_precise}"),
     );
     bar.set_message(msg);
+    hide_when_quiet(&bar);
     bar
 }
@@ -0,0 +1,279 @@
+//! Submit instructions as Squads v4 multisig proposals instead of signing them directly.
+//!
+//! The instruction is built with a placeholder authority, which is then swapped for the
+//! multisig vault so it executes with the vault's signature once the proposal is approved.
+
+use anyhow::{anyhow, Result};
+use borsh::BorshSerialize;
+use solana_client::rpc_client::RpcClient;
+use solana_program::{
+    hash::hash,
+    instruction::{AccountMeta, Instruction},
+    message::Message,
+    pubkey,
+    pubkey::Pubkey,
+    system_program,
+};
+use solana_sdk::signature::{Keypair, Signer};
+use std::str::FromStr;
+
+use crate::constants::{SQUADS_MULTISIG, SQUADS_VAULT_INDEX};
+use crate::utils::send_and_confirm_transaction;
+
+pub const SQUADS_PROGRAM_ID: Pubkey = pubkey!("SQDS4ep65T869zMMBKyuUq6aD6EgTu8psMjkvj52pCf");
+
+const SEED_PREFIX: &[u8] = b"multisig";
+const SEED_TRANSACTION: &[u8] = b"transaction";
+const SEED_PROPOSAL: &[u8] = b"proposal";
+const SEED_VAULT: &[u8] = b"vault";
+
+// Anchor discriminator + create_key + config_authority + threshold + time_lock
+const TRANSACTION_INDEX_OFFSET: usize = 8 + 32 + 32 + 2 + 4;
+
+pub struct MultisigTarget {
+    pub multisig: Pubkey,
+    pub vault_index: u8,
+}
+
+impl MultisigTarget {
+    pub fn vault(&self) -> Pubkey {
+        derive_vault_pda(&self.multisig, self.vault_index)
+    }
+}
+
+/// The multisig set with the global `--multisig` option, if any.
+pub fn active_multisig() -> Result<Option<MultisigTarget>> {
+    let multisig = SQUADS_MULTISIG.read().unwrap().clone();
+
+    multisig
+        .map(|address| {
+            Ok(MultisigTarget {
+                multisig: Pubkey::from_str(&address)
+                    .map_err(|_| anyhow!("Invalid multisig address: {address}"))?,
+                vault_index: *SQUADS_VAULT_INDEX.read().unwrap(),
+            })
+        })
+        .transpose()
+}
+
+pub fn derive_vault_pda(multisig: &Pubkey, vault_index: u8) -> Pubkey {
+    Pubkey::find_program_address(
+        &[SEED_PREFIX, multisig.as_ref(), SEED_VAULT, &[vault_index]],
+        &SQUADS_PROGRAM_ID,
+    )
+    .0
+}
+
+pub fn derive_transaction_pda(multisig: &Pubkey, transaction_index: u64) -> Pubkey {
+    Pubkey::find_program_address(
+        &[
+            SEED_PREFIX,
+            multisig.as_ref(),
+            SEED_TRANSACTION,
+            &transaction_index.to_le_bytes(),
+        ],
+        &SQUADS_PROGRAM_ID,
+    )
+    .0
+}
+
+pub fn derive_proposal_pda(multisig: &Pubkey, transaction_index: u64) -> Pubkey {
+    Pubkey::find_program_address(
+        &[
+            SEED_PREFIX,
+            multisig.as_ref(),
+            SEED_TRANSACTION,
+            &transaction_index.to_le_bytes(),
+            SEED_PROPOSAL,
+        ],
+        &SQUADS_PROGRAM_ID,
+    )
+    .0
+}
+
+/// Replace every reference to `placeholder` with `vault`.
+pub fn retarget_authority(ix: &mut Instruction, placeholder: &Pubkey, vault: &Pubkey) {
+    for meta in ix.accounts.iter_mut() {
+        if meta.pubkey == *placeholder {
+            meta.pubkey = *vault;
+        }
+    }
+}
+
+/// Build instructions with a throwaway authority keypair and submit them as a proposal
+/// for the vault to execute.
+pub fn propose_with_vault_authority<F>(
+    client: &RpcClient,
+    member: Keypair,
+    target: &MultisigTarget,
+    build: F,
+) -> Result<()>
+where
+    F: FnOnce(&Keypair) -> Result<Vec<Instruction>>,
+{
+    let placeholder = Keypair::new();
+    let vault = target.vault();
+
+    let mut ixs = build(&placeholder)?;
+    for ix in ixs.iter_mut() {
+        retarget_authority(ix, &placeholder.pubkey(), &vault);
+    }
+
+    let transaction_index = next_transaction_index(client, &target.multisig)?;
+    let proposal_ixs = create_proposal_ixs(
+        &member.pubkey(),
+        &target.multisig,
+        target.vault_index,
+        transaction_index,
+        &ixs,
+    )?;
+
+    send_and_confirm_transaction(client, member, &proposal_ixs)?;
+
+    println!(
+        "Created proposal {} (transaction #{transaction_index}) for vault {vault}",
+        derive_proposal_pda(&target.multisig, transaction_index)
+    );
+    println!("It will execute once the multisig threshold approves it.");
+
+    Ok(())
+}
+
+fn next_transaction_index(client: &RpcClient, multisig: &Pubkey) -> Result<u64> {
+    let account = client.get_account(multisig)?;
+    if account.owner != SQUADS_PROGRAM_ID {
+        return Err(anyhow!("{multisig} is not a Squads v4 multisig"));
+    }
+
+    let bytes = account
+        .data
+        .get(TRANSACTION_INDEX_OFFSET..TRANSACTION_INDEX_OFFSET + 8)
+        .ok_or_else(|| anyhow!("Multisig account data is too short"))?;
+
+    Ok(u64::from_le_bytes(bytes.try_into()?) + 1)
+}
+
+/// Vault transaction, proposal and the member's approval, in a single transaction.
+pub fn create_proposal_ixs(
+    member: &Pubkey,
+    multisig: &Pubkey,
+    vault_index: u8,
+    transaction_index: u64,
+    ixs: &[Instruction],
+) -> Result<Vec<Instruction>> {
+    let vault = derive_vault_pda(multisig, vault_index);
+    let transaction = derive_transaction_pda(multisig, transaction_index);
+    let proposal = derive_proposal_pda(multisig, transaction_index);
+
+    let vault_transaction_create = Instruction {
+        program_id: SQUADS_PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new(*multisig, false),
+            AccountMeta::new(transaction, false),
+            AccountMeta::new_readonly(*member, true),
+            AccountMeta::new(*member, true),
+            AccountMeta::new_readonly(system_program::ID, false),
+        ],
+        data: anchor_data(
+            "vault_transaction_create",
+            &VaultTransactionCreateArgs {
+                vault_index,
+                ephemeral_signers: 0,
+                transaction_message: compile_transaction_message(&vault, ixs)?,
+                memo: Some(String::from("metaboss")),
+            },
+        )?,
+    };
+
+    let proposal_create = Instruction {
+        program_id: SQUADS_PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new_readonly(*multisig, false),
+            AccountMeta::new(proposal, false),
+            AccountMeta::new_readonly(*member, true),
+            AccountMeta::new(*member, true),
+            AccountMeta::new_readonly(system_program::ID, false),
+        ],
+        data: anchor_data(
+            "proposal_create",
+            &ProposalCreateArgs {
+                transaction_index,
+                draft: false,
+            },
+        )?,
+    };
+
+    let proposal_approve = Instruction {
+        program_id: SQUADS_PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new_readonly(*multisig, false),
+            AccountMeta::new(*member, true),
+            AccountMeta::new(proposal, false),
+        ],
+        data: anchor_data("proposal_approve", &ProposalVoteArgs { memo: None })?,
+    };
+
+    Ok(vec![
+        vault_transaction_create,
+        proposal_create,
+        proposal_approve,
+    ])
+}
+
+#[derive(BorshSerialize)]
+struct VaultTransactionCreateArgs {
+    vault_index: u8,
+    ephemeral_signers: u8,
+    transaction_message: Vec<u8>,
+    memo: Option<String>,
+}
+
+#[derive(BorshSerialize)]
+struct ProposalCreateArgs {
+    transaction_index: u64,
+    draft: bool,
+}
+
+#[derive(BorshSerialize)]
+struct ProposalVoteArgs {
+    memo: Option<String>,
+}
+
+fn anchor_data<T: BorshSerialize>(name: &str, args: &T) -> Result<Vec<u8>> {
+    let mut data = hash(format!("global:{name}").as_bytes()).to_bytes()[..8].to_vec();
+    data.extend(args.try_to_vec()?);
+    Ok(data)
+}
+
+/// Serialize instructions into Squads' compact `TransactionMessage` format, which uses
+/// u8 length prefixes everywhere except instruction data (u16).
+fn compile_transaction_message(vault: &Pubkey, ixs: &[Instruction]) -> Result<Vec<u8>> {
+    let message = Message::new(ixs, Some(vault));
+    let header = message.header;
+
+    let num_signers = header.num_required_signatures;
+    let num_writable_signers = num_signers - header.num_readonly_signed_accounts;
+    let num_non_signers = message.account_keys.len() as u8 - num_signers;
+    let num_writable_non_signers = num_non_signers - header.num_readonly_unsigned_accounts;
+
+    let mut out = vec![num_signers, num_writable_signers, num_writable_non_signers];
+
+    out.push(u8::try_from(message.account_keys.len())?);
+    for key in &message.account_keys {
+        out.extend_from_slice(key.as_ref());
+    }
+
+    out.push(u8::try_from(message.instructions.len())?);
+    for ix in &message.instructions {
+        out.push(ix.program_id_index);
+        out.push(u8::try_from(ix.accounts.len())?);
+        out.extend_from_slice(&ix.accounts);
+        out.extend_from_slice(&u16::try_from(ix.data.len())?.to_le_bytes());
+        out.extend_from_slice(&ix.data);
+    }
+
+    // No address lookup tables.
+    out.push(0);
+
+    Ok(out)
+}
@@ -0,0 +1,294 @@
+use std::{
+    collections::HashSet,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{anyhow, Result};
+use metaboss_lib::{
+    data::Priority,
+    decode::decode_metadata_from_mint,
+    derive::{derive_edition_pda, derive_metadata_pda, derive_token_record_pda},
+};
+use mpl_token_metadata::{
+    instructions::TransferV1Builder,
+    types::{ProgrammableConfig, TokenStandard},
+};
+use serde::Serialize;
+use solana_client::rpc_client::RpcClient;
+use solana_program::pubkey::Pubkey;
+use solana_sdk::{instruction::Instruction, signature::Keypair, signer::Signer};
+use spl_associated_token_account::get_associated_token_address_with_program_id;
+use spl_token_2022::{extension::StateWithExtensions, state::Mint};
+
+use super::MPL_TOKEN_AUTH_RULES_ID;
+use crate::{
+    checkpoint,
+    files::write_json,
+    parse::{parse_keypair, parse_solana_config},
+    pipeline::Pipeline,
+    spinner::create_progress_bar,
+    utils::{
+        create_token_22_if_missing_instruction, create_token_if_missing_instruction,
+        get_token_program,
+    },
+    workspace::output_path,
+};
+
+const TRANSFER_BATCH_COMMAND: &str = "transfer-batch";
+
+pub struct TransferBatchArgs {
+    pub client: RpcClient,
+    pub keypair: Option<String>,
+    pub file: PathBuf,
+    pub priority: Priority,
+}
+
+#[derive(Debug)]
+struct TransferRow {
+    line: usize,
+    mint: Pubkey,
+    recipient: Pubkey,
+    amount: u64,
+}
+
+impl TransferRow {
+    /// Rows are tracked in the checkpoint by mint and recipient.
+    fn key(&self) -> String {
+        format!("{}:{}", self.mint, self.recipient)
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct TransferResult {
+    line: usize,
+    mint: String,
+    recipient: String,
+    amount: u64,
+    signature: Option<String>,
+    error: Option<String>,
+}
+
+/// Read the rows of a `mint,recipient[,amount]` CSV, skipping blank lines and a header row.
+fn read_transfers(path: &Path) -> Result<Vec<TransferRow>> {
+    let contents =
+        fs::read_to_string(path).map_err(|e| anyhow!("Failed to read {}: {e}", path.display()))?;
+
+    let mut rows = vec![];
+    for (i, line) in contents.lines().enumerate() {
+        let line_number = i + 1;
+        let fields: Vec<&str> = line.split(',').map(|f| f.trim()).collect();
+        if fields.iter().all(|f| f.is_empty()) {
+            continue;
+        }
+        if line_number == 1 && fields[0].eq_ignore_ascii_case("mint") {
+            continue;
+        }
+
+        let (mint, recipient, amount) = match fields.as_slice() {
+            [mint, recipient] => (mint, recipient, "1"),
+            [mint, recipient, ""] => (mint, recipient, "1"),
+            [mint, recipient, amount] => (mint, recipient, *amount),
+            _ => {
+                return Err(anyhow!(
+                    "Line {line_number}: expected mint,recipient[,amount], got {line}"
+                ))
+            }
+        };
+        rows.push(TransferRow {
+            line: line_number,
+            mint: mint
+                .parse()
+                .map_err(|_| anyhow!("Line {line_number}: invalid mint {mint}"))?,
+            recipient: recipient
+                .parse()
+                .map_err(|_| anyhow!("Line {line_number}: invalid recipient {recipient}"))?,
+            amount: amount
+                .parse()
+                .map_err(|_| anyhow!("Line {line_number}: invalid amount {amount}"))?,
+        });
+    }
+    Ok(rows)
+}
+
+/// Create the recipient's token account if it's missing, then transfer through Token
+/// Metadata, or the token program for tokens without metadata.
+fn transfer_instructions(
+    client: &RpcClient,
+    authority: &Pubkey,
+    row: &TransferRow,
+) -> Result<Vec<Instruction>> {
+    let mint = row.mint;
+    let token_program = get_token_program(client, &mint)?;
+    let source_token =
+        get_associated_token_address_with_program_id(authority, &mint, &token_program);
+    let destination_token =
+        get_associated_token_address_with_program_id(&row.recipient, &mint, &token_program);
+
+    let create_ix = if token_program == spl_token_2022::ID {
+        create_token_22_if_missing_instruction
+    } else {
+        create_token_if_missing_instruction
+    };
+    let mut instructions = vec![create_ix(
+        authority,
+        &destination_token,
+        &mint,
+        &row.recipient,
+        &destination_token,
+    )];
+
+    let md = match decode_metadata_from_mint(client, mint.to_string()) {
+        Ok(md) => md,
+        Err(_) => {
+            let decimals = StateWithExtensions::<Mint>::unpack(&client.get_account(&mint)?.data)?
+                .base
+                .decimals;
+            instructions.push(spl_token_2022::instruction::transfer_checked(
+                &token_program,
+                &source_token,
+                &mint,
+                &destination_token,
+                authority,
+                &[],
+                row.amount,
+                decimals,
+            )?);
+            return Ok(instructions);
+        }
+    };
+
+    let mut transfer_builder = TransferV1Builder::new();
+    transfer_builder
+        .payer(*authority)
+        .authority(*authority)
+        .token(source_token)
+        .token_owner(*authority)
+        .destination_token(destination_token)
+        .destination_owner(row.recipient)
+        .mint(mint)
+        .metadata(derive_metadata_pda(&mint))
+        .spl_token_program(token_program)
+        .amount(row.amount);
+
+    if matches!(
+        md.token_standard,
+        Some(
+            TokenStandard::ProgrammableNonFungible | TokenStandard::ProgrammableNonFungibleEdition
+        )
+    ) {
+        transfer_builder
+            .token_record(Some(derive_token_record_pda(&mint, &source_token)))
+            .destination_token_record(Some(derive_token_record_pda(&mint, &destination_token)));
+
+        if let Some(ProgrammableConfig::V1 {
+            rule_set: Some(auth_rules),
+        }) = md.programmable_config
+        {
+            transfer_builder.authorization_rules_program(Some(MPL_TOKEN_AUTH_RULES_ID));
+            transfer_builder.authorization_rules(Some(auth_rules));
+        }
+    }
+
+    if matches!(
+        md.token_standard,
+        Some(
+            TokenStandard::NonFungible
+                | TokenStandard::NonFungibleEdition
+                | TokenStandard::ProgrammableNonFungible
+                | TokenStandard::ProgrammableNonFungibleEdition
+        ) | None
+    ) {
+        transfer_builder.edition(Some(derive_edition_pda(&mint)));
+    }
+
+    instructions.push(transfer_builder.instruction());
+    Ok(instructions)
+}
+
+fn transfer_row(args: &TransferBatchArgs, keypair: &Keypair, row: &TransferRow) -> Result<String> {
+    let instructions = transfer_instructions(&args.client, &keypair.pubkey(), row)?;
+    let signature = Pipeline::with_priority(args.priority.clone()).run(
+        &args.client,
+        &[keypair],
+        instructions,
+    )?;
+    Ok(signature.to_string())
+}
+
+/// Transfer each row of a `mint,recipient[,amount]` CSV from the keypair's token accounts.
+///
+/// Each row's signature or error is written to `mb-transfer-batch.json`, and with `--resume`
+/// rows already sent are skipped on the next run.
+pub fn transfer_batch(args: TransferBatchArgs) -> Result<()> {
+    let keypair = parse_keypair(args.keypair.clone(), parse_solana_config());
+
+    let rows = read_transfers(&args.file)?;
+    if rows.is_empty() {
+        return Err(anyhow!("No transfers in {}", args.file.display()));
+    }
+    let remaining: HashSet<String> = checkpoint::remaining(
+        TRANSFER_BATCH_COMMAND,
+        rows.iter().map(|row| row.key()).collect(),
+    )
+    .into_iter()
+    .collect();
+    let skipped = rows.len() - remaining.len();
+    let rows: Vec<TransferRow> = rows
+        .into_iter()
+        .filter(|row| remaining.contains(&row.key()))
+        .collect();
+
+    let mut results = Vec::with_capacity(rows.len());
+    let mut failed = 0;
+    let pb = create_progress_bar("Transferring...", rows.len() as u64);
+    for row in &rows {
+        let (signature, error) = match transfer_row(&args, &keypair, row) {
+            Ok(signature) => {
+                checkpoint::record_done(
+                    TRANSFER_BATCH_COMMAND,
+                    &row.key(),
+                    Some(signature.clone()),
+                );
+                pb.println(format!(
+                    "Line {}: sent {} of {} to {}: {signature}",
+                    row.line, row.amount, row.mint, row.recipient
+                ));
+                (Some(signature), None)
+            }
+            Err(e) => {
+                checkpoint::record_failed(TRANSFER_BATCH_COMMAND, &row.key(), &e);
+                pb.println(format!(
+                    "Line {}: failed to send {} to {}: {e}",
+                    row.line, row.mint, row.recipient
+                ));
+                failed += 1;
+                (None, Some(e.to_string()))
+            }
+        };
+        results.push(TransferResult {
+            line: row.line,
+            mint: row.mint.to_string(),
+            recipient: row.recipient.to_string(),
+            amount: row.amount,
+            signature,
+            error,
+        });
+        pb.inc(1);
+    }
+    pb.finish_and_clear();
+
+    let results_file = output_path("mb-transfer-batch.json");
+    write_json(&results_file, &results)?;
+
+    if skipped > 0 {
+        println!("Skipped {skipped} rows already sent");
+    }
+    println!(
+        "Sent {} transfers, {failed} failed. Results written to {}",
+        results.len() - failed,
+        results_file.display()
+    );
+
+    Ok(())
+}
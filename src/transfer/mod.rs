@@ -1,13 +1,30 @@
+mod batch;
+
+pub use batch::*;
+
 use std::str::FromStr;
 
 use anyhow::Result;
-use metaboss_lib::transfer::{transfer_asset, TransferAssetArgs};
+use metaboss_lib::{
+    decode::decode_metadata_from_mint,
+    derive::{derive_edition_pda, derive_metadata_pda, derive_token_record_pda},
+    transaction::send_and_confirm_tx,
+    transfer::TransferAssetArgs,
+};
+use mpl_token_metadata::{
+    instructions::TransferV1Builder,
+    types::{ProgrammableConfig, TokenStandard},
+};
 use solana_client::rpc_client::RpcClient;
-use solana_program::pubkey::Pubkey;
-use solana_sdk::signer::Signer;
-use spl_associated_token_account::get_associated_token_address;
+use solana_program::{pubkey, pubkey::Pubkey};
+use solana_sdk::{signature::Signature, signer::Signer};
+use spl_associated_token_account::get_associated_token_address_with_program_id;
 
+use crate::output::record_transaction;
 use crate::parse::{parse_keypair, parse_solana_config};
+use crate::utils::get_token_program;
+
+const MPL_TOKEN_AUTH_RULES_ID: Pubkey = pubkey!("auth9SigNpDKz4sJJ1DfCTuZrZNSAgh9sFD3rboVmgg");
 
 pub fn process_transfer_asset(
     client: &RpcClient,
@@ -23,11 +40,13 @@ pub fn process_transfer_asset(
     let receiver = Pubkey::from_str(&receiver)?;
     let mint = Pubkey::from_str(&mint)?;
 
-    let source_ata = get_associated_token_address(&authority.pubkey(), &mint);
+    let token_program = get_token_program(client, &mint)?;
+    let source_ata =
+        get_associated_token_address_with_program_id(&authority.pubkey(), &mint, &token_program);
     let destination_token = if let Some(account) = receiver_account {
         Pubkey::from_str(&account)?
     } else {
-        get_associated_token_address(&receiver, &mint)
+        get_associated_token_address_with_program_id(&receiver, &mint, &token_program)
     };
 
     let args = TransferAssetArgs::V1 {
@@ -42,10 +61,81 @@ pub fn process_transfer_asset(
         authorization_data: None,
     };
 
-    let transfer_result = transfer_asset(client, args)?;
+    let transfer_result = transfer_asset_with_token_program(client, args, token_program)?;
 
     println!("Transferred asset: {mint:?}");
     println!("Transaction signature: {transfer_result:?}");
 
     Ok(())
 }
+
+// Built here with the same accounts as metaboss_lib, which always passes the legacy token
+// program to TransferV1 and doesn't hand back the instructions to record for the JSON output.
+fn transfer_asset_with_token_program(
+    client: &RpcClient,
+    args: TransferAssetArgs<Pubkey>,
+    token_program: Pubkey,
+) -> Result<Signature> {
+    let TransferAssetArgs::V1 {
+        payer,
+        authority,
+        mint,
+        source_owner,
+        source_token,
+        destination_owner,
+        destination_token,
+        amount,
+        authorization_data,
+    } = args;
+
+    let payer = payer.unwrap_or(authority);
+    let metadata = derive_metadata_pda(&mint);
+    let md = decode_metadata_from_mint(client, mint)?;
+
+    let mut transfer_builder = TransferV1Builder::new();
+    transfer_builder
+        .payer(payer.pubkey())
+        .authority(authority.pubkey())
+        .token(source_token)
+        .token_owner(source_owner)
+        .destination_token(destination_token)
+        .destination_owner(destination_owner)
+        .mint(mint)
+        .metadata(metadata)
+        .spl_token_program(token_program)
+        .amount(amount);
+
+    if let Some(data) = authorization_data {
+        transfer_builder.authorization_data(data);
+    }
+
+    if let Some(TokenStandard::ProgrammableNonFungible) = md.token_standard {
+        transfer_builder
+            .token_record(Some(derive_token_record_pda(&mint, &source_token)))
+            .destination_token_record(Some(derive_token_record_pda(&mint, &destination_token)));
+
+        if let Some(ProgrammableConfig::V1 {
+            rule_set: Some(auth_rules),
+        }) = md.programmable_config
+        {
+            transfer_builder.authorization_rules_program(Some(MPL_TOKEN_AUTH_RULES_ID));
+            transfer_builder.authorization_rules(Some(auth_rules));
+        }
+    }
+
+    if matches!(
+        md.token_standard,
+        Some(
+            TokenStandard::NonFungible
+                | TokenStandard::NonFungibleEdition
+                | TokenStandard::ProgrammableNonFungible
+        ) | None
+    ) {
+        transfer_builder.edition(Some(derive_edition_pda(&mint)));
+    }
+
+    let instructions = [transfer_builder.instruction()];
+    let sig = send_and_confirm_tx(client, &[payer, authority], &instructions)?;
+    record_transaction(&sig, &instructions);
+    Ok(sig)
+}
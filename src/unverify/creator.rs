@@ -1,4 +1,10 @@
+use std::str::FromStr;
+
+use metaboss_lib::derive::derive_metadata_pda;
+use solana_sdk::pubkey::Pubkey;
+
 use super::*;
+use crate::output::record_signature;
 
 pub struct UnverifyCreatorArgs {
     pub client: Arc<RpcClient>,
@@ -21,8 +27,15 @@ pub async fn unverify_creator(args: UnverifyCreatorArgs) -> Result<Signature, Ac
         mint: args.mint.clone(),
     };
 
-    metaboss_lib::unverify::unverify_creator(&args.client, verify_args)
-        .map_err(|e| ActionError::ActionFailed(args.mint.to_string(), e.to_string()))
+    let sig = metaboss_lib::unverify::unverify_creator(&args.client, verify_args)
+        .map_err(|e| ActionError::ActionFailed(args.mint.to_string(), e.to_string()))?;
+
+    // metaboss_lib sends the instruction itself, so the metadata is recorded as written to.
+    let metadata: Vec<Pubkey> = Pubkey::from_str(&args.mint)
+        .map(|mint| vec![derive_metadata_pda(&mint)])
+        .unwrap_or_default();
+    record_signature(&sig, "TokenMetadata.Unverify", &metadata);
+    Ok(sig)
 }
 
 pub struct UnverifyCreatorAll {}
@@ -33,14 +46,14 @@ impl Action for UnverifyCreatorAll {
         "unverify-creator-all"
     }
 
-    async fn action(args: RunActionArgs) -> Result<(), ActionError> {
+    async fn action(args: RunActionArgs) -> Result<ActionOutcome, ActionError> {
         unverify_creator(UnverifyCreatorArgs {
             client: args.client.clone(),
             keypair: args.keypair.clone(),
             mint: args.mint_account.clone(),
         })
         .await
-        .map(|_| ())
+        .map(ActionOutcome::Sent)
     }
 }
 
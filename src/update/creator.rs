@@ -1,4 +1,11 @@
+use std::collections::BTreeMap;
+
+use mpl_token_metadata::types::Creator;
+
 use super::*;
+use crate::decode::decode_metadata_batch;
+use crate::files::write_json;
+use crate::workspace::output_path;
 
 pub type UpdateResults = Vec<Result<(), ActionError>>;
 
@@ -11,7 +18,7 @@ pub struct UpdateCreatorArgs {
     pub priority: Priority,
 }
 
-pub async fn update_creator(args: UpdateCreatorArgs) -> Result<Signature, ActionError> {
+pub async fn update_creator(args: UpdateCreatorArgs) -> Result<ActionOutcome, ActionError> {
     let current_md = decode_metadata_from_mint(&args.client, args.mint_account.clone())
         .map_err(|e| ActionError::ActionFailed(args.mint_account.to_string(), e.to_string()))?;
 
@@ -20,7 +27,7 @@ pub async fn update_creator(args: UpdateCreatorArgs) -> Result<Signature, Action
         Err(e) => return Err(ActionError::ActionFailed(args.mint_account, e.to_string())),
     };
 
-    let new_creators = if let Some(mut old_creators) = current_md.creators {
+    let new_creators = if let Some(mut old_creators) = current_md.creators.clone() {
         if !args.should_append {
             parsed_creators
         } else {
@@ -36,6 +43,7 @@ pub async fn update_creator(args: UpdateCreatorArgs) -> Result<Signature, Action
     } else {
         parsed_creators
     };
+    let new_creators = keep_verified(current_md.creators.as_deref(), new_creators);
 
     let shares = new_creators.iter().fold(0, |acc, c| acc + c.share);
     if shares != 100 {
@@ -45,6 +53,10 @@ pub async fn update_creator(args: UpdateCreatorArgs) -> Result<Signature, Action
         ));
     }
 
+    if current_md.creators.as_ref() == Some(&new_creators) {
+        return Ok(ActionOutcome::AlreadyDone);
+    }
+
     // Token Metadata UpdateArgs enum.
     let mut update_args = V1UpdateArgs::default();
 
@@ -69,10 +81,40 @@ pub async fn update_creator(args: UpdateCreatorArgs) -> Result<Signature, Action
         priority: args.priority,
     };
 
-    update_asset(&args.client, update_args)
+    update_asset_or_export(&args.client, update_args)
+        .map(ActionOutcome::Sent)
         .map_err(|e| ActionError::ActionFailed(args.mint_account.to_string(), e.to_string()))
 }
 
+/// Keep the current `verified` flag of creators that stay on the asset. Token Metadata
+/// rejects updates that change the flag, so the one given on the command line is only
+/// used for new creators.
+fn keep_verified(current: Option<&[Creator]>, new_creators: Vec<Creator>) -> Vec<Creator> {
+    new_creators
+        .into_iter()
+        .map(|mut creator| {
+            if let Some(old) = current
+                .unwrap_or_default()
+                .iter()
+                .find(|c| c.address == creator.address)
+            {
+                creator.verified = old.verified;
+            }
+            creator
+        })
+        .collect()
+}
+
+/// The verified creators of the asset that aren't in the new creators.
+fn stripped_creators(current: Option<&[Creator]>, new_creators: &[Creator]) -> Vec<String> {
+    current
+        .unwrap_or_default()
+        .iter()
+        .filter(|c| c.verified && !new_creators.iter().any(|n| n.address == c.address))
+        .map(|c| c.address.to_string())
+        .collect()
+}
+
 pub struct UpdateCreatorAllArgs {
     pub client: RpcClient,
     pub keypair_path: Option<String>,
@@ -80,6 +122,7 @@ pub struct UpdateCreatorAllArgs {
     pub cache_file: Option<String>,
     pub new_creators: String,
     pub should_append: bool,
+    pub force: bool,
     pub rate_limit: usize,
     pub retries: u8,
     pub priority: Priority,
@@ -94,6 +137,18 @@ pub async fn update_creator_all(args: UpdateCreatorAllArgs) -> AnyResult<()> {
 
     let mint_list = parse_mint_list(args.mint_list, &args.cache_file)?;
 
+    // Check the new creators once up front, instead of failing on every mint.
+    let new_creators = parse_cli_creators(args.new_creators.clone(), false)?;
+    let shares = new_creators.iter().map(|c| c.share as u16).sum::<u16>();
+    if shares != 100 {
+        return Err(anyhow!("Creators shares must sum to 100, got {shares}!"));
+    }
+
+    if !args.force {
+        let mints = batch_mints(&mint_list, &args.cache_file)?;
+        check_verified_kept(&args.client, &mints, &new_creators)?;
+    }
+
     let args = BatchActionArgs {
         client: args.client,
         keypair,
@@ -108,6 +163,39 @@ pub async fn update_creator_all(args: UpdateCreatorAllArgs) -> AnyResult<()> {
     UpdateCreatorAll::run(args).await
 }
 
+/// Refuse to run if the new creators would remove a verified creator from any of the mints,
+/// listing the affected mints in `mb-creators-stripped.json`.
+fn check_verified_kept(
+    client: &RpcClient,
+    mints: &[String],
+    new_creators: &[Creator],
+) -> AnyResult<()> {
+    let mut stripped: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    for (mint, md) in decode_metadata_batch(client, mints) {
+        let md = match md {
+            Ok(md) => md,
+            // Left to the update itself, which reports the mint as failed.
+            Err(_) => continue,
+        };
+        let removed = stripped_creators(md.creators.as_deref(), new_creators);
+        if !removed.is_empty() {
+            stripped.insert(mint, removed);
+        }
+    }
+
+    if stripped.is_empty() {
+        return Ok(());
+    }
+
+    let file_name = output_path("mb-creators-stripped.json");
+    write_json(&file_name, &stripped)?;
+    Err(anyhow!(
+        "The new creators would remove verified creators from {} mints, listed in {}. Use --force to update them anyway.",
+        stripped.len(),
+        file_name.display()
+    ))
+}
+
 pub struct UpdateCreatorAll {}
 
 #[async_trait]
@@ -116,7 +204,7 @@ impl Action for UpdateCreatorAll {
         "update-creator-all"
     }
 
-    async fn action(args: RunActionArgs) -> Result<(), ActionError> {
+    async fn action(args: RunActionArgs) -> Result<ActionOutcome, ActionError> {
         update_creator(UpdateCreatorArgs {
             client: args.client,
             keypair: args.keypair,
@@ -126,6 +214,5 @@ impl Action for UpdateCreatorAll {
             priority: args.priority,
         })
         .await
-        .map(|_| ())
     }
 }
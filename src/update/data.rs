@@ -1,11 +1,24 @@
 use glob::glob;
 use indicatif::ParallelProgressIterator;
-use metaboss_lib::{data::UpdateNftData, update::V1UpdateArgs};
+use metaboss_lib::{
+    data::{UpdateNftData, UPDATE_COMPUTE_UNITS},
+    update::V1UpdateArgs,
+};
 use mpl_token_metadata::types::Data;
 use rayon::prelude::{IntoParallelRefIterator, ParallelIterator};
+use solana_client::nonblocking::rpc_client::RpcClient as AsyncRpcClient;
+use solana_sdk::compute_budget::ComputeBudgetInstruction;
 use std::{collections::HashMap, path::Path, sync::Mutex};
 
-use crate::cache::NewValue;
+use crate::cache::{MintValues, NewValue};
+use crate::changelog::Changelog;
+use crate::checkpoint;
+use crate::constants::{JITO_BLOCK_ENGINE, JITO_TIP_LAMPORTS, MAX_BUNDLE_SIZE};
+use crate::expect;
+use crate::order;
+use crate::spinner::create_progress_bar;
+use crate::utils::send_and_confirm_bundle;
+use crate::workspace::output_path;
 
 use super::*;
 
@@ -27,8 +40,14 @@ pub struct UpdateDataArgs {
     pub priority: Priority,
 }
 
-pub async fn update_data(args: UpdateDataArgs) -> Result<Signature, ActionError> {
+pub async fn update_data(args: UpdateDataArgs) -> Result<ActionOutcome, ActionError> {
     // Add metadata delegate record here later.
+    let current_md = decode_metadata_from_mint(&args.client, args.mint_account.clone())
+        .map_err(|e| ActionError::ActionFailed(args.mint_account.to_string(), e.to_string()))?;
+
+    if data_matches(&current_md, &args.new_data) {
+        return Ok(ActionOutcome::AlreadyDone);
+    }
 
     // Token Metadata UpdateArgs enum.
     let update_args = V1UpdateArgs {
@@ -47,7 +66,8 @@ pub async fn update_data(args: UpdateDataArgs) -> Result<Signature, ActionError>
         priority: args.priority,
     };
 
-    update_asset(&args.client, update_args)
+    update_asset_or_export(&args.client, update_args)
+        .map(ActionOutcome::Sent)
         .map_err(|e| ActionError::ActionFailed(args.mint_account.to_string(), e.to_string()))
 }
 
@@ -59,7 +79,7 @@ impl Action for UpdateDataAll {
         "update-data-all"
     }
 
-    async fn action(args: RunActionArgs) -> Result<(), ActionError> {
+    async fn action(args: RunActionArgs) -> Result<ActionOutcome, ActionError> {
         let new_data: Data = serde_json::from_str(&args.new_value).map_err(|e| {
             ActionError::ActionFailed(
                 args.mint_account.to_string(),
@@ -75,7 +95,6 @@ impl Action for UpdateDataAll {
             priority: args.priority,
         })
         .await
-        .map(|_| ())
     }
 }
 
@@ -152,6 +171,20 @@ pub async fn update_data_all(args: UpdateDataAllArgs) -> AnyResult<()> {
 
     let mint_values = mint_values.lock().unwrap().clone();
 
+    let block_engine = JITO_BLOCK_ENGINE.read().unwrap().clone();
+    if let Some(block_engine) = block_engine {
+        let tip = *JITO_TIP_LAMPORTS.read().unwrap();
+        return update_data_all_bundled(UpdateDataAllBundledArgs {
+            client: args.client,
+            keypair,
+            cache_file: args.cache_file,
+            mint_values,
+            block_engine,
+            tip,
+        })
+        .await;
+    }
+
     // We don't support an optional payer for this action currently.
     let payer = None;
 
@@ -168,3 +201,150 @@ pub async fn update_data_all(args: UpdateDataAllArgs) -> AnyResult<()> {
     };
     UpdateDataAll::run(args).await
 }
+
+struct UpdateDataAllBundledArgs {
+    client: RpcClient,
+    keypair: Keypair,
+    cache_file: Option<String>,
+    mint_values: MintValues,
+    block_engine: String,
+    tip: u64,
+}
+
+async fn update_data_all_bundled(args: UpdateDataAllBundledArgs) -> AnyResult<()> {
+    let cache_file_name = args.cache_file.clone().unwrap_or_else(|| {
+        output_path(format!("mb-cache-{}.json", UpdateDataAll::name()))
+            .display()
+            .to_string()
+    });
+
+    // When retrying from a cache file only the failed mints are resent.
+    let mints: Vec<String> = if let Some(cache_file) = &args.cache_file {
+        let cache: Cache = read_json(cache_file)?;
+        cache.keys().cloned().collect()
+    } else {
+        args.mint_values.keys().cloned().collect()
+    };
+    let mints = order::ordered(checkpoint::remaining(UpdateDataAll::name(), mints));
+
+    // Read the metadata up front so the changes the run makes can be written out after.
+    let changelog = Changelog::capture(&args.client, UpdateDataAll::name(), &mints)?;
+
+    // Leave out mints that changed since the --expect-file snapshot.
+    let mints = expect::unchanged(UpdateDataAll::name(), mints, changelog.before())?;
+
+    let async_client =
+        AsyncRpcClient::new_with_commitment(args.client.url(), args.client.commitment());
+
+    let mut failed = Vec::new();
+    let mut already_done = 0;
+    let pb = create_progress_bar("Sending bundles...", mints.len() as u64);
+
+    for chunk in mints.chunks(MAX_BUNDLE_SIZE) {
+        let mut bundled_mints = Vec::with_capacity(chunk.len());
+        let mut instruction_groups = Vec::with_capacity(chunk.len());
+
+        for mint in chunk {
+            match build_update_data_ixs(&args.client, &args.keypair, mint, &args.mint_values) {
+                Ok(Some(ixs)) => {
+                    bundled_mints.push(mint);
+                    instruction_groups.push(ixs);
+                }
+                Ok(None) => {
+                    already_done += 1;
+                    checkpoint::record_done(UpdateDataAll::name(), mint, None);
+                }
+                Err(e) => {
+                    checkpoint::record_failed(UpdateDataAll::name(), mint, &e);
+                    failed.push(Err(ActionError::ActionFailed(mint.clone(), e.to_string())));
+                }
+            }
+        }
+
+        if instruction_groups.is_empty() {
+            pb.inc(chunk.len() as u64);
+            continue;
+        }
+
+        match send_and_confirm_bundle(
+            &async_client,
+            &args.block_engine,
+            &[&args.keypair],
+            &instruction_groups,
+            args.tip,
+        )
+        .await
+        {
+            Ok(signatures) => {
+                for (mint, sig) in bundled_mints.iter().zip(signatures) {
+                    changelog.record(mint, sig);
+                    checkpoint::record_done(UpdateDataAll::name(), mint, Some(sig.to_string()));
+                }
+            }
+            // A bundle lands or fails as a whole so every mint in it is retried.
+            Err(e) => {
+                for mint in bundled_mints {
+                    checkpoint::record_failed(UpdateDataAll::name(), mint, &e);
+                    failed.push(Err(ActionError::ActionFailed(mint.clone(), e.to_string())));
+                }
+            }
+        }
+        pb.inc(chunk.len() as u64);
+    }
+    pb.finish_and_clear();
+
+    println!("Already done: {already_done}");
+    println!("Updates failed: {}", failed.len());
+
+    let mut cache = Cache::new();
+    if failed.is_empty() {
+        println!("All actions successfully run!");
+    } else {
+        cache.update_errors(failed);
+    }
+    cache.write(create_file(&cache_file_name)?)?;
+
+    // The changes are already on chain, so failing to write them out isn't an error.
+    match changelog.write(&args.client) {
+        Ok(Some(path)) => println!("Changelog written to {path}"),
+        Ok(None) => {}
+        Err(e) => warn!("Failed to write the changelog: {e}"),
+    }
+
+    Ok(())
+}
+
+fn build_update_data_ixs(
+    client: &RpcClient,
+    keypair: &Keypair,
+    mint: &str,
+    mint_values: &MintValues,
+) -> AnyResult<Option<Vec<solana_sdk::instruction::Instruction>>> {
+    let value = mint_values
+        .get(mint)
+        .ok_or_else(|| anyhow!("No new data found for mint"))?;
+    let new_data: Data = serde_json::from_str(value)?;
+
+    let current_md = decode_metadata_from_mint(client, mint.to_string())?;
+    if data_matches(&current_md, &new_data) {
+        return Ok(None);
+    }
+
+    let update_args = UpdateAssetArgs::V1 {
+        payer: None,
+        authority: keypair,
+        mint: mint.to_string(),
+        token: None::<String>,
+        delegate_record: None::<String>,
+        update_args: V1UpdateArgs {
+            data: Some(new_data),
+            ..Default::default()
+        },
+        priority: Priority::None,
+    };
+
+    Ok(Some(vec![
+        ComputeBudgetInstruction::set_compute_unit_limit(UPDATE_COMPUTE_UNITS),
+        update_asset_ix(client, update_args)?,
+    ]))
+}
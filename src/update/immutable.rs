@@ -1,6 +1,6 @@
 use metaboss_lib::update::V1UpdateArgs;
 
-use crate::cache::NewValue;
+use crate::{cache::NewValue, decode::decode_metadata_batch, offline::signer_pubkey};
 
 use super::*;
 
@@ -19,10 +19,81 @@ pub struct SetImmutableAllArgs {
     pub rate_limit: usize,
     pub retries: u8,
     pub priority: Priority,
+    pub dry_run: bool,
 }
 
-pub async fn set_immutable(args: SetImmutableArgs) -> Result<Signature, ActionError> {
+/// What `set immutable-all` would do to a mint, as shown by `--dry-run`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum ImmutableStatus {
+    Mutable,
+    OtherAuthority,
+    AlreadyImmutable,
+    Unreadable,
+}
+
+impl Display for ImmutableStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let status = match self {
+            ImmutableStatus::Mutable => "mutable",
+            ImmutableStatus::OtherAuthority => "other authority",
+            ImmutableStatus::AlreadyImmutable => "already immutable",
+            ImmutableStatus::Unreadable => "unreadable",
+        };
+        write!(f, "{status}")
+    }
+}
+
+/// Print which mints would be made immutable, which already are, and which the keypair
+/// can't update, without sending anything.
+fn print_immutable_report(client: &RpcClient, authority: &Pubkey, mint_list: &[String]) {
+    let mut rows: Vec<(ImmutableStatus, String, String)> = decode_metadata_batch(client, mint_list)
+        .into_iter()
+        .map(|(mint, md)| match md {
+            Ok(md) if !md.is_mutable => (
+                ImmutableStatus::AlreadyImmutable,
+                mint,
+                md.update_authority.to_string(),
+            ),
+            Ok(md) if md.update_authority != *authority => (
+                ImmutableStatus::OtherAuthority,
+                mint,
+                md.update_authority.to_string(),
+            ),
+            Ok(md) => (
+                ImmutableStatus::Mutable,
+                mint,
+                md.update_authority.to_string(),
+            ),
+            Err(e) => (ImmutableStatus::Unreadable, mint, e.to_string()),
+        })
+        .collect();
+    rows.sort();
+
+    println!("{:<44} {:<17} update authority", "mint", "status");
+    for (status, mint, detail) in &rows {
+        println!("{mint:<44} {:<17} {detail}", status.to_string());
+    }
+
+    let count = |status| rows.iter().filter(|(s, _, _)| *s == status).count();
+    println!();
+    println!(
+        "{} would be made immutable, {} already immutable, {} have a different update authority than {authority}, {} couldn't be read",
+        count(ImmutableStatus::Mutable),
+        count(ImmutableStatus::AlreadyImmutable),
+        count(ImmutableStatus::OtherAuthority),
+        count(ImmutableStatus::Unreadable),
+    );
+    println!("Dry run: nothing was sent");
+}
+
+pub async fn set_immutable(args: SetImmutableArgs) -> Result<ActionOutcome, ActionError> {
     // Add metadata delegate record here later.
+    let current_md = decode_metadata_from_mint(&args.client, args.mint_account.clone())
+        .map_err(|e| ActionError::ActionFailed(args.mint_account.to_string(), e.to_string()))?;
+
+    if !current_md.is_mutable {
+        return Ok(ActionOutcome::AlreadyDone);
+    }
 
     // Token Metadata UpdateArgs enum.
     let update_args = V1UpdateArgs {
@@ -41,7 +112,8 @@ pub async fn set_immutable(args: SetImmutableArgs) -> Result<Signature, ActionEr
         priority: args.priority,
     };
 
-    update_asset(&args.client, update_args)
+    update_asset_or_export(&args.client, update_args)
+        .map(ActionOutcome::Sent)
         .map_err(|e| ActionError::ActionFailed(args.mint_account.to_string(), e.to_string()))
 }
 
@@ -53,7 +125,7 @@ impl Action for SetImmutableAll {
         "set-immutable-all"
     }
 
-    async fn action(args: RunActionArgs) -> Result<(), ActionError> {
+    async fn action(args: RunActionArgs) -> Result<ActionOutcome, ActionError> {
         set_immutable(SetImmutableArgs {
             client: args.client.clone(),
             keypair: args.keypair.clone(),
@@ -61,7 +133,6 @@ impl Action for SetImmutableAll {
             priority: args.priority,
         })
         .await
-        .map(|_| ())
     }
 }
 
@@ -71,6 +142,12 @@ pub async fn set_immutable_all(args: SetImmutableAllArgs) -> AnyResult<()> {
 
     let mint_list = parse_mint_list(args.mint_list, &args.cache_file)?;
 
+    if args.dry_run {
+        let mints = batch_mints(&mint_list, &args.cache_file)?;
+        print_immutable_report(&args.client, &signer_pubkey(&keypair)?, &mints);
+        return Ok(());
+    }
+
     // We don't support an optional payer for this action currently.
     let payer = None;
 
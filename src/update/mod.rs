@@ -32,7 +32,7 @@ pub use metaboss_lib::{
     decode::{decode_metadata_from_mint, ToPubkey},
     derive::{derive_edition_pda, derive_metadata_pda},
     nft::get_nft_token_account,
-    update::{update_asset, UpdateAssetArgs, V1UpdateArgs},
+    update::{update_asset, update_asset_ix, UpdateAssetArgs, V1UpdateArgs},
 };
 pub use mpl_token_metadata::{
     accounts::Metadata,
@@ -53,11 +53,67 @@ pub use spl_token::state::Account as TokenAccount;
 use std::fs::File;
 pub use std::{cmp, fmt::Display, str::FromStr, sync::Arc};
 
-pub use crate::cache::{Action, BatchActionArgs, Cache, NewValue, RunActionArgs};
+pub use crate::cache::{Action, ActionOutcome, BatchActionArgs, Cache, NewValue, RunActionArgs};
 pub use crate::decode::{decode, get_metadata_pda};
 pub use crate::errors::ActionError;
+use crate::files::{create_file, read_json};
+use crate::offline::{export_dir, export_unsigned};
 pub use crate::parse::parse_solana_config;
 pub use crate::parse::{parse_cli_creators, parse_keypair};
+use crate::pipeline::Pipeline;
+
+/// Send an update, or write it as an unsigned transaction when exporting for offline signing.
+/// Uses the durable nonce from `--nonce-account` and the lookup table from `--lut` when set.
+///
+/// Updates are sent through the pipeline so batch runs share a cached blockhash and
+/// re-sign transactions whose blockhash expired before they landed.
+pub fn update_asset_or_export(
+    client: &RpcClient,
+    update_args: UpdateAssetArgs<String, String, String>,
+) -> AnyResult<Signature> {
+    let UpdateAssetArgs::V1 {
+        mint,
+        payer,
+        authority,
+        priority,
+        ..
+    } = &update_args;
+    let mint = mint.clone();
+    let priority = priority.clone();
+    let payer = payer.unwrap_or(*authority);
+    let authority = *authority;
+
+    let ix = update_asset_ix(client, update_args)?;
+
+    if export_dir().is_some() {
+        export_unsigned(client, &payer.pubkey(), &[ix], Some(&mint))?;
+        return Ok(Signature::default());
+    }
+
+    let mut signers = vec![payer];
+    if authority.pubkey() != payer.pubkey() {
+        signers.push(authority);
+    }
+
+    Pipeline::with_priority(priority)
+        .with_active_lookup_table()?
+        .with_active_nonce()?
+        .run(client, &signers, vec![ix])
+}
+
+/// Strip the null bytes on-chain metadata strings are padded with.
+pub fn trim_padding(value: &str) -> &str {
+    value.trim_matches(char::from(0))
+}
+
+/// Whether the metadata already holds `data`, in which case updating it would be a no-op.
+pub fn data_matches(current: &Metadata, data: &Data) -> bool {
+    trim_padding(&current.name) == trim_padding(&data.name)
+        && trim_padding(&current.symbol) == trim_padding(&data.symbol)
+        && trim_padding(&current.uri) == trim_padding(&data.uri)
+        && current.seller_fee_basis_points == data.seller_fee_basis_points
+        && current.creators == data.creators
+}
 
 pub fn parse_mint_list(
     mint_list_file: Option<String>,
@@ -66,10 +122,24 @@ pub fn parse_mint_list(
     if cache_file.is_none() {
         let mint_file = mint_list_file
             .ok_or_else(|| anyhow!("Must provide either a mint list or a cache file!"))?;
-        let f = File::open(mint_file)?;
-        let mint_list = serde_json::from_reader(f)?;
+        let mint_list = read_json(mint_file)?;
         Ok(Some(mint_list))
     } else {
         Ok(None)
     }
 }
+
+/// The mints a batch command will run on: the mint list, or the mints in the cache file.
+pub fn batch_mints(
+    mint_list: &Option<Vec<String>>,
+    cache_file: &Option<String>,
+) -> AnyResult<Vec<String>> {
+    match (mint_list, cache_file) {
+        (Some(mints), _) => Ok(mints.clone()),
+        (None, Some(cache_file)) => {
+            let cache: Cache = read_json(cache_file)?;
+            Ok(cache.0.into_keys().collect())
+        }
+        (None, None) => Err(anyhow!("Must provide either a mint list or a cache file!")),
+    }
+}
@@ -11,10 +11,14 @@ pub struct UpdateNameArgs {
     pub priority: Priority,
 }
 
-pub async fn update_name(args: UpdateNameArgs) -> Result<Signature, ActionError> {
+pub async fn update_name(args: UpdateNameArgs) -> Result<ActionOutcome, ActionError> {
     let current_md = decode_metadata_from_mint(&args.client, args.mint_account.clone())
         .map_err(|e| ActionError::ActionFailed(args.mint_account.to_string(), e.to_string()))?;
 
+    if trim_padding(&current_md.name) == trim_padding(&args.new_name) {
+        return Ok(ActionOutcome::AlreadyDone);
+    }
+
     // Token Metadata UpdateArgs enum.
     let mut update_args = V1UpdateArgs::default();
 
@@ -39,6 +43,7 @@ pub async fn update_name(args: UpdateNameArgs) -> Result<Signature, ActionError>
         priority: args.priority,
     };
 
-    update_asset(&args.client, update_args)
+    update_asset_or_export(&args.client, update_args)
+        .map(ActionOutcome::Sent)
         .map_err(|e| ActionError::ActionFailed(args.mint_account.to_string(), e.to_string()))
 }
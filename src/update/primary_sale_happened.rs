@@ -23,7 +23,14 @@ pub struct SetPrimarySaleHappenedArgs {
 
 pub async fn set_primary_sale_happened(
     args: SetPrimarySaleHappenedArgs,
-) -> Result<Signature, ActionError> {
+) -> Result<ActionOutcome, ActionError> {
+    let current_md = decode_metadata_from_mint(&args.client, args.mint_account.clone())
+        .map_err(|e| ActionError::ActionFailed(args.mint_account.to_string(), e.to_string()))?;
+
+    if current_md.primary_sale_happened {
+        return Ok(ActionOutcome::AlreadyDone);
+    }
+
     // Token Metadata UpdateArgs enum.
     let update_args = V1UpdateArgs {
         primary_sale_happened: Some(true),
@@ -41,7 +48,8 @@ pub async fn set_primary_sale_happened(
         priority: args.priority,
     };
 
-    update_asset(&args.client, update_args)
+    update_asset_or_export(&args.client, update_args)
+        .map(ActionOutcome::Sent)
         .map_err(|e| ActionError::ActionFailed(args.mint_account.to_string(), e.to_string()))
 }
 
@@ -53,7 +61,7 @@ impl Action for SetPrimarySaleHappenedAll {
         "set-secondary-all"
     }
 
-    async fn action(args: RunActionArgs) -> Result<(), ActionError> {
+    async fn action(args: RunActionArgs) -> Result<ActionOutcome, ActionError> {
         set_primary_sale_happened(SetPrimarySaleHappenedArgs {
             client: args.client.clone(),
             keypair: args.keypair.clone(),
@@ -61,7 +69,6 @@ impl Action for SetPrimarySaleHappenedAll {
             priority: args.priority,
         })
         .await
-        .map(|_| ())
     }
 }
 
@@ -1,9 +1,12 @@
 use metaboss_lib::update::V1UpdateArgs;
+use solana_sdk::pubkey;
 
 use crate::cache::NewValue;
 
 use super::*;
 
+const MPL_TOKEN_AUTH_RULES_ID: Pubkey = pubkey!("auth9SigNpDKz4sJJ1DfCTuZrZNSAgh9sFD3rboVmgg");
+
 pub struct UpdateRuleSetAllArgs {
     pub client: RpcClient,
     pub keypair: Option<String>,
@@ -40,10 +43,32 @@ pub struct ClearRuleSetArgs {
     pub priority: Priority,
 }
 
-pub async fn update_rule_set(args: UpdateRuleSetArgs) -> Result<Signature, ActionError> {
+fn current_rule_set(metadata: &Metadata) -> Option<Pubkey> {
+    match &metadata.programmable_config {
+        Some(ProgrammableConfig::V1 { rule_set }) => *rule_set,
+        None => None,
+    }
+}
+
+pub async fn update_rule_set(args: UpdateRuleSetArgs) -> Result<ActionOutcome, ActionError> {
     let new_rule_set = Pubkey::from_str(&args.new_rule_set)
         .map_err(|e| ActionError::ActionFailed(args.mint_account.to_string(), e.to_string()))?;
 
+    let current_md = decode_metadata_from_mint(&args.client, args.mint_account.clone())
+        .map_err(|e| ActionError::ActionFailed(args.mint_account.to_string(), e.to_string()))?;
+
+    if current_rule_set(&current_md) == Some(new_rule_set) {
+        return Ok(ActionOutcome::AlreadyDone);
+    }
+
+    // Only programmable NFTs have a rule set, the update fails for anything else.
+    if current_md.token_standard != Some(TokenStandard::ProgrammableNonFungible) {
+        return Err(ActionError::ActionFailed(
+            args.mint_account.to_string(),
+            "not a pNFT, only programmable NFTs have a rule set".to_string(),
+        ));
+    }
+
     // Add metadata delegate record here later.
 
     // Token Metadata UpdateArgs enum.
@@ -63,14 +88,22 @@ pub async fn update_rule_set(args: UpdateRuleSetArgs) -> Result<Signature, Actio
         priority: args.priority,
     };
 
-    update_asset(&args.client, update_args)
+    update_asset_or_export(&args.client, update_args)
+        .map(ActionOutcome::Sent)
         .map_err(|e| ActionError::ActionFailed(args.mint_account.to_string(), e.to_string()))
 }
 
-pub async fn clear_rule_set(args: ClearRuleSetArgs) -> Result<Signature, ActionError> {
+pub async fn clear_rule_set(args: ClearRuleSetArgs) -> Result<ActionOutcome, ActionError> {
     let mint = Pubkey::from_str(&args.mint_account)
         .map_err(|e| ActionError::ActionFailed(args.mint_account.to_string(), e.to_string()))?;
 
+    let current_md = decode_metadata_from_mint(&args.client, args.mint_account.clone())
+        .map_err(|e| ActionError::ActionFailed(args.mint_account.to_string(), e.to_string()))?;
+
+    if current_rule_set(&current_md).is_none() {
+        return Ok(ActionOutcome::AlreadyDone);
+    }
+
     // Add metadata delegate record here later.
 
     // Token Metadata UpdateArgs enum.
@@ -83,14 +116,15 @@ pub async fn clear_rule_set(args: ClearRuleSetArgs) -> Result<Signature, ActionE
     let update_args = UpdateAssetArgs::V1 {
         payer: None,
         authority: &args.keypair,
-        mint,
+        mint: mint.to_string(),
         token: None::<String>,
         delegate_record: None::<String>, // Not supported yet in update.
         update_args,
         priority: args.priority,
     };
 
-    update_asset(&args.client, update_args)
+    update_asset_or_export(&args.client, update_args)
+        .map(ActionOutcome::Sent)
         .map_err(|e| ActionError::ActionFailed(args.mint_account.to_string(), e.to_string()))
 }
 
@@ -102,7 +136,7 @@ impl Action for UpdateRuleSetAll {
         "update-rule-set-all"
     }
 
-    async fn action(args: RunActionArgs) -> Result<(), ActionError> {
+    async fn action(args: RunActionArgs) -> Result<ActionOutcome, ActionError> {
         update_rule_set(UpdateRuleSetArgs {
             client: args.client.clone(),
             keypair: args.keypair.clone(),
@@ -111,7 +145,6 @@ impl Action for UpdateRuleSetAll {
             priority: args.priority,
         })
         .await
-        .map(|_| ())
     }
 }
 
@@ -119,6 +152,20 @@ pub async fn update_rule_set_all(args: UpdateRuleSetAllArgs) -> AnyResult<()> {
     let solana_opts = parse_solana_config();
     let keypair = parse_keypair(args.keypair, solana_opts);
 
+    // Catch a mistyped rule set once, instead of on every mint.
+    let rule_set = Pubkey::from_str(&args.new_rule_set)
+        .map_err(|_| anyhow!("Invalid rule set: {}", args.new_rule_set))?;
+    let owner = args
+        .client
+        .get_account(&rule_set)
+        .map_err(|e| anyhow!("Failed to fetch rule set {rule_set}: {e}"))?
+        .owner;
+    if owner != MPL_TOKEN_AUTH_RULES_ID {
+        return Err(anyhow!(
+            "{rule_set} is not a Token Auth Rules rule set, it's owned by {owner}"
+        ));
+    }
+
     let mint_list = parse_mint_list(args.mint_list, &args.cache_file)?;
 
     // We don't support an optional payer for this action currently.
@@ -146,7 +193,7 @@ impl Action for ClearRuleSetAll {
         "clear-rule-set-all"
     }
 
-    async fn action(args: RunActionArgs) -> Result<(), ActionError> {
+    async fn action(args: RunActionArgs) -> Result<ActionOutcome, ActionError> {
         clear_rule_set(ClearRuleSetArgs {
             client: args.client.clone(),
             keypair: args.keypair.clone(),
@@ -154,7 +201,6 @@ impl Action for ClearRuleSetAll {
             priority: args.priority,
         })
         .await
-        .map(|_| ())
     }
 }
 
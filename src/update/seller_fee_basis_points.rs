@@ -23,11 +23,17 @@ pub struct UpdateSellerFeeBasisPointsAllArgs {
     pub priority: Priority,
 }
 
-pub async fn update_sfbp(args: UpdateSellerFeeBasisPointsArgs) -> Result<Signature, ActionError> {
+pub async fn update_sfbp(
+    args: UpdateSellerFeeBasisPointsArgs,
+) -> Result<ActionOutcome, ActionError> {
     // Add metadata delegate record here later.
     let current_md = decode_metadata_from_mint(&args.client, args.mint_account.clone())
         .map_err(|e| ActionError::ActionFailed(args.mint_account.to_string(), e.to_string()))?;
 
+    if current_md.seller_fee_basis_points == args.new_sfbp {
+        return Ok(ActionOutcome::AlreadyDone);
+    }
+
     let data = Some(Data {
         name: current_md.name,
         symbol: current_md.symbol,
@@ -53,7 +59,8 @@ pub async fn update_sfbp(args: UpdateSellerFeeBasisPointsArgs) -> Result<Signatu
         priority: args.priority,
     };
 
-    update_asset(&args.client, update_args)
+    update_asset_or_export(&args.client, update_args)
+        .map(ActionOutcome::Sent)
         .map_err(|e| ActionError::ActionFailed(args.mint_account.to_string(), e.to_string()))
 }
 
@@ -65,7 +72,7 @@ impl Action for UpdateSellerFeeBasisPointsAll {
         "update-sfbp-all"
     }
 
-    async fn action(args: RunActionArgs) -> Result<(), ActionError> {
+    async fn action(args: RunActionArgs) -> Result<ActionOutcome, ActionError> {
         // Converting back and forth between String and u16 is dumb but I couldn't figure out a
         // nice way to do this with generics.
         let sfbp = args.new_value.parse::<u16>().map_err(|e| {
@@ -83,7 +90,6 @@ impl Action for UpdateSellerFeeBasisPointsAll {
             priority: args.priority,
         })
         .await
-        .map(|_| ())
     }
 }
 
@@ -24,10 +24,14 @@ pub struct UpdateSymbolArgs {
     pub priority: Priority,
 }
 
-pub async fn update_symbol(args: UpdateSymbolArgs) -> Result<Signature, ActionError> {
+pub async fn update_symbol(args: UpdateSymbolArgs) -> Result<ActionOutcome, ActionError> {
     let current_md = decode_metadata_from_mint(&args.client, args.mint_account.clone())
         .map_err(|e| ActionError::ActionFailed(args.mint_account.to_string(), e.to_string()))?;
 
+    if trim_padding(&current_md.symbol) == trim_padding(&args.new_symbol) {
+        return Ok(ActionOutcome::AlreadyDone);
+    }
+
     // Token Metadata UpdateArgs enum.
     let mut update_args = V1UpdateArgs::default();
 
@@ -52,7 +56,8 @@ pub async fn update_symbol(args: UpdateSymbolArgs) -> Result<Signature, ActionEr
         priority: args.priority,
     };
 
-    update_asset(&args.client, update_args)
+    update_asset_or_export(&args.client, update_args)
+        .map(ActionOutcome::Sent)
         .map_err(|e| ActionError::ActionFailed(args.mint_account.to_string(), e.to_string()))
 }
 
@@ -64,7 +69,7 @@ impl Action for UpdateSymbolAll {
         "update-symbol-all"
     }
 
-    async fn action(args: RunActionArgs) -> Result<(), ActionError> {
+    async fn action(args: RunActionArgs) -> Result<ActionOutcome, ActionError> {
         update_symbol(UpdateSymbolArgs {
             client: args.client.clone(),
             keypair: args.keypair.clone(),
@@ -73,7 +78,6 @@ impl Action for UpdateSymbolAll {
             priority: args.priority,
         })
         .await
-        .map(|_| ())
     }
 }
 
@@ -2,6 +2,8 @@ use mpl_token_metadata::instructions::SetTokenStandard;
 use solana_sdk::commitment_config::CommitmentConfig;
 
 use crate::cache::NewValue;
+use crate::offline::{export_dir, export_unsigned};
+use crate::output::record_transaction;
 
 use super::*;
 
@@ -45,21 +47,36 @@ pub async fn set_token_standard_one(args: SetTokenStandardArgs) -> Result<Signat
     }
     .instruction();
 
+    if export_dir().is_some() {
+        export_unsigned(
+            &args.client,
+            &update_authority,
+            &[ix],
+            Some(&args.mint_account),
+        )
+        .map_err(|e| ActionError::ActionFailed(args.mint_account.to_string(), e.to_string()))?;
+        return Ok(Signature::default());
+    }
+
     let recent_blockhash = args
         .client
         .get_latest_blockhash()
         .map_err(|e| ActionError::ActionFailed(args.mint_account.to_string(), e.to_string()))?;
 
+    let instructions = [ix];
     let tx = Transaction::new_signed_with_payer(
-        &[ix],
+        &instructions,
         Some(&update_authority),
         &[&*args.keypair],
         recent_blockhash,
     );
 
-    args.client
+    let sig = args
+        .client
         .send_and_confirm_transaction(&tx)
-        .map_err(|e| ActionError::ActionFailed(args.mint_account.to_string(), e.to_string()))
+        .map_err(|e| ActionError::ActionFailed(args.mint_account.to_string(), e.to_string()))?;
+    record_transaction(&sig, &instructions);
+    Ok(sig)
 }
 
 pub struct SetTokenStandardAll {}
@@ -70,7 +87,7 @@ impl Action for SetTokenStandardAll {
         "set-token-standard-all"
     }
 
-    async fn action(args: RunActionArgs) -> Result<(), ActionError> {
+    async fn action(args: RunActionArgs) -> Result<ActionOutcome, ActionError> {
         set_token_standard_one(SetTokenStandardArgs {
             client: args.client.clone(),
             keypair: args.keypair.clone(),
@@ -78,7 +95,7 @@ impl Action for SetTokenStandardAll {
             priority: args.priority,
         })
         .await
-        .map(|_| ())
+        .map(ActionOutcome::Sent)
     }
 }
 
@@ -25,12 +25,21 @@ pub struct SetUpdateAuthorityArgs {
     pub priority: Priority,
 }
 
-pub async fn set_update_authority(args: SetUpdateAuthorityArgs) -> Result<Signature, ActionError> {
+pub async fn set_update_authority(
+    args: SetUpdateAuthorityArgs,
+) -> Result<ActionOutcome, ActionError> {
     let new_update_authority =
         Some(Pubkey::from_str(&args.new_authority).map_err(|e| {
             ActionError::ActionFailed(args.mint_account.to_string(), e.to_string())
         })?);
 
+    let current_md = decode_metadata_from_mint(&args.client, args.mint_account.clone())
+        .map_err(|e| ActionError::ActionFailed(args.mint_account.to_string(), e.to_string()))?;
+
+    if Some(current_md.update_authority) == new_update_authority {
+        return Ok(ActionOutcome::AlreadyDone);
+    }
+
     // Token Metadata UpdateArgs enum.
     let update_args = V1UpdateArgs {
         new_update_authority,
@@ -48,7 +57,8 @@ pub async fn set_update_authority(args: SetUpdateAuthorityArgs) -> Result<Signat
         priority: args.priority,
     };
 
-    update_asset(&args.client, update_args)
+    update_asset_or_export(&args.client, update_args)
+        .map(ActionOutcome::Sent)
         .map_err(|e| ActionError::ActionFailed(args.mint_account.to_string(), e.to_string()))
 }
 
@@ -60,7 +70,7 @@ impl Action for SetUpdateAuthorityAll {
         "set-update-authority-all"
     }
 
-    async fn action(args: RunActionArgs) -> Result<(), ActionError> {
+    async fn action(args: RunActionArgs) -> Result<ActionOutcome, ActionError> {
         // Set Update Authority can have an optional payer.
         set_update_authority(SetUpdateAuthorityArgs {
             client: args.client.clone(),
@@ -71,7 +81,6 @@ impl Action for SetUpdateAuthorityAll {
             priority: args.priority,
         })
         .await
-        .map(|_| ())
     }
 }
 
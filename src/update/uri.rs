@@ -1,9 +1,26 @@
-use std::{collections::HashMap, fs::File};
+use std::collections::HashMap;
 
-use metaboss_lib::{data::UpdateUriData, update::V1UpdateArgs};
+use metaboss_lib::{
+    data::{UpdateUriData, UPDATE_COMPUTE_UNITS},
+    update::V1UpdateArgs,
+};
 use mpl_token_metadata::types::Data;
+use solana_sdk::instruction::Instruction;
+
+use regex::Regex;
 
 use crate::cache::{MintValues, NewValue};
+use crate::changelog::Changelog;
+use crate::checkpoint;
+use crate::constants::MAX_URI_LENGTH;
+use crate::decode::decode_metadata_batch;
+use crate::expect;
+use crate::files::write_json;
+use crate::offline::export_dir;
+use crate::order;
+use crate::pack::{pack_instruction_groups, send_packed, InstructionGroup};
+use crate::spinner::create_progress_bar;
+use crate::workspace::output_path;
 
 use super::*;
 
@@ -11,10 +28,15 @@ pub struct UpdateUriAllArgs {
     pub client: RpcClient,
     pub keypair: Option<String>,
     pub cache_file: Option<String>,
-    pub new_uris_file: String,
+    pub new_uris_file: Option<String>,
+    pub mint_list: Option<String>,
+    pub pattern: Option<String>,
+    pub replace: Option<String>,
+    pub regex: bool,
     pub rate_limit: usize,
     pub retries: u8,
     pub priority: Priority,
+    pub pack: bool,
 }
 
 pub struct UpdateUriArgs {
@@ -25,15 +47,15 @@ pub struct UpdateUriArgs {
     pub priority: Priority,
 }
 
-pub async fn update_uri(args: UpdateUriArgs) -> Result<Signature, ActionError> {
+pub async fn update_uri(args: UpdateUriArgs) -> Result<ActionOutcome, ActionError> {
     let current_md = decode_metadata_from_mint(&args.client, args.mint_account.clone())
         .map_err(|e| ActionError::ActionFailed(args.mint_account.to_string(), e.to_string()))?;
 
     // Add metadata delegate record here later.
 
     // Save a transaction by not updating if the uri is the same.
-    if current_md.uri.trim_matches(char::from(0)) == args.new_uri.trim_matches(char::from(0)) {
-        return Ok(Signature::default());
+    if trim_padding(&current_md.uri) == trim_padding(&args.new_uri) {
+        return Ok(ActionOutcome::AlreadyDone);
     }
 
     // Token Metadata UpdateArgs enum.
@@ -60,7 +82,8 @@ pub async fn update_uri(args: UpdateUriArgs) -> Result<Signature, ActionError> {
         priority: args.priority,
     };
 
-    update_asset(&args.client, update_args)
+    update_asset_or_export(&args.client, update_args)
+        .map(ActionOutcome::Sent)
         .map_err(|e| ActionError::ActionFailed(args.mint_account.to_string(), e.to_string()))
 }
 
@@ -72,7 +95,7 @@ impl Action for UpdateUriAll {
         "update-uri-all"
     }
 
-    async fn action(args: RunActionArgs) -> Result<(), ActionError> {
+    async fn action(args: RunActionArgs) -> Result<ActionOutcome, ActionError> {
         update_uri(UpdateUriArgs {
             client: args.client.clone(),
             keypair: args.keypair.clone(),
@@ -81,20 +104,128 @@ impl Action for UpdateUriAll {
             priority: args.priority,
         })
         .await
-        .map(|_| ())
     }
 }
 
+/// Apply the substitution to a URI, matching the pattern literally unless a regex is
+/// given. Returns `None` if the URI doesn't change.
+fn rewrite_uri(
+    uri: &str,
+    pattern: &str,
+    replace: &str,
+    regex: Option<&Regex>,
+) -> AnyResult<Option<String>> {
+    let new_uri = match regex {
+        Some(regex) => regex.replace_all(uri, replace).into_owned(),
+        None => uri.replace(pattern, replace),
+    };
+    if new_uri == uri {
+        Ok(None)
+    } else if new_uri.len() > MAX_URI_LENGTH {
+        Err(anyhow!(
+            "the new uri {new_uri} is longer than {MAX_URI_LENGTH} bytes"
+        ))
+    } else {
+        Ok(Some(new_uri))
+    }
+}
+
+/// Apply the substitution to the current URI of each mint, returning the mints whose URI
+/// changes in the format of a new URIs file.
+fn rewrite_uris(
+    client: &RpcClient,
+    mints: &[String],
+    pattern: &str,
+    replace: &str,
+    regex: bool,
+) -> AnyResult<Vec<UpdateUriData>> {
+    let regex = if regex {
+        Some(Regex::new(pattern).map_err(|e| anyhow!("Invalid pattern: {e}"))?)
+    } else {
+        None
+    };
+
+    let mut rewrites = vec![];
+    let mut unchanged = 0;
+    let pb = create_progress_bar("Decoding URIs...", mints.len() as u64);
+    for (mint, md) in decode_metadata_batch(client, mints) {
+        pb.inc(1);
+        let md = match md {
+            Ok(md) => md,
+            Err(e) => {
+                warn!("Skipping {mint}: {e}");
+                checkpoint::record_failed(UpdateUriAll::name(), &mint, &e);
+                continue;
+            }
+        };
+        match rewrite_uri(trim_padding(&md.uri), pattern, replace, regex.as_ref()) {
+            Ok(Some(new_uri)) => rewrites.push(UpdateUriData {
+                mint_account: mint,
+                new_uri,
+            }),
+            Ok(None) => unchanged += 1,
+            Err(e) => {
+                warn!("Skipping {mint}: {e}");
+                checkpoint::record_failed(UpdateUriAll::name(), &mint, &e);
+            }
+        }
+    }
+    pb.finish_and_clear();
+
+    let path = output_path("mb-uri-rewrites.json");
+    write_json(&path, &rewrites)?;
+    println!(
+        "{} uris to rewrite, {unchanged} don't match the pattern. New uris written to {}",
+        rewrites.len(),
+        path.display()
+    );
+
+    Ok(rewrites)
+}
+
 pub async fn update_uri_all(args: UpdateUriAllArgs) -> AnyResult<()> {
     let solana_opts = parse_solana_config();
     let keypair = parse_keypair(args.keypair, solana_opts);
 
-    let f = File::open(args.new_uris_file)?;
-    let update_uris: Vec<UpdateUriData> = serde_json::from_reader(f)?;
+    // A pattern rewrites the current uris of the mint list, or of the mints left in the
+    // cache file, and only the mints that change are sent.
+    let (update_uris, cache_file): (Vec<UpdateUriData>, _) =
+        match (&args.new_uris_file, &args.pattern) {
+            (Some(new_uris_file), None) => (read_json(new_uris_file)?, args.cache_file),
+            (None, Some(pattern)) => {
+                let replace = args
+                    .replace
+                    .as_deref()
+                    .ok_or_else(|| anyhow!("--pattern needs a --replace value"))?;
+                let mints: Vec<String> = match (&args.mint_list, &args.cache_file) {
+                    (Some(mint_list), None) => read_json(mint_list)?,
+                    (None, Some(cache_file)) => {
+                        let cache: Cache = read_json(cache_file)?;
+                        cache.keys().cloned().collect()
+                    }
+                    _ => {
+                        return Err(anyhow!(
+                            "--pattern needs either a mint list or a cache file, but not both"
+                        ))
+                    }
+                };
+                let rewrites = rewrite_uris(&args.client, &mints, pattern, replace, args.regex)?;
+                if rewrites.is_empty() {
+                    println!("No uris to update");
+                    return Ok(());
+                }
+                (rewrites, None)
+            }
+            _ => {
+                return Err(anyhow!(
+                    "Please specify either a new uris file or a --pattern, but not both"
+                ))
+            }
+        };
 
     // If user hasn't passed in a cache file, then we construct the mint list from the URI file.
 
-    let mint_list = if args.cache_file.is_none() {
+    let mint_list = if cache_file.is_none() {
         Some(
             update_uris
                 .iter()
@@ -110,6 +241,16 @@ pub async fn update_uri_all(args: UpdateUriAllArgs) -> AnyResult<()> {
         .map(|data| (data.mint_account.clone(), data.new_uri.clone()))
         .collect::<HashMap<_, _>>();
 
+    if args.pack {
+        return update_uri_all_packed(
+            &args.client,
+            &keypair,
+            cache_file,
+            &mint_values,
+            &args.priority,
+        );
+    }
+
     // We don't support an optional payer for this action currently.
     let payer = None;
 
@@ -118,7 +259,7 @@ pub async fn update_uri_all(args: UpdateUriAllArgs) -> AnyResult<()> {
         keypair,
         payer,
         mint_list,
-        cache_file: args.cache_file,
+        cache_file,
         new_value: NewValue::List(mint_values),
         rate_limit: args.rate_limit,
         retries: args.retries,
@@ -127,3 +268,186 @@ pub async fn update_uri_all(args: UpdateUriAllArgs) -> AnyResult<()> {
     UpdateUriAll::run(args).await?;
     Ok(())
 }
+
+fn update_uri_all_packed(
+    client: &RpcClient,
+    keypair: &Keypair,
+    cache_file: Option<String>,
+    mint_values: &MintValues,
+    priority: &Priority,
+) -> AnyResult<()> {
+    if export_dir().is_some() {
+        return Err(anyhow!("--pack can't be used with --export-unsigned"));
+    }
+
+    let cache_file_name = cache_file.clone().unwrap_or_else(|| {
+        output_path(format!("mb-cache-{}.json", UpdateUriAll::name()))
+            .display()
+            .to_string()
+    });
+
+    // When retrying from a cache file only the failed mints are resent.
+    let mints: Vec<String> = if let Some(cache_file) = &cache_file {
+        let cache: Cache = read_json(cache_file)?;
+        cache.keys().cloned().collect()
+    } else {
+        mint_values.keys().cloned().collect()
+    };
+    let mints = order::ordered(checkpoint::remaining(UpdateUriAll::name(), mints));
+    let changelog = Changelog::capture(client, UpdateUriAll::name(), &mints)?;
+    let mints = expect::unchanged(UpdateUriAll::name(), mints, changelog.before())?;
+
+    let mut failed = Vec::new();
+    let mut already_done = 0;
+    let mut groups = Vec::with_capacity(mints.len());
+
+    let pb = create_progress_bar("Building instructions...", mints.len() as u64);
+    for mint in mints {
+        match build_update_uri_ix(client, keypair, &mint, mint_values) {
+            Ok(Some(ix)) => groups.push(InstructionGroup {
+                key: mint,
+                instructions: vec![ix],
+                compute_units: UPDATE_COMPUTE_UNITS,
+            }),
+            Ok(None) => {
+                checkpoint::record_done(UpdateUriAll::name(), &mint, None);
+                already_done += 1;
+            }
+            Err(e) => {
+                checkpoint::record_failed(UpdateUriAll::name(), &mint, &e);
+                failed.push(Err(ActionError::ActionFailed(mint, e.to_string())));
+            }
+        }
+        pb.inc(1);
+    }
+    pb.finish_and_clear();
+
+    let update_count = groups.len();
+    let packed = pack_instruction_groups(client, &keypair.pubkey(), groups)?;
+    println!(
+        "Packed {update_count} updates into {} transactions",
+        packed.len()
+    );
+
+    let (sent, failed_txs) = send_packed(client, &[keypair], priority, packed)?;
+    for (mints, signature) in sent {
+        for mint in mints {
+            checkpoint::record_done(UpdateUriAll::name(), &mint, Some(signature.to_string()));
+            changelog.record(&mint, signature);
+        }
+    }
+    for (mints, e) in failed_txs {
+        for mint in mints {
+            checkpoint::record_failed(UpdateUriAll::name(), &mint, &e);
+            failed.push(Err(ActionError::ActionFailed(mint, e.to_string())));
+        }
+    }
+
+    println!("Already done: {already_done}");
+    println!("Updates failed: {}", failed.len());
+
+    let mut cache = Cache::new();
+    if failed.is_empty() {
+        println!("All actions successfully run!");
+    } else {
+        cache.update_errors(failed);
+    }
+    cache.write(create_file(&cache_file_name)?)?;
+
+    match changelog.write(client) {
+        Ok(Some(path)) => println!("Changelog written to {path}"),
+        Ok(None) => {}
+        Err(e) => warn!("Failed to write the changelog: {e}"),
+    }
+
+    Ok(())
+}
+
+/// The update instruction for a mint's new URI, or `None` if it already has it.
+pub fn build_update_uri_ix(
+    client: &RpcClient,
+    keypair: &Keypair,
+    mint: &str,
+    mint_values: &MintValues,
+) -> AnyResult<Option<Instruction>> {
+    let new_uri = mint_values
+        .get(mint)
+        .ok_or_else(|| anyhow!("No new uri found for mint"))?;
+
+    let current_md = decode_metadata_from_mint(client, mint.to_string())?;
+    if trim_padding(&current_md.uri) == trim_padding(new_uri) {
+        return Ok(None);
+    }
+
+    let update_args = UpdateAssetArgs::V1 {
+        payer: None,
+        authority: keypair,
+        mint: mint.to_string(),
+        token: None::<String>,
+        delegate_record: None::<String>,
+        update_args: V1UpdateArgs {
+            data: Some(Data {
+                name: current_md.name,
+                symbol: current_md.symbol,
+                uri: new_uri.clone(),
+                seller_fee_basis_points: current_md.seller_fee_basis_points,
+                creators: current_md.creators,
+            }),
+            ..Default::default()
+        },
+        priority: Priority::None,
+    };
+
+    Ok(Some(update_asset_ix(client, update_args)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rewrites_literal_patterns() {
+        let uri = "https://arweave.net/abc.json?v=1.2";
+
+        assert_eq!(
+            rewrite_uri(uri, "arweave.net", "ar.io", None).unwrap(),
+            Some("https://ar.io/abc.json?v=1.2".to_string())
+        );
+        // Regex syntax is matched as is.
+        assert_eq!(rewrite_uri(uri, "v=1.*", "v=2", None).unwrap(), None);
+        assert_eq!(
+            rewrite_uri(uri, "1.2", "1.3", None).unwrap(),
+            Some("https://arweave.net/abc.json?v=1.3".to_string())
+        );
+    }
+
+    #[test]
+    fn rewrites_regex_patterns() {
+        let uri = "https://old.example.com/123.json";
+        let regex = Regex::new(r"https://old\.example\.com/(\d+)\.json").unwrap();
+
+        assert_eq!(
+            rewrite_uri(uri, "", "ipfs://cid/$1.json", Some(&regex)).unwrap(),
+            Some("ipfs://cid/123.json".to_string())
+        );
+        assert_eq!(
+            rewrite_uri("https://other.com/1.json", "", "x", Some(&regex)).unwrap(),
+            None
+        );
+    }
+
+    #[test]
+    fn refuses_uris_over_the_length_limit() {
+        let uri = "https://example.com/1.json";
+        let at_limit = "2".repeat(MAX_URI_LENGTH - uri.len() + 1);
+        let over_limit = "2".repeat(MAX_URI_LENGTH - uri.len() + 2);
+
+        assert_eq!(
+            rewrite_uri(uri, "1", &at_limit, None)
+                .unwrap()
+                .map(|u| u.len()),
+            Some(MAX_URI_LENGTH)
+        );
+        assert!(rewrite_uri(uri, "1", &over_limit, None).is_err());
+    }
+}
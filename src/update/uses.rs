@@ -66,6 +66,6 @@ pub fn update_uses_one(args: UsesArgs) -> Result<Signature, ActionError> {
         priority: args.priority,
     };
 
-    update_asset(&args.client, update_args)
+    update_asset_or_export(&args.client, update_args)
         .map_err(|e| ActionError::ActionFailed(args.account.to_string(), e.to_string()))
 }
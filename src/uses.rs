@@ -1,24 +1,89 @@
-use std::str::FromStr;
+use std::{collections::HashMap, fs::File, path::PathBuf, str::FromStr};
 
-use anyhow::Result;
+use anyhow::{anyhow, Result};
+use metaboss_lib::{data::Priority, nft::get_nft_token_account};
 use mpl_token_metadata::instructions::{
     ApproveUseAuthorityBuilder, RevokeUseAuthorityBuilder, UtilizeBuilder,
 };
+use serde::Serialize;
 use solana_client::rpc_client::RpcClient;
-use solana_sdk::{pubkey::Pubkey, signer::Signer};
+use solana_sdk::{
+    native_token::sol_to_lamports, pubkey::Pubkey, signature::Keypair, signer::Signer,
+    system_instruction,
+};
+use spl_token_2022::{extension::StateWithExtensions, state::Account as TokenAccount};
 
+use crate::checkpoint;
+use crate::workspace::output_path;
 use crate::{
-    derive::{derive_metadata_pda, derive_use_authority_record},
-    parse::{parse_keypair, parse_solana_config},
-    utils::send_and_confirm_transaction,
+    decode::decode_metadata_batch,
+    derive::{derive_metadata_pda, derive_use_authority_record, derive_use_burner_pda},
+    files::{read_json, write_json},
+    offline::signer_pubkey,
+    parse::{is_only_one_option, parse_keypair, parse_solana_config},
+    pipeline::Pipeline,
+    spinner::create_progress_bar,
+    utils::{get_associated_token_address_for_mint, send_and_confirm_transaction},
 };
 
+// Approvals that fit in one transaction alongside the funding transfer.
+const APPROVALS_PER_TX: usize = 3;
+
+/// Name the outcome of each burner is recorded under.
+const APPROVE_BURNERS_COMMAND: &str = "uses-approve-burners";
+
+pub struct ApproveBurnersArgs<'a> {
+    pub client: &'a RpcClient,
+    pub keypair: Option<String>,
+    pub mint_nft: Option<String>,
+    pub mint_list: Option<String>,
+    pub count: usize,
+    pub number_of_uses: u64,
+    pub fund: Option<f64>,
+    pub burner_program_id: Option<String>,
+    pub output: String,
+    pub priority: Priority,
+}
+
+/// A generated burner. The secret key is base58 encoded so it fits in a QR code
+/// and can be imported directly into wallets.
+#[derive(Debug, Serialize)]
+pub struct BurnerKey {
+    pub pubkey: String,
+    pub secret_key: String,
+    pub uses: u64,
+    pub mints: Vec<String>,
+}
+
+/// Uses of a single mint, as decoded from its metadata.
+#[derive(Debug, Serialize)]
+pub struct UsesStatus {
+    pub mint: String,
+    pub use_method: Option<String>,
+    pub remaining: Option<u64>,
+    pub total: Option<u64>,
+    pub error: Option<String>,
+}
+
+/// The given token account, or the keypair's associated token account for the mint.
+fn owner_token_account(
+    client: &RpcClient,
+    keypair: &Keypair,
+    mint: &Pubkey,
+    token_account: Option<String>,
+) -> Result<Pubkey> {
+    match token_account {
+        Some(account) => Ok(Pubkey::from_str(&account)?),
+        None => get_associated_token_address_for_mint(client, &signer_pubkey(keypair)?, mint),
+    }
+}
+
 pub fn approve_use_delegate(
     client: &RpcClient,
     keypair_path: Option<String>,
     nft_mint: String,
     use_auth_delegate: String,
-    owner_nft_token_account: String,
+    owner_nft_token_account: Option<String>,
     burner_program_id: String,
     number_of_uses: u64,
 ) -> Result<()> {
@@ -27,7 +92,8 @@ pub fn approve_use_delegate(
     let keypair = parse_keypair(keypair_path, solana_opts);
 
     let delegate_pubkey = Pubkey::from_str(&use_auth_delegate)?;
-    let owner_nft_token_pubkey = Pubkey::from_str(&owner_nft_token_account)?;
+    let owner_nft_token_pubkey =
+        owner_token_account(client, &keypair, &nft_pubkey, owner_nft_token_account)?;
     let burner_program_pubkey = Pubkey::from_str(&burner_program_id)?;
 
     let (use_authority_record, _bump) = derive_use_authority_record(&nft_pubkey, &delegate_pubkey);
@@ -56,14 +122,15 @@ pub fn revoke_use_delegate(
     keypair_path: Option<String>,
     nft_mint: String,
     use_auth_delegate: String,
-    owner_nft_token_account: String,
+    owner_nft_token_account: Option<String>,
 ) -> Result<()> {
     let nft_pubkey = Pubkey::from_str(&nft_mint)?;
     let solana_opts = parse_solana_config();
     let keypair = parse_keypair(keypair_path, solana_opts);
 
     let delegate_pubkey = Pubkey::from_str(&use_auth_delegate)?;
-    let owner_nft_token_pubkey = Pubkey::from_str(&owner_nft_token_account)?;
+    let owner_nft_token_pubkey =
+        owner_token_account(client, &keypair, &nft_pubkey, owner_nft_token_account)?;
 
     let (use_authority_record, _bump) = derive_use_authority_record(&nft_pubkey, &delegate_pubkey);
 
@@ -87,8 +154,8 @@ pub fn utilize_nft(
     client: &RpcClient,
     keypair_path: Option<String>,
     nft_mint: String,
-    nft_owner: String,
-    owner_nft_token_account: String,
+    nft_owner: Option<String>,
+    owner_nft_token_account: Option<String>,
     burner_program_id: Option<String>,
     is_delegate_present: bool,
 ) -> Result<()> {
@@ -96,9 +163,21 @@ pub fn utilize_nft(
     let solana_opts = parse_solana_config();
     let keypair = parse_keypair(keypair_path, solana_opts);
 
-    let nft_owner = Pubkey::from_str(&nft_owner)?;
-    let owner_nft_token_pubkey = Pubkey::from_str(&owner_nft_token_account)?;
-    let delegate_pubkey = keypair.pubkey();
+    // Ticket holders are usually unknown up front, so find whoever holds the NFT.
+    let owner_nft_token_pubkey = match owner_nft_token_account {
+        Some(account) => Pubkey::from_str(&account)?,
+        None => get_nft_token_account(client, &nft_mint)?,
+    };
+    let nft_owner = match nft_owner {
+        Some(owner) => Pubkey::from_str(&owner)?,
+        None => {
+            let data = client.get_account_data(&owner_nft_token_pubkey)?;
+            StateWithExtensions::<TokenAccount>::unpack(&data)?
+                .base
+                .owner
+        }
+    };
+    let delegate_pubkey = signer_pubkey(&keypair)?;
     let nft_metadata = derive_metadata_pda(&nft_pubkey);
 
     let use_authority_record = match is_delegate_present {
@@ -132,3 +211,197 @@ pub fn utilize_nft(
 
     Ok(())
 }
+
+pub fn approve_burners(args: ApproveBurnersArgs) -> Result<()> {
+    if !is_only_one_option(&args.mint_nft, &args.mint_list) {
+        return Err(anyhow!(
+            "Please specify either a mint or a mint list, but not both."
+        ));
+    }
+    if args.count == 0 {
+        return Err(anyhow!("Count must be at least 1"));
+    }
+
+    let solana_opts = parse_solana_config();
+    let keypair = parse_keypair(args.keypair, solana_opts);
+
+    let mint_strings: Vec<String> = if let Some(mint_list) = args.mint_list {
+        read_json(mint_list)?
+    } else {
+        vec![args.mint_nft.unwrap()]
+    };
+    let mints = mint_strings
+        .iter()
+        .map(|m| Pubkey::from_str(m).map_err(|_| anyhow!("Invalid mint address: {m}")))
+        .collect::<Result<Vec<_>>>()?;
+
+    let burner_program = match args.burner_program_id {
+        Some(id) => Pubkey::from_str(&id)?,
+        None => derive_use_burner_pda(),
+    };
+
+    // Owner token accounts don't change per burner, so look them up once.
+    let owner = signer_pubkey(&keypair)?;
+    let token_accounts = mints
+        .iter()
+        .map(|mint| get_associated_token_address_for_mint(args.client, &owner, mint))
+        .collect::<Result<Vec<_>>>()?;
+
+    let burners: Vec<Keypair> = (0..args.count).map(|_| Keypair::new()).collect();
+
+    // Write the keys before sending anything so they aren't lost if a transaction fails.
+    let burner_keys: Vec<BurnerKey> = burners
+        .iter()
+        .map(|burner| BurnerKey {
+            pubkey: burner.pubkey().to_string(),
+            secret_key: burner.to_base58_string(),
+            uses: args.number_of_uses,
+            mints: mint_strings.clone(),
+        })
+        .collect();
+
+    let output = output_path(&args.output);
+    std::fs::create_dir_all(&output)?;
+    let output_path = output.join("mb_use_burners.json");
+    serde_json::to_writer_pretty(File::create(&output_path)?, &burner_keys)?;
+    println!(
+        "Burner keypairs written to {}. Keep this file private: it holds the secret keys.",
+        output_path.display()
+    );
+
+    let pb = create_progress_bar("Approving burners...", burners.len() as u64);
+    let mut failed = Vec::new();
+
+    for burner in &burners {
+        let burner_key = burner.pubkey().to_string();
+        let mut error = None;
+        let mut ixs = Vec::new();
+        for (mint, token_account) in mints.iter().zip(token_accounts.iter()) {
+            let (use_authority_record, _bump) = derive_use_authority_record(mint, &burner.pubkey());
+
+            ixs.push(
+                ApproveUseAuthorityBuilder::new()
+                    .use_authority_record(use_authority_record)
+                    .metadata(derive_metadata_pda(mint))
+                    .owner(keypair.pubkey())
+                    .payer(keypair.pubkey())
+                    .mint(*mint)
+                    .burner(burner_program)
+                    .owner_token_account(*token_account)
+                    .user(burner.pubkey())
+                    .number_of_uses(args.number_of_uses)
+                    .instruction(),
+            );
+        }
+
+        for (i, chunk) in ixs.chunks(APPROVALS_PER_TX).enumerate() {
+            let mut chunk = chunk.to_vec();
+            if let (0, Some(sol)) = (i, args.fund) {
+                chunk.insert(
+                    0,
+                    system_instruction::transfer(
+                        &keypair.pubkey(),
+                        &burner.pubkey(),
+                        sol_to_lamports(sol),
+                    ),
+                );
+            }
+
+            let result = Pipeline::with_priority(args.priority.clone())
+                .with_active_lookup_table()
+                .and_then(|p| p.with_active_nonce())
+                .and_then(|p| p.run(args.client, &[&keypair], chunk));
+
+            if let Err(e) = result {
+                error = Some(e);
+                break;
+            }
+        }
+        match error {
+            Some(e) => {
+                checkpoint::record_failed(APPROVE_BURNERS_COMMAND, &burner_key, &e);
+                failed.push(format!("{burner_key}: {e}"));
+            }
+            None => checkpoint::record_done(APPROVE_BURNERS_COMMAND, &burner_key, None),
+        }
+        pb.inc(1);
+    }
+    pb.finish_and_clear();
+
+    println!(
+        "Approved {} of {} burners on {} mints",
+        burners.len() - failed.len(),
+        burners.len(),
+        mints.len()
+    );
+    for failure in &failed {
+        println!("Failed: {failure}");
+    }
+
+    Ok(())
+}
+
+/// Print the remaining uses of each mint, in the order given.
+pub fn remaining_uses(
+    client: &RpcClient,
+    mint_nft: Option<String>,
+    mint_list: Option<String>,
+    output: Option<PathBuf>,
+) -> Result<()> {
+    if !is_only_one_option(&mint_nft, &mint_list) {
+        return Err(anyhow!(
+            "Please specify either a mint or a mint list, but not both."
+        ));
+    }
+    let mints: Vec<String> = match mint_list {
+        Some(mint_list) => read_json(mint_list)?,
+        None => vec![mint_nft.unwrap()],
+    };
+
+    let mut decoded: HashMap<String, _> =
+        decode_metadata_batch(client, &mints).into_iter().collect();
+    let statuses: Vec<UsesStatus> = mints
+        .iter()
+        .map(|mint| {
+            let mut status = UsesStatus {
+                mint: mint.clone(),
+                use_method: None,
+                remaining: None,
+                total: None,
+                error: None,
+            };
+            match decoded.remove(mint) {
+                Some(Ok(md)) => {
+                    if let Some(uses) = md.uses {
+                        status.use_method = Some(format!("{:?}", uses.use_method));
+                        status.remaining = Some(uses.remaining);
+                        status.total = Some(uses.total);
+                    }
+                }
+                Some(Err(e)) => status.error = Some(e.to_string()),
+                None => status.error = Some("not decoded".to_string()),
+            }
+            status
+        })
+        .collect();
+
+    for status in &statuses {
+        match (&status.error, &status.use_method) {
+            (Some(e), _) => println!("{}: {e}", status.mint),
+            (None, Some(method)) => println!(
+                "{}: {}/{} uses remaining ({method})",
+                status.mint,
+                status.remaining.unwrap_or_default(),
+                status.total.unwrap_or_default()
+            ),
+            (None, None) => println!("{}: no uses", status.mint),
+        }
+    }
+
+    if let Some(path) = output {
+        write_json(&path, &statuses)?;
+        println!("Uses written to {}", path.display());
+    }
+
+    Ok(())
+}
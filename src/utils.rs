@@ -1,26 +1,34 @@
 use anyhow::{anyhow, Result};
 use borsh::{BorshDeserialize, BorshSerialize};
-use retry::{delay::Exponential, retry};
+use log::debug;
 use serde::Deserialize;
 use serde_json::json;
 use solana_client::rpc_request::RpcRequest;
 use solana_client::{nonblocking::rpc_client::RpcClient as AsyncRpcClient, rpc_client::RpcClient};
 use solana_program::instruction::AccountMeta;
-use solana_program::program_pack::Pack;
-use solana_program::system_program;
 use solana_program::{pubkey, pubkey::Pubkey};
+use solana_program::{system_instruction, system_program};
 use solana_sdk::commitment_config::CommitmentConfig;
 use solana_sdk::{
-    instruction::Instruction, signature::Keypair, signer::Signer, transaction::Transaction,
+    instruction::Instruction,
+    signature::{Keypair, Signature},
+    signer::Signer,
+    transaction::Transaction,
 };
-use spl_token::state::Account;
+use spl_associated_token_account::get_associated_token_address_with_program_id;
+use spl_token_2022::{extension::StateWithExtensions, state::Account};
 use std::str::FromStr;
 use std::{ops::Add, sync::Arc};
 
+use crate::constants::{JITO_TIP_ACCOUNTS, MAX_BUNDLE_SIZE};
 use crate::data::FoundError;
+use crate::idl_errors::cached_error_maps;
+use crate::offline::{export_dir, ExportUnsigned};
+use crate::pipeline::{Pipeline, Stage};
 use crate::wtf_errors::{
-    ANCHOR_ERROR, AUCTIONEER_ERROR, AUCTION_HOUSE_ERROR, CANDY_CORE_ERROR, CANDY_ERROR,
-    CANDY_GUARD_ERROR, METADATA_ERROR,
+    ACCOUNT_COMPRESSION_ERROR, ANCHOR_ERROR, AUCTIONEER_ERROR, AUCTION_HOUSE_ERROR,
+    BUBBLEGUM_ERROR, CANDY_CORE_ERROR, CANDY_ERROR, CANDY_GUARD_ERROR, METADATA_ERROR,
+    SYSTEM_ERROR, TOKEN_AUTH_RULES_ERROR, TOKEN_ERROR,
 };
 
 pub fn send_and_confirm_transaction(
@@ -28,21 +36,19 @@ pub fn send_and_confirm_transaction(
     keypair: Keypair,
     instructions: &[Instruction],
 ) -> Result<String> {
-    let recent_blockhash = client.get_latest_blockhash()?;
-    let tx = Transaction::new_signed_with_payer(
-        instructions,
-        Some(&keypair.pubkey()),
-        &[&keypair],
-        recent_blockhash,
-    );
-
-    // Send tx with retries.
-    let res = retry(
-        Exponential::from_millis_with_factor(250, 2.0).take(3),
-        || client.send_and_confirm_transaction(&tx),
-    );
+    if export_dir().is_some() {
+        Pipeline::standard()
+            .replace(Stage::Sign, ExportUnsigned)
+            .remove(Stage::Send)
+            .remove(Stage::Confirm)
+            .run(client, &[&keypair], instructions.to_vec())?;
+        return Ok(String::new());
+    }
 
-    let sig = res?;
+    let sig = Pipeline::standard()
+        .with_active_lookup_table()?
+        .with_active_nonce()?
+        .run(client, &[&keypair], instructions.to_vec())?;
 
     println!("Tx sig: {sig}");
     Ok(sig.to_string())
@@ -66,7 +72,118 @@ pub async fn async_send_and_confirm_transaction(
     Ok(sig.to_string())
 }
 
-pub async fn retry_with_cache() {}
+const BUNDLE_CONFIRM_ATTEMPTS: usize = 30;
+
+/// Send groups of instructions as a single Jito bundle, one transaction per group.
+///
+/// All transactions share a blockhash and the tip transfer is appended to the last
+/// transaction so the bundle only pays out if every transaction lands.
+pub async fn send_and_confirm_bundle(
+    client: &AsyncRpcClient,
+    block_engine_url: &str,
+    signers: &[&Keypair],
+    instruction_groups: &[Vec<Instruction>],
+    tip_lamports: u64,
+) -> Result<Vec<Signature>> {
+    if instruction_groups.is_empty() {
+        return Ok(vec![]);
+    }
+    if instruction_groups.len() > MAX_BUNDLE_SIZE {
+        return Err(anyhow!(
+            "Bundles can contain at most {MAX_BUNDLE_SIZE} transactions"
+        ));
+    }
+
+    let payer = signers[0].pubkey();
+    let tip_account = Pubkey::from_str(JITO_TIP_ACCOUNTS[rand_index(JITO_TIP_ACCOUNTS.len())])?;
+
+    let recent_blockhash = client.get_latest_blockhash().await?;
+    let last = instruction_groups.len() - 1;
+
+    let transactions = instruction_groups
+        .iter()
+        .enumerate()
+        .map(|(i, ixs)| {
+            let mut ixs = ixs.clone();
+            if i == last {
+                ixs.push(system_instruction::transfer(
+                    &payer,
+                    &tip_account,
+                    tip_lamports,
+                ));
+            }
+            Transaction::new_signed_with_payer(&ixs, Some(&payer), signers, recent_blockhash)
+        })
+        .collect::<Vec<_>>();
+
+    let encoded = transactions
+        .iter()
+        .map(|tx| Ok(bs58::encode(bincode::serialize(tx)?).into_string()))
+        .collect::<Result<Vec<_>>>()?;
+
+    let body = json!({
+        "jsonrpc": "2.0",
+        "id": 1,
+        "method": "sendBundle",
+        "params": [encoded],
+    });
+
+    let url = format!("{}/api/v1/bundles", block_engine_url.trim_end_matches('/'));
+    let response: serde_json::Value = reqwest::Client::new()
+        .post(url)
+        .json(&body)
+        .send()
+        .await?
+        .json()
+        .await?;
+
+    if let Some(err) = response.get("error") {
+        return Err(anyhow!("Block engine rejected bundle: {err}"));
+    }
+    debug!("Bundle id: {}", response["result"]);
+
+    let signatures: Vec<Signature> = transactions.iter().map(|tx| tx.signatures[0]).collect();
+
+    // Bundles are atomic so the last transaction landing means they all did.
+    let last_sig = signatures[last];
+    for _ in 0..BUNDLE_CONFIRM_ATTEMPTS {
+        match client.get_signature_status(&last_sig).await? {
+            Some(Ok(())) => return Ok(signatures),
+            Some(Err(e)) => return Err(anyhow!("Bundle failed: {e}")),
+            None => tokio::time::sleep(std::time::Duration::from_secs(2)).await,
+        }
+    }
+
+    // The bundle can still land until its blockhash expires, so keep waiting until it
+    // does and only then look the signature up in the transaction history.
+    while client
+        .is_blockhash_valid(&recent_blockhash, client.commitment())
+        .await?
+    {
+        tokio::time::sleep(std::time::Duration::from_secs(2)).await;
+    }
+    match client
+        .get_signature_statuses_with_history(&[last_sig])
+        .await?
+        .value[0]
+        .as_ref()
+        .map(|status| status.status.clone())
+    {
+        Some(Ok(())) => Ok(signatures),
+        Some(Err(e)) => Err(anyhow!("Bundle failed: {e}")),
+        None => Err(anyhow!(
+            "Bundle was not confirmed before the blockhash expired"
+        )),
+    }
+}
+
+fn rand_index(len: usize) -> usize {
+    // Spread tips across accounts without pulling in a RNG dependency.
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.subsec_nanos() as usize % len)
+        .unwrap_or(0)
+}
 
 pub fn generate_phf_map_var(var_name: &str) -> String {
     format!("pub static {var_name}: phf::Map<&'static str, &'static str> = phf_map! {{\n")
@@ -98,6 +215,8 @@ pub fn convert_to_wtf_error(file_name: &str, file_contents: &str) -> Result<Stri
         String::from("ErrorCode")
     } else if file_name_capitalized == "CANDY_CORE_ERROR" {
         String::from("CandyError")
+    } else if file_name_capitalized == "TOKEN_AUTH_RULES_ERROR" {
+        String::from("RuleSetError")
     } else {
         file_names_split
             .into_iter()
@@ -129,6 +248,9 @@ pub fn convert_to_wtf_error(file_name: &str, file_contents: &str) -> Result<Stri
 
     let mut parsed_error_line = String::from("\",\n");
 
+    // Attributes split over several lines, e.g. long `#[error(...)]` messages, are joined
+    // back into one line before parsing.
+    let mut attribute_lines: Vec<&str> = vec![];
     for error_line in error_lines {
         let error_line = error_line.trim();
 
@@ -136,6 +258,26 @@ pub fn convert_to_wtf_error(file_name: &str, file_contents: &str) -> Result<Stri
             break;
         }
 
+        if !attribute_lines.is_empty()
+            || (error_line.starts_with("#[") && !error_line.ends_with(")]"))
+        {
+            attribute_lines.push(error_line);
+            if !error_line.ends_with(")]") {
+                continue;
+            }
+        }
+        let joined_attribute;
+        let error_line = if attribute_lines.is_empty() {
+            error_line
+        } else {
+            joined_attribute = std::mem::take(&mut attribute_lines)
+                .join(" ")
+                .replace("( ", "(")
+                .replace(", )", ")")
+                .replace(" )", ")");
+            joined_attribute.as_str()
+        };
+
         if error_line.starts_with('/') || error_line.is_empty() {
             continue;
         } else if !error_line.starts_with("#[")
@@ -236,6 +378,54 @@ pub fn find_errors(hex_code: &str) -> Vec<FoundError> {
         });
     }
 
+    if let Some(e) = BUBBLEGUM_ERROR.get(&hex_code).cloned() {
+        found_errors.push(FoundError {
+            domain: "Bubblegum".to_string(),
+            message: e.to_string(),
+        });
+    }
+
+    if let Some(e) = TOKEN_AUTH_RULES_ERROR.get(&hex_code).cloned() {
+        found_errors.push(FoundError {
+            domain: "Token Auth Rules".to_string(),
+            message: e.to_string(),
+        });
+    }
+
+    if let Some(e) = TOKEN_ERROR.get(&hex_code).cloned() {
+        found_errors.push(FoundError {
+            domain: "SPL Token".to_string(),
+            message: e.to_string(),
+        });
+    }
+
+    if let Some(e) = ACCOUNT_COMPRESSION_ERROR.get(&hex_code).cloned() {
+        found_errors.push(FoundError {
+            domain: "Account Compression".to_string(),
+            message: e.to_string(),
+        });
+    }
+
+    if let Some(e) = SYSTEM_ERROR.get(&hex_code).cloned() {
+        found_errors.push(FoundError {
+            domain: "System Program".to_string(),
+            message: e.to_string(),
+        });
+    }
+
+    // Maps cached from on-chain IDLs are newer than the built-in ones, so they replace the
+    // built-in map of the same program.
+    let cached = cached_error_maps();
+    found_errors.retain(|e| !cached.iter().any(|map| map.domain == e.domain));
+    for map in cached {
+        if let Some(e) = map.errors.get(&hex_code) {
+            found_errors.push(FoundError {
+                domain: map.domain.clone(),
+                message: e.clone(),
+            });
+        }
+    }
+
     found_errors
 }
 
@@ -279,13 +469,42 @@ pub fn get_largest_token_account_owner(client: &RpcClient, mint: Pubkey) -> Resu
     let token_account = Pubkey::from_str(&token_accounts[0].address).unwrap();
 
     let account = client
-        .get_account_with_commitment(&token_account, CommitmentConfig::confirmed())
-        .unwrap()
+        .get_account_with_commitment(&token_account, CommitmentConfig::confirmed())?
         .value
-        .unwrap();
-    let account_data = Account::unpack(&account.data).unwrap();
+        .ok_or_else(|| anyhow!("Token account {token_account} not found"))?;
+
+    // Token-2022 accounts can be longer than the base layout, so unpack with extensions.
+    // This also handles plain SPL Token accounts.
+    let account_data = StateWithExtensions::<Account>::unpack(&account.data)?;
+
+    Ok(account_data.base.owner)
+}
 
-    Ok(account_data.owner)
+/// The token program that owns a mint, either SPL Token or Token-2022.
+pub fn get_token_program(client: &RpcClient, mint: &Pubkey) -> Result<Pubkey> {
+    let owner = client.get_account(mint)?.owner;
+
+    if owner == spl_token::ID || owner == spl_token_2022::ID {
+        Ok(owner)
+    } else {
+        Err(anyhow!(
+            "Mint {mint} is owned by {owner}, which is not a token program"
+        ))
+    }
+}
+
+/// Derive the associated token account for a mint under whichever token program owns it.
+pub fn get_associated_token_address_for_mint(
+    client: &RpcClient,
+    owner: &Pubkey,
+    mint: &Pubkey,
+) -> Result<Pubkey> {
+    let token_program = get_token_program(client, mint)?;
+    Ok(get_associated_token_address_with_program_id(
+        owner,
+        mint,
+        &token_program,
+    ))
 }
 
 #[derive(Debug, Deserialize)]
@@ -321,7 +540,7 @@ pub enum TokenExtrasInstruction {
     /// - Token exists and Token is not ATA: Instruction succeeds.
     /// - Token does not exist and Token is ATA: Instruction creates the ATA account and succeeds.
     /// - Token does not exist and Token is not ATA: Instruction fails as we cannot create a
-    ///    non-ATA account without it being a signer.
+    ///   non-ATA account without it being a signer.
     ///
     /// Note that additional checks are made to ensure that the token account provided
     /// matches the mint account and owner account provided.
@@ -0,0 +1,80 @@
+//! Run directories from the global `--workspace` option.
+//!
+//! Each command run under a workspace gets its own directory named by the start time and
+//! command, e.g. `drop/2024-05-01-12-00-00-update-uri-all/`, and the reports, caches,
+//! changelogs, snapshots and receipts it writes go there instead of the working directory.
+//! Every run is also appended to `runs.jsonl` at the workspace root with the full command
+//! line, so a campaign of several steps can be audited afterwards.
+
+use std::{
+    fs::{self, OpenOptions},
+    io::Write,
+    path::{Path, PathBuf},
+    sync::RwLock,
+};
+
+use anyhow::{anyhow, Result};
+use once_cell::sync::Lazy;
+use serde::Serialize;
+use structopt::clap::ArgMatches;
+
+static RUN_DIR: Lazy<RwLock<Option<PathBuf>>> = Lazy::new(|| RwLock::new(None));
+
+#[derive(Debug, Serialize)]
+struct RunRecord<'a> {
+    command: &'a str,
+    started_at: String,
+    dir: &'a Path,
+    args: Vec<String>,
+}
+
+/// The command name of a run, with its subcommands, e.g. `update-uri-all`.
+pub fn command_name(matches: &ArgMatches) -> String {
+    let mut names = vec![];
+    let mut matches = matches;
+    while let (name, Some(sub)) = matches.subcommand() {
+        names.push(name.to_string());
+        matches = sub;
+    }
+    names.join("-")
+}
+
+/// Create the directory for this run under the workspace and send outputs there.
+pub fn open_workspace<P: AsRef<Path>>(root: P, command: &str) -> Result<PathBuf> {
+    let root = root.as_ref();
+    let now = chrono::Local::now();
+    let dir = root.join(format!("{}-{command}", now.format("%Y-%m-%d-%H-%M-%S")));
+    fs::create_dir_all(&dir)
+        .map_err(|e| anyhow!("Failed to create run directory {}: {e}", dir.display()))?;
+
+    let record = RunRecord {
+        command,
+        started_at: now.to_rfc3339(),
+        dir: &dir,
+        args: std::env::args().collect(),
+    };
+    serde_json::to_writer_pretty(fs::File::create(dir.join("run.json"))?, &record)?;
+    let mut runs = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(root.join("runs.jsonl"))?;
+    writeln!(runs, "{}", serde_json::to_string(&record)?)?;
+
+    *RUN_DIR.write().unwrap() = Some(dir.clone());
+    Ok(dir)
+}
+
+/// Where to write an output file or directory. Relative paths go in the run directory when
+/// there is a workspace, and are left as they are otherwise.
+pub fn output_path<P: AsRef<Path>>(path: P) -> PathBuf {
+    let path = path.as_ref();
+    match RUN_DIR.read().unwrap().as_ref() {
+        Some(dir) if path.is_relative() => dir.join(path),
+        _ => path.to_path_buf(),
+    }
+}
+
+/// The directory of this run, if there is a workspace.
+pub fn run_dir() -> Option<PathBuf> {
+    RUN_DIR.read().unwrap().clone()
+}
@@ -439,3 +439,180 @@ pub static METADATA_ERROR: phf::Map<&'static str, &'static str> = phf_map! {
     "B7" => "InvalidUpdateArgs: Authority cannot apply all update args",
     "B8" => "InsufficientTokenBalance: Token account does not have enough tokens",
 };
+
+pub static BUBBLEGUM_ERROR: phf::Map<&'static str, &'static str> = phf_map! {
+    "1770" => "AssetOwnerMismatch: Asset Owner Does not match",
+    "1771" => "PublicKeyMismatch: PublicKeyMismatch",
+    "1772" => "HashingMismatch: Hashing Mismatch Within Leaf Schema",
+    "1773" => "UnsupportedSchemaVersion: Unsupported Schema Version",
+    "1774" => "CreatorShareTotalMustBe100: Creator shares must sum to 100",
+    "1775" => "DuplicateCreatorAddress: No duplicate creator addresses in metadata",
+    "1776" => "CreatorDidNotVerify: Creator did not verify the metadata",
+    "1777" => "CreatorNotFound: Creator not found in creator Vec",
+    "1778" => "NoCreatorsPresent: No creators in creator Vec",
+    "1779" => "CreatorHashMismatch: User-provided creator Vec must result in same user-provided creator hash",
+    "177A" => "DataHashMismatch: User-provided metadata must result in same user-provided data hash",
+    "177B" => "CreatorsTooLong: Creators list too long",
+    "177C" => "MetadataNameTooLong: Name in metadata is too long",
+    "177D" => "MetadataSymbolTooLong: Symbol in metadata is too long",
+    "177E" => "MetadataUriTooLong: Uri in metadata is too long",
+    "177F" => "MetadataBasisPointsTooHigh: Basis points in metadata cannot exceed 10000",
+    "1780" => "TreeAuthorityIncorrect: Tree creator or tree delegate must sign.",
+    "1781" => "InsufficientMintCapacity: Not enough unapproved mints left",
+    "1782" => "NumericalOverflowError: NumericalOverflowError",
+    "1783" => "IncorrectOwner: Incorrect account owner",
+    "1784" => "CollectionCannotBeVerifiedInThisInstruction: Cannot Verify Collection in this Instruction",
+    "1785" => "CollectionNotFound: Collection Not Found on Metadata",
+    "1786" => "AlreadyVerified: Collection item is already verified.",
+    "1787" => "AlreadyUnverified: Collection item is already unverified.",
+    "1788" => "UpdateAuthorityIncorrect: Incorrect leaf metadata update authority.",
+    "1789" => "LeafAuthorityMustSign: This transaction must be signed by either the leaf owner or leaf delegate",
+    "178A" => "CollectionMustBeSized: Collection Not Compatable with Compression, Must be Sized",
+    "178B" => "MetadataMintMismatch: Metadata mint does not match collection mint",
+    "178C" => "InvalidCollectionAuthority: Invalid collection authority",
+    "178D" => "InvalidDelegateRecord: Invalid delegate record pda derivation",
+    "178E" => "CollectionMasterEditionAccountInvalid: Edition account doesnt match collection",
+    "178F" => "CollectionMustBeAUniqueMasterEdition: Collection Must Be a Unique Master Edition v2",
+    "1790" => "UnknownExternalError: Could not convert external error to BubblegumError",
+    "1791" => "DecompressionDisabled: Decompression is disabled for this tree.",
+    "1792" => "MissingCollectionMintAccount: Missing collection mint account",
+    "1793" => "MissingCollectionMetadataAccount: Missing collection metadata account",
+    "1794" => "CollectionMismatch: Collection mismatch",
+    "1795" => "MetadataImmutable: Metadata not mutable",
+    "1796" => "PrimarySaleCanOnlyBeFlippedToTrue: Can only update primary sale to true",
+    "1797" => "CreatorDidNotUnverify: Creator did not unverify the metadata",
+    "1798" => "InvalidTokenStandard: Only NonFungible standard is supported",
+    "1799" => "InvalidCanopySize: Canopy size should be set bigger for this tree",
+};
+
+pub static TOKEN_AUTH_RULES_ERROR: phf::Map<&'static str, &'static str> = phf_map! {
+    "0" => "NumericalOverflow: Numerical Overflow",
+    "1" => "DataTypeMismatch: Data type mismatch",
+    "2" => "DataSliceUnexpectedIndexError: Data slice unexpected index error",
+    "3" => "IncorrectOwner: Incorrect account owner",
+    "4" => "PayloadVecIndexError: Could not index into PayloadVec",
+    "5" => "DerivedKeyInvalid: Derived key invalid",
+    "6" => "PayerIsNotSigner: Payer is not a signer",
+    "7" => "NotImplemented: Not implemented",
+    "8" => "BorshSerializationError: Borsh serialization error",
+    "9" => "BorshDeserializationError: Borsh deserialization error",
+    "A" => "ValueOccupied: Value in Payload or RuleSet is occupied",
+    "B" => "DataIsEmpty: Account data is empty",
+    "C" => "MessagePackSerializationError: MessagePack serialization error",
+    "D" => "MessagePackDeserializationError: MessagePack deserialization error",
+    "E" => "MissingAccount: Missing account",
+    "F" => "MissingPayloadValue: Missing Payload value",
+    "10" => "RuleSetOwnerMismatch: RuleSet owner must be payer",
+    "11" => "NameTooLong: Name too long",
+    "12" => "OperationNotFound: The operation retrieved is not in the selected RuleSet",
+    "13" => "RuleAuthorityIsNotSigner: Rule authority is not signer",
+    "14" => "UnsupportedRuleSetRevMapVersion: Unsupported RuleSet revision map version",
+    "15" => "UnsupportedRuleSetVersion: Unsupported RuleSet version",
+    "16" => "UnexpectedRuleSetFailure: Unexpected RuleSet failure",
+    "17" => "RuleSetRevisionNotAvailable: RuleSet revision not available",
+    "18" => "AdditionalSignerCheckFailed: Additional Signer check failed",
+    "19" => "PubkeyMatchCheckFailed: Pubkey Match check failed",
+    "1A" => "PubkeyListMatchCheckFailed: Pubkey List Match check failed",
+    "1B" => "PubkeyTreeMatchCheckFailed: Pubkey Tree Match check failed",
+    "1C" => "PDAMatchCheckFailed: PDA Match check failed",
+    "1D" => "ProgramOwnedCheckFailed: Program Owned check failed",
+    "1E" => "ProgramOwnedListCheckFailed: Program Owned List check failed",
+    "1F" => "ProgramOwnedTreeCheckFailed: Program Owned Tree check failed",
+    "20" => "AmountCheckFailed: Amount checked failed",
+    "21" => "FrequencyCheckFailed: Frequency check failed",
+    "22" => "IsWalletCheckFailed: IsWallet check failed",
+    "23" => "ProgramOwnedSetCheckFailed: Program Owned Set check failed",
+    "24" => "InvalidCompareOp: Invalid compare operator",
+    "25" => "InvalidConstraintType: Invalid constraint type value",
+    "26" => "RuleSetReadFailed: Failed to read the rule set",
+    "27" => "DuplicatedOperationName: Duplicated operation name",
+    "28" => "AlignmentError: Could not determine alignemnt",
+};
+
+pub static TOKEN_ERROR: phf::Map<&'static str, &'static str> = phf_map! {
+    "0" => "NotRentExempt: Lamport balance below rent-exempt threshold",
+    "1" => "InsufficientFunds: Insufficient funds",
+    "2" => "InvalidMint: Invalid Mint",
+    "3" => "MintMismatch: Account not associated with this Mint",
+    "4" => "OwnerMismatch: Owner does not match",
+    "5" => "FixedSupply: Fixed supply",
+    "6" => "AlreadyInUse: Already in use",
+    "7" => "InvalidNumberOfProvidedSigners: Invalid number of provided signers",
+    "8" => "InvalidNumberOfRequiredSigners: Invalid number of required signers",
+    "9" => "UninitializedState: State is uninitialized",
+    "A" => "NativeNotSupported: Instruction does not support native tokens",
+    "B" => "NonNativeHasBalance: Non-native account can only be closed if its balance is zero",
+    "C" => "InvalidInstruction: Invalid instruction",
+    "D" => "InvalidState: State is invalid for requested operation",
+    "E" => "Overflow: Operation overflowed",
+    "F" => "AuthorityTypeNotSupported: Account does not support specified authority type",
+    "10" => "MintCannotFreeze: This token mint cannot freeze accounts",
+    "11" => "AccountFrozen: Account is frozen",
+    "12" => "MintDecimalsMismatch: The provided decimals value different from the Mint decimals",
+    "13" => "NonNativeNotSupported: Instruction does not support non-native tokens",
+    "14" => "ExtensionTypeMismatch: Extension type does not match already existing extensions",
+    "15" => "ExtensionBaseMismatch: Extension does not match the base type provided",
+    "16" => "ExtensionAlreadyInitialized: Extension already initialized on this account",
+    "17" => "ConfidentialTransferAccountHasBalance: An account can only be closed if its confidential balance is zero",
+    "18" => "ConfidentialTransferAccountNotApproved: Account not approved for confidential transfers",
+    "19" => "ConfidentialTransferDepositsAndTransfersDisabled: Account not accepting deposits or transfers",
+    "1A" => "ConfidentialTransferElGamalPubkeyMismatch: ElGamal public key mismatch",
+    "1B" => "ConfidentialTransferBalanceMismatch: Balance mismatch",
+    "1C" => "MintHasSupply: Mint has non-zero supply. Burn all tokens before closing the mint",
+    "1D" => "NoAuthorityExists: No authority exists to perform the desired operation",
+    "1E" => "TransferFeeExceedsMaximum: Transfer fee exceeds maximum of 10,000 basis points",
+    "1F" => "MintRequiredForTransfer: Mint required for this account to transfer tokens, use `transfer_checked` or `transfer_checked_with_fee`",
+    "20" => "FeeMismatch: Calculated fee does not match expected fee",
+    "21" => "FeeParametersMismatch: Fee parameters associated with zero-knowledge proofs do not match fee parameters in mint",
+    "22" => "ImmutableOwner: The owner authority cannot be changed",
+    "23" => "AccountHasWithheldTransferFees: An account can only be closed if its withheld fee balance is zero, harvest fees to the mint and try again",
+    "24" => "NoMemo: No memo in previous instruction; required for recipient to receive a transfer",
+    "25" => "NonTransferable: Transfer is disabled for this mint",
+    "26" => "NonTransferableNeedsImmutableOwnership: Non-transferable tokens can't be minted to an account without immutable ownership",
+    "27" => "MaximumPendingBalanceCreditCounterExceeded: The total number of `Deposit` and `Transfer` instructions to an account cannot exceed the associated `maximum_pending_balance_credit_counter`",
+    "28" => "MaximumDepositAmountExceeded: Deposit amount exceeds maximum limit",
+    "29" => "CpiGuardSettingsLocked: CPI Guard cannot be enabled or disabled in CPI",
+    "2A" => "CpiGuardTransferBlocked: CPI Guard is enabled, and a program attempted to transfer user funds via CPI without using a delegate",
+    "2B" => "CpiGuardBurnBlocked: CPI Guard is enabled, and a program attempted to burn user funds via CPI without using a delegate",
+    "2C" => "CpiGuardCloseAccountBlocked: CPI Guard is enabled, and a program attempted to close an account via CPI without returning lamports to owner",
+    "2D" => "CpiGuardApproveBlocked: CPI Guard is enabled, and a program attempted to approve a delegate via CPI",
+    "2E" => "CpiGuardSetAuthorityBlocked: CPI Guard is enabled, and a program attempted to add or replace an authority via CPI",
+    "2F" => "CpiGuardOwnerChangeBlocked: Account ownership cannot be changed while CPI Guard is enabled",
+    "30" => "ExtensionNotFound: Extension not found in account data",
+    "31" => "NonConfidentialTransfersDisabled: Non-confidential transfers disabled",
+    "32" => "ConfidentialTransferFeeAccountHasWithheldFee: An account can only be closed if the confidential withheld fee is zero",
+    "33" => "InvalidExtensionCombination: A mint or an account is initialized to an invalid combination of extensions",
+    "34" => "InvalidLengthForAlloc: Extension allocation with overwrite must use the same length",
+    "35" => "AccountDecryption: Failed to decrypt a confidential transfer account",
+    "36" => "ProofGeneration: Failed to generate proof",
+    "37" => "InvalidProofInstructionOffset: An invalid proof instruction offset was provided",
+    "38" => "HarvestToMintDisabled: Harvest of withheld tokens to mint is disabled",
+    "39" => "SplitProofContextStateAccountsNotSupported: Split proof context state accounts not supported for instruction",
+    "3A" => "NotEnoughProofContextStateAccounts: Not enough proof context state accounts provided",
+    "3B" => "MalformedCiphertext: Ciphertext is malformed",
+    "3C" => "CiphertextArithmeticFailed: Ciphertext arithmetic failed",
+};
+
+pub static ACCOUNT_COMPRESSION_ERROR: phf::Map<&'static str, &'static str> = phf_map! {
+    "1770" => "IncorrectLeafLength: Incorrect leaf length. Expected vec of 32 bytes",
+    "1771" => "ConcurrentMerkleTreeError: Concurrent merkle tree error",
+    "1772" => "ZeroCopyError: Issue zero copying concurrent merkle tree data",
+    "1773" => "ConcurrentMerkleTreeConstantsError: An unsupported max depth or max buffer size constant was provided",
+    "1774" => "CanopyLengthMismatch: Expected a different byte length for the merkle tree canopy",
+    "1775" => "IncorrectAuthority: Provided authority does not match expected tree authority",
+    "1776" => "IncorrectAccountOwner: Account is owned by a different program, expected it to be owned by this program",
+    "1777" => "IncorrectAccountType: Account provided has incorrect account type",
+    "1778" => "LeafIndexOutOfBounds: Leaf index of concurrent merkle tree is out of bounds",
+};
+
+pub static SYSTEM_ERROR: phf::Map<&'static str, &'static str> = phf_map! {
+    "0" => "AccountAlreadyInUse: an account with the same address already exists",
+    "1" => "ResultWithNegativeLamports: account does not have enough SOL to perform the operation",
+    "2" => "InvalidProgramId: cannot assign account to this program id",
+    "3" => "InvalidAccountDataLength: cannot allocate account data of this length",
+    "4" => "MaxSeedLengthExceeded: length of requested seed is too long",
+    "5" => "AddressWithSeedMismatch: provided address does not match addressed derived from seed",
+    "6" => "NonceNoRecentBlockhashes: advancing stored nonce requires a populated RecentBlockhashes sysvar",
+    "7" => "NonceBlockhashNotExpired: stored nonce is still in recent_blockhashes",
+    "8" => "NonceUnexpectedBlockhashValue: specified nonce does not match stored nonce",
+};
@@ -0,0 +1,57 @@
+use std::io::{self, Write};
+use std::process::Command;
+
+use regex::Regex;
+use serde_json::Value;
+use solana_sdk::signature::Signature;
+use std::str::FromStr;
+
+#[test]
+#[ignore]
+fn json_output_records_update_signature() {
+    // Arrange
+
+    // Mint an NFT to update
+    let output = Command::new("metaboss")
+        .args(["mint", "one", "-d", "tests/files/new_nft.json"])
+        .output()
+        .expect("failed to execute process");
+
+    io::stdout().write_all(&output.stdout).unwrap();
+    assert!(output.status.success());
+
+    let output_string = String::from_utf8(output.stdout).unwrap();
+    let re = Regex::new(r"Mint account: (\S+)").unwrap();
+    let cap = re.captures(&output_string).unwrap();
+    let mint = &cap[1];
+
+    // Act
+    let output = Command::new("metaboss")
+        .args([
+            "--output-format",
+            "json",
+            "update",
+            "uri",
+            "-a",
+            mint,
+            "-u",
+            "https://arweave.net/updated.json",
+        ])
+        .output()
+        .expect("failed to execute process");
+
+    io::stderr().write_all(&output.stderr).unwrap();
+    assert!(output.status.success());
+
+    // Assert
+    let document: Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(document["success"], true);
+
+    let transactions = document["transactions"].as_array().unwrap();
+    assert_eq!(transactions.len(), 1);
+    assert!(Signature::from_str(transactions[0]["signature"].as_str().unwrap()).is_ok());
+    assert!(transactions[0]["operation"]
+        .as_str()
+        .unwrap()
+        .contains("TokenMetadata.Update"));
+}